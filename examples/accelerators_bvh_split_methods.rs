@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use pbrt::accelerators::bvh::BVHAccel;
+use pbrt::core::geometry::{Point3f, Ray, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::rng::Rng;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::shapes::disk::Disk;
+use pbrt::shapes::sphere::Sphere;
+
+fn translate(x: Float, y: Float, z: Float) -> Transform {
+    Transform::translate(&Vector3f { x, y, z })
+}
+
+fn primitive(shape: Shape) -> Arc<Primitive> {
+    Arc::new(Primitive::Geometric(Box::new(GeometricPrimitive::new(
+        Arc::new(shape),
+        None,
+        None,
+        None,
+    ))))
+}
+
+fn sphere(object_to_world: Transform, radius: Float) -> Arc<Primitive> {
+    primitive(Shape::Sphr(Sphere::new(
+        object_to_world,
+        Transform::inverse(&object_to_world),
+        false,
+        radius,
+        -radius,
+        radius,
+        360.0,
+    )))
+}
+
+fn disk(object_to_world: Transform, radius: Float) -> Arc<Primitive> {
+    primitive(Shape::Dsk(Disk::new(
+        object_to_world,
+        Transform::inverse(&object_to_world),
+        false,
+        0.0,
+        radius,
+        0.0,
+        360.0,
+    )))
+}
+
+fn scene_primitives(rng: &mut Rng) -> Vec<Arc<Primitive>> {
+    let mut prims: Vec<Arc<Primitive>> = Vec::new();
+    // randomly placed small spheres
+    for _ in 0..200 {
+        let t: Transform = translate(
+            20.0 * rng.uniform_float() - 10.0,
+            20.0 * rng.uniform_float() - 10.0,
+            20.0 * rng.uniform_float() - 10.0,
+        );
+        prims.push(sphere(t, 0.2 + 0.5 * rng.uniform_float()));
+    }
+    // flat disks with zero thickness bounds
+    for i in 0..20 {
+        prims.push(disk(translate(0.0, 0.0, i as Float - 10.0), 3.0));
+    }
+    // concentric spheres, all centroids coincide
+    for i in 0..10 {
+        prims.push(sphere(translate(5.0, -5.0, 5.0), 0.1 + 0.2 * i as Float));
+    }
+    // degenerate disks on a line, their bounds have zero area
+    for i in 0..10 {
+        prims.push(disk(translate(i as Float, 12.0, 0.0), 0.0));
+    }
+    prims
+}
+
+/// Closest hit by testing every primitive.
+fn brute_force(prims: &[Arc<Primitive>], ray: &Ray) -> Option<Float> {
+    let mut ray: Ray = ray.clone();
+    let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+    let mut hit: bool = false;
+    for prim in prims {
+        if prim.intersect(&mut ray, &mut isect) {
+            hit = true;
+        }
+    }
+    if hit {
+        Some(ray.t_max)
+    } else {
+        None
+    }
+}
+
+fn random_ray(rng: &mut Rng, axis_aligned: bool) -> Ray {
+    let o: Point3f = Point3f {
+        x: 24.0 * rng.uniform_float() - 12.0,
+        y: 24.0 * rng.uniform_float() - 12.0,
+        z: 24.0 * rng.uniform_float() - 12.0,
+    };
+    let d: Vector3f = if axis_aligned {
+        Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: if rng.uniform_float() < 0.5 { 1.0 } else { -1.0 },
+        }
+    } else {
+        Vector3f {
+            x: rng.uniform_float() - 0.5,
+            y: rng.uniform_float() - 0.5,
+            z: rng.uniform_float() - 0.5,
+        }
+        .normalize()
+    };
+    Ray {
+        o,
+        d,
+        t_max: std::f32::INFINITY,
+        time: 0.0,
+        differential: None,
+        medium: None,
+    }
+}
+
+fn main() {
+    let mut rng: Rng = Rng::new();
+    let prims: Vec<Arc<Primitive>> = scene_primitives(&mut rng);
+    for split_method in &["sah", "middle", "equal", "hlbvh"] {
+        let mut params: ParamSet = ParamSet::default();
+        params.add_string(String::from("splitmethod"), String::from(*split_method));
+        params.add_int(String::from("maxnodeprims"), 2);
+        let bvh: Primitive = BVHAccel::create(prims.clone(), &params);
+        if let Primitive::BVH(ref accel) = bvh {
+            // every primitive (degenerate or not) ends up in a leaf
+            assert_eq!(accel.primitives.len(), prims.len(), "{}", split_method);
+            assert!(accel.nodes.len() < 2 * prims.len(), "{}", split_method);
+        } else {
+            panic!("BVHAccel::create() didn't create a BVH");
+        }
+        let mut rng: Rng = Rng::new();
+        rng.set_sequence(7);
+        let mut n_hits: usize = 0;
+        for i in 0..2000 {
+            let ray: Ray = random_ray(&mut rng, i % 4 == 0);
+            let expected: Option<Float> = brute_force(&prims, &ray);
+            let mut bvh_ray: Ray = ray.clone();
+            let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+            let hit: bool = bvh.intersect(&mut bvh_ray, &mut isect);
+            assert_eq!(hit, expected.is_some(), "{}: ray {}", split_method, i);
+            assert_eq!(bvh.intersect_p(&ray), hit, "{}: ray {}", split_method, i);
+            if let Some(t) = expected {
+                assert_eq!(bvh_ray.t_max, t, "{}: ray {}", split_method, i);
+                n_hits += 1;
+            }
+        }
+        assert!(n_hits > 100, "{}: only {} hits", split_method, n_hits);
+        println!("{}: {} of 2000 rays hit", split_method, n_hits);
+    }
+}
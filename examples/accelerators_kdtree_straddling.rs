@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use pbrt::accelerators::kdtreeaccel::KdTreeAccel;
+use pbrt::core::geometry::{Point3f, Ray, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::rng::Rng;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::shapes::sphere::Sphere;
+
+fn sphere(x: Float, y: Float, z: Float, radius: Float) -> Arc<Primitive> {
+    let object_to_world: Transform = Transform::translate(&Vector3f { x, y, z });
+    let shape: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        object_to_world,
+        Transform::inverse(&object_to_world),
+        false,
+        radius,
+        -radius,
+        radius,
+        360.0,
+    )));
+    Arc::new(Primitive::Geometric(Box::new(GeometricPrimitive::new(
+        shape, None, None, None,
+    ))))
+}
+
+/// Closest hit by testing every primitive.
+fn brute_force(prims: &[Arc<Primitive>], ray: &Ray) -> Option<Float> {
+    let mut ray: Ray = ray.clone();
+    let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+    let mut hit: bool = false;
+    for prim in prims {
+        if prim.intersect(&mut ray, &mut isect) {
+            hit = true;
+        }
+    }
+    if hit {
+        Some(ray.t_max)
+    } else {
+        None
+    }
+}
+
+fn main() {
+    let mut rng: Rng = Rng::new();
+    // small spheres on a grid plus large ones, which straddle many
+    // split planes
+    let mut prims: Vec<Arc<Primitive>> = Vec::new();
+    for i in 0..8 {
+        for j in 0..8 {
+            prims.push(sphere(i as Float, j as Float, 0.0, 0.3));
+        }
+    }
+    for _ in 0..8 {
+        prims.push(sphere(
+            8.0 * rng.uniform_float(),
+            8.0 * rng.uniform_float(),
+            4.0 * rng.uniform_float() - 2.0,
+            1.0 + 2.0 * rng.uniform_float(),
+        ));
+    }
+    // defaults
+    let defaults: Primitive = KdTreeAccel::create(prims.clone(), &ParamSet::default());
+    if let Primitive::KdTree(ref kd_tree) = defaults {
+        assert_eq!(kd_tree.isect_cost, 80);
+        assert_eq!(kd_tree.traversal_cost, 1);
+        assert_eq!(kd_tree.empty_bonus, 0.5);
+        assert_eq!(kd_tree.max_prims, 1);
+    } else {
+        panic!("KdTreeAccel::create() didn't create a kd-tree");
+    }
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("intersectcost"), 40);
+    params.add_int(String::from("maxprims"), 2);
+    params.add_int(String::from("maxdepth"), 12);
+    let accel: Primitive = KdTreeAccel::create(prims.clone(), &params);
+    if let Primitive::KdTree(ref kd_tree) = accel {
+        assert_eq!(kd_tree.isect_cost, 40);
+        assert_eq!(kd_tree.max_prims, 2);
+        // leaves reference each primitive at most once, straddling
+        // primitives end up in several leaves, but every primitive
+        // is referenced somewhere
+        let mut referenced: HashSet<usize> = HashSet::new();
+        let mut n_leaves: usize = 0;
+        let mut n_references: usize = 0;
+        for node in kd_tree.nodes.iter().take(kd_tree.next_free_node as usize) {
+            if node.is_leaf() {
+                n_leaves += 1;
+                let mut in_leaf: HashSet<usize> = HashSet::new();
+                for i in 0..node.n_primitives() {
+                    let index: usize = kd_tree.leaf_primitive(node, i);
+                    assert!(index < prims.len());
+                    assert!(in_leaf.insert(index), "primitive {} twice in leaf", index);
+                    referenced.insert(index);
+                    n_references += 1;
+                }
+            }
+        }
+        assert_eq!(referenced.len(), prims.len());
+        assert!(n_references > prims.len());
+        println!(
+            "{} leaves with {} references to {} primitives",
+            n_leaves,
+            n_references,
+            prims.len()
+        );
+    } else {
+        unreachable!()
+    }
+    // the kd-tree finds the same closest hits as testing everything
+    let mut n_hits: usize = 0;
+    for i in 0..2000 {
+        let o: Point3f = Point3f {
+            x: 12.0 * rng.uniform_float() - 2.0,
+            y: 12.0 * rng.uniform_float() - 2.0,
+            z: 12.0 * rng.uniform_float() - 6.0,
+        };
+        let d: Vector3f = if i % 4 == 0 {
+            // parallel to the split planes of two axes
+            Vector3f {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            }
+        } else {
+            Vector3f {
+                x: rng.uniform_float() - 0.5,
+                y: rng.uniform_float() - 0.5,
+                z: rng.uniform_float() - 0.5,
+            }
+            .normalize()
+        };
+        let ray: Ray = Ray {
+            o,
+            d,
+            t_max: std::f32::INFINITY,
+            time: 0.0,
+            differential: None,
+            medium: None,
+        };
+        let expected: Option<Float> = brute_force(&prims, &ray);
+        let mut kd_ray: Ray = ray.clone();
+        let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+        let hit: bool = accel.intersect(&mut kd_ray, &mut isect);
+        assert_eq!(hit, expected.is_some(), "ray {}", i);
+        assert_eq!(accel.intersect_p(&ray), hit, "ray {}", i);
+        if let Some(t) = expected {
+            assert_eq!(kd_ray.t_max, t, "ray {}", i);
+            n_hits += 1;
+        }
+    }
+    assert!(n_hits > 100, "only {} hits", n_hits);
+}
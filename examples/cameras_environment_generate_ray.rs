@@ -0,0 +1,125 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use pbrt::cameras::environment::EnvironmentCamera;
+use pbrt::core::camera::{Camera, CameraSample};
+use pbrt::core::film::Film;
+use pbrt::core::filter::Filter;
+use pbrt::core::geometry::{Bounds2f, Point2f, Point2i, Ray, RayDifferential, Vector2f, Vector3f};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::transform::{AnimatedTransform, Transform};
+use pbrt::filters::boxfilter::BoxFilter;
+
+fn camera(params: &ParamSet) -> Arc<Camera> {
+    let filter: Box<Filter> = Box::new(Filter::Bx(BoxFilter {
+        radius: Vector2f { x: 0.5, y: 0.5 },
+        inv_radius: Vector2f { x: 2.0, y: 2.0 },
+    }));
+    let crop: Bounds2f = Bounds2f {
+        p_min: Point2f { x: 0.0, y: 0.0 },
+        p_max: Point2f { x: 1.0, y: 1.0 },
+    };
+    let film: Arc<Film> = Arc::new(Film::new(
+        Point2i { x: 64, y: 32 },
+        crop,
+        filter,
+        35.0,
+        String::from("cameras_environment_generate_ray.exr"),
+        1.0,
+        std::f32::INFINITY,
+    ));
+    let identity: Transform = Transform::default();
+    let cam2world: AnimatedTransform = AnimatedTransform::new(&identity, 0.0, &identity, 1.0);
+    EnvironmentCamera::create(params, cam2world, film, None)
+}
+
+fn ray(camera: &Camera, x: Float, y: Float) -> Ray {
+    let sample: CameraSample = CameraSample {
+        p_film: Point2f { x, y },
+        p_lens: Point2f { x: 0.3, y: 0.6 },
+        time: 0.5,
+    };
+    let mut ray: Ray = Ray::default();
+    assert_eq!(camera.generate_ray_differential(&sample, &mut ray), 1.0);
+    ray
+}
+
+fn is_finite(v: &Vector3f) -> bool {
+    v.x.is_finite() && v.y.is_finite() && v.z.is_finite()
+}
+
+fn assert_close(a: Vector3f, b: Vector3f, eps: Float) {
+    assert!((a - b).length() < eps, "{:?} != {:?}", a, b);
+}
+
+fn main() {
+    let env: Arc<Camera> = camera(&ParamSet::default());
+    // x maps to longitude, y to latitude (starting at the pole)
+    let r: Ray = ray(&env, 16.0, 16.0);
+    assert_close(
+        r.d,
+        Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        1e-5,
+    );
+    // differentials follow the change of direction over one pixel
+    let diff: RayDifferential = r.differential.unwrap();
+    let d_phi: Float = 2.0 * PI / 64.0;
+    let d_theta: Float = PI / 32.0;
+    assert_close(
+        diff.rx_direction - r.d,
+        Vector3f {
+            x: -d_phi,
+            y: 0.0,
+            z: 0.0,
+        },
+        1e-3,
+    );
+    assert_close(
+        diff.ry_direction - r.d,
+        Vector3f {
+            x: 0.0,
+            y: -d_theta,
+            z: 0.0,
+        },
+        1e-3,
+    );
+    // the top and bottom rows look straight up and down, samples
+    // beyond the film (within the filter radius) are clamped
+    let up: Vector3f = Vector3f {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    for &x in &[0.0, 10.5, 63.9] {
+        assert_close(ray(&env, x, 0.0).d, up, 1e-6);
+        assert_close(ray(&env, x, -0.5).d, up, 1e-6);
+        assert_close(ray(&env, x, 32.0).d, -up, 1e-6);
+        assert_close(ray(&env, x, 32.5).d, -up, 1e-6);
+    }
+    // all directions (and differentials) are well defined
+    for j in -2..=66 {
+        for i in -2..=130 {
+            let r: Ray = ray(&env, i as Float * 0.5, j as Float * 0.5);
+            assert!(is_finite(&r.d) && (r.d.length() - 1.0).abs() < 1e-5);
+            let diff: RayDifferential = r.differential.unwrap();
+            assert!(is_finite(&diff.rx_direction) && is_finite(&diff.ry_direction));
+        }
+    }
+    // the differentials at the poles still point away from the pole
+    let diff: RayDifferential = ray(&env, 5.0, 0.0).differential.unwrap();
+    assert!(diff.ry_direction.y < 1.0);
+    // lens parameters are ignored
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("lensradius"), 0.5);
+    params.add_float(String::from("focaldistance"), 2.0);
+    let lens: Arc<Camera> = camera(&params);
+    let a: Ray = ray(&lens, 20.3, 7.1);
+    let b: Ray = ray(&env, 20.3, 7.1);
+    assert_close(a.o - b.o, Vector3f::default(), 1e-6);
+    assert_close(a.d, b.d, 1e-6);
+}
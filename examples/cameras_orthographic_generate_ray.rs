@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use pbrt::cameras::orthographic::OrthographicCamera;
+use pbrt::core::camera::{Camera, CameraSample};
+use pbrt::core::film::Film;
+use pbrt::core::filter::Filter;
+use pbrt::core::geometry::{
+    Bounds2f, Point2f, Point2i, Point3f, Ray, RayDifferential, Vector2f, Vector3f,
+};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::transform::{AnimatedTransform, Transform};
+use pbrt::filters::boxfilter::BoxFilter;
+
+/// Film with 200x100 pixels, the default screen window is [-2, 2]
+/// by [-1, 1], so each pixel covers 0.02 by 0.02 units.
+fn film() -> Arc<Film> {
+    let filter: Box<Filter> = Box::new(Filter::Bx(BoxFilter {
+        radius: Vector2f { x: 0.5, y: 0.5 },
+        inv_radius: Vector2f { x: 2.0, y: 2.0 },
+    }));
+    let crop: Bounds2f = Bounds2f {
+        p_min: Point2f { x: 0.0, y: 0.0 },
+        p_max: Point2f { x: 1.0, y: 1.0 },
+    };
+    Arc::new(Film::new(
+        Point2i { x: 200, y: 100 },
+        crop,
+        filter,
+        35.0,
+        String::from("cameras_orthographic_generate_ray.exr"),
+        1.0,
+        std::f32::INFINITY,
+    ))
+}
+
+fn camera(params: &ParamSet, cam2world: &Transform) -> Arc<Camera> {
+    let animated: AnimatedTransform = AnimatedTransform::new(cam2world, 0.0, cam2world, 1.0);
+    OrthographicCamera::create(params, animated, film(), None)
+}
+
+fn sample(x: Float, y: Float, u: Float, v: Float) -> CameraSample {
+    CameraSample {
+        p_film: Point2f { x, y },
+        p_lens: Point2f { x: u, y: v },
+        time: 0.5,
+    }
+}
+
+fn assert_close(a: Vector3f, b: Vector3f) {
+    assert!((a - b).length() < 1e-4, "{:?} != {:?}", a, b);
+}
+
+fn main() {
+    let identity: Transform = Transform::default();
+    let z: Vector3f = Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    };
+    let dx: Vector3f = Vector3f {
+        x: 0.02,
+        y: 0.0,
+        z: 0.0,
+    };
+    let dy: Vector3f = Vector3f {
+        x: 0.0,
+        y: -0.02,
+        z: 0.0,
+    };
+    // parallel rays, starting on the film plane
+    let pinhole: Arc<Camera> = camera(&ParamSet::default(), &identity);
+    let mut ray: Ray = Ray::default();
+    pinhole.generate_ray_differential(&sample(50.0, 25.0, 0.3, 0.8), &mut ray);
+    let p_camera: Point3f = Point3f {
+        x: -1.0,
+        y: 0.5,
+        z: 0.0,
+    };
+    assert_close(ray.o - p_camera, Vector3f::default());
+    assert_close(ray.d, z);
+    // constant offsets for the neighbouring pixels
+    let diff: RayDifferential = ray.differential.unwrap();
+    assert_close(diff.rx_origin - ray.o, dx);
+    assert_close(diff.ry_origin - ray.o, dy);
+    assert_close(diff.rx_direction, z);
+    assert_close(diff.ry_direction, z);
+    // a thin lens moves the origin, but rays meet again on the plane
+    // of focus
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("lensradius"), 0.5);
+    params.add_float(String::from("focaldistance"), 3.0);
+    let lens: Arc<Camera> = camera(&params, &identity);
+    for &(u, v) in &[(0.1, 0.7), (0.9, 0.2), (0.5, 0.5)] {
+        let mut ray: Ray = Ray::default();
+        lens.generate_ray_differential(&sample(50.0, 25.0, u, v), &mut ray);
+        let offset: Vector3f = ray.o - p_camera;
+        assert!(offset.length() <= 0.5 + 1e-5 && offset.z.abs() < 1e-6);
+        let t: Float = (3.0 - ray.o.z) / ray.d.z;
+        let p_focus: Point3f = ray.position(t);
+        assert_close(
+            p_focus - p_camera,
+            Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: 3.0,
+            },
+        );
+        // the differentials are still populated, shifted by a pixel
+        let diff: RayDifferential = ray.differential.unwrap();
+        assert_close(diff.rx_origin - ray.o, dx);
+        assert_close(diff.ry_origin - ray.o, dy);
+        assert_close(diff.rx_direction, ray.d);
+        assert_close(diff.ry_direction, ray.d);
+    }
+    // the offsets are transformed to world space
+    let rotated: Arc<Camera> = camera(&ParamSet::default(), &Transform::rotate_z(90.0));
+    let mut ray: Ray = Ray::default();
+    rotated.generate_ray_differential(&sample(50.0, 25.0, 0.5, 0.5), &mut ray);
+    let diff: RayDifferential = ray.differential.unwrap();
+    assert_close(
+        diff.rx_origin - ray.o,
+        Vector3f {
+            x: 0.0,
+            y: 0.02,
+            z: 0.0,
+        },
+    );
+    // an explicit screen window replaces the default one
+    let mut params: ParamSet = ParamSet::default();
+    params.add_floats(String::from("screenwindow"), vec![0.0, 1.0, 0.0, 1.0]);
+    let window: Arc<Camera> = camera(&params, &identity);
+    let mut ray: Ray = Ray::default();
+    window.generate_ray_differential(&sample(0.0, 0.0, 0.5, 0.5), &mut ray);
+    assert_close(
+        ray.o - Point3f::default(),
+        Vector3f {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+    );
+    let diff: RayDifferential = ray.differential.unwrap();
+    assert_close(
+        diff.ry_origin - ray.o,
+        Vector3f {
+            x: 0.0,
+            y: -0.01,
+            z: 0.0,
+        },
+    );
+}
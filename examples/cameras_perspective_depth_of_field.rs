@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use pbrt::cameras::perspective::PerspectiveCamera;
+use pbrt::core::camera::{Camera, CameraSample};
+use pbrt::core::film::Film;
+use pbrt::core::filter::Filter;
+use pbrt::core::geometry::{
+    Bounds2f, Point2f, Point2i, Point3f, Ray, RayDifferential, Vector2f, Vector3f,
+};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::transform::{AnimatedTransform, Transform};
+use pbrt::filters::boxfilter::BoxFilter;
+
+fn film() -> Arc<Film> {
+    let filter: Box<Filter> = Box::new(Filter::Bx(BoxFilter {
+        radius: Vector2f { x: 0.5, y: 0.5 },
+        inv_radius: Vector2f { x: 2.0, y: 2.0 },
+    }));
+    let crop: Bounds2f = Bounds2f {
+        p_min: Point2f { x: 0.0, y: 0.0 },
+        p_max: Point2f { x: 1.0, y: 1.0 },
+    };
+    Arc::new(Film::new(
+        Point2i { x: 100, y: 100 },
+        crop,
+        filter,
+        35.0,
+        String::from("cameras_perspective_depth_of_field.exr"),
+        1.0,
+        std::f32::INFINITY,
+    ))
+}
+
+/// Calls *f* with the perspective camera created from *params*,
+/// looking down the z axis.
+fn with_camera<F: FnOnce(&PerspectiveCamera)>(params: &ParamSet, f: F) {
+    let identity: Transform = Transform::default();
+    let cam2world: AnimatedTransform = AnimatedTransform::new(&identity, 0.0, &identity, 1.0);
+    let camera: Arc<Camera> = PerspectiveCamera::create(params, cam2world, film(), None);
+    if let Camera::Perspective(ref perspective) = *camera {
+        f(perspective);
+    } else {
+        panic!("PerspectiveCamera::create() didn't create a perspective camera");
+    }
+}
+
+fn sample(x: Float, y: Float, u: Float, v: Float) -> CameraSample {
+    CameraSample {
+        p_film: Point2f { x, y },
+        p_lens: Point2f { x: u, y: v },
+        time: 0.5,
+    }
+}
+
+/// Point where the ray (*o*, *d*) crosses the plane at depth *z*.
+fn at_depth(o: &Point3f, d: &Vector3f, z: Float) -> Point3f {
+    let t: Float = (z - o.z) / d.z;
+    Point3f {
+        x: o.x + d.x * t,
+        y: o.y + d.y * t,
+        z,
+    }
+}
+
+fn assert_close(a: &Point3f, b: &Point3f) {
+    let eps: Float = 1e-4;
+    assert!(
+        (a.x - b.x).abs() < eps && (a.y - b.y).abs() < eps && (a.z - b.z).abs() < eps,
+        "{:?} != {:?}",
+        a,
+        b
+    );
+}
+
+fn main() {
+    // without a lens the camera is a pinhole, the lens sample is
+    // ignored
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("fov"), 60.0);
+    let mut pinhole_rays: Vec<Ray> = Vec::new();
+    with_camera(&params, |camera| {
+        for &(u, v) in &[(0.1, 0.7), (0.9, 0.2)] {
+            let mut ray: Ray = Ray::default();
+            let mut ray_diff: Ray = Ray::default();
+            assert_eq!(
+                camera.generate_ray(&sample(30.5, 60.5, u, v), &mut ray),
+                1.0
+            );
+            camera.generate_ray_differential(&sample(30.5, 60.5, u, v), &mut ray_diff);
+            assert_close(&ray.o, &Point3f::default());
+            assert_close(&ray_diff.o, &Point3f::default());
+            assert!((ray.d - ray_diff.d).length() < 1e-6);
+            assert!(ray.differential.is_none());
+            let diff: RayDifferential = ray_diff.differential.unwrap();
+            assert_close(&diff.rx_origin, &Point3f::default());
+            assert_close(&diff.ry_origin, &Point3f::default());
+            pinhole_rays.push(ray_diff);
+        }
+        assert!((pinhole_rays[0].d - pinhole_rays[1].d).length() < 1e-6);
+    });
+    // with a thin lens, rays start on the lens but meet the pinhole
+    // rays on the plane of focus, and so do their offset rays
+    let focal_distance: Float = 5.0;
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("fov"), 60.0);
+    params.add_float(String::from("lensradius"), 0.1);
+    params.add_float(String::from("focaldistance"), focal_distance);
+    let pinhole: &Ray = &pinhole_rays[0];
+    let pinhole_diff: RayDifferential = pinhole.differential.unwrap();
+    let focus: Point3f = at_depth(&pinhole.o, &pinhole.d, focal_distance);
+    let focus_dx: Point3f = at_depth(
+        &pinhole_diff.rx_origin,
+        &pinhole_diff.rx_direction,
+        focal_distance,
+    );
+    let focus_dy: Point3f = at_depth(
+        &pinhole_diff.ry_origin,
+        &pinhole_diff.ry_direction,
+        focal_distance,
+    );
+    with_camera(&params, |camera| {
+        let mut origins: Vec<Point3f> = Vec::new();
+        for &(u, v) in &[(0.1, 0.7), (0.9, 0.2), (0.5, 0.5)] {
+            let mut ray: Ray = Ray::default();
+            camera.generate_ray(&sample(30.5, 60.5, u, v), &mut ray);
+            let r2: Float = ray.o.x * ray.o.x + ray.o.y * ray.o.y;
+            assert!(ray.o.z.abs() < 1e-6 && r2 <= 0.01 + 1e-6);
+            assert_close(&at_depth(&ray.o, &ray.d, focal_distance), &focus);
+            let mut ray_diff: Ray = Ray::default();
+            camera.generate_ray_differential(&sample(30.5, 60.5, u, v), &mut ray_diff);
+            assert_close(&ray_diff.o, &ray.o);
+            assert!((ray_diff.d - ray.d).length() < 1e-6);
+            // offset rays share the lens point of the main ray
+            let diff: RayDifferential = ray_diff.differential.unwrap();
+            assert_close(&diff.rx_origin, &ray.o);
+            assert_close(&diff.ry_origin, &ray.o);
+            assert_close(
+                &at_depth(&diff.rx_origin, &diff.rx_direction, focal_distance),
+                &focus_dx,
+            );
+            assert_close(
+                &at_depth(&diff.ry_origin, &diff.ry_direction, focal_distance),
+                &focus_dy,
+            );
+            origins.push(ray.o);
+        }
+        // different lens samples give different origins (the center
+        // of the lens maps to the center of the disk)
+        assert!((origins[0] - origins[1]).length() > 0.01);
+        assert_close(&origins[2], &Point3f::default());
+    });
+    // the screen window selects part of the image plane, and
+    // "halffov" is half of the field of view
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("halffov"), 30.0);
+    params.add_floats(String::from("screenwindow"), vec![-0.5, 0.5, -0.5, 0.5]);
+    with_camera(&params, |camera| {
+        let tan_half_fov: Float = (30.0 as Float).to_radians().tan();
+        let mut ray: Ray = Ray::default();
+        camera.generate_ray(&sample(0.0, 0.0, 0.5, 0.5), &mut ray);
+        assert!((ray.d.x / ray.d.z + 0.5 * tan_half_fov).abs() < 1e-4);
+        assert!((ray.d.y / ray.d.z - 0.5 * tan_half_fov).abs() < 1e-4);
+        camera.generate_ray(&sample(50.0, 50.0, 0.5, 0.5), &mut ray);
+        assert!(ray.d.x.abs() < 1e-5 && ray.d.y.abs() < 1e-5 && ray.d.z > 0.0);
+    });
+}
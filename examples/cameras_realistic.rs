@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use pbrt::cameras::realistic::{LensElementInterface, RealisticCamera};
+use pbrt::core::api::{make_film, make_filter};
+use pbrt::core::camera::{Camera, CameraSample};
+use pbrt::core::film::Film;
+use pbrt::core::geometry::{Bounds2f, Point2f, Point3f, Ray, Vector3f};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::transform::{AnimatedTransform, Transform};
+
+const RESOLUTION: i32 = 8;
+
+/// Film with a diagonal of *diagonal* millimeters.
+fn film(diagonal: Float) -> Arc<Film> {
+    let filter = make_filter("box", &ParamSet::default()).unwrap();
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), RESOLUTION);
+    film_params.add_int(String::from("yresolution"), RESOLUTION);
+    film_params.add_float(String::from("diagonal"), diagonal);
+    make_film("image", &film_params, filter).unwrap()
+}
+
+fn identity() -> AnimatedTransform {
+    AnimatedTransform::new(&Transform::default(), 0.0, &Transform::default(), 1.0)
+}
+
+/// Film immersed in glass, behind a single (almost flat) interface
+/// to the air one centimeter away, with the given exit *pupil*.
+fn immersion(simple_weighting: bool, pupil: Bounds2f) -> RealisticCamera {
+    RealisticCamera {
+        camera_to_world: identity(),
+        shutter_open: 0.0,
+        shutter_close: 0.5,
+        film: film(5.0),
+        medium: None,
+        simple_weighting,
+        element_interfaces: vec![LensElementInterface {
+            curvature_radius: 1.0,
+            thickness: 0.01,
+            eta: 1.5,
+            aperture_radius: 0.5,
+        }],
+        exit_pupil_bounds: vec![pupil; 64],
+    }
+}
+
+fn bounds(x0: Float, y0: Float, x1: Float, y1: Float) -> Bounds2f {
+    Bounds2f {
+        p_min: Point2f { x: x0, y: y0 },
+        p_max: Point2f { x: x1, y: y1 },
+    }
+}
+
+/// Ray from the film center in direction *(x, 0, z)*.
+fn from_center(x: Float, z: Float) -> Ray {
+    Ray {
+        o: Point3f::default(),
+        d: Vector3f { x, y: 0.0, z },
+        t_max: std::f32::INFINITY,
+        time: 0.0,
+        medium: None,
+        differential: None,
+    }
+}
+
+fn sample(x: Float, y: Float, u: Float, v: Float) -> CameraSample {
+    CameraSample {
+        p_film: Point2f { x, y },
+        p_lens: Point2f { x: u, y: v },
+        time: 0.0,
+    }
+}
+
+fn main() {
+    // rays leaving the glass at steep angles are totally internally
+    // reflected and rejected ...
+    let pupil: Bounds2f = bounds(-0.001, -0.001, 0.001, 0.001);
+    let camera: RealisticCamera = immersion(false, pupil);
+    let mut out: Ray = Ray::default();
+    assert!(camera.trace_lenses_from_film(&from_center(0.1, 1.0), Some(&mut out)));
+    assert!(out.d.x > 0.1, "rays are bent away from the normal");
+    assert!(!camera.trace_lenses_from_film(&from_center(0.9, 0.1), None));
+    let center: Float = RESOLUTION as Float / 2.0;
+    let steep: RealisticCamera = immersion(false, bounds(0.04, -0.001, 0.05, 0.001));
+    let mut ray: Ray = Ray::default();
+    assert_eq!(
+        steep.generate_ray(&sample(center, center, 0.5, 0.5), &mut ray),
+        0.0
+    );
+    // ... others are weighted radiometrically, with cos^4(theta) and
+    // the exit pupil area (seen from the film) ...
+    let wt: Float = camera.generate_ray(&sample(center, center, 0.75, 0.5), &mut ray);
+    let d: Vector3f = Vector3f {
+        x: 0.0005,
+        y: 0.0,
+        z: 0.01,
+    };
+    let cos_theta: Float = d.z / d.length();
+    let cos_4_theta: Float = cos_theta.powi(4);
+    let expected: Float = 0.5 * cos_4_theta * pupil.area() / (0.01 * 0.01);
+    assert!(
+        (wt - expected).abs() < 1e-4 * expected,
+        "{} != {}",
+        wt,
+        expected
+    );
+    assert!((ray.d.length() - 1.0).abs() < 1e-5 && ray.d.z > 0.0);
+    // ... or relative to the pupil at the center of the film
+    let simple: RealisticCamera = immersion(true, pupil);
+    let wt: Float = simple.generate_ray(&sample(center, center, 0.75, 0.5), &mut ray);
+    assert!((wt - cos_4_theta).abs() < 1e-4, "{} != {}", wt, cos_4_theta);
+    let wt_corner: Float = simple.generate_ray(&sample(0.0, 0.0, 0.5, 0.5), &mut ray);
+    assert!(wt_corner > 0.0 && wt_corner < wt, "{} {}", wt_corner, wt);
+    // create() needs a lens file ...
+    let mut params: ParamSet = ParamSet::default();
+    assert!(RealisticCamera::create(&params, identity(), film(35.0), None, None).is_none());
+    let lens_file = std::env::temp_dir().join("pbrt_cameras_realistic.dat");
+    std::fs::write(
+        &lens_file,
+        "# radius thickness eta aperture\n50 5 1.5 20\n-50 2\n",
+    )
+    .unwrap();
+    params.add_string(
+        String::from("lensfile"),
+        String::from(lens_file.to_str().unwrap()),
+    );
+    assert!(RealisticCamera::create(&params, identity(), film(35.0), None, None).is_none());
+    // ... and reads the aperture, focus distance and weighting: a
+    // biconvex lens (f = 50mm) in front of the stop
+    std::fs::write(
+        &lens_file,
+        "# radius thickness eta aperture\n50 5 1.5 20\n-50 2 1 20\n0 45 0 10\n",
+    )
+    .unwrap();
+    params.add_float(String::from("aperturediameter"), 100.0);
+    params.add_float(String::from("focusdistance"), 5.0);
+    params.add_bool(String::from("simpleweighting"), false);
+    let created: Arc<Camera> =
+        RealisticCamera::create(&params, identity(), film(35.0), None, None).unwrap();
+    std::fs::remove_file(&lens_file).unwrap();
+    let camera: &RealisticCamera = match *created {
+        Camera::Realistic(ref camera) => camera,
+        _ => panic!("expected a realistic camera"),
+    };
+    assert!(!camera.simple_weighting);
+    assert_eq!(camera.element_interfaces.len(), 3);
+    // the aperture gets clamped to the stop (in meters)
+    assert!((camera.element_interfaces[2].aperture_radius - 0.005).abs() < 1e-6);
+    // the film is moved to focus at five meters, about f away from
+    // the lens
+    let film_distance: Float = camera.lens_rear_z();
+    assert!(
+        film_distance > 0.04 && film_distance < 0.06,
+        "{}",
+        film_distance
+    );
+    let focus: Float = camera.focus_distance(film_distance);
+    assert!((focus - 5.0).abs() < 0.1, "{}", focus);
+    let wt: Float = camera.generate_ray(&sample(center, center, 0.5, 0.5), &mut ray);
+    assert!(wt > 0.0);
+    assert!(ray.d.z > 0.99, "{:?}", ray.d);
+}
@@ -0,0 +1,44 @@
+// fixtures shared by the examples (not every example uses all of
+// them); the scene and camera fixtures live in scene.rs
+#![allow(dead_code)]
+
+pub mod scene;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::{ParamSet, TextureParams};
+use pbrt::core::pbrt::Float;
+
+pub fn vector(x: Float, y: Float, z: Float) -> Vector3f {
+    Vector3f { x, y, z }.normalize()
+}
+
+/// Material parameters without any named textures.
+pub fn texture_params(params: ParamSet) -> TextureParams {
+    TextureParams::new(
+        ParamSet::default(),
+        params,
+        Arc::new(HashMap::new()),
+        Arc::new(HashMap::new()),
+    )
+}
+
+/// Interaction at *uv* on the *z = 0* plane, where local and world
+/// space coincide.
+pub fn plane<'a>(uv: Point2f) -> SurfaceInteraction<'a> {
+    SurfaceInteraction::new(
+        &Point3f::default(),
+        &Vector3f::default(),
+        uv,
+        &vector(0.0, 0.0, 1.0),
+        &vector(1.0, 0.0, 0.0),
+        &vector(0.0, 1.0, 0.0),
+        &Normal3f::default(),
+        &Normal3f::default(),
+        0.0,
+        None,
+    )
+}
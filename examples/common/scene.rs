@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use pbrt::core::api::{make_camera, make_film, make_filter, make_integrator};
+use pbrt::core::camera::Camera;
+use pbrt::core::film::Film;
+use pbrt::core::geometry::Vector3f;
+use pbrt::core::integrator::{Integrator, RenderStats};
+use pbrt::core::light::Light;
+use pbrt::core::material::Material;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::{AnimatedTransform, Transform};
+use pbrt::lights::point::PointLight;
+use pbrt::materials::matte::MatteMaterial;
+use pbrt::samplers::random::RandomSampler;
+use pbrt::shapes::sphere::Sphere;
+use pbrt::textures::constant::ConstantTexture;
+
+/// Square image film of *resolution* pixels with a box filter, plus
+/// the additional *film_params*.
+pub fn film(resolution: i32, mut film_params: ParamSet) -> Arc<Film> {
+    let filter = make_filter("box", &ParamSet::default()).unwrap();
+    film_params.add_int(String::from("xresolution"), resolution);
+    film_params.add_int(String::from("yresolution"), resolution);
+    make_film("image", &film_params, filter).unwrap()
+}
+
+/// Perspective camera at the origin, looking along +z.
+pub fn camera(resolution: i32) -> Arc<Camera> {
+    camera_with_film(film(resolution, ParamSet::default()))
+}
+
+pub fn camera_with_film(film: Arc<Film>) -> Arc<Camera> {
+    make_camera(
+        "perspective",
+        &ParamSet::default(),
+        AnimatedTransform::new(&Transform::default(), 0.0, &Transform::default(), 1.0),
+        film,
+    )
+    .unwrap()
+}
+
+pub fn matte(kd: Float) -> Arc<Material> {
+    Arc::new(Material::Matte(Box::new(MatteMaterial::new(
+        Arc::new(ConstantTexture::new(Spectrum::new(kd))),
+        Arc::new(ConstantTexture::new(0.0 as Float)),
+        None,
+    ))))
+}
+
+/// Sphere of *radius* centered on the camera axis at *z*.
+pub fn sphere_shape(z: Float, radius: Float) -> Arc<Shape> {
+    let object_to_world: Transform = Transform::translate(&Vector3f { x: 0.0, y: 0.0, z });
+    Arc::new(Shape::Sphr(Sphere::new(
+        object_to_world,
+        Transform::inverse(&object_to_world),
+        false,
+        radius,
+        -radius,
+        radius,
+        360.0,
+    )))
+}
+
+pub fn sphere(z: Float, radius: Float, material: Option<Arc<Material>>) -> Arc<Primitive> {
+    Arc::new(Primitive::Geometric(Box::new(GeometricPrimitive::new(
+        sphere_shape(z, radius),
+        material,
+        None,
+        None,
+    ))))
+}
+
+pub fn point_light(p: Vector3f, intensity: Float) -> Arc<Light> {
+    Arc::new(Light::Point(Box::new(PointLight::new(
+        &Transform::translate(&p),
+        &MediumInterface::default(),
+        &Spectrum::new(intensity),
+    ))))
+}
+
+/// A matte sphere in front of the camera, lit by a point light at the
+/// camera position.
+pub fn lit_sphere() -> Scene {
+    Scene::new(
+        sphere(3.0, 1.5, Some(matte(0.8))),
+        vec![point_light(Vector3f::default(), 10.0)],
+    )
+}
+
+/// Renders *scene* with the integrator *name* (created from
+/// *params*), using a random sampler with *pixel_samples* samples per
+/// pixel. Returns the statistics and the RGB values of the film.
+pub fn render(
+    name: &str,
+    params: &ParamSet,
+    scene: &Scene,
+    camera: Arc<Camera>,
+    pixel_samples: i32,
+    threads: u8,
+) -> (RenderStats, Vec<Float>) {
+    let film: Arc<Film> = camera.get_film();
+    let mut sampler_params: ParamSet = ParamSet::default();
+    sampler_params.add_int(String::from("pixelsamples"), pixel_samples);
+    let mut integrator: Box<Integrator> =
+        make_integrator(name, params, camera, RandomSampler::create(&sampler_params)).unwrap();
+    let stats: RenderStats = integrator.render(scene, threads);
+    std::fs::remove_file("pbrt.png").unwrap();
+    (stats, film.get_rgb(1.0))
+}
@@ -0,0 +1,57 @@
+use pbrt::core::bssrdf::{pdf_sr, probe_axis, sample_sr};
+use pbrt::core::geometry::XYZEnum;
+use pbrt::core::pbrt::Float;
+use pbrt::core::rng::Rng;
+
+fn main() {
+    let sigma_t: Float = 2.0;
+    let n_samples: usize = 200_000;
+    let n_bins: usize = 30;
+    let bin_width: Float = 0.1;
+    let mut rng: Rng = Rng::default();
+    rng.set_sequence(0_u64);
+    // histogram sampled radii
+    let mut histogram: Vec<usize> = vec![0; n_bins];
+    for _i in 0..n_samples {
+        let r: Float = sample_sr(sigma_t, rng.uniform_float());
+        assert!(r >= 0.0);
+        let bin: usize = (r / bin_width) as usize;
+        if bin < n_bins {
+            histogram[bin] += 1;
+        }
+    }
+    // compare against the pdf integrated over each bin
+    for (bin, count) in histogram.iter().enumerate() {
+        let n_steps: usize = 100;
+        let mut expected: Float = 0.0;
+        for step in 0..n_steps {
+            let r: Float = (bin as Float + (step as Float + 0.5) / n_steps as Float) * bin_width;
+            expected += pdf_sr(sigma_t, r) * bin_width / n_steps as Float;
+        }
+        let observed: Float = *count as Float / n_samples as Float;
+        println!(
+            "bin {:2}: observed {:.5} expected {:.5}",
+            bin, observed, expected
+        );
+        assert!(
+            (observed - expected).abs() < 0.005,
+            "bin {}: observed {} expected {}",
+            bin,
+            observed,
+            expected
+        );
+    }
+    // probe axis probabilities: 1/2 for the normal, 1/4 for each tangent
+    let mut axis_counts: [usize; 3] = [0; 3];
+    let n: usize = 1000;
+    for i in 0..n {
+        let (axis, u, pdf) = probe_axis((i as Float + 0.5) / n as Float);
+        assert!((0.0..1.0).contains(&u));
+        match axis {
+            XYZEnum::Z => assert_eq!(pdf, 0.5),
+            _ => assert_eq!(pdf, 0.25),
+        }
+        axis_counts[axis as usize] += 1;
+    }
+    assert_eq!(axis_counts, [250, 250, 500]);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::scene::{camera, matte, sphere};
+use pbrt::core::builder::{RenderJob, SceneBuilder};
+use pbrt::core::integrator::RenderStats;
+use pbrt::core::light::Light;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::transform::Transform;
+use pbrt::lights::point::PointLight;
+use pbrt::samplers::stratified::StratifiedSampler;
+
+const RESOLUTION: i32 = 16;
+
+fn main() {
+    // a camera is required
+    assert!(SceneBuilder::new()
+        .add_primitive(sphere(3.0, 1.0, Some(matte(0.8))))
+        .build()
+        .is_none());
+    // one point light (at the camera position) and one sphere
+    let light: PointLight = PointLight::new(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::new(10.0),
+    );
+    let mut sampler_params: ParamSet = ParamSet::default();
+    sampler_params.add_int(String::from("xsamples"), 2);
+    sampler_params.add_int(String::from("ysamples"), 2);
+    let mut job: RenderJob = SceneBuilder::new()
+        .camera(camera(RESOLUTION))
+        .add_light(Light::Point(Box::new(light)))
+        .add_primitive(sphere(3.0, 1.0, Some(matte(0.8))))
+        .sampler(StratifiedSampler::create(&sampler_params))
+        .integrator("path", ParamSet::default())
+        .threads(2)
+        .build()
+        .unwrap();
+    assert_eq!(job.scene.lights.len(), 1);
+    let stats: RenderStats = job.render();
+    std::fs::remove_file("pbrt.png").unwrap();
+    assert_eq!(stats.total_samples, (4 * RESOLUTION * RESOLUTION) as u64);
+    let rgb: Vec<Float> = job.film.get_rgb(1.0);
+    let sum: Float = rgb.iter().sum();
+    assert!(rgb.iter().all(|v| v.is_finite()));
+    assert!(sum > 0.0, "image is black");
+    // the sphere doesn't cover the corners of the image
+    assert_eq!(rgb[0] + rgb[1] + rgb[2], 0.0);
+    println!(
+        "rendered {} samples, pixel sum {}",
+        stats.total_samples, sum
+    );
+}
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use pbrt::accelerators::bvh::BVHAccel;
+use pbrt::core::geometry::{Point3f, Vector3f};
+use pbrt::core::light::Light;
+use pbrt::core::lightdistrib::{
+    create_light_sample_distribution, LightBVHSampler, LightBounds, LightDistribution,
+};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{radians, Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::sampling::Distribution1D;
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::lights::distant::DistantLight;
+use pbrt::lights::point::PointLight;
+use pbrt::lights::spot::SpotLight;
+use pbrt::shapes::sphere::Sphere;
+
+fn point(x: Float, y: Float, z: Float) -> Point3f {
+    Point3f { x, y, z }
+}
+
+fn vector(x: Float, y: Float, z: Float) -> Vector3f {
+    Vector3f { x, y, z }
+}
+
+fn point_light(p: Point3f) -> Arc<Light> {
+    Arc::new(Light::Point(Box::new(PointLight::new(
+        &Transform::translate(&(p - Point3f::default())),
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+    ))))
+}
+
+/// Spot light at *p* shining along +z turned by *rotation*.
+fn spot_light(p: Point3f, rotation: Transform) -> Arc<Light> {
+    Arc::new(Light::Spot(Box::new(SpotLight::new(
+        &(Transform::translate(&(p - Point3f::default())) * rotation),
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+        20.0,
+        10.0,
+        None,
+    ))))
+}
+
+/// The lights with a big sphere giving the scene its extent.
+fn scene(lights: Vec<Arc<Light>>) -> Scene {
+    let shape: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        Transform::default(),
+        Transform::default(),
+        false,
+        10.0,
+        -10.0,
+        10.0,
+        360.0,
+    )));
+    let primitive: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(shape, None, None, None),
+    )));
+    let bvh: Arc<Primitive> = Arc::new(BVHAccel::create(vec![primitive], &ParamSet::default()));
+    Scene::new(bvh, lights)
+}
+
+fn probabilities(distrib: &Distribution1D) -> Vec<Float> {
+    (0..distrib.count())
+        .map(|i| distrib.discrete_pdf(i))
+        .collect()
+}
+
+fn assert_normalized(probs: &[Float]) {
+    let sum: Float = probs.iter().sum();
+    assert!((sum - 1.0).abs() < 1e-4, "{:?}", probs);
+}
+
+fn main() {
+    // "bvh" selects the light hierarchy
+    let two: Scene = scene(vec![
+        point_light(point(-8.0, 0.0, 0.0)),
+        point_light(point(8.0, 0.0, 0.0)),
+    ]);
+    let distrib = create_light_sample_distribution(String::from("bvh"), &two).unwrap();
+    match distrib.as_ref() {
+        LightDistribution::BVH(_) => {}
+        _ => panic!("expected a light BVH"),
+    }
+    // the closer light is more likely ...
+    let near_left: Vec<Float> = probabilities(&distrib.lookup(&point(-7.0, 0.0, 0.0)));
+    assert_normalized(&near_left);
+    assert!(near_left[0] > 0.9, "{:?}", near_left);
+    let near_right: Vec<Float> = probabilities(&distrib.lookup(&point(7.0, 0.0, 0.0)));
+    assert!(near_right[1] > 0.9, "{:?}", near_right);
+    let middle: Vec<Float> = probabilities(&distrib.lookup(&point(0.0, 5.0, 0.0)));
+    assert!((middle[0] - middle[1]).abs() < 0.1, "{:?}", middle);
+    // ... and distributions are cached per voxel
+    let a = distrib.lookup(&point(0.0, 5.0, 0.0));
+    let b = distrib.lookup(&point(0.0, 5.0, 0.0));
+    assert!(Arc::ptr_eq(&a, &b));
+    // lights facing away from a point get no probability
+    let along_x: Transform = Transform::rotate(90.0, &vector(0.0, 1.0, 0.0));
+    let spots: Scene = scene(vec![
+        spot_light(point(0.0, 0.0, 0.0), along_x),
+        spot_light(point(1.0, 0.0, 0.0), along_x),
+    ]);
+    let sampler: LightBVHSampler = LightBVHSampler::new(&spots, 64);
+    let ahead: Vec<Float> = sampler.light_probabilities(&point(8.0, 0.0, 0.0), 0.0);
+    assert_normalized(&ahead);
+    let behind: Vec<Float> = sampler.light_probabilities(&point(-8.0, 0.0, 0.0), 0.0);
+    assert_eq!(behind, vec![0.0, 0.0]);
+    // a voxel no light reaches falls back to picking any light
+    let fallback: Vec<Float> = probabilities(&sampler.lookup(&point(-9.5, 0.0, 0.0)));
+    assert_eq!(fallback, vec![0.5, 0.5]);
+    // the emission cones are merged up the hierarchy
+    let along_y: Transform = Transform::rotate(-90.0, &vector(1.0, 0.0, 0.0));
+    let bx: LightBounds = LightBounds::new(&spot_light(point(0.0, 0.0, 0.0), along_x)).unwrap();
+    let by: LightBounds = LightBounds::new(&spot_light(point(0.0, 0.0, 0.0), along_y)).unwrap();
+    assert!((bx.w.x - 1.0).abs() < 1e-5 && (by.w.y - 1.0).abs() < 1e-5);
+    assert!((bx.cos_theta_o - radians(10.0).cos()).abs() < 1e-5);
+    let both: LightBounds = bx.union(&by);
+    let diagonal: Float = (0.5 as Float).sqrt();
+    assert!((both.w.x - diagonal).abs() < 1e-4 && (both.w.y - diagonal).abs() < 1e-4);
+    assert!((both.cos_theta_o - radians(55.0).cos()).abs() < 1e-4);
+    assert!((both.phi - bx.phi - by.phi).abs() < 1e-4);
+    // lights at infinity can't be bounded and share the probability
+    // with the hierarchy
+    let distant: Arc<Light> = Arc::new(Light::Distant(Box::new(DistantLight::new(
+        &Transform::default(),
+        &Spectrum::new(1.0),
+        &vector(0.0, 0.0, 1.0),
+        0.0,
+    ))));
+    assert!(LightBounds::new(&distant).is_none());
+    let mixed: Scene = scene(vec![
+        point_light(point(-8.0, 0.0, 0.0)),
+        distant,
+        point_light(point(8.0, 0.0, 0.0)),
+    ]);
+    let sampler: LightBVHSampler = LightBVHSampler::new(&mixed, 64);
+    assert_eq!(sampler.infinite_lights, vec![1]);
+    let probs: Vec<Float> = probabilities(&sampler.lookup(&point(-7.0, 0.0, 0.0)));
+    assert_normalized(&probs);
+    assert!((probs[1] - 0.5).abs() < 1e-5, "{:?}", probs);
+    assert!(probs[0] > probs[2]);
+    // many lights: always normalized and the nearest light is the
+    // most likely one
+    let mut lights: Vec<Arc<Light>> = Vec::new();
+    for i in 0..10 {
+        for j in 0..10 {
+            for k in 0..10 {
+                let p: Point3f = point(
+                    -9.0 + 2.0 * i as Float,
+                    -9.0 + 2.0 * j as Float,
+                    -9.0 + 2.0 * k as Float,
+                );
+                lights.push(point_light(p));
+            }
+        }
+    }
+    let many: Scene = scene(lights);
+    let sampler: LightBVHSampler = LightBVHSampler::new(&many, 64);
+    for &p in &[point(-9.0, -9.0, -9.0), point(3.0, -1.0, 5.0)] {
+        let probs: Vec<Float> = sampler.light_probabilities(&p, 0.0);
+        assert_normalized(&probs);
+        let mut best: usize = 0;
+        for (i, prob) in probs.iter().enumerate() {
+            if *prob > probs[best] {
+                best = i;
+            }
+        }
+        let nearest = many.lights[best].clone();
+        match nearest.as_ref() {
+            Light::Point(light) => {
+                assert!((light.p_light - p).length() < 1e-4);
+            }
+            _ => panic!("expected a point light"),
+        }
+    }
+}
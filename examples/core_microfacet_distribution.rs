@@ -0,0 +1,158 @@
+use pbrt::core::geometry::{vec3_dot_vec3, Point2f, Vector3f};
+use pbrt::core::microfacet::{
+    roughness_to_alpha, BeckmannDistribution, MicrofacetDistribution, TrowbridgeReitzDistribution,
+};
+use pbrt::core::pbrt::Float;
+use std::f32::consts::PI;
+
+const N_THETA: usize = 1000;
+const N_PHI: usize = 256;
+
+/// Midpoint rule over the upper hemisphere (uniform in cos theta and
+/// phi).
+fn integrate<F: Fn(&Vector3f) -> Float>(f: F) -> Float {
+    let d_omega: Float = 2.0 * PI / (N_THETA * N_PHI) as Float;
+    let mut sum: f64 = 0.0;
+    for i in 0..N_THETA {
+        let cos_theta: Float = (i as Float + 0.5) / N_THETA as Float;
+        let sin_theta: Float = (1.0 - cos_theta * cos_theta).sqrt();
+        for j in 0..N_PHI {
+            let phi: Float = 2.0 * PI * (j as Float + 0.5) / N_PHI as Float;
+            let w: Vector3f = Vector3f {
+                x: sin_theta * phi.cos(),
+                y: sin_theta * phi.sin(),
+                z: cos_theta,
+            };
+            sum += (f(&w) * d_omega) as f64;
+        }
+    }
+    sum as Float
+}
+
+/// Mean of *f* over stratified samples of *wh*.
+fn sample_mean<F: Fn(&Vector3f) -> Float>(
+    distrib: &MicrofacetDistribution,
+    wo: &Vector3f,
+    f: F,
+) -> Float {
+    let n: usize = 256;
+    let mut sum: f64 = 0.0;
+    for i in 0..n {
+        for j in 0..n {
+            let u: Point2f = Point2f {
+                x: (i as Float + 0.5) / n as Float,
+                y: (j as Float + 0.5) / n as Float,
+            };
+            sum += f(&distrib.sample_wh(wo, u)) as f64;
+        }
+    }
+    (sum / (n * n) as f64) as Float
+}
+
+fn assert_close(a: Float, b: Float, epsilon: Float) {
+    assert!((a - b).abs() < epsilon, "{} != {}", a, b);
+}
+
+fn main() {
+    // roughness remapping, shared by both distributions
+    assert_close(roughness_to_alpha(1.0), 1.62142, 1e-6);
+    assert_eq!(roughness_to_alpha(0.0), roughness_to_alpha(1e-3));
+    let mut last: Float = roughness_to_alpha(1e-3);
+    for i in 1..=10 {
+        let alpha: Float = roughness_to_alpha(i as Float / 10.0);
+        assert!(alpha > last);
+        assert_eq!(
+            alpha,
+            BeckmannDistribution::roughness_to_alpha(i as Float / 10.0)
+        );
+        assert_eq!(
+            alpha,
+            TrowbridgeReitzDistribution::roughness_to_alpha(i as Float / 10.0)
+        );
+        last = alpha;
+    }
+    let wos: [Vector3f; 2] = [
+        Vector3f {
+            x: 0.5,
+            y: 0.3,
+            z: 0.6,
+        }
+        .normalize(),
+        Vector3f {
+            x: -0.2,
+            y: 0.9,
+            z: 0.2,
+        }
+        .normalize(),
+    ];
+    for &(alpha_x, alpha_y) in &[(0.3, 0.3), (0.2, 0.6)] {
+        for &visible in &[false, true] {
+            let distribs: [MicrofacetDistribution; 2] = [
+                MicrofacetDistribution::Beckmann(BeckmannDistribution::new(
+                    alpha_x, alpha_y, visible,
+                )),
+                MicrofacetDistribution::TrowbridgeReitz(TrowbridgeReitzDistribution::new(
+                    alpha_x, alpha_y, visible,
+                )),
+            ];
+            for distrib in distribs.iter() {
+                assert_eq!(distrib.get_sample_visible_area(), visible);
+                // the projected microfacet area is the macro surface
+                // area ...
+                assert_close(integrate(|wh| distrib.d(wh) * wh.z), 1.0, 1e-3);
+                for wo in wos.iter() {
+                    // ... and, seen from any direction, masking (which
+                    // is derived from d) keeps it that way
+                    let projected: Float =
+                        integrate(|wh| distrib.d(wh) * vec3_dot_vec3(wo, wh).max(0.0));
+                    assert_close(distrib.g1(wo) * projected, wo.z, 5e-3 * wo.z);
+                    let wi: Vector3f = Vector3f {
+                        x: -wo.x,
+                        y: wo.y,
+                        z: wo.z,
+                    };
+                    assert_close(
+                        distrib.g(wo, &wi),
+                        1.0 / (1.0 + distrib.lambda(wo) + distrib.lambda(&wi)),
+                        1e-6,
+                    );
+                    assert!(distrib.g(wo, &wi) <= distrib.g1(wo).min(distrib.g1(&wi)));
+                    // the pdf matches the samples drawn (only facets
+                    // facing wo are sampled if the visible area is)
+                    let facing =
+                        |wh: &Vector3f| -> bool { !visible || vec3_dot_vec3(wo, wh) > 0.0 };
+                    let weight = |wh: &Vector3f| -> Float {
+                        if facing(wh) {
+                            distrib.pdf(wo, wh)
+                        } else {
+                            0.0
+                        }
+                    };
+                    assert_close(integrate(&weight), 1.0, 5e-3);
+                    let expected: Float = integrate(|wh| weight(wh) * wh.z);
+                    let mean: Float = sample_mean(distrib, wo, |wh| {
+                        assert!(wh.z > 0.0);
+                        wh.z
+                    });
+                    assert_close(mean, expected, 5e-3);
+                    // directions below the surface sample mirrored
+                    // normals
+                    let below: Vector3f = -*wo;
+                    assert!(distrib.sample_wh(&below, Point2f { x: 0.3, y: 0.7 }).z < 0.0);
+                }
+                // grazing directions don't divide by zero
+                let grazing: Vector3f = Vector3f {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                };
+                let wh: Vector3f = Vector3f {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 1.0,
+                };
+                assert!(distrib.pdf(&grazing, &wh).is_finite());
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::reflection::{fr_conductor, fr_conductor_wavelength};
+
+/// Wavelength (nm), eta and k of gold (Johnson and Christy).
+const GOLD: [(Float, Float, Float); 4] = [
+    (450.0, 1.38, 1.92),
+    (500.0, 0.97, 1.87),
+    (550.0, 0.43, 2.45),
+    (650.0, 0.17, 3.15),
+];
+/// Wavelength (nm), eta and k of aluminum (Rakic).
+const ALUMINUM: [(Float, Float, Float); 4] = [
+    (400.0, 0.49, 4.86),
+    (500.0, 0.77, 6.08),
+    (600.0, 1.20, 7.26),
+    (800.0, 2.80, 8.45),
+];
+
+#[derive(Debug, Copy, Clone)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+    fn plus(self, o: Complex) -> Complex {
+        Complex::new(self.re + o.re, self.im + o.im)
+    }
+    fn minus(self, o: Complex) -> Complex {
+        Complex::new(self.re - o.re, self.im - o.im)
+    }
+    fn times(self, o: Complex) -> Complex {
+        Complex::new(
+            self.re * o.re - self.im * o.im,
+            self.re * o.im + self.im * o.re,
+        )
+    }
+    fn over(self, o: Complex) -> Complex {
+        let d: f64 = o.re * o.re + o.im * o.im;
+        Complex::new(
+            (self.re * o.re + self.im * o.im) / d,
+            (self.im * o.re - self.re * o.im) / d,
+        )
+    }
+    fn norm(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+    fn sqrt(self) -> Complex {
+        let r: f64 = self.norm().sqrt();
+        let re: f64 = (0.5 * (r + self.re)).sqrt();
+        let im: f64 = (0.5 * (r - self.re)).max(0.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+}
+
+/// Unpolarized Fresnel reflectance from vacuum into a medium with the
+/// complex index of refraction *eta* + i *k*.
+fn analytic_fresnel(cos_theta_i: f64, eta: f64, k: f64) -> f64 {
+    let n_t: Complex = Complex::new(eta, k);
+    let cos_i: Complex = Complex::new(cos_theta_i, 0.0);
+    let sin_i2: Complex = Complex::new(1.0 - cos_theta_i * cos_theta_i, 0.0);
+    // Snell's law with a complex refraction angle
+    let cos_t: Complex = Complex::new(1.0, 0.0)
+        .minus(sin_i2.over(n_t.times(n_t)))
+        .sqrt();
+    let r_s: Complex = cos_i
+        .minus(n_t.times(cos_t))
+        .over(cos_i.plus(n_t.times(cos_t)));
+    let r_p: Complex = n_t
+        .times(cos_i)
+        .minus(cos_t)
+        .over(n_t.times(cos_i).plus(cos_t));
+    0.5 * (r_s.norm() + r_p.norm())
+}
+
+fn main() {
+    for (name, metal) in &[("gold", GOLD), ("aluminum", ALUMINUM)] {
+        for (lambda, eta, k) in metal.iter() {
+            for cos_theta_i in &[1.0 as Float, 0.8, 0.5, 0.2, 0.05] {
+                let r: Float = fr_conductor_wavelength(*cos_theta_i, 1.0, *eta, *k);
+                let expected: f64 = analytic_fresnel(*cos_theta_i as f64, *eta as f64, *k as f64);
+                assert!(
+                    (r as f64 - expected).abs() < 1.0e-5,
+                    "{} at {}nm, cos = {}: {} != {}",
+                    name,
+                    lambda,
+                    cos_theta_i,
+                    r,
+                    expected
+                );
+            }
+        }
+        // each channel of a spectrum is evaluated as its own wavelength
+        let eta: Spectrum = Spectrum::rgb(metal[3].1, metal[2].1, metal[0].1);
+        let k: Spectrum = Spectrum::rgb(metal[3].2, metal[2].2, metal[0].2);
+        let r: Spectrum = fr_conductor(0.5, Spectrum::new(1.0), eta, k);
+        for (c, r_c) in r.c.iter().enumerate() {
+            let expected: Float = fr_conductor_wavelength(0.5, 1.0, eta.c[c], k.c[c]);
+            assert_eq!(*r_c, expected);
+        }
+    }
+    // gold reflects red a lot better than blue
+    let red: Float = fr_conductor_wavelength(1.0, 1.0, GOLD[3].1, GOLD[3].2);
+    let blue: Float = fr_conductor_wavelength(1.0, 1.0, GOLD[0].1, GOLD[0].2);
+    assert!(red > 0.9 && blue < 0.5, "red = {}, blue = {}", red, blue);
+    println!(
+        "gold at normal incidence: {} (650nm), {} (450nm)",
+        red, blue
+    );
+}
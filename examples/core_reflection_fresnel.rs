@@ -0,0 +1,80 @@
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::reflection::{
+    fr_dielectric, Fresnel, FresnelConductor, FresnelDielectric, FresnelNoOp,
+};
+
+fn assert_close(a: Float, b: Float, epsilon: Float) {
+    assert!((a - b).abs() < epsilon, "{} != {}", a, b);
+}
+
+fn main() {
+    let glass: Fresnel = Fresnel::Dielectric(FresnelDielectric::new(1.0, 1.5));
+    // normal incidence: ((eta_t - eta_i) / (eta_t + eta_i))^2, from
+    // both sides
+    assert_close(glass.evaluate(1.0).c[0], 0.04, 1e-6);
+    assert_close(glass.evaluate(-1.0).c[0], 0.04, 1e-6);
+    // leaving the denser medium swaps the indices ...
+    for i in 1..=20 {
+        let cos_theta: Float = i as Float / 20.0;
+        let inside: Float = fr_dielectric(-cos_theta, 1.0, 1.5);
+        assert_eq!(inside, fr_dielectric(cos_theta, 1.5, 1.0));
+        assert!(inside >= 0.0 && inside <= 1.0);
+        let outside: Float = glass.evaluate(cos_theta).c[0];
+        assert!(outside >= 0.0 && outside <= 1.0);
+    }
+    // ... and beyond the critical angle everything is reflected
+    let sin_critical: Float = 1.0 / 1.5;
+    let cos_critical: Float = (1.0 - sin_critical * sin_critical).sqrt();
+    assert_eq!(fr_dielectric(-(cos_critical - 0.01), 1.0, 1.5), 1.0);
+    assert_eq!(fr_dielectric(-0.1, 1.0, 1.5), 1.0);
+    assert!(fr_dielectric(-(cos_critical + 0.01), 1.0, 1.5) < 1.0);
+    // grazing angles reflect everything, matching indices nothing
+    assert_close(glass.evaluate(0.0).c[0], 1.0, 1e-6);
+    assert_eq!(fr_dielectric(0.7, 1.33, 1.33), 0.0);
+    // without absorption the conductor formula is the dielectric one
+    let k0: FresnelConductor =
+        FresnelConductor::new(Spectrum::new(1.0), Spectrum::new(1.5), Spectrum::new(0.0));
+    for i in 1..=20 {
+        let cos_theta: Float = i as Float / 20.0;
+        assert_close(
+            k0.evaluate(cos_theta).c[0],
+            fr_dielectric(cos_theta, 1.0, 1.5),
+            1e-4,
+        );
+    }
+    // normal incidence on a conductor: ((n - 1)^2 + k^2) / ((n + 1)^2
+    // + k^2) per channel (gold-like values)
+    let eta: Spectrum = Spectrum::rgb(0.18, 0.42, 1.37);
+    let k: Spectrum = Spectrum::rgb(3.42, 2.35, 1.77);
+    let gold: Fresnel = Fresnel::Conductor(FresnelConductor::new(Spectrum::new(1.0), eta, k));
+    let r: Spectrum = gold.evaluate(1.0);
+    for c in 0..3 {
+        let (n, k) = (eta.c[c], k.c[c]);
+        let expected: Float = ((n - 1.0) * (n - 1.0) + k * k) / ((n + 1.0) * (n + 1.0) + k * k);
+        assert_close(r.c[c], expected, 1e-5);
+    }
+    // conductors reflect the same from both sides, never more than
+    // everything, and everything at grazing angles
+    for i in 0..=20 {
+        let cos_theta: Float = i as Float / 20.0;
+        let front: Spectrum = gold.evaluate(cos_theta);
+        let back: Spectrum = gold.evaluate(-cos_theta);
+        for c in 0..3 {
+            assert_eq!(front.c[c], back.c[c]);
+            assert!(
+                front.c[c] >= 0.0 && front.c[c] <= 1.0 + 1e-6,
+                "{:?}",
+                front.c
+            );
+        }
+    }
+    assert_close(gold.evaluate(0.0).c[0], 1.0, 1e-5);
+    let matched: FresnelConductor =
+        FresnelConductor::new(Spectrum::new(1.0), Spectrum::new(1.0), Spectrum::new(0.0));
+    assert_eq!(matched.evaluate(0.0).c[0], 0.0);
+    // no-op reflects everything
+    let mirror: Fresnel = Fresnel::NoOp(FresnelNoOp {});
+    for &cos_theta in &[-1.0, 0.0, 0.3, 1.0] {
+        assert!(mirror.evaluate(cos_theta) == Spectrum::new(1.0));
+    }
+}
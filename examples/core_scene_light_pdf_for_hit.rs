@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Point3f, Vector3f};
+use pbrt::core::integrator::compute_light_power_distribution;
+use pbrt::core::interaction::MediumInteraction;
+use pbrt::core::light::Light;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::sampling::{balance_heuristic, cosine_hemisphere_pdf, Distribution1D};
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::lights::diffuse::DiffuseAreaLight;
+use pbrt::lights::point::PointLight;
+use pbrt::shapes::sphere::Sphere;
+
+fn sphere_light(light_to_world: &Transform, l_emit: Float) -> (Arc<Shape>, Arc<Light>) {
+    let shape: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        *light_to_world,
+        Transform::inverse(light_to_world),
+        false,
+        0.5,
+        -0.5,
+        0.5,
+        360.0,
+    )));
+    let light: Arc<Light> = Arc::new(Light::DiffuseArea(Box::new(DiffuseAreaLight::new(
+        light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(l_emit),
+        1,
+        shape.clone(),
+        false,
+    ))));
+    (shape, light)
+}
+
+fn main() {
+    let center: Vector3f = Vector3f {
+        x: 1.0,
+        y: 0.0,
+        z: 3.0,
+    };
+    let (shape, area_light) = sphere_light(&Transform::translate(&center), 4.0);
+    let point_light: Arc<Light> = Arc::new(Light::Point(Box::new(PointLight::new(
+        &Transform::translate(&Vector3f {
+            x: -1.0,
+            y: 0.0,
+            z: 3.0,
+        }),
+        &MediumInterface::default(),
+        &Spectrum::new(10.0),
+    ))));
+    let primitive: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(shape, None, Some(area_light.clone()), None),
+    )));
+    let scene: Scene = Scene::new(primitive, vec![area_light.clone(), point_light.clone()]);
+    let light_distrib: Arc<Distribution1D> = compute_light_power_distribution(&scene).unwrap();
+    // shading point below the area light, cosine-weighted BSDF sampling
+    let mut iref: MediumInteraction = MediumInteraction::default();
+    iref.common.p = Point3f::default();
+    let wi: Vector3f = center.normalize();
+    let bsdf_pdf: Float = cosine_hemisphere_pdf(wi.z);
+    for distrib in &[None, Some(light_distrib.as_ref())] {
+        let light_pdf: Float = scene.light_pdf_for_hit(&area_light, &iref, wi, *distrib);
+        // includes the probability of picking the area light for NEE
+        let selection_pdf: Float = match distrib {
+            Some(d) => d.discrete_pdf(0),
+            None => 0.5,
+        };
+        let expected: Float = area_light.pdf_li(&iref, wi) * selection_pdf;
+        assert!(light_pdf > 0.0);
+        assert!((light_pdf - expected).abs() <= 1.0e-6 * expected);
+        // the strategies' weights add up to one
+        let w_bsdf: Float = balance_heuristic(1, bsdf_pdf, 1, light_pdf);
+        let w_light: Float = balance_heuristic(1, light_pdf, 1, bsdf_pdf);
+        assert!((w_bsdf + w_light - 1.0).abs() < 1.0e-6);
+        println!("BSDF weight {}, light weight {}", w_bsdf, w_light);
+    }
+    // delta lights can't be hit by BSDF samples
+    let to_point: Vector3f = Vector3f {
+        x: -1.0,
+        y: 0.0,
+        z: 3.0,
+    }
+    .normalize();
+    assert_eq!(
+        scene.light_pdf_for_hit(&point_light, &iref, to_point, Some(&light_distrib)),
+        0.0
+    );
+    // lights which aren't part of the scene are never sampled
+    let (_shape, other_light) = sphere_light(&Transform::translate(&center), 4.0);
+    assert_eq!(scene.light_pdf_for_hit(&other_light, &iref, wi, None), 0.0);
+}
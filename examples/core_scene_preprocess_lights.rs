@@ -0,0 +1,126 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use pbrt::accelerators::bvh::BVHAccel;
+use pbrt::core::geometry::{Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::light::Light;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::lights::distant::DistantLight;
+use pbrt::lights::infinite::InfiniteAreaLight;
+use pbrt::shapes::sphere::Sphere;
+
+fn sphere(center: Vector3f, radius: Float) -> Arc<Primitive> {
+    let object_to_world: Transform = Transform::translate(&center);
+    let shape: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        object_to_world,
+        Transform::inverse(&object_to_world),
+        false,
+        radius,
+        -radius,
+        radius,
+        360.0,
+    )));
+    Arc::new(Primitive::Geometric(Box::new(GeometricPrimitive::new(
+        shape, None, None, None,
+    ))))
+}
+
+fn lights() -> (Arc<Light>, Arc<Light>) {
+    let distant: Arc<Light> = Arc::new(Light::Distant(Box::new(DistantLight::new(
+        &Transform::default(),
+        &Spectrum::new(1.0),
+        &Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        0.0,
+    ))));
+    let infinite: Arc<Light> = Arc::new(Light::InfiniteArea(Box::new(InfiniteAreaLight::new(
+        &Transform::default(),
+        &Spectrum::new(1.0),
+        1,
+        String::from(""),
+    ))));
+    (distant, infinite)
+}
+
+/// End point of the shadow ray a distant light samples from *p*.
+fn shadow_ray_end(light: &Light, p: Point3f) -> Point3f {
+    let mut iref: InteractionCommon = InteractionCommon::default();
+    iref.p = p;
+    let mut wi: Vector3f = Vector3f::default();
+    let mut pdf: Float = 0.0;
+    let (_li, vis) = light.sample_li(&iref, Point2f { x: 0.5, y: 0.5 }, &mut wi, &mut pdf);
+    vis.unwrap().p1.p
+}
+
+fn main() {
+    // Scene::new() computes the world bound and its bounding sphere
+    // once ...
+    let p: Point3f = Point3f {
+        x: 2.0,
+        y: -1.0,
+        z: 3.0,
+    };
+    let bvh: Arc<Primitive> = Arc::new(BVHAccel::create(
+        vec![sphere(p - Point3f::default(), 1.0)],
+        &ParamSet::default(),
+    ));
+    let (distant, infinite) = lights();
+    let scene: Scene = Scene::new(bvh, vec![distant.clone(), infinite.clone()]);
+    let (world_center, world_radius) = scene.world_bounding_sphere();
+    assert!((world_center - p).length() < 1e-5);
+    assert!((world_radius - (3.0 as Float).sqrt()).abs() < 1e-5);
+    // only the infinite area light counts as infinite, the distant
+    // light is a delta direction light
+    assert_eq!(scene.infinite_lights.len(), 1);
+    match scene.infinite_lights[0].as_ref() {
+        Light::InfiniteArea(_) => {}
+        _ => panic!("expected the infinite area light"),
+    }
+    // ... and hands it to the lights at infinity
+    for light in &[&distant, &infinite] {
+        match light.as_ref() {
+            Light::Distant(l) => {
+                assert_eq!(
+                    (*l.world_center.read().unwrap() - world_center).length(),
+                    0.0
+                );
+                assert_eq!(*l.world_radius.read().unwrap(), world_radius);
+            }
+            Light::InfiniteArea(l) => {
+                assert_eq!(
+                    (*l.world_center.read().unwrap() - world_center).length(),
+                    0.0
+                );
+                assert_eq!(*l.world_radius.read().unwrap(), world_radius);
+            }
+            _ => panic!("expected a light at infinity"),
+        }
+    }
+    // shadow rays towards the distant light end twice the radius
+    // away, which is outside the scene
+    let end: Point3f = shadow_ray_end(&distant, p);
+    assert!(((end - p).length() - 2.0 * world_radius).abs() < 1e-4);
+    assert!((end - world_center).length() > world_radius);
+    let power: Float = distant.power().c[0];
+    assert!((power - PI * world_radius * world_radius).abs() < 1e-4);
+    // a scene without geometry still gives the lights a usable
+    // (positive) radius
+    let empty: Arc<Primitive> = Arc::new(BVHAccel::create(Vec::new(), &ParamSet::default()));
+    let (distant, infinite) = lights();
+    let scene: Scene = Scene::new(empty, vec![distant.clone(), infinite.clone()]);
+    let (_world_center, world_radius) = scene.world_bounding_sphere();
+    assert_eq!(world_radius, 1.0);
+    let end: Point3f = shadow_ray_end(&distant, Point3f::default());
+    assert!((end.z - 2.0).abs() < 1e-6);
+    assert!(distant.power().c[0] > 0.0);
+    assert!(infinite.power().c[0].is_finite() && infinite.power().c[0] > 0.0);
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use pbrt::accelerators::bvh::BVHAccel;
+use pbrt::core::geometry::{Point3f, Vector3f};
+use pbrt::core::light::Light;
+use pbrt::core::lightdistrib::create_light_sample_distribution;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::Primitive;
+use pbrt::core::scene::Scene;
+use pbrt::core::transform::Transform;
+use pbrt::lights::point::PointLight;
+
+fn point_light(x: Float, intensity: Float) -> Arc<Light> {
+    Arc::new(Light::Point(Box::new(PointLight::new(
+        &Transform::translate(&Vector3f { x, y: 0.0, z: 0.0 }),
+        &MediumInterface::default(),
+        &Spectrum::new(intensity),
+    ))))
+}
+
+fn scene(lights: Vec<Arc<Light>>) -> Scene {
+    let empty: Arc<Primitive> = Arc::new(BVHAccel::create(Vec::new(), &ParamSet::default()));
+    Scene::new(empty, lights)
+}
+
+fn main() {
+    // lights are picked proportional to their power ...
+    let bright: Scene = scene(vec![point_light(-1.0, 1.0), point_light(1.0, 3.0)]);
+    assert!((bright.light_pdf(0) - 0.25).abs() < 1e-6);
+    assert!((bright.light_pdf(1) - 0.75).abs() < 1e-6);
+    assert_eq!(bright.light_pdf(2), 0.0);
+    let mut picked: [usize; 2] = [0; 2];
+    let n: usize = 1000;
+    for i in 0..n {
+        let u: Float = (i as Float + 0.5) / n as Float;
+        let (light_num, pdf) = bright.sample_light(u);
+        assert_eq!(pdf, bright.light_pdf(light_num));
+        picked[light_num] += 1;
+    }
+    assert_eq!(picked, [250, 750]);
+    // ... and the "power" light sample distribution uses the same
+    // probabilities
+    let distrib = create_light_sample_distribution(String::from("power"), &bright).unwrap();
+    let lookup = distrib.lookup(&Point3f::default());
+    for light_num in 0..2 {
+        assert_eq!(lookup.discrete_pdf(light_num), bright.light_pdf(light_num));
+    }
+    // without any emission all lights are equally likely (instead of
+    // dividing by a total power of zero)
+    let dark: Scene = scene(vec![point_light(-1.0, 0.0), point_light(1.0, 0.0)]);
+    for light_num in 0..2 {
+        assert_eq!(dark.light_pdf(light_num), 0.5);
+    }
+    let (light_num, pdf) = dark.sample_light(0.75);
+    assert_eq!((light_num, pdf), (1, 0.5));
+    let distrib = create_light_sample_distribution(String::from("power"), &dark).unwrap();
+    assert_eq!(distrib.lookup(&Point3f::default()).discrete_pdf(0), 0.5);
+    // a scene without lights has nothing to pick
+    let unlit: Scene = scene(Vec::new());
+    assert!(unlit.light_power_distrib.is_none());
+    assert_eq!(unlit.sample_light(0.5).1, 0.0);
+    assert_eq!(unlit.light_pdf(0), 0.0);
+}
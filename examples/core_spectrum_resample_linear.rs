@@ -0,0 +1,71 @@
+use pbrt::core::pbrt::Float;
+use pbrt::core::spectrum::{interpolate_spectrum_samples, resample_bin, resample_linear};
+
+/// Integral of a piecewise linear function (trapezoidal rule).
+fn integrate(lambdas: &[Float], values: &[Float]) -> Float {
+    let mut sum: Float = 0.0;
+    for i in 0..(lambdas.len() - 1) {
+        sum += 0.5 * (values[i] + values[i + 1]) * (lambdas[i + 1] - lambdas[i]);
+    }
+    sum
+}
+
+fn main() {
+    // a flat spectrum with a narrow (2nm wide) emission line at
+    // 553nm, sampled on a non-uniform grid
+    let mut lambdas: Vec<Float> = Vec::new();
+    lambdas.extend((0..22).map(|i| 400.0 + 7.0 * i as Float));
+    lambdas.extend((548..559).map(|l| l as Float));
+    lambdas.extend((0..20).map(|i| 565.0 + 7.0 * i as Float));
+    lambdas.push(700.0);
+    let values: Vec<Float> = lambdas
+        .iter()
+        .map(|l| if *l == 553.0 { 100.0 } else { 1.0 })
+        .collect();
+    assert!(values.iter().any(|v| *v == 100.0));
+    let integral: Float = integrate(&lambdas, &values);
+    // resample to a coarse 10nm grid which misses the spike
+    let target: Vec<Float> = (0..31).map(|i| 400.0 + 10.0 * i as Float).collect();
+    let resampled: Vec<Float> = resample_linear(&lambdas, &values, &target);
+    assert_eq!(resampled.len(), target.len());
+    // area preserving: sum of bin averages times bin widths
+    let mut resampled_integral: Float = 0.0;
+    for (i, v) in resampled.iter().enumerate() {
+        let (start, end) = resample_bin(&target, i);
+        // the outermost bins extend beyond the input range (clamped)
+        let start: Float = start.max(400.0);
+        let end: Float = end.min(700.0);
+        resampled_integral += v * (end - start);
+    }
+    println!(
+        "integral = {}, resampled integral = {}",
+        integral, resampled_integral
+    );
+    assert!((integral - resampled_integral).abs() < 1e-3 * integral);
+    // point sampling would lose the emission line completely
+    let point_sampled: Vec<Float> = target
+        .iter()
+        .map(|l| interpolate_spectrum_samples(&lambdas, &values, lambdas.len() as i32, *l))
+        .collect();
+    assert!(point_sampled.iter().all(|v| *v == 1.0));
+    assert!(resampled[15] > 5.0, "line lost: {}", resampled[15]);
+    // a target grid beyond the input range is clamped to the end values
+    let wide: Vec<Float> = vec![300.0, 350.0, 550.0, 850.0, 900.0];
+    let clamped: Vec<Float> = resample_linear(&lambdas, &values, &wide);
+    assert_eq!(clamped[0], 1.0);
+    assert_eq!(clamped[4], 1.0);
+    // resampling to the input grid itself keeps a linear spectrum
+    let ramp: Vec<Float> = lambdas.iter().map(|l| 0.01 * l).collect();
+    let same: Vec<Float> = resample_linear(&lambdas, &ramp, &lambdas);
+    for i in 1..(lambdas.len() - 1) {
+        // only exact for symmetric bins
+        if (lambdas[i] - lambdas[i - 1] - (lambdas[i + 1] - lambdas[i])).abs() < 1e-3 {
+            assert!(
+                (same[i] - ramp[i]).abs() < 1e-4,
+                "{} != {}",
+                same[i],
+                ramp[i]
+            );
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Point3f, Vector3f};
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::light::{Light, VisibilityTester};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive, TransformedPrimitive};
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::{AnimatedTransform, Transform};
+use pbrt::lights::distant::DistantLight;
+use pbrt::lights::point::PointLight;
+use pbrt::shapes::sphere::Sphere;
+
+fn tester(p0: Point3f, p1: Point3f) -> VisibilityTester {
+    VisibilityTester {
+        p0: InteractionCommon {
+            p: p0,
+            ..Default::default()
+        },
+        p1: InteractionCommon {
+            p: p1,
+            ..Default::default()
+        },
+    }
+}
+
+fn point(x: Float, y: Float, z: Float) -> Point3f {
+    Point3f { x, y, z }
+}
+
+fn point_light(p: Point3f) -> Arc<Light> {
+    Arc::new(Light::Point(Box::new(PointLight::new(
+        &Transform::translate(&(p - Point3f::default())),
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+    ))))
+}
+
+fn main() {
+    // unit sphere at the origin
+    let sphere: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        Transform::default(),
+        Transform::default(),
+        false,
+        1.0,
+        -1.0,
+        1.0,
+        360.0,
+    )));
+    let primitive: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(sphere, None, None, None),
+    )));
+    // two point lights above the sphere and a distant light
+    let lights: Vec<Arc<Light>> = vec![
+        point_light(point(0.0, 0.0, 5.0)),
+        point_light(point(3.0, 0.0, 5.0)),
+        Arc::new(Light::Distant(Box::new(DistantLight::new(
+            &Transform::default(),
+            &Spectrum::new(1.0),
+            &Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            0.0,
+        )))),
+    ];
+    let mut scene: Scene = Scene::new(primitive.clone(), lights.clone());
+    assert!(scene.enable_visibility_cache(16, 0.01));
+    let below: Point3f = point(0.0, 0.0, -5.0);
+    let blocked: VisibilityTester = tester(below, point(0.0, 0.0, 5.0));
+    let free: VisibilityTester = tester(point(3.0, 0.0, -5.0), point(3.0, 0.0, 5.0));
+    // same shading point, but the other light
+    let passing: VisibilityTester = tester(below, point(3.0, 0.0, 5.0));
+    let n_shadow_rays: u64 = scene.get_rays_traced();
+    for _i in 0..4 {
+        assert!(!blocked.unoccluded_from_light(&scene, 0));
+        assert!(free.unoccluded_from_light(&scene, 1));
+        assert!(passing.unoccluded_from_light(&scene, 1));
+    }
+    let cache = scene.visibility_cache.clone().unwrap();
+    // only the first query of each triple traced a shadow ray
+    assert_eq!(cache.get_misses(), 3);
+    assert_eq!(cache.get_hits(), 9);
+    assert_eq!(scene.get_rays_traced() - n_shadow_rays, 3);
+    // lights which aren't at a single point always trace
+    let distant: VisibilityTester = tester(below, point(0.0, 0.0, 5.0));
+    for _i in 0..4 {
+        assert!(!distant.unoccluded_from_light(&scene, 2));
+    }
+    assert_eq!(cache.get_misses() + cache.get_hits(), 12);
+    assert_eq!(scene.get_rays_traced() - n_shadow_rays, 7);
+    // least recently used entries get evicted
+    assert!(scene.enable_visibility_cache(1, 0.01));
+    assert!(!blocked.unoccluded_from_light(&scene, 0));
+    assert!(free.unoccluded_from_light(&scene, 1));
+    assert!(!blocked.unoccluded_from_light(&scene, 0));
+    let cache = scene.visibility_cache.clone().unwrap();
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get_misses(), 3);
+    cache.invalidate();
+    assert!(cache.is_empty());
+    // the capacity bounds all shards together
+    assert!(scene.enable_visibility_cache(100, 0.01));
+    for i in 0..300 {
+        let x: Float = 3.0 + i as Float * 0.1;
+        let free: VisibilityTester = tester(point(x, 0.0, -5.0), point(3.0, 0.0, 5.0));
+        assert!(free.unoccluded_from_light(&scene, 1));
+    }
+    let cache = scene.visibility_cache.clone().unwrap();
+    assert!(cache.len() <= 100 && !cache.is_empty(), "{}", cache.len());
+    assert_eq!(cache.get_misses(), 300);
+    // animated geometry disables the cache
+    let moving: Transform = Transform::translate(&Vector3f {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    });
+    let animated: Arc<Primitive> =
+        Arc::new(Primitive::Transformed(Box::new(TransformedPrimitive::new(
+            primitive,
+            AnimatedTransform::new(&Transform::default(), 0.0, &moving, 1.0),
+        ))));
+    let mut scene: Scene = Scene::new(animated, lights);
+    assert!(!scene.enable_visibility_cache(16, 0.01));
+    assert!(scene.visibility_cache.is_none());
+    println!("visibility cache OK");
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::Point3f;
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::light::VisibilityTester;
+use pbrt::core::material::Material;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::sampler::Sampler;
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::materials::glass::GlassMaterial;
+use pbrt::materials::matte::MatteMaterial;
+use pbrt::samplers::random::RandomSampler;
+use pbrt::shapes::disk::Disk;
+use pbrt::textures::constant::ConstantTexture;
+
+fn plane_scene(material: Arc<Material>) -> Scene {
+    // large disk in the z = 0 plane
+    let disk: Arc<Shape> = Arc::new(Shape::Dsk(Disk::new(
+        Transform::default(),
+        Transform::default(),
+        false,
+        0.0,
+        10.0,
+        0.0,
+        360.0,
+    )));
+    let primitive: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(disk, Some(material), None, None),
+    )));
+    Scene::new(primitive, Vec::new())
+}
+
+fn main() {
+    let tint: Spectrum = Spectrum::rgb(1.0, 0.5, 0.25);
+    // tinted glass without reflection and index matched to air
+    let glass: Arc<Material> = Arc::new(Material::Glass(Box::new(GlassMaterial::new(
+        Arc::new(ConstantTexture::new(Spectrum::new(0.0))),
+        Arc::new(ConstantTexture::new(tint)),
+        Arc::new(ConstantTexture::new(0.0 as Float)),
+        Arc::new(ConstantTexture::new(0.0 as Float)),
+        Arc::new(ConstantTexture::new(1.0 as Float)),
+        None,
+        false,
+    ))));
+    let matte: Arc<Material> = Arc::new(Material::Matte(Box::new(MatteMaterial::new(
+        Arc::new(ConstantTexture::new(Spectrum::new(0.5))),
+        Arc::new(ConstantTexture::new(0.0 as Float)),
+        None,
+    ))));
+    // shading point below the plane, point light above it
+    let vis: VisibilityTester = VisibilityTester {
+        p0: InteractionCommon {
+            p: Point3f {
+                x: 0.25,
+                y: 0.5,
+                z: -5.0,
+            },
+            ..Default::default()
+        },
+        p1: InteractionCommon {
+            p: Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 5.0,
+            },
+            ..Default::default()
+        },
+    };
+    let mut sampler: Sampler = Sampler::Random(RandomSampler::new(1));
+    // the glass casts a colored shadow ...
+    let scene: Scene = plane_scene(glass);
+    assert!(!vis.unoccluded(&scene));
+    assert!(vis.tr(&scene, &mut sampler).is_black());
+    let tr: Spectrum = vis.tr_through_surfaces(&scene, &mut sampler);
+    for i in 0..3 {
+        assert!((tr.c[i] - tint.c[i]).abs() < 1e-4, "{:?} != {:?}", tr, tint);
+    }
+    // ... while an opaque surface still blocks the light
+    let scene: Scene = plane_scene(matte);
+    assert!(vis.tr_through_surfaces(&scene, &mut sampler).is_black());
+    // no surface in between
+    let vis_free: VisibilityTester = VisibilityTester {
+        p0: InteractionCommon {
+            p: Point3f {
+                x: 20.0,
+                y: 0.0,
+                z: -5.0,
+            },
+            ..Default::default()
+        },
+        p1: InteractionCommon {
+            p: Point3f {
+                x: 20.0,
+                y: 0.0,
+                z: 5.0,
+            },
+            ..Default::default()
+        },
+    };
+    let tr: Spectrum = vis_free.tr_through_surfaces(&scene, &mut sampler);
+    assert_eq!(tr.c, [1.0; 3]);
+    println!("colored shadow {:?}", tint.c);
+}
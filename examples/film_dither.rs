@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use pbrt::core::film::Film;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::spectrum::inverse_gamma_convert_float;
+use pbrt::filters::boxfilter::BoxFilter;
+
+const WIDTH: usize = 256;
+const HEIGHT: usize = 16;
+/// The gradient covers the 8-bit values [FIRST_LEVEL, FIRST_LEVEL + LEVELS)
+const FIRST_LEVEL: Float = 100.0;
+const LEVELS: Float = 4.0;
+/// Histogram bins per 8-bit level
+const BINS_PER_LEVEL: usize = 4;
+
+/// A horizontal gray gradient, smooth in (gamma corrected) 8-bit units.
+fn gradient_film(dither: bool) -> Arc<Film> {
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), WIDTH as i32);
+    film_params.add_int(String::from("yresolution"), HEIGHT as i32);
+    film_params.add_bool(String::from("dither"), dither);
+    let film: Arc<Film> = Film::create(&film_params, BoxFilter::create(&ParamSet::default()));
+    let mut img: Vec<Spectrum> = Vec::with_capacity(WIDTH * HEIGHT);
+    for _y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let level: Float = FIRST_LEVEL + LEVELS * (x as Float + 0.5) / WIDTH as Float;
+            img.push(Spectrum::new(inverse_gamma_convert_float(level / 255.0)));
+        }
+    }
+    film.set_image(&img);
+    film
+}
+
+/// Histogram of the column averages (of the red channel), in fractions
+/// of an 8-bit level.
+fn column_histogram(buffer: &[u8]) -> Vec<usize> {
+    let n_bins: usize = BINS_PER_LEVEL * LEVELS as usize;
+    let mut histogram: Vec<usize> = vec![0; n_bins];
+    for x in 0..WIDTH {
+        let sum: usize = (0..HEIGHT)
+            .map(|y| buffer[3 * (y * WIDTH + x)] as usize)
+            .sum();
+        let mean: Float = sum as Float / HEIGHT as Float;
+        let bin: Float = ((mean - FIRST_LEVEL) * BINS_PER_LEVEL as Float).max(0.0);
+        histogram[(bin as usize).min(n_bins - 1)] += 1;
+    }
+    histogram
+}
+
+fn main() {
+    let banded: Arc<Film> = gradient_film(false);
+    let banded_histogram: Vec<usize> = column_histogram(&banded.get_rgb8(&banded.get_rgb(1.0)));
+    // without dithering only whole 8-bit levels show up
+    assert!(banded_histogram.iter().any(|n| *n == 0));
+    let dithered: Arc<Film> = gradient_film(true);
+    let rgb: Vec<Float> = dithered.get_rgb(1.0);
+    let buffer: Vec<u8> = dithered.get_rgb8(&rgb);
+    let histogram: Vec<usize> = column_histogram(&buffer);
+    assert!(
+        histogram.iter().all(|n| *n > 0),
+        "empty bins in {:?}",
+        histogram
+    );
+    // the dither pattern is deterministic per pixel
+    assert_eq!(buffer, dithered.get_rgb8(&rgb));
+    println!(
+        "histogram without dither {:?}, with dither {:?}",
+        banded_histogram, histogram
+    );
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use pbrt::core::film::Film;
+use pbrt::core::geometry::{Bounds2i, Point2f, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::filters::boxfilter::BoxFilter;
+
+/// A small film covered with neutral gray.
+fn gray_film() -> Arc<Film> {
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), 2);
+    film_params.add_int(String::from("yresolution"), 2);
+    let film: Arc<Film> = Film::create(&film_params, BoxFilter::create(&ParamSet::default()));
+    let bounds: Bounds2i = Bounds2i::new(Point2i { x: 0, y: 0 }, Point2i { x: 2, y: 2 });
+    let mut tile = film.get_film_tile(&bounds);
+    for pixel in &bounds {
+        let p_film: Point2f = Point2f {
+            x: pixel.x as Float + 0.5,
+            y: pixel.y as Float + 0.5,
+        };
+        let mut l: Spectrum = Spectrum::new(0.18);
+        tile.add_sample(p_film, &mut l, 1.0);
+    }
+    film.merge_film_tile(&tile);
+    film
+}
+
+fn main() {
+    let film: Arc<Film> = gray_film();
+    let neutral: Vec<Float> = film.get_rgb(1.0);
+    // +1 stop doubles the output values
+    film.set_exposure(1.0);
+    let brighter: Vec<Float> = film.get_rgb(1.0);
+    for (b, n) in brighter.iter().zip(neutral.iter()) {
+        assert!((b - 2.0 * n).abs() < 1.0e-5, "{} != 2 * {}", b, n);
+    }
+    film.set_exposure(0.0);
+    // the reference temperature doesn't change colors
+    film.set_white_balance(6500.0, 0.0);
+    for (w, n) in film.get_rgb(1.0).iter().zip(neutral.iter()) {
+        assert!((w - n).abs() < 1.0e-5, "{} != {}", w, n);
+    }
+    // compensating warm (tungsten) light shifts gray toward blue ...
+    film.set_white_balance(3200.0, 0.0);
+    let warm: Vec<Float> = film.get_rgb(1.0);
+    assert!(warm[2] > warm[1] && warm[1] > warm[0], "{:?}", &warm[0..3]);
+    assert!(warm[2] > neutral[2] && warm[0] < neutral[0]);
+    // ... while cool light gets compensated toward red
+    film.set_white_balance(10000.0, 0.0);
+    let cool: Vec<Float> = film.get_rgb(1.0);
+    assert!(cool[0] > cool[2], "{:?}", &cool[0..3]);
+    // a positive tint removes a green cast
+    film.set_white_balance(6500.0, 0.25);
+    let tinted: Vec<Float> = film.get_rgb(1.0);
+    assert!(tinted[1] < neutral[1]);
+    assert!((tinted[0] - neutral[0]).abs() < 1.0e-5);
+    println!(
+        "gray {:?} with 3200K white balance {:?}",
+        &neutral[0..3],
+        &warm[0..3]
+    );
+}
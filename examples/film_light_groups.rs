@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use pbrt::core::api::{make_camera, make_film, make_filter, make_sampler};
+use pbrt::core::camera::Camera;
+use pbrt::core::film::Film;
+use pbrt::core::geometry::{Bounds2i, Vector3f};
+use pbrt::core::integrator::{Integrator, SamplerIntegrator};
+use pbrt::core::light::Light;
+use pbrt::core::material::Material;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::sampler::Sampler;
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::{AnimatedTransform, Transform};
+use pbrt::integrators::directlighting::{DirectLightingIntegrator, LightStrategy};
+use pbrt::integrators::path::PathIntegrator;
+use pbrt::lights::point::PointLight;
+use pbrt::materials::matte::MatteMaterial;
+use pbrt::shapes::sphere::Sphere;
+use pbrt::textures::constant::ConstantTexture;
+
+const RESOLUTION: usize = 32;
+
+fn point_light(x: Float, i: Spectrum, group: usize) -> Arc<Light> {
+    let mut light: PointLight = PointLight::new(
+        &Transform::translate(&Vector3f { x, y: 0.0, z: 1.0 }),
+        &MediumInterface::default(),
+        &i,
+    );
+    light.group = group;
+    Arc::new(Light::Point(Box::new(light)))
+}
+
+/// A diffuse sphere lit from the left (group 0) and the right (group 1).
+fn make_scene() -> Scene {
+    let object_to_world: Transform = Transform::translate(&Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: 4.0,
+    });
+    let sphere: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        object_to_world,
+        Transform::inverse(&object_to_world),
+        false,
+        1.0,
+        -1.0,
+        1.0,
+        360.0,
+    )));
+    let kd = Arc::new(ConstantTexture::new(Spectrum::new(0.8)));
+    let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
+    let matte: Arc<Material> = Arc::new(Material::Matte(Box::new(MatteMaterial::new(
+        kd, sigma, None,
+    ))));
+    let primitive: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(sphere, Some(matte), None, None),
+    )));
+    let lights: Vec<Arc<Light>> = vec![
+        point_light(-3.0, Spectrum::rgb(20.0, 10.0, 5.0), 0),
+        point_light(3.0, Spectrum::rgb(5.0, 10.0, 20.0), 1),
+    ];
+    Scene::new(primitive, lights)
+}
+
+/// Sum of the luminance-ish values (all channels) of the left and the
+/// right half of an image.
+fn halves(rgb: &[Float]) -> (Float, Float) {
+    let mut left: Float = 0.0;
+    let mut right: Float = 0.0;
+    for (offset, pixel) in rgb.chunks(3).enumerate() {
+        let sum: Float = pixel.iter().sum();
+        if offset % RESOLUTION < RESOLUTION / 2 {
+            left += sum;
+        } else {
+            right += sum;
+        }
+    }
+    (left, right)
+}
+
+/// Renders the scene with the integrator *make_integrator* returns
+/// and checks the light groups of the film.
+fn check_light_groups<F>(name: &str, make_integrator: F)
+where
+    F: Fn(Arc<Camera>, Box<Sampler>, Bounds2i) -> SamplerIntegrator,
+{
+    let scene: Scene = make_scene();
+    let filter = make_filter("box", &ParamSet::default()).unwrap();
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), RESOLUTION as i32);
+    film_params.add_int(String::from("yresolution"), RESOLUTION as i32);
+    film_params.add_int(String::from("lightgroups"), 2);
+    let film: Arc<Film> = make_film("image", &film_params, filter).unwrap();
+    assert_eq!(film.light_groups, 2);
+    let mut sampler_params: ParamSet = ParamSet::default();
+    sampler_params.add_int(String::from("pixelsamples"), 4);
+    let sampler: Box<Sampler> = make_sampler("random", &sampler_params, film.clone()).unwrap();
+    let camera: Arc<Camera> = make_camera(
+        "perspective",
+        &ParamSet::default(),
+        AnimatedTransform::new(&Transform::default(), 0.0, &Transform::default(), 1.0),
+        film.clone(),
+    )
+    .unwrap();
+    let pixel_bounds: Bounds2i = film.get_sample_bounds();
+    let mut integrator: Integrator =
+        Integrator::Sampler(make_integrator(camera, sampler, pixel_bounds));
+    integrator.render(&scene, 2);
+    std::fs::remove_file("pbrt.png").unwrap();
+    let beauty: Vec<Float> = film.get_rgb(1.0);
+    let group_0: Vec<Float> = film.get_rgb_group(0, 1.0);
+    let group_1: Vec<Float> = film.get_rgb_group(1, 1.0);
+    // the light groups add up to the beauty pass
+    for ((b, g0), g1) in beauty.iter().zip(group_0.iter()).zip(group_1.iter()) {
+        assert!(
+            (g0 + g1 - b).abs() <= 1.0e-4 * (1.0 + b),
+            "{}: {} + {} != {}",
+            name,
+            g0,
+            g1,
+            b
+        );
+    }
+    // each group only sees its own light, lighting opposite sides
+    let (left_0, right_0) = halves(&group_0);
+    let (left_1, right_1) = halves(&group_1);
+    assert!(left_0 + right_0 > 0.0 && left_1 + right_1 > 0.0, "{}", name);
+    assert!((left_0 > right_0) != (left_1 > right_1), "{}", name);
+    // the first light is red-ish, the second one blue-ish
+    let red_0: Float = group_0.iter().step_by(3).sum();
+    let blue_0: Float = group_0.iter().skip(2).step_by(3).sum();
+    let red_1: Float = group_1.iter().step_by(3).sum();
+    let blue_1: Float = group_1.iter().skip(2).step_by(3).sum();
+    assert!(red_0 > blue_0 && blue_1 > red_1, "{}", name);
+    println!(
+        "{}: group 0: left {} right {}, group 1: left {} right {}",
+        name, left_0, right_0, left_1, right_1
+    );
+}
+
+fn main() {
+    check_light_groups("directlighting", |camera, sampler, pixel_bounds| {
+        SamplerIntegrator::DirectLighting(DirectLightingIntegrator::new(
+            LightStrategy::UniformSampleAll,
+            5,
+            camera,
+            sampler,
+            pixel_bounds,
+            0,
+        ))
+    });
+    check_light_groups("path", |camera, sampler, pixel_bounds| {
+        SamplerIntegrator::Path(PathIntegrator::new(
+            5,
+            camera,
+            sampler,
+            pixel_bounds,
+            1.0,
+            String::from("spatial"),
+        ))
+    });
+}
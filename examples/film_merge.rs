@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use pbrt::core::film::Film;
+use pbrt::core::geometry::{Bounds2i, Point2f, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::rng::Rng;
+use pbrt::filters::boxfilter::BoxFilter;
+
+const WIDTH: i32 = 4;
+const HEIGHT: i32 = 3;
+
+fn create_film() -> Arc<Film> {
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), WIDTH);
+    film_params.add_int(String::from("yresolution"), HEIGHT);
+    Film::create(&film_params, BoxFilter::create(&ParamSet::default()))
+}
+
+/// A fake "renderer": some smooth radiance function sampled at
+/// random positions within each pixel.
+fn render(films: &[&Arc<Film>], spp: usize, seed: u64) {
+    let mut rng: Rng = Rng::default();
+    rng.set_sequence(seed);
+    let bounds: Bounds2i = Bounds2i::new(
+        Point2i { x: 0, y: 0 },
+        Point2i {
+            x: WIDTH,
+            y: HEIGHT,
+        },
+    );
+    let mut tiles: Vec<_> = films.iter().map(|f| f.get_film_tile(&bounds)).collect();
+    for pixel in &bounds {
+        for _s in 0..spp {
+            let p_film: Point2f = Point2f {
+                x: pixel.x as Float + rng.uniform_float(),
+                y: pixel.y as Float + rng.uniform_float(),
+            };
+            let v: Float = (p_film.x * 1.3).sin() * (p_film.y * 0.7).cos() + 1.0;
+            for tile in tiles.iter_mut() {
+                let mut l: Spectrum = Spectrum::rgb(v, 0.5 * v, 0.25);
+                tile.add_sample(p_film, &mut l, 1.0);
+            }
+        }
+    }
+    for (film, tile) in films.iter().zip(tiles.iter()) {
+        film.merge_film_tile(tile);
+    }
+}
+
+fn main() {
+    // two machines render 8 spp each with different seeds ...
+    let film_a: Arc<Film> = create_film();
+    let film_b: Arc<Film> = create_film();
+    // ... and a single 16 spp render sees the same samples
+    let film_16: Arc<Film> = create_film();
+    render(&[&film_a, &film_16], 8, 1_u64);
+    render(&[&film_b, &film_16], 8, 2_u64);
+    film_a.merge(&film_b);
+    let merged: Vec<Float> = film_a.get_rgb(1.0);
+    let reference: Vec<Float> = film_16.get_rgb(1.0);
+    assert_eq!(merged.len(), (3 * WIDTH * HEIGHT) as usize);
+    for (m, r) in merged.iter().zip(reference.iter()) {
+        assert!((m - r).abs() < 1.0e-5, "merged {} vs. 16 spp {}", m, r);
+    }
+    // the merged result differs from a single 8 spp film
+    let film_b_rgb: Vec<Float> = film_b.get_rgb(1.0);
+    assert!(film_b_rgb
+        .iter()
+        .zip(reference.iter())
+        .any(|(b, r)| (b - r).abs() > 1.0e-3));
+    println!("merged 2 x 8 spp == 16 spp");
+}
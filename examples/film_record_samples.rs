@@ -0,0 +1,128 @@
+use std::io::BufReader;
+use std::sync::Arc;
+
+use pbrt::core::api::{make_camera, make_film, make_filter, make_sampler};
+use pbrt::core::camera::Camera;
+use pbrt::core::film::{Film, SampleRecord};
+use pbrt::core::geometry::{Bounds2i, Vector3f};
+use pbrt::core::integrator::{Integrator, RenderStats, SamplerIntegrator};
+use pbrt::core::light::Light;
+use pbrt::core::material::Material;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::sampler::Sampler;
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::{AnimatedTransform, Transform};
+use pbrt::integrators::directlighting::{DirectLightingIntegrator, LightStrategy};
+use pbrt::lights::point::PointLight;
+use pbrt::materials::matte::MatteMaterial;
+use pbrt::shapes::sphere::Sphere;
+use pbrt::textures::constant::ConstantTexture;
+
+const RESOLUTION: i32 = 8;
+const SPP: i32 = 4;
+const KD: Float = 0.8;
+
+fn make_scene() -> Scene {
+    let object_to_world: Transform = Transform::translate(&Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: 3.0,
+    });
+    let sphere: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        object_to_world,
+        Transform::inverse(&object_to_world),
+        false,
+        1.0,
+        -1.0,
+        1.0,
+        360.0,
+    )));
+    let kd = Arc::new(ConstantTexture::new(Spectrum::new(KD)));
+    let sigma = Arc::new(ConstantTexture::new(0.0 as Float));
+    let matte: Arc<Material> = Arc::new(Material::Matte(Box::new(MatteMaterial::new(
+        kd, sigma, None,
+    ))));
+    let primitive: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(sphere, Some(matte), None, None),
+    )));
+    let light: Arc<Light> = Arc::new(Light::Point(Box::new(PointLight::new(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::new(10.0),
+    ))));
+    Scene::new(primitive, vec![light])
+}
+
+fn main() {
+    let path = std::env::temp_dir().join("pbrt_recorded_samples.bin");
+    let filter = make_filter("box", &ParamSet::default()).unwrap();
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), RESOLUTION);
+    film_params.add_int(String::from("yresolution"), RESOLUTION);
+    film_params.add_string(
+        String::from("recordsamples"),
+        path.to_str().unwrap().to_string(),
+    );
+    let film: Arc<Film> = make_film("image", &film_params, filter).unwrap();
+    let mut sampler_params: ParamSet = ParamSet::default();
+    sampler_params.add_int(String::from("pixelsamples"), SPP);
+    let sampler: Box<Sampler> = make_sampler("random", &sampler_params, film.clone()).unwrap();
+    let camera: Arc<Camera> = make_camera(
+        "perspective",
+        &ParamSet::default(),
+        AnimatedTransform::new(&Transform::default(), 0.0, &Transform::default(), 1.0),
+        film.clone(),
+    )
+    .unwrap();
+    let pixel_bounds: Bounds2i = film.get_sample_bounds();
+    let mut integrator: Integrator = Integrator::Sampler(SamplerIntegrator::DirectLighting(
+        DirectLightingIntegrator::new(
+            LightStrategy::UniformSampleAll,
+            5,
+            camera,
+            sampler,
+            pixel_bounds,
+            0,
+        ),
+    ));
+    let stats: RenderStats = integrator.render(&make_scene(), 2);
+    std::fs::remove_file("pbrt.png").unwrap();
+    // read the binary stream back
+    let mut reader = BufReader::new(std::fs::File::open(&path).unwrap());
+    let mut records: Vec<SampleRecord> = Vec::new();
+    while let Some(record) = SampleRecord::read_from(&mut reader).unwrap() {
+        records.push(record);
+    }
+    std::fs::remove_file(&path).unwrap();
+    let expected: usize = (SPP * RESOLUTION * RESOLUTION) as usize;
+    assert_eq!(records.len(), expected);
+    assert_eq!(stats.total_samples, expected as u64);
+    assert_eq!(
+        film.sample_recorder.as_ref().unwrap().get_count(),
+        expected as u64
+    );
+    let size: Float = RESOLUTION as Float;
+    let mut n_hits: usize = 0;
+    for record in &records {
+        assert!(record.p_film.x >= 0.0 && record.p_film.x < size);
+        assert!(record.p_film.y >= 0.0 && record.p_film.y < size);
+        assert!(!record.l.has_nans());
+        if record.normal.x != 0.0 || record.normal.y != 0.0 || record.normal.z != 0.0 {
+            // the sphere is diffuse, so every BSDF sample returns its albedo
+            n_hits += 1;
+            assert!((record.albedo.c[0] - KD).abs() < 1.0e-3);
+        } else {
+            assert!(record.albedo.is_black() && record.l.is_black());
+        }
+    }
+    assert!(n_hits > 0 && n_hits < records.len());
+    println!(
+        "recorded {} samples ({} hitting the sphere)",
+        records.len(),
+        n_hits
+    );
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use std::thread;
+
+use pbrt::core::film::Film;
+use pbrt::core::filter::Filter;
+use pbrt::core::geometry::{Bounds2i, Point2f, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::filters::boxfilter::BoxFilter;
+use pbrt::filters::gaussian::GaussianFilter;
+
+const RESOLUTION: i32 = 40;
+
+fn create_film(filter: Box<Filter>) -> Arc<Film> {
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), RESOLUTION);
+    film_params.add_int(String::from("yresolution"), RESOLUTION);
+    Film::create(&film_params, filter)
+}
+
+fn gaussian(radius: Float) -> Box<Filter> {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("xwidth"), radius);
+    params.add_float(String::from("ywidth"), radius);
+    GaussianFilter::create(&params)
+}
+
+/// A fake "renderer": some smooth radiance function sampled at four
+/// fixed positions within each pixel of *bounds*.
+fn render_tile(film: &Film, bounds: &Bounds2i) {
+    let mut tile = film.get_film_tile(bounds);
+    for pixel in bounds {
+        for &(dx, dy) in &[(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)] {
+            let p_film: Point2f = Point2f {
+                x: pixel.x as Float + dx,
+                y: pixel.y as Float + dy,
+            };
+            let v: Float = (p_film.x * 0.3).sin() * (p_film.y * 0.2).cos() + 1.0;
+            let mut l: Spectrum = Spectrum::rgb(v, 0.5 * v, 0.25);
+            tile.add_sample(p_film, &mut l, 1.0);
+        }
+    }
+    film.merge_film_tile(&tile);
+}
+
+fn main() {
+    // tiles are at least twice as large as the filter radius ...
+    let film: Arc<Film> = create_film(BoxFilter::create(&ParamSet::default()));
+    assert_eq!(film.get_tile_size(), 16);
+    let film: Arc<Film> = create_film(gaussian(12.0));
+    assert_eq!(film.get_tile_size(), 24);
+    // ... so a tile's filter-extended border only reaches into its
+    // direct neighbours
+    let film: Arc<Film> = create_film(gaussian(3.0));
+    let tile_size: i32 = film.get_tile_size();
+    let sample_bounds: Bounds2i = film.get_sample_bounds();
+    let tile_bounds = |x: i32, y: i32| -> Bounds2i {
+        let x0: i32 = sample_bounds.p_min.x + x * tile_size;
+        let y0: i32 = sample_bounds.p_min.y + y * tile_size;
+        Bounds2i::new(
+            Point2i { x: x0, y: y0 },
+            Point2i {
+                x: std::cmp::min(x0 + tile_size, sample_bounds.p_max.x),
+                y: std::cmp::min(y0 + tile_size, sample_bounds.p_max.y),
+            },
+        )
+    };
+    let first = film.get_film_tile(&tile_bounds(0, 0));
+    let second = film.get_film_tile(&tile_bounds(1, 0));
+    let third = film.get_film_tile(&tile_bounds(2, 0));
+    assert!(first.pixel_bounds.p_max.x > second.pixel_bounds.p_min.x);
+    assert!(first.pixel_bounds.p_max.x <= third.pixel_bounds.p_min.x);
+    // rendering the image in one piece ...
+    let reference: Arc<Film> = create_film(gaussian(3.0));
+    render_tile(&reference, &reference.get_sample_bounds());
+    let expected: Vec<Float> = reference.get_rgb(1.0);
+    // ... or tile by tile, merged concurrently from several threads,
+    // gives the same image
+    let extent = sample_bounds.diagonal();
+    let n_x_tiles: i32 = (extent.x + tile_size - 1) / tile_size;
+    let n_y_tiles: i32 = (extent.y + tile_size - 1) / tile_size;
+    let mut tiles: Vec<Bounds2i> = Vec::new();
+    for y in 0..n_y_tiles {
+        for x in 0..n_x_tiles {
+            tiles.push(tile_bounds(x, y));
+        }
+    }
+    assert!(tiles.len() > 4);
+    let tiles: Arc<Vec<Bounds2i>> = Arc::new(tiles);
+    let n_threads: usize = 4;
+    let workers: Vec<_> = (0..n_threads)
+        .map(|t| {
+            let film: Arc<Film> = film.clone();
+            let tiles: Arc<Vec<Bounds2i>> = tiles.clone();
+            thread::spawn(move || {
+                for bounds in tiles.iter().skip(t).step_by(n_threads) {
+                    render_tile(&film, bounds);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    let rgb: Vec<Float> = film.get_rgb(1.0);
+    assert_eq!(rgb.len(), (3 * RESOLUTION * RESOLUTION) as usize);
+    for (a, b) in rgb.iter().zip(expected.iter()) {
+        assert!((a - b).abs() < 1.0e-4, "tiled {} vs. single tile {}", a, b);
+    }
+    println!("{} tiles == 1 tile", tiles.len());
+}
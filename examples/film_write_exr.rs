@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pbrt::core::film::{is_exr_filename, Film};
+use pbrt::core::geometry::{Bounds2i, Point2f};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::filters::boxfilter::BoxFilter;
+
+fn read_i32(bytes: &[u8], offset: usize) -> i32 {
+    let mut b: [u8; 4] = [0_u8; 4];
+    b.copy_from_slice(&bytes[offset..offset + 4]);
+    i32::from_le_bytes(b)
+}
+
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_bits(read_i32(bytes, offset) as u32)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut b: [u8; 8] = [0_u8; 8];
+    b.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(b)
+}
+
+/// Null-terminated string at *offset*, returns the string and the
+/// offset after the terminator.
+fn read_string(bytes: &[u8], offset: usize) -> (String, usize) {
+    let end: usize = offset + bytes[offset..].iter().position(|b| *b == 0).unwrap();
+    (
+        String::from_utf8(bytes[offset..end].to_vec()).unwrap(),
+        end + 1,
+    )
+}
+
+fn main() {
+    assert!(is_exr_filename("image.exr"));
+    assert!(is_exr_filename("renders/image.EXR"));
+    assert!(!is_exr_filename("image.png"));
+    assert!(!is_exr_filename("exr"));
+    // render a bright (HDR) image into a crop window
+    let filename: String = std::env::temp_dir()
+        .join("rs_pbrt_film_write_exr.exr")
+        .to_string_lossy()
+        .to_string();
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), 8);
+    film_params.add_int(String::from("yresolution"), 6);
+    film_params.add_floats(String::from("cropwindow"), vec![0.25, 0.75, 0.5, 1.0]);
+    film_params.add_string(String::from("filename"), filename.clone());
+    let film: Arc<Film> = Film::create(&film_params, BoxFilter::create(&ParamSet::default()));
+    let crop: Bounds2i = film.get_cropped_pixel_bounds();
+    assert_eq!((crop.p_min.x, crop.p_min.y), (2, 3));
+    assert_eq!((crop.p_max.x, crop.p_max.y), (6, 6));
+    let mut tile = film.get_film_tile(&film.get_sample_bounds());
+    for p in &crop {
+        let mut l: Spectrum = Spectrum::rgb(4.0, 0.5 + p.x as Float, 0.25 * p.y as Float);
+        tile.add_sample(
+            Point2f {
+                x: p.x as Float + 0.5,
+                y: p.y as Float + 0.5,
+            },
+            &mut l,
+            1.0,
+        );
+    }
+    film.merge_film_tile(&tile);
+    let rgb: Vec<Float> = film.get_rgb(1.0);
+    film.write_image(1.0);
+    let bytes: Vec<u8> = std::fs::read(&filename).unwrap();
+    std::fs::remove_file(&filename).unwrap();
+    // magic number and version
+    assert_eq!(read_i32(&bytes, 0), 20_000_630);
+    assert_eq!(read_i32(&bytes, 4), 2);
+    // header attributes
+    let mut attributes: HashMap<String, (String, Vec<u8>)> = HashMap::new();
+    let mut offset: usize = 8;
+    loop {
+        let (name, next) = read_string(&bytes, offset);
+        if name.is_empty() {
+            offset = next;
+            break;
+        }
+        let (type_name, next) = read_string(&bytes, next);
+        let size: usize = read_i32(&bytes, next) as usize;
+        let value: Vec<u8> = bytes[next + 4..next + 4 + size].to_vec();
+        attributes.insert(name, (type_name, value));
+        offset = next + 4 + size;
+    }
+    // float channels, sorted by name
+    let (type_name, chlist) = &attributes["channels"];
+    assert_eq!(type_name, "chlist");
+    let mut channels: Vec<String> = Vec::new();
+    let mut c: usize = 0;
+    while chlist[c] != 0 {
+        let (name, next) = read_string(chlist, c);
+        assert_eq!(read_i32(chlist, next), 2);
+        channels.push(name);
+        c = next + 16;
+    }
+    assert_eq!(channels, vec!["B", "G", "R"]);
+    assert_eq!(attributes["compression"].1, vec![0_u8]);
+    // the data window is the crop window (inclusive), the display
+    // window the full image
+    let window = |name: &str| -> Vec<i32> {
+        (0..4)
+            .map(|i| read_i32(&attributes[name].1, 4 * i))
+            .collect()
+    };
+    assert_eq!(window("dataWindow"), vec![2, 3, 5, 5]);
+    assert_eq!(window("displayWindow"), vec![0, 0, 7, 5]);
+    // one chunk per scanline of the data window with linear values
+    // (no gamma, nothing clamped to one)
+    let width: usize = 4;
+    for y in 0..3 {
+        let chunk: usize = read_u64(&bytes, offset + 8 * y) as usize;
+        assert_eq!(read_i32(&bytes, chunk), 3 + y as i32);
+        assert_eq!(read_i32(&bytes, chunk + 4), (4 * 3 * width) as i32);
+        for x in 0..width {
+            let index: usize = 3 * (y * width + x);
+            let b: f32 = read_f32(&bytes, chunk + 8 + 4 * x);
+            let g: f32 = read_f32(&bytes, chunk + 8 + 4 * (width + x));
+            let r: f32 = read_f32(&bytes, chunk + 8 + 4 * (2 * width + x));
+            assert_eq!((r, g, b), (rgb[index], rgb[index + 1], rgb[index + 2]));
+            assert!((r - 4.0).abs() < 1e-3, "{}", r);
+        }
+    }
+    println!("wrote {} bytes of OpenEXR", bytes.len());
+}
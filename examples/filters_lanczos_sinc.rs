@@ -0,0 +1,66 @@
+use pbrt::core::filter::Filter;
+use pbrt::core::geometry::{Point2f, Vector2f};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::filters::sinc::LanczosSincFilter;
+
+fn point(x: Float, y: Float) -> Point2f {
+    Point2f { x, y }
+}
+
+fn create(params: &ParamSet) -> LanczosSincFilter {
+    match *LanczosSincFilter::create(params) {
+        Filter::LanczosSinc(filter) => filter,
+        _ => panic!("expected a Lanczos sinc filter"),
+    }
+}
+
+fn main() {
+    // PBRT's defaults: a radius of 4 pixels and 3 lobes
+    let filter: LanczosSincFilter = create(&ParamSet::default());
+    assert_eq!(
+        (filter.radius.x, filter.radius.y, filter.tau),
+        (4.0, 4.0, 3.0)
+    );
+    let radius: Vector2f = filter.get_radius();
+    assert_eq!((radius.x, radius.y), (4.0, 4.0));
+    // one at the center, (almost) zero at the other integers, and
+    // negative between one and two pixels
+    assert_eq!(filter.evaluate(point(0.0, 0.0)), 1.0);
+    for i in 1..4 {
+        assert!(filter.evaluate(point(i as Float, 0.0)).abs() < 1e-6);
+    }
+    assert!(filter.evaluate(point(1.5, 0.0)) < 0.0);
+    assert!(filter.evaluate(point(-1.5, 0.0)) < 0.0);
+    // separable
+    for &(x, y) in &[(0.3, 0.7), (1.2, -2.5), (-3.1, 0.4)] {
+        let product: Float =
+            filter.windowed_sinc(x, filter.radius.x) * filter.windowed_sinc(y, filter.radius.y);
+        assert_eq!(filter.evaluate(point(x, y)), product);
+        assert_eq!(filter.evaluate(point(x, y)), filter.evaluate(point(y, x)));
+    }
+    // exactly zero at and beyond the radius, and it fades out towards
+    // the radius, so there's no step at the edge
+    for &(x, y) in &[(4.0, 0.0), (0.0, 4.0), (4.5, 0.2), (0.1, -7.0)] {
+        assert_eq!(filter.evaluate(point(x, y)), 0.0);
+    }
+    assert!(filter.evaluate(point(3.999, 0.0)).abs() < 1e-3);
+    // create() reads the radius and "tau" ...
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("xwidth"), 2.0);
+    params.add_float(String::from("ywidth"), 3.0);
+    params.add_float(String::from("tau"), 2.0);
+    let narrow: LanczosSincFilter = create(&params);
+    assert_eq!(
+        (narrow.radius.x, narrow.radius.y, narrow.tau),
+        (2.0, 3.0, 2.0)
+    );
+    assert_eq!(narrow.evaluate(point(2.0, 0.0)), 0.0);
+    assert!(narrow.evaluate(point(0.0, 2.5)).abs() > 1e-3);
+    // ... and falls back to the default for invalid values
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("tau"), 0.0);
+    let fallback: LanczosSincFilter = create(&params);
+    assert_eq!(fallback.tau, 3.0);
+    assert!(fallback.evaluate(point(0.5, 0.5)).is_finite());
+}
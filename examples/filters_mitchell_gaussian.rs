@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use pbrt::core::film::Film;
+use pbrt::core::filter::Filter;
+use pbrt::core::geometry::{Bounds2i, Point2f};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::filters::gaussian::GaussianFilter;
+use pbrt::filters::mitchell::MitchellNetravali;
+
+const RESOLUTION: i32 = 8;
+
+fn point(x: Float, y: Float) -> Point2f {
+    Point2f { x, y }
+}
+
+fn create_film(filter: Box<Filter>) -> Arc<Film> {
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), RESOLUTION);
+    film_params.add_int(String::from("yresolution"), RESOLUTION);
+    Film::create(&film_params, filter)
+}
+
+/// Red channel of pixel *(x, y)*.
+fn red(rgb: &[Float], x: i32, y: i32) -> Float {
+    rgb[3 * (y * RESOLUTION + x) as usize]
+}
+
+fn main() {
+    // create() reads the filter width and B and C ...
+    let mitchell: MitchellNetravali = match *MitchellNetravali::create(&ParamSet::default()) {
+        Filter::MitchellNetravali(filter) => filter,
+        _ => panic!("expected a Mitchell-Netravali filter"),
+    };
+    assert_eq!((mitchell.radius.x, mitchell.radius.y), (2.0, 2.0));
+    assert_eq!((mitchell.b, mitchell.c), (1.0 / 3.0, 1.0 / 3.0));
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("xwidth"), 1.5);
+    params.add_float(String::from("B"), 0.0);
+    params.add_float(String::from("C"), 0.5);
+    match *MitchellNetravali::create(&params) {
+        Filter::MitchellNetravali(filter) => {
+            assert_eq!((filter.radius.x, filter.radius.y), (1.5, 2.0));
+            assert_eq!((filter.b, filter.c), (0.0, 0.5));
+        }
+        _ => panic!("expected a Mitchell-Netravali filter"),
+    }
+    // ... the filter peaks at the center, has negative lobes and
+    // vanishes at its radius
+    let center: Float = 8.0 / 9.0;
+    assert!((mitchell.evaluate(point(0.0, 0.0)) - center * center).abs() < 1e-6);
+    assert!(mitchell.evaluate(point(1.5, 0.0)) < 0.0);
+    assert_eq!(mitchell.evaluate(point(2.0, 0.0)), 0.0);
+    assert_eq!(mitchell.evaluate(point(0.3, 2.5)), 0.0);
+    // for B + 2C = 1 shifted copies (one pixel apart for a radius of
+    // two pixels) add up to one, so a constant signal is preserved
+    for i in 0..10 {
+        let x: Float = i as Float / 20.0;
+        let sum: Float = (-4..=4)
+            .map(|k| mitchell.mitchell_1d(x + 0.5 * k as Float))
+            .sum();
+        assert!((sum - 1.0).abs() < 1e-5, "{}", sum);
+    }
+    // the Gaussian is shifted down to reach zero at its radius ...
+    let gaussian: GaussianFilter = match *GaussianFilter::create(&ParamSet::default()) {
+        Filter::Gaussian(filter) => filter,
+        _ => panic!("expected a Gaussian filter"),
+    };
+    assert_eq!(gaussian.alpha, 2.0);
+    let peak: Float = 1.0 - (-8.0 as Float).exp();
+    assert!((gaussian.evaluate(point(0.0, 0.0)) - peak * peak).abs() < 1e-6);
+    assert_eq!(gaussian.evaluate(point(2.0, 0.0)), 0.0);
+    let mut previous: Float = gaussian.evaluate(point(0.0, 0.0));
+    for i in 1..=20 {
+        let value: Float = gaussian.evaluate(point(i as Float / 10.0, 0.0));
+        assert!(value >= 0.0 && value < previous, "{} {}", value, previous);
+        previous = value;
+    }
+    // ... and falls off faster for a larger alpha
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("alpha"), 8.0);
+    let sharp: GaussianFilter = match *GaussianFilter::create(&params) {
+        Filter::Gaussian(filter) => filter,
+        _ => panic!("expected a Gaussian filter"),
+    };
+    let falloff = |filter: &GaussianFilter| -> Float {
+        filter.evaluate(point(0.5, 0.0)) / filter.evaluate(point(0.0, 0.0))
+    };
+    assert!(falloff(&sharp) < falloff(&gaussian));
+    // the film weights samples with the filter: a constant image stays
+    // constant ...
+    for filter in [
+        MitchellNetravali::create(&ParamSet::default()),
+        GaussianFilter::create(&ParamSet::default()),
+    ] {
+        let film: Arc<Film> = create_film(filter);
+        let sample_bounds: Bounds2i = film.get_sample_bounds();
+        let mut tile = film.get_film_tile(&sample_bounds);
+        for p in &sample_bounds {
+            for &(dx, dy) in &[(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)] {
+                let mut l: Spectrum = Spectrum::rgb(0.5, 0.5, 0.5);
+                tile.add_sample(point(p.x as Float + dx, p.y as Float + dy), &mut l, 1.0);
+            }
+        }
+        film.merge_film_tile(&tile);
+        let rgb: Vec<Float> = film.get_rgb(1.0);
+        assert!(rgb.iter().all(|v| (v - 0.5).abs() < 1e-3), "{:?}", rgb);
+    }
+    // ... and pixels which only see the negative lobes of a single
+    // sample stay black instead of flipping the sign of the sample
+    let film: Arc<Film> = create_film(MitchellNetravali::create(&ParamSet::default()));
+    let mut tile = film.get_film_tile(&film.get_sample_bounds());
+    let mut l: Spectrum = Spectrum::rgb(1.0, 1.0, 1.0);
+    tile.add_sample(point(2.0, 2.5), &mut l, 1.0);
+    film.merge_film_tile(&tile);
+    let rgb: Vec<Float> = film.get_rgb(1.0);
+    assert!(rgb.iter().all(|v| *v >= 0.0));
+    assert_eq!(red(&rgb, 0, 2), 0.0);
+    assert_eq!(red(&rgb, 3, 2), 0.0);
+    assert!((red(&rgb, 1, 2) - 1.0).abs() < 1e-3, "{}", red(&rgb, 1, 2));
+    assert!((red(&rgb, 2, 2) - 1.0).abs() < 1e-3, "{}", red(&rgb, 2, 2));
+}
@@ -0,0 +1,76 @@
+use pbrt::core::geometry::{coordinate_system, vec3_cross_vec3, vec3_dot_vec3, Point2f, Vector3f};
+use pbrt::core::pbrt::Float;
+use pbrt::core::rng::Rng;
+use pbrt::core::sampling::uniform_sample_sphere;
+
+fn check(v: Vector3f) {
+    let (v2, v3) = coordinate_system(v);
+    let v: Vector3f = v.normalize();
+    let eps: Float = 1e-5;
+    for (name, w) in &[("v2", v2), ("v3", v3)] {
+        assert!(
+            (w.length() - 1.0).abs() < eps,
+            "|{}| = {} for {:?}",
+            name,
+            w.length(),
+            v
+        );
+    }
+    assert!(vec3_dot_vec3(&v, &v2).abs() < eps, "v . v2 for {:?}", v);
+    assert!(vec3_dot_vec3(&v, &v3).abs() < eps, "v . v3 for {:?}", v);
+    assert!(vec3_dot_vec3(&v2, &v3).abs() < eps, "v2 . v3 for {:?}", v);
+    // right-handed: v x v2 = v3
+    let c: Vector3f = vec3_cross_vec3(&v, &v2);
+    assert!((c - v3).length() < eps, "v x v2 != v3 for {:?}", v);
+}
+
+fn main() {
+    // the coordinate axes (both directions) and directions close to them
+    let tiny: Float = 1e-7;
+    let axes: [Vector3f; 3] = [
+        Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        Vector3f {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+        Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+    ];
+    for axis in &axes {
+        for s in &[1.0 as Float, -1.0 as Float] {
+            let a: Vector3f = *axis * *s;
+            check(a);
+            check(Vector3f {
+                x: a.x + tiny,
+                y: a.y - tiny,
+                z: a.z + tiny,
+            });
+        }
+    }
+    // z slightly negative is the problematic case for the sign flip
+    check(Vector3f {
+        x: 1.0,
+        y: 1e-4,
+        z: -1e-8,
+    });
+    // many random directions
+    let mut rng: Rng = Rng::default();
+    rng.set_sequence(0_u64);
+    let n: usize = 100_000;
+    for _ in 0..n {
+        let u: Point2f = Point2f {
+            x: rng.uniform_float(),
+            y: rng.uniform_float(),
+        };
+        check(uniform_sample_sphere(u));
+    }
+    println!("{} orthonormal, right-handed frames", n + 12);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::scene::{camera, render, sphere};
+use pbrt::accelerators::bvh::BVHAccel;
+use pbrt::core::geometry::Point2i;
+use pbrt::core::integrator::{Integrator, SamplerIntegrator};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::primitive::Primitive;
+use pbrt::core::scene::Scene;
+use pbrt::integrators::ao::AOIntegrator;
+use pbrt::samplers::random::RandomSampler;
+
+const RESOLUTION: i32 = 16;
+
+/// A sphere in front of the camera, optionally enclosed by a much
+/// larger sphere (around the camera).
+fn scene(enclosed: bool) -> Scene {
+    let mut primitives: Vec<Arc<Primitive>> = vec![sphere(3.0, 1.0, None)];
+    if enclosed {
+        primitives.push(sphere(0.0, 10.0, None));
+    }
+    let bvh: Arc<Primitive> = Arc::new(BVHAccel::create(primitives, &ParamSet::default()));
+    Scene::new(bvh, Vec::new())
+}
+
+/// Occlusion factor of the center pixel.
+fn occlusion(params: &ParamSet, enclosed: bool) -> Float {
+    let (_stats, rgb) = render(
+        "ambientocclusion",
+        params,
+        &scene(enclosed),
+        camera(RESOLUTION),
+        4,
+        1,
+    );
+    let i: usize = 3 * (RESOLUTION / 2 * RESOLUTION + RESOLUTION / 2) as usize;
+    // grayscale
+    assert!((rgb[i] - rgb[i + 1]).abs() < 1e-4);
+    assert!((rgb[i] - rgb[i + 2]).abs() < 1e-4);
+    rgb[i]
+}
+
+fn main() {
+    // defaults
+    let defaults: Box<Integrator> = AOIntegrator::create(
+        &ParamSet::default(),
+        camera(RESOLUTION),
+        RandomSampler::create(&ParamSet::default()),
+    );
+    if let Integrator::Sampler(SamplerIntegrator::AO(ao)) = &*defaults {
+        assert_eq!(ao.n_samples, 64);
+        assert!(ao.cos_sample);
+        assert_eq!(ao.max_distance, std::f32::INFINITY);
+    } else {
+        panic!("AOIntegrator::create() didn't create an AO integrator");
+    }
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("nsamples"), 16);
+    params.add_bool(String::from("cossample"), false);
+    params.add_float(String::from("maxdistance"), 2.5);
+    params.add_ints(String::from("pixelbounds"), vec![0, 8, 4, 12]);
+    if let Integrator::Sampler(SamplerIntegrator::AO(ao)) = &*AOIntegrator::create(
+        &params,
+        camera(RESOLUTION),
+        RandomSampler::create(&ParamSet::default()),
+    ) {
+        assert_eq!(ao.n_samples, 16);
+        assert!(!ao.cos_sample);
+        assert_eq!(ao.max_distance, 2.5);
+        let bounds = ao.get_pixel_bounds();
+        assert_eq!(bounds.p_min, Point2i { x: 0, y: 4 });
+        assert_eq!(bounds.p_max, Point2i { x: 8, y: 12 });
+    } else {
+        unreachable!()
+    }
+    // nothing occludes the hemisphere over a convex object, rays
+    // escaping the scene count as unoccluded ...
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("nsamples"), 16);
+    let open: Float = occlusion(&params, false);
+    assert!((open - 1.0).abs() < 1e-3, "{}", open);
+    params.add_bool(String::from("cossample"), false);
+    let open: Float = occlusion(&params, false);
+    assert!((open - 1.0).abs() < 0.15, "{}", open);
+    // ... inside a closed sphere everything is occluded ...
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("nsamples"), 16);
+    let closed: Float = occlusion(&params, true);
+    assert_eq!(closed, 0.0);
+    // ... unless it is further away than the maximum distance
+    params.add_float(String::from("maxdistance"), 2.0);
+    let near: Float = occlusion(&params, true);
+    assert!((near - 1.0).abs() < 1e-3, "{}", near);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use common::scene::{camera, lit_sphere, render};
+use pbrt::core::geometry::Point2i;
+use pbrt::core::integrator::Integrator;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::integrators::bdpt::{buffer_index, BDPTIntegrator};
+use pbrt::samplers::random::RandomSampler;
+
+const RESOLUTION: i32 = 8;
+
+fn create(params: &ParamSet) -> Box<Integrator> {
+    BDPTIntegrator::create(
+        params,
+        camera(RESOLUTION),
+        RandomSampler::create(&ParamSet::default()),
+    )
+}
+
+fn main() {
+    // debug images are numbered by depth without gaps
+    let mut indices: Vec<usize> = Vec::new();
+    for depth in 0..6 {
+        for s in 0..=(depth + 2) {
+            indices.push(buffer_index(s, depth + 2 - s));
+        }
+    }
+    assert_eq!(indices, (0..indices.len()).collect::<Vec<usize>>());
+    // defaults
+    if let Integrator::BDPT(bdpt) = &*create(&ParamSet::default()) {
+        assert_eq!(bdpt.max_depth, 5);
+        assert!(!bdpt.visualize_strategies);
+        assert!(!bdpt.visualize_weights);
+        assert_eq!(bdpt.light_sample_strategy, "power");
+    } else {
+        panic!("BDPTIntegrator::create() didn't create a BDPT integrator");
+    }
+    // visualization limits the depth ...
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("maxdepth"), 8);
+    params.add_bool(String::from("visualizestrategies"), true);
+    if let Integrator::BDPT(bdpt) = &*create(&params) {
+        assert_eq!(bdpt.max_depth, 5);
+        assert!(bdpt.visualize_strategies);
+    } else {
+        unreachable!()
+    }
+    // ... pixel bounds are honored
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("maxdepth"), 1);
+    params.add_ints(String::from("pixelbounds"), vec![0, 4, 2, 6]);
+    if let Integrator::BDPT(bdpt) = &*create(&params) {
+        assert_eq!(bdpt.max_depth, 1);
+        assert_eq!(bdpt.pixel_bounds.p_min, Point2i { x: 0, y: 2 });
+        assert_eq!(bdpt.pixel_bounds.p_max, Point2i { x: 4, y: 6 });
+    } else {
+        unreachable!()
+    }
+    // one image per strategy (apart from s = 1, t = 1 and t = 0) is
+    // written next to the rendered image
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("maxdepth"), 1);
+    params.add_bool(String::from("visualizeweights"), true);
+    let (_stats, rgb) = render("bdpt", &params, &lit_sphere(), camera(RESOLUTION), 2, 1);
+    let expected: HashSet<&str> = [
+        "bdpt_d00_s00_t02.exr",
+        "bdpt_d01_s00_t03.exr",
+        "bdpt_d01_s01_t02.exr",
+        "bdpt_d01_s02_t01.exr",
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    for filename in &expected {
+        assert!(Path::new(filename).exists(), "{} is missing", filename);
+        std::fs::remove_file(filename).unwrap();
+    }
+    assert!(!Path::new("bdpt_d00_s01_t01.exr").exists());
+    let lit: Float = rgb.iter().sum();
+    assert!(lit > 0.0, "image is black");
+}
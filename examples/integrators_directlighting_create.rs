@@ -0,0 +1,143 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::scene::{camera, camera_with_film, film, matte, point_light, render, sphere};
+use pbrt::core::api::make_integrator;
+use pbrt::core::camera::Camera;
+use pbrt::core::geometry::{Point2i, Vector3f};
+use pbrt::core::integrator::{Integrator, SamplerIntegrator};
+use pbrt::core::light::Light;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::scene::Scene;
+use pbrt::integrators::directlighting::{DirectLightingIntegrator, LightStrategy};
+use pbrt::samplers::random::RandomSampler;
+
+const RESOLUTION: i32 = 16;
+
+fn camera_with_crop_window(crop_window: Vec<Float>) -> Arc<Camera> {
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_floats(String::from("cropwindow"), crop_window);
+    camera_with_film(film(RESOLUTION, film_params))
+}
+
+/// A matte sphere in front of the camera, lit by two point lights
+/// next to the camera.
+fn scene() -> Scene {
+    let light = |x: Float, intensity: Float| -> Arc<Light> {
+        point_light(Vector3f { x, y: 0.0, z: 0.0 }, intensity)
+    };
+    Scene::new(
+        sphere(3.0, 1.5, Some(matte(0.8))),
+        vec![light(-0.5, 10.0), light(0.5, 2.0)],
+    )
+}
+
+/// Renders the scene with direct lighting created from *params*,
+/// returns the sum of all pixels.
+fn brightness(params: &ParamSet) -> Float {
+    let (_stats, rgb) = render(
+        "directlighting",
+        params,
+        &scene(),
+        camera(RESOLUTION),
+        64,
+        1,
+    );
+    rgb.iter().sum()
+}
+
+fn create(params: &ParamSet) -> Box<Integrator> {
+    DirectLightingIntegrator::create(
+        params,
+        camera(RESOLUTION),
+        RandomSampler::create(&ParamSet::default()),
+    )
+}
+
+fn main() {
+    // defaults: all lights, the whole film
+    let defaults: Box<Integrator> = create(&ParamSet::default());
+    if let Integrator::Sampler(SamplerIntegrator::DirectLighting(direct)) = &*defaults {
+        assert_eq!(direct.strategy, LightStrategy::UniformSampleAll);
+        assert_eq!(direct.max_depth, 5);
+        assert_eq!(direct.ris_candidates, 0);
+        assert!(!direct.power_light_sampling);
+        let bounds = direct.get_pixel_bounds();
+        assert_eq!(bounds.p_min, Point2i { x: 0, y: 0 });
+        assert_eq!(
+            bounds.p_max,
+            Point2i {
+                x: RESOLUTION,
+                y: RESOLUTION
+            }
+        );
+    } else {
+        panic!("DirectLightingIntegrator::create() didn't create direct lighting");
+    }
+    // create() reads the strategy, depth and pixel bounds ...
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("strategy"), String::from("one"));
+    params.add_int(String::from("maxdepth"), 2);
+    params.add_string(String::from("lightsamplestrategy"), String::from("power"));
+    params.add_ints(String::from("pixelbounds"), vec![0, 8, 4, 12]);
+    if let Integrator::Sampler(SamplerIntegrator::DirectLighting(direct)) = &*create(&params) {
+        assert_eq!(direct.strategy, LightStrategy::UniformSampleOne);
+        assert_eq!(direct.max_depth, 2);
+        assert!(direct.power_light_sampling);
+        let bounds = direct.get_pixel_bounds();
+        assert_eq!(bounds.p_min, Point2i { x: 0, y: 4 });
+        assert_eq!(bounds.p_max, Point2i { x: 8, y: 12 });
+    } else {
+        unreachable!()
+    }
+    // ... and falls back to "all" for unknown strategies
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("strategy"), String::from("some"));
+    if let Integrator::Sampler(SamplerIntegrator::DirectLighting(direct)) = &*create(&params) {
+        assert_eq!(direct.strategy, LightStrategy::UniformSampleAll);
+    } else {
+        unreachable!()
+    }
+    // make_integrator() uses the film's sample bounds (e.g. of a crop
+    // window), like for the other integrators
+    let camera: Arc<Camera> = camera_with_crop_window(vec![0.25, 0.75, 0.0, 0.5]);
+    let sample_bounds = camera.get_film().get_sample_bounds();
+    for name in &["directlighting", "path"] {
+        let integrator: Box<Integrator> = make_integrator(
+            name,
+            &ParamSet::default(),
+            camera.clone(),
+            RandomSampler::create(&ParamSet::default()),
+        )
+        .unwrap();
+        if let Integrator::Sampler(sampler_integrator) = &*integrator {
+            let bounds = sampler_integrator.get_pixel_bounds();
+            assert_eq!(bounds.p_min, sample_bounds.p_min, "{}", name);
+            assert_eq!(bounds.p_max, sample_bounds.p_max, "{}", name);
+        } else {
+            unreachable!()
+        }
+    }
+    // sampling all lights or one (uniformly or by power) estimates the
+    // same image
+    let all: Float = brightness(&ParamSet::default());
+    assert!(all > 0.0, "image is black");
+    for light_strategy in &["uniform", "power"] {
+        let mut params: ParamSet = ParamSet::default();
+        params.add_string(String::from("strategy"), String::from("one"));
+        params.add_string(
+            String::from("lightsamplestrategy"),
+            String::from(*light_strategy),
+        );
+        let one: Float = brightness(&params);
+        assert!(
+            (one - all).abs() < 0.05 * all,
+            "{}: {} vs. {}",
+            light_strategy,
+            one,
+            all
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use pbrt::core::api::{
+    pbrt_camera, pbrt_cleanup, pbrt_film, pbrt_init, pbrt_integrator, pbrt_light_source,
+    pbrt_look_at, pbrt_material, pbrt_sampler, pbrt_shape, pbrt_world_begin,
+};
+use pbrt::core::geometry::Point3f;
+use pbrt::core::integrator::RenderStats;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::sampling::ris_select;
+
+fn named(name: &str) -> ParamSet {
+    let mut params: ParamSet = ParamSet::default();
+    params.name = String::from(name);
+    params
+}
+
+/// A glossy sphere lit by one bright and many dim point lights,
+/// rendered with the direct lighting integrator (strategy "one").
+/// Returns the render statistics and the average pixel value.
+fn render(ris_candidates: i32, spp: i32) -> (RenderStats, f64) {
+    let (mut api_state, mut bsdf_state) = pbrt_init(1_u8);
+    pbrt_look_at(&mut api_state, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0);
+    let mut camera_params: ParamSet = named("perspective");
+    camera_params.add_float(String::from("fov"), 30.0);
+    pbrt_camera(&mut api_state, camera_params);
+    let mut film_params: ParamSet = named("image");
+    film_params.add_int(String::from("xresolution"), 24);
+    film_params.add_int(String::from("yresolution"), 24);
+    pbrt_film(&mut api_state, film_params);
+    let mut sampler_params: ParamSet = named("random");
+    sampler_params.add_int(String::from("pixelsamples"), spp);
+    pbrt_sampler(&mut api_state, sampler_params);
+    let mut integrator_params: ParamSet = named("directlighting");
+    integrator_params.add_string(String::from("strategy"), String::from("one"));
+    integrator_params.add_int(String::from("riscandidates"), ris_candidates);
+    pbrt_integrator(&mut api_state, integrator_params);
+    pbrt_world_begin(&mut api_state);
+    let lights: [(Float, Float, Float, Float); 8] = [
+        (1.0, 1.0, 4.0, 20.0),
+        (-3.0, 0.0, -3.0, 0.5),
+        (3.0, 0.0, -3.0, 0.5),
+        (0.0, 3.0, -3.0, 0.5),
+        (0.0, -3.0, -3.0, 0.5),
+        (-2.0, -2.0, 2.0, 0.5),
+        (2.0, -2.0, 2.0, 0.5),
+        (-2.0, 2.0, 2.0, 0.5),
+    ];
+    for (x, y, z, i) in &lights {
+        let mut light_params: ParamSet = named("point");
+        light_params.add_point3f(
+            String::from("from"),
+            Point3f {
+                x: *x,
+                y: *y,
+                z: *z,
+            },
+        );
+        light_params.add_rgb_spectrum(String::from("I"), Spectrum::new(*i));
+        pbrt_light_source(&mut api_state, light_params);
+    }
+    let mut material_params: ParamSet = named("plastic");
+    material_params.add_rgb_spectrum(String::from("Kd"), Spectrum::new(0.1));
+    material_params.add_rgb_spectrum(String::from("Ks"), Spectrum::new(0.8));
+    material_params.add_float(String::from("roughness"), 0.05);
+    pbrt_material(&mut api_state, material_params);
+    pbrt_shape(&mut api_state, &mut bsdf_state, named("sphere"));
+    let stats: RenderStats = pbrt_cleanup(&api_state);
+    let pixels: Vec<u8> = image::open("pbrt.png").unwrap().to_rgb8().into_raw();
+    let mean: f64 = pixels.iter().map(|p| *p as f64).sum::<f64>() / pixels.len() as f64;
+    (stats, mean)
+}
+
+fn main() {
+    // a single candidate is returned with weight 1 / pdf (plain NEE)
+    let (x, weight) = ris_select(vec![(3.0 as Float, 0.25 as Float)], |x| *x, 0.7).unwrap();
+    assert_eq!(x, 3.0);
+    assert!((weight - 4.0).abs() < 1e-6);
+    // candidates with zero target are never selected
+    for u in &[0.0, 0.3, 0.999] {
+        let (x, _weight) = ris_select(
+            vec![(0.0 as Float, 1.0), (2.0, 1.0), (0.0, 1.0)],
+            |x| *x,
+            *u,
+        )
+        .unwrap();
+        assert_eq!(x, 2.0);
+    }
+    assert!(ris_select(vec![(0.0 as Float, 1.0)], |x| *x, 0.5).is_none());
+    // M = 1 converges to the same image as standard light selection
+    let (_stats, nee_mean) = render(0, 64);
+    let (ris_1, ris_1_mean) = render(1, 64);
+    let (ris_8, ris_8_mean) = render(8, 64);
+    println!(
+        "mean pixel value: nee {:.3}, ris(1) {:.3}, ris(8) {:.3}",
+        nee_mean, ris_1_mean, ris_8_mean
+    );
+    assert!((nee_mean - ris_1_mean).abs() < 0.02 * nee_mean);
+    assert!((nee_mean - ris_8_mean).abs() < 0.02 * nee_mean);
+    // more candidates, less noise at the same sample count
+    println!(
+        "mean variance: ris(1) {}, ris(8) {}",
+        ris_1.mean_variance, ris_8.mean_variance
+    );
+    assert!(ris_8.mean_variance < 0.5 * ris_1.mean_variance);
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use common::scene::{camera, sphere};
+use pbrt::core::api::{
+    make_integrator, pbrt_camera, pbrt_cleanup, pbrt_film, pbrt_init, pbrt_integrator,
+    pbrt_look_at, pbrt_sampler, pbrt_shape, pbrt_world_begin,
+};
+use pbrt::core::camera::Camera;
+use pbrt::core::integrator::{Integrator, RenderStats};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::rng::Rng;
+use pbrt::core::scene::Scene;
+use pbrt::samplers::random::RandomSampler;
+
+/// Luminance samples of a synthetic pixel: 4 with probability 1/4,
+/// zero otherwise (mean 1, variance 3).
+fn pixel_sample(rng: &mut Rng) -> f64 {
+    if rng.uniform_float() < 0.25 {
+        4.0
+    } else {
+        0.0
+    }
+}
+
+/// Per-pixel statistics of *n_pixels* synthetic pixels, gathered like
+/// the render loop of the **SamplerIntegrator** does.
+fn synthetic_render(rng: &mut Rng, n_pixels: usize, spp: u64) -> (RenderStats, Vec<f64>) {
+    let mut stats: RenderStats = RenderStats::default();
+    let mut pixels: Vec<f64> = Vec::with_capacity(n_pixels);
+    for _p in 0..n_pixels {
+        let mut sum_y: f64 = 0.0;
+        let mut sum_y2: f64 = 0.0;
+        for _s in 0..spp {
+            let y: f64 = pixel_sample(rng);
+            sum_y += y;
+            sum_y2 += y * y;
+        }
+        let n: f64 = spp as f64;
+        stats.total_samples += spp;
+        stats.mean_luminance += (sum_y / n) as Float;
+        stats.mean_variance += ((sum_y2 - sum_y * sum_y / n) / (n - 1.0)) as Float;
+        pixels.push(sum_y / n);
+    }
+    stats.mean_luminance /= n_pixels as Float;
+    stats.mean_variance /= n_pixels as Float;
+    (stats, pixels)
+}
+
+fn named(name: &str) -> ParamSet {
+    let mut params: ParamSet = ParamSet::default();
+    params.name = String::from(name);
+    params
+}
+
+fn main() {
+    let mut rng: Rng = Rng::new();
+    let target: Float = 0.05;
+    // pilot render with 8 samples per pixel
+    let (pilot, _pixels) = synthetic_render(&mut rng, 1000, 8);
+    let spp: i64 = pilot.estimate_spp_for_error(target);
+    // exact answer: variance / (target * mean)^2
+    let exact: f64 = 3.0 / (target as f64 * target as f64);
+    println!("pilot {:?}: {} spp (exact {})", pilot, spp, exact);
+    assert!(spp as f64 > exact / 1.5 && (spp as f64) < exact * 1.5);
+    // rendering with the recommended sample count reaches the target
+    let (_stats, pixels) = synthetic_render(&mut rng, 200, spp as u64);
+    let mse: f64 = pixels.iter().map(|p| (p - 1.0) * (p - 1.0)).sum::<f64>() / pixels.len() as f64;
+    let rel_error: f64 = mse.sqrt();
+    println!("relative error with {} spp: {}", spp, rel_error);
+    assert!(rel_error < 1.5 * target as f64);
+    // halving the error needs four times the samples
+    let spp_half: i64 = pilot.estimate_spp_for_error(0.5 * target);
+    assert!((spp_half - 4 * spp).abs() <= 4);
+    // black images (or untracked statistics) give no estimate
+    assert_eq!(RenderStats::default().estimate_spp_for_error(target), 0);
+    // the render loop tracks the statistics needed for the estimate
+    let (mut api_state, mut bsdf_state) = pbrt_init(1_u8);
+    pbrt_look_at(&mut api_state, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0);
+    pbrt_camera(&mut api_state, named("perspective"));
+    let mut film_params: ParamSet = named("image");
+    film_params.add_int(String::from("xresolution"), 16);
+    film_params.add_int(String::from("yresolution"), 16);
+    pbrt_film(&mut api_state, film_params);
+    let mut sampler_params: ParamSet = named("random");
+    sampler_params.add_int(String::from("pixelsamples"), 8);
+    pbrt_sampler(&mut api_state, sampler_params);
+    let mut integrator_params: ParamSet = named("ambientocclusion");
+    integrator_params.add_int(String::from("nsamples"), 1);
+    pbrt_integrator(&mut api_state, integrator_params);
+    pbrt_world_begin(&mut api_state);
+    pbrt_shape(&mut api_state, &mut bsdf_state, named("sphere"));
+    let stats: RenderStats = pbrt_cleanup(&api_state);
+    assert!(stats.mean_luminance > 0.0);
+    assert!(stats.estimate_spp_for_error(target) >= 1);
+    std::fs::remove_file("pbrt.png").unwrap();
+    // the pilot render of a sampler integrator neither writes an
+    // image nor leaves its samples in the film
+    let camera: Arc<Camera> = camera(16);
+    let film = camera.get_film();
+    let mut sampler_params: ParamSet = ParamSet::default();
+    sampler_params.add_int(String::from("pixelsamples"), 8);
+    let mut integrator: Box<Integrator> = make_integrator(
+        "ambientocclusion",
+        &ParamSet::default(),
+        camera,
+        RandomSampler::create(&sampler_params),
+    )
+    .unwrap();
+    let scene: Scene = Scene::new(sphere(3.0, 1.0, None), Vec::new());
+    if let Integrator::Sampler(integrator) = &mut *integrator {
+        assert!(integrator.estimate_spp_for_error(&scene, 1, target) >= 1);
+    } else {
+        unreachable!()
+    }
+    assert!(!Path::new("pbrt.png").exists());
+    assert!(film.get_rgb(1.0).iter().all(|v| *v == 0.0));
+}
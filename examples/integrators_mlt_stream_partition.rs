@@ -0,0 +1,62 @@
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::SamplerTrait;
+use pbrt::integrators::mlt::MLTSampler;
+
+const STREAM_COUNT: i32 = 2;
+const TOTAL_DIMENSIONS: i32 = 16;
+
+/// Draws all eight dimensions of *stream* (as 1D and 2D samples).
+fn draw_stream(sampler: &mut MLTSampler, stream: i32) -> Vec<Float> {
+    sampler.start_stream(stream);
+    let mut values: Vec<Float> = Vec::new();
+    for _i in 0..2 {
+        values.push(sampler.get_1d());
+        values.push(sampler.get_1d());
+        let u: Point2f = sampler.get_2d();
+        values.push(u.x);
+        values.push(u.y);
+    }
+    values
+}
+
+fn main() {
+    let mut sampler: MLTSampler = MLTSampler::new(16, 0, 0.01, 0.3, 3);
+    sampler.partition_streams(STREAM_COUNT, TOTAL_DIMENSIONS);
+    sampler.start_pixel(Point2i::default());
+    for iteration in 0..4 {
+        sampler.start_iteration();
+        // draw stream 1 first, the order shouldn't matter
+        let stream_1: Vec<Float> = draw_stream(&mut sampler, 1);
+        let stream_0: Vec<Float> = draw_stream(&mut sampler, 0);
+        let dimensions: usize = (TOTAL_DIMENSIONS / STREAM_COUNT) as usize;
+        assert_eq!(stream_0.len(), dimensions);
+        assert_eq!(stream_1.len(), dimensions);
+        // each stream lives in its own contiguous range ...
+        assert_eq!(sampler.x.len(), TOTAL_DIMENSIONS as usize);
+        for (d, v) in stream_0.iter().enumerate() {
+            assert_eq!(*v, sampler.x[d].value);
+        }
+        for (d, v) in stream_1.iter().enumerate() {
+            assert_eq!(*v, sampler.x[dimensions + d].value);
+        }
+        // ... so no underlying sample value is shared
+        for v in &stream_0 {
+            assert!(
+                !stream_1.contains(v),
+                "iteration {}: {} drawn from both streams",
+                iteration,
+                v
+            );
+        }
+        sampler.accept();
+    }
+    // drawing past the end of a stream is an error
+    sampler.start_stream(0);
+    for _d in 0..(TOTAL_DIMENSIONS / STREAM_COUNT) {
+        sampler.get_1d();
+    }
+    let overflow = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| sampler.get_1d()));
+    assert!(overflow.is_err());
+    println!("streams never share primary samples");
+}
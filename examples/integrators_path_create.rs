@@ -0,0 +1,86 @@
+mod common;
+
+use common::scene::{camera, lit_sphere, render};
+use pbrt::core::geometry::Point2i;
+use pbrt::core::integrator::{Integrator, SamplerIntegrator};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::integrators::path::PathIntegrator;
+use pbrt::samplers::random::RandomSampler;
+
+const RESOLUTION: i32 = 16;
+
+fn main() {
+    // defaults
+    let defaults: Box<Integrator> = PathIntegrator::create(
+        &ParamSet::default(),
+        camera(RESOLUTION),
+        RandomSampler::create(&ParamSet::default()),
+    );
+    if let Integrator::Sampler(SamplerIntegrator::Path(path)) = &*defaults {
+        assert_eq!(path.max_depth, 5);
+        assert_eq!(path.rr_threshold, 1.0);
+        assert_eq!(path.light_sample_strategy, "spatial");
+    } else {
+        panic!("PathIntegrator::create() didn't create a path tracer");
+    }
+    // only render the upper left quarter of the image
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("maxdepth"), 3);
+    params.add_float(String::from("rrthreshold"), 0.5);
+    params.add_ints(String::from("pixelbounds"), vec![0, 8, 0, 8]);
+    let path: Box<Integrator> = PathIntegrator::create(
+        &params,
+        camera(RESOLUTION),
+        RandomSampler::create(&ParamSet::default()),
+    );
+    if let Integrator::Sampler(SamplerIntegrator::Path(path)) = &*path {
+        assert_eq!(path.max_depth, 3);
+        assert_eq!(path.rr_threshold, 0.5);
+        let bounds = path.get_pixel_bounds();
+        assert_eq!(bounds.p_min, Point2i { x: 0, y: 0 });
+        assert_eq!(bounds.p_max, Point2i { x: 8, y: 8 });
+    } else {
+        unreachable!()
+    }
+    // a wrong number of values keeps the full image
+    let mut wrong: ParamSet = ParamSet::default();
+    wrong.add_ints(String::from("pixelbounds"), vec![0, 8, 0]);
+    let full: Box<Integrator> = PathIntegrator::create(
+        &wrong,
+        camera(RESOLUTION),
+        RandomSampler::create(&ParamSet::default()),
+    );
+    if let Integrator::Sampler(full) = &*full {
+        let bounds = full.get_pixel_bounds();
+        assert_eq!(bounds.p_min, Point2i { x: 0, y: 0 });
+        assert_eq!(
+            bounds.p_max,
+            Point2i {
+                x: RESOLUTION,
+                y: RESOLUTION
+            }
+        );
+    } else {
+        unreachable!()
+    }
+    let (stats, rgb) = render("path", &params, &lit_sphere(), camera(RESOLUTION), 4, 1);
+    assert_eq!(stats.total_samples, 8 * 8 * 4);
+    let lit: Float = rgb.iter().sum();
+    assert!(lit > 0.0, "image is black");
+    for y in 0..RESOLUTION {
+        for x in 0..RESOLUTION {
+            if x >= 8 || y >= 8 {
+                let i: usize = 3 * (y * RESOLUTION + x) as usize;
+                assert_eq!(rgb[i] + rgb[i + 1] + rgb[i + 2], 0.0, "({}, {})", x, y);
+            }
+        }
+    }
+    // without bounces only emitted light is visible (the point light
+    // isn't a surface)
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("maxdepth"), 0);
+    let (_stats, rgb) = render("path", &params, &lit_sphere(), camera(RESOLUTION), 4, 1);
+    assert_eq!(rgb.iter().sum::<Float>(), 0.0);
+    println!("sum of the upper left quarter: {}", lit);
+}
@@ -0,0 +1,37 @@
+use pbrt::core::api::{
+    pbrt_camera, pbrt_cleanup, pbrt_film, pbrt_init, pbrt_integrator, pbrt_look_at, pbrt_sampler,
+    pbrt_shape, pbrt_world_begin,
+};
+use pbrt::core::integrator::RenderStats;
+use pbrt::core::paramset::ParamSet;
+
+fn named(name: &str) -> ParamSet {
+    let mut params: ParamSet = ParamSet::default();
+    params.name = String::from(name);
+    params
+}
+
+fn main() {
+    let (width, height, spp): (i32, i32, i32) = (12, 8, 4);
+    let (mut api_state, mut bsdf_state) = pbrt_init(1_u8);
+    pbrt_look_at(&mut api_state, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0);
+    pbrt_camera(&mut api_state, named("perspective"));
+    let mut film_params: ParamSet = named("image");
+    film_params.add_int(String::from("xresolution"), width);
+    film_params.add_int(String::from("yresolution"), height);
+    pbrt_film(&mut api_state, film_params);
+    let mut sampler_params: ParamSet = named("random");
+    sampler_params.add_int(String::from("pixelsamples"), spp);
+    pbrt_sampler(&mut api_state, sampler_params);
+    let mut integrator_params: ParamSet = named("ambientocclusion");
+    integrator_params.add_int(String::from("nsamples"), 4);
+    pbrt_integrator(&mut api_state, integrator_params);
+    pbrt_world_begin(&mut api_state);
+    pbrt_shape(&mut api_state, &mut bsdf_state, named("sphere"));
+    let stats: RenderStats = pbrt_cleanup(&api_state);
+    println!("{:?}", stats);
+    assert_eq!(stats.total_samples, (spp * width * height) as u64);
+    // at least one camera ray per sample, plus occlusion rays
+    assert!(stats.rays_traced >= stats.total_samples);
+    assert_eq!(stats.dropped_nan_samples, 0);
+}
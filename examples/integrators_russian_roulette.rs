@@ -0,0 +1,30 @@
+use pbrt::core::integrator::russian_roulette;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::rng::Rng;
+
+fn main() {
+    // paths within the minimum number of bounces always survive
+    let dim: Spectrum = Spectrum::rgb(0.1, 0.2, 0.05);
+    assert_eq!(russian_roulette(dim, 0.0, 3, 3), Some(1.0));
+    // bright paths are terminated with probability 0.05 only
+    let bright: Spectrum = Spectrum::new(2.0);
+    assert_eq!(russian_roulette(bright, 0.01, 3, 4), None);
+    assert!(russian_roulette(bright, 0.5, 3, 4).is_some());
+    // the expected contribution with RR equals the one without
+    let mut rng: Rng = Rng::new();
+    let n_samples: usize = 1_000_000;
+    for throughput in [dim, Spectrum::rgb(0.5, 0.7, 0.9), bright].iter() {
+        let mut sum: Spectrum = Spectrum::default();
+        for _i in 0..n_samples {
+            if let Some(scale) = russian_roulette(*throughput, rng.uniform_float(), 3, 4) {
+                sum += *throughput * scale;
+            }
+        }
+        let mean: Spectrum = sum / n_samples as Float;
+        for c in 0..3 {
+            let rel_err: Float = (mean.c[c] - throughput.c[c]).abs() / throughput.c[c];
+            assert!(rel_err < 0.02, "RR estimate {:?} != {:?}", mean, throughput);
+        }
+        println!("throughput {:?}: RR estimate {:?}", throughput.c, mean.c);
+    }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::scene::{camera, lit_sphere, render};
+use pbrt::core::integrator::Integrator;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::integrators::sppm::SPPMIntegrator;
+
+const RESOLUTION: i32 = 10;
+
+fn sppm(params: &ParamSet) -> Box<Integrator> {
+    SPPMIntegrator::create(params, camera(RESOLUTION))
+}
+
+fn main() {
+    // defaults (one photon per pixel and iteration)
+    if let Integrator::SPPM(sppm) = &*sppm(&ParamSet::default()) {
+        assert_eq!(sppm.n_iterations, 64);
+        assert_eq!(sppm.photons_per_iteration, RESOLUTION * RESOLUTION);
+        assert_eq!(sppm.max_depth, 5);
+        assert_eq!(sppm.initial_search_radius, 1.0);
+        assert_eq!(sppm.write_frequency, std::i32::MAX);
+    } else {
+        panic!("SPPMIntegrator::create() didn't create an SPPM integrator");
+    }
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("numiterations"), 3);
+    params.add_int(String::from("photonsperiteration"), 7);
+    params.add_int(String::from("imagewritefrequency"), 2);
+    params.add_int(String::from("maxdepth"), 2);
+    params.add_float(String::from("radius"), 0.25);
+    if let Integrator::SPPM(sppm) = &*sppm(&params) {
+        assert_eq!(sppm.n_iterations, 3);
+        assert_eq!(sppm.photons_per_iteration, 7);
+        assert_eq!(sppm.max_depth, 2);
+        assert_eq!(sppm.initial_search_radius, 0.25);
+        assert_eq!(sppm.write_frequency, 2);
+    } else {
+        unreachable!()
+    }
+    // invalid values fall back to the defaults
+    let mut invalid: ParamSet = ParamSet::default();
+    invalid.add_int(String::from("imagewritefrequency"), 0);
+    invalid.add_float(String::from("radius"), -1.0);
+    if let Integrator::SPPM(sppm) = &*sppm(&invalid) {
+        assert_eq!(sppm.initial_search_radius, 1.0);
+        assert_eq!(sppm.write_frequency, std::i32::MAX);
+    } else {
+        unreachable!()
+    }
+    // pixels and photons which don't split evenly across the threads
+    // (100 pixels and 7 photons on 3 threads)
+    let (stats, rgb) = render("sppm", &params, &lit_sphere(), camera(RESOLUTION), 4, 3);
+    assert_eq!(stats.total_samples, 3 * 100);
+    let lit: Float = rgb.iter().sum();
+    assert!(lit > 0.0, "image is black");
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::scene::{camera, render, sphere};
+use pbrt::accelerators::bvh::BVHAccel;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::primitive::Primitive;
+use pbrt::core::scene::Scene;
+
+// several tiles of (at least) 16x16 pixels, partially covered at the
+// border
+const RESOLUTION: i32 = 40;
+
+/// Renders a noisy image (ambient occlusion with a maximum distance
+/// within a large sphere) with *threads* threads.
+fn occlusion(scene: &Scene, threads: u8) -> Vec<Float> {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("nsamples"), 2);
+    params.add_bool(String::from("cossample"), false);
+    params.add_float(String::from("maxdistance"), 10.0);
+    let (stats, rgb) = render(
+        "ambientocclusion",
+        &params,
+        scene,
+        camera(RESOLUTION),
+        2,
+        threads,
+    );
+    assert_eq!(stats.total_samples, (2 * RESOLUTION * RESOLUTION) as u64);
+    rgb
+}
+
+fn main() {
+    let bvh: Arc<Primitive> = Arc::new(BVHAccel::create(
+        vec![sphere(3.0, 1.0, None), sphere(0.0, 10.0, None)],
+        &ParamSet::default(),
+    ));
+    let scene: Scene = Scene::new(bvh, Vec::new());
+    let reference: Vec<Float> = occlusion(&scene, 1);
+    // the image is noisy, so it depends on the random numbers ...
+    assert!(
+        reference.iter().any(|v| *v > 0.0 && *v < 1.0),
+        "expected partial occlusion"
+    );
+    // ... which are seeded per tile, not per thread, so the number of
+    // threads (and the order in which tiles finish) doesn't matter
+    for &threads in &[2_u8, 3_u8, 7_u8] {
+        let rgb: Vec<Float> = occlusion(&scene, threads);
+        assert_eq!(rgb, reference, "{} threads", threads);
+    }
+}
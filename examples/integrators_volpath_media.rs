@@ -0,0 +1,159 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::scene::{film, matte, render};
+use pbrt::cameras::perspective::PerspectiveCamera;
+use pbrt::core::camera::Camera;
+use pbrt::core::integrator::{Integrator, SamplerIntegrator};
+use pbrt::core::light::Light;
+use pbrt::core::medium::{Medium, MediumInterface};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::{AnimatedTransform, Transform};
+use pbrt::integrators::volpath::VolPathIntegrator;
+use pbrt::lights::diffuse::DiffuseAreaLight;
+use pbrt::lights::infinite::InfiniteAreaLight;
+use pbrt::media::homogeneous::HomogeneousMedium;
+use pbrt::samplers::random::RandomSampler;
+use pbrt::shapes::sphere::Sphere;
+
+const RESOLUTION: i32 = 16;
+const RADIUS: Float = 2.0;
+
+fn fog(sigma_a: Float, sigma_s: Float) -> Arc<Medium> {
+    Arc::new(Medium::Homogeneous(HomogeneousMedium::new(
+        &Spectrum::new(sigma_a),
+        &Spectrum::new(sigma_s),
+        0.0,
+    )))
+}
+
+/// Perspective camera at the origin, inside of *medium*.
+fn camera(medium: Option<Arc<Medium>>) -> Arc<Camera> {
+    PerspectiveCamera::create(
+        &ParamSet::default(),
+        AnimatedTransform::new(&Transform::default(), 0.0, &Transform::default(), 1.0),
+        film(RESOLUTION, ParamSet::default()),
+        medium,
+    )
+}
+
+/// Sphere around the camera, filled with *medium*.
+fn sphere(medium: &Arc<Medium>) -> (Arc<Shape>, Arc<MediumInterface>) {
+    let shape: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        Transform::default(),
+        Transform::default(),
+        false,
+        RADIUS,
+        -RADIUS,
+        RADIUS,
+        360.0,
+    )));
+    let medium_interface: Arc<MediumInterface> =
+        Arc::new(MediumInterface::new(Some(medium.clone()), None));
+    (shape, medium_interface)
+}
+
+/// A black sphere emitting *Le = 1* towards the camera.
+fn emitter_scene(medium: &Arc<Medium>) -> Scene {
+    let (shape, medium_interface) = sphere(medium);
+    let light: Arc<Light> = Arc::new(Light::DiffuseArea(Box::new(DiffuseAreaLight::new(
+        &Transform::default(),
+        &medium_interface,
+        &Spectrum::new(1.0),
+        1,
+        shape.clone(),
+        true,
+    ))));
+    let primitive: Arc<Primitive> =
+        Arc::new(Primitive::Geometric(Box::new(GeometricPrimitive::new(
+            shape,
+            Some(matte(0.0)),
+            Some(light.clone()),
+            Some(medium_interface),
+        ))));
+    Scene::new(primitive, vec![light])
+}
+
+/// An invisible medium boundary, lit by an environment with *Le = 1*.
+fn environment_scene(medium: &Arc<Medium>) -> Scene {
+    let (shape, medium_interface) = sphere(medium);
+    let primitive: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(shape, None, None, Some(medium_interface)),
+    )));
+    let light: Arc<Light> = Arc::new(Light::InfiniteArea(Box::new(InfiniteAreaLight::new(
+        &Transform::default(),
+        &Spectrum::new(1.0),
+        1,
+        String::new(),
+    ))));
+    Scene::new(primitive, vec![light])
+}
+
+/// Renders *scene* with a volumetric path tracer and returns the
+/// average pixel value.
+fn average(scene: &Scene, medium: &Arc<Medium>) -> Float {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("maxdepth"), 100);
+    let (_stats, rgb) = render(
+        "volpath",
+        &params,
+        scene,
+        camera(Some(medium.clone())),
+        16,
+        1,
+    );
+    rgb.iter().sum::<Float>() / rgb.len() as Float
+}
+
+fn main() {
+    // defaults
+    let defaults: Box<Integrator> = VolPathIntegrator::create(
+        &ParamSet::default(),
+        camera(None),
+        RandomSampler::create(&ParamSet::default()),
+    );
+    if let Integrator::Sampler(SamplerIntegrator::VolPath(volpath)) = &*defaults {
+        assert_eq!(volpath.max_depth, 5);
+        assert_eq!(volpath.rr_threshold, 1.0);
+    } else {
+        panic!("VolPathIntegrator::create() didn't create a volumetric path tracer");
+    }
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("maxdepth"), 7);
+    params.add_float(String::from("rrthreshold"), 0.25);
+    let volpath: Box<Integrator> = VolPathIntegrator::create(
+        &params,
+        camera(None),
+        RandomSampler::create(&ParamSet::default()),
+    );
+    if let Integrator::Sampler(SamplerIntegrator::VolPath(volpath)) = &*volpath {
+        assert_eq!(volpath.max_depth, 7);
+        assert_eq!(volpath.rr_threshold, 0.25);
+    } else {
+        unreachable!()
+    }
+    // purely absorbing fog: only the transmitted part of the emitted
+    // light reaches the camera
+    let absorbing: Arc<Medium> = fog(1.0, 0.0);
+    let value: Float = average(&emitter_scene(&absorbing), &absorbing);
+    let expected: Float = (-RADIUS).exp();
+    println!("absorbing fog: {} (expected {})", value, expected);
+    assert!((value - expected).abs() < 0.02, "{}", value);
+    // furnace tests: a camera inside of non-absorbing fog, surrounded
+    // by uniform emission, sees exactly the emitted radiance, no
+    // matter how often light scatters ...
+    let scattering: Arc<Medium> = fog(0.0, 1.0);
+    let value: Float = average(&emitter_scene(&scattering), &scattering);
+    println!("emitting sphere: {} (expected 1)", value);
+    assert!((value - 1.0).abs() < 0.05, "{}", value);
+    // ... and the same is true for light from the environment, which
+    // passes an invisible medium boundary
+    let value: Float = average(&environment_scene(&scattering), &scattering);
+    println!("environment: {} (expected 1)", value);
+    assert!((value - 1.0).abs() < 0.05, "{}", value);
+}
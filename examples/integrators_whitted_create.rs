@@ -0,0 +1,128 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::scene::{camera, matte, render, sphere, sphere_shape};
+use pbrt::core::geometry::Point2i;
+use pbrt::core::integrator::{Integrator, SamplerIntegrator};
+use pbrt::core::light::Light;
+use pbrt::core::material::Material;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::integrators::whitted::WhittedIntegrator;
+use pbrt::lights::diffuse::DiffuseAreaLight;
+use pbrt::lights::infinite::InfiniteAreaLight;
+use pbrt::materials::mirror::MirrorMaterial;
+use pbrt::samplers::random::RandomSampler;
+use pbrt::textures::constant::ConstantTexture;
+
+const RESOLUTION: i32 = 16;
+
+/// A mirror sphere under a constant sky of radiance 0.5.
+fn mirror_scene() -> Scene {
+    let kr = Arc::new(ConstantTexture::new(Spectrum::new(1.0)));
+    let mirror: Arc<Material> = Arc::new(Material::Mirror(Box::new(MirrorMaterial::new(kr, None))));
+    let primitive: Arc<Primitive> = sphere(3.0, 1.5, Some(mirror));
+    let sky: Arc<Light> = Arc::new(Light::InfiniteArea(Box::new(InfiniteAreaLight::new(
+        &Transform::default(),
+        &Spectrum::new(0.5),
+        1,
+        String::from(""),
+    ))));
+    Scene::new(primitive, vec![sky])
+}
+
+/// A black sphere emitting a radiance of 2.
+fn emitter_scene() -> Scene {
+    let shape: Arc<Shape> = sphere_shape(3.0, 1.5);
+    let light: Arc<Light> = Arc::new(Light::DiffuseArea(Box::new(DiffuseAreaLight::new(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::new(2.0),
+        1,
+        shape.clone(),
+        false,
+    ))));
+    let primitive: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(shape, Some(matte(0.0)), Some(light.clone()), None),
+    )));
+    Scene::new(primitive, vec![light])
+}
+
+/// Renders *scene* with *params*, returns the red channel of all
+/// pixels.
+fn red_channel(params: &ParamSet, scene: &Scene) -> Vec<Float> {
+    let (_stats, rgb) = render("whitted", params, scene, camera(RESOLUTION), 4, 1);
+    rgb.iter().step_by(3).cloned().collect()
+}
+
+fn pixel(red: &[Float], x: i32, y: i32) -> Float {
+    red[(y * RESOLUTION + x) as usize]
+}
+
+fn main() {
+    // create() reads "maxdepth" and "pixelbounds"
+    let whitted: Box<Integrator> = WhittedIntegrator::create(
+        &ParamSet::default(),
+        camera(RESOLUTION),
+        RandomSampler::create(&ParamSet::default()),
+    );
+    if let Integrator::Sampler(SamplerIntegrator::Whitted(whitted)) = &*whitted {
+        assert_eq!(whitted.max_depth, 5);
+    } else {
+        panic!("WhittedIntegrator::create() didn't create a Whitted integrator");
+    }
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("maxdepth"), 1);
+    params.add_ints(String::from("pixelbounds"), vec![2, 6, 0, 4]);
+    let whitted: Box<Integrator> = WhittedIntegrator::create(
+        &params,
+        camera(RESOLUTION),
+        RandomSampler::create(&ParamSet::default()),
+    );
+    if let Integrator::Sampler(SamplerIntegrator::Whitted(whitted)) = &*whitted {
+        assert_eq!(whitted.max_depth, 1);
+        let bounds = whitted.get_pixel_bounds();
+        assert_eq!(bounds.p_min, Point2i { x: 2, y: 0 });
+        assert_eq!(bounds.p_max, Point2i { x: 6, y: 4 });
+    } else {
+        unreachable!()
+    }
+    // escaping rays see the sky, a perfect mirror reflects it ...
+    let red: Vec<Float> = red_channel(&ParamSet::default(), &mirror_scene());
+    let c: i32 = RESOLUTION / 2;
+    assert!(
+        (pixel(&red, 0, 0) - 0.5).abs() < 1e-3,
+        "{}",
+        pixel(&red, 0, 0)
+    );
+    assert!(
+        (pixel(&red, c, c) - 0.5).abs() < 1e-3,
+        "{}",
+        pixel(&red, c, c)
+    );
+    // ... but only if the depth allows a bounce, the mirror isn't lit
+    // directly and its BSDF isn't sampled otherwise
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("maxdepth"), 1);
+    let red: Vec<Float> = red_channel(&params, &mirror_scene());
+    assert!(
+        (pixel(&red, 0, 0) - 0.5).abs() < 1e-3,
+        "{}",
+        pixel(&red, 0, 0)
+    );
+    assert_eq!(pixel(&red, c, c), 0.0);
+    // emissive surfaces add their emitted radiance
+    let red: Vec<Float> = red_channel(&ParamSet::default(), &emitter_scene());
+    assert!(
+        (pixel(&red, c, c) - 2.0).abs() < 1e-3,
+        "{}",
+        pixel(&red, c, c)
+    );
+    assert_eq!(pixel(&red, 0, 0), 0.0);
+}
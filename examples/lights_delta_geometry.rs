@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Point3f, Vector3f};
+use pbrt::core::light::{DeltaGeometry, Light};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::pbrt::Spectrum;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::lights::diffuse::DiffuseAreaLight;
+use pbrt::lights::distant::DistantLight;
+use pbrt::lights::point::PointLight;
+use pbrt::shapes::disk::Disk;
+
+fn main() {
+    let light_to_world: Transform = Transform::translate(&Vector3f {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    });
+    // point light
+    let point: Light = Light::Point(Box::new(PointLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+    )));
+    let p_light: Point3f = Point3f {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    };
+    match point.delta_geometry() {
+        Some(DeltaGeometry::Position(p)) => {
+            assert!(p.x == p_light.x && p.y == p_light.y && p.z == p_light.z)
+        }
+        other => panic!("expected position, got {:?}", other),
+    }
+    // (hard) distant light
+    let w_light: Vector3f = Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    };
+    let distant: Light = Light::Distant(Box::new(DistantLight::new(
+        &Transform::default(),
+        &Spectrum::new(1.0),
+        &w_light,
+        0.0,
+    )));
+    match distant.delta_geometry() {
+        Some(DeltaGeometry::Direction(w)) => {
+            assert!(w.x == w_light.x && w.y == w_light.y && w.z == w_light.z)
+        }
+        other => panic!("expected direction, got {:?}", other),
+    }
+    // area light
+    let shape: Arc<Shape> = Arc::new(Shape::Dsk(Disk::new(
+        light_to_world,
+        Transform::inverse(&light_to_world),
+        false,
+        0.0,
+        1.0,
+        0.0,
+        360.0,
+    )));
+    let area: Light = Light::DiffuseArea(Box::new(DiffuseAreaLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+        1,
+        shape,
+        false,
+    )));
+    assert!(area.delta_geometry().is_none());
+    println!("delta geometry OK");
+}
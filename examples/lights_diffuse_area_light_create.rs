@@ -0,0 +1,143 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::{InteractionCommon, SurfaceInteraction};
+use pbrt::core::light::Light;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::rng::Rng;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::lights::diffuse::DiffuseAreaLight;
+use pbrt::shapes::disk::Disk;
+
+/// Unit disk at the origin, its normal points up (or down if
+/// *reverse_orientation* is set).
+fn disk(reverse_orientation: bool) -> Arc<Shape> {
+    Arc::new(Shape::Dsk(Disk::new(
+        Transform::default(),
+        Transform::default(),
+        reverse_orientation,
+        0.0,
+        1.0,
+        0.0,
+        360.0,
+    )))
+}
+
+fn area_light(params: &ParamSet, shape: Arc<Shape>) -> Arc<Light> {
+    DiffuseAreaLight::create(
+        params,
+        &Transform::default(),
+        &MediumInterface::default(),
+        shape,
+    )
+}
+
+fn point(z: Float) -> InteractionCommon {
+    let mut it: InteractionCommon = InteractionCommon::default();
+    it.p = Point3f { x: 0.0, y: 0.0, z };
+    it
+}
+
+/// Total radiance of *n* light samples, taken from *p*.
+fn sampled_radiance(light: &Light, p: &InteractionCommon, rng: &mut Rng) -> Float {
+    let mut sum: Float = 0.0;
+    for _ in 0..100 {
+        let u: Point2f = Point2f {
+            x: rng.uniform_float(),
+            y: rng.uniform_float(),
+        };
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let (li, _vis) = light.sample_li(p, u, &mut wi, &mut pdf);
+        assert!(pdf > 0.0);
+        sum += li.c[0];
+    }
+    sum
+}
+
+fn main() {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("L"), Spectrum::new(2.0));
+    params.add_rgb_spectrum(String::from("scale"), Spectrum::new(0.5));
+    params.add_int(String::from("samples"), 4);
+    let light: Arc<Light> = area_light(&params, disk(false));
+    if let Light::DiffuseArea(ref diffuse) = *light {
+        assert_eq!(diffuse.l_emit.c, [1.0; 3]);
+        assert_eq!(diffuse.n_samples, 4);
+        assert!(!diffuse.two_sided);
+        // power is emitted into one hemisphere of directions ...
+        assert!((diffuse.power().c[0] - PI * PI).abs() < 1e-4);
+        // ... and only leaves the front side
+        let mut it: InteractionCommon = point(0.0);
+        it.n = Normal3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+        let up: Vector3f = Vector3f {
+            x: 0.3,
+            y: 0.0,
+            z: 0.9,
+        };
+        assert_eq!(diffuse.l(&it, &up).c[0], 1.0);
+        assert_eq!(diffuse.l(&it, &-up).c[0], 0.0);
+        // seen from above, the disk covers a solid angle of pi / 4
+        // (for the direction straight down)
+        let mut iref: SurfaceInteraction = SurfaceInteraction::default();
+        iref.common.p = Point3f {
+            x: 0.0,
+            y: 0.0,
+            z: 2.0,
+        };
+        let down: Vector3f = Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        };
+        let pdf: Float = diffuse.pdf_li(&iref, down);
+        assert!((pdf - 4.0 / PI).abs() < 1e-4, "{}", pdf);
+    } else {
+        panic!("DiffuseAreaLight::create() didn't create an area light");
+    }
+    // light samples see the emitting side only
+    let mut rng: Rng = Rng::new();
+    assert_eq!(sampled_radiance(&light, &point(2.0), &mut rng), 100.0);
+    assert_eq!(sampled_radiance(&light, &point(-2.0), &mut rng), 0.0);
+    // two-sided lights emit twice the power, into both directions
+    let mut params: ParamSet = ParamSet::default();
+    params.add_bool(String::from("twosided"), true);
+    let two_sided: Arc<Light> = area_light(&params, disk(false));
+    assert!((two_sided.power().c[0] - 2.0 * PI * PI).abs() < 1e-4);
+    assert_eq!(sampled_radiance(&two_sided, &point(2.0), &mut rng), 100.0);
+    assert_eq!(sampled_radiance(&two_sided, &point(-2.0), &mut rng), 100.0);
+    // flipping the orientation moves the emitting side, for light
+    // samples as well as for rays hitting the shape
+    let flipped: Arc<Light> = area_light(&ParamSet::default(), disk(true));
+    assert_eq!(sampled_radiance(&flipped, &point(2.0), &mut rng), 0.0);
+    assert_eq!(sampled_radiance(&flipped, &point(-2.0), &mut rng), 100.0);
+    if let Light::DiffuseArea(ref diffuse) = *flipped {
+        let up: Vector3f = Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+        let below: Point3f = Point3f {
+            x: 0.1,
+            y: 0.2,
+            z: -2.0,
+        };
+        let above: Point3f = Point3f {
+            x: 0.1,
+            y: 0.2,
+            z: 2.0,
+        };
+        assert_eq!(diffuse.li_toward(below, up).c[0], 1.0);
+        assert_eq!(diffuse.li_toward(above, -up).c[0], 0.0);
+    } else {
+        unreachable!()
+    }
+}
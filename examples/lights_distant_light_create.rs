@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{vec3_dot_vec3, Normal3f, Point2f, Point3f, Ray, Vector3f};
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::light::{Light, LightFlags};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::scene::Scene;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::lights::distant::DistantLight;
+use pbrt::shapes::sphere::Sphere;
+
+fn main() {
+    // defaults: the light shines down the positive z axis
+    let default: Arc<Light> = DistantLight::create(&ParamSet::default(), &Transform::default());
+    if let Light::Distant(distant) = &*default {
+        assert_eq!(
+            distant.w_light,
+            Vector3f {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0
+            }
+        );
+    } else {
+        panic!("DistantLight::create() didn't create a distant light");
+    }
+    // a sun-like light from above
+    let mut params: ParamSet = ParamSet::default();
+    params.add_point3f(
+        String::from("from"),
+        Point3f {
+            x: 0.0,
+            y: 10.0,
+            z: 0.0,
+        },
+    );
+    params.add_point3f(String::from("to"), Point3f::default());
+    params.add_rgb_spectrum(String::from("L"), Spectrum::new(3.0));
+    let light: Arc<Light> = DistantLight::create(&params, &Transform::default());
+    assert_eq!(light.get_flags(), LightFlags::DeltaDirection as u8);
+    // the scene bounds get picked up by preprocess()
+    let object_to_world: Transform = Transform::translate(&Vector3f {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    });
+    let sphere: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        object_to_world,
+        Transform::inverse(&object_to_world),
+        false,
+        2.0,
+        -2.0,
+        2.0,
+        360.0,
+    )));
+    let primitive: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(sphere, None, None, None),
+    )));
+    let scene: Scene = Scene::new(primitive, vec![light.clone()]);
+    let (world_center, world_radius) = if let Light::Distant(distant) = &*light {
+        (
+            *distant.world_center.read().unwrap(),
+            *distant.world_radius.read().unwrap(),
+        )
+    } else {
+        unreachable!()
+    };
+    assert!(world_radius >= 2.0);
+    assert!((world_center.x - 1.0).abs() < 1.0e-5);
+    let up: Vector3f = Vector3f {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    // sample_li() always returns the light direction with pdf 1
+    let iref: InteractionCommon = InteractionCommon {
+        p: Point3f {
+            x: 1.0,
+            y: 2.0,
+            z: 0.0,
+        },
+        ..Default::default()
+    };
+    for u in &[Point2f { x: 0.1, y: 0.9 }, Point2f { x: 0.7, y: 0.2 }] {
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let (li, vis) = light.sample_li(&iref, *u, &mut wi, &mut pdf);
+        assert_eq!(wi, up);
+        assert_eq!(pdf, 1.0);
+        assert_eq!(li.c[0], 3.0);
+        // the shadow ray leaves the bounding sphere
+        let p1: Point3f = vis.unwrap().p1.p;
+        assert!((p1.y - (2.0 + 2.0 * world_radius)).abs() < 1.0e-4);
+    }
+    // sample_le() starts rays on a disk outside the bounding sphere,
+    // pointing along the light direction
+    for i in 0..16 {
+        let u1: Point2f = Point2f {
+            x: (i as Float + 0.5) / 16.0,
+            y: ((i * 7) % 16) as Float / 16.0,
+        };
+        let mut ray: Ray = Ray::default();
+        let mut n_light: Normal3f = Normal3f::default();
+        let mut pdf_pos: Float = 0.0;
+        let mut pdf_dir: Float = 0.0;
+        let le: Spectrum = light.sample_le(
+            u1,
+            Point2f::default(),
+            0.0,
+            &mut ray,
+            &mut n_light,
+            &mut pdf_pos,
+            &mut pdf_dir,
+        );
+        assert_eq!(le.c[0], 3.0);
+        assert_eq!(ray.d, -up);
+        assert_eq!(pdf_dir, 1.0);
+        assert!(
+            (pdf_pos * std::f32::consts::PI * world_radius * world_radius - 1.0).abs() < 1.0e-4
+        );
+        let offset: Vector3f = ray.o - world_center;
+        let height: Float = vec3_dot_vec3(&offset, &up);
+        assert!((height - world_radius).abs() < 1.0e-3);
+        let lateral: Vector3f = offset - up * height;
+        assert!(lateral.length() <= world_radius * 1.0001);
+    }
+    assert!(scene.world_bound().p_max.y <= 2.0 + 1.0e-4);
+    println!("distant light with a bounding radius of {}", world_radius);
+}
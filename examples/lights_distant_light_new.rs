@@ -19,5 +19,5 @@ fn main() {
     let dir: Vector3f = from - to;
     let light_to_world: Transform = Transform::default();
     let lsc: Spectrum = l * sc;
-    let _distant_light: DistantLight = DistantLight::new(&light_to_world, &lsc, &dir);
+    let _distant_light: DistantLight = DistantLight::new(&light_to_world, &lsc, &dir, 0.0);
 }
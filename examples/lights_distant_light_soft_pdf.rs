@@ -0,0 +1,61 @@
+use pbrt::core::geometry::{vec3_dot_vec3, Point2f, Vector3f};
+use pbrt::core::interaction::{InteractionCommon, SurfaceInteraction};
+use pbrt::core::light::is_delta_light;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::transform::Transform;
+use pbrt::lights::distant::DistantLight;
+
+fn main() {
+    let dir: Vector3f = Vector3f {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    // an ideal distant light stays a delta light ...
+    let sharp: DistantLight =
+        DistantLight::new(&Transform::default(), &Spectrum::new(1.0), &dir, 0.0);
+    assert!(is_delta_light(sharp.get_flags()));
+    // ... a soft one (the sun subtends roughly 0.27 degrees) doesn't
+    let soft: DistantLight =
+        DistantLight::new(&Transform::default(), &Spectrum::new(1.0), &dir, 5.0);
+    assert!(!is_delta_light(soft.get_flags()));
+    let iref: InteractionCommon = InteractionCommon::default();
+    let isect: SurfaceInteraction = SurfaceInteraction::default();
+    let n: usize = 8;
+    for i in 0..n {
+        for j in 0..n {
+            let u: Point2f = Point2f {
+                x: (i as Float + 0.5) / n as Float,
+                y: (j as Float + 0.5) / n as Float,
+            };
+            let mut wi: Vector3f = Vector3f::default();
+            let mut pdf: Float = 0.0;
+            let (li, _vis) = soft.sample_li(&iref, u, &mut wi, &mut pdf);
+            assert!(!li.is_black());
+            assert!(vec3_dot_vec3(&wi, &dir) >= soft.cos_theta_max - 1e-5);
+            // a BSDF sampled direction along wi gets the same density
+            let pdf_li: Float = soft.pdf_li(&isect, wi);
+            assert!(
+                (pdf_li - pdf).abs() <= 1e-4 * pdf,
+                "pdf_li = {} != {}",
+                pdf_li,
+                pdf
+            );
+            // and the same radiance when found without light sampling
+            assert_eq!(soft.li_toward(iref.p, wi), li);
+        }
+    }
+    // outside of the cone there is neither density nor radiance
+    let outside: Vector3f = Vector3f {
+        x: 1.0,
+        y: 1.0,
+        z: 0.0,
+    }
+    .normalize();
+    assert_eq!(soft.pdf_li(&isect, outside), 0.0);
+    assert!(soft.li_toward(iref.p, outside).is_black());
+    println!(
+        "soft distant light: pdf_li matches sample_li pdf ({})",
+        soft.pdf_li(&isect, dir)
+    );
+}
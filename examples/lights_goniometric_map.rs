@@ -0,0 +1,121 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Point2f, Point2i, Point3f, Vector3f};
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::light::{Light, LightFlags};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::mipmap::{ImageWrap, MipMap};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::transform::Transform;
+use pbrt::lights::goniometric::GonioPhotometricLight;
+
+const WIDTH: i32 = 16;
+const HEIGHT: i32 = 8;
+
+/// Light with an image map whose rows (theta) get brighter towards
+/// the bottom, *1 + row*, plus *seam* for the last column (phi).
+fn gradient_light(i: Float, seam: Float) -> GonioPhotometricLight {
+    let mut texels: Vec<Spectrum> = Vec::new();
+    for t in 0..HEIGHT {
+        for s in 0..WIDTH {
+            let extra: Float = if s == WIDTH - 1 { seam } else { 0.0 };
+            texels.push(Spectrum::new(1.0 + t as Float + extra));
+        }
+    }
+    let mipmap: MipMap<Spectrum> = MipMap::new(
+        Point2i {
+            x: WIDTH,
+            y: HEIGHT,
+        },
+        &texels[..],
+        false,
+        8.0,
+        ImageWrap::Repeat,
+    );
+    let mut light: GonioPhotometricLight = GonioPhotometricLight::new_hdr(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::new(i),
+        String::new(),
+    );
+    light.mipmap = Some(Arc::new(mipmap));
+    light
+}
+
+/// Direction (in light space) for the image coordinates *(s, t)*,
+/// theta is measured from the +y axis.
+fn direction(s: Float, t: Float) -> Vector3f {
+    let phi: Float = s * 2.0 * std::f32::consts::PI;
+    let theta: Float = t * std::f32::consts::PI;
+    Vector3f {
+        x: theta.sin() * phi.cos(),
+        y: theta.cos(),
+        z: theta.sin() * phi.sin(),
+    }
+}
+
+fn main() {
+    // without an image the light is isotropic
+    let plain: Arc<Light> = GonioPhotometricLight::create(
+        &ParamSet::default(),
+        &Transform::default(),
+        &MediumInterface::default(),
+    );
+    assert_eq!(plain.get_flags(), LightFlags::DeltaPosition as u8);
+    assert!((plain.power().c[0] as f64 - 4.0 * PI).abs() < 1.0e-4);
+    let light: GonioPhotometricLight = gradient_light(2.0, 0.0);
+    // poles are clamped to the first and last row
+    let up: Vector3f = Vector3f {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    assert!((light.scale(&up).c[0] - 1.0).abs() < 1.0e-4);
+    assert!((light.scale(&-up).c[0] - 8.0).abs() < 1.0e-4);
+    // bilinear filtering: linear in between two texel rows, no bands
+    for k in 0..=8 {
+        let t: Float = (3.5 + k as Float / 8.0) / HEIGHT as Float;
+        let value: Float = light.scale(&direction(0.25, t)).c[0];
+        let expected: Float = 4.0 + k as Float / 8.0;
+        assert!(
+            (value - expected).abs() < 1.0e-3,
+            "{} vs. {}",
+            value,
+            expected
+        );
+    }
+    // phi wraps around between the last and the first column
+    let seam_light: GonioPhotometricLight = gradient_light(2.0, 2.0);
+    let seam: Float = seam_light.scale(&direction(0.0, 0.5 / HEIGHT as Float)).c[0];
+    assert!((seam - 2.0).abs() < 1.0e-3, "{}", seam);
+    // sample_li() is a delta position light
+    let iref: InteractionCommon = InteractionCommon {
+        p: Point3f {
+            x: 0.0,
+            y: -2.0,
+            z: 0.0,
+        },
+        ..Default::default()
+    };
+    let mut wi: Vector3f = Vector3f::default();
+    let mut pdf: Float = 0.0;
+    let (li, vis) = light.sample_li(&iref, Point2f { x: 0.3, y: 0.8 }, &mut wi, &mut pdf);
+    assert_eq!(pdf, 1.0);
+    assert_eq!(wi, up);
+    assert!((li.c[0] - 2.0 * 8.0 / 4.0).abs() < 1.0e-4);
+    assert!(vis.is_some());
+    // power integrates the map over the sphere
+    let mut expected: f64 = 0.0;
+    for t in 0..HEIGHT {
+        let theta0: f64 = t as f64 * PI / HEIGHT as f64;
+        let theta1: f64 = (t + 1) as f64 * PI / HEIGHT as f64;
+        let row: f64 = (1.0 + t as f64) * WIDTH as f64 + 2.0;
+        expected += row * (theta0.cos() - theta1.cos()) * 2.0 * PI / WIDTH as f64;
+    }
+    expected *= 2.0;
+    let power: Float = seam_light.power().c[0];
+    println!("power {} (expected {})", power, expected);
+    assert!((power as f64 - expected).abs() < 1.0e-4 * expected);
+}
@@ -0,0 +1,108 @@
+use std::f32::consts::PI;
+use std::io::Write;
+use std::sync::Arc;
+
+use pbrt::core::geometry::vec3_dot_vec3;
+use pbrt::core::geometry::{Normal3f, Point2f, Ray, Vector3f};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::rng::Rng;
+use pbrt::core::transform::Transform;
+use pbrt::lights::goniometric::{GonioPhotometricLight, LightProfile};
+
+/// Writes a 15 degree *theta,phi,intensity* grid with a single bright
+/// lobe around the light's +x axis (theta = 90, phi = 0 degrees).
+fn write_profile(filename: &std::path::Path) {
+    let mut file = std::fs::File::create(filename).unwrap();
+    writeln!(file, "theta,phi,intensity").unwrap();
+    for it in 0..13 {
+        for ip in 0..24 {
+            let theta: Float = (it * 15) as Float;
+            let phi: Float = (ip * 15) as Float;
+            let (t, p) = (theta.to_radians(), phi.to_radians());
+            // cosine of the angle to the lobe direction
+            let cos_lobe: Float = t.sin() * p.cos();
+            let intensity: Float = 0.1 + 100.0 * cos_lobe.max(0.0).powi(8);
+            writeln!(file, "{},{},{}", theta, phi, intensity).unwrap();
+        }
+    }
+}
+
+fn main() {
+    let filename = std::env::temp_dir().join("pbrt_gonio_profile.csv");
+    write_profile(&filename);
+    let profile: LightProfile = LightProfile::read_csv(filename.to_str().unwrap()).unwrap();
+    assert_eq!(profile.thetas.len(), 13);
+    assert_eq!(profile.phis.len(), 24);
+    // wrap-around in phi ...
+    let near_zero: Float = profile.evaluate(0.5 * PI, (1.0 as Float).to_radians());
+    let near_360: Float = profile.evaluate(0.5 * PI, (359.0 as Float).to_radians());
+    assert!((near_zero - near_360).abs() < 1e-3 * near_zero);
+    assert!(profile.evaluate(0.5 * PI, (352.5 as Float).to_radians()) > 0.5 * near_zero);
+    // ... and no phi dependency at the poles
+    for pole in &[0.0 as Float, PI] {
+        let i0: Float = profile.evaluate(*pole, 0.0);
+        assert!((profile.evaluate(*pole, 2.0) - i0).abs() < 1e-6);
+    }
+    // emitted rays are importance sampled toward the lobe (+x in world space)
+    let light: GonioPhotometricLight = GonioPhotometricLight::new_profile(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+        Arc::new(profile),
+    );
+    let mut rng: Rng = Rng::new();
+    let n_rays: usize = 10_000;
+    let mut n_lobe: usize = 0;
+    for _i in 0..n_rays {
+        let u1: Point2f = Point2f {
+            x: rng.uniform_float(),
+            y: rng.uniform_float(),
+        };
+        let mut ray: Ray = Ray::default();
+        let mut n_light: Normal3f = Normal3f::default();
+        let mut pdf_pos: Float = 0.0;
+        let mut pdf_dir: Float = 0.0;
+        let le: Spectrum = light.sample_le(
+            u1,
+            Point2f::default(),
+            0.0,
+            &mut ray,
+            &mut n_light,
+            &mut pdf_pos,
+            &mut pdf_dir,
+        );
+        if pdf_dir == 0.0 {
+            continue;
+        }
+        assert!(!le.is_black());
+        // the returned density matches pdf_le()
+        let (mut pos, mut dir): (Float, Float) = (0.0, 0.0);
+        light.pdf_le(&ray, &n_light, &mut pos, &mut dir);
+        assert!(
+            (dir - pdf_dir).abs() <= 1e-3 * pdf_dir,
+            "{} != {}",
+            dir,
+            pdf_dir
+        );
+        let lobe: Vector3f = Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        if vec3_dot_vec3(&ray.d, &lobe) > 0.8 {
+            n_lobe += 1;
+        }
+    }
+    // uniform sampling would hit the lobe with 10% of the rays only
+    let fraction: Float = n_lobe as Float / n_rays as Float;
+    assert!(
+        fraction > 0.6,
+        "only {} of the rays toward the lobe",
+        fraction
+    );
+    println!(
+        "{:.1}% of the emitted rays toward the lobe",
+        100.0 * fraction
+    );
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::scene::{camera, matte, point_light, render, sphere, sphere_shape};
+use pbrt::core::geometry::{Point2f, Point3f, Vector3f};
+use pbrt::core::integrator::RenderStats;
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::light::Light;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::{GeometricPrimitive, Primitive};
+use pbrt::core::scene::{Scene, MAX_WORLD_EXTENT};
+use pbrt::core::transform::Transform;
+use pbrt::lights::distant::DistantLight;
+use pbrt::lights::infinite::InfiniteAreaLight;
+
+/// Point light to the left of (and in front of) the camera.
+fn side_light() -> Arc<Light> {
+    point_light(
+        Vector3f {
+            x: -2.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        10.0,
+    )
+}
+
+fn infinite_light() -> Arc<Light> {
+    Arc::new(Light::InfiniteArea(Box::new(InfiniteAreaLight::new(
+        &Transform::default(),
+        &Spectrum::new(0.5),
+        4,
+        String::new(),
+    ))))
+}
+
+/// Renders a diffuse sphere lit by *lights*, returns the statistics
+/// and the sum of all pixel values.
+fn lit_by(lights: Vec<Arc<Light>>) -> (RenderStats, Float) {
+    let scene: Scene = Scene::new(sphere(4.0, 1.0, Some(matte(0.8))), lights);
+    let (stats, rgb) = render(
+        "directlighting",
+        &ParamSet::default(),
+        &scene,
+        camera(32),
+        16,
+        2,
+    );
+    assert!(rgb.iter().all(|v| v.is_finite()));
+    (stats, rgb.iter().sum())
+}
+
+fn main() {
+    assert!(infinite_light().is_infinite());
+    assert!(!side_light().is_infinite());
+    let distant: Arc<Light> = Arc::new(Light::Distant(Box::new(DistantLight::new(
+        &Transform::default(),
+        &Spectrum::new(1.0),
+        &Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        0.0,
+    ))));
+    assert!(distant.is_infinite());
+    // both lights contribute, no NaNs from distance math
+    let (point_stats, point_sum) = lit_by(vec![side_light()]);
+    let (infinite_stats, infinite_sum) = lit_by(vec![infinite_light()]);
+    let (stats, sum) = lit_by(vec![side_light(), infinite_light()]);
+    for s in &[point_stats, infinite_stats, stats] {
+        assert_eq!(s.dropped_nan_samples, 0);
+    }
+    println!(
+        "point {}, infinite {}, both {}",
+        point_sum, infinite_sum, sum
+    );
+    assert!(point_sum > 0.0 && infinite_sum > 0.0);
+    let expected: Float = point_sum + infinite_sum;
+    assert!((sum - expected).abs() < 0.05 * expected);
+    // unbounded geometry gets a finite world bound, so shadow rays
+    // towards lights at infinity stay finite as well
+    let unbounded: Arc<Primitive> = Arc::new(Primitive::Geometric(Box::new(
+        GeometricPrimitive::new(sphere_shape(0.0, std::f32::INFINITY), None, None, None),
+    )));
+    let scene: Scene = Scene::new(unbounded, vec![distant.clone(), infinite_light()]);
+    let bound = scene.world_bound();
+    assert!(bound.p_min.x >= -MAX_WORLD_EXTENT && bound.p_max.z <= MAX_WORLD_EXTENT);
+    let iref: InteractionCommon = InteractionCommon {
+        p: Point3f::default(),
+        ..Default::default()
+    };
+    for light in &scene.lights {
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let (li, vis) = light.sample_li(&iref, Point2f { x: 0.3, y: 0.6 }, &mut wi, &mut pdf);
+        assert!(!li.has_nans() && pdf.is_finite());
+        let p1: Point3f = vis.unwrap().p1.p;
+        assert!(p1.x.is_finite() && p1.y.is_finite() && p1.z.is_finite());
+    }
+}
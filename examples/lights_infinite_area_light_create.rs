@@ -0,0 +1,165 @@
+use std::f32::consts::PI;
+use std::fs::File;
+use std::sync::Arc;
+
+use image::hdr::HDREncoder;
+use image::Rgb;
+use pbrt::core::geometry::{Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::{InteractionCommon, SurfaceInteraction};
+use pbrt::core::light::Light;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::rng::Rng;
+use pbrt::core::transform::Transform;
+use pbrt::lights::infinite::InfiniteAreaLight;
+
+/// Writes a 16x8 environment map: the upper hemisphere is brighter
+/// than the lower one, and the first half of the *phi* range is
+/// brighter than the second.
+fn write_map(filename: &str) {
+    let (width, height): (usize, usize) = (16, 8);
+    let mut data: Vec<Rgb<f32>> = Vec::with_capacity(width * height);
+    for t in 0..height {
+        for s in 0..width {
+            let mut v: f32 = if t < height / 2 { 4.0 } else { 0.25 };
+            if s < width / 2 {
+                v *= 2.0;
+            }
+            data.push(Rgb([v, v, v]));
+        }
+    }
+    let file: File = File::create(filename).unwrap();
+    HDREncoder::new(file).encode(&data, width, height).unwrap();
+}
+
+/// Direction for the spherical coordinates *theta* and *phi*.
+fn direction(theta: Float, phi: Float) -> Vector3f {
+    Vector3f {
+        x: theta.sin() * phi.cos(),
+        y: theta.sin() * phi.sin(),
+        z: theta.cos(),
+    }
+}
+
+/// Integrates the radiance over all directions (and the irradiance
+/// on a surface facing up) with a fine grid over *theta* and *phi*.
+fn integrate(light: &InfiniteAreaLight) -> (Float, Float) {
+    let p: Point3f = Point3f::default();
+    let (n_theta, n_phi): (usize, usize) = (400, 800);
+    let d_theta: Float = PI / n_theta as Float;
+    let d_phi: Float = 2.0 * PI / n_phi as Float;
+    let mut total: Float = 0.0;
+    let mut irradiance: Float = 0.0;
+    for i in 0..n_theta {
+        let theta: Float = (i as Float + 0.5) * d_theta;
+        for j in 0..n_phi {
+            let phi: Float = (j as Float + 0.5) * d_phi;
+            let wi: Vector3f = direction(theta, phi);
+            let l: Float = light.li_toward(p, wi).c[0] * theta.sin() * d_theta * d_phi;
+            total += l;
+            irradiance += l * wi.z.max(0.0);
+        }
+    }
+    (total, irradiance)
+}
+
+/// Importance sampled estimates of the same two integrals, checking
+/// that *pdf_li()* agrees with the densities returned by the samples.
+fn estimate(light: &InfiniteAreaLight, rng: &mut Rng) -> (Float, Float) {
+    let iref: InteractionCommon = InteractionCommon::default();
+    let isect: SurfaceInteraction = SurfaceInteraction::default();
+    let n_samples: usize = 200_000;
+    let mut total: Float = 0.0;
+    let mut irradiance: Float = 0.0;
+    for i in 0..n_samples {
+        let u: Point2f = Point2f {
+            x: rng.uniform_float(),
+            y: rng.uniform_float(),
+        };
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let (li, _vis) = light.sample_li(&iref, u, &mut wi, &mut pdf);
+        if pdf == 0.0 {
+            continue;
+        }
+        if i % 1000 == 0 {
+            let pdf_li: Float = light.pdf_li(&isect, wi);
+            assert!((pdf_li - pdf).abs() <= 1e-2 * pdf, "{} {}", pdf_li, pdf);
+        }
+        total += li.c[0] / pdf;
+        irradiance += li.c[0] * wi.z.max(0.0) / pdf;
+    }
+    (total / n_samples as Float, irradiance / n_samples as Float)
+}
+
+fn check(light: &InfiniteAreaLight, rng: &mut Rng) {
+    let (total, irradiance) = integrate(light);
+    let (total_mc, irradiance_mc) = estimate(light, rng);
+    println!(
+        "total: {} (expected {}), irradiance: {} (expected {})",
+        total_mc, total, irradiance_mc, irradiance
+    );
+    assert!((total_mc - total).abs() < 0.02 * total);
+    assert!((irradiance_mc - irradiance).abs() < 0.02 * irradiance);
+    // power arrives at the disk of the bounding sphere (radius 1)
+    let power: Float = light.power().c[0];
+    assert!((power - 0.25 * total).abs() < 0.03 * total, "{}", power);
+}
+
+fn main() {
+    let light_to_world: Transform = Transform::rotate_x(30.0);
+    let mut rng: Rng = Rng::new();
+    // constant radiance, without an environment map
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("L"), Spectrum::new(2.0));
+    params.add_rgb_spectrum(String::from("scale"), Spectrum::new(0.5));
+    params.add_int(String::from("samples"), 8);
+    let light: Arc<Light> = InfiniteAreaLight::create(&params, &light_to_world, None);
+    if let Light::InfiniteArea(ref infinite) = *light {
+        assert_eq!(infinite.n_samples, 8);
+        *infinite.world_radius.write().unwrap() = 1.0;
+        let (total, irradiance) = estimate(infinite, &mut rng);
+        assert!((total - 4.0 * PI).abs() < 0.02 * 4.0 * PI, "{}", total);
+        assert!((irradiance - PI).abs() < 0.02 * PI, "{}", irradiance);
+        assert!((infinite.power().c[0] - PI).abs() < 1e-4);
+    } else {
+        panic!("InfiniteAreaLight::create() didn't create an infinite light");
+    }
+    // an environment map, importance sampled
+    let filename: String = std::env::temp_dir()
+        .join("lights_infinite_area_light_create.hdr")
+        .to_str()
+        .unwrap()
+        .to_string();
+    write_map(&filename);
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("mapname"), filename.clone());
+    params.add_int(String::from("nsamples"), 4);
+    let light: Arc<Light> = InfiniteAreaLight::create(&params, &light_to_world, None);
+    if let Light::InfiniteArea(ref infinite) = *light {
+        assert_eq!(infinite.n_samples, 4);
+        assert_eq!(infinite.lmap.width(), 16);
+        assert_eq!(infinite.lmap.height(), 8);
+        *infinite.world_radius.write().unwrap() = 1.0;
+        // brightest straight "up" in light space
+        let up: Vector3f = light_to_world.transform_vector(&direction(0.2, 0.25 * PI));
+        let down: Vector3f = light_to_world.transform_vector(&direction(PI - 0.2, 1.25 * PI));
+        let l_up: Float = infinite.li_toward(Point3f::default(), up).c[0];
+        let l_down: Float = infinite.li_toward(Point3f::default(), down).c[0];
+        assert!((l_up - 8.0).abs() < 1e-3, "{}", l_up);
+        assert!((l_down - 0.25).abs() < 1e-3, "{}", l_down);
+        check(infinite, &mut rng);
+    } else {
+        unreachable!()
+    }
+    let _ = std::fs::remove_file(&filename);
+    // a missing map falls back to constant radiance
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(
+        String::from("mapname"),
+        String::from("/nonexistent/environment.hdr"),
+    );
+    let light: Arc<Light> = InfiniteAreaLight::create(&params, &light_to_world, None);
+    let wi: Vector3f = direction(0.3, 0.6);
+    assert_eq!(light.li_toward(Point3f::default(), wi), Spectrum::new(1.0));
+}
@@ -0,0 +1,66 @@
+use pbrt::core::geometry::{Point3f, Ray, Vector3f};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::pbrt::Spectrum;
+use pbrt::core::transform::Transform;
+use pbrt::lights::infinite::InfiniteAreaLight;
+use pbrt::lights::point::PointLight;
+
+fn main() {
+    let l: Spectrum = Spectrum::new(50.0);
+    let light_to_world: Transform = Transform::rotate_x(30.0);
+    let infinite_light: InfiniteAreaLight =
+        InfiniteAreaLight::new(&light_to_world, &l, 1_i32, String::from(""));
+    let p: Point3f = Point3f {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    };
+    let dirs: [Vector3f; 3] = [
+        Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        Vector3f {
+            x: 1.0,
+            y: -1.0,
+            z: 0.5,
+        }
+        .normalize(),
+        Vector3f {
+            x: -0.3,
+            y: 0.2,
+            z: -0.9,
+        }
+        .normalize(),
+    ];
+    for wi in dirs.iter() {
+        let mut ray: Ray = Ray {
+            o: p,
+            d: *wi,
+            ..Default::default()
+        };
+        let le: Spectrum = infinite_light.le(&mut ray);
+        let li: Spectrum = infinite_light.li_toward(p, *wi);
+        assert_eq!(li, le);
+    }
+    // delta lights only contribute along the exact direction
+    let point_light: PointLight = PointLight::new(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+    );
+    let p: Point3f = Point3f {
+        x: 0.0,
+        y: 0.0,
+        z: -2.0,
+    };
+    let toward: Vector3f = Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    };
+    assert_eq!(point_light.li_toward(p, toward), Spectrum::new(0.25));
+    assert!(point_light.li_toward(p, dirs[1]).is_black());
+    println!("li_toward matches le");
+}
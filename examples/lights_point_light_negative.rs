@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use pbrt::core::film::Film;
+use pbrt::core::geometry::{Bounds2i, Point2f, Point2i, Point3f, Vector3f};
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::transform::Transform;
+use pbrt::filters::boxfilter::BoxFilter;
+use pbrt::lights::point::PointLight;
+
+fn main() {
+    let iref: InteractionCommon = InteractionCommon {
+        p: Point3f::default(),
+        ..Default::default()
+    };
+    let light_to_world: Transform = Transform::translate(&Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    });
+    let mut wi: Vector3f = Vector3f::default();
+    let mut pdf: Float = 0.0;
+    let light: PointLight = PointLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+    );
+    let (lit, _vis) = light.sample_li(&iref, Point2f::default(), &mut wi, &mut pdf);
+    let negative_light: PointLight = PointLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(-0.25),
+    );
+    let (darken, _vis) = negative_light.sample_li(&iref, Point2f::default(), &mut wi, &mut pdf);
+    assert!(darken.y() < 0.0);
+    assert!((lit + darken).y() < lit.y());
+    // accumulate on a 2x1 film: pixel 0 is lit and darkened, pixel 1
+    // only receives the negative light
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), 2);
+    film_params.add_int(String::from("yresolution"), 1);
+    let film: Arc<Film> = Film::create(&film_params, BoxFilter::create(&ParamSet::default()));
+    let bounds: Bounds2i = Bounds2i::new(Point2i { x: 0, y: 0 }, Point2i { x: 2, y: 1 });
+    let mut tile = film.get_film_tile(&bounds);
+    let mut l: Spectrum = lit + darken;
+    tile.add_sample(Point2f { x: 0.5, y: 0.5 }, &mut l, 1.0);
+    let mut l: Spectrum = darken;
+    tile.add_sample(Point2f { x: 1.5, y: 0.5 }, &mut l, 1.0);
+    film.merge_film_tile(&tile);
+    let rgb: Vec<Float> = film.get_rgb(1.0);
+    println!("rgb = {:?}", rgb);
+    assert!((rgb[0] - (lit + darken).y()).abs() < 1.0e-4);
+    assert!(rgb[0] < lit.y());
+    // negative values are clamped on output only
+    assert_eq!(&rgb[3..6], &[0.0, 0.0, 0.0]);
+}
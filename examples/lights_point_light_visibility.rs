@@ -0,0 +1,110 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::scene::{camera, matte, render, sphere};
+use pbrt::core::api::make_accelerator;
+use pbrt::core::geometry::{Point3f, Ray, Vector3f};
+use pbrt::core::light::{light_visibility_from_str, Light, LightVisibility, LIGHT_VISIBILITY_ALL};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::primitive::Primitive;
+use pbrt::core::scene::Scene;
+use pbrt::core::transform::Transform;
+use pbrt::lights::point::PointLight;
+
+fn point_light(visibility: u8) -> PointLight {
+    let mut light: PointLight = PointLight::new(
+        &Transform::translate(&Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: -3.0,
+        }),
+        &MediumInterface::default(),
+        &Spectrum::new(50.0),
+    );
+    light.visibility = visibility;
+    light
+}
+
+/// Renders the sphere in front of the camera, lit by a point light
+/// behind the camera (optionally with an *occluder* in between).
+fn image(occluder: bool, visibility: u8) -> Vec<Float> {
+    let mut primitives: Vec<Arc<Primitive>> = vec![sphere(4.0, 1.0, Some(matte(0.8)))];
+    if occluder {
+        // behind the camera, so camera rays never hit it
+        primitives.push(sphere(-1.5, 0.5, Some(matte(0.8))));
+    }
+    let aggregate: Arc<Primitive> =
+        make_accelerator("bvh", &primitives, &ParamSet::default()).unwrap();
+    let light: Arc<Light> = Arc::new(Light::Point(Box::new(point_light(visibility))));
+    let scene: Scene = Scene::new(aggregate, vec![light]);
+    let (_stats, rgb) = render(
+        "directlighting",
+        &ParamSet::default(),
+        &scene,
+        camera(16),
+        4,
+        1,
+    );
+    rgb
+}
+
+fn main() {
+    let no_shadows: u8 = light_visibility_from_str("camera, diffuse specular");
+    assert_eq!(
+        no_shadows,
+        LIGHT_VISIBILITY_ALL & !(LightVisibility::Shadow as u8)
+    );
+    // typos keep the light visible to all ray types
+    assert_eq!(
+        light_visibility_from_str("camera shaddow"),
+        LIGHT_VISIBILITY_ALL
+    );
+    assert_eq!(
+        point_light(LIGHT_VISIBILITY_ALL).visibility,
+        LIGHT_VISIBILITY_ALL
+    );
+    let unoccluded: Vec<Float> = image(false, LIGHT_VISIBILITY_ALL);
+    let shadowed: Vec<Float> = image(true, LIGHT_VISIBILITY_ALL);
+    let shadowless: Vec<Float> = image(true, no_shadows);
+    let sum = |rgb: &[Float]| -> Float { rgb.iter().sum() };
+    println!(
+        "unoccluded {}, shadowed {}, without shadows {}",
+        sum(&unoccluded),
+        sum(&shadowed),
+        sum(&shadowless)
+    );
+    assert!(sum(&unoccluded) > 0.0);
+    assert_eq!(sum(&shadowed), 0.0);
+    // without shadows the occluder doesn't matter at all
+    for (a, b) in shadowless.iter().zip(unoccluded.iter()) {
+        assert!((a - b).abs() <= 1.0e-5 * (1.0 + b), "{} != {}", a, b);
+    }
+    // a light which doesn't contribute to diffuse reflection leaves
+    // the matte sphere black
+    let not_diffuse: u8 = light_visibility_from_str("camera shadow specular");
+    assert_eq!(sum(&image(false, not_diffuse)), 0.0);
+    // visible bulbs can be hidden from camera rays
+    let mut bulb: PointLight = point_light(LIGHT_VISIBILITY_ALL);
+    bulb.radius = 0.1;
+    let light: Light = Light::Point(Box::new(bulb.clone()));
+    let mut ray: Ray = Ray {
+        o: Point3f::default(),
+        d: Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        },
+        t_max: std::f32::INFINITY,
+        time: 0.0,
+        differential: None,
+        medium: None,
+    };
+    assert!(!light.le_visible(&mut ray, true).is_black());
+    bulb.visibility = light_visibility_from_str("shadow diffuse specular");
+    let hidden: Light = Light::Point(Box::new(bulb));
+    assert!(hidden.le_visible(&mut ray, true).is_black());
+    assert!(!hidden.le_visible(&mut ray, false).is_black());
+}
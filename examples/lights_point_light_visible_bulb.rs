@@ -0,0 +1,86 @@
+use std::f32::consts::PI;
+
+use pbrt::core::api::{
+    pbrt_camera, pbrt_cleanup, pbrt_film, pbrt_init, pbrt_integrator, pbrt_light_source,
+    pbrt_look_at, pbrt_sampler, pbrt_world_begin,
+};
+use pbrt::core::geometry::{Point3f, Ray, Vector3f};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::spectrum::gamma_correct;
+use pbrt::core::transform::Transform;
+use pbrt::lights::point::PointLight;
+
+fn named(name: &str) -> ParamSet {
+    let mut params: ParamSet = ParamSet::default();
+    params.name = String::from(name);
+    params
+}
+
+fn camera_ray(x: Float, t_max: Float) -> Ray {
+    Ray {
+        o: Point3f { x, y: 0.0, z: 5.0 },
+        d: Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        },
+        t_max,
+        time: 0.0,
+        differential: None,
+        medium: None,
+    }
+}
+
+fn main() {
+    let i: Float = 2.0;
+    let mut point_light: PointLight = PointLight::new(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::new(i),
+    );
+    // a delta point light stays invisible
+    assert!(point_light
+        .le(&mut camera_ray(0.0, Float::INFINITY))
+        .is_black());
+    // the bulb's radiance is consistent with the intensity
+    let radius: Float = 0.1;
+    point_light.radius = radius;
+    let expected: Float = i / (PI * radius * radius);
+    let le: Spectrum = point_light.le(&mut camera_ray(0.0, Float::INFINITY));
+    assert!((le.y() - expected).abs() < 1e-3 * expected, "{:?}", le);
+    // missing the bulb, or hitting something in front of it
+    assert!(point_light
+        .le(&mut camera_ray(0.2, Float::INFINITY))
+        .is_black());
+    assert!(point_light.le(&mut camera_ray(0.0, 4.0)).is_black());
+    // render the bulb with the path tracer: it covers the center pixel
+    let (i, radius): (Float, Float) = (0.05, 0.5);
+    let (mut api_state, _bsdf_state) = pbrt_init(1_u8);
+    pbrt_look_at(&mut api_state, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0);
+    let mut camera_params: ParamSet = named("perspective");
+    camera_params.add_float(String::from("fov"), 30.0);
+    pbrt_camera(&mut api_state, camera_params);
+    let mut film_params: ParamSet = named("image");
+    film_params.add_int(String::from("xresolution"), 9);
+    film_params.add_int(String::from("yresolution"), 9);
+    pbrt_film(&mut api_state, film_params);
+    let mut sampler_params: ParamSet = named("random");
+    sampler_params.add_int(String::from("pixelsamples"), 4);
+    pbrt_sampler(&mut api_state, sampler_params);
+    pbrt_integrator(&mut api_state, named("path"));
+    pbrt_world_begin(&mut api_state);
+    let mut light_params: ParamSet = named("point");
+    light_params.add_rgb_spectrum(String::from("I"), Spectrum::new(i));
+    light_params.add_float(String::from("radius"), radius);
+    pbrt_light_source(&mut api_state, light_params);
+    pbrt_cleanup(&api_state);
+    let image = image::open("pbrt.png").unwrap().to_rgb8();
+    let center: u8 = image.get_pixel(4, 4)[0];
+    let corner: u8 = image.get_pixel(0, 0)[0];
+    let expected: Float = 255.0 * gamma_correct(i / (PI * radius * radius));
+    println!("center pixel = {}, expected {}", center, expected);
+    assert!((center as Float - expected).abs() <= 1.0);
+    assert_eq!(corner, 0);
+}
@@ -0,0 +1,54 @@
+use pbrt::core::geometry::{Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::spectrum::WavelengthSampling;
+use pbrt::core::transform::Transform;
+use pbrt::lights::point::PointLight;
+
+fn estimate(light: &PointLight, iref: &InteractionCommon, n: usize) -> [Float; 3] {
+    let mut sum: Spectrum = Spectrum::default();
+    for i in 0..n {
+        let u: Point2f = Point2f {
+            x: (i as Float + 0.5) / n as Float,
+            y: 0.5,
+        };
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let (li, _vis) = light.sample_li(iref, u, &mut wi, &mut pdf);
+        sum += li;
+    }
+    let mut rgb: [Float; 3] = [0.0; 3];
+    (sum / n as Float).to_rgb(&mut rgb);
+    rgb
+}
+
+fn main() {
+    let iref: InteractionCommon = InteractionCommon {
+        p: Point3f {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        },
+        ..Default::default()
+    };
+    let mut light: PointLight = PointLight::new(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::rgb(0.8, 0.5, 0.2),
+    );
+    let n: usize = 1 << 16;
+    light.wavelength_sampling = Some(WavelengthSampling::new("uniform"));
+    let uniform: [Float; 3] = estimate(&light, &iref, n);
+    light.wavelength_sampling = Some(WavelengthSampling::new("ciey"));
+    let cie_y: [Float; 3] = estimate(&light, &iref, n);
+    println!("uniform = {:?}", uniform);
+    println!("ciey    = {:?}", cie_y);
+    for c in 0..3 {
+        assert!(
+            (uniform[c] - cie_y[c]).abs() < 0.01,
+            "strategies disagree in channel {}",
+            c
+        );
+    }
+}
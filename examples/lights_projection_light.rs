@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Point2f, Point2i, Point3f, Vector3f};
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::light::{Light, LightFlags};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::mipmap::{ImageWrap, MipMap};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{radians, Float, Spectrum};
+use pbrt::core::transform::Transform;
+use pbrt::lights::projection::ProjectionLight;
+
+const RES: i32 = 16;
+
+/// Radiance arriving at *p* from *light*.
+fn li(light: &ProjectionLight, p: Point3f) -> Float {
+    let iref: InteractionCommon = InteractionCommon {
+        p,
+        ..Default::default()
+    };
+    let mut wi: Vector3f = Vector3f::default();
+    let mut pdf: Float = 0.0;
+    let (li, _vis) = light.sample_li(&iref, Point2f { x: 0.5, y: 0.5 }, &mut wi, &mut pdf);
+    assert_eq!(pdf, 1.0);
+    li.c[0]
+}
+
+fn main() {
+    // solid angle of the (square) screen window, half angle alpha
+    let alpha: f64 = (22.5_f64).to_radians();
+    let solid_angle: f64 = 4.0 * (alpha.sin() * alpha.sin()).asin();
+    // without an image the light projects white
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("fov"), 45.0);
+    params.add_rgb_spectrum(String::from("I"), Spectrum::new(2.0));
+    let plain: Arc<Light> =
+        ProjectionLight::create(&params, &Transform::default(), &MediumInterface::default());
+    assert_eq!(plain.get_flags(), LightFlags::DeltaPosition as u8);
+    let power: f64 = plain.power().c[0] as f64;
+    println!("power {} (expected {})", power, 2.0 * solid_angle);
+    assert!((power - 2.0 * solid_angle).abs() < 1.0e-3 * power);
+    // an image with a dark left and a bright right half
+    let mut texels: Vec<Spectrum> = Vec::new();
+    for _y in 0..RES {
+        for x in 0..RES {
+            texels.push(Spectrum::new(if x < RES / 2 { 1.0 } else { 3.0 }));
+        }
+    }
+    let mipmap: MipMap<Spectrum> = MipMap::new(
+        Point2i { x: RES, y: RES },
+        &texels[..],
+        false,
+        8.0,
+        ImageWrap::Clamp,
+    );
+    let light: ProjectionLight = ProjectionLight::with_map(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::new(2.0),
+        Some(Arc::new(mipmap)),
+        45.0,
+    );
+    let tan_half_fov: Float = radians(22.5).tan();
+    // in front of the light, along +z
+    let front: Point3f = Point3f {
+        x: 0.5 * tan_half_fov * 4.0,
+        y: 0.0,
+        z: 4.0,
+    };
+    let d2: Float = front.x * front.x + front.z * front.z;
+    assert!((li(&light, front) - 2.0 * 3.0 / d2).abs() < 1.0e-4);
+    // behind the light
+    let behind: Point3f = Point3f {
+        x: 0.0,
+        y: 0.0,
+        z: -4.0,
+    };
+    assert_eq!(li(&light, behind), 0.0);
+    // close to the right edge of the screen window the image doesn't
+    // wrap around to its left edge ...
+    let z: Float = 2.0;
+    let edge: Point3f = Point3f {
+        x: 0.9999 * tan_half_fov * z,
+        y: 0.0,
+        z,
+    };
+    let d2: Float = edge.x * edge.x + z * z;
+    let value: Float = li(&light, edge);
+    assert!((value - 2.0 * 3.0 / d2).abs() < 1.0e-3, "{}", value);
+    // ... and outside of it there is no light
+    let outside: Point3f = Point3f {
+        x: 1.001 * tan_half_fov * z,
+        y: 0.0,
+        z,
+    };
+    assert_eq!(li(&light, outside), 0.0);
+    // both halves cover the same solid angle
+    let power: f64 = light.power().c[0] as f64;
+    let expected: f64 = 2.0 * 2.0 * solid_angle;
+    println!("power {} (expected {})", power, expected);
+    assert!((power - expected).abs() < 5.0e-3 * expected);
+}
@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{nrm_abs_dot_vec3, Normal3f, Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::light::Light;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::sampling::{uniform_sample_sphere, uniform_sphere_pdf};
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::lights::diffuse::DiffuseAreaLight;
+use pbrt::lights::point::PointLight;
+use pbrt::shapes::sphere::Sphere;
+
+/// Brute-force ground truth for (unoccluded) direct lighting, meant
+/// to validate *sample_li()* and *pdf_li()* in tests. Area lights are
+/// integrated by dense stratified sampling of the light's surface,
+/// infinite lights by sampling the sphere of directions. For delta
+/// lights a single *sample_li()* call is already exact.
+pub struct ReferenceLightSampler {
+    /// number of strata per sample dimension
+    pub n_strata: u32,
+}
+
+impl ReferenceLightSampler {
+    pub fn new(n_strata: u32) -> Self {
+        ReferenceLightSampler { n_strata }
+    }
+    /// Incident radiance at *iref* integrated over all directions,
+    /// weighted by the cosine to *iref.n* (unless it's zero).
+    pub fn li(&self, light: &Light, iref: &InteractionCommon) -> Spectrum {
+        let n: u32 = self.n_strata.max(1);
+        let mut sum: Spectrum = Spectrum::default();
+        match light {
+            Light::DiffuseArea(area_light) => {
+                for u in ReferenceLightSampler::strata(n) {
+                    let mut pdf_area: Float = 0.0 as Float;
+                    let it: InteractionCommon = area_light.shape.sample(u, &mut pdf_area);
+                    let d: Vector3f = it.p - iref.p;
+                    let dist2: Float = d.length_squared();
+                    if pdf_area == 0.0 as Float || dist2 == 0.0 as Float {
+                        continue;
+                    }
+                    let wi: Vector3f = d / dist2.sqrt();
+                    // convert from area to solid angle measure
+                    let g: Float = nrm_abs_dot_vec3(&it.n, &wi) / dist2;
+                    sum += area_light.l(&it, &-wi) * cos_weight(iref, &wi) * g / pdf_area;
+                }
+            }
+            Light::InfiniteArea(infinite_light) => {
+                for u in ReferenceLightSampler::strata(n) {
+                    let wi: Vector3f = uniform_sample_sphere(u);
+                    sum += infinite_light.li_toward(iref.p, wi) * cos_weight(iref, &wi)
+                        / uniform_sphere_pdf();
+                }
+            }
+            _ => {
+                let mut wi: Vector3f = Vector3f::default();
+                let mut pdf: Float = 0.0 as Float;
+                let (li, _vis) = light.sample_li(iref, Point2f::default(), &mut wi, &mut pdf);
+                if pdf == 0.0 as Float {
+                    return Spectrum::default();
+                }
+                return li * cos_weight(iref, &wi) / pdf;
+            }
+        }
+        sum / (n * n) as Float
+    }
+    fn strata(n: u32) -> impl Iterator<Item = Point2f> {
+        (0..n * n).map(move |i| Point2f {
+            x: ((i % n) as Float + 0.5 as Float) / n as Float,
+            y: ((i / n) as Float + 0.5 as Float) / n as Float,
+        })
+    }
+}
+
+fn cos_weight(iref: &InteractionCommon, wi: &Vector3f) -> Float {
+    if iref.n == Normal3f::default() {
+        1.0 as Float
+    } else {
+        nrm_abs_dot_vec3(&iref.n, wi)
+    }
+}
+
+/// Importance-sampled estimate of the (unoccluded) direct lighting,
+/// with an optional bogus scale on the returned pdf.
+fn estimate(light: &Light, iref: &InteractionCommon, n: u32, pdf_scale: Float) -> Spectrum {
+    let mut sum: Spectrum = Spectrum::default();
+    for i in 0..n * n {
+        let u: Point2f = Point2f {
+            x: ((i % n) as Float + 0.5) / n as Float,
+            y: ((i / n) as Float + 0.5) / n as Float,
+        };
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let (li, _vis) = light.sample_li(iref, u, &mut wi, &mut pdf);
+        if pdf > 0.0 {
+            sum += li * nrm_abs_dot_vec3(&iref.n, &wi) / (pdf * pdf_scale);
+        }
+    }
+    sum / (n * n) as Float
+}
+
+fn relative_error(a: Spectrum, b: Spectrum) -> Float {
+    (a.y() - b.y()).abs() / b.y()
+}
+
+fn main() {
+    let iref: InteractionCommon = InteractionCommon {
+        p: Point3f::default(),
+        n: Normal3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        ..Default::default()
+    };
+    let reference: ReferenceLightSampler = ReferenceLightSampler::new(512);
+    let light_to_world: Transform = Transform::translate(&Vector3f {
+        x: 0.5,
+        y: 0.0,
+        z: 3.0,
+    });
+    // point light (trivial)
+    let point_light: Light = Light::Point(Box::new(PointLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(10.0),
+    )));
+    let truth: Spectrum = reference.li(&point_light, &iref);
+    let est: Spectrum = estimate(&point_light, &iref, 4, 1.0);
+    println!("point light: reference {:?} estimate {:?}", truth, est);
+    assert!(relative_error(est, truth) < 1.0e-4);
+    // spherical area light (sampled by cone, reference samples the area)
+    let shape: Arc<Shape> = Arc::new(Shape::Sphr(Sphere::new(
+        light_to_world,
+        Transform::inverse(&light_to_world),
+        false,
+        0.75,
+        -0.75,
+        0.75,
+        360.0,
+    )));
+    let area_light: Light = Light::DiffuseArea(Box::new(DiffuseAreaLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(4.0),
+        1,
+        shape,
+        false,
+    )));
+    let truth: Spectrum = reference.li(&area_light, &iref);
+    let est: Spectrum = estimate(&area_light, &iref, 64, 1.0);
+    println!("sphere light: reference {:?} estimate {:?}", truth, est);
+    assert!(relative_error(est, truth) < 0.01);
+    // a deliberately wrong pdf is detected
+    let wrong: Spectrum = estimate(&area_light, &iref, 64, 0.8);
+    println!("sphere light with wrong pdf: {:?}", wrong);
+    assert!(relative_error(wrong, truth) > 0.1);
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Point3f, Vector3f};
+use pbrt::core::light::Light;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{radians, Float, Spectrum};
+use pbrt::core::transform::Transform;
+use pbrt::lights::spot::SpotLight;
+
+fn spot(params: &ParamSet) -> SpotLight {
+    let light: Arc<Light> = SpotLight::create(
+        params,
+        &Transform::default(),
+        &MediumInterface::default(),
+        None,
+    );
+    match &*light {
+        Light::Spot(spot) => (**spot).clone(),
+        _ => panic!("SpotLight::create() didn't create a spot light"),
+    }
+}
+
+fn main() {
+    // defaults: 30 degree cone, 5 degree falloff
+    let default: SpotLight = spot(&ParamSet::default());
+    assert!((default.cos_total_width - radians(30.0).cos()).abs() < 1.0e-6);
+    assert!((default.cos_falloff_start - radians(25.0).cos()).abs() < 1.0e-6);
+    // a spot light pointing down the negative z axis
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("coneangle"), 40.0);
+    params.add_float(String::from("conedeltaangle"), 15.0);
+    params.add_point3f(
+        String::from("from"),
+        Point3f {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        },
+    );
+    params.add_point3f(
+        String::from("to"),
+        Point3f {
+            x: 1.0,
+            y: 2.0,
+            z: 2.0,
+        },
+    );
+    params.add_rgb_spectrum(String::from("I"), Spectrum::new(2.0));
+    let light: SpotLight = spot(&params);
+    let p: Point3f = light.p_light;
+    assert!((p.x - 1.0).abs() < 1.0e-5 && (p.y - 2.0).abs() < 1.0e-5 && (p.z - 3.0).abs() < 1.0e-5);
+    let down: Vector3f = Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: -1.0,
+    };
+    assert!((light.falloff(&down) - 1.0).abs() < 1.0e-5);
+    assert_eq!(light.falloff(&-down), 0.0);
+    // the falloff between both cone angles is smooth and monotonic
+    let mut last: Float = 1.0;
+    for degrees in 25..=40 {
+        let theta: Float = radians(degrees as Float);
+        let w: Vector3f = Vector3f {
+            x: theta.sin(),
+            y: 0.0,
+            z: -theta.cos(),
+        };
+        let f: Float = light.falloff(&w);
+        assert!(f <= last + 1.0e-6 && f >= 0.0);
+        last = f;
+    }
+    // power integrates the intensity over the sphere of directions
+    let n: usize = 200_000;
+    let mut integral: f64 = 0.0;
+    for k in 0..n {
+        let cos_theta: Float = -1.0 + 2.0 * (k as Float + 0.5) / n as Float;
+        let sin_theta: Float = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let w: Vector3f = Vector3f {
+            x: sin_theta,
+            y: 0.0,
+            z: -cos_theta,
+        };
+        integral += light.falloff(&w) as f64 * 2.0 / n as f64;
+    }
+    let expected: f64 = 2.0 * 2.0 * std::f64::consts::PI * integral;
+    let power: Float = light.power().c[0];
+    println!("power {} (numerically {})", power, expected);
+    assert!((power as f64 - expected).abs() < 1.0e-3 * expected);
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::InteractionCommon;
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::texture::{Texture, TextureMapping2D, UVMapping2D};
+use pbrt::core::transform::Transform;
+use pbrt::lights::spot::SpotLight;
+use pbrt::textures::checkerboard::{AAMethod, Checkerboard2DTexture};
+use pbrt::textures::constant::ConstantTexture;
+
+fn main() {
+    // a 4x4 checkerboard projected through the cone
+    let mapping = Box::new(TextureMapping2D::UV(UVMapping2D {
+        su: 4.0,
+        sv: 4.0,
+        du: 0.0,
+        dv: 0.0,
+    }));
+    let lit: Arc<dyn Texture<Float> + Send + Sync> = Arc::new(ConstantTexture::new(1.0));
+    let dark: Arc<dyn Texture<Float> + Send + Sync> = Arc::new(ConstantTexture::new(0.0));
+    let gobo: Arc<dyn Texture<Float> + Send + Sync> = Arc::new(Checkerboard2DTexture::new(
+        mapping,
+        lit,
+        dark,
+        AAMethod::None,
+    ));
+    // spot light 1 unit above the plane z = 0, pointing down (-z)
+    let light_to_world: Transform = Transform::translate(&Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    }) * Transform::rotate_x(180.0);
+    let spot_light: SpotLight = SpotLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+        45.0,
+        45.0,
+        Some(gobo),
+    );
+    // half of the checkerboard is dark, so is half of the power
+    let open: SpotLight = SpotLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+        45.0,
+        45.0,
+        None,
+    );
+    let ratio: Float = spot_light.power().y() / open.power().y();
+    assert!((ratio - 0.5).abs() < 0.01, "{}", ratio);
+    // walk across the plane beneath the light
+    let mut values: Vec<Float> = Vec::new();
+    let n: usize = 4;
+    for i in 0..n {
+        let x: Float = -1.0 + (i as Float + 0.5) * 2.0 / n as Float;
+        let iref: InteractionCommon = InteractionCommon {
+            p: Point3f { x, y: 0.1, z: 0.0 },
+            ..Default::default()
+        };
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let (li, _vis) = spot_light.sample_li(&iref, Point2f::default(), &mut wi, &mut pdf);
+        values.push(li.y());
+    }
+    println!("gobo values = {:?}", values);
+    for i in 1..n {
+        assert!(
+            (values[i - 1] > 0.0) != (values[i] > 0.0),
+            "expected alternating lit/dark regions"
+        );
+    }
+    // directions outside the cone stay black
+    let iref: InteractionCommon = InteractionCommon {
+        p: Point3f {
+            x: 5.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        ..Default::default()
+    };
+    let mut wi: Vector3f = Vector3f::default();
+    let mut pdf: Float = 0.0;
+    let (li, _vis) = spot_light.sample_li(&iref, Point2f::default(), &mut wi, &mut pdf);
+    assert!(li.is_black());
+    // cones wider than 90 degrees still project the gobo
+    let clear: Arc<dyn Texture<Float> + Send + Sync> = Arc::new(ConstantTexture::new(1.0));
+    let wide: SpotLight = SpotLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+        120.0,
+        100.0,
+        Some(clear),
+    );
+    let wide_open: SpotLight = SpotLight::new(
+        &light_to_world,
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+        120.0,
+        100.0,
+        None,
+    );
+    for &(x, z) in &[(0.0, 0.0), (2.0, 0.5), (1.0, 1.5)] {
+        let iref: InteractionCommon = InteractionCommon {
+            p: Point3f { x, y: 0.0, z },
+            ..Default::default()
+        };
+        let (li, _vis) = wide.sample_li(&iref, Point2f::default(), &mut wi, &mut pdf);
+        let (li_open, _vis) = wide_open.sample_li(&iref, Point2f::default(), &mut wi, &mut pdf);
+        assert!(!li.is_black(), "({}, {})", x, z);
+        assert!((li.y() - li_open.y()).abs() < 1e-5 * li_open.y());
+    }
+    let ratio: Float = wide.power().y() / wide_open.power().y();
+    assert!((ratio - 1.0).abs() < 0.01, "{}", ratio);
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{plane, texture_params, vector};
+use pbrt::core::geometry::{Point2f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::reflection::{Bsdf, Bxdf, BxdfType};
+use pbrt::materials::disney::{DisneyClearCoat, DisneyMaterial};
+use std::f32::consts::PI;
+
+fn create(params: ParamSet) -> Arc<Material> {
+    DisneyMaterial::create(&mut texture_params(params))
+}
+
+fn bsdf(material: &Material) -> Bsdf {
+    let mut si: SurfaceInteraction = plane(Point2f::default());
+    material.compute_scattering_functions(&mut si, TransportMode::Radiance, false, None, None);
+    si.bsdf.unwrap()
+}
+
+/// Specular (glossy reflection) part of the BSDF at normal incidence.
+fn specular(params: ParamSet) -> Spectrum {
+    let normal: Vector3f = vector(0.0, 0.0, 1.0);
+    let glossy: u8 = BxdfType::BsdfReflection as u8 | BxdfType::BsdfGlossy as u8;
+    bsdf(&create(params)).f(&normal, &normal, glossy)
+}
+
+fn main() {
+    let all: u8 = BxdfType::BsdfAll as u8;
+    // defaults: diffuse, retro-reflection and specular
+    let plain: Bsdf = bsdf(&create(ParamSet::default()));
+    assert_eq!(plain.num_components(all), 3);
+    match (&plain.bxdfs[0], &plain.bxdfs[1], &plain.bxdfs[2]) {
+        (Bxdf::DisDiff(_), Bxdf::DisRetro(_), Bxdf::MicrofacetRefl(_)) => {}
+        _ => panic!("unexpected lobes"),
+    }
+    // sheen, clearcoat and transmission add their lobes, thin surfaces
+    // also fake subsurface scattering and transmit diffusely
+    let mut params: ParamSet = ParamSet::default();
+    for &name in &["sheen", "clearcoat", "spectrans"] {
+        params.add_float(String::from(name), 0.5);
+    }
+    params.add_bool(String::from("thin"), true);
+    let layered: Bsdf = bsdf(&create(params));
+    assert_eq!(layered.num_components(all), 8);
+    assert_eq!(
+        layered.num_components(BxdfType::BsdfTransmission as u8 | BxdfType::BsdfGlossy as u8),
+        1
+    );
+    // the specular lobe of a dielectric isn't tinted by its color, a
+    // metal reflects its color at normal incidence, anything in
+    // between blends the two Fresnel terms
+    let color: Spectrum = Spectrum::rgb(0.9, 0.5, 0.1);
+    let with_metallic = |metallic: Float| -> Spectrum {
+        let mut params: ParamSet = ParamSet::default();
+        params.add_rgb_spectrum(String::from("color"), color);
+        params.add_float(String::from("metallic"), metallic);
+        specular(params)
+    };
+    let dielectric: Spectrum = with_metallic(0.0);
+    assert!(dielectric.c[0] > 0.0);
+    assert!((dielectric.c[0] - dielectric.c[2]).abs() < 1e-6 * dielectric.c[0]);
+    let f0: Float = 0.04;
+    for &metallic in &[0.5, 1.0] {
+        let f: Spectrum = with_metallic(metallic);
+        for c in 0..3 {
+            let r0: Float = (1.0 - metallic) * f0 + metallic * color.c[c];
+            let fresnel: Float = (1.0 - metallic) * f0 + metallic * r0;
+            let expected: Float = dielectric.c[c] * fresnel / f0;
+            assert!(
+                (f.c[c] - expected).abs() < 1e-4 * expected,
+                "{}: {:?}",
+                metallic,
+                f.c
+            );
+        }
+    }
+    // metals don't have a diffuse base
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("metallic"), 1.0);
+    assert_eq!(bsdf(&create(params)).num_components(all), 1);
+    // "specular" maps to the reflectance at normal incidence (0.5 is
+    // the default index of refraction 1.5), and overrides "eta"
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("specular"), 0.5);
+    params.add_float(String::from("eta"), 2.0);
+    let half: Spectrum = specular(params);
+    let default: Spectrum = specular(ParamSet::default());
+    assert!((half.c[0] - default.c[0]).abs() < 1e-4 * default.c[0]);
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("specular"), 1.0);
+    let full: Spectrum = specular(params);
+    assert!((full.c[0] - 2.0 * default.c[0]).abs() < 1e-3 * default.c[0]);
+    // the clearcoat distribution is normalized: the pdf integrates to
+    // the probability of sampling a half vector within 45 degrees of
+    // the normal (the others reflect below the surface)
+    let wo: Vector3f = vector(0.0, 0.0, 1.0);
+    for &gloss in &[0.1, 0.03] {
+        let clearcoat: DisneyClearCoat = DisneyClearCoat::new(1.0, gloss, None);
+        let (n_theta, n_phi) = (4000, 64);
+        let mut sum: Float = 0.0;
+        for i in 0..n_theta {
+            let cos_theta: Float = (i as Float + 0.5) / n_theta as Float;
+            let sin_theta: Float = (1.0 - cos_theta * cos_theta).sqrt();
+            for j in 0..n_phi {
+                let phi: Float = 2.0 * PI * (j as Float + 0.5) / n_phi as Float;
+                let wi: Vector3f = Vector3f {
+                    x: sin_theta * phi.cos(),
+                    y: sin_theta * phi.sin(),
+                    z: cos_theta,
+                };
+                sum += clearcoat.pdf(&wo, &wi) * 2.0 * PI / (n_theta * n_phi) as Float;
+            }
+        }
+        let alpha2: Float = gloss * gloss;
+        let expected: Float = 1.0 - ((1.0 + alpha2) / 2.0).ln() / alpha2.ln();
+        assert!((sum - expected).abs() < 1e-2, "{}: {}", gloss, sum);
+    }
+}
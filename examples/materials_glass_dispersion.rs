@@ -0,0 +1,186 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{plane, texture_params, vector};
+use pbrt::core::geometry::{Point2f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::reflection::{Bsdf, Bxdf, BxdfType, FresnelSpecular};
+use pbrt::core::rng::Rng;
+use pbrt::materials::glass::GlassMaterial;
+use pbrt::textures::constant::ConstantTexture;
+
+fn glass(roughness: Float, dispersion: Float) -> GlassMaterial {
+    let mut glass: GlassMaterial = GlassMaterial::new(
+        Arc::new(ConstantTexture::new(Spectrum::new(1.0))),
+        Arc::new(ConstantTexture::new(Spectrum::new(1.0))),
+        Arc::new(ConstantTexture::new(roughness)),
+        Arc::new(ConstantTexture::new(roughness)),
+        Arc::new(ConstantTexture::new(1.5 as Float)),
+        None,
+        false,
+    );
+    glass.dispersion = dispersion;
+    glass
+}
+
+fn bsdf(material: Material, allow_multiple_lobes: bool) -> Bsdf {
+    let mut si: SurfaceInteraction = plane(Point2f::default());
+    material.compute_scattering_functions(
+        &mut si,
+        TransportMode::Radiance,
+        allow_multiple_lobes,
+        None,
+        None,
+    );
+    si.bsdf.unwrap()
+}
+
+fn sample(bsdf: &Bsdf, wo: &Vector3f, x: Float, y: Float) -> (Spectrum, Vector3f, Float, u8) {
+    let mut wi: Vector3f = Vector3f::default();
+    let mut pdf: Float = 0.0;
+    let mut sampled_type: u8 = BxdfType::BsdfAll as u8;
+    let f: Spectrum = bsdf.sample_f(
+        wo,
+        &mut wi,
+        Point2f { x, y },
+        &mut pdf,
+        BxdfType::BsdfAll as u8,
+        &mut sampled_type,
+    );
+    (f, wi, pdf, sampled_type)
+}
+
+/// Monte Carlo estimate of the reflected and transmitted fraction of
+/// light (per channel) leaving in direction *wo*.
+fn albedo(bxdf: &FresnelSpecular, wo: &Vector3f, rng: &mut Rng) -> [Float; 3] {
+    let n_samples: usize = 100_000;
+    let mut sum: [Float; 3] = [0.0; 3];
+    for _ in 0..n_samples {
+        let u: Point2f = Point2f {
+            x: rng.uniform_float(),
+            y: rng.uniform_float(),
+        };
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let mut sampled_type: u8 = 0;
+        let f: Spectrum = bxdf.sample_f(wo, &mut wi, u, &mut pdf, &mut sampled_type);
+        if pdf > 0.0 {
+            for (c, s) in sum.iter_mut().enumerate() {
+                *s += f.c[c] * wi.z.abs() / pdf;
+            }
+        }
+    }
+    [
+        sum[0] / n_samples as Float,
+        sum[1] / n_samples as Float,
+        sum[2] / n_samples as Float,
+    ]
+}
+
+fn main() {
+    let all: u8 = BxdfType::BsdfAll as u8;
+    let reflection: u8 = BxdfType::BsdfReflection as u8;
+    // smooth glass: a single BxDF choosing between reflection and
+    // transmission, or one BxDF for each
+    let smooth: Bsdf = bsdf(Material::Glass(Box::new(glass(0.0, 0.0))), true);
+    assert_eq!(smooth.num_components(all), 1);
+    match smooth.bxdfs[0] {
+        Bxdf::FresnelSpec(_) => {}
+        _ => panic!("expected a FresnelSpecular BxDF"),
+    }
+    let split: Bsdf = bsdf(Material::Glass(Box::new(glass(0.0, 0.0))), false);
+    match (&split.bxdfs[0], &split.bxdfs[1]) {
+        (Bxdf::SpecRefl(_), Bxdf::SpecTrans(_)) => {}
+        _ => panic!("expected specular reflection and transmission"),
+    }
+    // rough glass: microfacet reflection and transmission
+    let rough: Bsdf = bsdf(Material::Glass(Box::new(glass(0.2, 0.0))), true);
+    match (&rough.bxdfs[0], &rough.bxdfs[1]) {
+        (Bxdf::MicrofacetRefl(_), Bxdf::MicrofacetTrans(_)) => {}
+        _ => panic!("expected microfacet reflection and transmission"),
+    }
+    // total internal reflection: inside the glass at a grazing angle
+    // all light is reflected ...
+    let wo: Vector3f = vector(0.9, 0.0, -0.3);
+    for i in 0..10 {
+        let (f, wi, pdf, sampled_type) = sample(&smooth, &wo, i as Float / 10.0, 0.5);
+        assert!((pdf - 1.0).abs() < 1e-5, "{}", pdf);
+        assert!(f.c[0].is_finite() && f.c[0] > 0.0, "{:?}", f.c);
+        assert!((wi - vector(-0.9, 0.0, -0.3)).length() < 1e-5);
+        assert!(sampled_type & reflection != 0);
+    }
+    // ... and the transmission BxDF doesn't contribute
+    let (f, _wi, pdf, _sampled_type) = sample(&split, &wo, 0.25, 0.5);
+    assert!(pdf > 0.0 && f.c[0].is_finite() && f.c[0] > 0.0);
+    let (f, _wi, pdf, _sampled_type) = sample(&split, &wo, 0.75, 0.5);
+    assert!(f.is_black() && pdf == 0.0, "{:?} {}", f.c, pdf);
+    // dispersion: the index of refraction depends on the channel
+    let dispersive: GlassMaterial = glass(0.0, 0.05);
+    let eta_rgb: [Float; 3] = dispersive.eta_rgb(1.5);
+    assert!(eta_rgb[0] < eta_rgb[1] && eta_rgb[1] < eta_rgb[2]);
+    assert!((eta_rgb[1] - 1.5).abs() < 1e-6);
+    let prism: Bsdf = bsdf(Material::Glass(Box::new(dispersive)), true);
+    let wo: Vector3f = vector(0.6, 0.0, 0.8);
+    let mut bent: Vec<Float> = Vec::new();
+    for (c, eta) in eta_rgb.iter().enumerate() {
+        // transmission (Fresnel reflectance is small), non-zero only
+        // in the selected channel
+        let (f, wi, pdf, _sampled_type) = sample(&prism, &wo, (c as Float + 0.9) / 3.0, 0.5);
+        assert!(pdf > 0.0 && wi.z < 0.0);
+        for k in 0..3 {
+            assert_eq!(f.c[k] > 0.0, k == c, "{:?}", f.c);
+        }
+        // Snell's law with the channel's index of refraction
+        assert!((wi.x + 0.6 / eta).abs() < 1e-4, "{} {}", wi.x, eta);
+        bent.push(wi.x);
+        // reflection goes into the same direction for all channels
+        let (f, wi, _pdf, _sampled_type) = sample(&prism, &wo, (c as Float + 0.01) / 3.0, 0.5);
+        assert!((wi - vector(-0.6, 0.0, 0.8)).length() < 1e-5);
+        assert!(f.c[c] > 0.0 && f.c[(c + 1) % 3] == 0.0);
+    }
+    // red is bent least, blue most
+    assert!(bent[0] < bent[1] && bent[1] < bent[2], "{:?}", bent);
+    // on average each channel behaves like glass with its own index
+    // of refraction
+    let mut rng: Rng = Rng::new();
+    let r: Spectrum = Spectrum::new(1.0);
+    let mode: TransportMode = TransportMode::Radiance;
+    let dispersed: [Float; 3] = albedo(
+        &FresnelSpecular::new_dispersive(r, r, 1.0, eta_rgb, mode, None),
+        &wo,
+        &mut rng,
+    );
+    for (c, eta) in eta_rgb.iter().enumerate() {
+        let single: [Float; 3] = albedo(
+            &FresnelSpecular::new(r, r, 1.0, *eta, mode, None),
+            &wo,
+            &mut rng,
+        );
+        assert!(
+            (dispersed[c] - single[c]).abs() < 0.03 * single[c],
+            "{} {}",
+            dispersed[c],
+            single[c]
+        );
+    }
+    // create() reads "roughness" for both directions and "dispersion"
+    let mut material_params: ParamSet = ParamSet::default();
+    material_params.add_float(String::from("roughness"), 0.3);
+    material_params.add_float(String::from("dispersion"), 0.01);
+    material_params.add_float(String::from("index"), 1.33);
+    if let Material::Glass(ref glass) = *GlassMaterial::create(&mut texture_params(material_params))
+    {
+        let si: SurfaceInteraction = SurfaceInteraction::default();
+        assert_eq!(glass.u_roughness.evaluate(&si), 0.3);
+        assert_eq!(glass.v_roughness.evaluate(&si), 0.3);
+        assert_eq!(glass.index.evaluate(&si), 1.33);
+        assert_eq!(glass.dispersion, 0.01);
+        assert!(glass.remap_roughness);
+    } else {
+        panic!("GlassMaterial::create() didn't create a glass material");
+    }
+}
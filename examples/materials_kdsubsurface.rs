@@ -0,0 +1,116 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{plane, texture_params};
+use pbrt::core::bssrdf::{compute_beam_diffusion_bssrdf, subsurface_from_diffuse, BssrdfTable};
+use pbrt::core::geometry::Point2f;
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::interpolation::{catmull_rom_weights, invert_catmull_rom};
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::materials::kdsubsurface::KdSubsurfaceMaterial;
+use pbrt::materials::subsurface::SubsurfaceMaterial;
+
+/// Surface interaction on the *z = 0* plane, shaded with *material*.
+fn shade(material: &Arc<Material>) -> SurfaceInteraction<'_> {
+    let mut si: SurfaceInteraction = plane(Point2f::default());
+    material.compute_scattering_functions(
+        &mut si,
+        TransportMode::Radiance,
+        true,
+        Some(material.clone()),
+        None,
+    );
+    si
+}
+
+/// Effective albedo for the single scattering albedo *rho*,
+/// interpolated from *table*.
+fn rho_eff(table: &BssrdfTable, rho: Float) -> Float {
+    let mut offset: i32 = 0;
+    let mut weights: [Float; 4] = [0.0; 4];
+    assert!(catmull_rom_weights(
+        &table.rho_samples,
+        rho,
+        &mut offset,
+        &mut weights
+    ));
+    let mut result: Float = 0.0;
+    for (i, weight) in weights.iter().enumerate() {
+        if *weight != 0.0 {
+            result += weight * table.rho_eff[(offset + i as i32) as usize];
+        }
+    }
+    result
+}
+
+fn main() {
+    // inverting a spline through linear data is exact, values out of
+    // range are clamped to the first and last node
+    let x: [Float; 5] = [0.0, 1.0, 2.0, 3.0, 4.0];
+    let values: Vec<Float> = x.iter().map(|x| 2.0 * x + 1.0).collect();
+    for &u in &[1.5, 3.0, 4.25, 8.9] {
+        let inverse: Float = invert_catmull_rom(&x, &values, u);
+        assert!((inverse - (u - 1.0) / 2.0).abs() < 1e-5, "{}", inverse);
+    }
+    assert_eq!(invert_catmull_rom(&x, &values, 0.0), 0.0);
+    assert_eq!(invert_catmull_rom(&x, &values, 10.0), 4.0);
+    // the scattering coefficients reproduce the diffuse reflectance
+    // and the mean free path
+    let mut table: BssrdfTable = BssrdfTable::new(100, 64);
+    compute_beam_diffusion_bssrdf(0.0, 1.33, &mut table);
+    let kd: Spectrum = Spectrum::rgb(0.2, 0.5, 0.8);
+    let mfp: Spectrum = Spectrum::rgb(0.5, 1.0, 2.0);
+    let mut sigma_a: Spectrum = Spectrum::default();
+    let mut sigma_s: Spectrum = Spectrum::default();
+    subsurface_from_diffuse(&table, &kd, &mfp, &mut sigma_a, &mut sigma_s);
+    for c in 0..3 {
+        let sigma_t: Float = sigma_a.c[c] + sigma_s.c[c];
+        assert!((sigma_t * mfp.c[c] - 1.0).abs() < 1e-5);
+        let rho: Float = sigma_s.c[c] / sigma_t;
+        assert!(
+            (rho_eff(&table, rho) - kd.c[c]).abs() < 1e-4,
+            "{}: {} vs. {}",
+            c,
+            rho_eff(&table, rho),
+            kd.c[c]
+        );
+    }
+    // "kdsubsurface" shades with a dielectric interface and a BSSRDF
+    // built from those coefficients ("scale" scales the mean free
+    // path)
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("Kd"), kd);
+    params.add_rgb_spectrum(String::from("mfp"), mfp);
+    params.add_float(String::from("scale"), 0.5);
+    let material: Arc<Material> = KdSubsurfaceMaterial::create(&mut texture_params(params));
+    let si: SurfaceInteraction = shade(&material);
+    assert!(si.bsdf.is_some());
+    let bssrdf = si.bssrdf.unwrap();
+    assert_eq!(bssrdf.eta, 1.33);
+    for c in 0..3 {
+        let sigma_t: Float = sigma_a.c[c] + sigma_s.c[c];
+        assert!((bssrdf.sigma_t.c[c] - 2.0 * sigma_t).abs() < 1e-4 * sigma_t);
+        assert!((bssrdf.rho.c[c] - sigma_s.c[c] / sigma_t).abs() < 1e-5);
+    }
+    // nothing scatters without an interface
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("Kr"), Spectrum::new(0.0));
+    params.add_rgb_spectrum(String::from("Kt"), Spectrum::new(0.0));
+    let material: Arc<Material> = KdSubsurfaceMaterial::create(&mut texture_params(params));
+    let si: SurfaceInteraction = shade(&material);
+    assert!(si.bsdf.is_none() && si.bssrdf.is_none());
+    // "subsurface" reads the transmission from "Kt" (not "Kr")
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("Kt"), Spectrum::new(0.25));
+    let material: Arc<Material> = SubsurfaceMaterial::create(&mut texture_params(params));
+    if let Material::Subsurface(ref subsurface) = *material {
+        let si: SurfaceInteraction = SurfaceInteraction::default();
+        assert!(subsurface.kr.evaluate(&si) == Spectrum::new(1.0));
+        assert!(subsurface.kt.evaluate(&si) == Spectrum::new(0.25));
+    } else {
+        panic!("SubsurfaceMaterial::create() didn't create a subsurface material");
+    }
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{plane, vector};
+use pbrt::core::geometry::{Point2f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::pbrt::{Float, Spectrum, INV_PI};
+use pbrt::core::reflection::{Bsdf, Bxdf, BxdfType, OrenNayar};
+use pbrt::core::texture::Texture;
+use pbrt::materials::matte::MatteMaterial;
+use pbrt::textures::constant::ConstantTexture;
+
+/// Roughness increasing along *u*, 60 degrees per unit.
+struct SigmaRamp {}
+
+impl Texture<Float> for SigmaRamp {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Float {
+        60.0 * si.uv.x
+    }
+}
+
+fn bsdf(material: &Material, u: Float, scale: Option<Spectrum>) -> Bsdf {
+    let mut si: SurfaceInteraction = plane(Point2f { x: u, y: 0.0 });
+    material.compute_scattering_functions(&mut si, TransportMode::Radiance, false, None, scale);
+    si.bsdf.unwrap()
+}
+
+fn main() {
+    let material: Material = Material::Matte(Box::new(MatteMaterial::new(
+        Arc::new(ConstantTexture::new(Spectrum::new(0.5))),
+        Arc::new(SigmaRamp {}),
+        None,
+    )));
+    let wo: Vector3f = vector(0.6, 0.0, 0.8);
+    let wi: Vector3f = vector(-0.3, 0.4, 0.5);
+    let all: u8 = BxdfType::BsdfAll as u8;
+    // no roughness: Lambertian reflection
+    let smooth: Bsdf = bsdf(&material, 0.0, None);
+    assert_eq!(smooth.num_components(all), 1);
+    match smooth.bxdfs[0] {
+        Bxdf::LambertianRefl(_) => {}
+        _ => panic!("expected a Lambertian BxDF for sigma = 0"),
+    }
+    let f: Float = smooth.f(&wo, &wi, all).c[0];
+    assert!((f - 0.5 * INV_PI).abs() < 1e-6, "{}", f);
+    // the roughness is evaluated where the surface is hit
+    for &u in &[0.25, 0.5, 1.0] {
+        let rough: Bsdf = bsdf(&material, u, None);
+        assert_eq!(rough.num_components(all), 1);
+        if let Bxdf::OrenNayarRefl(ref oren_nayar) = rough.bxdfs[0] {
+            let expected: OrenNayar = OrenNayar::new(Spectrum::new(0.5), 60.0 * u, None);
+            assert!((oren_nayar.a - expected.a).abs() < 1e-6);
+            assert!((oren_nayar.b - expected.b).abs() < 1e-6);
+        } else {
+            panic!("expected an Oren-Nayar BxDF for sigma > 0");
+        }
+        // rough surfaces look brighter towards the viewer (back
+        // scattering) than away from it
+        let back: Float = rough.f(&wo, &vector(0.5, 0.0, 0.5), all).c[0];
+        let forward: Float = rough.f(&wo, &vector(-0.5, 0.0, 0.5), all).c[0];
+        assert!(back > forward, "{} {}", back, forward);
+    }
+    // roughness is clamped to 90 degrees
+    let rougher: Bsdf = bsdf(&material, 2.0, None);
+    if let Bxdf::OrenNayarRefl(ref oren_nayar) = rougher.bxdfs[0] {
+        let expected: OrenNayar = OrenNayar::new(Spectrum::new(0.5), 90.0, None);
+        assert!((oren_nayar.a - expected.a).abs() < 1e-6);
+        assert!((oren_nayar.b - expected.b).abs() < 1e-6);
+    } else {
+        unreachable!()
+    }
+    // the scale (used when mixing materials) applies to the BxDF
+    let scaled: Bsdf = bsdf(&material, 0.0, Some(Spectrum::new(0.25)));
+    let f_scaled: Float = scaled.f(&wo, &wi, all).c[0];
+    assert!((f_scaled - 0.25 * f).abs() < 1e-6, "{}", f_scaled);
+    // black surfaces don't scatter at all
+    let black: Material = Material::Matte(Box::new(MatteMaterial::new(
+        Arc::new(ConstantTexture::new(Spectrum::new(0.0))),
+        Arc::new(ConstantTexture::new(20.0 as Float)),
+        None,
+    )));
+    assert_eq!(bsdf(&black, 0.5, None).num_components(all), 0);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{plane, texture_params, vector};
+use pbrt::core::geometry::{Point2f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::microfacet::TrowbridgeReitzDistribution;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::reflection::{Bsdf, Bxdf, BxdfType, Fresnel, FresnelConductor};
+use pbrt::materials::metal::{
+    MetalMaterial, COPPER_K, COPPER_N, COPPER_SAMPLES, COPPER_WAVELENGTHS,
+};
+use pbrt::textures::constant::ConstantTexture;
+
+/// Fresnel reflectance at normal incidence.
+fn reflectance(eta: Spectrum, k: Spectrum) -> Spectrum {
+    Fresnel::Conductor(FresnelConductor {
+        eta_i: Spectrum::new(1.0),
+        eta_t: eta,
+        k,
+    })
+    .evaluate(1.0)
+}
+
+fn create(params: ParamSet) -> Arc<Material> {
+    MetalMaterial::create(&mut texture_params(params))
+}
+
+/// Index of refraction and absorption coefficient of a metal.
+fn spectra(material: &Material) -> (Spectrum, Spectrum) {
+    if let Material::Metal(ref metal) = *material {
+        let si: SurfaceInteraction = SurfaceInteraction::default();
+        (metal.eta.evaluate(&si), metal.k.evaluate(&si))
+    } else {
+        panic!("MetalMaterial::create() didn't create a metal");
+    }
+}
+
+fn bsdf(material: &Material) -> Bsdf {
+    let mut si: SurfaceInteraction = plane(Point2f::default());
+    material.compute_scattering_functions(&mut si, TransportMode::Radiance, false, None, None);
+    si.bsdf.unwrap()
+}
+
+fn main() {
+    // colored metals reflect more red than blue light ...
+    for &name in &["copper", "gold"] {
+        let (eta, k) = MetalMaterial::named_spectra(name).unwrap();
+        let r: Spectrum = reflectance(eta, k);
+        println!("{}: {:?}", name, r.c);
+        assert!(r.c[0] > r.c[1] && r.c[1] > r.c[2]);
+        assert!(r.c[0] > 0.8 && r.c[2] < 0.7);
+    }
+    // ... white metals reflect (almost) everything
+    for &name in &["aluminium", "silver"] {
+        let (eta, k) = MetalMaterial::named_spectra(name).unwrap();
+        let r: Spectrum = reflectance(eta, k);
+        println!("{}: {:?}", name, r.c);
+        for c in 0..3 {
+            assert!(r.c[c] > 0.85 && (r.c[c] - r.c[1]).abs() < 0.05);
+        }
+    }
+    assert!(MetalMaterial::named_spectra("aluminum") == MetalMaterial::named_spectra("aluminium"));
+    assert!(MetalMaterial::named_spectra("unobtainium").is_none());
+    // measured copper is the default ...
+    let copper_n: Spectrum =
+        Spectrum::from_sampled(&COPPER_WAVELENGTHS, &COPPER_N, COPPER_SAMPLES as i32);
+    let copper_k: Spectrum =
+        Spectrum::from_sampled(&COPPER_WAVELENGTHS, &COPPER_K, COPPER_SAMPLES as i32);
+    assert!(spectra(&create(ParamSet::default())) == (copper_n, copper_k));
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("metal"), String::from("unobtainium"));
+    assert!(spectra(&create(params)) == (copper_n, copper_k));
+    // ... other metals are selected by name, and explicit spectra
+    // still win
+    let gold: (Spectrum, Spectrum) = MetalMaterial::named_spectra("gold").unwrap();
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("metal"), String::from("gold"));
+    assert!(spectra(&create(params)) == gold);
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("metal"), String::from("gold"));
+    params.add_rgb_spectrum(String::from("eta"), Spectrum::new(0.2));
+    assert!(spectra(&create(params)) == (Spectrum::new(0.2), gold.1));
+    // a single microfacet lobe, with the roughness remapped like for
+    // other Trowbridge-Reitz materials, "uroughness" and "vroughness"
+    // replace "roughness"
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("roughness"), 0.3);
+    params.add_float(String::from("uroughness"), 0.1);
+    let rough: Bsdf = bsdf(&create(params));
+    let all: u8 = BxdfType::BsdfAll as u8;
+    assert_eq!(rough.num_components(all), 1);
+    match rough.bxdfs[0] {
+        Bxdf::MicrofacetRefl(_) => {}
+        _ => panic!("expected a microfacet BxDF"),
+    }
+    let expected: Bsdf = bsdf(&Material::Metal(Box::new(MetalMaterial::new(
+        Arc::new(ConstantTexture::new(copper_n)),
+        Arc::new(ConstantTexture::new(copper_k)),
+        Arc::new(ConstantTexture::new(0.0 as Float)),
+        Some(Arc::new(ConstantTexture::new(
+            TrowbridgeReitzDistribution::roughness_to_alpha(0.1),
+        ))),
+        Some(Arc::new(ConstantTexture::new(
+            TrowbridgeReitzDistribution::roughness_to_alpha(0.3),
+        ))),
+        None,
+        false,
+    ))));
+    let wo: Vector3f = vector(0.6, 0.0, 0.8);
+    for &wi in &[
+        vector(-0.6, 0.0, 0.8),
+        vector(-0.5, 0.2, 0.7),
+        vector(-0.2, -0.6, 0.5),
+    ] {
+        let f: Spectrum = rough.f(&wo, &wi, all);
+        let f_expected: Spectrum = expected.f(&wo, &wi, all);
+        for c in 0..3 {
+            assert!((f.c[c] - f_expected.c[c]).abs() < 1e-4 * f_expected.c[c]);
+        }
+    }
+}
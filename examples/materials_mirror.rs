@@ -0,0 +1,85 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{plane, texture_params, vector};
+use pbrt::core::geometry::{Point2f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::reflection::{Bsdf, BxdfType};
+use pbrt::core::texture::Texture;
+use pbrt::materials::mirror::MirrorMaterial;
+
+/// Reflects in the left half of the (u, v) domain only.
+struct LeftHalf {}
+
+impl Texture<Spectrum> for LeftHalf {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Spectrum {
+        if si.uv.x < 0.5 {
+            Spectrum::new(0.2)
+        } else {
+            Spectrum::new(0.0)
+        }
+    }
+}
+
+fn bsdf(material: &Material, uv: Point2f) -> Bsdf {
+    let mut si: SurfaceInteraction = plane(uv);
+    material.compute_scattering_functions(&mut si, TransportMode::Radiance, false, None, None);
+    si.bsdf.unwrap()
+}
+
+/// Reflectance when sampling the BSDF for *wo*.
+fn reflectance(bsdf: &Bsdf, wo: &Vector3f) -> Spectrum {
+    let mut wi: Vector3f = Vector3f::default();
+    let mut pdf: Float = 0.0;
+    let mut sampled_type: u8 = u8::max_value(); // != 0
+    let f: Spectrum = bsdf.sample_f(
+        wo,
+        &mut wi,
+        Point2f { x: 0.5, y: 0.5 },
+        &mut pdf,
+        BxdfType::BsdfAll as u8,
+        &mut sampled_type,
+    );
+    if pdf == 0.0 {
+        return Spectrum::new(0.0);
+    }
+    // perfect mirror direction, sampled as a specular bounce
+    assert!((wi.x + wo.x).abs() < 1e-6 && (wi.y + wo.y).abs() < 1e-6);
+    assert!((wi.z - wo.z).abs() < 1e-6);
+    assert_ne!(sampled_type & BxdfType::BsdfSpecular as u8, 0);
+    f * wi.z.abs() / pdf
+}
+
+fn main() {
+    let all: u8 = BxdfType::BsdfAll as u8;
+    let non_specular: u8 = all & !(BxdfType::BsdfSpecular as u8);
+    // "Kr" defaults to 0.9, reflected at all angles
+    let mirror: Arc<Material> = MirrorMaterial::create(&mut texture_params(ParamSet::default()));
+    let default: Bsdf = bsdf(&mirror, Point2f::default());
+    assert_eq!(default.num_components(all), 1);
+    // nothing for integrators to sample lights with
+    assert_eq!(default.num_components(non_specular), 0);
+    for &wo in &[
+        vector(0.0, 0.0, 1.0),
+        vector(0.6, 0.0, 0.8),
+        vector(-0.3, 0.9, 0.1),
+    ] {
+        let r: Spectrum = reflectance(&default, &wo);
+        assert!((r.c[0] - 0.9).abs() < 1e-5, "{:?}", r.c);
+        assert!(default.f(&wo, &vector(-wo.x, -wo.y, wo.z), all).is_black());
+    }
+    // the reflectance is a texture ...
+    let painted: Material =
+        Material::Mirror(Box::new(MirrorMaterial::new(Arc::new(LeftHalf {}), None)));
+    let left: Bsdf = bsdf(&painted, Point2f { x: 0.25, y: 0.5 });
+    let r: Spectrum = reflectance(&left, &vector(0.6, 0.0, 0.8));
+    assert!((r.c[0] - 0.2).abs() < 1e-5, "{:?}", r.c);
+    // ... and regions which don't reflect get an empty BSDF
+    let right: Bsdf = bsdf(&painted, Point2f { x: 0.75, y: 0.5 });
+    assert_eq!(right.num_components(all), 0);
+    assert!(reflectance(&right, &vector(0.6, 0.0, 0.8)).is_black());
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common::{plane, texture_params, vector};
+use pbrt::core::geometry::{Point2f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::reflection::{Bsdf, Bxdf, BxdfType};
+use pbrt::core::rng::Rng;
+use pbrt::core::texture::Texture;
+use pbrt::materials::matte::MatteMaterial;
+use pbrt::materials::mixmat::MixMaterial;
+use pbrt::materials::plastic::PlasticMaterial;
+use pbrt::textures::constant::ConstantTexture;
+
+/// Mask increasing along *u*.
+struct MaskRamp {}
+
+impl Texture<Spectrum> for MaskRamp {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Spectrum {
+        Spectrum::new(si.uv.x)
+    }
+}
+
+fn matte(kd: Float) -> Arc<Material> {
+    Arc::new(Material::Matte(Box::new(MatteMaterial::new(
+        Arc::new(ConstantTexture::new(Spectrum::new(kd))),
+        Arc::new(ConstantTexture::new(0.0 as Float)),
+        None,
+    ))))
+}
+
+/// Plastic with a diffuse and a glossy lobe.
+fn plastic() -> Arc<Material> {
+    Arc::new(Material::Plastic(Box::new(PlasticMaterial::new(
+        Arc::new(ConstantTexture::new(Spectrum::new(0.4))),
+        Arc::new(ConstantTexture::new(Spectrum::new(0.6))),
+        Arc::new(ConstantTexture::new(0.2 as Float)),
+        None,
+        true,
+    ))))
+}
+
+fn mix(m1: Arc<Material>, m2: Arc<Material>, amount: Float) -> Arc<Material> {
+    Arc::new(Material::Mix(Box::new(MixMaterial::new(
+        m1,
+        m2,
+        Arc::new(ConstantTexture::new(Spectrum::new(amount))),
+    ))))
+}
+
+fn bsdf(material: &Material, u: Float) -> Bsdf {
+    let mut si: SurfaceInteraction = plane(Point2f { x: u, y: 0.0 });
+    material.compute_scattering_functions(&mut si, TransportMode::Radiance, false, None, None);
+    si.bsdf.unwrap()
+}
+
+fn assert_close(a: Spectrum, b: Spectrum) {
+    for c in 0..3 {
+        assert!((a.c[c] - b.c[c]).abs() < 1e-5, "{:?} != {:?}", a.c, b.c);
+    }
+}
+
+fn main() {
+    let all: u8 = BxdfType::BsdfAll as u8;
+    let wo: Vector3f = vector(0.6, 0.0, 0.8);
+    let directions: [Vector3f; 3] = [
+        vector(-0.6, 0.0, 0.8),
+        vector(-0.5, 0.2, 0.7),
+        vector(0.1, -0.6, 0.4),
+    ];
+    // the mixed BSDF keeps all lobes of both materials (one for matte,
+    // two for plastic), each scaled by its material's weight
+    let mixed: Bsdf = bsdf(&mix(matte(0.5), plastic(), 0.3), 0.0);
+    assert_eq!(mixed.num_components(all), 3);
+    let f_matte: Bsdf = bsdf(&matte(0.5), 0.0);
+    let f_plastic: Bsdf = bsdf(&plastic(), 0.0);
+    for wi in &directions {
+        assert_close(
+            mixed.f(&wo, wi, all),
+            f_matte.f(&wo, wi, all) * Spectrum::new(0.3)
+                + f_plastic.f(&wo, wi, all) * Spectrum::new(0.7),
+        );
+    }
+    // sampling a scaled lobe returns the same (scaled once) value as
+    // evaluating it
+    let mut rng: Rng = Rng::new();
+    for bxdf in mixed.bxdfs.iter() {
+        if let Bxdf::Empty(_) = bxdf {
+            break;
+        }
+        for _ in 0..100 {
+            let u: Point2f = Point2f {
+                x: rng.uniform_float(),
+                y: rng.uniform_float(),
+            };
+            let mut wi: Vector3f = Vector3f::default();
+            let mut pdf: Float = 0.0;
+            let mut sampled_type: u8 = 0;
+            let f: Spectrum = bxdf.sample_f(&wo, &mut wi, u, &mut pdf, &mut sampled_type);
+            if pdf > 0.0 {
+                assert_close(f, bxdf.f(&wo, &wi));
+            }
+        }
+    }
+    // the mask is evaluated where the surface is hit
+    let masked: Arc<Material> = Arc::new(Material::Mix(Box::new(MixMaterial::new(
+        matte(0.5),
+        plastic(),
+        Arc::new(MaskRamp {}),
+    ))));
+    for &u in &[0.0, 0.25, 1.0] {
+        let mixed: Bsdf = bsdf(&masked, u);
+        let wi: &Vector3f = &directions[1];
+        assert_close(
+            mixed.f(&wo, wi, all),
+            f_matte.f(&wo, wi, all) * Spectrum::new(u)
+                + f_plastic.f(&wo, wi, all) * Spectrum::new(1.0 - u),
+        );
+    }
+    // nested mix materials multiply the weights
+    let f_dark: Bsdf = bsdf(&matte(0.1), 0.0);
+    let nested: Bsdf = bsdf(&mix(mix(matte(0.5), plastic(), 0.3), matte(0.1), 0.6), 0.0);
+    assert_eq!(nested.num_components(all), 4);
+    for wi in &directions {
+        assert_close(
+            nested.f(&wo, wi, all),
+            f_matte.f(&wo, wi, all) * Spectrum::new(0.6 * 0.3)
+                + f_plastic.f(&wo, wi, all) * Spectrum::new(0.6 * 0.7)
+                + f_dark.f(&wo, wi, all) * Spectrum::new(0.4),
+        );
+    }
+    // lobes which don't fit into the BSDF are dropped
+    let mut many: Arc<Material> = plastic();
+    for _ in 0..4 {
+        many = mix(many, plastic(), 0.5);
+    }
+    assert_eq!(bsdf(&many, 0.0).num_components(all), 8);
+    // create() looks up both named materials and reads "amount"
+    let mut named_materials: HashMap<String, Option<Arc<Material>>> = HashMap::new();
+    named_materials.insert(String::from("dull"), Some(matte(0.5)));
+    named_materials.insert(String::from("shiny"), Some(plastic()));
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("namedmaterial1"), String::from("dull"));
+    params.add_string(String::from("namedmaterial2"), String::from("shiny"));
+    params.add_rgb_spectrum(String::from("amount"), Spectrum::new(0.3));
+    let created: Arc<Material> =
+        MixMaterial::create(&mut texture_params(params), &named_materials).unwrap();
+    let wi: &Vector3f = &directions[2];
+    assert_close(
+        bsdf(&created, 0.0).f(&wo, wi, all),
+        bsdf(&mix(matte(0.5), plastic(), 0.3), 0.0).f(&wo, wi, all),
+    );
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("namedmaterial1"), String::from("dull"));
+    params.add_string(String::from("namedmaterial2"), String::from("missing"));
+    assert!(MixMaterial::create(&mut texture_params(params), &named_materials).is_none());
+}
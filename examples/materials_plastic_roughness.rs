@@ -0,0 +1,107 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{plane, vector};
+use pbrt::core::geometry::{Point2f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::microfacet::TrowbridgeReitzDistribution;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::reflection::{Bsdf, Bxdf, BxdfType};
+use pbrt::core::rng::Rng;
+use pbrt::materials::plastic::PlasticMaterial;
+use pbrt::textures::constant::ConstantTexture;
+
+fn plastic(kd: Float, ks: Float, roughness: Float, remap_roughness: bool) -> Material {
+    Material::Plastic(Box::new(PlasticMaterial::new(
+        Arc::new(ConstantTexture::new(Spectrum::new(kd))),
+        Arc::new(ConstantTexture::new(Spectrum::new(ks))),
+        Arc::new(ConstantTexture::new(roughness)),
+        None,
+        remap_roughness,
+    )))
+}
+
+fn bsdf(material: &Material) -> Bsdf {
+    let mut si: SurfaceInteraction = plane(Point2f::default());
+    material.compute_scattering_functions(&mut si, TransportMode::Radiance, false, None, None);
+    si.bsdf.unwrap()
+}
+
+/// Samples the BSDF from *wo* and returns the mean angle (in
+/// radians) between the sampled directions and the mirror direction,
+/// checking that all values are finite.
+fn mean_deviation(bsdf: &Bsdf, wo: &Vector3f, rng: &mut Rng) -> Float {
+    let mirror: Vector3f = Vector3f {
+        x: -wo.x,
+        y: -wo.y,
+        z: wo.z,
+    };
+    let all: u8 = BxdfType::BsdfAll as u8;
+    let n_samples: usize = 1000;
+    let mut sum: Float = 0.0;
+    for _ in 0..n_samples {
+        let u: Point2f = Point2f {
+            x: rng.uniform_float(),
+            y: rng.uniform_float(),
+        };
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let mut sampled_type: u8 = 0;
+        let f: Spectrum = bsdf.sample_f(wo, &mut wi, u, &mut pdf, all, &mut sampled_type);
+        assert!(pdf.is_finite() && f.c[0].is_finite(), "{} {:?}", pdf, f.c);
+        if pdf > 0.0 {
+            let cos: Float = (wi.x * mirror.x + wi.y * mirror.y + wi.z * mirror.z).min(1.0);
+            sum += cos.acos();
+        }
+    }
+    sum / n_samples as Float
+}
+
+fn main() {
+    let wo: Vector3f = vector(0.6, 0.0, 0.8);
+    let all: u8 = BxdfType::BsdfAll as u8;
+    let mut rng: Rng = Rng::new();
+    // a diffuse and a glossy lobe
+    let both: Bsdf = bsdf(&plastic(0.5, 0.5, 0.1, true));
+    assert_eq!(both.num_components(all), 2);
+    match (&both.bxdfs[0], &both.bxdfs[1]) {
+        (Bxdf::LambertianRefl(_), Bxdf::MicrofacetRefl(_)) => {}
+        _ => panic!("expected a Lambertian and a microfacet BxDF"),
+    }
+    assert_eq!(bsdf(&plastic(0.5, 0.0, 0.1, true)).num_components(all), 1);
+    assert_eq!(bsdf(&plastic(0.0, 0.5, 0.1, true)).num_components(all), 1);
+    // remapped roughness goes through roughness_to_alpha()
+    let remapped: Bsdf = bsdf(&plastic(0.0, 1.0, 0.3, true));
+    let raw: Bsdf = bsdf(&plastic(0.0, 1.0, 0.3, false));
+    let alpha: Float = TrowbridgeReitzDistribution::roughness_to_alpha(0.3);
+    let expected: Bsdf = bsdf(&plastic(0.0, 1.0, alpha, false));
+    let wi: Vector3f = vector(-0.5, 0.1, 0.7);
+    assert!((remapped.f(&wo, &wi, all).c[0] - expected.f(&wo, &wi, all).c[0]).abs() < 1e-5);
+    assert!((remapped.f(&wo, &wi, all).c[0] - raw.f(&wo, &wi, all).c[0]).abs() > 1e-3);
+    // the glossy lobe narrows with the roughness ...
+    let mut last: Float = std::f32::INFINITY;
+    for &roughness in &[0.5, 0.1, 0.01, 0.0] {
+        let deviation: Float =
+            mean_deviation(&bsdf(&plastic(0.0, 1.0, roughness, false)), &wo, &mut rng);
+        println!("roughness {}: {} radians", roughness, deviation);
+        assert!(deviation <= last);
+        last = deviation;
+    }
+    // ... down to a near-mirror, without infinite or NaN values
+    assert!(last < 1e-2, "{}", last);
+    let smooth: Bsdf = bsdf(&plastic(0.0, 1.0, 0.0, false));
+    for &wi in &[
+        vector(-0.6, 0.0, 0.8),
+        vector(-0.5, 0.1, 0.7),
+        vector(0.0, 0.0, 1.0),
+        vector(1.0, 0.0, 1e-4),
+    ] {
+        let f: Float = smooth.f(&wo, &wi, all).c[0];
+        let pdf: Float = smooth.pdf(&wo, &wi, all);
+        assert!(f.is_finite() && pdf.is_finite(), "{} {}", f, pdf);
+    }
+    // a remapped roughness of zero is clamped, too
+    mean_deviation(&bsdf(&plastic(0.5, 1.0, 0.0, true)), &wo, &mut rng);
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{plane, texture_params, vector};
+use pbrt::core::geometry::{Point2f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{radians, Float, Spectrum};
+use pbrt::core::reflection::{Bsdf, Bxdf, BxdfType, FresnelBlend};
+use pbrt::materials::substrate::SubstrateMaterial;
+use std::f32::consts::PI;
+
+/// Direction *theta* degrees away from the normal, towards
+/// (*x*, *y*).
+fn tilted(theta: Float, x: Float, y: Float) -> Vector3f {
+    let (sin_theta, cos_theta) = radians(theta).sin_cos();
+    vector(sin_theta * x, sin_theta * y, cos_theta)
+}
+
+fn create(params: ParamSet) -> Arc<Material> {
+    SubstrateMaterial::create(&mut texture_params(params))
+}
+
+fn bsdf(material: &Material) -> Bsdf {
+    let mut si: SurfaceInteraction = plane(Point2f::default());
+    material.compute_scattering_functions(&mut si, TransportMode::Radiance, false, None, None);
+    si.bsdf.unwrap()
+}
+
+/// Fraction of the light from *wo* which is reflected (midpoint rule
+/// over the hemisphere).
+fn albedo<F: Fn(&Vector3f) -> Float>(f: F) -> Float {
+    let (n_theta, n_phi) = (400, 128);
+    let mut sum: Float = 0.0;
+    for i in 0..n_theta {
+        let cos_theta: Float = (i as Float + 0.5) / n_theta as Float;
+        let sin_theta: Float = (1.0 - cos_theta * cos_theta).sqrt();
+        for j in 0..n_phi {
+            let phi: Float = 2.0 * PI * (j as Float + 0.5) / n_phi as Float;
+            let wi: Vector3f = Vector3f {
+                x: sin_theta * phi.cos(),
+                y: sin_theta * phi.sin(),
+                z: cos_theta,
+            };
+            sum += f(&wi) * cos_theta * 2.0 * PI / (n_theta * n_phi) as Float;
+        }
+    }
+    sum
+}
+
+/// The same by importance sampling the BxDF.
+fn sampled_albedo(blend: &FresnelBlend, wo: &Vector3f) -> Float {
+    let n: usize = 128;
+    let mut sum: Float = 0.0;
+    for i in 0..n {
+        for j in 0..n {
+            let u: Point2f = Point2f {
+                x: (i as Float + 0.5) / n as Float,
+                y: (j as Float + 0.5) / n as Float,
+            };
+            let mut wi: Vector3f = Vector3f::default();
+            let mut pdf: Float = 0.0;
+            let mut sampled_type: u8 = 0;
+            let f: Spectrum = blend.sample_f(wo, &mut wi, u, &mut pdf, &mut sampled_type);
+            if pdf > 0.0 {
+                assert!((pdf - blend.pdf(wo, &wi)).abs() < 1e-4 * pdf);
+                sum += f.c[0] * wi.z.abs() / pdf;
+            }
+        }
+    }
+    sum / (n * n) as Float
+}
+
+fn main() {
+    let all: u8 = BxdfType::BsdfAll as u8;
+    // a single glossy FresnelBlend lobe
+    let default: Bsdf = bsdf(&create(ParamSet::default()));
+    assert_eq!(default.num_components(all), 1);
+    assert_eq!(
+        default.num_components(BxdfType::BsdfReflection as u8 | BxdfType::BsdfGlossy as u8),
+        1
+    );
+    // white substrates reflect (almost) everything the coating lets
+    // through, but never more than they receive, from any direction
+    for &ks in &[0.0, 0.04, 0.5, 1.0] {
+        let mut params: ParamSet = ParamSet::default();
+        params.add_rgb_spectrum(String::from("Kd"), Spectrum::new(1.0));
+        params.add_rgb_spectrum(String::from("Ks"), Spectrum::new(ks));
+        params.add_float(String::from("uroughness"), 0.3);
+        params.add_float(String::from("vroughness"), 0.3);
+        params.add_bool(String::from("remaproughness"), false);
+        let coated: Bsdf = bsdf(&create(params));
+        for &theta in &[0.0, 45.0, 75.0, 89.0] {
+            let wo: Vector3f = tilted(theta, 1.0, 0.0);
+            let reflected: Float = albedo(|wi| coated.f(&wo, wi, all).c[0]);
+            assert!(
+                reflected > 0.1 && reflected < 1.0,
+                "{} {}: {}",
+                ks,
+                theta,
+                reflected
+            );
+            if theta == 0.0 && ks == 0.0 {
+                assert!(reflected > 0.95, "{}", reflected);
+            }
+            // importance sampling the blend gives the same estimate
+            if theta == 45.0 {
+                if let Bxdf::FresnelBlnd(ref blend) = coated.bxdfs[0] {
+                    let sampled: Float = sampled_albedo(blend, &wo);
+                    assert!(
+                        (sampled - reflected).abs() < 0.02,
+                        "{}: {} vs. {}",
+                        ks,
+                        sampled,
+                        reflected
+                    );
+                } else {
+                    panic!("expected a FresnelBlend");
+                }
+            }
+        }
+    }
+    // anisotropic roughness stretches highlights along the rougher
+    // direction ("uroughness" along the first tangent)
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("uroughness"), 0.05);
+    params.add_float(String::from("vroughness"), 0.3);
+    params.add_bool(String::from("remaproughness"), false);
+    let brushed: Bsdf = bsdf(&create(params));
+    let wo: Vector3f = vector(0.0, 0.0, 1.0);
+    let along_u: Float = brushed.f(&wo, &tilted(10.0, 1.0, 0.0), all).c[0];
+    let along_v: Float = brushed.f(&wo, &tilted(10.0, 0.0, 1.0), all).c[0];
+    assert!(along_v > 2.0 * along_u, "{} vs. {}", along_u, along_v);
+    // without a coating only the substrate is sampled
+    let bare: FresnelBlend = FresnelBlend::new(Spectrum::new(0.5), Spectrum::new(0.0), None, None);
+    let wo: Vector3f = tilted(30.0, 1.0, 0.0);
+    let reflected: Float = albedo(|wi| bare.f(&wo, wi).c[0]);
+    assert!((sampled_albedo(&bare, &wo) - reflected).abs() < 0.01);
+}
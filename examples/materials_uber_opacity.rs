@@ -0,0 +1,124 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{plane, texture_params, vector};
+use pbrt::core::geometry::{Point2f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::material::{Material, TransportMode};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::reflection::{Bsdf, Bxdf, BxdfType, FresnelSpecular, SpecularTransmission};
+use pbrt::materials::uber::UberMaterial;
+
+fn create(params: ParamSet) -> Arc<Material> {
+    UberMaterial::create(&mut texture_params(params))
+}
+
+fn bsdf(material: &Material) -> Bsdf {
+    let mut si: SurfaceInteraction = plane(Point2f::default());
+    material.compute_scattering_functions(&mut si, TransportMode::Radiance, false, None, None);
+    si.bsdf.unwrap()
+}
+
+fn main() {
+    let all: u8 = BxdfType::BsdfAll as u8;
+    let non_specular: u8 = all & !(BxdfType::BsdfSpecular as u8);
+    let specular: u8 = all & !(BxdfType::BsdfDiffuse as u8 | BxdfType::BsdfGlossy as u8);
+    // defaults: diffuse and glossy, index of refraction 1.5
+    let default: Bsdf = bsdf(&create(ParamSet::default()));
+    assert_eq!(default.num_components(all), 2);
+    match (&default.bxdfs[0], &default.bxdfs[1]) {
+        (Bxdf::LambertianRefl(_), Bxdf::MicrofacetRefl(_)) => {}
+        _ => panic!("unexpected lobes"),
+    }
+    assert_eq!(default.eta, 1.5);
+    // "index" is read if there is no "eta"
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("index"), 1.33);
+    assert_eq!(bsdf(&create(params)).eta, 1.33);
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("index"), 1.33);
+    params.add_float(String::from("eta"), 2.0);
+    assert_eq!(bsdf(&create(params)).eta, 2.0);
+    // "Kr" and "Kt" add perfectly specular lobes
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("Kr"), Spectrum::new(1.0));
+    params.add_rgb_spectrum(String::from("Kt"), Spectrum::new(1.0));
+    let layered: Bsdf = bsdf(&create(params));
+    assert_eq!(layered.num_components(all), 4);
+    assert_eq!(layered.num_components(specular), 2);
+    // partially opaque surfaces let the rest pass through unchanged,
+    // sampled as a specular event ...
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("opacity"), Spectrum::new(0.25));
+    let cutout: Bsdf = bsdf(&create(params));
+    assert_eq!(cutout.num_components(all), 3);
+    assert_eq!(cutout.num_components(non_specular), 2);
+    assert_eq!(cutout.eta, 1.0);
+    let wo: Vector3f = vector(0.6, 0.0, 0.8);
+    let mut wi: Vector3f = Vector3f::default();
+    let mut pdf: Float = 0.0;
+    let mut sampled_type: u8 = u8::max_value(); // != 0
+    let f: Spectrum = cutout.sample_f(
+        &wo,
+        &mut wi,
+        Point2f { x: 0.1, y: 0.5 },
+        &mut pdf,
+        all,
+        &mut sampled_type,
+    );
+    assert_eq!(
+        sampled_type,
+        BxdfType::BsdfTransmission as u8 | BxdfType::BsdfSpecular as u8
+    );
+    assert!((wi.x + wo.x).abs() < 1e-6 && (wi.y + wo.y).abs() < 1e-6 && (wi.z + wo.z).abs() < 1e-6);
+    // (picked one out of three components)
+    let throughput: Float = f.c[0] * wi.z.abs() / pdf;
+    assert!((throughput / 3.0 - 0.75).abs() < 1e-5, "{}", throughput);
+    assert!(cutout.f(&wo, &wi, all).is_black());
+    // ... which doesn't count towards the pdf of the other lobes
+    let mut sampled_type: u8 = u8::max_value(); // != 0
+    let f: Spectrum = cutout.sample_f(
+        &wo,
+        &mut wi,
+        Point2f { x: 0.5, y: 0.3 },
+        &mut pdf,
+        all,
+        &mut sampled_type,
+    );
+    assert_eq!(
+        sampled_type,
+        BxdfType::BsdfReflection as u8 | BxdfType::BsdfDiffuse as u8
+    );
+    assert!(!f.is_black() && wi.z > 0.0);
+    let expected: Float = (cutout.bxdfs[1].pdf(&wo, &wi) + cutout.bxdfs[2].pdf(&wo, &wi)) / 3.0;
+    assert!(
+        (pdf - expected).abs() < 1e-5 * expected,
+        "{} vs. {}",
+        pdf,
+        expected
+    );
+    assert!((cutout.pdf(&wo, &wi, all) - expected).abs() < 1e-5 * expected);
+    let reflected: Vector3f = vector(-wo.x, -wo.y, wo.z);
+    let passthrough: SpecularTransmission =
+        SpecularTransmission::new(Spectrum::new(1.0), 1.0, 1.0, TransportMode::Radiance, None);
+    assert_eq!(passthrough.pdf(&wo, &reflected), 0.0);
+    let glass: FresnelSpecular = FresnelSpecular::new(
+        Spectrum::new(1.0),
+        Spectrum::new(1.0),
+        1.0,
+        1.5,
+        TransportMode::Radiance,
+        None,
+    );
+    assert_eq!(glass.pdf(&wo, &reflected), 0.0);
+    // fully transparent regions leave nothing for integrators to sample
+    // lights with
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("opacity"), Spectrum::new(0.0));
+    let hole: Bsdf = bsdf(&create(params));
+    assert_eq!(hole.num_components(all), 1);
+    assert_eq!(hole.num_components(non_specular), 0);
+    assert!(hole.f(&wo, &reflected, all).is_black());
+}
@@ -0,0 +1,69 @@
+use pbrt::core::geometry::{vec3_dot_vec3, Point2f, Vector3f};
+use pbrt::core::medium::{phase_hg, HenyeyGreenstein};
+use pbrt::core::pbrt::Float;
+use pbrt::core::rng::Rng;
+
+const ASYMMETRIES: [Float; 7] = [-0.9, -0.3, -0.0005, 0.0, 0.0005, 0.5, 0.95];
+
+fn main() {
+    let wo: Vector3f = Vector3f {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    }
+    .normalize();
+    let mut rng: Rng = Rng::new();
+    for &g in ASYMMETRIES.iter() {
+        let phase: HenyeyGreenstein = HenyeyGreenstein { g };
+        // normalized: the integral over the sphere (of a function of
+        // the cosine only) is one
+        let steps: usize = 100_000;
+        let mut integral: f64 = 0.0;
+        for i in 0..steps {
+            let cos_theta: Float = -1.0 + 2.0 * (i as Float + 0.5) / steps as Float;
+            integral += phase_hg(cos_theta, g) as f64 * 2.0 / steps as f64;
+        }
+        integral *= 2.0 * std::f64::consts::PI;
+        assert!((integral - 1.0).abs() < 1e-3, "g = {}: {}", g, integral);
+        // sampled directions are unit vectors, the returned pdf is the
+        // phase function, and the mean cosine (to the propagation
+        // direction -wo) is g
+        let samples: usize = 100_000;
+        let mut mean_cos: f64 = 0.0;
+        for _ in 0..samples {
+            let u: Point2f = Point2f {
+                x: rng.uniform_float(),
+                y: rng.uniform_float(),
+            };
+            let mut wi: Vector3f = Vector3f::default();
+            let pdf: Float = phase.sample_p(&wo, &mut wi, u);
+            assert!((wi.length() - 1.0).abs() < 1e-4, "{:?}", wi);
+            let p: Float = phase.p(&wo, &wi);
+            assert!((pdf - p).abs() <= 2e-3 * p.max(1.0), "{} != {}", pdf, p);
+            mean_cos -= vec3_dot_vec3(&wo, &wi) as f64 / samples as f64;
+        }
+        assert!(
+            (mean_cos - g as f64).abs() < 0.01,
+            "g = {}: {}",
+            g,
+            mean_cos
+        );
+        // the extreme sample values are well defined, too
+        for &(x, y) in &[(0.0, 0.0), (0.999_999_94, 0.999_999_94)] {
+            let mut wi: Vector3f = Vector3f::default();
+            let pdf: Float = phase.sample_p(&wo, &mut wi, Point2f { x, y });
+            assert!(pdf.is_finite() && pdf > 0.0, "g = {}: {}", g, pdf);
+            assert!((wi.length() - 1.0).abs() < 1e-3, "{:?}", wi);
+        }
+    }
+    // forward scattering keeps the direction of propagation ...
+    let forward: HenyeyGreenstein = HenyeyGreenstein { g: 0.9 };
+    assert!(forward.p(&wo, &(-wo)) > forward.p(&wo, &wo));
+    // ... and the phase function is symmetric in its arguments
+    let wi: Vector3f = Vector3f {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    assert_eq!(forward.p(&wo, &wi), forward.p(&wi, &wo));
+}
@@ -0,0 +1,107 @@
+use pbrt::core::geometry::{Point2i, Point3f, Ray, Vector3f};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::sampler::Sampler;
+use pbrt::media::homogeneous::HomogeneousMedium;
+use pbrt::samplers::random::RandomSampler;
+
+const SAMPLES: usize = 200_000;
+
+/// Ray along +x with a direction of length two, so *t_max* covers
+/// twice the distance.
+fn ray(t_max: Float) -> Ray {
+    Ray {
+        o: Point3f::default(),
+        d: Vector3f {
+            x: 2.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        t_max,
+        time: 0.0,
+        medium: None,
+        differential: None,
+    }
+}
+
+fn assert_close(a: Float, b: Float, epsilon: Float) {
+    assert!((a - b).abs() < epsilon, "{} != {}", a, b);
+}
+
+fn main() {
+    let mut sampler: Sampler = Sampler::Random(RandomSampler::new(1));
+    sampler.reseed(1_u64);
+    sampler.start_pixel(Point2i::default());
+    // very different extinction per channel
+    let sigma_t: [Float; 3] = [0.1, 1.0, 5.0];
+    let sigma_a: Spectrum = Spectrum::rgb(0.02, 0.2, 1.0);
+    let sigma_s: Spectrum = Spectrum::rgb(0.08, 0.8, 4.0);
+    let medium: HomogeneousMedium = HomogeneousMedium::new(&sigma_a, &sigma_s, 0.3);
+    // Beer-Lambert, measured in world space distance
+    let tr: Spectrum = medium.tr(&ray(0.25), &mut sampler);
+    for c in 0..3 {
+        assert_close(tr.c[c], (-sigma_t[c] * 0.5).exp(), 1e-6);
+    }
+    // infinite rays are opaque, unless there is no extinction
+    let tr: Spectrum = medium.tr(&ray(std::f32::INFINITY), &mut sampler);
+    assert!(tr.is_black());
+    let vacuum: HomogeneousMedium =
+        HomogeneousMedium::new(&Spectrum::new(0.0), &Spectrum::rgb(0.0, 0.0, 1.0), 0.0);
+    let tr: Spectrum = vacuum.tr(&ray(std::f32::INFINITY), &mut sampler);
+    assert_eq!((tr.c[0], tr.c[1]), (1.0, 1.0));
+    for _ in 0..100 {
+        let (beta, mi) = vacuum.sample(&ray(std::f32::INFINITY), &mut sampler);
+        assert!(beta.c.iter().all(|v| v.is_finite()), "{:?}", beta.c);
+        if let Some(mi) = mi {
+            assert!(mi.common.p.x.is_finite());
+        }
+    }
+    // sampling is unbiased per channel: passing through weighs in the
+    // transmittance, scattering the in-scattered fraction
+    let t_max: Float = 0.5;
+    let mut passed: [Float; 3] = [0.0; 3];
+    let mut scattered: [Float; 3] = [0.0; 3];
+    for _ in 0..SAMPLES {
+        let (beta, mi) = medium.sample(&ray(t_max), &mut sampler);
+        let sum: &mut [Float; 3] = match mi {
+            Some(ref mi) => {
+                // inside the segment, with an HG phase function
+                assert!(mi.common.p.x > 0.0 && mi.common.p.x < 2.0 * t_max);
+                assert_eq!(mi.phase.as_ref().unwrap().g, 0.3);
+                &mut scattered
+            }
+            None => &mut passed,
+        };
+        for c in 0..3 {
+            sum[c] += beta.c[c] / SAMPLES as Float;
+        }
+    }
+    for c in 0..3 {
+        let expected: Float = (-sigma_t[c] * 2.0 * t_max).exp();
+        assert_close(passed[c], expected, 0.01);
+        let albedo: Float = sigma_s.c[c] / sigma_t[c];
+        assert_close(scattered[c], albedo * (1.0 - expected), 0.01);
+    }
+    // create() reads the coefficients, scales them ...
+    let mut params: ParamSet = ParamSet::default();
+    params.add_rgb_spectrum(String::from("sigma_a"), Spectrum::rgb(1.0, 2.0, 3.0));
+    params.add_rgb_spectrum(String::from("sigma_s"), Spectrum::new(0.5));
+    params.add_float(String::from("scale"), 2.0);
+    params.add_float(String::from("g"), -0.5);
+    let created: HomogeneousMedium = HomogeneousMedium::create(&params);
+    assert!(created.sigma_a == Spectrum::rgb(2.0, 4.0, 6.0));
+    assert!(created.sigma_s == Spectrum::new(1.0));
+    assert!(created.sigma_t == Spectrum::rgb(3.0, 5.0, 7.0));
+    assert_eq!(created.g, -0.5);
+    // ... falls back to PBRT's defaults ...
+    let created: HomogeneousMedium = HomogeneousMedium::create(&ParamSet::default());
+    assert!(created.sigma_a == Spectrum::rgb(0.0011, 0.0024, 0.014));
+    assert!(created.sigma_s == Spectrum::rgb(2.55, 3.21, 3.77));
+    assert_eq!(created.g, 0.0);
+    // ... and knows the measured presets
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("preset"), String::from("Ketchup"));
+    let created: HomogeneousMedium = HomogeneousMedium::create(&params);
+    assert!(created.sigma_a == Spectrum::rgb(0.061, 0.97, 1.45));
+    assert!(created.sigma_s == Spectrum::rgb(0.18, 0.07, 0.03));
+}
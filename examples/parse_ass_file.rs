@@ -881,6 +881,7 @@ fn main() -> std::io::Result<()> {
                                             &(color * intensity),
                                             cone_angle,
                                             cone_angle - cone_delta_angle,
+                                            None,
                                         ))));
                                     lights.push(spot_light);
                                     println!("}}");
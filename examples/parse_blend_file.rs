@@ -398,6 +398,7 @@ impl SceneDescriptionBuilder {
             &object_to_world,
             &(l * sc),
             &dir,
+            0.0 as Float,
         ))));
         self.lights.push(distant_light);
         self
@@ -514,6 +515,7 @@ impl RenderOptions {
                             index: index,
                             bump_map: None,
                             remap_roughness: true,
+                            dispersion: 0.0,
                         })));
                         shapes.push(cylinder.clone());
                         shape_materials.push(glass.clone());
@@ -704,6 +706,7 @@ impl RenderOptions {
                             index: index,
                             bump_map: None,
                             remap_roughness: true,
+                            dispersion: 0.0,
                         })));
                         shapes.push(disk.clone());
                         shape_materials.push(glass.clone());
@@ -894,6 +897,7 @@ impl RenderOptions {
                             index: index,
                             bump_map: None,
                             remap_roughness: true,
+                            dispersion: 0.0,
                         })));
                         shapes.push(sphere.clone());
                         shape_materials.push(glass.clone());
@@ -1100,6 +1104,7 @@ impl RenderOptions {
                             index: index,
                             bump_map: None,
                             remap_roughness: true,
+                            dispersion: 0.0,
                         })));
                         for _i in 0..triangles.len() {
                             shape_materials.push(glass.clone());
@@ -1755,6 +1760,7 @@ fn make_integrator(
                         camera,
                         sampler,
                         pixel_bounds,
+                        0_u32,
                     ),
                 )));
                 some_integrator = Some(integrator);
@@ -1810,6 +1816,8 @@ fn make_integrator(
                     sampler,
                     pixel_bounds,
                     max_depth as u32,
+                    false,
+                    false,
                     light_strategy,
                 )));
                 some_integrator = Some(integrator);
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Bounds2i, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::adaptive::{AdaptiveSampleStats, AdaptiveSampler};
+use pbrt::samplers::random::RandomSampler;
+
+/// Runs the sample loop for pixel *p*, reporting *radiance(u)* as the
+/// luminance of each sample, and returns the number of samples taken.
+fn render_pixel(sampler: &mut Sampler, p: Point2i, radiance: &dyn Fn(Float) -> Float) -> u32 {
+    let mut n: u32 = 0;
+    sampler.start_pixel(p);
+    loop {
+        let u: Float = sampler.get_1d();
+        sampler.add_sample_luminance(radiance(u));
+        n += 1;
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    n
+}
+
+fn main() {
+    let sample_bounds: Bounds2i = Bounds2i::new(Point2i { x: 0, y: 0 }, Point2i { x: 4, y: 4 });
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("pixelsamples"), 256);
+    params.add_int(String::from("minsamples"), 16);
+    params.add_float(String::from("maxerror"), 0.05);
+    let inner: Box<Sampler> = RandomSampler::create(&params);
+    let sampler: Box<Sampler> = AdaptiveSampler::create(&params, inner, &sample_bounds);
+    assert_eq!(sampler.get_samples_per_pixel(), 256);
+    let stats: Arc<AdaptiveSampleStats> = sampler.get_adaptive_stats().unwrap();
+    // per tile clones share the statistics
+    let mut tile_sampler: Box<Sampler> = sampler.clone_with_seed(3_u64);
+    // a constant pixel converges after the minimum number of samples
+    let constant: u32 = render_pixel(&mut tile_sampler, Point2i { x: 0, y: 0 }, &|_u| 2.0);
+    assert_eq!(constant, 16);
+    // black pixels don't divide by zero and stop early, too
+    let black: u32 = render_pixel(&mut tile_sampler, Point2i { x: 1, y: 0 }, &|_u| 0.0);
+    assert_eq!(black, 16);
+    // nearly black, noisy pixels never reach the relative error, but
+    // the absolute error floor stops them
+    let dark: u32 = render_pixel(&mut tile_sampler, Point2i { x: 2, y: 0 }, &|u| 1.0e-6 * u);
+    assert_eq!(dark, 16);
+    // noisy pixels need more samples, the noisier the more
+    let noisy: u32 = render_pixel(&mut tile_sampler, Point2i { x: 0, y: 1 }, &|u| u);
+    let noisier: u32 = render_pixel(&mut tile_sampler, Point2i { x: 1, y: 1 }, &|u| 20.0 * u * u);
+    assert!(noisy > 16 && noisy < 256, "{}", noisy);
+    assert!(noisier > noisy, "{} vs. {}", noisier, noisy);
+    // the statistics know how many samples each pixel used
+    assert_eq!(stats.get(Point2i { x: 0, y: 0 }), constant);
+    assert_eq!(stats.get(Point2i { x: 1, y: 0 }), black);
+    assert_eq!(stats.get(Point2i { x: 0, y: 1 }), noisy);
+    assert_eq!(stats.get(Point2i { x: 1, y: 1 }), noisier);
+    assert_eq!(stats.get(Point2i { x: 3, y: 3 }), 0);
+    assert_eq!(
+        stats.total(),
+        (constant + black + dark + noisy + noisier) as u64
+    );
+    // without luminance feedback the sampler takes all samples
+    tile_sampler.start_pixel(Point2i { x: 3, y: 3 });
+    let mut n: u32 = 1;
+    while tile_sampler.start_next_sample() {
+        n += 1;
+    }
+    assert_eq!(n, 256);
+    assert_eq!(stats.get(Point2i { x: 3, y: 3 }), 256);
+    println!(
+        "samples used: constant {}, black {}, dark {}, noisy {}, noisier {}; average {}",
+        constant,
+        black,
+        dark,
+        noisy,
+        noisier,
+        stats.average()
+    );
+}
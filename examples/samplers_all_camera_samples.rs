@@ -0,0 +1,52 @@
+use pbrt::core::camera::CameraSample;
+use pbrt::core::geometry::{Bounds2i, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::halton::HaltonSampler;
+use pbrt::samplers::random::RandomSampler;
+use pbrt::samplers::stratified::StratifiedSampler;
+use pbrt::samplers::zerotwosequence::ZeroTwoSequenceSampler;
+
+/// Camera samples of pixel *p*, one *get_camera_sample()* call per
+/// pixel sample (like the render loop does).
+fn per_sample(sampler: &mut Sampler, p: Point2i) -> Vec<CameraSample> {
+    let mut samples: Vec<CameraSample> = Vec::new();
+    sampler.start_pixel(p);
+    loop {
+        samples.push(sampler.get_camera_sample(p));
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    samples
+}
+
+fn main() {
+    let sample_bounds: Bounds2i = Bounds2i::new(Point2i { x: 0, y: 0 }, Point2i { x: 64, y: 64 });
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("pixelsamples"), 16);
+    let samplers: Vec<(&str, Box<Sampler>)> = vec![
+        ("random", RandomSampler::create(&params)),
+        ("stratified", StratifiedSampler::create(&params)),
+        ("halton", HaltonSampler::create(&params, &sample_bounds)),
+        ("02sequence", ZeroTwoSequenceSampler::create(&params)),
+    ];
+    let pixels: [Point2i; 2] = [Point2i { x: 37, y: 11 }, Point2i { x: 3, y: 60 }];
+    for (name, sampler) in samplers {
+        let mut batched: Box<Sampler> = sampler.clone_with_seed(0_u64);
+        let mut sequential: Box<Sampler> = sampler.clone_with_seed(0_u64);
+        let spp: usize = sampler.get_samples_per_pixel() as usize;
+        for p in &pixels {
+            let all: Vec<CameraSample> = batched.get_all_camera_samples(*p);
+            let expected: Vec<CameraSample> = per_sample(&mut sequential, *p);
+            assert_eq!(all.len(), spp, "{}: {} samples", name, all.len());
+            assert_eq!(all.len(), expected.len());
+            for (a, e) in all.iter().zip(expected.iter()) {
+                assert_eq!(a.p_film, e.p_film, "{}: film sample differs", name);
+                assert_eq!(a.p_lens, e.p_lens, "{}: lens sample differs", name);
+                assert_eq!(a.time, e.time, "{}: time differs", name);
+            }
+        }
+        println!("{}: {} camera samples per pixel in one batch", name, spp);
+    }
+}
@@ -0,0 +1,75 @@
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::block::BlockSampler;
+use pbrt::samplers::stratified::StratifiedSampler;
+
+fn pixel_samples(sampler: &mut Sampler, p: Point2i) -> (Vec<Point2f>, Point2f) {
+    sampler.start_pixel(p);
+    let mut samples: Vec<Point2f> = Vec::new();
+    loop {
+        samples.push(sampler.get_2d());
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    let offset: Point2f = match sampler {
+        Sampler::Block(block) => block.pixel_offset,
+        _ => unreachable!(),
+    };
+    (samples, offset)
+}
+
+fn unrotate(u: Point2f, offset: Point2f) -> Point2f {
+    let x: Float = u.x - offset.x;
+    let y: Float = u.y - offset.y;
+    Point2f {
+        x: x - x.floor(),
+        y: y - y.floor(),
+    }
+}
+
+fn main() {
+    let (nx, ny): (i32, i32) = (4, 4);
+    let base: StratifiedSampler = StratifiedSampler::new(nx, ny, true, 4);
+    let mut sampler: Sampler = Sampler::Block(BlockSampler::new(4, base));
+    sampler.reseed(0_u64);
+    // two pixels of block (0, 0) and one of block (1, 0)
+    let (a, offset_a) = pixel_samples(&mut sampler, Point2i { x: 0, y: 0 });
+    let (b, offset_b) = pixel_samples(&mut sampler, Point2i { x: 3, y: 2 });
+    let (c, offset_c) = pixel_samples(&mut sampler, Point2i { x: 4, y: 2 });
+    assert_eq!(a.len(), (nx * ny) as usize);
+    // pixels differ by their rotation offset ...
+    assert!(offset_a.x != offset_b.x || offset_a.y != offset_b.y);
+    assert!(a
+        .iter()
+        .zip(b.iter())
+        .any(|(u, v)| u.x != v.x || u.y != v.y));
+    // ... but share the same stratified set underneath
+    let eps: Float = 1e-4;
+    let mut strata: Vec<usize> = vec![0; (nx * ny) as usize];
+    for (u, v) in a.iter().zip(b.iter()) {
+        let su: Point2f = unrotate(*u, offset_a);
+        let sv: Point2f = unrotate(*v, offset_b);
+        let dx: Float = (su.x - sv.x).abs();
+        let dy: Float = (su.y - sv.y).abs();
+        assert!(
+            (dx < eps || dx > 1.0 - eps) && (dy < eps || dy > 1.0 - eps),
+            "{:?} != {:?}",
+            su,
+            sv
+        );
+        let sx: usize = ((su.x * nx as Float) as usize).min(nx as usize - 1);
+        let sy: usize = ((su.y * ny as Float) as usize).min(ny as usize - 1);
+        strata[sy * nx as usize + sx] += 1;
+    }
+    assert!(strata.iter().all(|n| *n == 1), "strata = {:?}", strata);
+    // the next block gets a different set
+    let shared: bool = a.iter().zip(c.iter()).all(|(u, v)| {
+        let su: Point2f = unrotate(*u, offset_a);
+        let sv: Point2f = unrotate(*v, offset_c);
+        (su.x - sv.x).abs() < eps && (su.y - sv.y).abs() < eps
+    });
+    assert!(!shared);
+    println!("pixels of a block share one stratified set");
+}
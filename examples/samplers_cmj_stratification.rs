@@ -0,0 +1,53 @@
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::cmj::CmjSampler;
+
+fn main() {
+    let (nx, ny): (usize, usize) = (4, 8);
+    let n: usize = nx * ny;
+    let mut sampler: Sampler = Sampler::Cmj(CmjSampler::new(nx as i32, ny as i32));
+    sampler.reseed(7_u64);
+    for pixel in &[Point2i { x: 0, y: 0 }, Point2i { x: 17, y: 3 }] {
+        sampler.start_pixel(*pixel);
+        let mut film: Vec<Point2f> = Vec::with_capacity(n);
+        let mut time: Vec<Float> = Vec::with_capacity(n);
+        loop {
+            film.push(sampler.get_2d());
+            time.push(sampler.get_1d());
+            if !sampler.start_next_sample() {
+                break;
+            }
+        }
+        assert_eq!(film.len(), n);
+        // exactly one sample per (nx x ny) stratum
+        let mut strata: Vec<usize> = vec![0; n];
+        for p in &film {
+            assert!(p.x >= 0.0 && p.x < 1.0 && p.y >= 0.0 && p.y < 1.0);
+            let sx: usize = (p.x * nx as Float) as usize;
+            let sy: usize = (p.y * ny as Float) as usize;
+            strata[sy * nx + sx] += 1;
+        }
+        assert!(strata.iter().all(|c| *c == 1), "strata = {:?}", strata);
+        // 1D projections of the 2D samples are stratified into n intervals
+        let mut xs: Vec<usize> = vec![0; n];
+        let mut ys: Vec<usize> = vec![0; n];
+        for p in &film {
+            xs[(p.x * n as Float) as usize] += 1;
+            ys[(p.y * n as Float) as usize] += 1;
+        }
+        assert!(xs.iter().all(|c| *c == 1), "x projection = {:?}", xs);
+        assert!(ys.iter().all(|c| *c == 1), "y projection = {:?}", ys);
+        // 1D samples are stratified as well
+        let mut ts: Vec<usize> = vec![0; n];
+        for t in &time {
+            ts[(t * n as Float) as usize] += 1;
+        }
+        assert!(ts.iter().all(|c| *c == 1), "1D strata = {:?}", ts);
+        println!(
+            "pixel {:?}: {} samples, all strata covered",
+            pixel,
+            film.len()
+        );
+    }
+}
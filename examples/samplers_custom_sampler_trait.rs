@@ -0,0 +1,62 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::scene::{camera, sphere};
+use pbrt::core::camera::Camera;
+use pbrt::core::geometry::Bounds2i;
+use pbrt::core::integrator::{Integrator, RenderStats, SamplerIntegrator};
+use pbrt::core::sampler::{Sampler, SamplerTrait};
+use pbrt::core::scene::Scene;
+use pbrt::integrators::ao::AOIntegrator;
+use pbrt::samplers::stratified::StratifiedSampler;
+
+fn make_scene() -> Scene {
+    // unit sphere 4 units in front of the camera
+    Scene::new(sphere(4.0, 1.0, None), Vec::new())
+}
+
+fn render(scene: &Scene, sampler: Box<Sampler>) -> (RenderStats, Vec<u8>) {
+    let camera: Arc<Camera> = camera(32);
+    let pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
+    let mut integrator: Integrator = Integrator::Sampler(SamplerIntegrator::AO(AOIntegrator::new(
+        true,
+        4,
+        camera,
+        sampler,
+        pixel_bounds,
+    )));
+    let stats: RenderStats = integrator.render(scene, 2);
+    let pixels: Vec<u8> = image::open("pbrt.png").unwrap().to_rgb8().into_raw();
+    (stats, pixels)
+}
+
+fn main() {
+    let scene: Scene = make_scene();
+    // built-in enum dispatch
+    let (builtin_stats, builtin) = render(
+        &scene,
+        Box::new(Sampler::Stratified(StratifiedSampler::new(2, 2, true, 4))),
+    );
+    // the same sampler, but dispatched through the trait object
+    let (custom_stats, custom) = render(
+        &scene,
+        Sampler::from_trait(Box::new(StratifiedSampler::new(2, 2, true, 4))),
+    );
+    // ... or shared with other code
+    let shared: Arc<dyn SamplerTrait + Send + Sync> =
+        Arc::new(StratifiedSampler::new(2, 2, true, 4));
+    let (shared_stats, from_shared) = render(&scene, Sampler::from_shared(shared.clone()));
+    std::fs::remove_file("pbrt.png").unwrap();
+    assert_eq!(shared.get_current_sample_number(), 0);
+    assert_eq!(builtin_stats.total_samples, custom_stats.total_samples);
+    assert_eq!(builtin_stats.total_samples, shared_stats.total_samples);
+    assert!(builtin == from_shared, "shared trait object render differs");
+    assert_eq!(custom_stats.total_samples, 32 * 32 * 4);
+    assert!(custom.iter().any(|v| *v > 0), "sphere not visible");
+    assert!(builtin == custom, "trait object render differs");
+    println!(
+        "rendered {} samples through the SamplerTrait object",
+        custom_stats.total_samples
+    );
+}
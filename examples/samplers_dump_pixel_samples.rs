@@ -0,0 +1,43 @@
+use pbrt::core::geometry::{Bounds2i, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::halton::HaltonSampler;
+use pbrt::samplers::random::RandomSampler;
+use pbrt::samplers::stratified::StratifiedSampler;
+
+fn main() {
+    let sample_bounds: Bounds2i = Bounds2i::new(Point2i { x: 0, y: 0 }, Point2i { x: 64, y: 64 });
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("pixelsamples"), 16);
+    let samplers: Vec<(&str, Box<Sampler>)> = vec![
+        ("random", RandomSampler::create(&params)),
+        ("stratified", StratifiedSampler::create(&params)),
+        ("halton", HaltonSampler::create(&params, &sample_bounds)),
+    ];
+    for (name, sampler) in samplers {
+        let mut sampler: Box<Sampler> = sampler.clone_with_seed(0_u64);
+        let spp: i64 = sampler.get_samples_per_pixel();
+        let mut csv: Vec<u8> = Vec::new();
+        sampler
+            .dump_pixel_samples(Point2i { x: 37, y: 11 }, &mut csv)
+            .unwrap();
+        let csv: String = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("x,y"));
+        let mut n: i64 = 0;
+        for line in lines {
+            let xy: Vec<Float> = line.split(',').map(|v| v.parse().unwrap()).collect();
+            assert_eq!(xy.len(), 2);
+            assert!(
+                xy.iter().all(|v| *v >= 0.0 && *v < 1.0),
+                "{}: {} outside of [0,1)^2",
+                name,
+                line
+            );
+            n += 1;
+        }
+        assert_eq!(n, spp, "{}: {} points for {} spp", name, n, spp);
+        println!("{}: {} film samples dumped", name, n);
+    }
+}
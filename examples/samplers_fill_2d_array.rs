@@ -0,0 +1,47 @@
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::stratified::StratifiedSampler;
+
+fn main() {
+    let mut sampler: Sampler = Sampler::Stratified(StratifiedSampler::new(2, 2, true, 4));
+    sampler.request_2d_array(4);
+    sampler.request_2d_array(4);
+    sampler.reseed(7_u64);
+    sampler.start_pixel(Point2i { x: 3, y: 5 });
+    let mut u1: Vec<Point2f> = Vec::new();
+    let mut u2: Vec<Point2f> = Vec::new();
+    let mut sample_index: usize = 0;
+    loop {
+        // both arrays can be held at the same time
+        assert!(sampler.get_2d_arrays(4, &mut u1, &mut u2));
+        assert_eq!(u1.len(), 4);
+        assert_eq!(u2.len(), 4);
+        for (i, (p1, p2)) in u1.iter().zip(u2.iter()).enumerate() {
+            let idx: usize = sample_index * 4 + i;
+            assert_eq!(*p1, sampler.get_2d_sample(0, idx));
+            assert_eq!(*p2, sampler.get_2d_sample(1, idx));
+        }
+        assert_ne!(u1, u2);
+        // all requested arrays are consumed now
+        assert!(!sampler.fill_2d_array(4, &mut u1));
+        assert!(u1.is_empty());
+        if !sampler.start_next_sample() {
+            break;
+        }
+        sample_index += 1;
+    }
+    assert_eq!(sample_index, 3);
+    // same values as the borrowing API
+    let mut borrowed: Sampler = Sampler::Stratified(StratifiedSampler::new(2, 2, true, 4));
+    borrowed.request_2d_array(4);
+    borrowed.reseed(7_u64);
+    borrowed.start_pixel(Point2i { x: 3, y: 5 });
+    let mut filled: Sampler = Sampler::Stratified(StratifiedSampler::new(2, 2, true, 4));
+    filled.request_2d_array(4);
+    filled.reseed(7_u64);
+    filled.start_pixel(Point2i { x: 3, y: 5 });
+    let expected: Vec<Point2f> = borrowed.get_2d_array(4).unwrap().to_vec();
+    assert!(filled.fill_2d_array(4, &mut u1));
+    assert_eq!(u1, expected);
+    println!("fill_2d_array: {:?}", u1);
+}
@@ -0,0 +1,71 @@
+use pbrt::core::api::{
+    pbrt_camera, pbrt_cleanup, pbrt_film, pbrt_init, pbrt_integrator, pbrt_look_at, pbrt_sampler,
+    pbrt_shape, pbrt_world_begin,
+};
+use pbrt::core::paramset::ParamSet;
+
+fn named(name: &str) -> ParamSet {
+    let mut params: ParamSet = ParamSet::default();
+    params.name = String::from(name);
+    params
+}
+
+/// Render a static scene (ambient occlusion of a sphere) for a given
+/// frame and return the pixels written to *pbrt.png*.
+fn render_frame(sampler_name: &str, frame_index: i32, freeze_noise: bool) -> Vec<u8> {
+    let (mut api_state, mut bsdf_state) = pbrt_init(1_u8);
+    pbrt_look_at(&mut api_state, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0);
+    pbrt_camera(&mut api_state, named("perspective"));
+    let mut film_params: ParamSet = named("image");
+    film_params.add_int(String::from("xresolution"), 16);
+    film_params.add_int(String::from("yresolution"), 16);
+    pbrt_film(&mut api_state, film_params);
+    let mut sampler_params: ParamSet = named(sampler_name);
+    sampler_params.add_int(String::from("pixelsamples"), 2);
+    sampler_params.add_int(String::from("xsamples"), 1);
+    sampler_params.add_int(String::from("ysamples"), 2);
+    sampler_params.add_int(String::from("frameindex"), frame_index);
+    sampler_params.add_bool(String::from("freezenoise"), freeze_noise);
+    pbrt_sampler(&mut api_state, sampler_params);
+    let mut integrator_params: ParamSet = named("ambientocclusion");
+    integrator_params.add_int(String::from("nsamples"), 1);
+    pbrt_integrator(&mut api_state, integrator_params);
+    pbrt_world_begin(&mut api_state);
+    pbrt_shape(&mut api_state, &mut bsdf_state, named("sphere"));
+    pbrt_cleanup(&api_state);
+    image::open("pbrt.png").unwrap().to_rgb8().into_raw()
+}
+
+fn main() {
+    for sampler_name in &["random", "stratified", "02sequence", "cmj"] {
+        // animated noise: every frame gets its own (deterministic) samples
+        let frame_0: Vec<u8> = render_frame(sampler_name, 0, false);
+        let frame_1: Vec<u8> = render_frame(sampler_name, 1, false);
+        let frame_2: Vec<u8> = render_frame(sampler_name, 2, false);
+        assert_ne!(
+            frame_0, frame_1,
+            "{}: frames 0 and 1 are identical",
+            sampler_name
+        );
+        assert_ne!(
+            frame_1, frame_2,
+            "{}: frames 1 and 2 are identical",
+            sampler_name
+        );
+        assert_eq!(
+            frame_1,
+            render_frame(sampler_name, 1, false),
+            "{}: frame 1 is not deterministic",
+            sampler_name
+        );
+        // frozen noise: the frame index is ignored
+        let frozen_1: Vec<u8> = render_frame(sampler_name, 1, true);
+        let frozen_2: Vec<u8> = render_frame(sampler_name, 2, true);
+        assert_eq!(frozen_1, frozen_2, "{}: frozen frames differ", sampler_name);
+        assert_eq!(frozen_1, frame_0);
+        println!(
+            "{}: animated noise differs, frozen noise doesn't",
+            sampler_name
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std::collections::HashSet;
+
+use pbrt::core::geometry::{Bounds2i, Point2i};
+use pbrt::core::sampler::SamplerTrait;
+use pbrt::samplers::halton::HaltonSampler;
+
+/// Checks that every pixel within *sample_bounds* starts with its own
+/// Halton sample index (offset), so no two pixels share samples.
+fn check_offsets(sample_bounds: Bounds2i) -> usize {
+    let mut sampler: HaltonSampler = HaltonSampler::new(16, &sample_bounds, false);
+    let mut offsets: HashSet<u64> = HashSet::new();
+    for y in sample_bounds.p_min.y..sample_bounds.p_max.y {
+        for x in sample_bounds.p_min.x..sample_bounds.p_max.x {
+            sampler.start_pixel(Point2i { x, y });
+            let offset: u64 = sampler.get_index_for_sample(0);
+            assert!(offset < sampler.sample_stride);
+            assert!(
+                offsets.insert(offset),
+                "pixel ({}, {}) aliases another pixel",
+                x,
+                y
+            );
+        }
+    }
+    offsets.len()
+}
+
+fn main() {
+    // full image
+    let full: Bounds2i = Bounds2i::new(Point2i { x: 0, y: 0 }, Point2i { x: 50, y: 50 });
+    assert_eq!(check_offsets(full), 2500);
+    // crop window of a larger image (the image resolution exceeds the
+    // sample bounds)
+    let crop: Bounds2i = Bounds2i::new(Point2i { x: 100, y: 100 }, Point2i { x: 150, y: 150 });
+    assert_eq!(check_offsets(crop), 2500);
+    // larger than the maximum resolution, still unique per tile
+    let tile: Bounds2i = Bounds2i::new(Point2i { x: 7, y: 3 }, Point2i { x: 135, y: 131 });
+    assert_eq!(check_offsets(tile), 128 * 128);
+    println!("no aliasing of pixel sample indices");
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use pbrt::core::api::{
+    pbrt_camera, pbrt_cleanup, pbrt_film, pbrt_init, pbrt_integrator, pbrt_look_at, pbrt_sampler,
+    pbrt_shape, pbrt_world_begin,
+};
+use pbrt::core::geometry::{Bounds2i, Point2i};
+use pbrt::core::integrator::RenderStats;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::sampler::PixelSampleBudget;
+
+fn named(name: &str) -> ParamSet {
+    let mut params: ParamSet = ParamSet::default();
+    params.name = String::from(name);
+    params
+}
+
+/// Ambient occlusion render of a sphere with *budget* samples
+/// distributed according to the importance map at *path*.
+fn render(path: &Path, width: i32, height: i32, budget: i32) -> RenderStats {
+    let (mut api_state, mut bsdf_state) = pbrt_init(2_u8);
+    pbrt_look_at(&mut api_state, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0);
+    pbrt_camera(&mut api_state, named("perspective"));
+    let mut film_params: ParamSet = named("image");
+    film_params.add_int(String::from("xresolution"), width);
+    film_params.add_int(String::from("yresolution"), height);
+    pbrt_film(&mut api_state, film_params);
+    pbrt_sampler(&mut api_state, named("stratified"));
+    let mut integrator_params: ParamSet = named("ambientocclusion");
+    integrator_params.add_string(
+        String::from("importancemap"),
+        path.to_str().unwrap().to_string(),
+    );
+    integrator_params.add_int(String::from("samplebudget"), budget);
+    pbrt_integrator(&mut api_state, integrator_params);
+    pbrt_world_begin(&mut api_state);
+    pbrt_shape(&mut api_state, &mut bsdf_state, named("sphere"));
+    pbrt_cleanup(&api_state)
+}
+
+fn main() {
+    let (width, height): (i32, i32) = (16, 8);
+    let total_budget: u64 = 1000;
+    // low resolution importance map: the right half matters most
+    let mut map = image::GrayImage::new(2, 1);
+    map.put_pixel(0, 0, image::Luma([20_u8]));
+    map.put_pixel(1, 0, image::Luma([220_u8]));
+    let map = image::DynamicImage::ImageLuma8(map);
+    let bounds: Bounds2i = Bounds2i::new(
+        Point2i { x: 0, y: 0 },
+        Point2i {
+            x: width,
+            y: height,
+        },
+    );
+    let budget: PixelSampleBudget = PixelSampleBudget::from_image(&map, bounds, total_budget);
+    assert_eq!(budget.total(), total_budget);
+    let left: i64 = budget.get(Point2i { x: 2, y: 3 });
+    let right: i64 = budget.get(Point2i { x: 13, y: 3 });
+    println!("left pixel: {} spp, right pixel: {} spp", left, right);
+    assert!(right > 5 * left);
+    assert!(budget.spp.iter().all(|n| *n >= 1));
+    // render with the map: the stratified sampler takes exactly the budget
+    let path = std::env::temp_dir().join("pbrt_importance_map.png");
+    map.save(&path).unwrap();
+    let stats: RenderStats = render(&path, width, height, total_budget as i32);
+    println!("{:?}", stats);
+    assert_eq!(stats.total_samples, total_budget);
+    // invalid budgets keep the samples of a render without the map
+    let stats: RenderStats = render(&path, width, height, -5);
+    assert_eq!(stats.total_samples, (16 * width * height) as u64);
+}
@@ -0,0 +1,110 @@
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::lowdiscrepancy::{sample_generator_matrix, C_MAX_MIN_DIST};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::maxmin::MaxMinDistSampler;
+
+const SPP: usize = 16;
+
+/// Minimum toroidal distance between the points of *set*.
+fn min_distance(set: &[Point2f]) -> Float {
+    let mut d_min: Float = std::f32::INFINITY;
+    for i in 0..set.len() {
+        for j in (i + 1)..set.len() {
+            let dx: Float = (set[i].x - set[j].x).abs();
+            let dy: Float = (set[i].y - set[j].y).abs();
+            let dx: Float = dx.min(1.0 - dx);
+            let dy: Float = dy.min(1.0 - dy);
+            d_min = d_min.min((dx * dx + dy * dy).sqrt());
+        }
+    }
+    d_min
+}
+
+/// First 2D sample (and *n* array values) of all pixel samples of *p*.
+fn pixel_samples(sampler: &mut Sampler, p: Point2i, n: i32) -> (Vec<Point2f>, Vec<Vec<Point2f>>) {
+    sampler.start_pixel(p);
+    let mut samples: Vec<Point2f> = Vec::new();
+    let mut arrays: Vec<Vec<Point2f>> = Vec::new();
+    loop {
+        samples.push(sampler.get_2d());
+        if n > 0 {
+            arrays.push(sampler.get_2d_array(n).unwrap().to_vec());
+        }
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    (samples, arrays)
+}
+
+fn create(spp: i32) -> Box<Sampler> {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("pixelsamples"), spp);
+    MaxMinDistSampler::create(&params)
+}
+
+fn main() {
+    // sample counts are rounded up to powers of two, and capped
+    for &(requested, expected) in &[(16, 16), (12, 16), (1, 1), (0, 1), (1 << 20, 1 << 16)] {
+        assert_eq!(create(requested).get_samples_per_pixel(), expected);
+    }
+    // the first 2D dimension keeps the maximized minimum distance of
+    // the generator matrices, and stays stratified in x ...
+    let mut sampler: Box<Sampler> = create(SPP as i32);
+    sampler.request_2d_array(8);
+    let reference: Vec<Point2f> = (0..SPP)
+        .map(|i| Point2f {
+            x: i as Float / SPP as Float,
+            y: sample_generator_matrix(&C_MAX_MIN_DIST[4], i as u32, 0),
+        })
+        .collect();
+    let d_reference: Float = min_distance(&reference);
+    let mut tile_sampler: Box<Sampler> = sampler.clone_with_seed(0);
+    let (a, arrays) = pixel_samples(&mut tile_sampler, Point2i { x: 3, y: 5 }, 8);
+    let (b, _) = pixel_samples(&mut tile_sampler, Point2i { x: 4, y: 5 }, 8);
+    for set in &[&a, &b] {
+        assert!((min_distance(set) - d_reference).abs() < 1e-5);
+        let mut x: Vec<Float> = set.iter().map(|p| p.x).collect();
+        x.sort_by(|u, v| u.partial_cmp(v).unwrap());
+        for i in 1..SPP {
+            assert!((x[i] - x[i - 1] - 1.0 / SPP as Float).abs() < 1e-5);
+        }
+    }
+    // ... but is shifted per pixel, so neighbours differ ...
+    let sorted = |set: &[Point2f]| -> Vec<(Float, Float)> {
+        let mut points: Vec<(Float, Float)> = set.iter().map(|p| (p.x, p.y)).collect();
+        points.sort_by(|u, v| u.partial_cmp(v).unwrap());
+        points
+    };
+    assert_ne!(sorted(&a[..]), sorted(&b[..]));
+    // ... by an offset depending on the pixel only, not on the tile
+    let mut other_tile: Box<Sampler> = sampler.clone_with_seed(42);
+    let (c, _) = pixel_samples(&mut other_tile, Point2i { x: 3, y: 5 }, 8);
+    assert_eq!(sorted(&a[..]), sorted(&c[..]));
+    // arrays come from a (0,2)-sequence: the 8 values of each pixel
+    // sample fall into different cells of all elementary intervals
+    assert_eq!(arrays.len(), SPP);
+    for array in &arrays {
+        assert_eq!(array.len(), 8);
+        for &(nx, ny) in &[(1, 8), (2, 4), (4, 2), (8, 1)] {
+            let mut cells: Vec<bool> = vec![false; 8];
+            for p in array {
+                let cell: usize =
+                    (p.x * nx as Float) as usize * ny as usize + (p.y * ny as Float) as usize;
+                assert!(!cells[cell], "{} x {} cell {} twice", nx, ny, cell);
+                cells[cell] = true;
+            }
+        }
+    }
+    // more dimensions than requested fall back to random numbers
+    let mut tile_sampler: Box<Sampler> = create(4).clone_with_seed(1);
+    tile_sampler.start_pixel(Point2i { x: 0, y: 0 });
+    for _ in 0..10 {
+        let u: Float = tile_sampler.get_1d();
+        assert!(u >= 0.0 && u < 1.0);
+        let u: Point2f = tile_sampler.get_2d();
+        assert!(u.x >= 0.0 && u.x < 1.0 && u.y >= 0.0 && u.y < 1.0);
+    }
+}
@@ -0,0 +1,83 @@
+use pbrt::core::geometry::{Bounds2i, Point2f, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::{Sampler, SamplerTrait};
+use pbrt::samplers::block::BlockSampler;
+use pbrt::samplers::cmj::CmjSampler;
+use pbrt::samplers::halton::HaltonSampler;
+use pbrt::samplers::maxmin::MaxMinDistSampler;
+use pbrt::samplers::random::RandomSampler;
+use pbrt::samplers::sobol::SobolSampler;
+use pbrt::samplers::stratified::StratifiedSampler;
+use pbrt::samplers::zerotwosequence::ZeroTwoSequenceSampler;
+
+/// Sample values of pixel *p*, drawn through the **Sampler** enum.
+fn enum_samples(sampler: &mut Sampler, p: Point2i) -> Vec<Float> {
+    let mut values: Vec<Float> = Vec::new();
+    sampler.start_pixel(p);
+    loop {
+        values.push(sampler.get_1d());
+        let u: Point2f = sampler.get_2d();
+        values.push(u.x);
+        values.push(u.y);
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    values
+}
+
+/// Sample values of pixel *p*, drawn through a **SamplerTrait** object.
+fn trait_samples(sampler: &mut dyn SamplerTrait, p: Point2i) -> Vec<Float> {
+    let mut values: Vec<Float> = Vec::new();
+    sampler.start_pixel(p);
+    loop {
+        values.push(sampler.get_1d());
+        let u: Point2f = sampler.get_2d();
+        values.push(u.x);
+        values.push(u.y);
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    values
+}
+
+fn main() {
+    let sample_bounds: Bounds2i = Bounds2i::new(Point2i { x: 0, y: 0 }, Point2i { x: 16, y: 16 });
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("pixelsamples"), 8);
+    params.add_int(String::from("xsamples"), 2);
+    params.add_int(String::from("ysamples"), 4);
+    let samplers: Vec<(&str, Box<Sampler>)> = vec![
+        ("block", BlockSampler::create(&params)),
+        ("cmj", CmjSampler::create(&params)),
+        ("halton", HaltonSampler::create(&params, &sample_bounds)),
+        ("maxmindist", MaxMinDistSampler::create(&params)),
+        (
+            "sobol_pbrtcompat",
+            SobolSampler::create_pbrt_compat(&params, &sample_bounds),
+        ),
+        ("random", RandomSampler::create(&params)),
+        ("sobol", SobolSampler::create(&params, &sample_bounds)),
+        ("stratified", StratifiedSampler::create(&params)),
+        ("02sequence", ZeroTwoSequenceSampler::create(&params)),
+    ];
+    let p: Point2i = Point2i { x: 5, y: 11 };
+    for (name, sampler) in samplers {
+        let spp: i64 = sampler.get_samples_per_pixel();
+        let expected: Vec<Float> = enum_samples(&mut sampler.clone_with_seed(7_u64), p);
+        // every built-in sampler can be used as a trait object ...
+        let mut object: Box<dyn SamplerTrait + Send + Sync> =
+            (*sampler.clone_with_seed(7_u64)).into_trait();
+        assert_eq!(object.get_samples_per_pixel(), spp, "{}", name);
+        assert_eq!(trait_samples(&mut *object, p), expected, "{}", name);
+        // ... cloned through the trait ...
+        let mut clone: Box<dyn SamplerTrait + Send + Sync> = object.clone_with_seed(7_u64);
+        assert_eq!(trait_samples(&mut *clone, p), expected, "{}", name);
+        // ... and wrapped again to render with it
+        let mut custom: Sampler = Sampler::Custom(clone.clone_with_seed(7_u64));
+        assert_eq!(enum_samples(&mut custom, p), expected, "{}", name);
+        println!("{}: {} samples per pixel", name, spp);
+    }
+}
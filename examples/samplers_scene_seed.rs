@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use pbrt::core::api::{make_sampler, pbrt_init, pbrt_scene_seed};
+use pbrt::core::film::Film;
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::filters::boxfilter::BoxFilter;
+
+const SCENE: &str = "LookAt 0 0 5  0 0 0  0 1 0
+Camera \"perspective\" \"float fov\" [ 45 ]
+Sampler \"random\" \"integer pixelsamples\" [ 4 ]
+WorldBegin
+LightSource \"point\" \"rgb I\" [ 10 10 10 ]
+Shape \"sphere\" \"float radius\" [ 1 ]
+WorldEnd
+";
+
+fn scene_seed(scene: &str) -> u64 {
+    let (mut api_state, _bsdf_state) = pbrt_init(1);
+    pbrt_scene_seed(&mut api_state, scene.as_bytes())
+}
+
+/// Samples of one pixel of tile 3, as the integrator would draw them.
+fn noise(sampler_params: &ParamSet, base_seed: Option<u64>) -> Vec<Float> {
+    let film: Arc<Film> = Film::create(
+        &ParamSet::default(),
+        BoxFilter::create(&ParamSet::default()),
+    );
+    let mut sampler: Box<Sampler> = make_sampler("random", sampler_params, film).unwrap();
+    if let Some(base_seed) = base_seed {
+        sampler.set_base_seed(base_seed);
+    }
+    let mut tile_sampler: Box<Sampler> = sampler.clone_with_seed(0_u64);
+    tile_sampler.reseed(3_u64);
+    tile_sampler.start_pixel(Point2i { x: 5, y: 7 });
+    let mut values: Vec<Float> = Vec::new();
+    for _i in 0..8 {
+        let u: Point2f = tile_sampler.get_2d();
+        values.push(u.x);
+        values.push(u.y);
+    }
+    values
+}
+
+fn main() {
+    let seed: u64 = scene_seed(SCENE);
+    // the same scene always gets the same seed ...
+    assert_eq!(seed, scene_seed(SCENE));
+    // ... while any edit changes it
+    let edited: String = SCENE.replace("[ 45 ]", "[ 46 ]");
+    let edited_seed: u64 = scene_seed(&edited);
+    assert_ne!(seed, edited_seed);
+    let params: ParamSet = ParamSet::default();
+    let scene_noise: Vec<Float> = noise(&params, Some(seed));
+    assert_eq!(scene_noise, noise(&params, Some(seed)));
+    assert_ne!(scene_noise, noise(&params, Some(edited_seed)));
+    assert_ne!(scene_noise, noise(&params, None));
+    // an explicit "seed" parameter works the same way
+    let mut seed_params: ParamSet = ParamSet::default();
+    seed_params.add_int(String::from("seed"), 1234);
+    assert_eq!(noise(&seed_params, None), noise(&params, Some(1234)));
+    println!("scene seed {:#018x}, edited {:#018x}", seed, edited_seed);
+}
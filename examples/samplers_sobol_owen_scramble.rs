@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+
+use pbrt::core::geometry::{Bounds2i, Point2f, Point2i};
+use pbrt::core::lowdiscrepancy::owen_scramble;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::sobol::SobolSampler;
+
+const N_DIMENSIONS: usize = 8;
+
+/// All sample values (the first *N_DIMENSIONS* dimensions) of pixel *p*.
+fn pixel_samples(sampler: &mut Sampler, p: Point2i) -> Vec<Vec<Float>> {
+    let mut samples: Vec<Vec<Float>> = Vec::new();
+    sampler.start_pixel(p);
+    loop {
+        let mut values: Vec<Float> = Vec::new();
+        for _ in 0..N_DIMENSIONS / 2 {
+            let v: Point2f = sampler.get_2d();
+            values.push(v.x);
+            values.push(v.y);
+        }
+        samples.push(values);
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    samples
+}
+
+/// Number of distinct strata (out of *n*) hit by dimension *dim*.
+fn strata(samples: &[Vec<Float>], dim: usize, n: usize) -> usize {
+    let hit: HashSet<usize> = samples
+        .iter()
+        .map(|s| (s[dim] * n as Float) as usize)
+        .collect();
+    hit.len()
+}
+
+fn main() {
+    // nested uniform scrambling is a bijection
+    let seed: u32 = 0x1234_5678;
+    let scrambled: HashSet<u32> = (0..4096_u32)
+        .map(|i| owen_scramble(i << 20, seed) >> 20)
+        .collect();
+    assert_eq!(scrambled.len(), 4096);
+    let sample_bounds: Bounds2i = Bounds2i::new(Point2i { x: 0, y: 0 }, Point2i { x: 32, y: 32 });
+    // non power-of-two sample counts get rounded up
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("samplesperpixel"), 12);
+    let sampler: Box<Sampler> = SobolSampler::create(&params, &sample_bounds);
+    assert_eq!(sampler.get_samples_per_pixel(), 16);
+    let mut unscrambled_params: ParamSet = ParamSet::default();
+    unscrambled_params.add_int(String::from("samplesperpixel"), 12);
+    unscrambled_params.add_bool(String::from("owenscramble"), false);
+    let unscrambled: Box<Sampler> = SobolSampler::create(&unscrambled_params, &sample_bounds);
+    // the scramble only depends on the pixel, not on the order pixels
+    // are visited in (or the seed of the per-tile clone)
+    let pixels: [Point2i; 3] = [
+        Point2i { x: 3, y: 7 },
+        Point2i { x: 30, y: 1 },
+        Point2i { x: 12, y: 12 },
+    ];
+    let mut forward: Box<Sampler> = sampler.clone_with_seed(1_u64);
+    let mut backward: Box<Sampler> = sampler.clone_with_seed(2_u64);
+    let mut plain: Box<Sampler> = unscrambled.clone_with_seed(1_u64);
+    let expected: Vec<Vec<Vec<Float>>> = pixels
+        .iter()
+        .map(|p| pixel_samples(&mut forward, *p))
+        .collect();
+    for (p, e) in pixels.iter().zip(expected.iter()).rev() {
+        assert_eq!(pixel_samples(&mut backward, *p), *e);
+        let samples: Vec<Vec<Float>> = pixel_samples(&mut plain, *p);
+        assert_ne!(samples, *e, "scrambling didn't change the samples");
+        for (s, u) in e.iter().zip(samples.iter()) {
+            assert!(s.iter().all(|v| *v >= 0.0 && *v < 1.0));
+            assert!(u.iter().all(|v| *v >= 0.0 && *v < 1.0));
+        }
+        // scrambling keeps the stratification of the sample offsets
+        // within the pixel
+        for dim in 0..2 {
+            assert_eq!(strata(e, dim, 16), strata(&samples, dim, 16));
+        }
+    }
+    // scrambled samples are still uniformly distributed
+    let mut sum: f64 = 0.0;
+    let mut n: usize = 0;
+    for y in 0..32 {
+        for x in 0..32 {
+            for s in pixel_samples(&mut forward, Point2i { x, y }) {
+                for v in &s[2..] {
+                    sum += *v as f64;
+                    n += 1;
+                }
+            }
+        }
+    }
+    let mean: f64 = sum / n as f64;
+    println!("mean of {} scrambled sample values: {}", n, mean);
+    assert!((mean - 0.5).abs() < 0.01);
+}
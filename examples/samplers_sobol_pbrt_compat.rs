@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use pbrt::core::api::make_sampler;
+use pbrt::core::film::Film;
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::filters::boxfilter::BoxFilter;
+
+/// Sample values of PBRT-v3's "sobol" sampler for pixel (0, 0) of a
+/// 16x16 image, dimensions 0 to 8 (the pixel dimensions 0 and 1 are
+/// offsets within the pixel).
+const PBRT_REFERENCE: [[Float; 9]; 3] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [
+        0.53125,
+        0.53125,
+        0.501_953_1,
+        0.126_953_1,
+        0.634_765_6,
+        0.158_203_1,
+        0.900_390_6,
+        0.994_140_6,
+        0.064_453_125,
+    ],
+    [
+        0.265625,
+        0.796875,
+        0.752_929_7,
+        0.317_382_8,
+        0.952_148_4,
+        0.329_101_6,
+        0.717_773_4,
+        0.727_539_1,
+        0.547_851_6,
+    ],
+];
+
+fn main() {
+    let mut film_params: ParamSet = ParamSet::default();
+    film_params.add_int(String::from("xresolution"), 16);
+    film_params.add_int(String::from("yresolution"), 16);
+    let film: Arc<Film> = Film::create(&film_params, BoxFilter::create(&ParamSet::default()));
+    let mut sampler_params: ParamSet = ParamSet::default();
+    sampler_params.add_int(String::from("pixelsamples"), 4);
+    let mut sampler: Box<Sampler> = make_sampler("sobol_pbrtcompat", &sampler_params, film.clone())
+        .expect("\"sobol_pbrtcompat\" should be registered");
+    match *sampler {
+        Sampler::Sobol(ref sobol) => assert!(!sobol.owen_scramble),
+        _ => panic!("\"sobol_pbrtcompat\" should create a SobolSampler"),
+    }
+    let mut sampler: Box<Sampler> = sampler.clone_with_seed(0_u64);
+    sampler.start_pixel(Point2i { x: 0, y: 0 });
+    for reference in PBRT_REFERENCE.iter() {
+        // same order as the camera sample and the first bounce, 2D
+        // samples never straddle the array dimensions (starting at 5)
+        let p_film: Point2f = sampler.get_2d();
+        let time: Float = sampler.get_1d();
+        let u: Float = sampler.get_1d();
+        let p_lens: Point2f = sampler.get_2d();
+        let values: [Float; 6] = [p_film.x, p_film.y, time, u, p_lens.x, p_lens.y];
+        let expected: [Float; 6] = [
+            reference[0],
+            reference[1],
+            reference[2],
+            reference[3],
+            reference[5],
+            reference[6],
+        ];
+        for (v, r) in values.iter().zip(expected.iter()) {
+            assert!((v - r).abs() < 1.0e-6, "{:?} != {:?}", values, expected);
+        }
+        assert!(sampler.start_next_sample());
+    }
+    // a requested 2D array takes dimensions 5 and 6, the following
+    // samples continue after it
+    let mut sampler: Box<Sampler> = make_sampler("sobol_pbrtcompat", &sampler_params, film)
+        .expect("\"sobol_pbrtcompat\" should be registered");
+    sampler.request_2d_array(1);
+    sampler.start_pixel(Point2i { x: 0, y: 0 });
+    for reference in PBRT_REFERENCE.iter() {
+        let _p_film: Point2f = sampler.get_2d();
+        let _time: Float = sampler.get_1d();
+        let _u: Float = sampler.get_1d();
+        let p_lens: Point2f = sampler.get_2d();
+        let array: Vec<Point2f> = sampler.get_2d_array(1).unwrap().to_vec();
+        let values: [Float; 4] = [array[0].x, array[0].y, p_lens.x, p_lens.y];
+        let expected: [Float; 4] = [reference[5], reference[6], reference[7], reference[8]];
+        for (v, r) in values.iter().zip(expected.iter()) {
+            assert!((v - r).abs() < 1.0e-6, "{:?} != {:?}", values, expected);
+        }
+        assert!(sampler.start_next_sample());
+    }
+    println!("sobol_pbrtcompat matches PBRT-v3 reference values");
+}
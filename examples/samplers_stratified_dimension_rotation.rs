@@ -0,0 +1,87 @@
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::core::sampling::cranley_patterson_rotation;
+use pbrt::samplers::stratified::StratifiedSampler;
+
+/// One sample per stratum of a *nx x ny* grid, after undoing the
+/// rotation by *offset*.
+fn is_stratified(points: &[Point2f], offset: Point2f, nx: usize, ny: usize) -> bool {
+    let mut strata: Vec<usize> = vec![0; nx * ny];
+    for p in points {
+        let u: Point2f = cranley_patterson_rotation(
+            *p,
+            Point2f {
+                x: 1.0 - offset.x,
+                y: 1.0 - offset.y,
+            },
+        );
+        let sx: usize = ((u.x * nx as Float) as usize).min(nx - 1);
+        let sy: usize = ((u.y * ny as Float) as usize).min(ny - 1);
+        strata[sy * nx + sx] += 1;
+    }
+    strata.iter().all(|c| *c == 1)
+}
+
+fn main() {
+    let (nx, ny): (usize, usize) = (4, 4);
+    let n: usize = nx * ny;
+    let n_dimensions: usize = 4;
+    // without jitter every dimension used to get the very same point set
+    let mut sampler: Sampler = Sampler::Stratified(StratifiedSampler::new(
+        nx as i32,
+        ny as i32,
+        false,
+        n_dimensions as i64,
+    ));
+    sampler.reseed(0_u64);
+    sampler.start_pixel(Point2i { x: 1, y: 2 });
+    let mut points: Vec<Vec<Point2f>> = vec![Vec::with_capacity(n); n_dimensions];
+    loop {
+        for dim in points.iter_mut() {
+            dim.push(sampler.get_2d());
+        }
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    for (d, dim) in points.iter().enumerate() {
+        let offset: Point2f = StratifiedSampler::dimension_offset_2d(d as i32);
+        assert!(is_stratified(dim, offset, nx, ny), "dimension {}", d);
+    }
+    // the film dimension is not rotated at all
+    let zero: Point2f = StratifiedSampler::dimension_offset_2d(0);
+    assert!(zero.x == 0.0 && zero.y == 0.0);
+    // same sample index, different dimensions: different points ...
+    for d in 1..n_dimensions {
+        for e in (d + 1)..n_dimensions {
+            let mut same: usize = 0;
+            for i in 0..n {
+                let a: Point2f = points[d][i];
+                let b: Point2f = points[e][i];
+                if (a.x - b.x).abs() < 1e-6 && (a.y - b.y).abs() < 1e-6 {
+                    same += 1;
+                }
+            }
+            assert_eq!(same, 0, "dimensions {} and {} share points", d, e);
+            // ... and (as point sets) no longer the same grid
+            let offset_d: Point2f = StratifiedSampler::dimension_offset_2d(d as i32);
+            let offset_e: Point2f = StratifiedSampler::dimension_offset_2d(e as i32);
+            assert!(
+                (offset_d.x - offset_e.x).abs() * nx as Float % 1.0 > 1e-3
+                    || (offset_d.y - offset_e.y).abs() * ny as Float % 1.0 > 1e-3,
+                "dimensions {} and {} are rotated onto the same grid",
+                d,
+                e
+            );
+        }
+    }
+    // deterministic: the same pixel gives the same rotated samples
+    sampler.reseed(0_u64);
+    sampler.start_pixel(Point2i { x: 1, y: 2 });
+    for dim in points.iter() {
+        let p: Point2f = sampler.get_2d();
+        assert!(p.x == dim[0].x && p.y == dim[0].y);
+    }
+    println!("{} dimensions rotated, each stratified", n_dimensions);
+}
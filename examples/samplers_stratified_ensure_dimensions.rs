@@ -0,0 +1,63 @@
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::core::sampling::cranley_patterson_rotation;
+use pbrt::samplers::stratified::StratifiedSampler;
+
+fn main() {
+    let (nx, ny): (i32, i32) = (4, 4);
+    let n: usize = (nx * ny) as usize;
+    // only 4 dimensions requested via "dimensions" ...
+    let mut sampler: Sampler = Sampler::Stratified(StratifiedSampler::new(nx, ny, true, 4));
+    // ... but the integrator needs 8
+    sampler.ensure_sampled_dimensions(8);
+    sampler.reseed(0_u64);
+    sampler.start_pixel(Point2i { x: 3, y: 5 });
+    let mut values_1d: Vec<Float> = Vec::with_capacity(n);
+    let mut values_2d: Vec<Point2f> = Vec::with_capacity(n);
+    loop {
+        let mut u: Float = 0.0;
+        let mut u2: Point2f = Point2f::default();
+        for _dim in 0..8 {
+            u = sampler.get_1d();
+            u2 = sampler.get_2d();
+        }
+        values_1d.push(u);
+        values_2d.push(u2);
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    assert_eq!(values_1d.len(), n);
+    // the 8th dimension has exactly one sample per stratum
+    let mut strata_1d: Vec<usize> = vec![0; n];
+    for u in &values_1d {
+        strata_1d[(u * n as Float) as usize] += 1;
+    }
+    assert!(
+        strata_1d.iter().all(|c| *c == 1),
+        "1D strata = {:?}",
+        strata_1d
+    );
+    // 2D dimensions are Cranley-Patterson rotated, undo that first
+    let offset: Point2f = StratifiedSampler::dimension_offset_2d(7);
+    let mut strata_2d: Vec<usize> = vec![0; n];
+    for u in &values_2d {
+        let u: Point2f = cranley_patterson_rotation(
+            *u,
+            Point2f {
+                x: 1.0 - offset.x,
+                y: 1.0 - offset.y,
+            },
+        );
+        let sx: usize = (u.x * nx as Float) as usize;
+        let sy: usize = (u.y * ny as Float) as usize;
+        strata_2d[sy * nx as usize + sx] += 1;
+    }
+    assert!(
+        strata_2d.iter().all(|c| *c == 1),
+        "2D strata = {:?}",
+        strata_2d
+    );
+    println!("8th dimension is stratified");
+}
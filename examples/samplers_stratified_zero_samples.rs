@@ -0,0 +1,47 @@
+use pbrt::core::api::{
+    pbrt_camera, pbrt_cleanup, pbrt_film, pbrt_init, pbrt_integrator, pbrt_look_at, pbrt_sampler,
+    pbrt_shape, pbrt_world_begin,
+};
+use pbrt::core::geometry::Point2i;
+use pbrt::core::integrator::RenderStats;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::stratified::StratifiedSampler;
+
+fn named(name: &str) -> ParamSet {
+    let mut params: ParamSet = ParamSet::default();
+    params.name = String::from(name);
+    params
+}
+
+fn main() {
+    // degenerate sample counts are clamped instead of asserting later
+    let mut sampler: Sampler = Sampler::Stratified(StratifiedSampler::new(0, 0, true, 4));
+    assert_eq!(sampler.get_samples_per_pixel(), 1);
+    sampler.reseed(0_u64);
+    sampler.start_pixel(Point2i { x: 0, y: 0 });
+    let _u = sampler.get_1d();
+    let _u2 = sampler.get_2d();
+    assert!(!sampler.start_next_sample());
+    let sampler: Sampler = Sampler::Stratified(StratifiedSampler::new(3, -2, true, 4));
+    assert_eq!(sampler.get_samples_per_pixel(), 3);
+    // a scene with "xsamples" 0 renders with one sample per pixel
+    let (width, height): (i32, i32) = (8, 4);
+    let (mut api_state, mut bsdf_state) = pbrt_init(1_u8);
+    pbrt_look_at(&mut api_state, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0);
+    pbrt_camera(&mut api_state, named("perspective"));
+    let mut film_params: ParamSet = named("image");
+    film_params.add_int(String::from("xresolution"), width);
+    film_params.add_int(String::from("yresolution"), height);
+    pbrt_film(&mut api_state, film_params);
+    let mut sampler_params: ParamSet = named("stratified");
+    sampler_params.add_int(String::from("xsamples"), 0);
+    sampler_params.add_int(String::from("ysamples"), 0);
+    pbrt_sampler(&mut api_state, sampler_params);
+    pbrt_integrator(&mut api_state, named("ambientocclusion"));
+    pbrt_world_begin(&mut api_state);
+    pbrt_shape(&mut api_state, &mut bsdf_state, named("sphere"));
+    let stats: RenderStats = pbrt_cleanup(&api_state);
+    assert_eq!(stats.total_samples, (width * height) as u64);
+    println!("rendered {} samples", stats.total_samples);
+}
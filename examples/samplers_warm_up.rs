@@ -0,0 +1,60 @@
+use pbrt::core::geometry::{Bounds2i, Point2f, Point2i};
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::Sampler;
+use pbrt::samplers::cmj::CmjSampler;
+use pbrt::samplers::halton::HaltonSampler;
+use pbrt::samplers::random::RandomSampler;
+use pbrt::samplers::stratified::StratifiedSampler;
+
+fn samplers() -> Vec<Sampler> {
+    let sample_bounds: Bounds2i = Bounds2i::new(Point2i { x: 0, y: 0 }, Point2i { x: 32, y: 32 });
+    let mut stratified: Sampler = Sampler::Stratified(StratifiedSampler::new(2, 2, true, 4));
+    stratified.request_2d_array(4);
+    vec![
+        stratified,
+        Sampler::Cmj(CmjSampler::new(2, 4)),
+        Sampler::Halton(HaltonSampler::new(8, &sample_bounds, false)),
+        Sampler::Random(RandomSampler::new(8)),
+    ]
+}
+
+/// All sample values (including 2D arrays) of pixel *p*.
+fn render_pixel(sampler: &mut Sampler, p: Point2i) -> Vec<Float> {
+    let mut values: Vec<Float> = Vec::new();
+    sampler.reseed(42_u64);
+    sampler.start_pixel(p);
+    loop {
+        for _d in 0..6 {
+            let u: Point2f = sampler.get_2d();
+            values.push(u.x);
+            values.push(u.y);
+            values.push(sampler.get_1d());
+        }
+        if let Some(array) = sampler.get_2d_array(4) {
+            for u in array {
+                values.push(u.x);
+                values.push(u.y);
+            }
+        }
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    values
+}
+
+fn main() {
+    for i in 0..samplers().len() {
+        // the warm up pixel itself renders like a cold one as well
+        for pixel in &[Point2i { x: 5, y: 7 }, Point2i::default()] {
+            let mut cold: Sampler = samplers().remove(i);
+            let mut warm: Sampler = samplers().remove(i);
+            warm.warm_up();
+            let cold_values: Vec<Float> = render_pixel(&mut cold, *pixel);
+            let warm_values: Vec<Float> = render_pixel(&mut warm, *pixel);
+            assert!(!cold_values.is_empty());
+            assert_eq!(cold_values, warm_values);
+        }
+    }
+    println!("warmed up samplers match cold runs");
+}
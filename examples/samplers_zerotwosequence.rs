@@ -0,0 +1,112 @@
+use pbrt::core::geometry::{Point2f, Point2i};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::sampler::{Sampler, SamplerTrait};
+use pbrt::samplers::zerotwosequence::ZeroTwoSequenceSampler;
+
+const SPP: i32 = 16;
+
+/// Checks that *points* (a power of two many) fall into different
+/// cells of all elementary intervals of the unit square.
+fn assert_net(points: &[Point2f]) {
+    let n: usize = points.len();
+    let mut nx: usize = 1;
+    while nx <= n {
+        let ny: usize = n / nx;
+        let mut cells: Vec<bool> = vec![false; n];
+        for p in points {
+            let cell: usize = (p.x * nx as Float) as usize * ny + (p.y * ny as Float) as usize;
+            assert!(!cells[cell], "{} x {} cell {} twice", nx, ny, cell);
+            cells[cell] = true;
+        }
+        nx *= 2;
+    }
+}
+
+/// Checks that the *values* fall into different strata.
+fn assert_stratified(values: &[Float]) {
+    let n: usize = values.len();
+    let mut strata: Vec<bool> = vec![false; n];
+    for v in values {
+        let stratum: usize = (v * n as Float) as usize;
+        assert!(!strata[stratum], "stratum {} twice", stratum);
+        strata[stratum] = true;
+    }
+}
+
+fn create(spp: i32) -> ZeroTwoSequenceSampler {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("pixelsamples"), spp);
+    match *ZeroTwoSequenceSampler::create(&params) {
+        Sampler::ZeroTwoSequence(sampler) => sampler,
+        _ => unreachable!(),
+    }
+}
+
+/// All samples of pixel *p*: one 1D and one 2D sample, plus a 1D and
+/// a 2D array, per pixel sample.
+fn pixel_samples(
+    sampler: &mut ZeroTwoSequenceSampler,
+    p: Point2i,
+) -> (Vec<Float>, Vec<Point2f>, Vec<Vec<Float>>, Vec<Vec<Point2f>>) {
+    let mut u_1d: Vec<Float> = Vec::new();
+    let mut u_2d: Vec<Point2f> = Vec::new();
+    let mut arrays_1d: Vec<Vec<Float>> = Vec::new();
+    let mut arrays_2d: Vec<Vec<Point2f>> = Vec::new();
+    sampler.start_pixel(p);
+    loop {
+        u_1d.push(sampler.get_1d());
+        u_2d.push(sampler.get_2d());
+        arrays_1d.push(sampler.get_1d_array(4).unwrap().to_vec());
+        arrays_2d.push(sampler.get_2d_array(8).unwrap().to_vec());
+        if !sampler.start_next_sample() {
+            break;
+        }
+    }
+    (u_1d, u_2d, arrays_1d, arrays_2d)
+}
+
+fn main() {
+    // sample counts (and array sizes) are rounded up to powers of two
+    for &(requested, expected) in &[(16, 16), (12, 16), (1, 1), (0, 1)] {
+        assert_eq!(create(requested).samples_per_pixel, expected);
+    }
+    let mut sampler: ZeroTwoSequenceSampler = create(SPP);
+    assert_eq!(sampler.round_count(5), 8);
+    sampler.request_1d_array(4);
+    sampler.request_2d_array(8);
+    let tile_sampler = |seed: u64| -> ZeroTwoSequenceSampler {
+        match *sampler.clone_with_seed(seed) {
+            Sampler::ZeroTwoSequence(sampler) => sampler,
+            _ => unreachable!(),
+        }
+    };
+    let mut first: ZeroTwoSequenceSampler = tile_sampler(0);
+    let (u_1d, u_2d, arrays_1d, arrays_2d) = pixel_samples(&mut first, Point2i { x: 5, y: 9 });
+    // the pixel samples are stratified in 1D and form a (0,4,2)-net
+    // in 2D ...
+    assert_stratified(&u_1d);
+    assert_net(&u_2d);
+    // ... and so are the arrays of each pixel sample
+    assert_eq!(arrays_1d.len(), SPP as usize);
+    for array in &arrays_1d {
+        assert_eq!(array.len(), 4);
+        assert_stratified(array);
+    }
+    for array in &arrays_2d {
+        assert_eq!(array.len(), 8);
+        assert_net(array);
+    }
+    // the scrambles depend on the pixel, neighbours differ ...
+    let (neighbour_1d, neighbour_2d, _, _) = pixel_samples(&mut first, Point2i { x: 6, y: 9 });
+    assert_ne!(neighbour_1d, u_1d);
+    assert!(neighbour_2d
+        .iter()
+        .zip(u_2d.iter())
+        .any(|(a, b)| a.x != b.x || a.y != b.y));
+    // ... but not on the tile (seed) the pixel is rendered in
+    let mut other: ZeroTwoSequenceSampler = tile_sampler(42);
+    let (other_1d, _, other_arrays_1d, _) = pixel_samples(&mut other, Point2i { x: 5, y: 9 });
+    assert_eq!(other_1d, u_1d);
+    assert_eq!(other_arrays_1d, arrays_1d);
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Point3f, Ray, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::shapes::curve::create_curve_shape;
+
+/// Straight curve along the x axis, from *x = -1* to *x = 1*.
+fn straight_params() -> ParamSet {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_point3fs(
+        String::from("P"),
+        vec![
+            -1.0,
+            0.0,
+            0.0,
+            -1.0 / 3.0,
+            0.0,
+            0.0,
+            1.0 / 3.0,
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+            0.0,
+        ],
+    );
+    params
+}
+
+fn create(params: &ParamSet) -> Vec<Arc<Shape>> {
+    let identity: Transform = Transform::default();
+    create_curve_shape(&identity, &identity, false, params)
+}
+
+/// Shoots a ray straight down (from *z = 5*) onto *shapes* and
+/// returns the closest hit.
+fn hit(shapes: &[Arc<Shape>], x: Float, y: Float, t_max: Float) -> Option<(Float, Float)> {
+    let ray: Ray = Ray {
+        o: Point3f { x, y, z: 5.0 },
+        d: Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        },
+        t_max,
+        time: 0.0,
+        differential: None,
+        medium: None,
+    };
+    let mut result: Option<(Float, Float)> = None;
+    for shape in shapes {
+        let mut t_hit: Float = 0.0;
+        let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+        if shape.intersect(&ray, &mut t_hit, &mut isect) {
+            assert!((isect.common.p.x - x).abs() < 1e-3);
+            if result.map_or(true, |(t, _)| t_hit < t) {
+                result = Some((t_hit, isect.uv.x));
+            }
+        }
+    }
+    result
+}
+
+fn main() {
+    let inf: Float = std::f32::INFINITY;
+    // a flat curve of constant width
+    let mut params: ParamSet = straight_params();
+    params.add_float(String::from("width"), 0.2);
+    let shapes: Vec<Arc<Shape>> = create(&params);
+    // one segment, split 2^3 times by default
+    assert_eq!(shapes.len(), 8);
+    let (t, u) = hit(&shapes, 0.0, 0.0, inf).unwrap();
+    assert!((t - 5.0).abs() < 1e-3, "{}", t);
+    assert!((u - 0.5).abs() < 1e-3, "{}", u);
+    assert!(hit(&shapes, 0.4, 0.05, inf).is_some());
+    assert!(hit(&shapes, 0.4, 0.15, inf).is_none());
+    assert!(hit(&shapes, 1.2, 0.0, inf).is_none());
+    // the ray ends before reaching the curve
+    assert!(hit(&shapes, 0.0, 0.0, 4.0).is_none());
+    // the width varies linearly along the curve
+    let mut params: ParamSet = straight_params();
+    params.add_float(String::from("width0"), 0.4);
+    params.add_float(String::from("width1"), 0.0);
+    let shapes: Vec<Arc<Shape>> = create(&params);
+    assert!(hit(&shapes, -0.5, 0.12, inf).is_some());
+    assert!(hit(&shapes, 0.5, 0.12, inf).is_none());
+    // cylinders are hit wherever flat curves are
+    let mut params: ParamSet = straight_params();
+    params.add_float(String::from("width"), 0.2);
+    params.add_string(String::from("type"), String::from("cylinder"));
+    let shapes: Vec<Arc<Shape>> = create(&params);
+    assert!(hit(&shapes, 0.2, 0.05, inf).is_some());
+    assert!(hit(&shapes, 0.2, 0.15, inf).is_none());
+    // several segments sharing control points
+    let mut params: ParamSet = ParamSet::default();
+    let mut cp: Vec<Float> = Vec::new();
+    for i in 0..7 {
+        cp.extend_from_slice(&[i as Float / 3.0, 0.0, 0.0]);
+    }
+    params.add_point3fs(String::from("P"), cp.clone());
+    params.add_int(String::from("splitdepth"), 0);
+    let shapes: Vec<Arc<Shape>> = create(&params);
+    assert_eq!(shapes.len(), 2);
+    // each segment is parameterized from 0 to 1
+    let (_t, u) = hit(&shapes, 0.5, 0.0, inf).unwrap();
+    assert!((u - 0.5).abs() < 1e-3, "{}", u);
+    let (_t, u) = hit(&shapes, 1.5, 0.0, inf).unwrap();
+    assert!((u - 0.5).abs() < 1e-3, "{}", u);
+    let mut params: ParamSet = ParamSet::default();
+    params.add_point3fs(String::from("P"), cp[..15].to_vec());
+    params.add_string(String::from("basis"), String::from("bspline"));
+    params.add_int(String::from("splitdepth"), 0);
+    let shapes: Vec<Arc<Shape>> = create(&params);
+    assert_eq!(shapes.len(), 2);
+    assert!(hit(&shapes, 0.7, 0.0, inf).is_some());
+    let mut params: ParamSet = ParamSet::default();
+    params.add_point3fs(String::from("P"), cp[..9].to_vec());
+    params.add_int(String::from("degree"), 2);
+    params.add_int(String::from("splitdepth"), 0);
+    assert_eq!(create(&params).len(), 1);
+    // 6 control points don't make up cubic Bezier segments
+    let mut params: ParamSet = ParamSet::default();
+    params.add_point3fs(String::from("P"), cp[..18].to_vec());
+    assert!(create(&params).is_empty());
+    // ribbons facing the ray (with parallel normals) ...
+    let mut params: ParamSet = straight_params();
+    params.add_float(String::from("width"), 0.2);
+    params.add_string(String::from("type"), String::from("ribbon"));
+    params.add_normal3fs(String::from("N"), vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+    let shapes: Vec<Arc<Shape>> = create(&params);
+    assert!(hit(&shapes, 0.0, 0.05, inf).is_some());
+    // ... are invisible when seen edge-on ...
+    let mut params: ParamSet = straight_params();
+    params.add_float(String::from("width"), 0.2);
+    params.add_string(String::from("type"), String::from("ribbon"));
+    params.add_normal3fs(String::from("N"), vec![0.0, 1.0, 0.0, 0.0, 1.0, 0.0]);
+    let shapes: Vec<Arc<Shape>> = create(&params);
+    assert!(hit(&shapes, 0.0, 0.05, inf).is_none());
+    // ... and twist from one endpoint normal to the other
+    let mut params: ParamSet = straight_params();
+    params.add_float(String::from("width"), 0.2);
+    params.add_string(String::from("type"), String::from("ribbon"));
+    params.add_normal3fs(String::from("N"), vec![0.0, 0.0, 1.0, 0.0, 1.0, 0.0]);
+    let shapes: Vec<Arc<Shape>> = create(&params);
+    assert!(hit(&shapes, -0.9, 0.05, inf).is_some());
+    assert!(hit(&shapes, 0.99, 0.05, inf).is_none());
+    // ribbons need one normal per segment endpoint
+    let mut params: ParamSet = straight_params();
+    params.add_string(String::from("type"), String::from("ribbon"));
+    assert!(create(&params).is_empty());
+    params.add_normal3fs(String::from("N"), vec![0.0, 0.0, 1.0]);
+    assert!(create(&params).is_empty());
+}
@@ -0,0 +1,106 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Point2f, Point3f, Ray, Vector3f};
+use pbrt::core::interaction::{InteractionCommon, SurfaceInteraction};
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::rng::Rng;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::shapes::cylinder::Cylinder;
+
+fn cylinder(phi_max: Float) -> Arc<Shape> {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("radius"), 1.0);
+    params.add_float(String::from("zmin"), 0.0);
+    params.add_float(String::from("zmax"), 2.0);
+    params.add_float(String::from("phimax"), phi_max);
+    Cylinder::create(&Transform::default(), &Transform::default(), false, &params)
+}
+
+fn ray(o: (Float, Float, Float), d: (Float, Float, Float)) -> Ray {
+    Ray {
+        o: Point3f {
+            x: o.0,
+            y: o.1,
+            z: o.2,
+        },
+        d: Vector3f {
+            x: d.0,
+            y: d.1,
+            z: d.2,
+        },
+        t_max: std::f32::INFINITY,
+        time: 0.0,
+        differential: None,
+        medium: None,
+    }
+}
+
+/// Returns the hit distance (if any), after checking that the fast
+/// occlusion test agrees and the hit point lies on the surface.
+fn hit(shape: &Arc<Shape>, ray: &Ray) -> Option<Float> {
+    let mut t_hit: Float = 0.0;
+    let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+    let found: bool = shape.intersect(ray, &mut t_hit, &mut isect);
+    assert_eq!(shape.intersect_p(ray), found);
+    if found {
+        let p: Point3f = isect.common.p;
+        // the error bounds cover the distance to the exact surface
+        let r: Float = (p.x * p.x + p.y * p.y).sqrt();
+        let err: Vector3f = isect.common.p_error;
+        assert!(err.x > 0.0 || p.x == 0.0);
+        assert!((r - 1.0).abs() <= err.x + err.y + 1e-6, "{} {:?}", r, err);
+        assert!(p.z >= 0.0 && p.z <= 2.0);
+        Some(t_hit)
+    } else {
+        None
+    }
+}
+
+fn main() {
+    let full: Arc<Shape> = cylinder(360.0);
+    let half: Arc<Shape> = cylinder(180.0);
+    assert!((half.area() - 2.0 * PI).abs() < 1e-5);
+    // from outside, the near root is used ...
+    let t: Float = hit(&half, &ray((0.0, 5.0, 1.0), (0.0, -1.0, 0.0))).unwrap();
+    assert!((t - 4.0).abs() < 1e-5, "{}", t);
+    // ... unless it's outside the phi sweep, then the far one is
+    let t: Float = hit(&half, &ray((0.0, -5.0, 1.0), (0.0, 1.0, 0.0))).unwrap();
+    assert!((t - 6.0).abs() < 1e-5, "{}", t);
+    // from the inside, only the far root is in front of the ray
+    let t: Float = hit(&full, &ray((0.0, 0.0, 1.0), (0.0, 1.0, 0.0))).unwrap();
+    assert!((t - 1.0).abs() < 1e-5, "{}", t);
+    // both roots outside the z range
+    assert!(hit(&full, &ray((-5.0, 0.5, 3.0), (1.0, 0.0, 0.0))).is_none());
+    // one root within the z range
+    let t: Float = hit(&full, &ray((-2.0, 0.0, 3.5), (1.0, 0.0, -1.0))).unwrap();
+    assert!((t - 3.0).abs() < 1e-5, "{}", t);
+    // rays parallel to the axis never hit
+    assert!(hit(&full, &ray((0.5, 0.5, -5.0), (0.0, 0.0, 1.0))).is_none());
+    assert!(hit(&full, &ray((1.0, 0.0, -5.0), (0.0, 0.0, 1.0))).is_none());
+    // a ray grazing the surface hits it at the (double) root
+    let t: Float = hit(&full, &ray((1.0, -5.0, 1.0), (0.0, 1.0, 0.0))).unwrap();
+    assert!((t - 5.0).abs() < 1e-4, "{}", t);
+    assert!(hit(&full, &ray((1.001, -5.0, 1.0), (0.0, 1.0, 0.0))).is_none());
+    // the tangent point lies outside of a quarter sweep
+    let quarter: Arc<Shape> = cylinder(90.0);
+    assert!(hit(&quarter, &ray((-1.0, -5.0, 1.0), (0.0, 1.0, 0.0))).is_none());
+    // samples lie on the partial surface, with outward normals
+    let mut rng: Rng = Rng::new();
+    for _ in 0..1000 {
+        let u: Point2f = Point2f {
+            x: rng.uniform_float(),
+            y: rng.uniform_float(),
+        };
+        let mut pdf: Float = 0.0;
+        let it: InteractionCommon = half.sample(u, &mut pdf);
+        assert!((pdf - 1.0 / (2.0 * PI)).abs() < 1e-5);
+        let r: Float = (it.p.x * it.p.x + it.p.y * it.p.y).sqrt();
+        assert!((r - 1.0).abs() < 1e-5);
+        assert!(it.p.y >= -1e-5 && it.p.z >= 0.0 && it.p.z <= 2.0);
+        assert!(it.n.x * it.p.x + it.n.y * it.p.y > 0.99);
+        assert!(it.p_error.x + it.p_error.y > 0.0);
+    }
+}
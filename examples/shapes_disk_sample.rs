@@ -0,0 +1,160 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
+use pbrt::core::interaction::{InteractionCommon, SurfaceInteraction};
+use pbrt::core::medium::MediumInterface;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::rng::Rng;
+use pbrt::core::shape::Shape;
+use pbrt::core::transform::Transform;
+use pbrt::lights::diffuse::DiffuseAreaLight;
+use pbrt::shapes::disk::Disk;
+
+/// Quarter of an annulus at *z = 1*, between radius 1 and 2.
+fn quarter_annulus() -> Arc<Shape> {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("height"), 1.0);
+    params.add_float(String::from("radius"), 2.0);
+    params.add_float(String::from("innerradius"), 1.0);
+    params.add_float(String::from("phimax"), 90.0);
+    Disk::create(&Transform::default(), &Transform::default(), false, &params)
+}
+
+fn down(x: Float, y: Float) -> Ray {
+    Ray {
+        o: Point3f { x, y, z: 5.0 },
+        d: Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        },
+        t_max: std::f32::INFINITY,
+        time: 0.0,
+        differential: None,
+        medium: None,
+    }
+}
+
+/// Monte Carlo estimate of the irradiance at *p* (facing down) due
+/// to a disk shaped emitter with *Le = 1*.
+fn irradiance(shape: Arc<Shape>, p: Point3f, rng: &mut Rng) -> Float {
+    let light: DiffuseAreaLight = DiffuseAreaLight::new(
+        &Transform::default(),
+        &MediumInterface::default(),
+        &Spectrum::new(1.0),
+        1,
+        shape,
+        false,
+    );
+    let mut iref: InteractionCommon = InteractionCommon::default();
+    iref.p = p;
+    iref.n = Normal3f {
+        x: 0.0,
+        y: 0.0,
+        z: -1.0,
+    };
+    let n_samples: usize = 100_000;
+    let mut sum: Float = 0.0;
+    for _ in 0..n_samples {
+        let u: Point2f = Point2f {
+            x: rng.uniform_float(),
+            y: rng.uniform_float(),
+        };
+        let mut wi: Vector3f = Vector3f::default();
+        let mut pdf: Float = 0.0;
+        let (li, _vis) = light.sample_li(&iref, u, &mut wi, &mut pdf);
+        if pdf > 0.0 {
+            sum += li.c[0] * wi.z.abs() / pdf;
+        }
+    }
+    sum / n_samples as Float
+}
+
+fn main() {
+    let shape: Arc<Shape> = quarter_annulus();
+    if let Shape::Dsk(ref disk) = *shape {
+        assert_eq!(disk.height, 1.0);
+        assert_eq!(disk.radius, 2.0);
+        assert_eq!(disk.inner_radius, 1.0);
+        assert!((disk.phi_max - 0.5 * PI).abs() < 1e-6);
+    } else {
+        panic!("Disk::create() didn't create a disk");
+    }
+    let area: Float = shape.area();
+    assert!((area - 0.75 * PI).abs() < 1e-5, "{}", area);
+    // rays hit the annulus, but not the hole or the missing sweep
+    for &(r, phi, expected) in &[
+        (1.5, 0.25 * PI, true),
+        (1.9, 0.1 * PI, true),
+        (0.5, 0.25 * PI, false),
+        (2.5, 0.25 * PI, false),
+        (1.5, 0.75 * PI, false),
+        (1.5, 1.5 * PI, false),
+    ] {
+        let ray: Ray = down(r * phi.cos(), r * phi.sin());
+        let mut t_hit: Float = 0.0;
+        let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+        assert_eq!(shape.intersect(&ray, &mut t_hit, &mut isect), expected);
+        assert_eq!(shape.intersect_p(&ray), expected);
+        if expected {
+            assert!((t_hit - 4.0).abs() < 1e-5);
+            assert!((isect.common.p.z - 1.0).abs() < 1e-5);
+        }
+    }
+    // samples are spread uniformly over the partial annulus
+    let mut rng: Rng = Rng::new();
+    let n_samples: usize = 10_000;
+    let mut n_inner: usize = 0;
+    let mut n_first_half: usize = 0;
+    for _ in 0..n_samples {
+        let u: Point2f = Point2f {
+            x: rng.uniform_float(),
+            y: rng.uniform_float(),
+        };
+        let mut pdf: Float = 0.0;
+        let it: InteractionCommon = shape.sample(u, &mut pdf);
+        assert!((pdf - 1.0 / area).abs() < 1e-5);
+        assert!((it.p.z - 1.0).abs() < 1e-5);
+        let r2: Float = it.p.x * it.p.x + it.p.y * it.p.y;
+        assert!(r2 >= 1.0 - 1e-4 && r2 <= 4.0 + 1e-4, "{}", r2);
+        assert!(it.p.x >= -1e-5 && it.p.y >= -1e-5);
+        // half of the area lies within r^2 < 2.5, half below 45 degrees
+        if r2 < 2.5 {
+            n_inner += 1;
+        }
+        if it.p.y < it.p.x {
+            n_first_half += 1;
+        }
+    }
+    let inner: Float = n_inner as Float / n_samples as Float;
+    let first_half: Float = n_first_half as Float / n_samples as Float;
+    assert!((inner - 0.5).abs() < 0.03, "{}", inner);
+    assert!((first_half - 0.5).abs() < 0.03, "{}", first_half);
+    // as an emitter: irradiance on the axis, one unit above the
+    // disk, matches the analytic value
+    let p: Point3f = Point3f {
+        x: 0.0,
+        y: 0.0,
+        z: 2.0,
+    };
+    let e: Float = irradiance(shape, p, &mut rng);
+    let expected: Float = 0.25 * PI * (4.0 / 5.0 - 1.0 / 2.0);
+    println!("quarter annulus: {} (expected {})", e, expected);
+    assert!((e - expected).abs() < 0.01 * expected.max(1.0), "{}", e);
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("height"), 1.0);
+    let full: Arc<Shape> =
+        Disk::create(&Transform::default(), &Transform::default(), false, &params);
+    // a ray through the center gets a valid shading frame
+    let mut t_hit: Float = 0.0;
+    let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+    assert!(full.intersect(&down(0.0, 0.0), &mut t_hit, &mut isect));
+    assert!((isect.common.n.z.abs() - 1.0).abs() < 1e-5);
+    assert_eq!(isect.uv.x, 0.0);
+    assert_eq!(isect.uv.y, 1.0);
+    let e: Float = irradiance(full, p, &mut rng);
+    println!("full disk: {} (expected {})", e, 0.5 * PI);
+    assert!((e - 0.5 * PI).abs() < 0.02, "{}", e);
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{nrm_dot_nrm, vec3_dot_nrm};
+use pbrt::core::geometry::{Normal3f, Point3f, Ray, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::ParamSet;
+use pbrt::core::pbrt::Float;
+use pbrt::core::shape::Shape;
+use pbrt::core::texture::Texture;
+use pbrt::core::transform::Transform;
+use pbrt::shapes::plymesh::create_ply_mesh;
+use pbrt::shapes::triangle::create_triangle_mesh;
+
+type FloatTextures = HashMap<String, Arc<dyn Texture<Float> + Send + Sync>>;
+
+/// Unit square in the *z = 0* plane, made of two triangles.
+fn quad_params() -> ParamSet {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_ints(String::from("indices"), vec![0, 1, 2, 0, 2, 3]);
+    params.add_point3fs(
+        String::from("P"),
+        vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0],
+    );
+    params.add_floats(
+        String::from("uv"),
+        vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0],
+    );
+    params
+}
+
+/// Shoots a ray straight down (from *z = 3*) onto *shapes*.
+fn hit(shapes: &[Arc<Shape>], x: Float, y: Float) -> Option<SurfaceInteraction> {
+    let ray: Ray = Ray {
+        o: Point3f { x, y, z: 3.0 },
+        d: Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        },
+        t_max: std::f32::INFINITY,
+        time: 0.0,
+        differential: None,
+        medium: None,
+    };
+    let mut result: Option<SurfaceInteraction> = None;
+    let mut t_closest: Float = std::f32::INFINITY;
+    for shape in shapes {
+        let mut t_hit: Float = 0.0;
+        let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+        if shape.intersect(&ray, &mut t_hit, &mut isect) && t_hit < t_closest {
+            t_closest = t_hit;
+            result = Some(isect);
+        }
+    }
+    result
+}
+
+fn main() {
+    let float_textures: Arc<FloatTextures> = Arc::new(HashMap::new());
+    // the mesh is given in object space, one unit above the origin
+    let object_to_world: Transform = Transform::translate(&Vector3f {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    });
+    let world_to_object: Transform = Transform::inverse(&object_to_world);
+    // per vertex normals, but no tangents
+    let mut params: ParamSet = quad_params();
+    params.add_normal3fs(
+        String::from("N"),
+        vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.6, 0.0, 0.8, 0.0, 0.0, 1.0],
+    );
+    let shapes: Vec<Arc<Shape>> = create_triangle_mesh(
+        &object_to_world,
+        &world_to_object,
+        false,
+        &params,
+        float_textures.clone(),
+    );
+    assert_eq!(shapes.len(), 2);
+    let normals: [Normal3f; 3] = [
+        Normal3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        Normal3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        Normal3f {
+            x: 0.6,
+            y: 0.0,
+            z: 0.8,
+        },
+    ];
+    for &(x, y) in &[(0.5, 0.25), (0.9, 0.1), (0.3, 0.2), (0.99, 0.98)] {
+        let isect: SurfaceInteraction = hit(&shapes, x, y).unwrap();
+        // world space hit point and barycentric interpolation of uv
+        assert!((isect.common.p.z - 1.0).abs() < 1e-5);
+        assert!((isect.uv.x - x).abs() < 1e-5 && (isect.uv.y - y).abs() < 1e-5);
+        // the shading normal is the interpolated vertex normal ...
+        let (b0, b1, b2) = (1.0 - x, x - y, y);
+        let expected: Normal3f = (normals[0] * b0 + normals[1] * b1 + normals[2] * b2).normalize();
+        assert!(
+            nrm_dot_nrm(&isect.shading.n, &expected) > 0.9999,
+            "{:?}",
+            isect.shading.n
+        );
+        // ... and the tangent derived from the uv gradient is
+        // perpendicular to it
+        assert!(vec3_dot_nrm(&isect.shading.dpdu, &isect.shading.n).abs() < 1e-5);
+        assert!(vec3_dot_nrm(&isect.shading.dpdv, &isect.shading.n).abs() < 1e-5);
+        assert!((isect.shading.dpdu.length() - 1.0).abs() < 1e-5);
+        // the geometric normal faces the same side as the shading normal
+        assert!(nrm_dot_nrm(&isect.common.n, &isect.shading.n) > 0.0);
+    }
+    // rays exactly on the shared edge never slip through
+    for i in 0..=100 {
+        let t: Float = i as Float / 100.0;
+        assert!(hit(&shapes, t, t).is_some(), "missed the diagonal at {}", t);
+    }
+    // without normals the geometric normal follows the orientation
+    let plain: ParamSet = quad_params();
+    let front: Vec<Arc<Shape>> = create_triangle_mesh(
+        &object_to_world,
+        &world_to_object,
+        false,
+        &plain,
+        float_textures.clone(),
+    );
+    let back: Vec<Arc<Shape>> = create_triangle_mesh(
+        &object_to_world,
+        &world_to_object,
+        true,
+        &plain,
+        float_textures.clone(),
+    );
+    let n_front: Normal3f = hit(&front, 0.5, 0.25).unwrap().common.n;
+    let n_back: Normal3f = hit(&back, 0.5, 0.25).unwrap().common.n;
+    assert!((n_front.z.abs() - 1.0).abs() < 1e-5);
+    assert_eq!(n_front.z, -n_back.z);
+    // mismatched normals are discarded, bad indices reject the mesh
+    let mut params: ParamSet = quad_params();
+    params.add_normal3fs(String::from("N"), vec![0.0, 0.0, 1.0]);
+    let shapes: Vec<Arc<Shape>> = create_triangle_mesh(
+        &object_to_world,
+        &world_to_object,
+        false,
+        &params,
+        float_textures.clone(),
+    );
+    assert_eq!(shapes.len(), 2);
+    let mut params: ParamSet = ParamSet::default();
+    params.add_ints(String::from("indices"), vec![0, 1, 4]);
+    params.add_point3fs(
+        String::from("P"),
+        vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+    );
+    assert!(create_triangle_mesh(
+        &object_to_world,
+        &world_to_object,
+        false,
+        &params,
+        float_textures.clone(),
+    )
+    .is_empty());
+    // the same quad read from a PLY file (with double precision
+    // vertices and extra color properties)
+    let path = std::env::temp_dir().join("shapes_triangle_create_triangle_mesh.ply");
+    let mut file = std::fs::File::create(&path).unwrap();
+    write!(
+        file,
+        "ply\n\
+         format ascii 1.0\n\
+         element vertex 4\n\
+         property double x\n\
+         property double y\n\
+         property double z\n\
+         property uchar red\n\
+         element face 1\n\
+         property list uchar int vertex_indices\n\
+         end_header\n\
+         0 0 0 255\n\
+         1 0 0 255\n\
+         1 1 0 255\n\
+         0 1 0 255\n\
+         4 0 1 2 3\n"
+    )
+    .unwrap();
+    drop(file);
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(
+        String::from("filename"),
+        String::from(path.to_str().unwrap()),
+    );
+    let ply_shapes: Vec<Arc<Shape>> = create_ply_mesh(
+        &object_to_world,
+        &world_to_object,
+        false,
+        &params,
+        float_textures,
+        None,
+    );
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(ply_shapes.len(), 2);
+    for &(x, y) in &[(0.5, 0.25), (0.25, 0.5)] {
+        let isect: SurfaceInteraction = hit(&ply_shapes, x, y).unwrap();
+        assert!((isect.common.p.z - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::{ParamSet, TextureParams};
+use pbrt::core::pbrt::Float;
+use pbrt::core::texture::{
+    IdentityMapping3D, Texture, TextureMapping2D, TextureMapping3D, UVMapping2D,
+};
+use pbrt::core::transform::Transform;
+use pbrt::textures::checkerboard::{AAMethod, Checkerboard2DTexture, Checkerboard3DTexture};
+use pbrt::textures::constant::ConstantTexture;
+
+fn uv_mapping(scale: Float) -> Box<TextureMapping2D> {
+    Box::new(TextureMapping2D::UV(UVMapping2D {
+        su: scale,
+        sv: scale,
+        du: 0.0,
+        dv: 0.0,
+    }))
+}
+
+/// Checkerboard alternating between 1 and 0.
+fn checkerboard(scale: Float, aa_method: AAMethod) -> Checkerboard2DTexture<Float> {
+    Checkerboard2DTexture::new(
+        uv_mapping(scale),
+        Arc::new(ConstantTexture::new(1.0 as Float)),
+        Arc::new(ConstantTexture::new(0.0 as Float)),
+        aa_method,
+    )
+}
+
+/// Surface point at *p* with texture coordinates *(u, v)*, moving
+/// *dudx* in *u* and *dvdy* in *v* per pixel.
+fn interaction<'a>(
+    p: Point3f,
+    u: Float,
+    v: Float,
+    dudx: Float,
+    dvdy: Float,
+) -> SurfaceInteraction<'a> {
+    let si: SurfaceInteraction = SurfaceInteraction::new(
+        &p,
+        &Vector3f::default(),
+        Point2f { x: u, y: v },
+        &Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        &Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        &Vector3f {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+        &Normal3f::default(),
+        &Normal3f::default(),
+        0.0,
+        None,
+    );
+    si.dudx.set(dudx);
+    si.dvdy.set(dvdy);
+    si
+}
+
+fn lookup(
+    texture: &Checkerboard2DTexture<Float>,
+    u: Float,
+    v: Float,
+    du: Float,
+    dv: Float,
+) -> Float {
+    texture.evaluate(&interaction(Point3f::default(), u, v, du, dv))
+}
+
+fn main() {
+    // point sampling alternates between both textures, also for
+    // negative texture coordinates
+    let point: Checkerboard2DTexture<Float> = checkerboard(1.0, AAMethod::None);
+    for &(u, v, expected) in &[
+        (0.5, 0.5, 1.0),
+        (1.5, 0.5, 0.0),
+        (-0.5, 0.5, 0.0),
+        (-0.5, -0.5, 1.0),
+        (-1.5, 1.5, 0.0),
+    ] {
+        assert_eq!(lookup(&point, u, v, 0.0, 0.0), expected, "({}, {})", u, v);
+    }
+    // the box filter matches point sampling within a single check ...
+    let filtered: Checkerboard2DTexture<Float> = checkerboard(1.0, AAMethod::ClosedForm);
+    for &(u, v) in &[(0.5, 0.5), (1.5, 0.5), (-0.5, 0.5), (-0.5, -0.5)] {
+        assert_eq!(
+            lookup(&filtered, u, v, 0.1, 0.1),
+            lookup(&point, u, v, 0.0, 0.0)
+        );
+    }
+    // ... blends both textures across an edge ...
+    let straddle: Float = lookup(&filtered, 1.0, 0.5, 0.25, 0.0);
+    assert!((straddle - 0.5).abs() < 1e-5, "{}", straddle);
+    let partial: Float = lookup(&filtered, 0.9, 0.5, 0.25, 0.0);
+    assert!(partial > 0.5 && partial < 1.0, "{}", partial);
+    // ... and stays within the range of both textures
+    for i in 0..100 {
+        let u: Float = -2.0 + i as Float * 0.0437;
+        let value: Float = lookup(&filtered, u, 0.3 * u, 0.3, 0.7);
+        assert!((0.0..=1.0).contains(&value), "{} {}", u, value);
+    }
+    // checks smaller than the footprint average out ...
+    assert_eq!(lookup(&filtered, 0.3, 0.7, 3.0, 0.1), 0.5);
+    assert_eq!(lookup(&filtered, 0.3, 0.7, Float::INFINITY, 0.1), 0.5);
+    // ... so do texture coordinates too large to resolve single checks
+    let huge: Checkerboard2DTexture<Float> = checkerboard(1e8, AAMethod::ClosedForm);
+    assert_eq!(lookup(&huge, 0.5, 0.5, 0.0, 0.0), 0.5);
+    // tiny footprints fall back to point sampling
+    let tiny: Checkerboard2DTexture<Float> = checkerboard(1e-6, AAMethod::ClosedForm);
+    assert_eq!(lookup(&tiny, 0.5, 0.5, 1e-3, 1e-3), 1.0);
+    assert_eq!(lookup(&filtered, 1.5, 0.5, 1e-7, 1e-7), 0.0);
+    // the solid checkerboard alternates in all three dimensions
+    let solid: Checkerboard3DTexture<Float> = Checkerboard3DTexture::new(
+        Box::new(TextureMapping3D::Identity(IdentityMapping3D::new(
+            Transform::default(),
+        ))),
+        Arc::new(ConstantTexture::new(1.0 as Float)),
+        Arc::new(ConstantTexture::new(0.0 as Float)),
+    );
+    for &(x, y, z, expected) in &[
+        (0.5, 0.5, 0.5, 1.0),
+        (1.5, 0.5, 0.5, 0.0),
+        (1.5, 1.5, 0.5, 1.0),
+        (1.5, 1.5, 1.5, 0.0),
+        (-0.5, 0.5, 0.5, 0.0),
+        (-0.5, -0.5, -0.5, 0.0),
+    ] {
+        let si: SurfaceInteraction = interaction(Point3f { x, y, z }, 0.0, 0.0, 0.0, 0.0);
+        assert_eq!(solid.evaluate(&si), expected, "({}, {}, {})", x, y, z);
+    }
+    // create() reads "aamode", closed form antialiasing is the default
+    let texture_params = |aamode: Option<&str>| -> TextureParams {
+        let mut params: ParamSet = ParamSet::default();
+        if let Some(aamode) = aamode {
+            params.add_string(String::from("aamode"), String::from(aamode));
+        }
+        TextureParams::new(
+            params,
+            ParamSet::default(),
+            Arc::new(HashMap::new()),
+            Arc::new(HashMap::new()),
+        )
+    };
+    for &(aamode, expected) in &[
+        (None, AAMethod::ClosedForm),
+        (Some("none"), AAMethod::None),
+        (Some("closedform"), AAMethod::ClosedForm),
+        (Some("supersample"), AAMethod::ClosedForm),
+    ] {
+        let created: Checkerboard2DTexture<Float> = Checkerboard2DTexture::create(
+            &mut texture_params(aamode),
+            uv_mapping(1.0),
+            Arc::new(ConstantTexture::new(1.0 as Float)),
+            Arc::new(ConstantTexture::new(0.0 as Float)),
+        );
+        assert_eq!(created.aa_method, expected);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::{ParamSet, TextureParams};
+use pbrt::core::pbrt::Float;
+use pbrt::core::texture::{noise_flt, IdentityMapping3D, Texture, TextureMapping3D};
+use pbrt::core::transform::Transform;
+use pbrt::textures::fbm::FBmTexture;
+use pbrt::textures::wrinkled::WrinkledTexture;
+
+fn identity() -> Box<TextureMapping3D> {
+    Box::new(TextureMapping3D::Identity(IdentityMapping3D::new(
+        Transform::default(),
+    )))
+}
+
+/// Surface point at *p*, moving *width* along x and y per pixel.
+fn interaction<'a>(p: (Float, Float, Float), width: Float) -> SurfaceInteraction<'a> {
+    let si: SurfaceInteraction = SurfaceInteraction::new(
+        &Point3f {
+            x: p.0,
+            y: p.1,
+            z: p.2,
+        },
+        &Vector3f::default(),
+        Point2f::default(),
+        &Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        &Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        &Vector3f {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+        &Normal3f::default(),
+        &Normal3f::default(),
+        0.0,
+        None,
+    );
+    si.dpdx.set(Vector3f {
+        x: width,
+        y: 0.0,
+        z: 0.0,
+    });
+    si.dpdy.set(Vector3f {
+        x: 0.0,
+        y: width,
+        z: 0.0,
+    });
+    si
+}
+
+fn assert_close(a: Float, b: Float) {
+    assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+}
+
+fn main() {
+    // gradient noise vanishes on the integer lattice ...
+    for &(x, y, z) in &[(0.0, 0.0, 0.0), (3.0, -5.0, 17.0), (255.0, 256.0, -1.0)] {
+        assert_eq!(noise_flt(x, y, z), 0.0);
+    }
+    // ... and uses the same lattice as PBRT (and Ken Perlin's reference
+    // implementation, which gives 0.136919958784 at (3.14, 42, 7))
+    assert_close(noise_flt(3.14, 42.0, 7.0), 0.136_920);
+    // PBRT's FBm() and Turbulence() with 8 octaves (and omega 0.5) at
+    // a few points, for unfiltered lookups
+    let fbm: FBmTexture = FBmTexture::new(identity(), 8, 0.5);
+    let wrinkled: WrinkledTexture = WrinkledTexture::new(identity(), 8, 0.5);
+    for &(p, noise, fbm_value, turbulence) in &[
+        ((0.5, 0.5, 0.5), -0.125_000, -0.118_038, 0.187_651),
+        ((1.25, -2.75, 3.5), 0.120_097, 0.361_962, 0.365_035),
+        ((-7.3, 0.1, 12.9), -0.456_209, -0.355_037, 0.640_999),
+        ((3.14, 42.0, 7.0), 0.136_920, 0.363_955, 0.386_873),
+    ] {
+        assert_close(noise_flt(p.0, p.1, p.2), noise);
+        let si: SurfaceInteraction = interaction(p, 0.0);
+        let value: Float = fbm.evaluate(&si);
+        assert_close(value, fbm_value);
+        // turbulence adds the average of the (empty) partial octave
+        let value: Float = wrinkled.evaluate(&si);
+        assert_close(value, turbulence + 0.2 * (0.5 as Float).powi(8));
+    }
+    // octaves above the Nyquist limit of the footprint are dropped:
+    // a footprint of 1/8 resolves two octaves ...
+    let p: (Float, Float, Float) = (1.25, -2.75, 3.5);
+    let value: Float = fbm.evaluate(&interaction(p, 0.125));
+    assert_close(value, 0.233_934);
+    let two_octaves: Float = FBmTexture::new(identity(), 2, 0.5).evaluate(&interaction(p, 0.0));
+    assert_close(value, two_octaves);
+    // ... and fractional octaves fade in smoothly
+    let mut previous: Float = fbm.evaluate(&interaction(p, 0.125));
+    for i in 1..=10 {
+        let width: Float = 0.125 * (2.0 as Float).powf(-(i as Float) / 10.0);
+        let value: Float = fbm.evaluate(&interaction(p, width));
+        assert!((value - previous).abs() < 0.1, "{} {}", value, previous);
+        previous = value;
+    }
+    // footprints larger than the noise features give the average
+    let si: SurfaceInteraction = interaction(p, 10.0);
+    let value: Float = fbm.evaluate(&si);
+    assert_eq!(value, 0.0);
+    let value: Float = wrinkled.evaluate(&si);
+    let average: Float = 0.2 + 0.2 * (1.0 - (0.5 as Float).powi(8)) / 0.5;
+    assert_close(value, average);
+    // create() reads "octaves" and "roughness" ...
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("octaves"), 3);
+    params.add_float(String::from("roughness"), 0.7);
+    let mut tp: TextureParams = TextureParams::new(
+        params,
+        ParamSet::default(),
+        Arc::new(HashMap::new()),
+        Arc::new(HashMap::new()),
+    );
+    let created: FBmTexture = FBmTexture::create(&Transform::default(), &mut tp);
+    assert_eq!((created.octaves, created.omega), (3, 0.7));
+    let value: Float = created.evaluate(&interaction(p, 0.0));
+    assert_close(value, 0.359_447);
+    let created: WrinkledTexture = WrinkledTexture::create(&Transform::default(), &mut tp);
+    assert_eq!((created.octaves, created.omega), (3, 0.7));
+    // ... and applies the texture space transformation
+    let mut tp: TextureParams = TextureParams::default();
+    let moved: FBmTexture = FBmTexture::create(
+        &Transform::translate(&Vector3f {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        }),
+        &mut tp,
+    );
+    let value: Float = moved.evaluate(&interaction((0.25, -4.75, 0.5), 0.0));
+    assert_close(value, 0.361_962);
+    // negative octave counts give constant textures
+    let value: Float = WrinkledTexture::new(identity(), -1, 0.5).evaluate(&interaction(p, 0.0));
+    assert_close(value, 0.2);
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::Arc;
+
+use image::hdr::HDREncoder;
+use image::{Rgb, RgbImage};
+use pbrt::core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::{ParamSet, TextureParams};
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::texture::{Texture, TextureMapping2D, UVMapping2D};
+use pbrt::textures::imagemap::{convert_to_float, convert_to_spectrum, ImageTexture};
+
+fn temp_file(name: &str) -> String {
+    std::env::temp_dir()
+        .join(name)
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+fn uv_mapping() -> Box<TextureMapping2D> {
+    Box::new(TextureMapping2D::UV(UVMapping2D {
+        su: 1.0,
+        sv: 1.0,
+        du: 0.0,
+        dv: 0.0,
+    }))
+}
+
+fn texture_params(filename: &str, wrap: &str) -> TextureParams {
+    let mut params: ParamSet = ParamSet::default();
+    params.add_string(String::from("filename"), String::from(filename));
+    params.add_string(String::from("wrap"), String::from(wrap));
+    TextureParams::new(
+        params,
+        ParamSet::default(),
+        Arc::new(HashMap::new()),
+        Arc::new(HashMap::new()),
+    )
+}
+
+fn float_texture(tp: &mut TextureParams) -> ImageTexture<Float> {
+    ImageTexture::create(tp, uv_mapping(), None, convert_to_float)
+}
+
+fn spectrum_texture(tp: &mut TextureParams) -> ImageTexture<Spectrum> {
+    ImageTexture::create(tp, uv_mapping(), None, convert_to_spectrum)
+}
+
+/// Surface point with texture coordinates *(u, v)*, moving *dudx*
+/// in *u* and *dvdy* in *v* per pixel.
+fn interaction<'a>(u: Float, v: Float, dudx: Float, dvdy: Float) -> SurfaceInteraction<'a> {
+    let si: SurfaceInteraction = SurfaceInteraction::new(
+        &Point3f::default(),
+        &Vector3f::default(),
+        Point2f { x: u, y: v },
+        &Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        &Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        &Vector3f {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+        &Normal3f::default(),
+        &Normal3f::default(),
+        0.0,
+        None,
+    );
+    si.dudx.set(dudx);
+    si.dvdy.set(dvdy);
+    si
+}
+
+fn lookup(texture: &ImageTexture<Float>, u: Float, v: Float) -> Float {
+    texture.evaluate(&interaction(u, v, 0.0, 0.0))
+}
+
+fn main() {
+    // an 8x8 image with a different value for each texel ...
+    let ramp: String = temp_file("textures_image_texture_ramp.png");
+    RgbImage::from_fn(8, 8, |x, y| {
+        let v: u8 = (x * 32 + y * 4) as u8;
+        Rgb([v, v, v])
+    })
+    .save(&ramp)
+    .unwrap();
+    // ... repeats for negative texture coordinates, too
+    let repeat: ImageTexture<Float> = float_texture(&mut texture_params(&ramp, "repeat"));
+    for &(u, v) in &[(0.3, 0.6), (0.05, 0.95), (0.77, 0.21)] {
+        let value: Float = lookup(&repeat, u, v);
+        for &(du, dv) in &[(-1.0, 0.0), (0.0, -1.0), (-2.0, -3.0), (1.0, 1.0)] {
+            let wrapped: Float = lookup(&repeat, u + du, v + dv);
+            assert!((wrapped - value).abs() < 1e-4, "{} {}", wrapped, value);
+        }
+    }
+    // ... is black outside of [0, 1]^2
+    let black: ImageTexture<Float> = float_texture(&mut texture_params(&ramp, "black"));
+    assert_eq!(lookup(&black, -0.5, 0.5), 0.0);
+    assert_eq!(lookup(&black, 0.5, 1.5), 0.0);
+    assert!(lookup(&black, 0.5, 0.5) > 0.0);
+    // ... or repeats the texels at the border
+    let clamp: ImageTexture<Float> = float_texture(&mut texture_params(&ramp, "clamp"));
+    let edge: Float = lookup(&clamp, 7.5 / 8.0, 4.5 / 8.0);
+    assert!((lookup(&clamp, 1.5, 4.5 / 8.0) - edge).abs() < 1e-6);
+    assert!((lookup(&clamp, 1.5, 4.5 / 8.0) - lookup(&repeat, 1.5, 4.5 / 8.0)).abs() > 0.1);
+    let _ = std::fs::remove_file(&ramp);
+    // 8-bit images are gamma decoded, floating point images aren't
+    let png: String = temp_file("textures_image_texture_gray.png");
+    RgbImage::from_pixel(2, 2, Rgb([128, 128, 128]))
+        .save(&png)
+        .unwrap();
+    let hdr: String = temp_file("textures_image_texture_gray.hdr");
+    HDREncoder::new(File::create(&hdr).unwrap())
+        .encode(&[Rgb([0.5_f32, 0.5, 0.5]); 4], 2, 2)
+        .unwrap();
+    let si: SurfaceInteraction = interaction(0.5, 0.5, 0.0, 0.0);
+    let decoded: Spectrum = spectrum_texture(&mut texture_params(&png, "repeat")).evaluate(&si);
+    assert!((decoded.c[0] - 0.2158).abs() < 1e-3, "{:?}", decoded.c);
+    let linear: Spectrum = spectrum_texture(&mut texture_params(&hdr, "repeat")).evaluate(&si);
+    assert!((linear.c[0] - 0.5).abs() < 1e-2, "{:?}", linear.c);
+    for filename in &[&png, &hdr] {
+        let mut tp: TextureParams = texture_params(filename, "repeat");
+        tp.geom_params.add_bool(String::from("gamma"), true);
+        tp.geom_params.add_float(String::from("scale"), 2.0);
+        let scaled: Spectrum = spectrum_texture(&mut tp).evaluate(&si);
+        let expected: Float = if filename.ends_with(".png") {
+            2.0 * 0.2158
+        } else {
+            1.0
+        };
+        assert!((scaled.c[0] - expected).abs() < 2e-2, "{:?}", scaled.c);
+    }
+    let _ = std::fs::remove_file(&png);
+    let _ = std::fs::remove_file(&hdr);
+    // horizontal stripes, two texels high
+    let stripes: String = temp_file("textures_image_texture_stripes.png");
+    RgbImage::from_fn(8, 8, |_x, y| {
+        let v: u8 = if (y / 2) % 2 == 0 { 255 } else { 0 };
+        Rgb([v, v, v])
+    })
+    .save(&stripes)
+    .unwrap();
+    // EWA filtering blurs along the (long) u axis of the footprint
+    // only, trilinear filtering blurs in both directions
+    let ewa: ImageTexture<Float> = float_texture(&mut texture_params(&stripes, "repeat"));
+    let mut tp: TextureParams = texture_params(&stripes, "repeat");
+    tp.geom_params.add_bool(String::from("trilinear"), true);
+    let trilinear: ImageTexture<Float> = float_texture(&mut tp);
+    for &v in &[1.0 / 8.0, 3.0 / 8.0, 5.0 / 8.0] {
+        let sharp: Float = lookup(&ewa, 0.3, v);
+        assert!(sharp < 1e-3 || sharp > 1.0 - 1e-3, "{}", sharp);
+        let footprint: SurfaceInteraction = interaction(0.3, v, 0.5, 0.01);
+        let filtered: Float = ewa.evaluate(&footprint);
+        assert!((filtered - sharp).abs() < 1e-3, "{} {}", filtered, sharp);
+        let blurred: Float = trilinear.evaluate(&footprint);
+        assert!((blurred - 0.5).abs() < 0.1, "{}", blurred);
+    }
+    // tiny derivatives select the most detailed level
+    let tiny: Float = ewa.evaluate(&interaction(0.3, 3.0 / 8.0, 1e-6, 1e-7));
+    assert!((tiny - lookup(&ewa, 0.3, 3.0 / 8.0)).abs() < 1e-3);
+    let _ = std::fs::remove_file(&stripes);
+    // missing images become a constant texture
+    let missing: ImageTexture<Float> =
+        float_texture(&mut texture_params("/nonexistent/texture.png", "repeat"));
+    assert!((lookup(&missing, 0.3, 0.4) - 1.0).abs() < 1e-6);
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::{ParamSet, TextureParams};
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::texture::{IdentityMapping3D, Texture, TextureMapping3D};
+use pbrt::core::transform::Transform;
+use pbrt::textures::marble::{MarbleTexture, MARBLE_COLORS};
+
+fn marble(octaves: i32, scale: Float, variation: Float) -> MarbleTexture {
+    MarbleTexture::new(
+        Box::new(TextureMapping3D::Identity(IdentityMapping3D::new(
+            Transform::default(),
+        ))),
+        octaves,
+        0.5,
+        scale,
+        variation,
+    )
+}
+
+/// Surface point at *(x, y, z)*, moving *width* along x and y per
+/// pixel.
+fn interaction<'a>(x: Float, y: Float, z: Float, width: Float) -> SurfaceInteraction<'a> {
+    let si: SurfaceInteraction = SurfaceInteraction::new(
+        &Point3f { x, y, z },
+        &Vector3f::default(),
+        Point2f::default(),
+        &Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        &Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        &Vector3f {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+        &Normal3f::default(),
+        &Normal3f::default(),
+        0.0,
+        None,
+    );
+    si.dpdx.set(Vector3f {
+        x: width,
+        y: 0.0,
+        z: 0.0,
+    });
+    si.dpdy.set(Vector3f {
+        x: 0.0,
+        y: width,
+        z: 0.0,
+    });
+    si
+}
+
+fn assert_close(a: Spectrum, b: Spectrum, epsilon: Float) {
+    for c in 0..3 {
+        assert!((a.c[c] - b.c[c]).abs() < epsilon, "{:?} != {:?}", a.c, b.c);
+    }
+}
+
+fn main() {
+    // the spline passes through its (scaled) control points ...
+    for (i, rgb) in MARBLE_COLORS.iter().enumerate() {
+        let t: Float = i as Float / 8.0;
+        assert_close(MarbleTexture::color(t), Spectrum::from_rgb(rgb) * 1.5, 1e-5);
+    }
+    // ... and has no kinks there (the slope is continuous), so wide
+    // gradients don't show bands
+    let h: Float = 1e-4;
+    for i in 1..8 {
+        let t: Float = i as Float / 8.0;
+        let c: Spectrum = MarbleTexture::color(t);
+        let left: Spectrum = (c - MarbleTexture::color(t - h)) / h;
+        let right: Spectrum = (MarbleTexture::color(t + h) - c) / h;
+        assert_close(left, right, 0.05);
+    }
+    let mut previous: Spectrum = MarbleTexture::color(0.0);
+    for i in 1..=1000 {
+        let c: Spectrum = MarbleTexture::color(i as Float / 1000.0);
+        assert_close(c, previous, 0.02);
+        assert!(c.c.iter().all(|v| *v > 0.0 && *v < 1.0), "{:?}", c.c);
+        previous = c;
+    }
+    // without variation the veins follow a sine wave along x ...
+    let straight: MarbleTexture = marble(8, 2.0, 0.0);
+    for &x in &[0.0, 0.3, -1.7, 2.9] {
+        let expected: Spectrum = MarbleTexture::color(0.5 + 0.5 * (2.0 * x as Float).sin());
+        for &(y, z) in &[(0.0, 0.0), (1.3, -0.4), (-5.0, 7.5)] {
+            assert_close(
+                straight.evaluate(&interaction(x, y, z, 0.0)),
+                expected,
+                1e-5,
+            );
+        }
+    }
+    // ... which turbulence perturbs
+    let veined: MarbleTexture = marble(8, 2.0, 0.5);
+    let a: Spectrum = veined.evaluate(&interaction(0.3, 0.1, 0.2, 0.0));
+    let b: Spectrum = veined.evaluate(&interaction(0.3, 2.6, -1.2, 0.0));
+    assert!((a.c[0] - b.c[0]).abs() > 1e-4, "{:?} {:?}", a.c, b.c);
+    // large footprints filter out all octaves, leaving a constant
+    // offset of the sine wave
+    let a: Spectrum = veined.evaluate(&interaction(0.3, 0.1, 0.2, 10.0));
+    let b: Spectrum = veined.evaluate(&interaction(0.3, 2.6, -1.2, 10.0));
+    assert_close(a, b, 1e-6);
+    // create() reads "octaves", "roughness", "scale" and "variation"
+    let mut params: ParamSet = ParamSet::default();
+    params.add_int(String::from("octaves"), 4);
+    params.add_float(String::from("roughness"), 0.6);
+    params.add_float(String::from("scale"), 3.0);
+    let mut tp: TextureParams = TextureParams::new(
+        params,
+        ParamSet::default(),
+        Arc::new(HashMap::new()),
+        Arc::new(HashMap::new()),
+    );
+    let created: MarbleTexture = MarbleTexture::create(&Transform::default(), &mut tp);
+    assert_eq!(created.octaves, 4);
+    assert_eq!(created.omega, 0.6);
+    assert_eq!(created.scale, 3.0);
+    assert_eq!(created.variation, 0.2);
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pbrt::core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
+use pbrt::core::interaction::SurfaceInteraction;
+use pbrt::core::paramset::{ParamSet, TextureParams};
+use pbrt::core::pbrt::{Float, Spectrum};
+use pbrt::core::texture::{Texture, TextureMapping2D, UVMapping2D};
+use pbrt::textures::bilerp::BilerpTexture;
+use pbrt::textures::constant::ConstantTexture;
+use pbrt::textures::mix::MixTexture;
+use pbrt::textures::scale::ScaleTexture;
+
+/// Float texture returning *u*.
+struct URamp {}
+
+impl Texture<Float> for URamp {
+    fn evaluate(&self, si: &SurfaceInteraction) -> Float {
+        si.uv.x
+    }
+}
+
+fn uv_mapping() -> Box<TextureMapping2D> {
+    Box::new(TextureMapping2D::UV(UVMapping2D {
+        su: 1.0,
+        sv: 1.0,
+        du: 0.0,
+        dv: 0.0,
+    }))
+}
+
+fn interaction<'a>(u: Float, v: Float) -> SurfaceInteraction<'a> {
+    SurfaceInteraction::new(
+        &Point3f::default(),
+        &Vector3f::default(),
+        Point2f { x: u, y: v },
+        &Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        &Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        &Vector3f {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+        &Normal3f::default(),
+        &Normal3f::default(),
+        0.0,
+        None,
+    )
+}
+
+fn assert_close(a: Float, b: Float) {
+    assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+}
+
+fn main() {
+    let si: SurfaceInteraction = interaction(0.25, 0.75);
+    // scale multiplies both textures, for floats and spectra
+    let scale: ScaleTexture<Float> = ScaleTexture::new(
+        Arc::new(ConstantTexture::new(2.0 as Float)),
+        Arc::new(URamp {}),
+    );
+    assert_close(scale.evaluate(&si), 0.5);
+    let tint: ScaleTexture<Spectrum> = ScaleTexture::new(
+        Arc::new(ConstantTexture::new(Spectrum::rgb(1.0, 0.5, 0.25))),
+        Arc::new(ConstantTexture::new(Spectrum::new(2.0))),
+    );
+    assert!(tint.evaluate(&si) == Spectrum::rgb(2.0, 1.0, 0.5));
+    // mix blends from the first to the second texture
+    let mix: MixTexture<Spectrum> = MixTexture::new(
+        Arc::new(ConstantTexture::new(Spectrum::rgb(1.0, 0.0, 0.0))),
+        Arc::new(ConstantTexture::new(Spectrum::rgb(0.0, 0.0, 1.0))),
+        Arc::new(URamp {}),
+    );
+    for &u in &[0.0, 0.25, 1.0] {
+        let c: Spectrum = mix.evaluate(&interaction(u, 0.5));
+        assert_close(c.c[0], 1.0 - u);
+        assert_close(c.c[1], 0.0);
+        assert_close(c.c[2], u);
+    }
+    // bilerp reproduces its corner values and blends in between
+    let bilerp: BilerpTexture<Float> = BilerpTexture::new(uv_mapping(), 1.0, 2.0, 3.0, 4.0);
+    for &(u, v, expected) in &[
+        (0.0, 0.0, 1.0),
+        (0.0, 1.0, 2.0),
+        (1.0, 0.0, 3.0),
+        (1.0, 1.0, 4.0),
+        (0.5, 0.5, 2.5),
+        (0.25, 0.75, 1.0 + 0.75 + 2.0 * 0.25),
+    ] {
+        assert_close(bilerp.evaluate(&interaction(u, v)), expected);
+    }
+    let gradient: BilerpTexture<Spectrum> = BilerpTexture::new(
+        uv_mapping(),
+        Spectrum::rgb(1.0, 0.0, 0.0),
+        Spectrum::rgb(0.0, 1.0, 0.0),
+        Spectrum::rgb(0.0, 0.0, 1.0),
+        Spectrum::new(1.0),
+    );
+    let c: Spectrum = gradient.evaluate(&interaction(0.5, 0.5));
+    for channel in 0..3 {
+        assert_close(c.c[channel], 0.5);
+    }
+    // textures compose: mixing a scaled and a bilinear texture
+    let composed: MixTexture<Float> = MixTexture::new(
+        Arc::new(scale),
+        Arc::new(bilerp),
+        Arc::new(ConstantTexture::new(0.5 as Float)),
+    );
+    assert_close(composed.evaluate(&si), 0.5 * 0.5 + 0.5 * 2.25);
+    // create() reads constants ...
+    let mut params: ParamSet = ParamSet::default();
+    params.add_float(String::from("tex1"), 3.0);
+    params.add_float(String::from("v11"), 5.0);
+    params.add_rgb_spectrum(String::from("v00"), Spectrum::rgb(0.1, 0.2, 0.3));
+    let mut tp: TextureParams = TextureParams::new(
+        params,
+        ParamSet::default(),
+        Arc::new(HashMap::new()),
+        Arc::new(HashMap::new()),
+    );
+    assert_close(ScaleTexture::<Float>::create(&mut tp).evaluate(&si), 3.0);
+    assert!(ScaleTexture::<Spectrum>::create(&mut tp).evaluate(&si) == Spectrum::new(1.0));
+    assert_close(MixTexture::<Float>::create(&mut tp).evaluate(&si), 2.0);
+    let corner: SurfaceInteraction = interaction(1.0, 1.0);
+    assert_close(
+        BilerpTexture::<Float>::create(&mut tp, uv_mapping()).evaluate(&corner),
+        5.0,
+    );
+    let origin: SurfaceInteraction = interaction(0.0, 0.0);
+    assert!(
+        BilerpTexture::<Spectrum>::create(&mut tp, uv_mapping()).evaluate(&origin)
+            == Spectrum::rgb(0.1, 0.2, 0.3)
+    );
+    // ... and named textures
+    let mut float_textures: HashMap<String, Arc<dyn Texture<Float> + Send + Sync>> = HashMap::new();
+    float_textures.insert(String::from("ramp"), Arc::new(URamp {}));
+    let mut params: ParamSet = ParamSet::default();
+    params.add_texture(String::from("amount"), String::from("ramp"));
+    params.add_rgb_spectrum(String::from("tex2"), Spectrum::new(4.0));
+    let mut tp: TextureParams = TextureParams::new(
+        params,
+        ParamSet::default(),
+        Arc::new(float_textures),
+        Arc::new(HashMap::new()),
+    );
+    let mix: MixTexture<Spectrum> = MixTexture::<Spectrum>::create(&mut tp);
+    assert_close(mix.evaluate(&si).c[0], 0.25 * 4.0);
+}
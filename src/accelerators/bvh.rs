@@ -10,11 +10,13 @@ use crate::core::interaction::SurfaceInteraction;
 use crate::core::light::Light;
 use crate::core::material::Material;
 use crate::core::paramset::ParamSet;
-use crate::core::pbrt::Float;
+use crate::core::pbrt::{find_interval, Float};
 use crate::core::primitive::Primitive;
 
 // see bvh.h
 
+/// How **BVHAccel** partitions primitives, selected by the
+/// *splitmethod* parameter ("sah", "middle", "equal" or "hlbvh").
 #[derive(Debug, Clone)]
 pub enum SplitMethod {
     SAH,
@@ -100,6 +102,32 @@ impl Default for BucketInfo {
     }
 }
 
+#[derive(Debug, Default, Copy, Clone)]
+struct MortonPrimitive {
+    primitive_index: usize,
+    morton_code: u32,
+}
+
+/// Leaves store their primitive count in 16 bits.
+const MAX_LEAF_PRIMITIVES: usize = u16::MAX as usize;
+
+fn left_shift_3(x: u32) -> u32 {
+    let mut x: u32 = x;
+    if x == (1 << 10) {
+        x -= 1;
+    }
+    x = (x | (x << 16)) & 0b0000_0011_0000_0000_0000_0000_1111_1111;
+    x = (x | (x << 8)) & 0b0000_0011_0000_0000_1111_0000_0000_1111;
+    x = (x | (x << 4)) & 0b0000_0011_0000_1100_0011_0000_1100_0011;
+    x = (x | (x << 2)) & 0b0000_1001_0010_0100_1001_0010_0100_1001;
+    x
+}
+
+/// Interleaves the bits of the (10 bit) integer coordinates of *v*.
+fn encode_morton_3(v: &Vector3f) -> u32 {
+    (left_shift_3(v.z as u32) << 2) | (left_shift_3(v.y as u32) << 1) | left_shift_3(v.x as u32)
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct LinearBVHNode {
     bounds: Bounds3f,
@@ -141,21 +169,29 @@ impl BVHAccel {
             let world_bound = bvh.primitives[i].world_bound();
             *item = BVHPrimitiveInfo::new(i, world_bound);
         }
-        // TODO: if (splitMethod == SplitMethod::HLBVH)
         let arena: Arena<BVHBuildNode> = Arena::with_capacity(1024 * 1024);
         let mut total_nodes: usize = 0;
         let mut ordered_prims: Vec<Arc<Primitive>> = Vec::with_capacity(num_prims);
         // println!("BVHAccel::recursive_build(..., {}, ...)", num_prims);
         // let start = PreciseTime::now();
-        let root = BVHAccel::recursive_build(
-            bvh, // instead of self
-            &arena,
-            &mut primitive_info,
-            0,
-            num_prims,
-            &mut total_nodes,
-            &mut ordered_prims,
-        );
+        let root = match split_method {
+            SplitMethod::HLBVH => BVHAccel::hlbvh_build(
+                bvh,
+                &arena,
+                &primitive_info,
+                &mut total_nodes,
+                &mut ordered_prims,
+            ),
+            _ => BVHAccel::recursive_build(
+                bvh, // instead of self
+                &arena,
+                &mut primitive_info,
+                0,
+                num_prims,
+                &mut total_nodes,
+                &mut ordered_prims,
+            ),
+        };
         // let end = PreciseTime::now();
         // println!("{} seconds for building BVH ...", start.to(end));
         // flatten first
@@ -202,6 +238,40 @@ impl BVHAccel {
             split_method,
         )))
     }
+    /// Appends the primitives of *primitive_info[start..end]* to
+    /// *ordered_prims* and turns *node* into a leaf referencing them.
+    fn init_leaf_node(
+        bvh: &BVHAccel,
+        node: &mut BVHBuildNode,
+        primitive_info: &[BVHPrimitiveInfo],
+        start: usize,
+        end: usize,
+        bounds: &Bounds3f,
+        ordered_prims: &mut Vec<Arc<Primitive>>,
+    ) {
+        let first_prim_offset: usize = ordered_prims.len();
+        for item in primitive_info.iter().take(end).skip(start) {
+            let prim_num: usize = item.primitive_number;
+            ordered_prims.push(bvh.primitives[prim_num].clone());
+        }
+        node.init_leaf(first_prim_offset, end - start, bounds);
+    }
+    /// Partitions *primitive_info[start..end]* into two equally
+    /// sized subsets along *dim* and returns the split position.
+    fn split_equal_counts(
+        primitive_info: &mut [BVHPrimitiveInfo],
+        start: usize,
+        end: usize,
+        dim: XYZEnum,
+    ) -> usize {
+        let mid: usize = (start + end) / 2;
+        primitive_info[start..end].select_nth_unstable_by(mid - start, |a, b| {
+            a.centroid[dim]
+                .partial_cmp(&b.centroid[dim])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        mid
+    }
     pub fn recursive_build<'a>(
         bvh: Arc<BVHAccel>,
         arena: &'a Arena<BVHBuildNode<'a>>,
@@ -222,164 +292,441 @@ impl BVHAccel {
         let n_primitives: usize = end - start;
         if n_primitives == 1 {
             // create leaf _BVHBuildNode_
-            let first_prim_offset: usize = ordered_prims.len();
-            for item in primitive_info.iter().take(end).skip(start) {
-                let prim_num: usize = item.primitive_number;
-                ordered_prims.push(bvh.primitives[prim_num].clone());
-            }
-            node.init_leaf(first_prim_offset, n_primitives, &bounds);
+            BVHAccel::init_leaf_node(
+                &bvh,
+                node,
+                primitive_info,
+                start,
+                end,
+                &bounds,
+                ordered_prims,
+            );
             return node;
-        } else {
-            // compute bound of primitive centroids, choose split dimension _dim_
-            let mut centroid_bounds: Bounds3f = Bounds3f::default();
-            for item in primitive_info.iter().take(end).skip(start) {
-                centroid_bounds = bnd3_union_pnt3(&centroid_bounds, &item.centroid);
-            }
-            let dim: u8 = centroid_bounds.maximum_extent();
-            let dim_i: XYZEnum = match dim {
-                0 => XYZEnum::X,
-                1 => XYZEnum::Y,
-                _ => XYZEnum::Z,
-            };
-            // partition primitives into two sets and build children
-            let mut mid: usize = (start + end) / 2_usize;
-            if centroid_bounds.p_max[dim_i] == centroid_bounds.p_min[dim_i] {
+        }
+        // compute bound of primitive centroids, choose split dimension _dim_
+        let mut centroid_bounds: Bounds3f = Bounds3f::default();
+        for item in primitive_info.iter().take(end).skip(start) {
+            centroid_bounds = bnd3_union_pnt3(&centroid_bounds, &item.centroid);
+        }
+        let dim: u8 = centroid_bounds.maximum_extent();
+        let dim_i: XYZEnum = match dim {
+            0 => XYZEnum::X,
+            1 => XYZEnum::Y,
+            _ => XYZEnum::Z,
+        };
+        // partition primitives into two sets and build children
+        let mut mid: usize;
+        if centroid_bounds.p_max[dim_i] == centroid_bounds.p_min[dim_i] {
+            // all centroids coincide (e.g. degenerate primitives
+            // with zero-area bounds), no split can separate them
+            if n_primitives <= MAX_LEAF_PRIMITIVES {
                 // create leaf _BVHBuildNode_
-                let first_prim_offset: usize = ordered_prims.len();
-                for item in primitive_info.iter().take(end).skip(start) {
-                    let prim_num: usize = item.primitive_number;
-                    ordered_prims.push(bvh.primitives[prim_num].clone());
-                }
-                node.init_leaf(first_prim_offset, n_primitives, &bounds);
+                BVHAccel::init_leaf_node(
+                    &bvh,
+                    node,
+                    primitive_info,
+                    start,
+                    end,
+                    &bounds,
+                    ordered_prims,
+                );
                 return node;
-            } else {
-                // partition primitives based on _splitMethod_
-                match bvh.split_method {
-                    SplitMethod::Middle => {
-                        // TODO
-                    }
-                    SplitMethod::EqualCounts => {
-                        // TODO
+            }
+            // too many for a single leaf, split into halves
+            mid = (start + end) / 2;
+        } else {
+            // partition primitives based on _splitMethod_
+            match bvh.split_method {
+                SplitMethod::Middle => {
+                    // partition primitives through node's midpoint
+                    let p_mid: Float =
+                        (centroid_bounds.p_min[dim_i] + centroid_bounds.p_max[dim_i]) / 2.0;
+                    let (mut left, mut right): (Vec<BVHPrimitiveInfo>, Vec<BVHPrimitiveInfo>) =
+                        primitive_info[start..end]
+                            .iter()
+                            .partition(|&pi| pi.centroid[dim_i] < p_mid);
+                    mid = start + left.len();
+                    if mid == start || mid == end {
+                        // all centroids on one side of the midpoint
+                        // (floating point precision), use equal counts
+                        mid = BVHAccel::split_equal_counts(primitive_info, start, end, dim_i);
+                    } else {
+                        left.append(&mut right);
+                        primitive_info.splice(start..end, left);
                     }
-                    SplitMethod::SAH | SplitMethod::HLBVH => {
-                        if n_primitives <= 2 {
-                            mid = (start + end) / 2;
-                            if start != end - 1
-                                && primitive_info[end - 1].centroid[dim_i]
-                                    < primitive_info[start].centroid[dim_i]
-                            {
-                                primitive_info.swap(start, end - 1);
+                }
+                SplitMethod::EqualCounts => {
+                    // partition primitives into equally sized subsets
+                    mid = BVHAccel::split_equal_counts(primitive_info, start, end, dim_i);
+                }
+                SplitMethod::SAH | SplitMethod::HLBVH => {
+                    if n_primitives <= 2 {
+                        mid = (start + end) / 2;
+                        if start != end - 1
+                            && primitive_info[end - 1].centroid[dim_i]
+                                < primitive_info[start].centroid[dim_i]
+                        {
+                            primitive_info.swap(start, end - 1);
+                        }
+                    } else if bounds.surface_area() <= 0.0 as Float {
+                        // zero-area bounds (e.g. degenerate primitives
+                        // along a line) make the SAH costs meaningless
+                        if n_primitives <= bvh.max_prims_in_node {
+                            // create leaf _BVHBuildNode_
+                            BVHAccel::init_leaf_node(
+                                &bvh,
+                                node,
+                                primitive_info,
+                                start,
+                                end,
+                                &bounds,
+                                ordered_prims,
+                            );
+                            return node;
+                        }
+                        mid = BVHAccel::split_equal_counts(primitive_info, start, end, dim_i);
+                    } else {
+                        // allocate _BucketInfo_ for SAH partition buckets
+                        let n_buckets: usize = 12;
+                        let mut buckets: [BucketInfo; 12] = [BucketInfo::default(); 12];
+                        // initialize _BucketInfo_ for SAH partition buckets
+                        for item in primitive_info.iter().take(end).skip(start) {
+                            let mut b: usize = (n_buckets as Float
+                                * centroid_bounds.offset(&item.centroid)[dim_i])
+                                as usize;
+                            if b == n_buckets {
+                                b = n_buckets - 1;
                             }
-                        } else {
-                            // allocate _BucketInfo_ for SAH partition buckets
-                            let n_buckets: usize = 12;
-                            let mut buckets: [BucketInfo; 12] = [BucketInfo::default(); 12];
-                            // initialize _BucketInfo_ for SAH partition buckets
-                            for item in primitive_info.iter().take(end).skip(start) {
+                            // assert!(b >= 0_usize, "b >= 0");
+                            assert!(b < n_buckets, "b < {}", n_buckets);
+                            buckets[b].count += 1;
+                            buckets[b].bounds = bnd3_union_bnd3(&buckets[b].bounds, &item.bounds);
+                        }
+                        // compute costs for splitting after each bucket
+                        let mut cost: [Float; 11] = [0.0; 11];
+                        for (i, cost_item) in cost.iter_mut().enumerate().take(n_buckets - 1) {
+                            let mut b0: Bounds3f = Bounds3f::default();
+                            let mut b1: Bounds3f = Bounds3f::default();
+                            let mut count0: usize = 0;
+                            let mut count1: usize = 0;
+                            for item in buckets.iter().take(i + 1) {
+                                b0 = bnd3_union_bnd3(&b0, &item.bounds);
+                                count0 += item.count;
+                            }
+                            for item in buckets.iter().take(n_buckets).skip(i + 1) {
+                                b1 = bnd3_union_bnd3(&b1, &item.bounds);
+                                count1 += item.count;
+                            }
+                            *cost_item = 1.0
+                                + (count0 as Float * b0.surface_area()
+                                    + count1 as Float * b1.surface_area())
+                                    / bounds.surface_area();
+                        }
+                        // find bucket to split at that minimizes SAH metric
+                        let mut min_cost: Float = cost[0];
+                        let mut min_cost_split_bucket: usize = 0;
+                        for (i, item) in cost.iter().enumerate().take(n_buckets - 1) {
+                            if item < &min_cost {
+                                min_cost = *item;
+                                min_cost_split_bucket = i;
+                            }
+                        }
+                        // either create leaf or split primitives
+                        // at selected SAH bucket
+                        let leaf_cost: Float = n_primitives as Float;
+                        if n_primitives > bvh.max_prims_in_node || min_cost < leaf_cost {
+                            let (mut left, mut right): (
+                                Vec<BVHPrimitiveInfo>,
+                                Vec<BVHPrimitiveInfo>,
+                            ) = primitive_info[start..end].iter().partition(|&pi| {
                                 let mut b: usize = (n_buckets as Float
-                                    * centroid_bounds.offset(&item.centroid)[dim_i])
+                                    * centroid_bounds.offset(&pi.centroid)[dim_i])
                                     as usize;
                                 if b == n_buckets {
                                     b = n_buckets - 1;
                                 }
                                 // assert!(b >= 0_usize, "b >= 0");
                                 assert!(b < n_buckets, "b < {}", n_buckets);
-                                buckets[b].count += 1;
-                                buckets[b].bounds =
-                                    bnd3_union_bnd3(&buckets[b].bounds, &item.bounds);
-                            }
-                            // compute costs for splitting after each bucket
-                            let mut cost: [Float; 11] = [0.0; 11];
-                            for (i, cost_item) in cost.iter_mut().enumerate().take(n_buckets - 1) {
-                                let mut b0: Bounds3f = Bounds3f::default();
-                                let mut b1: Bounds3f = Bounds3f::default();
-                                let mut count0: usize = 0;
-                                let mut count1: usize = 0;
-                                for item in buckets.iter().take(i + 1) {
-                                    b0 = bnd3_union_bnd3(&b0, &item.bounds);
-                                    count0 += item.count;
-                                }
-                                for item in buckets.iter().take(n_buckets).skip(i + 1) {
-                                    b1 = bnd3_union_bnd3(&b1, &item.bounds);
-                                    count1 += item.count;
-                                }
-                                *cost_item = 1.0
-                                    + (count0 as Float * b0.surface_area()
-                                        + count1 as Float * b1.surface_area())
-                                        / bounds.surface_area();
-                            }
-                            // find bucket to split at that minimizes SAH metric
-                            let mut min_cost: Float = cost[0];
-                            let mut min_cost_split_bucket: usize = 0;
-                            for (i, item) in cost.iter().enumerate().take(n_buckets - 1) {
-                                if item < &min_cost {
-                                    min_cost = *item;
-                                    min_cost_split_bucket = i;
-                                }
-                            }
-                            // either create leaf or split primitives
-                            // at selected SAH bucket
-                            let leaf_cost: Float = n_primitives as Float;
-                            if n_primitives > bvh.max_prims_in_node || min_cost < leaf_cost {
-                                let (mut left, mut right): (
-                                    Vec<BVHPrimitiveInfo>,
-                                    Vec<BVHPrimitiveInfo>,
-                                ) = primitive_info[start..end].iter().partition(|&pi| {
-                                    let mut b: usize = (n_buckets as Float
-                                        * centroid_bounds.offset(&pi.centroid)[dim_i])
-                                        as usize;
-                                    if b == n_buckets {
-                                        b = n_buckets - 1;
-                                    }
-                                    // assert!(b >= 0_usize, "b >= 0");
-                                    assert!(b < n_buckets, "b < {}", n_buckets);
-                                    b <= min_cost_split_bucket
-                                });
-                                mid = start + left.len();
-                                let combined_len = left.len() + right.len();
-                                if combined_len == primitive_info.len() {
-                                    primitive_info.clear();
-                                    primitive_info.append(&mut left);
-                                    primitive_info.append(&mut right);
-                                } else {
-                                    primitive_info.splice(start..mid, left.iter().cloned());
-                                    primitive_info.splice(mid..end, right.iter().cloned());
-                                }
+                                b <= min_cost_split_bucket
+                            });
+                            mid = start + left.len();
+                            let combined_len = left.len() + right.len();
+                            if combined_len == primitive_info.len() {
+                                primitive_info.clear();
+                                primitive_info.append(&mut left);
+                                primitive_info.append(&mut right);
                             } else {
-                                // create leaf _BVHBuildNode_
-                                let first_prim_offset: usize = ordered_prims.len();
-                                for item in primitive_info.iter().take(end).skip(start) {
-                                    let prim_num: usize = item.primitive_number;
-                                    ordered_prims.push(bvh.primitives[prim_num].clone());
-                                }
-                                node.init_leaf(first_prim_offset, n_primitives, &bounds);
-                                return node;
+                                primitive_info.splice(start..mid, left.iter().cloned());
+                                primitive_info.splice(mid..end, right.iter().cloned());
                             }
+                        } else {
+                            // create leaf _BVHBuildNode_
+                            BVHAccel::init_leaf_node(
+                                &bvh,
+                                node,
+                                primitive_info,
+                                start,
+                                end,
+                                &bounds,
+                                ordered_prims,
+                            );
+                            return node;
                         }
                     }
                 }
-                // make sure we get result for c1 before c0
-                let c1 = BVHAccel::recursive_build(
-                    bvh.clone(),
+            }
+        }
+        // make sure we get result for c1 before c0
+        let c1 = BVHAccel::recursive_build(
+            bvh.clone(),
+            arena,
+            primitive_info,
+            mid,
+            end,
+            total_nodes,
+            ordered_prims,
+        );
+        let c0 = BVHAccel::recursive_build(
+            bvh,
+            arena,
+            primitive_info,
+            start,
+            mid,
+            total_nodes,
+            ordered_prims,
+        );
+        node.init_interior(dim, c0, c1);
+        node
+    }
+    /// Builds the BVH from treelets of primitives whose centroids
+    /// share the same cell of a 3D Morton curve, and combines the
+    /// treelets with the surface area heuristic.
+    pub fn hlbvh_build<'a>(
+        bvh: Arc<BVHAccel>,
+        arena: &'a Arena<BVHBuildNode<'a>>,
+        primitive_info: &[BVHPrimitiveInfo],
+        total_nodes: &mut usize,
+        ordered_prims: &mut Vec<Arc<Primitive>>,
+    ) -> &'a BVHBuildNode<'a> {
+        // compute bounding box of all primitive centroids
+        let mut bounds: Bounds3f = Bounds3f::default();
+        for item in primitive_info.iter() {
+            bounds = bnd3_union_pnt3(&bounds, &item.centroid);
+        }
+        // compute Morton indices of primitives
+        let morton_bits: u32 = 10;
+        let morton_scale: Float = (1 << morton_bits) as Float;
+        let mut morton_prims: Vec<MortonPrimitive> = primitive_info
+            .iter()
+            .enumerate()
+            .map(|(i, item)| MortonPrimitive {
+                primitive_index: i,
+                morton_code: encode_morton_3(&(bounds.offset(&item.centroid) * morton_scale)),
+            })
+            .collect();
+        // sort primitive Morton indices
+        morton_prims.sort_by_key(|mp| mp.morton_code);
+        // create LBVH treelets at bottom of BVH (each treelet
+        // covers one of the 2^12 cells of the top 12 bits)
+        let mask: u32 = 0b0011_1111_1111_1100_0000_0000_0000_0000;
+        let first_bit_index: i32 = 29 - 12;
+        let mut treelet_roots: Vec<&'a BVHBuildNode<'a>> = Vec::new();
+        let mut start: usize = 0;
+        for end in 1..=morton_prims.len() {
+            if end == morton_prims.len()
+                || (morton_prims[start].morton_code & mask)
+                    != (morton_prims[end].morton_code & mask)
+            {
+                treelet_roots.push(BVHAccel::emit_lbvh(
+                    &bvh,
                     arena,
                     primitive_info,
-                    mid,
-                    end,
+                    &morton_prims[start..end],
                     total_nodes,
                     ordered_prims,
-                );
-                let c0 = BVHAccel::recursive_build(
+                    first_bit_index,
+                ));
+                start = end;
+            }
+        }
+        // create and return SAH BVH from LBVH treelets
+        let n_treelets: usize = treelet_roots.len();
+        BVHAccel::build_upper_sah(arena, &mut treelet_roots, 0, n_treelets, total_nodes)
+    }
+    fn emit_lbvh<'a>(
+        bvh: &BVHAccel,
+        arena: &'a Arena<BVHBuildNode<'a>>,
+        primitive_info: &[BVHPrimitiveInfo],
+        morton_prims: &[MortonPrimitive],
+        total_nodes: &mut usize,
+        ordered_prims: &mut Vec<Arc<Primitive>>,
+        bit_index: i32,
+    ) -> &'a BVHBuildNode<'a> {
+        let n_primitives: usize = morton_prims.len();
+        assert!(n_primitives > 0);
+        if (bit_index == -1 && n_primitives <= MAX_LEAF_PRIMITIVES)
+            || n_primitives < bvh.max_prims_in_node
+        {
+            // create and return leaf node of LBVH treelet
+            let node: &mut BVHBuildNode<'a> = arena.alloc(BVHBuildNode::default());
+            *total_nodes += 1_usize;
+            let mut bounds: Bounds3f = Bounds3f::default();
+            let first_prim_offset: usize = ordered_prims.len();
+            for mp in morton_prims {
+                let item: &BVHPrimitiveInfo = &primitive_info[mp.primitive_index];
+                ordered_prims.push(bvh.primitives[item.primitive_number].clone());
+                bounds = bnd3_union_bnd3(&bounds, &item.bounds);
+            }
+            node.init_leaf(first_prim_offset, n_primitives, &bounds);
+            return node;
+        }
+        let (split_offset, axis): (usize, u8) = if bit_index == -1 {
+            // identical Morton codes, but too many for a single leaf
+            (n_primitives / 2, 0_u8)
+        } else {
+            let mask: u32 = 1 << bit_index;
+            // advance to next subtree level if there's no LBVH
+            // split for this bit
+            if (morton_prims[0].morton_code & mask)
+                == (morton_prims[n_primitives - 1].morton_code & mask)
+            {
+                return BVHAccel::emit_lbvh(
                     bvh,
                     arena,
                     primitive_info,
-                    start,
-                    mid,
+                    morton_prims,
                     total_nodes,
                     ordered_prims,
+                    bit_index - 1,
                 );
-                node.init_interior(dim, c0, c1);
             }
+            // find LBVH split point for this dimension
+            let split_offset: i32 = find_interval(n_primitives as i32, |index| {
+                (morton_prims[0].morton_code & mask)
+                    == (morton_prims[index as usize].morton_code & mask)
+            }) + 1;
+            (split_offset as usize, (bit_index % 3) as u8)
+        };
+        assert!(split_offset > 0 && split_offset < n_primitives);
+        // create and return interior LBVH node
+        let node: &mut BVHBuildNode<'a> = arena.alloc(BVHBuildNode::default());
+        *total_nodes += 1_usize;
+        let c0 = BVHAccel::emit_lbvh(
+            bvh,
+            arena,
+            primitive_info,
+            &morton_prims[..split_offset],
+            total_nodes,
+            ordered_prims,
+            bit_index.max(0) - 1,
+        );
+        let c1 = BVHAccel::emit_lbvh(
+            bvh,
+            arena,
+            primitive_info,
+            &morton_prims[split_offset..],
+            total_nodes,
+            ordered_prims,
+            bit_index.max(0) - 1,
+        );
+        node.init_interior(axis, c0, c1);
+        node
+    }
+    fn build_upper_sah<'a>(
+        arena: &'a Arena<BVHBuildNode<'a>>,
+        treelet_roots: &mut Vec<&'a BVHBuildNode<'a>>,
+        start: usize,
+        end: usize,
+        total_nodes: &mut usize,
+    ) -> &'a BVHBuildNode<'a> {
+        assert!(start < end);
+        let n_nodes: usize = end - start;
+        if n_nodes == 1 {
+            return treelet_roots[start];
         }
+        let node: &mut BVHBuildNode<'a> = arena.alloc(BVHBuildNode::default());
+        *total_nodes += 1_usize;
+        // compute bounds of all nodes under this HLBVH node
+        let mut bounds: Bounds3f = Bounds3f::default();
+        for root in treelet_roots.iter().take(end).skip(start) {
+            bounds = bnd3_union_bnd3(&bounds, &root.bounds);
+        }
+        // compute bound of HLBVH node centroids, choose split dimension _dim_
+        let centroid = |b: &Bounds3f| -> Point3f { b.p_min * 0.5 + b.p_max * 0.5 };
+        let mut centroid_bounds: Bounds3f = Bounds3f::default();
+        for root in treelet_roots.iter().take(end).skip(start) {
+            centroid_bounds = bnd3_union_pnt3(&centroid_bounds, &centroid(&root.bounds));
+        }
+        let dim: u8 = centroid_bounds.maximum_extent();
+        let dim_i: XYZEnum = match dim {
+            0 => XYZEnum::X,
+            1 => XYZEnum::Y,
+            _ => XYZEnum::Z,
+        };
+        let mid: usize = if centroid_bounds.p_max[dim_i] == centroid_bounds.p_min[dim_i]
+            || bounds.surface_area() <= 0.0 as Float
+        {
+            // no meaningful SAH split, split into halves
+            (start + end) / 2
+        } else {
+            // allocate _BucketInfo_ for SAH partition buckets
+            let n_buckets: usize = 12;
+            let bucket = |b: &Bounds3f| -> usize {
+                let b: usize = (n_buckets as Float
+                    * ((centroid(b)[dim_i] - centroid_bounds.p_min[dim_i])
+                        / (centroid_bounds.p_max[dim_i] - centroid_bounds.p_min[dim_i])))
+                    as usize;
+                b.min(n_buckets - 1)
+            };
+            let mut buckets: [BucketInfo; 12] = [BucketInfo::default(); 12];
+            // initialize _BucketInfo_ for HLBVH SAH partition buckets
+            for root in treelet_roots.iter().take(end).skip(start) {
+                let b: usize = bucket(&root.bounds);
+                buckets[b].count += 1;
+                buckets[b].bounds = bnd3_union_bnd3(&buckets[b].bounds, &root.bounds);
+            }
+            // compute costs for splitting after each bucket
+            let mut cost: [Float; 11] = [0.0; 11];
+            for (i, cost_item) in cost.iter_mut().enumerate() {
+                let mut b0: Bounds3f = Bounds3f::default();
+                let mut b1: Bounds3f = Bounds3f::default();
+                let mut count0: usize = 0;
+                let mut count1: usize = 0;
+                for item in buckets.iter().take(i + 1) {
+                    b0 = bnd3_union_bnd3(&b0, &item.bounds);
+                    count0 += item.count;
+                }
+                for item in buckets.iter().skip(i + 1) {
+                    b1 = bnd3_union_bnd3(&b1, &item.bounds);
+                    count1 += item.count;
+                }
+                *cost_item = 0.125
+                    + (count0 as Float * b0.surface_area() + count1 as Float * b1.surface_area())
+                        / bounds.surface_area();
+            }
+            // find bucket to split at that minimizes SAH metric
+            let mut min_cost: Float = cost[0];
+            let mut min_cost_split_bucket: usize = 0;
+            for (i, item) in cost.iter().enumerate() {
+                if item < &min_cost {
+                    min_cost = *item;
+                    min_cost_split_bucket = i;
+                }
+            }
+            // split nodes and create interior HLBVH SAH node
+            let (mut left, mut right): (Vec<&'a BVHBuildNode<'a>>, Vec<&'a BVHBuildNode<'a>>) =
+                treelet_roots[start..end]
+                    .iter()
+                    .partition(|root| bucket(&root.bounds) <= min_cost_split_bucket);
+            let mid: usize = start + left.len();
+            left.append(&mut right);
+            treelet_roots.splice(start..end, left);
+            mid
+        };
+        assert!(mid > start && mid < end);
+        let c0 = BVHAccel::build_upper_sah(arena, treelet_roots, start, mid, total_nodes);
+        let c1 = BVHAccel::build_upper_sah(arena, treelet_roots, mid, end, total_nodes);
+        node.init_interior(dim, c0, c1);
         node
     }
     pub fn flatten_bvh_tree<'a>(
@@ -459,11 +459,8 @@ impl KdTreeAccel {
         let mut bounds1: Bounds3f = *node_bounds;
         bounds0.p_max[best_axis_i] = t_split;
         bounds1.p_min[best_axis_i] = t_split;
-        // copy prims0
-        let mut prim_nums: Vec<usize> = Vec::with_capacity(prims0.len());
-        for i in 0..prims0.len() {
-            prim_nums.push(prims0[i]);
-        }
+        // copy prims0 (the below child reuses the buffer)
+        let prim_nums: Vec<usize> = prims0[..n0].to_vec();
         self.build_tree(
             node_num + 1,
             &bounds0,
@@ -479,10 +476,7 @@ impl KdTreeAccel {
         let above_child: i32 = self.next_free_node;
         self.nodes[node_num as usize].init_interior(best_axis, above_child, t_split);
         // copy prims1
-        let mut prim_nums: Vec<usize> = Vec::with_capacity(prims1.len());
-        for i in 0..prims1.len() {
-            prim_nums.push(prims1[i]);
-        }
+        let prim_nums: Vec<usize> = prims1[..n1].to_vec();
         self.build_tree(
             above_child,
             &bounds1,
@@ -496,6 +490,25 @@ impl KdTreeAccel {
             bad_refines,
         );
     }
+    /// Index (into *primitives*) of the *i*th primitive of the leaf
+    /// *node*. Primitives straddling a split plane are referenced
+    /// by the leaves on both sides, but only once per leaf.
+    pub fn leaf_primitive(&self, node: &KdAccelNode, i: i32) -> usize {
+        assert!(node.is_leaf() && i < node.n_primitives());
+        if node.n_primitives() == 1 {
+            let one_primitive: i32;
+            unsafe {
+                one_primitive = node.priv_union.one_primitive;
+            }
+            one_primitive as usize
+        } else {
+            let primitive_indices_offset: i32;
+            unsafe {
+                primitive_indices_offset = node.priv_union.primitive_indices_offset;
+            }
+            self.primitive_indices[(primitive_indices_offset + i) as usize] as usize
+        }
+    }
     // Primitive
     pub fn world_bound(&self) -> Bounds3f {
         self.bounds
@@ -577,32 +590,12 @@ impl KdTreeAccel {
                 }
             } else {
                 // check for intersections inside leaf node
-                let n_primitives: i32 = node.n_primitives();
-                if n_primitives == 1 {
-                    let one_primitive: i32;
-                    unsafe {
-                        one_primitive = node.priv_union.one_primitive;
-                    }
-                    let p: &Arc<Primitive> = &self.primitives[one_primitive as usize];
+                for i in 0..node.n_primitives() {
+                    let p: &Arc<Primitive> = &self.primitives[self.leaf_primitive(node, i)];
                     // check one primitive inside leaf node
                     if p.intersect(ray, isect) {
                         hit = true;
                     }
-                } else {
-                    for i in 0..n_primitives {
-                        let primitive_indices_offset: i32;
-                        unsafe {
-                            primitive_indices_offset = node.priv_union.primitive_indices_offset;
-                        }
-                        let index: usize = self.primitive_indices
-                            [(primitive_indices_offset + i) as usize]
-                            as usize;
-                        let p: &Arc<Primitive> = &self.primitives[index];
-                        // check one primitive inside leaf node
-                        if p.intersect(ray, isect) {
-                            hit = true;
-                        }
-                    }
                 }
                 // grab next node to process from todo list
                 if todo_pos > 0 {
@@ -642,30 +635,11 @@ impl KdTreeAccel {
         while let Some(node) = node_opt {
             if node.is_leaf() {
                 // check for shadow ray intersections inside leaf node
-                let n_primitives: i32 = node.n_primitives();
-                if n_primitives == 1 {
-                    let one_primitive: i32;
-                    unsafe {
-                        one_primitive = node.priv_union.one_primitive;
-                    }
-                    let p: &Arc<Primitive> = &self.primitives[one_primitive as usize];
-                    if p.intersect_p(ray) {
+                for i in 0..node.n_primitives() {
+                    let prim: &Arc<Primitive> = &self.primitives[self.leaf_primitive(node, i)];
+                    if prim.intersect_p(ray) {
                         return true;
                     }
-                } else {
-                    for i in 0..n_primitives {
-                        let primitive_indices_offset: i32;
-                        unsafe {
-                            primitive_indices_offset = node.priv_union.primitive_indices_offset;
-                        }
-                        let primitive_index: usize = self.primitive_indices
-                            [(primitive_indices_offset + i) as usize]
-                            as usize;
-                        let prim: &Arc<Primitive> = &self.primitives[primitive_index];
-                        if prim.intersect_p(ray) {
-                            return true;
-                        }
-                    }
                 }
                 // grab next node to process from todo list
                 if todo_pos > 0 {
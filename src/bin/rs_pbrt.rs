@@ -19,9 +19,9 @@ use pbrt::core::api::{
     pbrt_film, pbrt_init, pbrt_integrator, pbrt_light_source, pbrt_look_at,
     pbrt_make_named_material, pbrt_make_named_medium, pbrt_material, pbrt_medium_interface,
     pbrt_named_material, pbrt_object_begin, pbrt_object_end, pbrt_object_instance,
-    pbrt_pixel_filter, pbrt_reverse_orientation, pbrt_rotate, pbrt_sampler, pbrt_scale, pbrt_shape,
-    pbrt_texture, pbrt_transform, pbrt_transform_begin, pbrt_transform_end, pbrt_translate,
-    pbrt_world_begin,
+    pbrt_pixel_filter, pbrt_reverse_orientation, pbrt_rotate, pbrt_sampler, pbrt_scale,
+    pbrt_scene_seed, pbrt_shape, pbrt_texture, pbrt_transform, pbrt_transform_begin,
+    pbrt_transform_end, pbrt_translate, pbrt_world_begin,
 };
 use pbrt::core::api::{ApiState, BsdfState};
 use pbrt::core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
@@ -42,6 +42,9 @@ struct Cli {
     /// use specified number of threads for rendering
     #[structopt(short = "t", long = "nthreads", default_value = "0")]
     nthreads: u8,
+    /// seed the sampler with a hash of the scene file (same scene, same noise)
+    #[structopt(long = "seed-from-scene")]
+    seed_from_scene: bool,
     /// The path to the file to read
     #[structopt(parse(from_os_str))]
     path: std::path::PathBuf,
@@ -857,6 +860,18 @@ fn main() {
     println!("Copyright (c) 2016-2020 Jan Douglas Bert Walter.");
     println!("Rust code based on C++ code by Matt Pharr, Greg Humphreys, and Wenzel Jakob.");
     let (mut api_state, mut bsdf_state) = pbrt_init(number_of_threads);
+    if args.seed_from_scene {
+        match std::fs::read(&args.path) {
+            Ok(scene) => {
+                let seed: u64 = pbrt_scene_seed(&mut api_state, &scene);
+                println!("Scene seed {:#018x}", seed);
+            }
+            Err(e) => println!(
+                "ERROR: Can't read {:?} for the scene seed ({}). Using the default seed.",
+                args.path, e
+            ),
+        }
+    }
     parse_file(
         args.path.into_os_string().into_string().unwrap(),
         &mut api_state,
@@ -4,7 +4,7 @@ use std::sync::Arc;
 // pbrt
 use crate::core::camera::{Camera, CameraSample};
 use crate::core::film::Film;
-use crate::core::geometry::{Bounds2f, Point2f, Point3f, Ray, Vector3f};
+use crate::core::geometry::{Bounds2f, Point2f, Point3f, Ray, RayDifferential, Vector3f};
 use crate::core::interaction::InteractionCommon;
 use crate::core::light::VisibilityTester;
 use crate::core::medium::Medium;
@@ -46,10 +46,15 @@ impl EnvironmentCamera {
         film: Arc<Film>,
         medium: Option<Arc<Medium>>,
     ) -> Arc<Camera> {
-        let shutteropen: Float = params.find_one_float("shutteropen", 0.0);
-        let shutterclose: Float = params.find_one_float("shutterclose", 1.0);
-        // TODO: std::swap(shutterclose, shutteropen);
-        assert!(shutterclose >= shutteropen);
+        let mut shutteropen: Float = params.find_one_float("shutteropen", 0.0);
+        let mut shutterclose: Float = params.find_one_float("shutterclose", 1.0);
+        if shutterclose < shutteropen {
+            println!(
+                "WARNING: Shutter close time [{}] < shutter open [{}].  Swapping them.",
+                shutterclose, shutteropen
+            );
+            std::mem::swap(&mut shutterclose, &mut shutteropen);
+        }
         // let lensradius: Float = params.find_one_float(String::from("lensradius"), 0.0);
         // let focaldistance: Float = params.find_one_float(String::from("focaldistance"), 1e30);
         let frame: Float = params.find_one_float(
@@ -88,8 +93,14 @@ impl EnvironmentCamera {
         ))))
     }
     // Camera
-    pub fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
-        let theta: Float = PI * sample.p_film.y / self.film.full_resolution.y as Float;
+    pub fn generate_ray(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
+        // compute environment camera ray direction, samples beyond the
+        // top and bottom rows (within the filter radius) are clamped
+        // to the poles instead of wrapping around them
+        let theta: Float = (PI * sample.p_film.y / self.film.full_resolution.y as Float)
+            .max(0.0 as Float)
+            .min(PI);
         let phi: Float = 2.0 as Float * PI * sample.p_film.x / self.film.full_resolution.x as Float;
         let dir: Vector3f = Vector3f {
             x: theta.sin() * phi.cos(),
@@ -107,12 +118,39 @@ impl EnvironmentCamera {
         // ray->medium = medium;
         if let Some(ref medium_arc) = self.medium {
             in_ray.medium = Some(medium_arc.clone());
-        } else {
-            in_ray.medium = None;
         }
         *ray = self.camera_to_world.transform_ray(&in_ray);
         1.0
     }
+    pub fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        let wt: Float = self.generate_ray(sample, ray);
+        let eps: Float = 0.05 as Float;
+        // find camera ray after shifting a fraction of a pixel in the $x$ direction
+        let mut sshift: CameraSample = *sample;
+        sshift.p_film.x += eps;
+        let mut rx: Ray = Ray::default();
+        self.generate_ray(&sshift, &mut rx);
+        // find camera ray after shifting a fraction of a pixel in the
+        // $y$ direction (towards the equator, to stay away from the
+        // clamped rows at the poles)
+        let half_height: Float = 0.5 as Float * self.film.full_resolution.y as Float;
+        let eps_y: Float = if sample.p_film.y > half_height {
+            -eps
+        } else {
+            eps
+        };
+        let mut sshift: CameraSample = *sample;
+        sshift.p_film.y += eps_y;
+        let mut ry: Ray = Ray::default();
+        self.generate_ray(&sshift, &mut ry);
+        ray.differential = Some(RayDifferential {
+            rx_origin: ray.o + (rx.o - ray.o) / eps,
+            ry_origin: ray.o + (ry.o - ray.o) / eps_y,
+            rx_direction: ray.d + (rx.d - ray.d) / eps,
+            ry_direction: ray.d + (ry.d - ray.d) / eps_y,
+        });
+        wt
+    }
     pub fn we(&self, _ray: &Ray, _p_raster2: Option<&mut Point2f>) -> Spectrum {
         panic!("camera::we() is not implemented!");
         // Spectrum::default()
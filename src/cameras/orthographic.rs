@@ -101,10 +101,15 @@ impl OrthographicCamera {
         film: Arc<Film>,
         medium: Option<Arc<Medium>>,
     ) -> Arc<Camera> {
-        let shutteropen: Float = params.find_one_float("shutteropen", 0.0);
-        let shutterclose: Float = params.find_one_float("shutterclose", 1.0);
-        // TODO: std::swap(shutterclose, shutteropen);
-        assert!(shutterclose >= shutteropen);
+        let mut shutteropen: Float = params.find_one_float("shutteropen", 0.0);
+        let mut shutterclose: Float = params.find_one_float("shutterclose", 1.0);
+        if shutterclose < shutteropen {
+            println!(
+                "WARNING: Shutter close time [{}] < shutter open [{}].  Swapping them.",
+                shutterclose, shutteropen
+            );
+            std::mem::swap(&mut shutterclose, &mut shutteropen);
+        }
         let lensradius: Float = params.find_one_float("lensradius", 0.0);
         let focaldistance: Float = params.find_one_float("focaldistance", 1e6);
         let frame: Float = params.find_one_float(
@@ -131,7 +136,7 @@ impl OrthographicCamera {
                 screen.p_min.y = sw[2];
                 screen.p_max.y = sw[3];
             } else {
-                panic!("\"screenwindow\" should have four values");
+                println!("ERROR: \"screenwindow\" should have four values");
             }
         }
         Arc::new(Camera::Orthographic(Box::new(OrthographicCamera::new(
@@ -145,8 +150,26 @@ impl OrthographicCamera {
             medium,
         ))))
     }
+    /// Moves the origin of the camera space ray starting at the film
+    /// point *p_camera* by *p_lens* and refocuses it, so that it
+    /// still passes through the same point on the plane of focus.
+    fn focus_through_lens(&self, p_camera: &Point3f, p_lens: &Point2f) -> (Point3f, Vector3f) {
+        // compute point on plane of focus
+        let p_focus: Point3f = Point3f {
+            x: p_camera.x,
+            y: p_camera.y,
+            z: p_camera.z + self.focal_distance,
+        };
+        // update ray for effect of lens
+        let o: Point3f = Point3f {
+            x: p_camera.x + p_lens.x,
+            y: p_camera.y + p_lens.y,
+            z: p_camera.z,
+        };
+        (o, (p_focus - o).normalize())
+    }
     // Camera
-    pub fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+    pub fn generate_ray(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
         // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
         // compute raster and camera sample positions
         let p_film: Point3f = Point3f {
@@ -155,7 +178,7 @@ impl OrthographicCamera {
             z: 0.0,
         };
         let p_camera: Point3f = self.raster_to_camera.transform_point(&p_film);
-        *ray = Ray {
+        let mut in_ray: Ray = Ray {
             o: p_camera,
             d: Vector3f {
                 x: 0.0,
@@ -171,67 +194,34 @@ impl OrthographicCamera {
         if self.lens_radius > 0.0 as Float {
             // sample point on lens
             let p_lens: Point2f = concentric_sample_disk(sample.p_lens) * self.lens_radius;
-            // compute point on plane of focus
-            let ft: Float = self.focal_distance / ray.d.z;
-            let p_focus: Point3f = ray.position(ft);
-            // update ray for effect of lens
-            ray.o = Point3f {
-                x: p_lens.x,
-                y: p_lens.y,
-                z: 0.0 as Float,
-            };
-            ray.d = (p_focus - ray.o).normalize();
+            let (o, d) = self.focus_through_lens(&p_camera, &p_lens);
+            in_ray.o = o;
+            in_ray.d = d;
         }
-        // compute offset rays for _OrthographicCamera_ ray differentials
-        if self.lens_radius > 0.0 as Float {
-            // compute _OrthographicCamera_ ray differentials accounting for lens
-
-            // sample point on lens
-            let p_lens: Point2f = concentric_sample_disk(sample.p_lens) * self.lens_radius;
-            let ft: Float = self.focal_distance / ray.d.z;
-            let p_focus: Point3f = p_camera
-                + self.dx_camera
-                + (Vector3f {
-                    x: 0.0 as Float,
-                    y: 0.0 as Float,
-                    z: 1.0 as Float,
-                } * ft);
-            let rx_origin = Point3f {
-                x: p_lens.x,
-                y: p_lens.y,
-                z: 0.0 as Float,
-            };
-            let ry_origin = Point3f {
-                x: p_lens.x,
-                y: p_lens.y,
-                z: 0.0 as Float,
-            };
-            let diff = RayDifferential {
-                rx_origin,
-                rx_direction: (p_focus - rx_origin).normalize(),
-                ry_origin,
-                ry_direction: (p_focus - ry_origin).normalize(),
-            };
-            // replace differential
-            ray.differential = Some(diff);
-        } else {
-            let diff: RayDifferential = RayDifferential {
-                rx_origin: ray.o + self.dx_camera,
-                ry_origin: ray.o + self.dy_camera,
-                rx_direction: ray.d,
-                ry_direction: ray.d,
-            };
-            ray.differential = Some(diff);
-        }
-        // ray->medium = medium;
         if let Some(ref medium_arc) = self.medium {
-            ray.medium = Some(medium_arc.clone());
-        } else {
-            ray.medium = None;
+            in_ray.medium = Some(medium_arc.clone());
         }
-        *ray = self.camera_to_world.transform_ray(ray);
+        *ray = self.camera_to_world.transform_ray(&in_ray);
         1.0
     }
+    /// Like *generate_ray()*, the projection is affine, so the offset
+    /// rays for neighbouring pixels are the main ray shifted by one
+    /// pixel in x and y (with or without a lens).
+    pub fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        let wt: Float = self.generate_ray(sample, ray);
+        // compute offset rays for _OrthographicCamera_ ray differentials
+        let mut c2w: Transform = Transform::default();
+        self.camera_to_world.interpolate(ray.time, &mut c2w);
+        let dx: Vector3f = c2w.transform_vector(&self.dx_camera);
+        let dy: Vector3f = c2w.transform_vector(&self.dy_camera);
+        ray.differential = Some(RayDifferential {
+            rx_origin: ray.o + dx,
+            ry_origin: ray.o + dy,
+            rx_direction: ray.d,
+            ry_direction: ray.d,
+        });
+        wt
+    }
     pub fn we(&self, _ray: &Ray, _p_raster2: Option<&mut Point2f>) -> Spectrum {
         panic!("camera::we() is not implemented!");
         // Spectrum::default()
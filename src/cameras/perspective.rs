@@ -133,10 +133,15 @@ impl PerspectiveCamera {
         film: Arc<Film>,
         medium: Option<Arc<Medium>>,
     ) -> Arc<Camera> {
-        let shutteropen: Float = params.find_one_float("shutteropen", 0.0);
-        let shutterclose: Float = params.find_one_float("shutterclose", 1.0);
-        // TODO: std::swap(shutterclose, shutteropen);
-        assert!(shutterclose >= shutteropen);
+        let mut shutteropen: Float = params.find_one_float("shutteropen", 0.0);
+        let mut shutterclose: Float = params.find_one_float("shutterclose", 1.0);
+        if shutterclose < shutteropen {
+            println!(
+                "WARNING: Shutter close time [{}] < shutter open [{}].  Swapping them.",
+                shutterclose, shutteropen
+            );
+            std::mem::swap(&mut shutterclose, &mut shutteropen);
+        }
         let lensradius: Float = params.find_one_float("lensradius", 0.0);
         let focaldistance: Float = params.find_one_float("focaldistance", 1e6);
         let frame: Float = params.find_one_float(
@@ -156,17 +161,22 @@ impl PerspectiveCamera {
             screen.p_max.y = 1.0 / frame;
         }
         let sw: Vec<Float> = params.find_float("screenwindow");
-        if !sw.is_empty() && sw.len() == 4 {
-            screen.p_min.x = sw[0];
-            screen.p_max.x = sw[1];
-            screen.p_min.y = sw[2];
-            screen.p_max.y = sw[3];
+        if !sw.is_empty() {
+            if sw.len() == 4 {
+                screen.p_min.x = sw[0];
+                screen.p_max.x = sw[1];
+                screen.p_min.y = sw[2];
+                screen.p_max.y = sw[3];
+            } else {
+                println!("ERROR: \"screenwindow\" should have four values");
+            }
+        }
+        let mut fov: Float = params.find_one_float("fov", 90.0);
+        let halffov: Float = params.find_one_float("halffov", -1.0);
+        if halffov > 0.0 as Float {
+            // hack for structure synth, which exports half of the full fov
+            fov = 2.0 as Float * halffov;
         }
-        let fov: Float = params.find_one_float("fov", 90.0);
-        // let halffov: Float =
-        //     params.find_one_float(String::from("halffov"), -1.0);
-        // TODO: if (halffov > 0.f)
-        // TODO: let perspective_camera: Arc<Camera + Sync + Send> =
         Arc::new(Camera::Perspective(Box::new(PerspectiveCamera::new(
             cam2world,
             screen,
@@ -179,9 +189,26 @@ impl PerspectiveCamera {
             medium,
         ))))
     }
-    // Camera
-    pub fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
-        // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
+    /// Moves the origin of the camera space ray through the film
+    /// point *p_camera* to *p_lens* on the lens and refocuses it, so
+    /// that it still passes through the same point on the plane of
+    /// focus.
+    fn focus_through_lens(&self, p_camera: &Point3f, p_lens: &Point2f) -> (Point3f, Vector3f) {
+        let dir: Vector3f = Vector3f::from(*p_camera).normalize();
+        // compute point on plane of focus
+        let ft: Float = self.focal_distance / dir.z;
+        let p_focus: Point3f = Point3f::default() + dir * ft;
+        // update ray for effect of lens
+        let o: Point3f = Point3f {
+            x: p_lens.x,
+            y: p_lens.y,
+            z: 0.0 as Float,
+        };
+        (o, (p_focus - o).normalize())
+    }
+    /// Camera space position of the film sample and, for a thin lens,
+    /// the sampled point on the lens.
+    fn sample_camera(&self, sample: &CameraSample) -> (Point3f, Option<Point2f>) {
         // compute raster and camera sample positions
         let p_film: Point3f = Point3f {
             x: sample.p_film.x,
@@ -189,85 +216,63 @@ impl PerspectiveCamera {
             z: 0.0,
         };
         let p_camera: Point3f = self.raster_to_camera.transform_point(&p_film);
-        let dir: Vector3f = Vector3f {
-            x: p_camera.x,
-            y: p_camera.y,
-            z: p_camera.z,
+        // sample point on lens
+        let p_lens: Option<Point2f> = if self.lens_radius > 0.0 as Float {
+            Some(concentric_sample_disk(sample.p_lens) * self.lens_radius)
+        } else {
+            None
+        };
+        (p_camera, p_lens)
+    }
+    /// Camera space origin and direction of the ray through
+    /// *p_camera*, modified for depth of field if there is a point
+    /// *p_lens* on the lens.
+    fn primary_ray(&self, p_camera: &Point3f, p_lens: Option<&Point2f>) -> (Point3f, Vector3f) {
+        if let Some(p_lens) = p_lens {
+            self.focus_through_lens(p_camera, p_lens)
+        } else {
+            (Point3f::default(), Vector3f::from(*p_camera).normalize())
         }
-        .normalize();
-        let mut diff: RayDifferential = RayDifferential {
-            rx_origin: ray.o,
-            ry_origin: ray.o,
-            rx_direction: (Vector3f {
-                x: p_camera.x,
-                y: p_camera.y,
-                z: p_camera.z,
-            } + self.dx_camera)
-                .normalize(),
-            ry_direction: (Vector3f {
-                x: p_camera.x,
-                y: p_camera.y,
-                z: p_camera.z,
-            } + self.dy_camera)
-                .normalize(),
+    }
+    // Camera
+    pub fn generate_ray(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
+        let (p_camera, p_lens) = self.sample_camera(sample);
+        let (o, d) = self.primary_ray(&p_camera, p_lens.as_ref());
+        let in_ray: Ray = Ray {
+            o,
+            d,
+            t_max: std::f32::INFINITY,
+            time: lerp(sample.time, self.shutter_open, self.shutter_close),
+            medium: self.medium.clone(),
+            differential: None,
         };
-        // *ray = RayDifferential(Point3f(0, 0, 0), dir);
-        let mut in_ray: Ray = Ray {
-            o: Point3f::default(),
-            d: dir,
+        *ray = self.camera_to_world.transform_ray(&in_ray);
+        1.0
+    }
+    pub fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
+        let (p_camera, p_lens) = self.sample_camera(sample);
+        let (o, d) = self.primary_ray(&p_camera, p_lens.as_ref());
+        // compute offset rays for _PerspectiveCamera_ ray
+        // differentials, which go through the same point on the lens
+        let (rx_origin, rx_direction) =
+            self.primary_ray(&(p_camera + self.dx_camera), p_lens.as_ref());
+        let (ry_origin, ry_direction) =
+            self.primary_ray(&(p_camera + self.dy_camera), p_lens.as_ref());
+        let in_ray: Ray = Ray {
+            o,
+            d,
             t_max: std::f32::INFINITY,
             time: lerp(sample.time, self.shutter_open, self.shutter_close),
-            medium: None,
-            differential: Some(diff),
+            medium: self.medium.clone(),
+            differential: Some(RayDifferential {
+                rx_origin,
+                ry_origin,
+                rx_direction,
+                ry_direction,
+            }),
         };
-        // modify ray for depth of field
-        if self.lens_radius > 0.0 as Float {
-            // sample point on lens
-            let p_lens: Point2f = concentric_sample_disk(sample.p_lens) * self.lens_radius;
-            // compute point on plane of focus
-            let ft: Float = self.focal_distance / in_ray.d.z;
-            let p_focus: Point3f = in_ray.position(ft);
-            // update ray for effect of lens
-            in_ray.o = Point3f {
-                x: p_lens.x,
-                y: p_lens.y,
-                z: 0.0 as Float,
-            };
-            in_ray.d = (p_focus - in_ray.o).normalize();
-        }
-        // compute offset rays for _PerspectiveCamera_ ray differentials
-        if self.lens_radius > 0.0 as Float {
-            // compute _PerspectiveCamera_ ray differentials accounting for lens
-
-            // sample point on lens
-            let p_lens: Point2f = concentric_sample_disk(sample.p_lens) * self.lens_radius;
-            let dx: Vector3f = Vector3f::from(p_camera + self.dx_camera).normalize();
-            let ft: Float = self.focal_distance / dx.z;
-            let p_focus: Point3f = Point3f::default() + (dx * ft);
-            diff.rx_origin = Point3f {
-                x: p_lens.x,
-                y: p_lens.y,
-                z: 0.0 as Float,
-            };
-            diff.rx_direction = (p_focus - diff.rx_origin).normalize();
-            let dy: Vector3f = Vector3f::from(p_camera + self.dy_camera).normalize();
-            let ft: Float = self.focal_distance / dy.z;
-            let p_focus: Point3f = Point3f::default() + (dy * ft);
-            diff.ry_origin = Point3f {
-                x: p_lens.x,
-                y: p_lens.y,
-                z: 0.0 as Float,
-            };
-            diff.ry_direction = (p_focus - diff.ry_origin).normalize();
-            // replace differential
-            in_ray.differential = Some(diff);
-        }
-        // ray->medium = medium;
-        if let Some(ref medium_arc) = self.medium {
-            in_ray.medium = Some(medium_arc.clone());
-        } else {
-            in_ray.medium = None;
-        }
         *ray = self.camera_to_world.transform_ray(&in_ray);
         1.0
     }
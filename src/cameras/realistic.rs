@@ -21,6 +21,10 @@ use crate::core::transform::{AnimatedTransform, Transform};
 
 // see realistic.h
 
+/// One spherical interface (or the aperture stop, for a zero
+/// *curvature_radius*) of the lens system, in meters. *eta* is the
+/// index of refraction of the medium behind the interface (towards
+/// the film).
 #[derive(Debug, Default, Copy, Clone)]
 pub struct LensElementInterface {
     pub curvature_radius: Float,
@@ -29,6 +33,8 @@ pub struct LensElementInterface {
     pub aperture_radius: Float,
 }
 
+/// Camera tracing rays through a system of lens elements, as
+/// described by a lens file (see **RealisticCamera::create()**).
 #[derive(Clone)]
 pub struct RealisticCamera {
     // inherited from Camera (see camera.h)
@@ -60,7 +66,7 @@ impl RealisticCamera {
             let mut diameter: Float = lens_data[i + 3];
             if lens_data[i] == 0.0 as Float {
                 if aperture_diameter > lens_data[i + 3] {
-                    println!("Specified aperture diameter {} is greater than maximum possible {}.  Clamping it.",
+                    println!("WARNING: Specified aperture diameter {} is greater than maximum possible {}.  Clamping it.",
                              aperture_diameter,
                              lens_data[i + 3]);
                 } else {
@@ -99,13 +105,14 @@ impl RealisticCamera {
         let mut exit_pupil_bounds: Vec<Bounds2f> = Vec::new();
         exit_pupil_bounds.resize(n_samples, Bounds2f::default());
         let num_cores: usize = num_cpus::get();
-        let chunk_size: usize = n_samples / num_cores;
+        let chunk_size: usize = n_samples.div_ceil(num_cores).max(1);
         {
             let bands: Vec<&mut [Bounds2f]> = exit_pupil_bounds.chunks_mut(chunk_size).collect();
+            let n_bands: usize = bands.len();
             let camera = &camera;
             let film = &film;
             crossbeam::scope(|scope| {
-                let (band_tx, band_rx) = crossbeam_channel::bounded(num_cores);
+                let (band_tx, band_rx) = crossbeam_channel::bounded(n_bands);
                 // spawn worker threads
                 for (b, band) in bands.into_iter().enumerate() {
                     let band_tx = band_tx.clone();
@@ -126,7 +133,7 @@ impl RealisticCamera {
                 }
                 // spawn thread to report progress
                 scope.spawn(move |_| {
-                    for _ in pbr::PbIter::new(0..num_cores) {
+                    for _ in pbr::PbIter::new(0..n_bands) {
                         band_rx.recv().unwrap();
                     }
                 });
@@ -139,17 +146,29 @@ impl RealisticCamera {
         }
         camera
     }
+    /// Reads the *"lensfile"* (four values per element, from the
+    /// scene towards the film: curvature radius, thickness, index of
+    /// refraction and aperture diameter, all in millimeters; the stop
+    /// has a curvature radius of zero), *"aperturediameter"*,
+    /// *"focusdistance"* (or *"focaldistance"*) and
+    /// *"simpleweighting"*. Returns **None** if the lens file is
+    /// missing or malformed.
     pub fn create(
         params: &ParamSet,
         cam2world: AnimatedTransform,
         film: Arc<Film>,
         medium: Option<Arc<Medium>>,
         search_directory: Option<&PathBuf>,
-    ) -> Arc<Camera> {
-        let shutteropen: Float = params.find_one_float("shutteropen", 0.0);
-        let shutterclose: Float = params.find_one_float("shutterclose", 1.0);
-        // TODO: std::swap(shutterclose, shutteropen);
-        assert!(shutterclose >= shutteropen);
+    ) -> Option<Arc<Camera>> {
+        let mut shutteropen: Float = params.find_one_float("shutteropen", 0.0);
+        let mut shutterclose: Float = params.find_one_float("shutterclose", 1.0);
+        if shutterclose < shutteropen {
+            println!(
+                "WARNING: Shutter close time [{}] < shutter open [{}].  Swapping them.",
+                shutterclose, shutteropen
+            );
+            std::mem::swap(&mut shutterclose, &mut shutteropen);
+        }
         // realistic camera-specific parameters
         let mut lens_file: String = params.find_one_filename("lensfile", String::from(""));
         if lens_file != "" {
@@ -162,11 +181,13 @@ impl RealisticCamera {
         }
         if lens_file == "" {
             println!("ERROR: No lens description file supplied!");
-        } else {
-            println!("lens_file = {:?}", lens_file);
+            return None;
         }
         let aperture_diameter: Float = params.find_one_float("aperturediameter", 1.0);
-        let focus_distance: Float = params.find_one_float("focusdistance", 10.0);
+        let focus_distance: Float = params.find_one_float(
+            "focusdistance",
+            params.find_one_float("focaldistance", 10.0),
+        );
         let simple_weighting: bool = params.find_one_bool("simpleweighting", true);
         let mut lens_data: Vec<Float> = Vec::new();
         if !read_float_file(&lens_file, &mut lens_data) {
@@ -174,13 +195,18 @@ impl RealisticCamera {
                 "ERROR: Error reading lens specification file {:?}.",
                 lens_file
             );
+            return None;
         }
         if lens_data.len() % 4_usize != 0_usize {
             println!("ERROR: Excess values in lens specification file {:?}; must be multiple-of-four values, read {}.",
                      lens_file, lens_data.len());
+            return None;
+        }
+        if lens_data.is_empty() {
+            println!("ERROR: No lens elements in {:?}.", lens_file);
+            return None;
         }
-        // println!("lens_data = {:?}", lens_data);
-        Arc::new(Camera::Realistic(Box::new(RealisticCamera::new(
+        Some(Arc::new(Camera::Realistic(Box::new(RealisticCamera::new(
             cam2world,
             shutteropen,
             shutterclose,
@@ -190,8 +216,14 @@ impl RealisticCamera {
             &lens_data,
             film,
             medium,
-        ))))
+        )))))
     }
+    /// Traces a ray from the film through the exit pupil and the lens
+    /// system. Rays blocked by an aperture or totally internally
+    /// reflected at an element get a weight of zero. Otherwise the
+    /// weight is the radiometric one, *cos^4(theta) A / z^2* (scaled
+    /// by the shutter interval), or, with *simple_weighting*, relative
+    /// to the exit pupil at the film center.
     pub fn generate_ray(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
         // TODO: ProfilePhase prof(Prof::GenerateCameraRay);
         // ++totalRays;
@@ -258,6 +290,9 @@ impl RealisticCamera {
     pub fn rear_element_radius(&self) -> Float {
         self.element_interfaces.last().unwrap().aperture_radius
     }
+    /// Traces *r_camera* (in camera space) from the film through all
+    /// elements. Returns false if the ray is blocked or totally
+    /// internally reflected.
     pub fn trace_lenses_from_film(&self, r_camera: &Ray, r_out: Option<&mut Ray>) -> bool {
         let mut element_z: Float = 0.0 as Float;
         // transform _rCamera_ from camera to lens system space
@@ -289,7 +324,9 @@ impl RealisticCamera {
                     return false;
                 }
             }
-            assert!(t >= 0.0 as Float);
+            if t < 0.0 as Float {
+                return false;
+            }
             // test intersection point against element aperture
             let p_hit: Point3f = r_lens.position(t);
             let r2: Float = p_hit.x * p_hit.x + p_hit.y * p_hit.y;
@@ -370,6 +407,10 @@ impl RealisticCamera {
             let mut n: Normal3f = Normal3f::default();
             let is_stop: bool = element.curvature_radius == 0.0 as Float;
             if is_stop {
+                // from the scene the ray has to move towards the film (+z)
+                if r_lens.d.z <= 0.0 as Float {
+                    return false;
+                }
                 t = (element_z - r_lens.o.z) / r_lens.d.z;
             } else {
                 let radius: Float = element.curvature_radius;
@@ -378,7 +419,9 @@ impl RealisticCamera {
                     return false;
                 }
             }
-            assert!(t >= 0.0 as Float);
+            if t < 0.0 as Float {
+                return false;
+            }
             // test intersection point against element aperture
             let p_hit: Point3f = r_lens.position(t);
             let r2: Float = p_hit.x * p_hit.x + p_hit.y * p_hit.y;
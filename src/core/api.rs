@@ -17,22 +17,21 @@ use crate::cameras::realistic::RealisticCamera;
 use crate::core::camera::Camera;
 use crate::core::film::Film;
 use crate::core::filter::Filter;
-use crate::core::geometry::{vec3_coordinate_system, vec3_cross_vec3};
-use crate::core::geometry::{Bounds2i, Normal3f, Point2f, Point2i, Point3f, Vector3f};
-use crate::core::integrator::{Integrator, SamplerIntegrator};
-use crate::core::light::Light;
+use crate::core::geometry::vec3_cross_vec3;
+use crate::core::geometry::{Bounds2i, Normal3f, Point2f, Point3f, Vector3f};
+use crate::core::integrator::{Integrator, RenderStats};
+use crate::core::light::{light_visibility_from_str, Light};
 use crate::core::material::Material;
-use crate::core::medium::get_medium_scattering_properties;
 use crate::core::medium::{Medium, MediumInterface};
-use crate::core::mipmap::ImageWrap;
 use crate::core::paramset::{ParamSet, TextureParams};
-use crate::core::pbrt::lerp;
+use crate::core::pbrt::{hash_bytes, lerp};
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::primitive::{GeometricPrimitive, Primitive, TransformedPrimitive};
 use crate::core::reflection::FourierBSDFTable;
 use crate::core::sampler::Sampler;
 use crate::core::scene::Scene;
 use crate::core::shape::Shape;
+use crate::core::spectrum::WavelengthSampling;
 use crate::core::texture::{
     CylindricalMapping2D, IdentityMapping3D, PlanarMapping2D, SphericalMapping2D, Texture,
     TextureMapping2D, TextureMapping3D, UVMapping2D,
@@ -45,7 +44,7 @@ use crate::filters::sinc::LanczosSincFilter;
 use crate::filters::triangle::TriangleFilter;
 use crate::integrators::ao::AOIntegrator;
 use crate::integrators::bdpt::BDPTIntegrator;
-use crate::integrators::directlighting::{DirectLightingIntegrator, LightStrategy};
+use crate::integrators::directlighting::DirectLightingIntegrator;
 use crate::integrators::mlt::MLTIntegrator;
 use crate::integrators::path::PathIntegrator;
 use crate::integrators::sppm::SPPMIntegrator;
@@ -62,6 +61,7 @@ use crate::materials::disney::DisneyMaterial;
 use crate::materials::fourier::FourierMaterial;
 use crate::materials::glass::GlassMaterial;
 use crate::materials::hair::HairMaterial;
+use crate::materials::kdsubsurface::KdSubsurfaceMaterial;
 use crate::materials::matte::MatteMaterial;
 use crate::materials::metal::MetalMaterial;
 use crate::materials::mirror::MirrorMaterial;
@@ -73,6 +73,9 @@ use crate::materials::translucent::TranslucentMaterial;
 use crate::materials::uber::UberMaterial;
 use crate::media::grid::GridDensityMedium;
 use crate::media::homogeneous::HomogeneousMedium;
+use crate::samplers::adaptive::AdaptiveSampler;
+use crate::samplers::block::BlockSampler;
+use crate::samplers::cmj::CmjSampler;
 use crate::samplers::halton::HaltonSampler;
 use crate::samplers::maxmin::MaxMinDistSampler;
 use crate::samplers::random::RandomSampler;
@@ -87,8 +90,9 @@ use crate::shapes::nurbs::nurbs_evaluate_surface;
 use crate::shapes::nurbs::Homogeneous3;
 use crate::shapes::plymesh::create_ply_mesh;
 use crate::shapes::sphere::Sphere;
-use crate::shapes::triangle::{Triangle, TriangleMesh};
-use crate::textures::checkerboard::Checkerboard2DTexture;
+use crate::shapes::triangle::{create_triangle_mesh, Triangle, TriangleMesh};
+use crate::textures::bilerp::BilerpTexture;
+use crate::textures::checkerboard::{Checkerboard2DTexture, Checkerboard3DTexture};
 use crate::textures::constant::ConstantTexture;
 use crate::textures::dots::DotsTexture;
 use crate::textures::fbm::FBmTexture;
@@ -203,243 +207,35 @@ pub struct RenderOptions {
     pub instances: HashMap<String, Vec<Arc<Primitive>>>,
     pub current_instance: String,
     pub have_scattering_media: bool, // false
+    /// Base seed for the sampler (unless it has a *"seed"* parameter),
+    /// see *pbrt_scene_seed()*
+    pub scene_seed: Option<u64>,
 }
 
 impl RenderOptions {
     pub fn make_integrator(&self) -> Option<Box<Integrator>> {
-        let mut some_integrator: Option<Box<Integrator>> = None;
         let some_camera: Option<Arc<Camera>> = self.make_camera();
         if let Some(camera) = some_camera {
             let some_sampler: Option<Box<Sampler>> =
                 make_sampler(&self.sampler_name, &self.sampler_params, camera.get_film());
-            if let Some(sampler) = some_sampler {
-                if self.integrator_name == "whitted" {
-                    // CreateWhittedIntegrator
-                    let max_depth: i32 = self.integrator_params.find_one_int("maxdepth", 5);
-                    let pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
-                    let integrator = Box::new(Integrator::Sampler(SamplerIntegrator::Whitted(
-                        WhittedIntegrator::new(max_depth as u32, camera, sampler, pixel_bounds),
-                    )));
-                    some_integrator = Some(integrator);
-                } else if self.integrator_name == "directlighting" {
-                    // CreateDirectLightingIntegrator
-                    let max_depth: i32 = self.integrator_params.find_one_int("maxdepth", 5);
-                    let st: String = self
-                        .integrator_params
-                        .find_one_string("strategy", String::from("all"));
-                    let strategy: LightStrategy;
-                    if st == "one" {
-                        strategy = LightStrategy::UniformSampleOne;
-                    } else if st == "all" {
-                        strategy = LightStrategy::UniformSampleAll;
-                    } else {
-                        panic!("Strategy \"{}\" for direct lighting unknown.", st);
+            if let Some(mut sampler) = some_sampler {
+                if let Some(scene_seed) = self.scene_seed {
+                    if self.sampler_params.find_int("seed").is_empty() {
+                        sampler.set_base_seed(scene_seed);
                     }
-                    // TODO: const int *pb = params.FindInt("pixelbounds", &np);
-                    let xres: i32 = self.film_params.find_one_int("xresolution", 1280);
-                    let yres: i32 = self.film_params.find_one_int("yresolution", 720);
-                    let pixel_bounds: Bounds2i = Bounds2i {
-                        p_min: Point2i { x: 0, y: 0 },
-                        p_max: Point2i { x: xres, y: yres },
-                    };
-                    let integrator = Box::new(Integrator::Sampler(
-                        SamplerIntegrator::DirectLighting(DirectLightingIntegrator::new(
-                            strategy,
-                            max_depth as u32,
-                            camera,
-                            sampler,
-                            pixel_bounds,
-                        )),
-                    ));
-                    some_integrator = Some(integrator);
-                } else if self.integrator_name == "path" {
-                    // CreatePathIntegrator
-                    let max_depth: i32 = self.integrator_params.find_one_int("maxdepth", 5);
-                    let pb: Vec<i32> = self.integrator_params.find_int("pixelbounds");
-                    let np: usize = pb.len();
-                    let pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
-                    if np > 0 as usize {
-                        if np != 4 as usize {
-                            panic!(
-                                "Expected four values for \"pixelbounds\" parameter. Got {}.",
-                                np
-                            );
-                        } else {
-                            println!("TODO: pixelBounds = Intersect(...)");
-                            // pixelBounds = Intersect(pixelBounds,
-                            //                         Bounds2i{{pb[0], pb[2]}, {pb[1], pb[3]}});
-                            // if (pixelBounds.Area() == 0)
-                            //     Error("Degenerate \"pixelbounds\" specified.");
-                        }
-                    }
-                    let rr_threshold: Float = self
-                        .integrator_params
-                        .find_one_float("rrthreshold", 1.0 as Float);
-                    let light_strategy: String = self
-                        .integrator_params
-                        .find_one_string("lightsamplestrategy", String::from("spatial"));
-                    let integrator = Box::new(Integrator::Sampler(SamplerIntegrator::Path(
-                        PathIntegrator::new(
-                            max_depth as u32,
-                            camera,
-                            sampler,
-                            pixel_bounds,
-                            rr_threshold,
-                            light_strategy,
-                        ),
-                    )));
-                    some_integrator = Some(integrator);
-                } else if self.integrator_name == "volpath" {
-                    // CreateVolPathIntegrator
-                    let max_depth: i32 = self.integrator_params.find_one_int("maxdepth", 5);
-                    let pb: Vec<i32> = self.integrator_params.find_int("pixelbounds");
-                    let np: usize = pb.len();
-                    let pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
-                    if np > 0 as usize {
-                        if np != 4 as usize {
-                            panic!(
-                                "Expected four values for \"pixelbounds\" parameter. Got {}.",
-                                np
-                            );
-                        } else {
-                            println!("TODO: pixelBounds = Intersect(...)");
-                            // pixelBounds = Intersect(pixelBounds,
-                            //                         Bounds2i{{pb[0], pb[2]}, {pb[1], pb[3]}});
-                            // if (pixelBounds.Area() == 0)
-                            //     Error("Degenerate \"pixelbounds\" specified.");
-                        }
-                    }
-                    let rr_threshold: Float = self
-                        .integrator_params
-                        .find_one_float("rrthreshold", 1.0 as Float);
-                    let light_strategy: String = self
-                        .integrator_params
-                        .find_one_string("lightsamplestrategy", String::from("spatial"));
-                    let integrator = Box::new(Integrator::Sampler(SamplerIntegrator::VolPath(
-                        VolPathIntegrator::new(
-                            max_depth as u32,
-                            camera,
-                            sampler,
-                            pixel_bounds,
-                            rr_threshold,
-                            light_strategy,
-                        ),
-                    )));
-                    some_integrator = Some(integrator);
-                } else if self.integrator_name == "bdpt" {
-                    // CreateBDPTIntegrator
-                    let mut max_depth: i32 = self.integrator_params.find_one_int("maxdepth", 5);
-                    let visualize_strategies: bool = self
-                        .integrator_params
-                        .find_one_bool("visualizestrategies", false);
-                    let visualize_weights: bool = self
-                        .integrator_params
-                        .find_one_bool("visualizeweights", false);
-                    if (visualize_strategies || visualize_weights) && max_depth > 5_i32 {
-                        print!("WARNING: visualizestrategies/visualizeweights was enabled,");
-                        println!(" limiting maxdepth to 5");
-                        max_depth = 5;
-                    }
-                    let pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
-                    let light_strategy: String = self
-                        .integrator_params
-                        .find_one_string("lightsamplestrategy", String::from("power"));
-                    let integrator = Box::new(Integrator::BDPT(BDPTIntegrator::new(
-                        camera,
-                        sampler,
-                        pixel_bounds,
-                        max_depth as u32,
-                        light_strategy,
-                    )));
-                    some_integrator = Some(integrator);
-                } else if self.integrator_name == "mlt" {
-                    // CreateMLTIntegrator
-                    let max_depth: i32 = self.integrator_params.find_one_int("maxdepth", 5);
-                    let n_bootstrap: i32 = self
-                        .integrator_params
-                        .find_one_int("bootstrapsamples", 100_000);
-                    let n_chains: i32 = self.integrator_params.find_one_int("chains", 1000);
-                    let mutations_per_pixel: i32 = self
-                        .integrator_params
-                        .find_one_int("mutationsperpixel", 100);
-                    let large_step_probability: Float = self
-                        .integrator_params
-                        .find_one_float("largestepprobability", 0.3 as Float);
-                    let sigma: Float = self
-                        .integrator_params
-                        .find_one_float("sigma", 0.01 as Float);
-                    let integrator = Box::new(Integrator::MLT(MLTIntegrator::new(
-                        camera,
-                        max_depth as u32,
-                        n_bootstrap as u32,
-                        n_chains as u32,
-                        mutations_per_pixel as u32,
-                        sigma,
-                        large_step_probability,
-                    )));
-                    some_integrator = Some(integrator);
-                } else if self.integrator_name == "ambientocclusion" {
-                    // CreateAOIntegrator
-                    let pb: Vec<i32> = self.integrator_params.find_int("pixelbounds");
-                    let np: usize = pb.len();
-                    let pixel_bounds: Bounds2i = camera.get_film().get_sample_bounds();
-                    if np > 0 as usize {
-                        if np != 4 as usize {
-                            panic!(
-                                "Expected four values for \"pixelbounds\" parameter. Got {}.",
-                                np
-                            );
-                        } else {
-                            println!("TODO: pixelBounds = Intersect(...)");
-                            // pixelBounds = Intersect(pixelBounds,
-                            //                         Bounds2i{{pb[0], pb[2]}, {pb[1], pb[3]}});
-                            // if (pixelBounds.Area() == 0)
-                            //     Error("Degenerate \"pixelbounds\" specified.");
-                        }
-                    }
-                    let cos_sample: bool = self.integrator_params.find_one_bool("cossample", true);
-                    let n_samples: i32 = self.integrator_params.find_one_int("nsamples", 64 as i32);
-                    let integrator = Box::new(Integrator::Sampler(SamplerIntegrator::AO(
-                        AOIntegrator::new(cos_sample, n_samples, camera, sampler, pixel_bounds),
-                    )));
-                    some_integrator = Some(integrator);
-                } else if self.integrator_name == "sppm" {
-                    // CreateSPPMIntegrator
-                    let mut n_iterations: i32 =
-                        self.integrator_params.find_one_int("numiterations", 64);
-                    n_iterations = self
-                        .integrator_params
-                        .find_one_int("iterations", n_iterations);
-                    let max_depth: i32 = self.integrator_params.find_one_int("maxdepth", 5);
-                    let photons_per_iter: i32 = self
-                        .integrator_params
-                        .find_one_int("photonsperiteration", -1);
-                    let write_freq: i32 = self
-                        .integrator_params
-                        .find_one_int("imagewritefrequency", 1 << 31);
-                    let radius: Float = self
-                        .integrator_params
-                        .find_one_float("radius", 1.0 as Float);
-                    // TODO: if (PbrtOptions.quickRender) nIterations = std::max(1, nIterations / 16);
-                    let integrator = Box::new(Integrator::SPPM(SPPMIntegrator::new(
-                        camera,
-                        n_iterations,
-                        photons_per_iter,
-                        max_depth as u32,
-                        radius,
-                        write_freq,
-                    )));
-                    some_integrator = Some(integrator);
-                } else {
-                    println!("Integrator \"{}\" unknown.", self.integrator_name);
                 }
+                make_integrator(
+                    &self.integrator_name,
+                    &self.integrator_params,
+                    camera,
+                    sampler,
+                )
             } else {
                 panic!("Unable to create sampler.");
             }
         } else {
             panic!("Unable to create camera.");
         }
-        some_integrator
     }
     pub fn make_scene(&self) -> Scene {
         let some_accelerator = make_accelerator(
@@ -521,6 +317,7 @@ impl Default for RenderOptions {
             instances: HashMap::new(),
             current_instance: String::from(""),
             have_scattering_media: false,
+            scene_seed: None,
         }
     }
 }
@@ -634,33 +431,7 @@ fn create_material(api_state: &ApiState, bsdf_state: &mut BsdfState) -> Option<A
         } else if api_state.graphics_state.material == "hair" {
             return Some(HairMaterial::create(&mut mp));
         } else if api_state.graphics_state.material == "mix" {
-            let m1: String = mp.find_string("namedmaterial1", String::from(""));
-            let m2: String = mp.find_string("namedmaterial2", String::from(""));
-            let mat1 = match api_state.graphics_state.named_materials.get(&m1) {
-                Some(named_material) => named_material,
-                None => {
-                    panic!("Material \"{}\" unknown.", m1);
-                }
-            };
-            let mat2 = match api_state.graphics_state.named_materials.get(&m2) {
-                Some(named_material) => named_material,
-                None => {
-                    panic!("Material \"{}\" unknown.", m2);
-                }
-            };
-            let scale: Arc<dyn Texture<Spectrum> + Send + Sync> =
-                mp.get_spectrum_texture("amount", Spectrum::new(0.5));
-            if let Some(m1) = mat1 {
-                if let Some(m2) = mat2 {
-                    let mix = Arc::new(Material::Mix(Box::new(MixMaterial::new(
-                        m1.clone(),
-                        m2.clone(),
-                        scale,
-                    ))));
-                    return Some(mix);
-                }
-            }
-            return None;
+            return MixMaterial::create(&mut mp, &api_state.graphics_state.named_materials);
         } else if api_state.graphics_state.material == "metal" {
             return Some(MetalMaterial::create(&mut mp));
         } else if api_state.graphics_state.material == "substrate" {
@@ -670,7 +441,7 @@ fn create_material(api_state: &ApiState, bsdf_state: &mut BsdfState) -> Option<A
         } else if api_state.graphics_state.material == "subsurface" {
             return Some(SubsurfaceMaterial::create(&mut mp));
         } else if api_state.graphics_state.material == "kdsubsurface" {
-            println!("TODO: CreateKdsubsurfaceMaterial");
+            return Some(KdSubsurfaceMaterial::create(&mut mp));
         } else if api_state.graphics_state.material == "fourier" {
             return Some(FourierMaterial::create(&mut mp, bsdf_state));
         } else if api_state.graphics_state.material == "disney" {
@@ -741,168 +512,81 @@ fn make_light(api_state: &mut ApiState, medium_interface: &MediumInterface) {
             y: p.y,
             z: p.z,
         }) * api_state.cur_transform.t[0];
-        let point_light = Arc::new(Light::Point(Box::new(PointLight::new(
-            &l2w,
-            medium_interface,
-            &(i * sc),
-        ))));
+        let mut point_light: PointLight = PointLight::new(&l2w, medium_interface, &(i * sc));
+        let wavelength_sampling: String = api_state
+            .param_set
+            .find_one_string("wavelengthsampling", String::new());
+        if !wavelength_sampling.is_empty() {
+            point_light.wavelength_sampling = Some(WavelengthSampling::new(&wavelength_sampling));
+        }
+        // optional visible bulb
+        point_light.radius = api_state.param_set.find_one_float("radius", 0.0 as Float);
+        // "group" and "visibility" are only supported by point lights
+        // (see Light::get_group() and Light::get_visibility())
+        point_light.group = api_state.param_set.find_one_int("group", 0).max(0) as usize;
+        // e.g. "string visibility" [ "camera diffuse specular" ] (no shadows)
+        let visibility: String = api_state
+            .param_set
+            .find_one_string("visibility", String::new());
+        if !visibility.is_empty() {
+            point_light.visibility = light_visibility_from_str(&visibility);
+        }
+        let point_light = Arc::new(Light::Point(Box::new(point_light)));
         api_state.render_options.lights.push(point_light);
     } else if api_state.param_set.name == "spot" {
         // CreateSpotLight
-        let i: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("I", Spectrum::new(1.0 as Float));
-        let sc: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("scale", Spectrum::new(1.0 as Float));
-        let coneangle: Float = api_state
-            .param_set
-            .find_one_float("coneangle", 30.0 as Float);
-        let conedelta: Float = api_state
-            .param_set
-            .find_one_float("conedeltaangle", 5.0 as Float);
-        // compute spotlight world to light transformation
-        let from: Point3f = api_state.param_set.find_one_point3f(
-            "from",
-            Point3f {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-            },
-        );
-        let to: Point3f = api_state.param_set.find_one_point3f(
-            "to",
-            Point3f {
-                x: 0.0,
-                y: 0.0,
-                z: 1.0,
-            },
-        );
-        let dir: Vector3f = (to - from).normalize();
-        let mut du: Vector3f = Vector3f::default();
-        let mut dv: Vector3f = Vector3f::default();
-        vec3_coordinate_system(&dir, &mut du, &mut dv);
-        let dir_to_z: Transform = Transform::new(
-            du.x, du.y, du.z, 0.0, dv.x, dv.y, dv.z, 0.0, dir.x, dir.y, dir.z, 0.0, 0.0, 0.0, 0.0,
-            1.0,
-        );
-        let light2world: Transform = api_state.cur_transform.t[0]
-            * Transform::translate(&Vector3f {
-                x: from.x,
-                y: from.y,
-                z: from.z,
-            })
-            * Transform::inverse(&dir_to_z);
-        let spot_light = Arc::new(Light::Spot(Box::new(SpotLight::new(
-            &light2world,
+        // optional gobo pattern (a named float texture)
+        let mut gobo: Option<Arc<dyn Texture<Float> + Send + Sync>> = None;
+        let gobo_name: String = api_state.param_set.find_texture("gobomap");
+        if !gobo_name.is_empty() {
+            match api_state
+                .graphics_state
+                .float_textures
+                .get(gobo_name.as_str())
+            {
+                Some(float_texture) => gobo = Some(float_texture.clone()),
+                None => println!(
+                    "Couldn't find float texture named \"{}\" for parameter \"gobomap\"",
+                    gobo_name
+                ),
+            }
+        }
+        let spot_light: Arc<Light> = SpotLight::create(
+            &api_state.param_set,
+            &api_state.cur_transform.t[0],
             medium_interface,
-            &(i * sc),
-            coneangle,
-            coneangle - conedelta,
-        ))));
+            gobo,
+        );
         api_state.render_options.lights.push(spot_light);
     } else if api_state.param_set.name == "goniometric" {
         // CreateGoniometricLight
-        let i: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("I", Spectrum::new(1.0 as Float));
-        let sc: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("scale", Spectrum::new(1.0 as Float));
-        let texname: String = api_state
-            .param_set
-            .find_one_filename("mapname", String::from(""));
-        let projection_light = Arc::new(Light::GonioPhotometric(Box::new(
-            GonioPhotometricLight::new(
-                &api_state.cur_transform.t[0],
-                medium_interface,
-                &(i * sc),
-                texname,
-            ),
-        )));
-        api_state.render_options.lights.push(projection_light);
+        let gonio_light: Arc<Light> = GonioPhotometricLight::create(
+            &api_state.param_set,
+            &api_state.cur_transform.t[0],
+            medium_interface,
+        );
+        api_state.render_options.lights.push(gonio_light);
     } else if api_state.param_set.name == "projection" {
         // CreateProjectionLight
-        let i: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("I", Spectrum::new(1.0 as Float));
-        let sc: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("scale", Spectrum::new(1.0 as Float));
-        let fov: Float = api_state.param_set.find_one_float("fov", 45.0 as Float);
-        let texname: String = api_state
-            .param_set
-            .find_one_filename("mapname", String::from(""));
-        let projection_light = Arc::new(Light::Projection(Box::new(ProjectionLight::new(
+        let projection_light: Arc<Light> = ProjectionLight::create(
+            &api_state.param_set,
             &api_state.cur_transform.t[0],
             medium_interface,
-            &(i * sc),
-            texname,
-            fov,
-        ))));
+        );
         api_state.render_options.lights.push(projection_light);
     } else if api_state.param_set.name == "distant" {
         // CreateDistantLight
-        let l: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("L", Spectrum::new(1.0 as Float));
-        let sc: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("scale", Spectrum::new(1.0 as Float));
-        let from: Point3f = api_state.param_set.find_one_point3f(
-            "from",
-            Point3f {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-            },
-        );
-        let to: Point3f = api_state.param_set.find_one_point3f(
-            "to",
-            Point3f {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-            },
-        );
-        let dir: Vector3f = from - to;
-        // return std::make_shared<DistantLight>(light2world, L * sc, dir);
-        let distant_light = Arc::new(Light::Distant(Box::new(DistantLight::new(
-            &api_state.cur_transform.t[0],
-            &(l * sc),
-            &dir,
-        ))));
+        let distant_light: Arc<Light> =
+            DistantLight::create(&api_state.param_set, &api_state.cur_transform.t[0]);
         api_state.render_options.lights.push(distant_light);
     } else if api_state.param_set.name == "infinite" || api_state.param_set.name == "exinfinite" {
-        let l: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("L", Spectrum::new(1.0 as Float));
-        let sc: Spectrum = api_state
-            .param_set
-            .find_one_spectrum("scale", Spectrum::new(1.0 as Float));
-        let mut texmap: String = api_state
-            .param_set
-            .find_one_filename("mapname", String::from(""));
-        if texmap != "" {
-            if let Some(ref search_directory) = api_state.search_directory {
-                // texmap = AbsolutePath(ResolveFilename(texmap));
-                let mut path_buf: PathBuf = PathBuf::from("/");
-                path_buf.push(search_directory.as_ref());
-                path_buf.push(texmap);
-                texmap = String::from(path_buf.to_str().unwrap());
-            }
-        }
-        let n_samples: i32 = api_state.param_set.find_one_int("nsamples", 1 as i32);
-        // TODO: if (PbrtOptions.quickRender) nSamples = std::max(1, nSamples / 4);
-
-        // return std::make_shared<InfiniteAreaLight>(light2world, L * sc, nSamples, texmap);
-        let infinte_light = Arc::new(Light::InfiniteArea(Box::new(InfiniteAreaLight::new(
+        // CreateInfiniteLight
+        let infinite_light: Arc<Light> = InfiniteAreaLight::create(
+            &api_state.param_set,
             &api_state.cur_transform.t[0],
-            &(l * sc),
-            n_samples,
-            texmap,
-        ))));
-        api_state.render_options.lights.push(infinte_light);
+            api_state.search_directory.as_deref(),
+        );
+        api_state.render_options.lights.push(infinite_light);
     } else {
         panic!("MakeLight: unknown name {}", api_state.param_set.name);
     }
@@ -913,76 +597,15 @@ fn make_medium(api_state: &mut ApiState) {
     if medium_type == "" {
         panic!("ERROR: No parameter string \"type\" found in MakeNamedMedium");
     }
-    // MakeMedium (api.cpp:685)
-    let sig_a_rgb: [Float; 3] = [0.0011, 0.0024, 0.014];
-    let sig_s_rgb: [Float; 3] = [2.55, 3.21, 3.77];
-    let mut sig_a: Spectrum = Spectrum::from_rgb(&sig_a_rgb);
-    let mut sig_s: Spectrum = Spectrum::from_rgb(&sig_s_rgb);
-    let preset: String = api_state.param_set.find_one_string("preset", String::new());
-    let found: bool = get_medium_scattering_properties(&preset, &mut sig_a, &mut sig_s);
-    if preset != "" && !found {
-        println!(
-            "WARNING: Material preset \"{:?}\" not found.  Using defaults.",
-            preset
-        );
-    }
-    let scale: Float = api_state.param_set.find_one_float("scale", 1.0 as Float);
-    let g: Float = api_state.param_set.find_one_float("g", 0.0 as Float);
-    sig_a = api_state.param_set.find_one_spectrum("sigma_a", sig_a) * scale;
-    sig_s = api_state.param_set.find_one_spectrum("sigma_s", sig_s) * scale;
     let some_medium: Option<Arc<Medium>>;
     if medium_type == "homogeneous" {
-        some_medium = Some(Arc::new(Medium::Homogeneous(HomogeneousMedium::new(
-            &sig_a, &sig_s, g,
+        some_medium = Some(Arc::new(Medium::Homogeneous(HomogeneousMedium::create(
+            &api_state.param_set,
         ))));
     } else if medium_type == "heterogeneous" {
-        let data: Arc<Vec<Float>> = Arc::new(api_state.param_set.find_float("density"));
-        if data.is_empty() {
-            println!("ERROR: No \"density\" values provided for heterogeneous medium?");
-            some_medium = None;
-        } else {
-            let nx: i32 = api_state.param_set.find_one_int("nx", 1_i32);
-            let ny: i32 = api_state.param_set.find_one_int("ny", 1_i32);
-            let nz: i32 = api_state.param_set.find_one_int("nz", 1_i32);
-            let p0: Point3f = api_state.param_set.find_one_point3f(
-                "p0",
-                Point3f {
-                    x: 0.0 as Float,
-                    y: 0.0 as Float,
-                    z: 0.0 as Float,
-                },
-            );
-            let p1: Point3f = api_state.param_set.find_one_point3f(
-                "p1",
-                Point3f {
-                    x: 1.0 as Float,
-                    y: 1.0 as Float,
-                    z: 1.0 as Float,
-                },
-            );
-            if data.len() != (nx * ny * nz) as usize {
-                println!(
-                    "ERROR: GridDensityMedium has {} density values; expected nx*ny*nz = {}",
-                    data.len(),
-                    nx * ny * nz
-                );
-                some_medium = None;
-            } else {
-                let data_2_medium: Transform = Transform::translate(&Vector3f::from(p0))
-                    * Transform::scale(p1.x - p0.x, p1.y - p0.y, p1.z - p0.z);
-                let medium_2_world = api_state.cur_transform.t[0];
-                some_medium = Some(Arc::new(Medium::GridDensity(GridDensityMedium::new(
-                    &sig_a,
-                    &sig_s,
-                    g,
-                    nx,
-                    ny,
-                    nz,
-                    &(medium_2_world * data_2_medium),
-                    data,
-                ))));
-            }
-        }
+        let medium_2_world: Transform = api_state.cur_transform.t[0];
+        some_medium = GridDensityMedium::create(&api_state.param_set, &medium_2_world)
+            .map(|medium| Arc::new(Medium::GridDensity(medium)));
     } else {
         panic!("MakeMedium: unknown name {}", medium_type);
     }
@@ -994,6 +617,53 @@ fn make_medium(api_state: &mut ApiState) {
     }
 }
 
+/// Create the 2D texture mapping selected by the *"mapping"*
+/// parameter (with its scale and offset parameters).
+fn make_texture_mapping_2d(
+    tp: &mut TextureParams,
+    tex_2_world: Transform,
+) -> Box<TextureMapping2D> {
+    let mapping: String = tp.find_string("mapping", String::from("uv"));
+    if mapping == "uv" {
+        let su: Float = tp.find_float("uscale", 1.0);
+        let sv: Float = tp.find_float("vscale", 1.0);
+        let du: Float = tp.find_float("udelta", 0.0);
+        let dv: Float = tp.find_float("vdelta", 0.0);
+        Box::new(TextureMapping2D::UV(UVMapping2D { su, sv, du, dv }))
+    } else if mapping == "spherical" {
+        Box::new(TextureMapping2D::Spherical(SphericalMapping2D::new(
+            tex_2_world,
+        )))
+    } else if mapping == "cylindrical" {
+        Box::new(TextureMapping2D::Cylindrical(CylindricalMapping2D::new(
+            tex_2_world,
+        )))
+    } else if mapping == "planar" {
+        Box::new(TextureMapping2D::Planar(PlanarMapping2D {
+            vs: tp.find_vector3f(
+                "v1",
+                Vector3f {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            ),
+            vt: tp.find_vector3f(
+                "v2",
+                Vector3f {
+                    x: 0.0,
+                    y: 1.0,
+                    z: 0.0,
+                },
+            ),
+            ds: tp.find_float("udelta", 0.0),
+            dt: tp.find_float("vdelta", 0.0),
+        }))
+    } else {
+        panic!("2D texture mapping \"{}\" unknown", mapping);
+    }
+}
+
 fn make_texture(api_state: &mut ApiState) {
     // pbrtTexture (api.cpp:1049)
     let mut geom_params: ParamSet = ParamSet::default();
@@ -1023,185 +693,83 @@ fn make_texture(api_state: &mut ApiState) {
             Arc::make_mut(&mut api_state.graphics_state.float_textures)
                 .insert(api_state.param_set.name.clone(), ct);
         } else if api_state.param_set.tex_name == "scale" {
-            let ft = Arc::new(ScaleTexture::<Float>::new(
-                tp.get_float_texture("tex1", 1.0 as Float),
-                tp.get_float_texture("tex2", 1.0 as Float),
-            ));
+            let ft = Arc::new(ScaleTexture::<Float>::create(&mut tp));
             Arc::make_mut(&mut api_state.graphics_state.float_textures)
                 .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "mix" {
-            let mt = Arc::new(MixTexture::<Float>::new(
-                tp.get_float_texture("tex1", 0.0 as Float),
-                tp.get_float_texture("tex2", 1.0 as Float),
-                tp.get_float_texture("amount", 0.5 as Float),
-            ));
+            let mt = Arc::new(MixTexture::<Float>::create(&mut tp));
             Arc::make_mut(&mut api_state.graphics_state.float_textures)
                 .insert(api_state.param_set.name.clone(), mt);
         } else if api_state.param_set.tex_name == "bilerp" {
-            println!("TODO: CreateBilerpFloatTexture");
+            // CreateBilerpFloatTexture
+            let mapping: Box<TextureMapping2D> =
+                make_texture_mapping_2d(&mut tp, api_state.cur_transform.t[0]);
+            let ft = Arc::new(BilerpTexture::<Float>::create(&mut tp, mapping));
+            Arc::make_mut(&mut api_state.graphics_state.float_textures)
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "imagemap" {
             // CreateImageFloatTexture
-            let map: Option<Box<TextureMapping2D>>;
-            let mapping: String = tp.find_string("mapping", String::from("uv"));
-            if mapping == "uv" {
-                let su: Float = tp.find_float("uscale", 1.0);
-                let sv: Float = tp.find_float("vscale", 1.0);
-                let du: Float = tp.find_float("udelta", 0.0);
-                let dv: Float = tp.find_float("vdelta", 0.0);
-                map = Some(Box::new(TextureMapping2D::UV(UVMapping2D {
-                    su,
-                    sv,
-                    du,
-                    dv,
-                })));
-            } else if mapping == "spherical" {
-                let tex_2_world = api_state.cur_transform.t[0];
-                map = Some(Box::new(TextureMapping2D::Spherical(
-                    SphericalMapping2D::new(tex_2_world),
-                )));
-            } else if mapping == "cylindrical" {
-                let tex_2_world = api_state.cur_transform.t[0];
-                map = Some(Box::new(TextureMapping2D::Cylindrical(
-                    CylindricalMapping2D::new(tex_2_world),
-                )));
-            } else if mapping == "planar" {
-                map = Some(Box::new(TextureMapping2D::Planar(PlanarMapping2D {
-                    vs: tp.find_vector3f(
-                        "v1",
-                        Vector3f {
-                            x: 1.0,
-                            y: 0.0,
-                            z: 0.0,
-                        },
-                    ),
-                    vt: tp.find_vector3f(
-                        "v2",
-                        Vector3f {
-                            x: 0.0,
-                            y: 1.0,
-                            z: 0.0,
-                        },
-                    ),
-                    ds: tp.find_float("udelta", 0.0),
-                    dt: tp.find_float("vdelta", 0.0),
-                })));
-            } else {
-                panic!("2D texture mapping \"{}\" unknown", mapping);
-            }
-            // initialize _ImageTexture_ parameters
-            let max_aniso: Float = tp.find_float("maxanisotropy", 8.0);
-            let do_trilinear: bool = tp.find_bool("trilinear", false);
-            let wrap: String = tp.find_string("wrap", String::from("repeat"));
-            let mut wrap_mode: ImageWrap = ImageWrap::Repeat;
-            if wrap == "black" {
-                wrap_mode = ImageWrap::Black;
-            } else if wrap == "clamp" {
-                wrap_mode = ImageWrap::Clamp;
-            }
-            let scale: Float = tp.find_float("scale", 1.0);
-            let mut filename: String = tp.find_filename("filename", String::new());
-            if let Some(ref search_directory) = api_state.search_directory {
-                // filename = AbsolutePath(ResolveFilename(filename));
-                let mut path_buf: PathBuf = PathBuf::from("/");
-                path_buf.push(search_directory.as_ref());
-                path_buf.push(filename);
-                filename = String::from(path_buf.to_str().unwrap());
-            }
-            // TODO: default depends on:
-            // HasExtension(filename,
-            // ".tga") ||
-            // HasExtension(filename,
-            // ".png"));
-            let gamma: bool = tp.find_bool("gamma", true);
-
-            if let Some(mapping) = map {
-                let ft = Arc::new(ImageTexture::new(
-                    mapping,
-                    filename,
-                    do_trilinear,
-                    max_aniso,
-                    wrap_mode,
-                    scale,
-                    gamma,
-                    convert_to_float,
-                ));
-                Arc::make_mut(&mut api_state.graphics_state.float_textures)
-                    .insert(api_state.param_set.name.clone(), ft);
-            }
+            let mapping: Box<TextureMapping2D> =
+                make_texture_mapping_2d(&mut tp, api_state.cur_transform.t[0]);
+            let ft = Arc::new(ImageTexture::create(
+                &mut tp,
+                mapping,
+                api_state.search_directory.as_deref(),
+                convert_to_float,
+            ));
+            Arc::make_mut(&mut api_state.graphics_state.float_textures)
+                .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "uv" {
             println!("TODO: CreateUVFloatTexture");
         } else if api_state.param_set.tex_name == "checkerboard" {
-            println!("TODO: CreateCheckerboardFloatTexture");
-        } else if api_state.param_set.tex_name == "dots" {
-            // CreateDotsFloatTexture
-            let map: Option<Box<TextureMapping2D>>;
-            let mapping: String = tp.find_string("mapping", String::from("uv"));
-            if mapping == "uv" {
-                let su: Float = tp.find_float("uscale", 1.0);
-                let sv: Float = tp.find_float("vscale", 1.0);
-                let du: Float = tp.find_float("udelta", 0.0);
-                let dv: Float = tp.find_float("vdelta", 0.0);
-                map = Some(Box::new(TextureMapping2D::UV(UVMapping2D {
-                    su,
-                    sv,
-                    du,
-                    dv,
-                })));
-            } else if mapping == "spherical" {
-                let tex_2_world = api_state.cur_transform.t[0];
-                map = Some(Box::new(TextureMapping2D::Spherical(
-                    SphericalMapping2D::new(tex_2_world),
-                )));
-            } else if mapping == "cylindrical" {
-                let tex_2_world = api_state.cur_transform.t[0];
-                map = Some(Box::new(TextureMapping2D::Cylindrical(
-                    CylindricalMapping2D::new(tex_2_world),
-                )));
-            } else if mapping == "planar" {
-                map = Some(Box::new(TextureMapping2D::Planar(PlanarMapping2D {
-                    vs: tp.find_vector3f(
-                        "v1",
-                        Vector3f {
-                            x: 1.0,
-                            y: 0.0,
-                            z: 0.0,
-                        },
-                    ),
-                    vt: tp.find_vector3f(
-                        "v2",
-                        Vector3f {
-                            x: 0.0,
-                            y: 1.0,
-                            z: 0.0,
-                        },
-                    ),
-                    ds: tp.find_float("udelta", 0.0),
-                    dt: tp.find_float("vdelta", 0.0),
-                })));
+            // CreateCheckerboardFloatTexture
+            let dim: i32 = tp.find_int("dimension", 2);
+            let tex1: Arc<dyn Texture<Float> + Send + Sync> =
+                tp.get_float_texture("tex1", 1.0 as Float);
+            let tex2: Arc<dyn Texture<Float> + Send + Sync> =
+                tp.get_float_texture("tex2", 0.0 as Float);
+            if dim != 2 && dim != 3 {
+                println!(
+                    "ERROR: {} dimensional checkerboard texture not supported",
+                    dim
+                );
+            } else if dim == 2 {
+                let mapping: Box<TextureMapping2D> =
+                    make_texture_mapping_2d(&mut tp, api_state.cur_transform.t[0]);
+                let ft = Arc::new(Checkerboard2DTexture::create(&mut tp, mapping, tex1, tex2));
+                Arc::make_mut(&mut api_state.graphics_state.float_textures)
+                    .insert(api_state.param_set.name.clone(), ft);
             } else {
-                panic!("2D texture mapping \"{}\" unknown", mapping);
-            }
-            if let Some(mapping) = map {
-                let dt = Arc::new(DotsTexture::new(
-                    mapping,
-                    tp.get_float_texture("inside", 1.0 as Float),
-                    tp.get_float_texture("outside", 0.0 as Float),
+                // dim == 3
+                let tex_2_world: Transform = Transform {
+                    m: api_state.cur_transform.t[0].m,
+                    m_inv: api_state.cur_transform.t[0].m_inv,
+                };
+                let map: Box<TextureMapping3D> = Box::new(TextureMapping3D::Identity(
+                    IdentityMapping3D::new(tex_2_world),
                 ));
+                let ft = Arc::new(Checkerboard3DTexture::new(map, tex1, tex2));
                 Arc::make_mut(&mut api_state.graphics_state.float_textures)
-                    .insert(api_state.param_set.name.clone(), dt);
+                    .insert(api_state.param_set.name.clone(), ft);
             }
+        } else if api_state.param_set.tex_name == "dots" {
+            // CreateDotsFloatTexture
+            let mapping: Box<TextureMapping2D> =
+                make_texture_mapping_2d(&mut tp, api_state.cur_transform.t[0]);
+            let dt = Arc::new(DotsTexture::new(
+                mapping,
+                tp.get_float_texture("inside", 1.0 as Float),
+                tp.get_float_texture("outside", 0.0 as Float),
+            ));
+            Arc::make_mut(&mut api_state.graphics_state.float_textures)
+                .insert(api_state.param_set.name.clone(), dt);
         } else if api_state.param_set.tex_name == "fbm" {
             // CreateFBmFloatTexture
             let tex_2_world: Transform = Transform {
                 m: api_state.cur_transform.t[0].m,
                 m_inv: api_state.cur_transform.t[0].m_inv,
             };
-            let map: Box<TextureMapping3D> = Box::new(TextureMapping3D::Identity(
-                IdentityMapping3D::new(tex_2_world),
-            ));
-            let octaves: i32 = tp.find_int("octaves", 8_i32);
-            let roughness: Float = tp.find_float("roughness", 0.5 as Float);
-            let ft = Arc::new(FBmTexture::new(map, octaves, roughness));
+            let ft = Arc::new(FBmTexture::create(&tex_2_world, &mut tp));
             Arc::make_mut(&mut api_state.graphics_state.float_textures)
                 .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "wrinkled" {
@@ -1210,12 +778,7 @@ fn make_texture(api_state: &mut ApiState) {
                 m: api_state.cur_transform.t[0].m,
                 m_inv: api_state.cur_transform.t[0].m_inv,
             };
-            let map: Box<TextureMapping3D> = Box::new(TextureMapping3D::Identity(
-                IdentityMapping3D::new(tex_2_world),
-            ));
-            let octaves: i32 = tp.find_int("octaves", 8_i32);
-            let roughness: Float = tp.find_float("roughness", 0.5 as Float);
-            let ft = Arc::new(WrinkledTexture::new(map, octaves, roughness));
+            let ft = Arc::new(WrinkledTexture::create(&tex_2_world, &mut tp));
             Arc::make_mut(&mut api_state.graphics_state.float_textures)
                 .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "marble" {
@@ -1258,252 +821,83 @@ fn make_texture(api_state: &mut ApiState) {
             Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
                 .insert(api_state.param_set.name.clone(), ct);
         } else if api_state.param_set.tex_name == "scale" {
-            let tex1: Arc<dyn Texture<Spectrum> + Send + Sync> =
-                tp.get_spectrum_texture("tex1", Spectrum::new(1.0));
-            let tex2: Arc<dyn Texture<Spectrum> + Send + Sync> =
-                tp.get_spectrum_texture("tex2", Spectrum::new(0.0));
-            let st = Arc::new(ScaleTexture::<Spectrum>::new(tex1, tex2));
+            let st = Arc::new(ScaleTexture::<Spectrum>::create(&mut tp));
             Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
                 .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "mix" {
-            let mt = Arc::new(MixTexture::<Spectrum>::new(
-                tp.get_spectrum_texture("tex1", Spectrum::new(0.0)),
-                tp.get_spectrum_texture("tex2", Spectrum::new(1.0)),
-                tp.get_float_texture("amount", 0.5 as Float),
-            ));
+            let mt = Arc::new(MixTexture::<Spectrum>::create(&mut tp));
             Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
                 .insert(api_state.param_set.name.clone(), mt);
         } else if api_state.param_set.tex_name == "bilerp" {
-            println!("TODO: CreateBilerpSpectrumTexture");
+            // CreateBilerpSpectrumTexture
+            let mapping: Box<TextureMapping2D> =
+                make_texture_mapping_2d(&mut tp, api_state.cur_transform.t[0]);
+            let st = Arc::new(BilerpTexture::<Spectrum>::create(&mut tp, mapping));
+            Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "imagemap" {
             // CreateImageSpectrumTexture
-            let map: Option<Box<TextureMapping2D>>;
-            let mapping: String = tp.find_string("mapping", String::from("uv"));
-            if mapping == "uv" {
-                let su: Float = tp.find_float("uscale", 1.0);
-                let sv: Float = tp.find_float("vscale", 1.0);
-                let du: Float = tp.find_float("udelta", 0.0);
-                let dv: Float = tp.find_float("vdelta", 0.0);
-                map = Some(Box::new(TextureMapping2D::UV(UVMapping2D {
-                    su,
-                    sv,
-                    du,
-                    dv,
-                })));
-            } else if mapping == "spherical" {
-                let tex_2_world = api_state.cur_transform.t[0];
-                map = Some(Box::new(TextureMapping2D::Spherical(
-                    SphericalMapping2D::new(tex_2_world),
-                )));
-            } else if mapping == "cylindrical" {
-                let tex_2_world = api_state.cur_transform.t[0];
-                map = Some(Box::new(TextureMapping2D::Cylindrical(
-                    CylindricalMapping2D::new(tex_2_world),
-                )));
-            } else if mapping == "planar" {
-                map = Some(Box::new(TextureMapping2D::Planar(PlanarMapping2D {
-                    vs: tp.find_vector3f(
-                        "v1",
-                        Vector3f {
-                            x: 1.0,
-                            y: 0.0,
-                            z: 0.0,
-                        },
-                    ),
-                    vt: tp.find_vector3f(
-                        "v2",
-                        Vector3f {
-                            x: 0.0,
-                            y: 1.0,
-                            z: 0.0,
-                        },
-                    ),
-                    ds: tp.find_float("udelta", 0.0),
-                    dt: tp.find_float("vdelta", 0.0),
-                })));
-            } else {
-                panic!("2D texture mapping \"{}\" unknown", mapping);
-            }
-            // initialize _ImageTexture_ parameters
-            let max_aniso: Float = tp.find_float("maxanisotropy", 8.0);
-            let do_trilinear: bool = tp.find_bool("trilinear", false);
-            let wrap: String = tp.find_string("wrap", String::from("repeat"));
-            let mut wrap_mode: ImageWrap = ImageWrap::Repeat;
-            if wrap == "black" {
-                wrap_mode = ImageWrap::Black;
-            } else if wrap == "clamp" {
-                wrap_mode = ImageWrap::Clamp;
-            }
-            let scale: Float = tp.find_float("scale", 1.0);
-            let mut filename: String = tp.find_filename("filename", String::new());
-            if let Some(ref search_directory) = api_state.search_directory {
-                // filename = AbsolutePath(ResolveFilename(filename));
-                let mut path_buf: PathBuf = PathBuf::from("/");
-                path_buf.push(search_directory.as_ref());
-                path_buf.push(filename);
-                filename = String::from(path_buf.to_str().unwrap());
-            }
-            // TODO: default depends on:
-            // HasExtension(filename,
-            // ".tga") ||
-            // HasExtension(filename,
-            // ".png"));
-            let gamma: bool = tp.find_bool("gamma", true);
-
-            if let Some(mapping) = map {
-                let st = Arc::new(ImageTexture::new(
-                    mapping,
-                    filename,
-                    do_trilinear,
-                    max_aniso,
-                    wrap_mode,
-                    scale,
-                    gamma,
-                    convert_to_spectrum,
-                ));
-                Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
-                    .insert(api_state.param_set.name.clone(), st);
-            }
+            let mapping: Box<TextureMapping2D> =
+                make_texture_mapping_2d(&mut tp, api_state.cur_transform.t[0]);
+            let st = Arc::new(ImageTexture::create(
+                &mut tp,
+                mapping,
+                api_state.search_directory.as_deref(),
+                convert_to_spectrum,
+            ));
+            Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
+                .insert(api_state.param_set.name.clone(), st);
         } else if api_state.param_set.tex_name == "uv" {
             println!("TODO: CreateUVSpectrumTexture");
         } else if api_state.param_set.tex_name == "checkerboard" {
             // CreateCheckerboardSpectrumTexture
             let dim: i32 = tp.find_int("dimension", 2);
-            if dim != 2 && dim != 3 {
-                panic!("{} dimensional checkerboard texture not supported", dim);
-            }
             let tex1: Arc<dyn Texture<Spectrum> + Send + Sync> =
                 tp.get_spectrum_texture("tex1", Spectrum::new(1.0));
             let tex2: Arc<dyn Texture<Spectrum> + Send + Sync> =
                 tp.get_spectrum_texture("tex2", Spectrum::new(0.0));
-            if dim == 2 {
-                let map: Option<Box<TextureMapping2D>>;
-                let mapping: String = tp.find_string("mapping", String::from("uv"));
-                if mapping == "uv" {
-                    let su: Float = tp.find_float("uscale", 1.0);
-                    let sv: Float = tp.find_float("vscale", 1.0);
-                    let du: Float = tp.find_float("udelta", 0.0);
-                    let dv: Float = tp.find_float("vdelta", 0.0);
-                    map = Some(Box::new(TextureMapping2D::UV(UVMapping2D {
-                        su,
-                        sv,
-                        du,
-                        dv,
-                    })));
-                } else if mapping == "spherical" {
-                    let tex_2_world = api_state.cur_transform.t[0];
-                    map = Some(Box::new(TextureMapping2D::Spherical(
-                        SphericalMapping2D::new(tex_2_world),
-                    )));
-                } else if mapping == "cylindrical" {
-                    let tex_2_world = api_state.cur_transform.t[0];
-                    map = Some(Box::new(TextureMapping2D::Cylindrical(
-                        CylindricalMapping2D::new(tex_2_world),
-                    )));
-                } else if mapping == "planar" {
-                    map = Some(Box::new(TextureMapping2D::Planar(PlanarMapping2D {
-                        vs: tp.find_vector3f(
-                            "v1",
-                            Vector3f {
-                                x: 1.0,
-                                y: 0.0,
-                                z: 0.0,
-                            },
-                        ),
-                        vt: tp.find_vector3f(
-                            "v2",
-                            Vector3f {
-                                x: 0.0,
-                                y: 1.0,
-                                z: 0.0,
-                            },
-                        ),
-                        ds: tp.find_float("udelta", 0.0),
-                        dt: tp.find_float("vdelta", 0.0),
-                    })));
-                } else {
-                    panic!("2D texture mapping \"{}\" unknown", mapping);
-                }
-                // TODO: aamode
-                if let Some(mapping) = map {
-                    let st = Arc::new(Checkerboard2DTexture::new(mapping, tex1, tex2));
-                    Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
-                        .insert(api_state.param_set.name.clone(), st);
-                }
+            if dim != 2 && dim != 3 {
+                println!(
+                    "ERROR: {} dimensional checkerboard texture not supported",
+                    dim
+                );
+            } else if dim == 2 {
+                let mapping: Box<TextureMapping2D> =
+                    make_texture_mapping_2d(&mut tp, api_state.cur_transform.t[0]);
+                let st = Arc::new(Checkerboard2DTexture::create(&mut tp, mapping, tex1, tex2));
+                Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
+                    .insert(api_state.param_set.name.clone(), st);
             } else {
                 // dim == 3
-                println!("TODO: TextureMapping3D");
+                let tex_2_world: Transform = Transform {
+                    m: api_state.cur_transform.t[0].m,
+                    m_inv: api_state.cur_transform.t[0].m_inv,
+                };
+                let map: Box<TextureMapping3D> = Box::new(TextureMapping3D::Identity(
+                    IdentityMapping3D::new(tex_2_world),
+                ));
+                let st = Arc::new(Checkerboard3DTexture::new(map, tex1, tex2));
+                Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
+                    .insert(api_state.param_set.name.clone(), st);
             }
         } else if api_state.param_set.tex_name == "dots" {
             // CreateDotsSpectrumTexture
-            let map: Option<Box<TextureMapping2D>>;
-            let mapping: String = tp.find_string("mapping", String::from("uv"));
-            if mapping == "uv" {
-                let su: Float = tp.find_float("uscale", 1.0);
-                let sv: Float = tp.find_float("vscale", 1.0);
-                let du: Float = tp.find_float("udelta", 0.0);
-                let dv: Float = tp.find_float("vdelta", 0.0);
-                map = Some(Box::new(TextureMapping2D::UV(UVMapping2D {
-                    su,
-                    sv,
-                    du,
-                    dv,
-                })));
-            } else if mapping == "spherical" {
-                let tex_2_world = api_state.cur_transform.t[0];
-                map = Some(Box::new(TextureMapping2D::Spherical(
-                    SphericalMapping2D::new(tex_2_world),
-                )));
-            } else if mapping == "cylindrical" {
-                let tex_2_world = api_state.cur_transform.t[0];
-                map = Some(Box::new(TextureMapping2D::Cylindrical(
-                    CylindricalMapping2D::new(tex_2_world),
-                )));
-            } else if mapping == "planar" {
-                map = Some(Box::new(TextureMapping2D::Planar(PlanarMapping2D {
-                    vs: tp.find_vector3f(
-                        "v1",
-                        Vector3f {
-                            x: 1.0,
-                            y: 0.0,
-                            z: 0.0,
-                        },
-                    ),
-                    vt: tp.find_vector3f(
-                        "v2",
-                        Vector3f {
-                            x: 0.0,
-                            y: 1.0,
-                            z: 0.0,
-                        },
-                    ),
-                    ds: tp.find_float("udelta", 0.0),
-                    dt: tp.find_float("vdelta", 0.0),
-                })));
-            } else {
-                panic!("2D texture mapping \"{}\" unknown", mapping);
-            }
+            let mapping: Box<TextureMapping2D> =
+                make_texture_mapping_2d(&mut tp, api_state.cur_transform.t[0]);
             let inside: Arc<dyn Texture<Spectrum> + Send + Sync> =
                 tp.get_spectrum_texture("inside", Spectrum::new(1.0));
             let outside: Arc<dyn Texture<Spectrum> + Send + Sync> =
                 tp.get_spectrum_texture("outside", Spectrum::new(0.0));
-            if let Some(mapping) = map {
-                let dt = Arc::new(DotsTexture::new(mapping, inside, outside));
-                Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
-                    .insert(api_state.param_set.name.clone(), dt);
-            }
+            let dt = Arc::new(DotsTexture::new(mapping, inside, outside));
+            Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
+                .insert(api_state.param_set.name.clone(), dt);
         } else if api_state.param_set.tex_name == "fbm" {
             // CreateFBmSpectrumTexture
             let tex_2_world: Transform = Transform {
                 m: api_state.cur_transform.t[0].m,
                 m_inv: api_state.cur_transform.t[0].m_inv,
             };
-            let map: Box<TextureMapping3D> = Box::new(TextureMapping3D::Identity(
-                IdentityMapping3D::new(tex_2_world),
-            ));
-            let octaves: i32 = tp.find_int("octaves", 8_i32);
-            let roughness: Float = tp.find_float("roughness", 0.5 as Float);
-            let ft = Arc::new(FBmTexture::new(map, octaves, roughness));
+            let ft = Arc::new(FBmTexture::create(&tex_2_world, &mut tp));
             Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
                 .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "wrinkled" {
@@ -1512,29 +906,16 @@ fn make_texture(api_state: &mut ApiState) {
                 m: api_state.cur_transform.t[0].m,
                 m_inv: api_state.cur_transform.t[0].m_inv,
             };
-            let map: Box<TextureMapping3D> = Box::new(TextureMapping3D::Identity(
-                IdentityMapping3D::new(tex_2_world),
-            ));
-            let octaves: i32 = tp.find_int("octaves", 8_i32);
-            let roughness: Float = tp.find_float("roughness", 0.5 as Float);
-            let ft = Arc::new(WrinkledTexture::new(map, octaves, roughness));
+            let ft = Arc::new(WrinkledTexture::create(&tex_2_world, &mut tp));
             Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
                 .insert(api_state.param_set.name.clone(), ft);
         } else if api_state.param_set.tex_name == "marble" {
+            // CreateMarbleSpectrumTexture
             let tex_2_world: Transform = Transform {
                 m: api_state.cur_transform.t[0].m,
                 m_inv: api_state.cur_transform.t[0].m_inv,
             };
-            let map: Box<TextureMapping3D> = Box::new(TextureMapping3D::Identity(
-                IdentityMapping3D::new(tex_2_world),
-            ));
-            let octaves: i32 = tp.find_int("octaves", 8_i32);
-            let roughness: Float = tp.find_float("roughness", 0.5 as Float);
-            let scale: Float = tp.find_float("scale", 1.0 as Float);
-            let variation: Float = tp.find_float("variation", 0.2 as Float);
-            let mt = Arc::new(MarbleTexture::new(
-                map, octaves, roughness, scale, variation,
-            ));
+            let mt = Arc::new(MarbleTexture::create(&tex_2_world, &mut tp));
             Arc::make_mut(&mut api_state.graphics_state.spectrum_textures)
                 .insert(api_state.param_set.name.clone(), mt);
         } else if api_state.param_set.tex_name == "windy" {
@@ -1621,7 +1002,7 @@ pub fn make_camera(
         //     );
         //     some_camera = Some(camera);
         // } else {
-        let camera: Arc<Camera> = RealisticCamera::create(
+        some_camera = RealisticCamera::create(
             &camera_params,
             animated_cam_to_world,
             film,
@@ -1629,7 +1010,6 @@ pub fn make_camera(
             // additional parameters:
             None,
         );
-        some_camera = Some(camera);
     // }
     } else if camera_name == "environment" {
         let camera: Arc<Camera> = EnvironmentCamera::create(
@@ -1663,14 +1043,35 @@ pub fn make_sampler(name: &str, param_set: &ParamSet, film: Arc<Film>) -> Option
         // CreateSobolSampler
         let sampler = SobolSampler::create(param_set, &film.get_sample_bounds());
         some_sampler = Some(sampler);
+    } else if name == "sobol_pbrtcompat" {
+        // like "sobol", but guaranteed to match PBRT-v3's sample values
+        let sampler = SobolSampler::create_pbrt_compat(param_set, &film.get_sample_bounds());
+        some_sampler = Some(sampler);
     } else if name == "random" {
         // CreateRandomSampler
         let sampler = RandomSampler::create(param_set);
         some_sampler = Some(sampler);
+    } else if name == "cmj" {
+        // CreateCmjSampler
+        let sampler = CmjSampler::create(param_set);
+        some_sampler = Some(sampler);
     } else if name == "stratified" {
         // CreateStratifiedSampler
         let sampler = StratifiedSampler::create(param_set);
         some_sampler = Some(sampler);
+    } else if name == "block" {
+        // CreateBlockSampler
+        let sampler = BlockSampler::create(param_set);
+        some_sampler = Some(sampler);
+    } else if name == "adaptive" {
+        // wraps another sampler, which provides the maximum sample count
+        let inner_name: String = param_set.find_one_string("sampler", String::from("sobol"));
+        if inner_name == "adaptive" {
+            println!("Adaptive sampler can't wrap another adaptive sampler.");
+        } else if let Some(inner) = make_sampler(&inner_name, param_set, film.clone()) {
+            let sampler = AdaptiveSampler::create(param_set, inner, &film.get_sample_bounds());
+            some_sampler = Some(sampler);
+        }
     } else {
         println!("Sampler \"{}\" unknown.", name);
     }
@@ -1704,6 +1105,95 @@ pub fn make_film(name: &str, param_set: &ParamSet, filter: Box<Filter>) -> Optio
     }
 }
 
+/// Creates the integrator called *name* for the given *camera* and
+/// *sampler* (see **RenderOptions::make_integrator()**).
+pub fn make_integrator(
+    name: &str,
+    params: &ParamSet,
+    camera: Arc<Camera>,
+    sampler: Box<Sampler>,
+) -> Option<Box<Integrator>> {
+    let mut some_integrator: Option<Box<Integrator>> = None;
+    if name == "whitted" {
+        // CreateWhittedIntegrator
+        some_integrator = Some(WhittedIntegrator::create(params, camera, sampler));
+    } else if name == "directlighting" {
+        // CreateDirectLightingIntegrator
+        some_integrator = Some(DirectLightingIntegrator::create(params, camera, sampler));
+    } else if name == "path" {
+        // CreatePathIntegrator
+        some_integrator = Some(PathIntegrator::create(params, camera, sampler));
+    } else if name == "volpath" {
+        // CreateVolPathIntegrator
+        some_integrator = Some(VolPathIntegrator::create(params, camera, sampler));
+    } else if name == "bdpt" {
+        // CreateBDPTIntegrator
+        some_integrator = Some(BDPTIntegrator::create(params, camera, sampler));
+    } else if name == "mlt" {
+        // CreateMLTIntegrator
+        let max_depth: i32 = params.find_one_int("maxdepth", 5);
+        let n_bootstrap: i32 = params.find_one_int("bootstrapsamples", 100_000);
+        let n_chains: i32 = params.find_one_int("chains", 1000);
+        let mutations_per_pixel: i32 = params.find_one_int("mutationsperpixel", 100);
+        let large_step_probability: Float =
+            params.find_one_float("largestepprobability", 0.3 as Float);
+        let sigma: Float = params.find_one_float("sigma", 0.01 as Float);
+        let integrator = Box::new(Integrator::MLT(MLTIntegrator::new(
+            camera,
+            max_depth as u32,
+            n_bootstrap as u32,
+            n_chains as u32,
+            mutations_per_pixel as u32,
+            sigma,
+            large_step_probability,
+        )));
+        some_integrator = Some(integrator);
+    } else if name == "ambientocclusion" {
+        // CreateAOIntegrator
+        some_integrator = Some(AOIntegrator::create(params, camera, sampler));
+    } else if name == "sppm" {
+        // CreateSPPMIntegrator
+        some_integrator = Some(SPPMIntegrator::create(params, camera));
+    } else {
+        println!("Integrator \"{}\" unknown.", name);
+    }
+    // optional importance map for a per-pixel sample budget
+    let importance_map: String = params.find_one_string("importancemap", String::new());
+    if !importance_map.is_empty() {
+        if let Some(ref mut integrator) = some_integrator {
+            if let Integrator::Sampler(ref mut sampler_integrator) = **integrator {
+                match image::open(&importance_map) {
+                    Ok(map) => {
+                        // by default as many samples as without the map
+                        let pixel_bounds: Bounds2i = sampler_integrator.get_pixel_bounds();
+                        let default_budget: u64 = sampler_integrator
+                            .get_sampler()
+                            .get_samples_per_pixel()
+                            .max(0) as u64
+                            * pixel_bounds.area().max(0) as u64;
+                        let mut budget: u64 = default_budget;
+                        if let Some(samples) = params.find_int("samplebudget").first() {
+                            if *samples > 0 {
+                                budget = *samples as u64;
+                            } else {
+                                println!(
+                                    "WARNING: \"samplebudget\" {} not positive. Using {}.",
+                                    samples, default_budget
+                                );
+                            }
+                        }
+                        sampler_integrator.set_importance_map(&map, budget);
+                    }
+                    Err(_) => {
+                        println!("Error reading \"{}\"", importance_map);
+                    }
+                }
+            }
+        }
+    }
+    some_integrator
+}
+
 fn get_shapes_and_materials(
     api_state: &ApiState,
     bsdf_state: &mut BsdfState,
@@ -1756,36 +1246,22 @@ fn get_shapes_and_materials(
         shapes.push(sphere);
         materials.push(mtl);
     } else if api_state.param_set.name == "cylinder" {
-        let radius: Float = api_state.param_set.find_one_float("radius", 1.0);
-        let z_min: Float = api_state.param_set.find_one_float("zmin", -radius);
-        let z_max: Float = api_state.param_set.find_one_float("zmax", radius);
-        let phi_max: Float = api_state.param_set.find_one_float("phimax", 360.0 as Float);
-        let cylinder = Arc::new(Shape::Clndr(Cylinder::new(
-            obj_to_world,
-            world_to_obj,
-            false,
-            radius,
-            z_min,
-            z_max,
-            phi_max,
-        )));
+        let cylinder: Arc<Shape> = Cylinder::create(
+            &obj_to_world,
+            &world_to_obj,
+            api_state.graphics_state.reverse_orientation,
+            &api_state.param_set,
+        );
         let mtl: Option<Arc<Material>> = create_material(&api_state, bsdf_state);
         shapes.push(cylinder);
         materials.push(mtl);
     } else if api_state.param_set.name == "disk" {
-        let height: Float = api_state.param_set.find_one_float("height", 0.0);
-        let radius: Float = api_state.param_set.find_one_float("radius", 1.0);
-        let inner_radius: Float = api_state.param_set.find_one_float("innerradius", 0.0);
-        let phi_max: Float = api_state.param_set.find_one_float("phimax", 360.0);
-        let disk = Arc::new(Shape::Dsk(Disk::new(
-            obj_to_world,
-            world_to_obj,
-            false,
-            height,
-            radius,
-            inner_radius,
-            phi_max,
-        )));
+        let disk: Arc<Shape> = Disk::create(
+            &obj_to_world,
+            &world_to_obj,
+            api_state.graphics_state.reverse_orientation,
+            &api_state.param_set,
+        );
         let mtl: Option<Arc<Material>> = create_material(&api_state, bsdf_state);
         shapes.push(disk);
         materials.push(mtl);
@@ -1800,7 +1276,7 @@ fn get_shapes_and_materials(
         let curve_shapes: Vec<Arc<Shape>> = create_curve_shape(
             &obj_to_world,
             &world_to_obj,
-            false, // reverse_orientation
+            api_state.graphics_state.reverse_orientation,
             &api_state.param_set,
         );
         for shape in curve_shapes {
@@ -1808,103 +1284,16 @@ fn get_shapes_and_materials(
             materials.push(mtl.clone());
         }
     } else if api_state.param_set.name == "trianglemesh" {
-        let vi = api_state.param_set.find_int("indices");
-        let p = api_state.param_set.find_point3f("P");
-        // try "uv" with Point2f
-        let mut uvs = api_state.param_set.find_point2f("uv");
-        if uvs.is_empty() {
-            // try "st" with Point2f
-            uvs = api_state.param_set.find_point2f("st");
-        }
-        if uvs.is_empty() {
-            // try "uv" with float
-            let mut fuv = api_state.param_set.find_float("uv");
-            if fuv.is_empty() {
-                // try "st" with float
-                fuv = api_state.param_set.find_float("st");
-            }
-            if !fuv.is_empty() {
-                // found some float UVs
-                for i in 0..(fuv.len() / 2) {
-                    uvs.push(Point2f {
-                        x: fuv[2 * i],
-                        y: fuv[2 * i + 1],
-                    });
-                }
-            }
-        }
-        if !uvs.is_empty() {
-            // TODO: if (nuvi < npi) {...} else if (nuvi > npi) ...
-            assert!(uvs.len() == p.len());
-        }
-        assert!(!vi.is_empty());
-        assert!(!p.is_empty());
-        let s = api_state.param_set.find_vector3f("S");
-        let mut s_ws: Vec<Vector3f> = Vec::new();
-        if !s.is_empty() {
-            assert!(s.len() == p.len());
-            // transform tangents to world space
-            let n_tangents: usize = s.len();
-            for item in s.iter().take(n_tangents) {
-                s_ws.push(obj_to_world.transform_vector(&item));
-            }
-        }
-        let n = api_state.param_set.find_normal3f("N");
-        let mut n_ws: Vec<Normal3f> = Vec::new();
-        if !n.is_empty() {
-            assert!(n.len() == p.len());
-            // transform normals to world space
-            let n_normals: usize = n.len();
-            for item in n.iter().take(n_normals) {
-                n_ws.push(obj_to_world.transform_normal(&item));
-            }
-        }
-        for item in &vi {
-            if *item as usize >= p.len() {
-                panic!(
-                    "trianglemesh has out of-bounds vertex index {} ({} \"P\" values were given)",
-                    item,
-                    p.len()
-                );
-            }
-        }
-        // TODO: alpha
-        // CreateTriangleMesh
-        // transform mesh vertices to world space
-        let mut p_ws: Vec<Point3f> = Vec::new();
-        let n_vertices: usize = p.len();
-        for item in p.iter().take(n_vertices) {
-            p_ws.push(obj_to_world.transform_point(&item));
-        }
-        // vertex indices are expected as usize, not i32
-        let mut vertex_indices: Vec<u32> = Vec::new();
-        for item in &vi {
-            vertex_indices.push(*item as u32);
-        }
-        let mesh = Arc::new(TriangleMesh::new(
-            obj_to_world,
-            world_to_obj,
-            api_state.graphics_state.reverse_orientation,
-            (vi.len() / 3).try_into().unwrap(), // n_triangles
-            vertex_indices,
-            n_vertices.try_into().unwrap(),
-            p_ws, // in world space
-            s_ws, // in world space
-            n_ws, // in world space
-            uvs,
-            None,
-            None,
-        ));
         let mtl: Option<Arc<Material>> = create_material(&api_state, bsdf_state);
-        for id in 0..mesh.n_triangles {
-            let triangle = Arc::new(Shape::Trngl(Triangle::new(
-                mesh.object_to_world,
-                mesh.world_to_object,
-                mesh.reverse_orientation,
-                mesh.clone(),
-                id.try_into().unwrap(),
-            )));
-            shapes.push(triangle.clone());
+        let tri_shapes: Vec<Arc<Shape>> = create_triangle_mesh(
+            &obj_to_world,
+            &world_to_obj,
+            api_state.graphics_state.reverse_orientation,
+            &api_state.param_set,
+            api_state.graphics_state.float_textures.clone(),
+        );
+        for shape in tri_shapes {
+            shapes.push(shape.clone());
             materials.push(mtl.clone());
         }
     } else if api_state.param_set.name == "plymesh" {
@@ -1913,7 +1302,7 @@ fn get_shapes_and_materials(
             let ply_shapes: Vec<Arc<Shape>> = create_ply_mesh(
                 &obj_to_world,
                 &world_to_obj,
-                false, // reverse_orientation
+                api_state.graphics_state.reverse_orientation,
                 &api_state.param_set,
                 api_state.graphics_state.float_textures.clone(),
                 // additional parameters:
@@ -2256,7 +1645,17 @@ pub fn pbrt_init(number_of_threads: u8) -> (ApiState, BsdfState) {
     (api_state, bsdf_state)
 }
 
-pub fn pbrt_cleanup(api_state: &ApiState) {
+/// Derive the sampler's base seed from the scene description (e.g.
+/// the contents of the scene file), so the same scene always renders
+/// with the same noise and any edit changes it. An explicit *"seed"*
+/// sampler parameter takes precedence.
+pub fn pbrt_scene_seed(api_state: &mut ApiState, scene: &[u8]) -> u64 {
+    let seed: u64 = hash_bytes(scene);
+    api_state.render_options.scene_seed = Some(seed);
+    seed
+}
+
+pub fn pbrt_cleanup(api_state: &ApiState) -> RenderStats {
     // println!("WorldEnd");
     assert!(
         api_state.pushed_graphics_states.is_empty(),
@@ -2271,7 +1670,7 @@ pub fn pbrt_cleanup(api_state: &ApiState) {
     if let Some(mut integrator) = some_integrator {
         let scene = api_state.render_options.make_scene();
         let num_threads: u8 = api_state.number_of_threads;
-        integrator.render(&scene, num_threads);
+        integrator.render(&scene, num_threads)
     } else {
         panic!("Unable to create integrator.");
     }
@@ -2715,37 +2114,12 @@ pub fn pbrt_shape(api_state: &mut ApiState, bsdf_state: &mut BsdfState, params:
             for i in 0..shapes.len() {
                 let shape = &shapes[i];
                 let material = &materials[i];
-                // CreateDiffuseAreaLight
-                let light_to_world: Transform = api_state.cur_transform.t[0];
-                let l: Spectrum = api_state
-                    .graphics_state
-                    .area_light_params
-                    .find_one_spectrum("L", Spectrum::new(1.0));
-                let sc: Spectrum = api_state
-                    .graphics_state
-                    .area_light_params
-                    .find_one_spectrum("scale", Spectrum::new(1.0));
-                let n_samples: i32 = // try "nsamples" first
-                    api_state.graphics_state.area_light_params.find_one_int("nsamples",
-                                                                  1);
-                let n_samples: i32 = // try "samples"next
-                    api_state.graphics_state.area_light_params.find_one_int("samples",
-                                                                  n_samples);
-                let two_sided: bool = api_state
-                    .graphics_state
-                    .area_light_params
-                    .find_one_bool("twosided", false);
-                // TODO: if (PbrtOptions.quickRender) nSamples = std::max(1, nSamples / 4);
-                let l_emit: Spectrum = l * sc;
-                let area_light: Arc<Light> =
-                    Arc::new(Light::DiffuseArea(Box::new(DiffuseAreaLight::new(
-                        &light_to_world,
-                        &mi,
-                        &l_emit,
-                        n_samples,
-                        shape.clone(),
-                        two_sided,
-                    ))));
+                let area_light: Arc<Light> = DiffuseAreaLight::create(
+                    &api_state.graphics_state.area_light_params,
+                    &api_state.cur_transform.t[0],
+                    &mi,
+                    shape.clone(),
+                );
                 area_lights.push(area_light.clone());
                 let geo_prim = Arc::new(Primitive::Geometric(Box::new(GeometricPrimitive::new(
                     shape.clone(),
@@ -2980,11 +2354,11 @@ pub fn pbrt_object_instance(api_state: &mut ApiState, params: ParamSet) {
                 instance_vec.clear();
                 instance_vec.push(accelerator);
             } else if api_state.render_options.accelerator_name == "kdtree" {
-                // println!("TODO: CreateKdTreeAccelerator");
-                // WARNING: Use BVHAccel for now !!!
-                let accelerator: Arc<Primitive> = Arc::new(Primitive::BVH(Box::new(
-                    BVHAccel::new(instance_vec.clone(), 4, SplitMethod::SAH),
-                )));
+                // CreateKdTreeAccelerator
+                let accelerator: Arc<Primitive> = Arc::new(KdTreeAccel::create(
+                    instance_vec.clone(),
+                    &api_state.render_options.accelerator_params,
+                ));
                 instance_vec.clear();
                 instance_vec.push(accelerator);
             } else {
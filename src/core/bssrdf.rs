@@ -16,7 +16,7 @@ use crate::core::geometry::{
 use crate::core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f, XYZEnum};
 use crate::core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
 use crate::core::interpolation::{
-    catmull_rom_weights, integrate_catmull_rom, sample_catmull_rom_2d,
+    catmull_rom_weights, integrate_catmull_rom, invert_catmull_rom, sample_catmull_rom_2d,
 };
 use crate::core::material::{Material, TransportMode};
 use crate::core::medium::phase_hg;
@@ -25,6 +25,7 @@ use crate::core::pbrt::INV_4_PI;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::reflection::{cos_theta, fr_dielectric};
 use crate::core::reflection::{Bsdf, Bxdf, BxdfType};
+use crate::core::rng::FLOAT_ONE_MINUS_EPSILON;
 use crate::core::scene::Scene;
 use crate::core::spectrum::RGBEnum;
 
@@ -146,29 +147,29 @@ impl TabulatedBssrdf {
         pdf: &mut Float,
     ) -> Spectrum {
         // ProfilePhase pp(Prof::BSSRDFEvaluation);
-        let mut u1: Float = u1; // shadowing input parameter
-
         // choose projection axis for BSSRDF sampling
+        let (axis, mut u1, _axis_pdf) = probe_axis(u1);
         let vx: Vector3f;
         let vy: Vector3f;
         let vz: Vector3f;
-        if u1 < 0.5 as Float {
-            vx = self.ss;
-            vy = self.ts;
-            vz = Vector3f::from(self.ns);
-            u1 *= 2.0 as Float;
-        } else if u1 < 0.75 as Float {
-            // prepare for sampling rays with respect to _self.ss_
-            vx = self.ts;
-            vy = Vector3f::from(self.ns);
-            vz = self.ss;
-            u1 = (u1 - 0.5 as Float) * 4.0 as Float;
-        } else {
-            // prepare for sampling rays with respect to _self.ts_
-            vx = Vector3f::from(self.ns);
-            vy = self.ss;
-            vz = self.ts;
-            u1 = (u1 - 0.75 as Float) * 4.0 as Float;
+        match axis {
+            XYZEnum::Z => {
+                vx = self.ss;
+                vy = self.ts;
+                vz = Vector3f::from(self.ns);
+            }
+            XYZEnum::X => {
+                // prepare for sampling rays with respect to _self.ss_
+                vx = self.ts;
+                vy = Vector3f::from(self.ns);
+                vz = self.ss;
+            }
+            XYZEnum::Y => {
+                // prepare for sampling rays with respect to _self.ts_
+                vx = Vector3f::from(self.ns);
+                vy = self.ss;
+                vz = self.ts;
+            }
         }
         // choose spectral channel for BSSRDF sampling
         let ch: u8 = clamp_t((u1 * 3.0 as Float) as u8, 0_u8, 2_u8);
@@ -528,6 +529,49 @@ impl Clone for SeparableBssrdfAdapter {
     }
 }
 
+/// Choose the projection axis for probe ray sampling (in local
+/// shading coordinates): the normal (*Z*) with probability 1/2, the
+/// two tangents (*X*, *Y*) with probability 1/4 each. Returns the
+/// axis, the remapped sample in *[0, 1)* and the axis probability.
+pub fn probe_axis(u: Float) -> (XYZEnum, Float, Float) {
+    if u < 0.5 as Float {
+        (XYZEnum::Z, u * 2.0 as Float, 0.5 as Float)
+    } else if u < 0.75 as Float {
+        (XYZEnum::X, (u - 0.5) * 4.0 as Float, 0.25 as Float)
+    } else {
+        (XYZEnum::Y, (u - 0.75) * 4.0 as Float, 0.25 as Float)
+    }
+}
+
+/// Sample a radius from a normalized diffusion profile (see
+/// Christensen and Burley 2015) with mean free path *1 / sigma_t*.
+/// Its radial pdf is a mix of two exponentials, one with weight 1/4
+/// and scale *d*, one with weight 3/4 and scale *3 d*.
+pub fn sample_sr(sigma_t: Float, u: Float) -> Float {
+    if sigma_t == 0.0 as Float {
+        return -1.0 as Float;
+    }
+    let d: Float = 1.0 as Float / sigma_t;
+    let u: Float = u.min(FLOAT_ONE_MINUS_EPSILON);
+    if u < 0.25 as Float {
+        let u: Float = u * 4.0 as Float;
+        -d * (1.0 as Float - u).ln()
+    } else {
+        let u: Float = (u - 0.25 as Float) / 0.75 as Float;
+        -3.0 as Float * d * (1.0 as Float - u).ln()
+    }
+}
+
+/// Radial pdf of *sample_sr()* (per unit radius, i.e. including the
+/// *2 pi r* factor of polar coordinates).
+pub fn pdf_sr(sigma_t: Float, r: Float) -> Float {
+    if sigma_t == 0.0 as Float || r < 0.0 as Float {
+        return 0.0 as Float;
+    }
+    let d: Float = 1.0 as Float / sigma_t;
+    ((-r / d).exp() + (-r / (3.0 as Float * d)).exp()) / (4.0 as Float * d)
+}
+
 pub fn fresnel_moment1(eta: Float) -> Float {
     let eta2: Float = eta * eta;
     let eta3: Float = eta2 * eta;
@@ -683,3 +727,21 @@ pub fn compute_beam_diffusion_bssrdf(g: Float, eta: Float, t: &mut BssrdfTable)
     }
     // }, t.n_rho_samples);
 }
+
+/// Finds the scattering coefficients *sigma_a* and *sigma_s* which
+/// produce the (multiple scattering) diffuse reflectance *rho_eff*
+/// for a medium with the mean free path *mfp*, using the tabulated
+/// effective albedos of *t*.
+pub fn subsurface_from_diffuse(
+    t: &BssrdfTable,
+    rho_eff: &Spectrum,
+    mfp: &Spectrum,
+    sigma_a: &mut Spectrum,
+    sigma_s: &mut Spectrum,
+) {
+    for c in RGBEnum::iter() {
+        let rho: Float = invert_catmull_rom(&t.rho_samples, &t.rho_eff, rho_eff[c]);
+        sigma_s[c] = rho / mfp[c];
+        sigma_a[c] = (1.0 as Float - rho) / mfp[c];
+    }
+}
@@ -0,0 +1,186 @@
+//! Programmatic scene construction. Instead of writing a *.pbrt* file
+//! a **SceneBuilder** collects the camera, lights, primitives,
+//! sampler and integrator and builds a **RenderJob** from them.
+//!
+//! ```rust,no_run
+//! # use std::sync::Arc;
+//! # use pbrt::core::api::{make_camera, make_film, make_filter};
+//! # use pbrt::core::builder::SceneBuilder;
+//! # use pbrt::core::light::Light;
+//! # use pbrt::core::medium::MediumInterface;
+//! # use pbrt::core::paramset::ParamSet;
+//! # use pbrt::core::pbrt::Spectrum;
+//! # use pbrt::core::transform::{AnimatedTransform, Transform};
+//! # use pbrt::lights::point::PointLight;
+//! # use pbrt::samplers::stratified::StratifiedSampler;
+//! let filter = make_filter("box", &ParamSet::default()).unwrap();
+//! let film = make_film("image", &ParamSet::default(), filter).unwrap();
+//! let camera_to_world: Transform = Transform::default();
+//! let camera = make_camera(
+//!     "perspective",
+//!     &ParamSet::default(),
+//!     AnimatedTransform::new(&camera_to_world, 0.0, &camera_to_world, 1.0),
+//!     film,
+//! )
+//! .unwrap();
+//! let light: PointLight = PointLight::new(
+//!     &Transform::default(),
+//!     &MediumInterface::default(),
+//!     &Spectrum::new(10.0),
+//! );
+//! let mut job = SceneBuilder::new()
+//!     .camera(camera)
+//!     .add_light(Light::Point(Box::new(light)))
+//!     .sampler(StratifiedSampler::create(&ParamSet::default()))
+//!     .integrator("path", ParamSet::default())
+//!     .build()
+//!     .unwrap();
+//! let stats = job.render();
+//! ```
+
+// std
+use std::sync::Arc;
+// pbrt
+use crate::core::api::{make_accelerator, make_integrator, make_sampler};
+use crate::core::camera::Camera;
+use crate::core::film::Film;
+use crate::core::integrator::{Integrator, RenderStats};
+use crate::core::light::Light;
+use crate::core::paramset::ParamSet;
+use crate::core::primitive::Primitive;
+use crate::core::sampler::{Sampler, SamplerTrait};
+use crate::core::scene::Scene;
+
+/// Collects everything needed for rendering. Defaults match the
+/// scene file defaults: a *halton* sampler, the *path* integrator
+/// and a *bvh* accelerator, rendering with all available cores.
+pub struct SceneBuilder {
+    pub camera: Option<Arc<Camera>>,
+    pub lights: Vec<Arc<Light>>,
+    pub primitives: Vec<Arc<Primitive>>,
+    pub sampler: Option<Box<Sampler>>,
+    pub integrator_name: String,
+    pub integrator_params: ParamSet,
+    pub accelerator_name: String,
+    pub accelerator_params: ParamSet,
+    pub num_threads: u8,
+}
+
+impl Default for SceneBuilder {
+    fn default() -> Self {
+        SceneBuilder {
+            camera: None,
+            lights: Vec::new(),
+            primitives: Vec::new(),
+            sampler: None,
+            integrator_name: String::from("path"),
+            integrator_params: ParamSet::default(),
+            accelerator_name: String::from("bvh"),
+            accelerator_params: ParamSet::default(),
+            num_threads: 0_u8,
+        }
+    }
+}
+
+impl SceneBuilder {
+    pub fn new() -> Self {
+        SceneBuilder::default()
+    }
+    /// The camera (and its film) used for rendering.
+    pub fn camera(mut self, camera: Arc<Camera>) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+    pub fn add_light(mut self, light: Light) -> Self {
+        self.lights.push(Arc::new(light));
+        self
+    }
+    pub fn add_primitive(mut self, primitive: Arc<Primitive>) -> Self {
+        self.primitives.push(primitive);
+        self
+    }
+    pub fn sampler(mut self, sampler: Box<Sampler>) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+    /// A user-defined sampler (see **SamplerTrait**) instead of a
+    /// built-in one.
+    pub fn shared_sampler(self, sampler: Arc<dyn SamplerTrait + Send + Sync>) -> Self {
+        self.sampler(Sampler::from_shared(sampler))
+    }
+    /// Selects an integrator by *name*, using the same names and
+    /// parameters as the *Integrator* statement of a scene file.
+    pub fn integrator(mut self, name: &str, params: ParamSet) -> Self {
+        self.integrator_name = name.to_string();
+        self.integrator_params = params;
+        self
+    }
+    /// Selects an accelerator (*bvh* or *kdtree*) by *name*.
+    pub fn accelerator(mut self, name: &str, params: ParamSet) -> Self {
+        self.accelerator_name = name.to_string();
+        self.accelerator_params = params;
+        self
+    }
+    /// Number of render threads (*0* uses all available cores).
+    pub fn threads(mut self, num_threads: u8) -> Self {
+        self.num_threads = num_threads;
+        self
+    }
+    /// Creates the scene and the integrator. Returns *None* (after
+    /// printing the reason) if no camera was set or the integrator
+    /// or accelerator is unknown.
+    pub fn build(self) -> Option<RenderJob> {
+        let camera: Arc<Camera> = match self.camera {
+            Some(camera) => camera,
+            None => {
+                println!("SceneBuilder: no camera specified.");
+                return None;
+            }
+        };
+        let film: Arc<Film> = camera.get_film();
+        let sampler: Box<Sampler> = match self.sampler {
+            Some(sampler) => sampler,
+            None => make_sampler("halton", &ParamSet::default(), film.clone())?,
+        };
+        let aggregate: Arc<Primitive> = match make_accelerator(
+            &self.accelerator_name,
+            &self.primitives,
+            &self.accelerator_params,
+        ) {
+            Some(aggregate) => aggregate,
+            None => {
+                println!("Accelerator \"{}\" unknown.", self.accelerator_name);
+                return None;
+            }
+        };
+        let integrator: Box<Integrator> = make_integrator(
+            &self.integrator_name,
+            &self.integrator_params,
+            camera,
+            sampler,
+        )?;
+        Some(RenderJob {
+            scene: Scene::new(aggregate, self.lights),
+            integrator,
+            film,
+            num_threads: self.num_threads,
+        })
+    }
+}
+
+/// A scene ready for rendering, created by **SceneBuilder::build()**.
+pub struct RenderJob {
+    pub scene: Scene,
+    pub integrator: Box<Integrator>,
+    /// the camera's film, holding the image after rendering
+    pub film: Arc<Film>,
+    pub num_threads: u8,
+}
+
+impl RenderJob {
+    /// Renders the scene and writes the image (like the scene file
+    /// based renderer does).
+    pub fn render(&mut self) -> RenderStats {
+        self.integrator.render(&self.scene, self.num_threads)
+    }
+}
@@ -25,6 +25,14 @@ pub enum Camera {
 }
 
 impl Camera {
+    pub fn generate_ray(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
+        match self {
+            Camera::Environment(camera) => camera.generate_ray(sample, ray),
+            Camera::Orthographic(camera) => camera.generate_ray(sample, ray),
+            Camera::Perspective(camera) => camera.generate_ray(sample, ray),
+            Camera::Realistic(camera) => camera.generate_ray(sample, ray),
+        }
+    }
     pub fn generate_ray_differential(&self, sample: &CameraSample, ray: &mut Ray) -> Float {
         match self {
             Camera::Environment(camera) => camera.generate_ray_differential(sample, ray),
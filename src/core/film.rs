@@ -10,28 +10,161 @@
 //!
 
 // std
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
 use std::ops::{DerefMut, Index};
 use std::path::Path;
-use std::sync::{Arc, RwLock, RwLockWriteGuard};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockWriteGuard};
 
 // others
-#[cfg(feature = "openexr")]
-use openexr::{FrameBuffer, Header, PixelType, ScanlineOutputFile};
 use smallvec::SmallVec;
 // pbrt
 use crate::core::filter::Filter;
 use crate::core::geometry::{
     bnd2_intersect_bnd2, pnt2_ceil, pnt2_floor, pnt2_inside_exclusive, pnt2_max_pnt2, pnt2_min_pnt2,
 };
-use crate::core::geometry::{Bounds2f, Bounds2i, Point2f, Point2i, Vector2f};
+use crate::core::geometry::{Bounds2f, Bounds2i, Normal3f, Point2f, Point2i, Vector2f};
 use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{clamp_t, gamma_correct};
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::spectrum::xyz_to_rgb;
+use crate::core::spectrum::{blackbody, CIE_LAMBDA, N_CIE_SAMPLES};
 
 // see film.h
 
+/// Does *filename* have the extension of an OpenEXR file (*.exr*)?
+pub fn is_exr_filename(filename: &str) -> bool {
+    match Path::new(filename).extension() {
+        Some(extension) => extension.to_string_lossy().eq_ignore_ascii_case("exr"),
+        None => false,
+    }
+}
+
+/// Writes a single-part, uncompressed scanline OpenEXR image with
+/// 32-bit float channels to *out*. Each of the *channels* (a name
+/// and the values of all pixels in *data_window*, row by row) is
+/// stored under its name, *display_window* is the full image (both
+/// windows exclude their maximum like all **Bounds2i**).
+pub fn write_exr(
+    out: &mut dyn Write,
+    data_window: &Bounds2i,
+    display_window: &Bounds2i,
+    channels: &[(String, Vec<Float>)],
+) -> std::io::Result<()> {
+    let width: usize = (data_window.p_max.x - data_window.p_min.x).max(0) as usize;
+    let height: usize = (data_window.p_max.y - data_window.p_min.y).max(0) as usize;
+    for (name, values) in channels {
+        if values.len() != width * height {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "channel {:?} has {} values, expected {}",
+                    name,
+                    values.len(),
+                    width * height
+                ),
+            ));
+        }
+    }
+    // the channel list has to be sorted by name
+    let mut sorted: Vec<&(String, Vec<Float>)> = channels.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    // header attributes: name, type, size and value
+    let mut header: Vec<u8> = Vec::new();
+    let mut attribute = |name: &str, type_name: &str, value: &[u8]| {
+        header.extend_from_slice(name.as_bytes());
+        header.push(0_u8);
+        header.extend_from_slice(type_name.as_bytes());
+        header.push(0_u8);
+        header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+        header.extend_from_slice(value);
+    };
+    let mut chlist: Vec<u8> = Vec::new();
+    for (name, _values) in sorted.iter() {
+        chlist.extend_from_slice(name.as_bytes());
+        chlist.push(0_u8);
+        // pixel type FLOAT, not linear (plus 3 reserved bytes),
+        // sampled at every pixel
+        chlist.extend_from_slice(&2_i32.to_le_bytes());
+        chlist.extend_from_slice(&[0_u8; 4]);
+        chlist.extend_from_slice(&1_i32.to_le_bytes());
+        chlist.extend_from_slice(&1_i32.to_le_bytes());
+    }
+    chlist.push(0_u8);
+    let box2i = |bounds: &Bounds2i| -> Vec<u8> {
+        [
+            bounds.p_min.x,
+            bounds.p_min.y,
+            bounds.p_max.x - 1,
+            bounds.p_max.y - 1,
+        ]
+        .iter()
+        .flat_map(|v| v.to_le_bytes().to_vec())
+        .collect()
+    };
+    attribute("channels", "chlist", &chlist);
+    // NO_COMPRESSION
+    attribute("compression", "compression", &[0_u8]);
+    attribute("dataWindow", "box2i", &box2i(data_window));
+    attribute("displayWindow", "box2i", &box2i(display_window));
+    // INCREASING_Y
+    attribute("lineOrder", "lineOrder", &[0_u8]);
+    attribute("pixelAspectRatio", "float", &1.0_f32.to_le_bytes());
+    attribute("screenWindowCenter", "v2f", &[0_u8; 8]);
+    attribute("screenWindowWidth", "float", &1.0_f32.to_le_bytes());
+    header.push(0_u8);
+    // magic number and version 2 (single-part scanline file)
+    out.write_all(&20_000_630_i32.to_le_bytes())?;
+    out.write_all(&2_i32.to_le_bytes())?;
+    out.write_all(&header)?;
+    // offset table: one chunk per scanline, each storing its y
+    // coordinate, the data size and the channels one after another
+    let line_size: usize = 4 * width * sorted.len();
+    let chunk_size: u64 = 8 + line_size as u64;
+    let table_start: u64 = 8 + header.len() as u64;
+    let chunks_start: u64 = table_start + 8 * height as u64;
+    for y in 0..height {
+        out.write_all(&(chunks_start + y as u64 * chunk_size).to_le_bytes())?;
+    }
+    for y in 0..height {
+        out.write_all(&(data_window.p_min.y + y as i32).to_le_bytes())?;
+        out.write_all(&(line_size as i32).to_le_bytes())?;
+        for (_name, values) in sorted.iter() {
+            for v in &values[y * width..(y + 1) * width] {
+                out.write_all(&v.to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
 const FILTER_TABLE_WIDTH: usize = 16;
+/// White balance temperature (in Kelvin) which leaves colors unchanged
+const WHITE_BALANCE_REFERENCE_K: Float = 6500.0;
+/// Side length of the dither tile
+const BLUE_NOISE_SIZE: usize = 16;
+/// Blue-noise ranks (void-and-cluster) of a tileable 16x16 pattern,
+/// used to dither 8-bit output
+#[rustfmt::skip]
+const BLUE_NOISE_TILE: [u8; BLUE_NOISE_SIZE * BLUE_NOISE_SIZE] = [
+    234, 50, 188, 19, 58, 171, 121, 47, 163, 2, 247, 104, 22, 132, 14, 65,
+    209, 8, 118, 97, 240, 205, 23, 228, 138, 64, 123, 170, 72, 224, 99, 149,
+    85, 139, 229, 165, 78, 146, 111, 84, 176, 216, 30, 231, 153, 201, 42, 180,
+    25, 62, 195, 29, 43, 185, 7, 249, 41, 100, 191, 48, 87, 5, 128, 243,
+    221, 152, 101, 253, 130, 220, 59, 200, 156, 12, 136, 112, 255, 174, 69, 109,
+    46, 189, 3, 73, 172, 90, 142, 116, 80, 237, 210, 61, 147, 33, 206, 160,
+    81, 124, 217, 113, 208, 15, 241, 27, 168, 45, 178, 20, 193, 96, 225, 18,
+    242, 164, 60, 35, 157, 53, 181, 68, 223, 105, 125, 83, 236, 131, 55, 141,
+    197, 10, 227, 134, 246, 95, 126, 198, 148, 1, 244, 161, 71, 9, 182, 106,
+    40, 93, 179, 75, 192, 6, 218, 36, 91, 57, 202, 34, 215, 155, 233, 74,
+    252, 120, 150, 24, 110, 63, 166, 119, 232, 183, 133, 103, 49, 117, 31, 167,
+    16, 212, 51, 238, 207, 137, 254, 21, 76, 151, 13, 250, 190, 88, 203, 135,
+    102, 184, 82, 169, 38, 89, 187, 52, 204, 98, 173, 67, 129, 4, 222, 56,
+    230, 144, 0, 127, 226, 11, 154, 114, 239, 39, 219, 28, 235, 145, 175, 77,
+    196, 37, 248, 70, 107, 199, 66, 177, 17, 143, 115, 159, 86, 44, 108, 26,
+    122, 92, 158, 214, 140, 32, 245, 94, 213, 79, 194, 54, 211, 186, 251, 162,
+];
 
 #[derive(Debug, Clone)]
 pub struct Pixel {
@@ -65,6 +198,8 @@ pub struct FilmTile<'a> {
     filter_table: &'a [Float; FILTER_TABLE_WIDTH * FILTER_TABLE_WIDTH],
     filter_table_size: usize,
     pixels: Vec<FilmTilePixel>,
+    /// Contributions per light group (and pixel)
+    group_contribs: Vec<Vec<Spectrum>>,
     max_sample_luminance: Float,
 }
 
@@ -75,6 +210,7 @@ impl<'a> FilmTile<'a> {
         filter_table: &'a [Float; FILTER_TABLE_WIDTH * FILTER_TABLE_WIDTH],
         filter_table_size: usize,
         max_sample_luminance: Float,
+        n_light_groups: usize,
     ) -> Self {
         FilmTile {
             pixel_bounds,
@@ -87,13 +223,33 @@ impl<'a> FilmTile<'a> {
             filter_table_size,
             // TODO: pixels = std::vector<FilmTilePixel>(std::max(0, pixelBounds.Area()));
             pixels: vec![FilmTilePixel::default(); pixel_bounds.area() as usize],
+            group_contribs: vec![
+                vec![Spectrum::default(); pixel_bounds.area() as usize];
+                n_light_groups
+            ],
             max_sample_luminance,
         }
     }
     pub fn add_sample(&mut self, p_film: Point2f, l: &mut Spectrum, sample_weight: Float) {
+        self.add_sample_groups(p_film, l, &[], sample_weight);
+    }
+    /// Like *add_sample()*, but with the radiance *l* split into light
+    /// groups, see **Film::get_rgb_group()**. Without *groups* all of
+    /// the radiance goes into light group 0.
+    pub fn add_sample_groups(
+        &mut self,
+        p_film: Point2f,
+        l: &mut Spectrum,
+        groups: &[Spectrum],
+        sample_weight: Float,
+    ) {
         // TODO: ProfilePhase _(Prof::AddFilmSample);
-        if l.y() > self.max_sample_luminance {
-            *l *= Spectrum::new(self.max_sample_luminance / l.y());
+        // negative radiance (e.g. from negative lights) is accumulated
+        // as is, so clamp the magnitude only
+        let mut luminance_scale: Float = 1.0 as Float;
+        if l.y().abs() > self.max_sample_luminance {
+            luminance_scale = self.max_sample_luminance / l.y().abs();
+            *l *= Spectrum::new(luminance_scale);
         }
         // compute sample's raster bounds
         let p_film_discrete: Point2f = p_film - Vector2f { x: 0.5, y: 0.5 };
@@ -143,6 +299,16 @@ impl<'a> FilmTile<'a> {
                 pixel.contrib_sum +=
                     *l * Spectrum::new(sample_weight) * Spectrum::new(filter_weight);
                 pixel.filter_weight_sum += filter_weight;
+                if groups.is_empty() {
+                    if let Some(contribs) = self.group_contribs.first_mut() {
+                        contribs[idx] += *l * Spectrum::new(sample_weight * filter_weight);
+                    }
+                } else {
+                    let weight: Float = luminance_scale * sample_weight * filter_weight;
+                    for (contribs, l_group) in self.group_contribs.iter_mut().zip(groups) {
+                        contribs[idx] += *l_group * Spectrum::new(weight);
+                    }
+                }
             }
         }
     }
@@ -153,6 +319,117 @@ impl<'a> FilmTile<'a> {
     }
 }
 
+/// Raw data of a single camera sample, see **SampleRecorder**.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SampleRecord {
+    /// Film position (in raster space)
+    pub p_film: Point2f,
+    /// Radiance (before filtering)
+    pub l: Spectrum,
+    /// Albedo estimate of the first surface hit
+    pub albedo: Spectrum,
+    /// Shading normal of the first surface hit
+    pub normal: Normal3f,
+}
+
+impl SampleRecord {
+    /// Number of bytes of a record in the binary stream
+    pub const SIZE: usize = 11 * 4;
+    /// Writes the record as 11 little-endian 32-bit floats: *p_film*
+    /// (x, y), *l* (r, g, b), *albedo* (r, g, b) and *normal* (x, y,
+    /// z).
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        let values: [Float; 11] = [
+            self.p_film.x,
+            self.p_film.y,
+            self.l.c[0],
+            self.l.c[1],
+            self.l.c[2],
+            self.albedo.c[0],
+            self.albedo.c[1],
+            self.albedo.c[2],
+            self.normal.x,
+            self.normal.y,
+            self.normal.z,
+        ];
+        for v in values.iter() {
+            out.write_all(&v.to_le_bytes())?;
+        }
+        Ok(())
+    }
+    /// Reads the next record written by *write_to()*, returns **None**
+    /// at the end of the stream.
+    pub fn read_from(input: &mut dyn Read) -> std::io::Result<Option<SampleRecord>> {
+        let mut bytes: [u8; SampleRecord::SIZE] = [0_u8; SampleRecord::SIZE];
+        match input.read_exact(&mut bytes) {
+            Ok(()) => {}
+            Err(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let mut values: [Float; 11] = [0.0 as Float; 11];
+        for (v, chunk) in values.iter_mut().zip(bytes.chunks(4)) {
+            *v = Float::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        Ok(Some(SampleRecord {
+            p_film: Point2f {
+                x: values[0],
+                y: values[1],
+            },
+            l: Spectrum::rgb(values[2], values[3], values[4]),
+            albedo: Spectrum::rgb(values[5], values[6], values[7]),
+            normal: Normal3f {
+                x: values[8],
+                y: values[9],
+                z: values[10],
+            },
+        }))
+    }
+}
+
+/// Writes every camera sample (see **SampleRecord**) to a binary
+/// stream, e.g. as training data for learned reconstruction or
+/// denoising. Samples are still added to the film as well.
+pub struct SampleRecorder {
+    writer: Mutex<BufWriter<Box<dyn Write + Send>>>,
+    count: AtomicU64,
+}
+
+impl SampleRecorder {
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        SampleRecorder {
+            writer: Mutex::new(BufWriter::new(out)),
+            count: AtomicU64::new(0),
+        }
+    }
+    pub fn create(filename: &str) -> std::io::Result<Self> {
+        let file: File = File::create(filename)?;
+        Ok(SampleRecorder::new(Box::new(file)))
+    }
+    /// Appends *records* (e.g. all samples of an image tile) to the
+    /// stream.
+    pub fn record(&self, records: &[SampleRecord]) {
+        let mut writer = self.writer.lock().unwrap();
+        for record in records {
+            record
+                .write_to(&mut *writer)
+                .unwrap_or_else(|e| panic!("Failed to record sample: {}", e));
+        }
+        self.count
+            .fetch_add(records.len() as u64, Ordering::Relaxed);
+    }
+    /// Number of samples recorded so far
+    pub fn get_count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+    pub fn flush(&self) {
+        self.writer
+            .lock()
+            .unwrap()
+            .flush()
+            .unwrap_or_else(|e| panic!("Failed to flush recorded samples: {}", e));
+    }
+}
+
 pub struct Film {
     // Film Public Data
     /// The overall resolution of the image in pixels
@@ -165,12 +442,23 @@ pub struct Film {
     pub filename: String,
     /// A crop window that may specify a subset of the image to render
     pub cropped_pixel_bounds: Bounds2i,
+    /// Dither 8-bit output (with a blue-noise tile) to avoid banding
+    pub dither: bool,
+    /// Number of light groups with separate output images (0 disables
+    /// light groups), see *set_light_groups()*
+    pub light_groups: usize,
+    /// Optional raw output of all camera samples (film parameter
+    /// *"recordsamples"* with the filename)
+    pub sample_recorder: Option<SampleRecorder>,
 
     // Film Private Data
     pub pixels: RwLock<Vec<Pixel>>,
+    group_xyz: RwLock<Vec<Vec<[Float; 3]>>>,
     filter_table: [Float; FILTER_TABLE_WIDTH * FILTER_TABLE_WIDTH],
     scale: Float,
     max_sample_luminance: Float,
+    exposure_scale: RwLock<Float>,
+    white_balance: RwLock<[Float; 3]>,
 }
 
 impl Film {
@@ -216,10 +504,16 @@ impl Film {
             filter,
             filename,
             cropped_pixel_bounds,
+            dither: false,
+            light_groups: 0_usize,
+            sample_recorder: None,
             pixels: RwLock::new(vec![Pixel::default(); cropped_pixel_bounds.area() as usize]),
+            group_xyz: RwLock::new(Vec::new()),
             filter_table,
             scale,
             max_sample_luminance,
+            exposure_scale: RwLock::new(1.0 as Float),
+            white_balance: RwLock::new([1.0 as Float; 3]),
         }
     }
     pub fn create(params: &ParamSet, filter: Box<Filter>) -> Arc<Film> {
@@ -240,8 +534,8 @@ impl Film {
             crop.p_min.y = clamp_t(cr[2].min(cr[3]), 0.0, 1.0);
             crop.p_max.y = clamp_t(cr[2].max(cr[3]), 0.0, 1.0);
         } else if !cr.is_empty() {
-            panic!(
-                "{:?} values supplied for \"cropwindow\". Expected 4.",
+            println!(
+                "ERROR: {:?} values supplied for \"cropwindow\". Expected 4.",
                 cr.len()
             );
         }
@@ -249,7 +543,7 @@ impl Film {
         let diagonal: Float = params.find_one_float("diagonal", 35.0);
         let max_sample_luminance: Float =
             params.find_one_float("maxsampleluminance", std::f32::INFINITY);
-        Arc::new(Film::new(
+        let mut film: Film = Film::new(
             resolution,
             crop,
             filter,
@@ -257,7 +551,64 @@ impl Film {
             filename,
             scale,
             max_sample_luminance,
-        ))
+        );
+        film.dither = params.find_one_bool("dither", false);
+        film.set_light_groups(params.find_one_int("lightgroups", 0).max(0) as usize);
+        let record_samples: String = params.find_one_string("recordsamples", String::new());
+        if !record_samples.is_empty() {
+            let recorder: SampleRecorder = SampleRecorder::create(&record_samples)
+                .unwrap_or_else(|e| panic!("Can't record samples to {:?}: {}", record_samples, e));
+            film.sample_recorder = Some(recorder);
+        }
+        Arc::new(film)
+    }
+    /// Accumulate the radiance of each light group (see
+    /// **Light::get_group()**) in a separate buffer as well. Radiance
+    /// which isn't tagged by the integrator (emission, splats, other
+    /// integrators) ends up in group 0, so all groups add up to the
+    /// beauty pass.
+    pub fn set_light_groups(&mut self, n_groups: usize) {
+        let n_pixels: usize = self.cropped_pixel_bounds.area() as usize;
+        self.light_groups = n_groups;
+        self.group_xyz = RwLock::new(vec![vec![[0.0 as Float; 3]; n_pixels]; n_groups]);
+    }
+    /// Linear exposure in photographic stops, applied when the image
+    /// gets resolved: +1 doubles all pixel values, -1 halves them.
+    pub fn set_exposure(&self, stops: Float) {
+        *self.exposure_scale.write().unwrap() = (2.0 as Float).powf(stops);
+    }
+    /// Neutralizes an illuminant with the color of a blackbody at
+    /// *temp_k* Kelvin, e.g. 3200K for warm tungsten light gets
+    /// compensated by boosting blue. A *tint* above zero compensates
+    /// a green cast (less green), below zero a magenta cast. 6500K
+    /// without tint leaves colors unchanged.
+    pub fn set_white_balance(&self, temp_k: Float, tint: Float) {
+        assert!(
+            temp_k > 0.0 as Float,
+            "White balance temperature must be positive"
+        );
+        assert!(
+            tint > -1.0 as Float,
+            "White balance tint must be greater than -1"
+        );
+        let target: [Float; 3] = Film::blackbody_rgb(temp_k);
+        let reference: [Float; 3] = Film::blackbody_rgb(WHITE_BALANCE_REFERENCE_K);
+        let gains: [Float; 3] = [
+            reference[0] / target[0],
+            reference[1] / (target[1] * (1.0 as Float + tint)),
+            reference[2] / target[2],
+        ];
+        *self.white_balance.write().unwrap() = gains;
+    }
+    /// RGB color of a blackbody with unit luminance.
+    fn blackbody_rgb(temp_k: Float) -> [Float; 3] {
+        let mut v: Vec<Float> = Vec::with_capacity(N_CIE_SAMPLES as usize);
+        blackbody(&CIE_LAMBDA, N_CIE_SAMPLES as usize, temp_k, &mut v);
+        let s: Spectrum = Spectrum::from_sampled(&CIE_LAMBDA, &v, N_CIE_SAMPLES as i32);
+        let s: Spectrum = s / s.y();
+        let mut rgb: [Float; 3] = [0.0 as Float; 3];
+        s.to_rgb(&mut rgb);
+        rgb
     }
     pub fn get_cropped_pixel_bounds(&self) -> Bounds2i {
         self.cropped_pixel_bounds
@@ -340,22 +691,37 @@ impl Film {
             &self.filter_table,
             FILTER_TABLE_WIDTH,
             self.max_sample_luminance,
+            self.light_groups,
         )
     }
+    /// Edge length (in pixels) of the square tiles the image gets
+    /// rendered in. Samples near a tile's border spread over the
+    /// filter radius into the neighbouring tiles, making tiles at
+    /// least twice as large as the radius keeps that overlap to the
+    /// directly adjacent tiles.
+    pub fn get_tile_size(&self) -> i32 {
+        let radius: Vector2f = self.filter.get_radius();
+        let filter_extent: i32 = (2.0 as Float * radius.x.max(radius.y)).ceil() as i32;
+        filter_extent.max(16)
+    }
+    /// Adds the filtered radiance and filter weight sums of *tile* to
+    /// the film. Neighbouring tiles overlap at their filter-extended
+    /// borders, the pixels are locked while the tile's region gets
+    /// added, so tiles can be merged from several threads.
     pub fn merge_film_tile(&self, tile: &FilmTile) {
         // TODO: ProfilePhase p(Prof::MergeFilmTile);
         // println!("Merging film tile {:?}", tile.pixel_bounds);
-        // TODO: std::lock_guard<std::mutex> lock(mutex);
+        let width: i32 = self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x;
+        let mut pixels_write = self.pixels.write().unwrap();
+        let mut group_xyz_write = self.group_xyz.write().unwrap();
         for pixel in &tile.pixel_bounds {
             // merge _pixel_ into _Film::pixels_
             let idx = tile.get_pixel_index(pixel.x, pixel.y);
             let tile_pixel = &tile.pixels[idx];
             // START let mut merge_pixel: &mut Pixel = self.get_pixel_mut(pixel);
             assert!(pnt2_inside_exclusive(pixel, &self.cropped_pixel_bounds));
-            let width: i32 = self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x;
             let offset: i32 = (pixel.x - self.cropped_pixel_bounds.p_min.x)
                 + (pixel.y - self.cropped_pixel_bounds.p_min.y) * width;
-            let mut pixels_write = self.pixels.write().unwrap();
             let mut merge_pixel = &mut pixels_write[offset as usize];
             // END let mut merge_pixel: &mut Pixel = self.get_pixel_mut(pixel);
             let mut xyz: [Float; 3] = [0.0; 3];
@@ -364,8 +730,56 @@ impl Film {
                 merge_pixel.xyz[i] += item;
             }
             merge_pixel.filter_weight_sum += tile_pixel.filter_weight_sum;
-            // write pixel back
-            // pixels_write[offset as usize] = *merge_pixel;
+            for (group_xyz, contribs) in group_xyz_write.iter_mut().zip(tile.group_contribs.iter())
+            {
+                contribs[idx].to_xyz(&mut xyz);
+                for (i, item) in xyz.iter().enumerate() {
+                    group_xyz[offset as usize][i] += item;
+                }
+            }
+        }
+    }
+    /// Merge the pixels of another film rendered with a disjoint set
+    /// of samples (e.g. with a different seed on another machine).
+    /// Pixels store unresolved weighted sums and filter weights, so
+    /// adding them up gives the correctly weighted mean of all
+    /// samples. Splats are added as well, the caller has to adjust
+    /// the splat scale passed to *write_image()* accordingly.
+    pub fn merge(&self, other: &Film) {
+        assert!(
+            self.cropped_pixel_bounds.p_min == other.cropped_pixel_bounds.p_min
+                && self.cropped_pixel_bounds.p_max == other.cropped_pixel_bounds.p_max,
+            "Can't merge films with different pixel bounds"
+        );
+        let other_pixels = other.pixels.read().unwrap();
+        let mut pixels_write = self.pixels.write().unwrap();
+        for (merge_pixel, other_pixel) in pixels_write.iter_mut().zip(other_pixels.iter()) {
+            for i in 0..3 {
+                merge_pixel.xyz[i] += other_pixel.xyz[i];
+                merge_pixel.splat_xyz[i] += other_pixel.splat_xyz[i];
+            }
+            merge_pixel.filter_weight_sum += other_pixel.filter_weight_sum;
+        }
+        let other_group_xyz = other.group_xyz.read().unwrap();
+        let mut group_xyz_write = self.group_xyz.write().unwrap();
+        for (group_xyz, other_xyz) in group_xyz_write.iter_mut().zip(other_group_xyz.iter()) {
+            for (merge_xyz, pixel_xyz) in group_xyz.iter_mut().zip(other_xyz.iter()) {
+                for (merge_item, item) in merge_xyz.iter_mut().zip(pixel_xyz.iter()) {
+                    *merge_item += item;
+                }
+            }
+        }
+    }
+    /// Reset all pixels (and light groups) to black, e.g. after a
+    /// pilot render.
+    pub fn clear(&self) {
+        for pixel in self.pixels.write().unwrap().iter_mut() {
+            *pixel = Pixel::default();
+        }
+        for group_xyz in self.group_xyz.write().unwrap().iter_mut() {
+            for xyz in group_xyz.iter_mut() {
+                *xyz = [0.0 as Float; 3];
+            }
         }
     }
     pub fn set_image(&self, img: &[Spectrum]) {
@@ -433,34 +847,70 @@ impl Film {
         splat_xyz[1] += xyz[1];
         splat_xyz[2] += xyz[2];
     }
-    #[cfg(not(feature = "openexr"))]
-    pub fn write_image(&self, splat_scale: Float) {
+    /// Final RGB values of all pixels (in scanline order) as written
+    /// by *write_image()*, including exposure and white balance. Pixel
+    /// values may become negative during accumulation (e.g. because of
+    /// negative lights), they are only clamped to zero here.
+    pub fn get_rgb(&self, splat_scale: Float) -> Vec<Float> {
+        self.resolve_rgb(None, splat_scale)
+    }
+    /// Final RGB values (like *get_rgb()*) of light group *group* only.
+    /// Splats are part of group 0.
+    pub fn get_rgb_group(&self, group: usize, splat_scale: Float) -> Vec<Float> {
+        assert!(
+            group < self.light_groups,
+            "Light group {} out of range ({} groups)",
+            group,
+            self.light_groups
+        );
+        self.resolve_rgb(Some(group), splat_scale)
+    }
+    fn resolve_rgb(&self, group: Option<usize>, splat_scale: Float) -> Vec<Float> {
         let mut rgb: Vec<Float> =
             vec![0.0 as Float; (3 * self.cropped_pixel_bounds.area()) as usize];
+        let exposure_scale: Float = *self.exposure_scale.read().unwrap();
+        let white_balance: [Float; 3] = *self.white_balance.read().unwrap();
+        // same lock order as merge_film_tile()
+        let pixels = self.pixels.read().unwrap();
+        let group_xyz = self.group_xyz.read().unwrap();
+        let width: i32 = self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x;
         let mut offset;
         for p in &self.cropped_pixel_bounds {
             // convert pixel XYZ color to RGB
             assert!(pnt2_inside_exclusive(p, &self.cropped_pixel_bounds));
-            let width: i32 = self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x;
             offset = ((p.x - self.cropped_pixel_bounds.p_min.x)
                 + (p.y - self.cropped_pixel_bounds.p_min.y) * width) as usize;
-            let pixel: &Pixel = &self.pixels.read().unwrap()[offset];
+            let pixel: &Pixel = &pixels[offset];
 
             let start: usize = 3 * offset;
             let mut rgb_array: [Float; 3] = [0.0 as Float; 3];
-            xyz_to_rgb(&pixel.xyz, &mut rgb_array); // TODO: Use 'rgb' directly.
+            let xyz: &[Float; 3] = match group {
+                Some(g) => &group_xyz[g][offset],
+                None => &pixel.xyz,
+            };
+            xyz_to_rgb(xyz, &mut rgb_array); // TODO: Use 'rgb' directly.
             rgb[start] = rgb_array[0];
             rgb[start + 1] = rgb_array[1];
             rgb[start + 2] = rgb_array[2];
-            // normalize pixel with weight sum
+            // normalize pixel with weight sum (negative filter lobes
+            // can leave a pixel with a weight sum which isn't positive,
+            // dividing by it would flip the sign of the pixel)
             let filter_weight_sum: Float = pixel.filter_weight_sum;
-            if filter_weight_sum != 0.0 as Float {
+            if filter_weight_sum > 0.0 as Float {
                 let inv_wt: Float = 1.0 as Float / filter_weight_sum;
                 rgb[start] = (rgb[start] * inv_wt).max(0.0 as Float);
                 rgb[start + 1] = (rgb[start + 1] * inv_wt).max(0.0 as Float);
                 rgb[start + 2] = (rgb[start + 2] * inv_wt).max(0.0 as Float);
+            } else {
+                rgb[start] = 0.0 as Float;
+                rgb[start + 1] = 0.0 as Float;
+                rgb[start + 2] = 0.0 as Float;
             }
             // add splat value at pixel
+            let splat_scale: Float = match group {
+                Some(g) if g > 0 => 0.0 as Float,
+                _ => splat_scale,
+            };
             let mut splat_rgb: [Float; 3] = [0.0 as Float; 3];
             let pixel_splat_xyz: &[Float; 3] = &pixel.splat_xyz;
             let splat_xyz: [Float; 3] = [
@@ -476,173 +926,120 @@ impl Film {
             rgb[start] *= self.scale;
             rgb[start + 1] *= self.scale;
             rgb[start + 2] *= self.scale;
+            // apply exposure and white balance
+            rgb[start] *= exposure_scale * white_balance[0];
+            rgb[start + 1] *= exposure_scale * white_balance[1];
+            rgb[start + 2] *= exposure_scale * white_balance[2];
         }
-        let filename = "pbrt.png";
-        println!(
-            "Writing image {:?} with bounds {:?}",
-            filename, // TODO: self.filename,
-            self.cropped_pixel_bounds
-        );
-        // TODO: pbrt::WriteImage(filename, &rgb[0], croppedPixelBounds, fullResolution);
-        let mut buffer: Vec<u8> = vec![0.0 as u8; (3 * self.cropped_pixel_bounds.area()) as usize];
-        // 8-bit format; apply gamma (see WriteImage(...) in imageio.cpp)
-        let width: u32 =
-            (self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x) as u32;
-        let height: u32 =
-            (self.cropped_pixel_bounds.p_max.y - self.cropped_pixel_bounds.p_min.y) as u32;
-        for y in 0..height {
-            for x in 0..width {
-                // red
-                let index: usize = (3 * (y * width + x)) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-                // green
-                let index: usize = (3 * (y * width + x) + 1) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-                // blue
-                let index: usize = (3 * (y * width + x) + 2) as usize;
+        rgb
+    }
+    /// Converts RGB values (as returned by *get_rgb()*) to 8-bit
+    /// values with gamma applied (see WriteImage(...) in
+    /// imageio.cpp). With *dither* enabled a sub-LSB offset from a
+    /// blue-noise tile (indexed by the pixel coordinates) replaces the
+    /// rounding offset, so smooth gradients don't show banding. The
+    /// result is deterministic per pixel.
+    pub fn get_rgb8(&self, rgb: &[Float]) -> Vec<u8> {
+        let mut buffer: Vec<u8> = vec![0_u8; (3 * self.cropped_pixel_bounds.area()) as usize];
+        for (offset, p) in (&self.cropped_pixel_bounds).into_iter().enumerate() {
+            let rounding: Float = if self.dither {
+                let x: usize = p.x.rem_euclid(BLUE_NOISE_SIZE as i32) as usize;
+                let y: usize = p.y.rem_euclid(BLUE_NOISE_SIZE as i32) as usize;
+                let rank: u8 = BLUE_NOISE_TILE[y * BLUE_NOISE_SIZE + x];
+                (rank as Float + 0.5 as Float) / (BLUE_NOISE_SIZE * BLUE_NOISE_SIZE) as Float
+            } else {
+                0.5 as Float
+            };
+            for c in 0..3 {
+                let index: usize = 3 * offset + c;
                 buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
+                    255.0 as Float * gamma_correct(rgb[index]) + rounding,
                     0.0 as Float,
                     255.0 as Float,
                 ) as u8;
             }
         }
-        // write "pbrt.png" to disk
-        image::save_buffer(
-            &Path::new("pbrt.png"),
-            &buffer,
-            width,
-            height,
-            image::ColorType::Rgb8,
-        )
-        .unwrap();
+        buffer
     }
-    #[cfg(feature = "openexr")]
+    /// Writes the image to the file given by the film parameter
+    /// *"filename"* ("pbrt.png" by default). The extension selects
+    /// the format: OpenEXR (*.exr*) stores linear 32-bit float RGB
+    /// without tone-mapping, other formats get 8-bit values with
+    /// gamma applied (see *get_rgb8()*).
     pub fn write_image(&self, splat_scale: Float) {
-        let mut rgb: Vec<Float> =
-            vec![0.0 as Float; (3 * self.cropped_pixel_bounds.area()) as usize];
-        let mut exr: Vec<(Float, Float, Float)> = // copy data for OpenEXR image
-            vec![(0.0_f32, 0.0_f32, 0.0_f32); self.cropped_pixel_bounds.area() as usize];
-        let mut offset;
-        for p in &self.cropped_pixel_bounds {
-            // convert pixel XYZ color to RGB
-            assert!(pnt2_inside_exclusive(p, &self.cropped_pixel_bounds));
-            let width: i32 = self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x;
-            offset = ((p.x - self.cropped_pixel_bounds.p_min.x)
-                + (p.y - self.cropped_pixel_bounds.p_min.y) * width) as usize;
-            let pixel: &Pixel = &self.pixels.read().unwrap()[offset];
-            let start = 3 * offset;
-            let mut rgb_array: [Float; 3] = [0.0 as Float; 3];
-            xyz_to_rgb(&pixel.xyz, &mut rgb_array); // TODO: Use 'rgb' directly.
-            rgb[start] = rgb_array[0];
-            rgb[start + 1] = rgb_array[1];
-            rgb[start + 2] = rgb_array[2];
-            // normalize pixel with weight sum
-            let filter_weight_sum: Float = pixel.filter_weight_sum;
-            if filter_weight_sum != 0.0 as Float {
-                let inv_wt: Float = 1.0 as Float / filter_weight_sum;
-                rgb[start] = (rgb[start] * inv_wt).max(0.0 as Float);
-                rgb[start + 1] = (rgb[start + 1] * inv_wt).max(0.0 as Float);
-                rgb[start + 2] = (rgb[start + 2] * inv_wt).max(0.0 as Float);
-            }
-            // add splat value at pixel
-            let mut splat_rgb: [Float; 3] = [0.0 as Float; 3];
-            let pixel_splat_xyz: &[Float; 3] = &pixel.splat_xyz;
-            let splat_xyz: [Float; 3] = [
-                *pixel_splat_xyz.index(0),
-                *pixel_splat_xyz.index(1),
-                *pixel_splat_xyz.index(2),
-            ];
-            xyz_to_rgb(&splat_xyz, &mut splat_rgb);
-            rgb[start] += splat_scale * splat_rgb[0];
-            rgb[start + 1] += splat_scale * splat_rgb[1];
-            rgb[start + 2] += splat_scale * splat_rgb[2];
-            // scale pixel value by _scale_
-            rgb[start] *= self.scale;
-            rgb[start + 1] *= self.scale;
-            rgb[start + 2] *= self.scale;
-            // copy data for OpenEXR image
-            exr[offset].0 = rgb[start];
-            exr[offset].1 = rgb[start + 1];
-            exr[offset].2 = rgb[start + 2];
-        }
-        let filename = "pbrt.png";
+        let filename: &str = if self.filename.is_empty() {
+            "pbrt.png"
+        } else {
+            &self.filename
+        };
         println!(
             "Writing image {:?} with bounds {:?}",
-            filename, // TODO: self.filename,
-            self.cropped_pixel_bounds
+            filename, self.cropped_pixel_bounds
         );
-        // TODO: pbrt::WriteImage(filename, &rgb[0], croppedPixelBounds, fullResolution);
-        let mut buffer: Vec<u8> = vec![0.0 as u8; (3 * self.cropped_pixel_bounds.area()) as usize];
-        // 8-bit format; apply gamma (see WriteImage(...) in imageio.cpp)
+        let result: std::io::Result<()> = if is_exr_filename(filename) {
+            self.write_exr(filename, splat_scale)
+        } else {
+            self.write_ldr(filename, splat_scale)
+        };
+        if let Err(e) = result {
+            println!("ERROR: Can't write image {:?}: {}", filename, e);
+        }
+    }
+    /// Writes the (cropped) image as 8-bit RGB, the format depends on
+    /// the extension of *filename*.
+    fn write_ldr(&self, filename: &str, splat_scale: Float) -> std::io::Result<()> {
+        let rgb: Vec<Float> = self.get_rgb(splat_scale);
+        let buffer: Vec<u8> = self.get_rgb8(&rgb);
         let width: u32 =
             (self.cropped_pixel_bounds.p_max.x - self.cropped_pixel_bounds.p_min.x) as u32;
         let height: u32 =
             (self.cropped_pixel_bounds.p_max.y - self.cropped_pixel_bounds.p_min.y) as u32;
-        // OpenEXR
-        let filename = "pbrt_rust.exr";
-        println!(
-            "Writing image {:?} with bounds {:?}",
-            filename, // TODO: self.filename,
-            self.cropped_pixel_bounds
-        );
-        let mut file = std::fs::File::create("pbrt_rust.exr").unwrap();
-        let mut output_file = ScanlineOutputFile::new(
-            &mut file,
-            Header::new()
-                .set_resolution(width, height)
-                .add_channel("R", PixelType::FLOAT)
-                .add_channel("G", PixelType::FLOAT)
-                .add_channel("B", PixelType::FLOAT),
-        )
-        .unwrap();
-        let mut fb = FrameBuffer::new(width as u32, height as u32);
-        fb.insert_channels(&["R", "G", "B"], &exr);
-        output_file.write_pixels(&fb).unwrap();
-
-        // OpenEXR
-        for y in 0..height {
-            for x in 0..width {
-                // red
-                let index: usize = (3 * (y * width + x)) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-                // green
-                let index: usize = (3 * (y * width + x) + 1) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-                // blue
-                let index: usize = (3 * (y * width + x) + 2) as usize;
-                buffer[index] = clamp_t(
-                    255.0 as Float * gamma_correct(rgb[index]) + 0.5,
-                    0.0 as Float,
-                    255.0 as Float,
-                ) as u8;
-            }
-        }
-        // write "pbrt.png" to disk
         image::save_buffer(
-            &Path::new("pbrt.png"),
+            Path::new(filename),
             &buffer,
             width,
             height,
             image::ColorType::Rgb8,
         )
-        .unwrap();
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+    }
+    /// Writes the image as OpenEXR file with the float channels *R*,
+    /// *G* and *B* (plus *lightgroup<i>.R* etc. per light group). The
+    /// data window is the crop window, the display window the full
+    /// resolution.
+    pub fn write_exr(&self, filename: &str, splat_scale: Float) -> std::io::Result<()> {
+        let mut channels: Vec<(String, Vec<Float>)> =
+            Vec::with_capacity(3 * (1 + self.light_groups));
+        let rgb: Vec<Float> = self.get_rgb(splat_scale);
+        for (c, name) in ["R", "G", "B"].iter().enumerate() {
+            channels.push((
+                name.to_string(),
+                rgb.iter().skip(c).step_by(3).cloned().collect(),
+            ));
+        }
+        // light groups are stored as additional layers
+        for group in 0..self.light_groups {
+            let group_rgb: Vec<Float> = self.get_rgb_group(group, splat_scale);
+            for (c, name) in ["R", "G", "B"].iter().enumerate() {
+                channels.push((
+                    format!("lightgroup{}.{}", group, name),
+                    group_rgb.iter().skip(c).step_by(3).cloned().collect(),
+                ));
+            }
+        }
+        let display_window: Bounds2i = Bounds2i {
+            p_min: Point2i { x: 0, y: 0 },
+            p_max: self.full_resolution,
+        };
+        let file: File = File::create(filename)?;
+        let mut writer: BufWriter<File> = BufWriter::new(file);
+        write_exr(
+            &mut writer,
+            &self.cropped_pixel_bounds,
+            &display_window,
+            &channels,
+        )?;
+        writer.flush()
     }
     // pub fn get_pixel<'a>(&self, p: &Point2i) -> &'a Pixel {
     //     assert!(pnt2_inside_exclusive(p, &self.cropped_pixel_bounds));
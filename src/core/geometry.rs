@@ -680,22 +680,34 @@ where
     }
 }
 
+/// Construct an orthonormal, right-handed basis *(v2, v3)* around
+/// *v* (so that *v x v2 = v3*). This is the branchless version by
+/// Duff et al. (Building an Orthonormal Basis, Revisited), which
+/// stays accurate for directions close to the coordinate axes.
+pub fn coordinate_system(v: Vector3f) -> (Vector3f, Vector3f) {
+    let v: Vector3f = v.normalize();
+    let sign: Float = (1.0 as Float).copysign(v.z);
+    let a: Float = -1.0 as Float / (sign + v.z);
+    let b: Float = v.x * v.y * a;
+    (
+        Vector3f {
+            x: 1.0 as Float + sign * v.x * v.x * a,
+            y: sign * b,
+            z: -sign * v.x,
+        },
+        Vector3f {
+            x: b,
+            y: sign + v.y * v.y * a,
+            z: -v.y,
+        },
+    )
+}
+
 /// Construct a local coordinate system given only a single 3D vector.
 pub fn vec3_coordinate_system(v1: &Vector3f, v2: &mut Vector3f, v3: &mut Vector3f) {
-    if v1.x.abs() > v1.y.abs() {
-        *v2 = Vector3f {
-            x: -v1.z,
-            y: 0.0 as Float,
-            z: v1.x,
-        } / (v1.x * v1.x + v1.z * v1.z).sqrt();
-    } else {
-        *v2 = Vector3f {
-            x: 0.0 as Float,
-            y: v1.z,
-            z: -v1.y,
-        } / (v1.y * v1.y + v1.z * v1.z).sqrt();
-    }
-    *v3 = vec3_cross_vec3(v1, &*v2);
+    let (v2_, v3_) = coordinate_system(*v1);
+    *v2 = v2_;
+    *v3 = v3_;
 }
 
 #[derive(Debug, Default, Copy, Clone)]
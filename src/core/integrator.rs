@@ -3,19 +3,25 @@
 
 // std
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+// others
+use image::DynamicImage;
 // pbrt
 use crate::blockqueue::BlockQueue;
 use crate::core::camera::{Camera, CameraSample};
-use crate::core::geometry::{pnt2_inside_exclusive, vec3_abs_dot_nrm};
-use crate::core::geometry::{Bounds2i, Point2f, Point2i, Ray, Vector2i, Vector3f};
+use crate::core::film::SampleRecord;
+use crate::core::geometry::{bnd2_intersect_bnd2, pnt2_inside_exclusive, vec3_abs_dot_nrm};
+use crate::core::geometry::{Bounds2i, Normal3f, Point2f, Point2i, Ray, Vector2i, Vector3f};
 use crate::core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
 use crate::core::light::is_delta_light;
-use crate::core::light::Light;
+use crate::core::light::{Light, LightVisibility, VisibilityTester};
+use crate::core::material::TransportMode;
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::reflection::BxdfType;
-use crate::core::sampler::Sampler;
-use crate::core::sampling::power_heuristic;
+use crate::core::sampler::{PixelSampleBudget, Sampler};
 use crate::core::sampling::Distribution1D;
+use crate::core::sampling::{power_heuristic, ris_select};
 use crate::core::scene::Scene;
 use crate::integrators::ao::AOIntegrator;
 use crate::integrators::bdpt::BDPTIntegrator;
@@ -28,6 +34,46 @@ use crate::integrators::whitted::WhittedIntegrator;
 
 // see integrator.h
 
+/// Statistics gathered while rendering, returned to the caller of
+/// *render()* so embedding applications can log and compare runs.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RenderStats {
+    /// number of (camera) samples taken, summed over all pixels
+    pub total_samples: u64,
+    /// number of rays traced through the scene (including shadow rays)
+    pub rays_traced: u64,
+    pub render_time: Duration,
+    /// per-pixel sample variance of the luminance, averaged over all
+    /// pixels (zero if not tracked by the integrator)
+    pub mean_variance: Float,
+    /// per-pixel mean luminance, averaged over all pixels (zero if not
+    /// tracked by the integrator)
+    pub mean_luminance: Float,
+    /// samples which were set to black because of a NaN radiance value
+    pub dropped_nan_samples: u64,
+}
+
+impl RenderStats {
+    /// Samples per pixel needed to bring the relative error (standard
+    /// deviation over mean) of an average pixel down to
+    /// *target_rel_error*, assuming the variance decays with 1/N. Use
+    /// the statistics of a pilot render with a small number of
+    /// samples; returns zero if the integrator didn't track the pixel
+    /// statistics (or the image is black).
+    pub fn estimate_spp_for_error(&self, target_rel_error: Float) -> i64 {
+        assert!(
+            target_rel_error > 0.0 as Float,
+            "target_rel_error ({}) > 0",
+            target_rel_error
+        );
+        if self.mean_luminance <= 0.0 as Float {
+            return 0_i64;
+        }
+        let spp: Float = self.mean_variance / (target_rel_error * self.mean_luminance).powi(2);
+        (spp.ceil() as i64).max(1_i64)
+    }
+}
+
 pub enum Integrator {
     BDPT(BDPTIntegrator),
     MLT(MLTIntegrator),
@@ -36,13 +82,18 @@ pub enum Integrator {
 }
 
 impl Integrator {
-    pub fn render(&mut self, scene: &Scene, num_threads: u8) {
-        match self {
+    pub fn render(&mut self, scene: &Scene, num_threads: u8) -> RenderStats {
+        let start: Instant = Instant::now();
+        let rays_before: u64 = scene.get_rays_traced();
+        let mut stats: RenderStats = match self {
             Integrator::BDPT(integrator) => integrator.render(scene, num_threads),
             Integrator::MLT(integrator) => integrator.render(scene, num_threads),
             Integrator::SPPM(integrator) => integrator.render(scene, num_threads),
             Integrator::Sampler(integrator) => integrator.render(scene, num_threads),
-        }
+        };
+        stats.rays_traced = scene.get_rays_traced() - rays_before;
+        stats.render_time = start.elapsed();
+        stats
     }
 }
 
@@ -64,12 +115,20 @@ impl SamplerIntegrator {
             SamplerIntegrator::Whitted(integrator) => integrator.preprocess(scene),
         }
     }
-    pub fn render(&mut self, scene: &Scene, num_threads: u8) {
+    pub fn render(&mut self, scene: &Scene, num_threads: u8) -> RenderStats {
+        let stats: RenderStats = self.render_tiles(scene, num_threads);
+        self.get_camera().get_film().write_image(1.0 as Float);
+        stats
+    }
+    /// Renders all tiles into the film without writing the image.
+    fn render_tiles(&mut self, scene: &Scene, num_threads: u8) -> RenderStats {
         let film = self.get_camera().get_film();
         let sample_bounds: Bounds2i = film.get_sample_bounds();
         self.preprocess(scene);
         let sample_extent: Vector2i = sample_bounds.diagonal();
-        let tile_size: i32 = 16;
+        // tiles are sized to the filter radius, so samples only spread
+        // into directly adjacent tiles
+        let tile_size: i32 = film.get_tile_size();
         let x: i32 = (sample_extent.x + tile_size - 1) / tile_size;
         let y: i32 = (sample_extent.y + tile_size - 1) / tile_size;
         let n_tiles: Point2i = Point2i { x, y };
@@ -80,6 +139,7 @@ impl SamplerIntegrator {
             num_threads as usize
         };
         println!("Rendering with {:?} thread(s) ...", num_cores);
+        let mut stats: RenderStats = RenderStats::default();
         {
             let block_queue = BlockQueue::new(
                 (
@@ -100,15 +160,18 @@ impl SamplerIntegrator {
                 // spawn worker threads
                 for _ in 0..num_cores {
                     let pixel_tx = pixel_tx.clone();
-                    let mut tile_sampler: Box<Sampler> = sampler.clone_with_seed(0_u64);
                     scope.spawn(move |_| {
                         while let Some((x, y)) = bq.next() {
                             let tile: Point2i = Point2i {
                                 x: x as i32,
                                 y: y as i32,
                             };
+                            // each tile gets a fresh sampler, seeded by the
+                            // tile index, so images don't depend on which
+                            // thread renders which tile
                             let seed: i32 = tile.y * n_tiles.x + tile.x;
-                            tile_sampler.reseed(seed as u64);
+                            let mut tile_sampler: Box<Sampler> =
+                                sampler.clone_with_seed(seed as u64);
                             let x0: i32 = sample_bounds.p_min.x + tile.x * tile_size;
                             let x1: i32 = std::cmp::min(x0 + tile_size, sample_bounds.p_max.x);
                             let y0: i32 = sample_bounds.p_min.y + tile.y * tile_size;
@@ -117,11 +180,21 @@ impl SamplerIntegrator {
                                 Bounds2i::new(Point2i { x: x0, y: y0 }, Point2i { x: x1, y: y1 });
                             // println!("Starting image tile {:?}", tile_bounds);
                             let mut film_tile = film.get_film_tile(&tile_bounds);
+                            let mut groups: Vec<Spectrum> =
+                                vec![Spectrum::default(); film.light_groups];
+                            let mut scratch: Vec<Vec<Spectrum>> = Vec::new();
+                            let mut records: Vec<SampleRecord> = Vec::new();
+                            let mut tile_stats: RenderStats = RenderStats::default();
+                            let mut tile_pixels: u64 = 0_u64;
                             for pixel in &tile_bounds {
                                 tile_sampler.start_pixel(pixel);
                                 if !pnt2_inside_exclusive(pixel, &pixel_bounds) {
                                     continue;
                                 }
+                                // luminance sums for the pixel's sample variance
+                                let mut n_samples: u64 = 0_u64;
+                                let mut sum_y: f64 = 0.0;
+                                let mut sum_y2: f64 = 0.0;
                                 let mut done: bool = false;
                                 while !done {
                                     // let's use the copy_arena crate instead of pbrt's MemoryArena
@@ -140,17 +213,31 @@ impl SamplerIntegrator {
                                                 .sqrt(),
                                     );
                                     // TODO: ++nCameraRays;
+                                    let mut record: SampleRecord = SampleRecord {
+                                        p_film: camera_sample.p_film,
+                                        ..Default::default()
+                                    };
+                                    if film.sample_recorder.is_some() && ray_weight > 0.0 {
+                                        let (albedo, normal) = first_hit_features(&ray, scene);
+                                        record.albedo = albedo;
+                                        record.normal = normal;
+                                    }
                                     // evaluate radiance along camera ray
                                     let mut l: Spectrum = Spectrum::new(0.0 as Float);
-                                    let y: Float = l.y();
+                                    groups.iter_mut().for_each(|g| *g = Spectrum::default());
                                     if ray_weight > 0.0 {
-                                        l = integrator.li(
+                                        l = integrator.li_groups(
                                             &mut ray,
                                             scene,
                                             &mut tile_sampler, // &mut arena,
                                             0_i32,
+                                            &mut groups,
+                                            &mut scratch,
                                         );
                                     }
+                                    // negative luminance values are kept (negative
+                                    // lights), the film clamps them on output only
+                                    let y: Float = l.y();
                                     if l.has_nans() {
                                         println!(
                                             "Not-a-number radiance value returned for pixel \
@@ -160,16 +247,8 @@ impl SamplerIntegrator {
                                             tile_sampler.get_current_sample_number()
                                         );
                                         l = Spectrum::new(0.0);
-                                    } else if y < -10.0e-5 as Float {
-                                        println!(
-                                            "Negative luminance value, {:?}, returned for pixel \
-                                                 ({:?}, {:?}), sample {:?}. Setting to black.",
-                                            y,
-                                            pixel.x,
-                                            pixel.y,
-                                            tile_sampler.get_current_sample_number()
-                                        );
-                                        l = Spectrum::new(0.0);
+                                        groups.iter_mut().for_each(|g| *g = Spectrum::default());
+                                        tile_stats.dropped_nan_samples += 1;
                                     } else if y.is_infinite() {
                                         println!(
                                             "Infinite luminance value returned for pixel ({:?}, \
@@ -179,33 +258,102 @@ impl SamplerIntegrator {
                                             tile_sampler.get_current_sample_number()
                                         );
                                         l = Spectrum::new(0.0);
+                                        groups.iter_mut().for_each(|g| *g = Spectrum::default());
                                     }
                                     // println!("Camera sample: {:?} -> ray: {:?} -> L = {:?}",
                                     //          camera_sample, ray, l);
+                                    if film.sample_recorder.is_some() {
+                                        record.l = l;
+                                        records.push(record);
+                                    }
                                     // add camera ray's contribution to image
-                                    film_tile.add_sample(camera_sample.p_film, &mut l, ray_weight);
+                                    film_tile.add_sample_groups(
+                                        camera_sample.p_film,
+                                        &mut l,
+                                        &groups,
+                                        ray_weight,
+                                    );
+                                    tile_sampler.add_sample_luminance(l.y());
+                                    n_samples += 1;
+                                    sum_y += l.y() as f64;
+                                    sum_y2 += l.y() as f64 * l.y() as f64;
                                     done = !tile_sampler.start_next_sample();
                                 } // arena is dropped here !
+                                tile_stats.total_samples += n_samples;
+                                tile_pixels += 1;
+                                if n_samples > 0 {
+                                    tile_stats.mean_luminance +=
+                                        (sum_y / n_samples as f64) as Float;
+                                }
+                                if n_samples > 1 {
+                                    let n: f64 = n_samples as f64;
+                                    let variance: f64 = (sum_y2 - sum_y * sum_y / n) / (n - 1.0);
+                                    tile_stats.mean_variance += variance.max(0.0) as Float;
+                                }
                             }
-                            // send the tile through the channel to main thread
+                            if let Some(recorder) = &film.sample_recorder {
+                                recorder.record(&records);
+                                records.clear();
+                            }
+                            // merge image tile into _Film_ (which locks the
+                            // pixels overlapped by neighbouring tiles)
+                            film.merge_film_tile(&film_tile);
+                            scene.flush_ray_counts();
+                            // send the tile's statistics through the channel to main thread
                             pixel_tx
-                                .send(film_tile)
+                                .send((tile_stats, tile_pixels))
                                 .unwrap_or_else(|_| panic!("Failed to send tile"));
                         }
                     });
                 }
-                // spawn thread to collect pixels and render image to file
-                scope.spawn(move |_| {
+                // spawn thread to collect statistics and report progress
+                let collector = scope.spawn(move |_| {
+                    let mut stats: RenderStats = RenderStats::default();
+                    let mut n_pixels: u64 = 0_u64;
                     for _ in pbr::PbIter::new(0..bq.len()) {
-                        let film_tile = pixel_rx.recv().unwrap();
-                        // merge image tile into _Film_
-                        film.merge_film_tile(&film_tile);
+                        let (tile_stats, tile_pixels) = pixel_rx.recv().unwrap();
+                        stats.total_samples += tile_stats.total_samples;
+                        stats.dropped_nan_samples += tile_stats.dropped_nan_samples;
+                        stats.mean_variance += tile_stats.mean_variance;
+                        stats.mean_luminance += tile_stats.mean_luminance;
+                        n_pixels += tile_pixels;
+                    }
+                    if n_pixels > 0 {
+                        stats.mean_variance /= n_pixels as Float;
+                        stats.mean_luminance /= n_pixels as Float;
                     }
+                    stats
                 });
+                stats = collector.join().unwrap();
             })
             .unwrap();
         }
-        film.write_image(1.0 as Float);
+        if let Some(recorder) = &film.sample_recorder {
+            recorder.flush();
+        }
+        stats
+    }
+    /// Pilot render with the integrator's (low sample count) sampler,
+    /// returning the samples per pixel recommended to reach a relative
+    /// error of *target_rel_error*, see
+    /// *RenderStats::estimate_spp_for_error()*. This costs a full
+    /// render pass: the image isn't written and the film is cleared
+    /// afterwards, but a sample recorder of the film records the pilot
+    /// samples as well.
+    pub fn estimate_spp_for_error(
+        &mut self,
+        scene: &Scene,
+        num_threads: u8,
+        target_rel_error: Float,
+    ) -> i64 {
+        let stats: RenderStats = self.render_tiles(scene, num_threads);
+        self.get_camera().get_film().clear();
+        let spp: i64 = stats.estimate_spp_for_error(target_rel_error);
+        println!(
+            "Pilot render: {} samples per pixel recommended for a relative error of {}",
+            spp, target_rel_error
+        );
+        spp
     }
     pub fn li(&self, ray: &mut Ray, scene: &Scene, sampler: &mut Sampler, depth: i32) -> Spectrum {
         match self {
@@ -218,6 +366,35 @@ impl SamplerIntegrator {
             SamplerIntegrator::Whitted(integrator) => integrator.li(ray, scene, sampler, depth),
         }
     }
+    /// Like *li()*, but also splits the radiance into the light groups
+    /// of the film (see **Film::set_light_groups()**). The direct
+    /// lighting and path integrators tag contributions per light, the
+    /// others add all of their radiance to group 0. *scratch* holds
+    /// buffers for the light groups of recursive rays, reuse it for
+    /// all samples of a tile.
+    pub fn li_groups(
+        &self,
+        ray: &mut Ray,
+        scene: &Scene,
+        sampler: &mut Sampler,
+        depth: i32,
+        groups: &mut [Spectrum],
+        scratch: &mut Vec<Vec<Spectrum>>,
+    ) -> Spectrum {
+        match self {
+            SamplerIntegrator::DirectLighting(integrator) => {
+                integrator.li_groups(ray, scene, sampler, depth, groups, scratch)
+            }
+            SamplerIntegrator::Path(integrator) => {
+                integrator.li_groups(ray, scene, sampler, depth, groups)
+            }
+            _ => {
+                let l: Spectrum = self.li(ray, scene, sampler, depth);
+                add_to_light_group(groups, 0, l);
+                l
+            }
+        }
+    }
     pub fn get_camera(&self) -> Arc<Camera> {
         match self {
             SamplerIntegrator::AO(integrator) => integrator.get_camera(),
@@ -236,6 +413,25 @@ impl SamplerIntegrator {
             SamplerIntegrator::Whitted(integrator) => integrator.get_sampler(),
         }
     }
+    /// Guided rendering: allocate a fixed *total_budget* of samples
+    /// over the pixels, proportional to the (upscaled) luminance of a
+    /// low resolution importance *map*.
+    pub fn set_importance_map(&mut self, map: &DynamicImage, total_budget: u64) {
+        let budget: Arc<PixelSampleBudget> = Arc::new(PixelSampleBudget::from_image(
+            map,
+            self.get_pixel_bounds(),
+            total_budget,
+        ));
+        match self {
+            SamplerIntegrator::AO(integrator) => integrator.sampler.set_pixel_budget(budget),
+            SamplerIntegrator::DirectLighting(integrator) => {
+                integrator.sampler.set_pixel_budget(budget)
+            }
+            SamplerIntegrator::Path(integrator) => integrator.sampler.set_pixel_budget(budget),
+            SamplerIntegrator::VolPath(integrator) => integrator.sampler.set_pixel_budget(budget),
+            SamplerIntegrator::Whitted(integrator) => integrator.sampler.set_pixel_budget(budget),
+        }
+    }
     pub fn get_pixel_bounds(&self) -> Bounds2i {
         match self {
             SamplerIntegrator::AO(integrator) => integrator.get_pixel_bounds(),
@@ -285,6 +481,85 @@ impl SamplerIntegrator {
 
 // see integrator.cpp
 
+/// Optional *"pixelbounds"* (x0 x1 y0 y1) of an integrator, clipped to
+/// the sample bounds of the camera's film. Without (valid) values all
+/// of the sample bounds are rendered.
+pub fn parse_pixel_bounds(params: &ParamSet, camera: &Arc<Camera>) -> Bounds2i {
+    let sample_bounds: Bounds2i = camera.get_film().get_sample_bounds();
+    let pb: Vec<i32> = params.find_int("pixelbounds");
+    let np: usize = pb.len();
+    if np == 0 {
+        return sample_bounds;
+    }
+    if np != 4 {
+        println!(
+            "ERROR: Expected four values for \"pixelbounds\" parameter. Got {}.",
+            np
+        );
+        return sample_bounds;
+    }
+    let pixel_bounds: Bounds2i = bnd2_intersect_bnd2(
+        &sample_bounds,
+        &Bounds2i {
+            p_min: Point2i { x: pb[0], y: pb[2] },
+            p_max: Point2i { x: pb[1], y: pb[3] },
+        },
+    );
+    if pixel_bounds.area() <= 0 {
+        println!("ERROR: Degenerate \"pixelbounds\" specified.");
+    }
+    pixel_bounds
+}
+
+/// Albedo (estimated with a few fixed BSDF samples) and shading normal
+/// of the first surface hit by *ray*, both zero if nothing is hit. Used
+/// as feature values for recorded samples (see **SampleRecorder**).
+pub fn first_hit_features(ray: &Ray, scene: &Scene) -> (Spectrum, Normal3f) {
+    let mut ray: Ray = ray.clone();
+    let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+    if !scene.intersect(&mut ray, &mut isect) {
+        return (Spectrum::default(), Normal3f::default());
+    }
+    isect.compute_scattering_functions(&ray, false, TransportMode::Radiance);
+    let mut albedo: Spectrum = Spectrum::default();
+    if let Some(ref bsdf) = isect.bsdf {
+        let wo: Vector3f = isect.common.wo;
+        let u_samples: [Point2f; 4] = [
+            Point2f { x: 0.25, y: 0.25 },
+            Point2f { x: 0.75, y: 0.25 },
+            Point2f { x: 0.25, y: 0.75 },
+            Point2f { x: 0.75, y: 0.75 },
+        ];
+        for u in u_samples.iter() {
+            let mut wi: Vector3f = Vector3f::default();
+            let mut pdf: Float = 0.0 as Float;
+            let mut sampled_type: u8 = 0_u8;
+            let f: Spectrum = bsdf.sample_f(
+                &wo,
+                &mut wi,
+                *u,
+                &mut pdf,
+                BxdfType::BsdfAll as u8,
+                &mut sampled_type,
+            );
+            if pdf > 0.0 as Float {
+                albedo += f * Spectrum::new(vec3_abs_dot_nrm(&wi, &isect.shading.n) / pdf);
+            }
+        }
+        albedo /= u_samples.len() as Float;
+    }
+    (albedo, isect.shading.n)
+}
+
+/// Adds the radiance *l* to light group *group* of *groups* (see
+/// **Film** with *"lightgroups"*). Nothing happens if no light groups
+/// are tracked, groups beyond the last one end up in the last one.
+pub fn add_to_light_group(groups: &mut [Spectrum], group: usize, l: Spectrum) {
+    if let Some(last) = groups.len().checked_sub(1) {
+        groups[group.min(last)] += l;
+    }
+}
+
 /// Most basic direct lighting strategy.
 pub fn uniform_sample_all_lights(
     it: &SurfaceInteraction,
@@ -292,6 +567,19 @@ pub fn uniform_sample_all_lights(
     sampler: &mut Sampler,
     n_light_samples: &[i32],
     handle_media: bool,
+) -> Spectrum {
+    uniform_sample_all_lights_groups(it, scene, sampler, n_light_samples, handle_media, &mut [])
+}
+
+/// Like *uniform_sample_all_lights()*, but also adds the contribution
+/// of each light to its light group in *groups*.
+pub fn uniform_sample_all_lights_groups(
+    it: &SurfaceInteraction,
+    scene: &Scene,
+    sampler: &mut Sampler,
+    n_light_samples: &[i32],
+    handle_media: bool,
+    groups: &mut [Spectrum],
 ) -> Spectrum {
     // TODO: ProfilePhase p(Prof::DirectLighting);
     let mut l: Spectrum = Spectrum::new(0.0);
@@ -306,16 +594,18 @@ pub fn uniform_sample_all_lights(
             // use a single sample for illumination from _light_
             let u_light: Point2f = sampler.get_2d();
             let u_scattering: Point2f = sampler.get_2d();
-            l += estimate_direct(
+            let ld: Spectrum = estimate_direct(
                 it,
                 u_scattering,
-                light.clone(),
+                j,
                 u_light,
                 scene,
                 sampler,
                 handle_media,
                 false,
             );
+            l += ld;
+            add_to_light_group(groups, light.get_group(), ld);
         } else {
             // estimate direct lighting using sample arrays
             let mut ld: Spectrum = Spectrum::new(0.0);
@@ -329,7 +619,7 @@ pub fn uniform_sample_all_lights(
                 ld += estimate_direct(
                     it,
                     u_scattering_array_sample,
-                    light.clone(),
+                    j,
                     u_light_array_sample,
                     scene,
                     sampler,
@@ -337,7 +627,9 @@ pub fn uniform_sample_all_lights(
                     false,
                 );
             }
-            l += ld / *n_samples as Float;
+            ld /= *n_samples as Float;
+            l += ld;
+            add_to_light_group(groups, light.get_group(), ld);
         }
     }
     l
@@ -352,12 +644,43 @@ pub fn uniform_sample_one_light(
     handle_media: bool,
     light_distrib: Option<&Distribution1D>,
 ) -> Spectrum {
+    uniform_sample_one_light_groups(it, scene, sampler, handle_media, light_distrib, &mut [])
+}
+
+/// Like *uniform_sample_one_light()*, but also adds the contribution
+/// to the light group (in *groups*) of the chosen light.
+pub fn uniform_sample_one_light_groups(
+    it: &dyn Interaction,
+    scene: &Scene,
+    sampler: &mut Sampler,
+    handle_media: bool,
+    light_distrib: Option<&Distribution1D>,
+    groups: &mut [Spectrum],
+) -> Spectrum {
+    let (ld, light_num) =
+        uniform_sample_one_light_index(it, scene, sampler, handle_media, light_distrib);
+    if let Some(light_num) = light_num {
+        add_to_light_group(groups, scene.lights[light_num].get_group(), ld);
+    }
+    ld
+}
+
+/// Like *uniform_sample_one_light()*, but also returns the index of
+/// the chosen light (**None** if none was chosen), e.g. to weight
+/// its contribution before adding it to a light group.
+pub fn uniform_sample_one_light_index(
+    it: &dyn Interaction,
+    scene: &Scene,
+    sampler: &mut Sampler,
+    handle_media: bool,
+    light_distrib: Option<&Distribution1D>,
+) -> (Spectrum, Option<usize>) {
     // TODO: ProfilePhase p(Prof::DirectLighting);
 
     // randomly choose a single light to sample, _light_
     let n_lights: usize = scene.lights.len();
     if n_lights == 0_usize {
-        return Spectrum::default();
+        return (Spectrum::default(), None);
     }
     let light_num: usize;
     let mut light_pdf: Option<Float> = Some(0.0 as Float);
@@ -367,7 +690,7 @@ pub fn uniform_sample_one_light(
         light_num = light_distribution.sample_discrete(sampler.get_1d(), light_pdf.as_mut());
         pdf = light_pdf.unwrap();
         if pdf == 0.0 as Float {
-            return Spectrum::default();
+            return (Spectrum::default(), None);
         }
     } else {
         light_num = std::cmp::min(
@@ -376,26 +699,113 @@ pub fn uniform_sample_one_light(
         );
         pdf = 1.0 as Float / n_lights as Float;
     }
-    let light = &scene.lights[light_num];
     let u_light: Point2f = sampler.get_2d();
     let u_scattering: Point2f = sampler.get_2d();
-    estimate_direct(
+    let ld: Spectrum = estimate_direct(
         it,
         u_scattering,
-        light.clone(),
+        light_num,
         u_light,
         scene,
         sampler,
         handle_media,
         false,
-    ) / pdf
+    ) / pdf;
+    (ld, Some(light_num))
+}
+
+/// Direct lighting with resampled importance sampling (RIS): draws
+/// *n_candidates* light samples (uniformly chosen lights, sampled via
+/// *sample_li()*), weights each by the unshadowed contribution
+/// (BSDF x Li x cosine) and traces a shadow ray only for the one
+/// resampled candidate. With a single candidate this is plain next
+/// event estimation (light sampling only, no MIS).
+pub fn ris_sample_one_light(
+    it: &dyn Interaction,
+    scene: &Scene,
+    sampler: &mut Sampler,
+    n_candidates: u32,
+    handle_media: bool,
+) -> Spectrum {
+    ris_sample_one_light_groups(it, scene, sampler, n_candidates, handle_media, &mut [])
+}
+
+/// Like *ris_sample_one_light()*, but also adds the contribution to
+/// the light group (in *groups*) of the resampled light.
+pub fn ris_sample_one_light_groups(
+    it: &dyn Interaction,
+    scene: &Scene,
+    sampler: &mut Sampler,
+    n_candidates: u32,
+    handle_media: bool,
+    groups: &mut [Spectrum],
+) -> Spectrum {
+    // TODO: ProfilePhase p(Prof::DirectLighting);
+    let n_lights: usize = scene.lights.len();
+    if n_lights == 0_usize || n_candidates == 0_u32 {
+        return Spectrum::default();
+    }
+    let bsdf_flags: u8 = BxdfType::BsdfAll as u8 & !(BxdfType::BsdfSpecular as u8);
+    let it_common: &InteractionCommon = it.get_common();
+    let mut candidates: Vec<((Spectrum, Option<VisibilityTester>, usize), Float)> =
+        Vec::with_capacity(n_candidates as usize);
+    for _ in 0..n_candidates {
+        let light_num: usize = std::cmp::min(
+            (sampler.get_1d() * n_lights as Float) as usize,
+            n_lights - 1,
+        );
+        let light = &scene.lights[light_num];
+        let u_light: Point2f = sampler.get_2d();
+        let mut wi: Vector3f = Vector3f::default();
+        let mut light_pdf: Float = 0.0 as Float;
+        let (li, visibility_opt) = light.sample_li(it_common, u_light, &mut wi, &mut light_pdf);
+        // compute BSDF or phase function's value for light sample
+        let mut f: Spectrum = Spectrum::new(0.0);
+        if light_pdf > 0.0 as Float && !li.is_black() {
+            if it.is_surface_interaction() {
+                if let Some(bsdf) = it.get_bsdf() {
+                    if let Some(shading_n) = it.get_shading_n() {
+                        f = bsdf.f(it.get_wo(), &wi, light.mask_bsdf_flags(bsdf_flags))
+                            * Spectrum::new(vec3_abs_dot_nrm(&wi, shading_n));
+                    }
+                }
+            } else if let Some(ref phase) = it.get_phase() {
+                f = Spectrum::new(phase.p(it.get_wo(), &wi));
+            }
+        }
+        // source pdf: uniform light selection times light sampling pdf
+        candidates.push((
+            (f * li, visibility_opt, light_num),
+            light_pdf / n_lights as Float,
+        ));
+    }
+    let u: Float = sampler.get_1d();
+    if let Some(((f_li, visibility_opt, light_num), weight)) =
+        ris_select(candidates, |(f_li, _vis, _light_num)| f_li.y(), u)
+    {
+        // only the selected candidate needs a shadow ray
+        let mut ld: Spectrum = f_li * Spectrum::new(weight);
+        let casts_shadows: bool = scene.lights[light_num].is_visible_to(LightVisibility::Shadow);
+        if let Some(visibility) = visibility_opt.filter(|_| casts_shadows) {
+            if handle_media {
+                ld *= visibility.tr(scene, sampler);
+            } else if !visibility.unoccluded_from_light(scene, light_num) {
+                ld = Spectrum::new(0.0 as Float);
+            }
+        }
+        add_to_light_group(groups, scene.lights[light_num].get_group(), ld);
+        ld
+    } else {
+        Spectrum::default()
+    }
 }
 
-/// Computes a direct lighting estimate for a single light source sample.
+/// Computes a direct lighting estimate for a single light source
+/// sample of the *light_num*th light of the scene.
 pub fn estimate_direct(
     it: &dyn Interaction,
     u_scattering: Point2f,
-    light: Arc<Light>,
+    light_num: usize,
     u_light: Point2f,
     scene: &Scene,
     sampler: &mut Sampler,
@@ -403,12 +813,13 @@ pub fn estimate_direct(
     handle_media: bool,
     specular: bool,
 ) -> Spectrum {
-    let bsdf_flags = if !specular {
+    let light: &Light = &scene.lights[light_num];
+    let bsdf_flags = light.mask_bsdf_flags(if !specular {
         // bitwise not in Rust is ! (not the ~ operator like in C)
         BxdfType::BsdfAll as u8 & !(BxdfType::BsdfSpecular as u8)
     } else {
         BxdfType::BsdfAll as u8
-    };
+    });
     let mut ld: Spectrum = Spectrum::new(0.0);
     // sample light source with multiple importance sampling
     let mut wi: Vector3f = Vector3f::default();
@@ -441,10 +852,12 @@ pub fn estimate_direct(
         }
         if !f.is_black() {
             // compute effect of visibility for light source sample
-            if let Some(visibility) = visibility_opt {
+            // (unless the light casts no shadows)
+            let casts_shadows: bool = light.is_visible_to(LightVisibility::Shadow);
+            if let Some(visibility) = visibility_opt.filter(|_| casts_shadows) {
                 if handle_media {
                     li *= visibility.tr(scene, sampler);
-                } else if !visibility.unoccluded(scene) {
+                } else if !visibility.unoccluded_from_light(scene, light_num) {
                     li = Spectrum::new(0.0 as Float);
                 }
             }
@@ -465,7 +878,7 @@ pub fn estimate_direct(
         let mut sampled_specular: bool = false;
         if it.is_surface_interaction() {
             // sample scattered direction for surface interactions
-            let mut sampled_type: u8 = 0_u8;
+            let mut sampled_type: u8 = u8::max_value(); // != 0
             if let Some(ref bsdf) = it.get_bsdf() {
                 if let Some(shading_n) = it.get_shading_n() {
                     f = bsdf.sample_f(
@@ -553,7 +966,8 @@ pub fn estimate_direct(
 }
 
 /// The light to start each photon path from is chosen according to a
-/// PDF defined by the lights' respective powers.
+/// PDF defined by the lights' respective powers. If none of the lights
+/// emits anything, all of them are equally likely.
 pub fn compute_light_power_distribution(scene: &Scene) -> Option<Arc<Distribution1D>> {
     if scene.lights.is_empty() {
         return None;
@@ -561,7 +975,38 @@ pub fn compute_light_power_distribution(scene: &Scene) -> Option<Arc<Distributio
     let mut light_power: Vec<Float> = Vec::with_capacity(scene.lights.len());
     for li in 0..scene.lights.len() {
         let light = &scene.lights[li];
-        light_power.push(light.power().y());
+        let power: Float = light.power().y();
+        if power.is_finite() && power > 0.0 as Float {
+            light_power.push(power);
+        } else {
+            light_power.push(0.0 as Float);
+        }
+    }
+    if light_power.iter().all(|power| *power == 0.0 as Float) {
+        light_power = vec![1.0 as Float; scene.lights.len()];
     }
     Some(Arc::new(Distribution1D::new(light_power)))
 }
+
+/// Russian roulette shared by the path tracing integrators. Paths
+/// shorter than *min_bounces* always survive. Otherwise the path gets
+/// terminated (**None**) with probability *q* based on the
+/// *throughput*, or survives with the weight *1 / (1 - q)* the
+/// throughput has to be scaled by to keep the estimate unbiased. The
+/// 1D sample *u* is usually taken from **Sampler::get_1d()**.
+pub fn russian_roulette(
+    throughput: Spectrum,
+    u: Float,
+    min_bounces: usize,
+    bounce: usize,
+) -> Option<Float> {
+    if bounce <= min_bounces {
+        return Some(1.0 as Float);
+    }
+    let q: Float = (0.05 as Float).max(1.0 as Float - throughput.max_component_value());
+    if u < q {
+        None
+    } else {
+        Some(1.0 as Float / (1.0 as Float - q))
+    }
+}
@@ -3,12 +3,19 @@
 //! to the camera sensor.
 
 // std
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 // pbrt
-use crate::core::geometry::{Normal3f, Point2f, Ray, Vector3f};
+use crate::core::geometry::nrm_abs_dot_vec3;
+use crate::core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
 use crate::core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
+use crate::core::material::TransportMode;
 use crate::core::medium::MediumInterface;
-use crate::core::pbrt::{Float, Spectrum};
+use crate::core::mipmap::MipMap;
+use crate::core::pbrt::{mix_bits, Float, Spectrum};
+use crate::core::reflection::BxdfType;
 use crate::core::sampler::Sampler;
 use crate::core::scene::Scene;
 use crate::lights::diffuse::DiffuseAreaLight;
@@ -29,6 +36,65 @@ pub enum LightFlags {
     Infinite = 8,
 }
 
+/// Ray types a light is visible to, combined as a bitmask (see
+/// *"visibility"* parameter of point lights).
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LightVisibility {
+    /// Seen directly by camera rays (e.g. a visible bulb)
+    Camera = 1,
+    /// Shadow rays are traced (otherwise the light shines through
+    /// occluders)
+    Shadow = 2,
+    /// Contributes to diffuse reflection and transmission
+    Diffuse = 4,
+    /// Contributes to glossy and specular reflection and transmission
+    Specular = 8,
+}
+
+/// Light visibility bitmask with all ray types enabled
+pub const LIGHT_VISIBILITY_ALL: u8 = LightVisibility::Camera as u8
+    | LightVisibility::Shadow as u8
+    | LightVisibility::Diffuse as u8
+    | LightVisibility::Specular as u8;
+
+/// Converts a list of ray types (*"camera"*, *"shadow"*, *"diffuse"*
+/// and *"specular"*, separated by spaces or commas) to a
+/// **LightVisibility** bitmask. Unknown ray types make the light
+/// visible to all of them.
+pub fn light_visibility_from_str(names: &str) -> u8 {
+    let mut visibility: u8 = 0_u8;
+    for name in names
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|n| !n.is_empty())
+    {
+        visibility |= match name {
+            "camera" => LightVisibility::Camera as u8,
+            "shadow" => LightVisibility::Shadow as u8,
+            "diffuse" => LightVisibility::Diffuse as u8,
+            "specular" => LightVisibility::Specular as u8,
+            _ => {
+                println!(
+                    "WARNING: Light visibility \"{}\" unknown. Using all ray types.",
+                    name
+                );
+                return LIGHT_VISIBILITY_ALL;
+            }
+        };
+    }
+    visibility
+}
+
+/// Exact geometry of a delta light, e.g. to place a Dirac lobe in a
+/// path guiding structure instead of trying to learn it.
+#[derive(Debug, Copy, Clone)]
+pub enum DeltaGeometry {
+    /// Light position of point-like lights
+    Position(Point3f),
+    /// Direction *towards* a (non-soft) distant light
+    Direction(Vector3f),
+}
+
 pub enum Light {
     DiffuseArea(Box<DiffuseAreaLight>),
     Distant(Box<DistantLight>),
@@ -93,6 +159,34 @@ impl Light {
             Light::Spot(light) => light.le(ray),
         }
     }
+    /// Like *le()*, but respects the light's visibility: escaping
+    /// camera rays need **LightVisibility::Camera**, other (specular)
+    /// rays **LightVisibility::Specular**.
+    pub fn le_visible(&self, ray: &mut Ray, camera_ray: bool) -> Spectrum {
+        let visibility: LightVisibility = if camera_ray {
+            LightVisibility::Camera
+        } else {
+            LightVisibility::Specular
+        };
+        if !self.is_visible_to(visibility) {
+            return Spectrum::default();
+        }
+        self.le(ray)
+    }
+    /// Radiance arriving at *p* from direction *wi* without sampling.
+    /// Delta position lights only contribute if *wi* points exactly at
+    /// the light (see **points_at_light()**).
+    pub fn li_toward(&self, p: Point3f, wi: Vector3f) -> Spectrum {
+        match self {
+            Light::DiffuseArea(light) => light.li_toward(p, wi),
+            Light::Distant(light) => light.li_toward(p, wi),
+            Light::GonioPhotometric(light) => light.li_toward(p, wi),
+            Light::InfiniteArea(light) => light.li_toward(p, wi),
+            Light::Point(light) => light.li_toward(p, wi),
+            Light::Projection(light) => light.li_toward(p, wi),
+            Light::Spot(light) => light.li_toward(p, wi),
+        }
+    }
     pub fn pdf_li(&self, iref: &dyn Interaction, wi: Vector3f) -> Float {
         match self {
             Light::DiffuseArea(light) => light.pdf_li(iref, wi),
@@ -154,6 +248,35 @@ impl Light {
             Light::Spot(light) => light.get_flags(),
         }
     }
+    /// **LightVisibility** bitmask, only point lights can be hidden
+    /// from some ray types.
+    pub fn get_visibility(&self) -> u8 {
+        match self {
+            Light::Point(light) => light.visibility,
+            _ => LIGHT_VISIBILITY_ALL,
+        }
+    }
+    pub fn is_visible_to(&self, visibility: LightVisibility) -> bool {
+        self.get_visibility() & visibility as u8 != 0_u8
+    }
+    /// Restricts the BSDF lobes in *bsdf_flags* to the ones the light
+    /// contributes to (see **LightVisibility**).
+    pub fn mask_bsdf_flags(&self, bsdf_flags: u8) -> u8 {
+        let mut flags: u8 = bsdf_flags;
+        if !self.is_visible_to(LightVisibility::Diffuse) {
+            flags &= !(BxdfType::BsdfDiffuse as u8);
+        }
+        if !self.is_visible_to(LightVisibility::Specular) {
+            flags &= !(BxdfType::BsdfGlossy as u8 | BxdfType::BsdfSpecular as u8);
+        }
+        flags
+    }
+    /// Lights at infinity (distant and infinite area lights) have no
+    /// finite position, so there is no distance falloff and their
+    /// *pdf_li()* is a solid angle density (not an area one).
+    pub fn is_infinite(&self) -> bool {
+        self.get_flags() & (LightFlags::Infinite as u8 | LightFlags::DeltaDirection as u8) > 0
+    }
     pub fn get_n_samples(&self) -> i32 {
         match self {
             Light::DiffuseArea(light) => light.get_n_samples(),
@@ -165,6 +288,26 @@ impl Light {
             Light::Spot(light) => light.get_n_samples(),
         }
     }
+    /// Light group (for per-group output images of the **Film**), only
+    /// point lights can be assigned to a group other than 0.
+    pub fn get_group(&self) -> usize {
+        match self {
+            Light::Point(light) => light.group,
+            _ => 0_usize,
+        }
+    }
+    /// Position or direction of a delta light, **None** for area and
+    /// infinite lights.
+    pub fn delta_geometry(&self) -> Option<DeltaGeometry> {
+        match self {
+            Light::Distant(light) => light.delta_geometry(),
+            Light::GonioPhotometric(light) => light.delta_geometry(),
+            Light::Point(light) => light.delta_geometry(),
+            Light::Projection(light) => light.delta_geometry(),
+            Light::Spot(light) => light.delta_geometry(),
+            _ => None,
+        }
+    }
     // AreaLight
     pub fn l(&self, intr: &InteractionCommon, w: &Vector3f) -> Spectrum {
         match self {
@@ -188,6 +331,32 @@ pub fn is_delta_light(flags: u8) -> bool {
     pos || dir
 }
 
+/// Check if the direction *wi* from *p* points exactly at the
+/// position *p_light* of a delta light.
+pub fn points_at_light(p: &Point3f, wi: &Vector3f, p_light: &Point3f) -> bool {
+    *wi == (*p_light - *p).normalize()
+}
+
+/// Image map in latitude-longitude layout integrated over the sphere
+/// of directions, each texel weighted by the solid angle it covers.
+pub fn solid_angle_integral(mipmap: &MipMap<Spectrum>) -> Spectrum {
+    let width: usize = mipmap.pyramid[0].u_size();
+    let height: usize = mipmap.pyramid[0].v_size();
+    let d_phi: Float = 2.0 as Float * PI / width as Float;
+    let d_theta: Float = PI / height as Float;
+    let mut sum: Spectrum = Spectrum::default();
+    for t in 0..height {
+        // exact solid angle of the texel row
+        let theta0: Float = t as Float * d_theta;
+        let theta1: Float = theta0 + d_theta;
+        let solid_angle: Float = (theta0.cos() - theta1.cos()) * d_phi;
+        for s in 0..width {
+            sum += *mipmap.texel(0, s as isize, t as isize) * solid_angle;
+        }
+    }
+    sum
+}
+
 /// VisibilityTesters are created by providing two Interaction
 /// objects, one for each end point of the shadow ray to be traced.
 #[derive(Default, Clone)]
@@ -200,6 +369,19 @@ impl VisibilityTester {
     pub fn unoccluded(&self, scene: &Scene) -> bool {
         !scene.intersect_p(&mut self.p0.spawn_ray_to(&self.p1))
     }
+    /// Like **unoccluded()** for a shadow ray towards the
+    /// *light_num*th light of the scene. Results for lights at a
+    /// single point are memoized in the scene's **VisibilityCache**
+    /// (if enabled). Other lights are sampled at a different point for
+    /// each shadow ray, so sharing results would bias their shadows.
+    pub fn unoccluded_from_light(&self, scene: &Scene, light_num: usize) -> bool {
+        if let Some(ref cache) = scene.visibility_cache {
+            if scene.lights[light_num].get_flags() & LightFlags::DeltaPosition as u8 != 0 {
+                return cache.unoccluded(&self.p0.p, light_num, || self.unoccluded(scene));
+            }
+        }
+        self.unoccluded(scene)
+    }
     pub fn tr(&self, scene: &Scene, sampler: &mut Sampler) -> Spectrum {
         let mut ray: Ray = self.p0.spawn_ray_to(&self.p1);
         let mut tr: Spectrum = Spectrum::new(1.0 as Float);
@@ -240,6 +422,212 @@ impl VisibilityTester {
         }
         tr
     }
+    /// Like **tr()**, but surfaces with specular transmission (e.g. a
+    /// tinted glass pane) don't block the shadow ray. The ray keeps its
+    /// direction and picks up the transmittance of each such surface,
+    /// which gives colored shadows. Any other surface is opaque.
+    pub fn tr_through_surfaces(&self, scene: &Scene, sampler: &mut Sampler) -> Spectrum {
+        let mut ray: Ray = self.p0.spawn_ray_to(&self.p1);
+        let mut tr: Spectrum = Spectrum::new(1.0 as Float);
+        let bsdf_flags: u8 = BxdfType::BsdfSpecular as u8 | BxdfType::BsdfTransmission as u8;
+        loop {
+            let mut it: InteractionCommon = InteractionCommon::default();
+            let mut medium_interface: Option<Arc<MediumInterface>> = None;
+            let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+            let hit_surface: bool = scene.intersect(&mut ray, &mut isect);
+            // update transmittance for current ray segment
+            if let Some(ref medium_arc) = ray.medium {
+                tr *= medium_arc.tr(&ray, sampler);
+            }
+            if !hit_surface {
+                break;
+            }
+            if let Some(primitive_raw) = isect.primitive {
+                let primitive = unsafe { &*primitive_raw };
+                if primitive.get_material().is_some() {
+                    isect.compute_scattering_functions(&ray, false, TransportMode::Radiance);
+                    if let Some(ref bsdf) = isect.bsdf {
+                        if bsdf.num_components(bsdf_flags) == 0_u8 {
+                            return Spectrum::default();
+                        }
+                        // multiply transmittance of surface
+                        let wo: Vector3f = -ray.d;
+                        let mut wi: Vector3f = Vector3f::default();
+                        let mut pdf: Float = 0.0 as Float;
+                        let mut sampled_type: u8 = 0_u8;
+                        let f: Spectrum = bsdf.sample_f(
+                            &wo,
+                            &mut wi,
+                            sampler.get_2d(),
+                            &mut pdf,
+                            bsdf_flags,
+                            &mut sampled_type,
+                        );
+                        if pdf == 0.0 as Float || f.is_black() {
+                            return Spectrum::default();
+                        }
+                        tr *= f * nrm_abs_dot_vec3(&isect.shading.n, &wi) / pdf;
+                    } else {
+                        return Spectrum::default();
+                    }
+                }
+            }
+            if tr.is_black() {
+                return tr;
+            }
+            if let Some(mi_arc) = &isect.common.medium_interface {
+                medium_interface = Some(mi_arc.clone());
+            }
+            it.p = isect.common.p;
+            it.time = isect.common.time;
+            it.p_error = isect.common.p_error;
+            it.wo = isect.common.wo;
+            it.n = isect.common.n;
+            it.medium_interface = medium_interface;
+            ray = it.spawn_ray_to(&self.p1);
+        }
+        tr
+    }
+}
+
+/// Quantized shading point and index of the light.
+type VisibilityKey = ([i64; 3], usize);
+
+/// Upper bound for the number of independently locked parts of a
+/// **VisibilityCache**.
+const VISIBILITY_CACHE_SHARDS: usize = 64;
+
+#[derive(Default)]
+struct VisibilityLru {
+    capacity: usize,
+    entries: HashMap<VisibilityKey, (bool, u64)>,
+    // last use -> key, oldest first
+    last_used: BTreeMap<u64, VisibilityKey>,
+    tick: u64,
+}
+
+impl VisibilityLru {
+    fn get(&mut self, key: &VisibilityKey) -> Option<bool> {
+        self.tick += 1;
+        let tick: u64 = self.tick;
+        if let Some(entry) = self.entries.get_mut(key) {
+            let (unoccluded, last_used) = *entry;
+            entry.1 = tick;
+            self.last_used.remove(&last_used);
+            self.last_used.insert(tick, *key);
+            Some(unoccluded)
+        } else {
+            None
+        }
+    }
+    fn insert(&mut self, key: VisibilityKey, unoccluded: bool) {
+        self.tick += 1;
+        let tick: u64 = self.tick;
+        if let Some((_unoccluded, last_used)) = self.entries.insert(key, (unoccluded, tick)) {
+            // another thread was faster
+            self.last_used.remove(&last_used);
+        }
+        self.last_used.insert(tick, key);
+        while self.entries.len() > self.capacity {
+            let oldest: u64 = *self.last_used.keys().next().unwrap();
+            let evicted: VisibilityKey = self.last_used.remove(&oldest).unwrap();
+            self.entries.remove(&evicted);
+        }
+    }
+}
+
+/// Capacity-bounded LRU cache of shadow ray results towards point
+/// (and other delta position) lights, shared by all render threads. Only valid for static scenes (see
+/// **Scene::enable_visibility_cache()**) and has to be invalidated
+/// whenever the geometry changes. Entries are spread over up to 64
+/// shards (by a hash of the key), each with its own lock and its own
+/// share of the capacity, so threads rarely wait for each other.
+pub struct VisibilityCache {
+    pub capacity: usize,
+    /// edge length of the grid cells end points are quantized to
+    pub cell_size: Float,
+    shards: Vec<Mutex<VisibilityLru>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl VisibilityCache {
+    pub fn new(capacity: usize, cell_size: Float) -> Self {
+        let capacity: usize = capacity.max(1);
+        let n_shards: usize = capacity.min(VISIBILITY_CACHE_SHARDS);
+        let shards: Vec<Mutex<VisibilityLru>> = (0..n_shards)
+            .map(|i| {
+                Mutex::new(VisibilityLru {
+                    // distribute the capacity evenly
+                    capacity: capacity / n_shards + usize::from(i < capacity % n_shards),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        VisibilityCache {
+            capacity,
+            cell_size,
+            shards,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+    fn quantize(&self, p: &Point3f) -> [i64; 3] {
+        [
+            (p.x / self.cell_size).floor() as i64,
+            (p.y / self.cell_size).floor() as i64,
+            (p.z / self.cell_size).floor() as i64,
+        ]
+    }
+    fn shard(&self, key: &VisibilityKey) -> &Mutex<VisibilityLru> {
+        let mut h: u64 = 0_u64;
+        for c in key.0.iter() {
+            h = mix_bits(h ^ *c as u64);
+        }
+        h = mix_bits(h ^ key.1 as u64);
+        &self.shards[(h % self.shards.len() as u64) as usize]
+    }
+    /// Cached visibility between *p* and the *light_num*th light,
+    /// calling *trace* (and remembering its result) on a cache miss.
+    pub fn unoccluded<F>(&self, p: &Point3f, light_num: usize, trace: F) -> bool
+    where
+        F: FnOnce() -> bool,
+    {
+        let key: VisibilityKey = (self.quantize(p), light_num);
+        let shard: &Mutex<VisibilityLru> = self.shard(&key);
+        if let Some(unoccluded) = shard.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return unoccluded;
+        }
+        // trace without holding the lock
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let unoccluded: bool = trace();
+        shard.lock().unwrap().insert(key, unoccluded);
+        unoccluded
+    }
+    /// Forget all cached results, e.g. after the geometry changed.
+    pub fn invalidate(&self) {
+        for shard in &self.shards {
+            let mut lru = shard.lock().unwrap();
+            lru.entries.clear();
+            lru.last_used.clear();
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().entries.len())
+            .sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn get_hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+    pub fn get_misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
 }
 
 // Area lights are light sources defined by one or more **Shapes**
@@ -1,20 +1,25 @@
 //! Various probability distributions for sampling light sources.
 
 // std
+use std::f32::consts::PI;
 use std::sync::Arc;
 // others
 use atom::AtomSetOnce;
 use atomic::{Atomic, Ordering};
 use strum::IntoEnumIterator;
 // pbrt
+use crate::core::geometry::{
+    bnd3_union_bnd3, bnd3_union_pnt3, pnt3_distance_squared, vec3_cross_vec3, vec3_dot_vec3,
+};
 use crate::core::geometry::{Bounds3f, Normal3f, Point2f, Point3f, Point3i, Vector3f, XYZEnum};
-use crate::core::integrator::compute_light_power_distribution;
 use crate::core::interaction::InteractionCommon;
+use crate::core::light::Light;
 use crate::core::lowdiscrepancy::radical_inverse;
 use crate::core::pbrt::clamp_t;
-use crate::core::pbrt::Float;
+use crate::core::pbrt::{degrees, Float};
 use crate::core::sampling::Distribution1D;
 use crate::core::scene::Scene;
+use crate::core::transform::Transform;
 
 // see lightdistrib.h
 
@@ -25,6 +30,7 @@ pub enum LightDistribution {
     Uniform(UniformLightDistribution),
     Power(PowerLightDistribution),
     Spatial(SpatialLightDistribution),
+    BVH(LightBVHSampler),
 }
 
 impl LightDistribution {
@@ -33,6 +39,7 @@ impl LightDistribution {
             LightDistribution::Uniform(distribution) => distribution.lookup(p),
             LightDistribution::Power(distribution) => distribution.lookup(p),
             LightDistribution::Spatial(distribution) => distribution.lookup(p),
+            LightDistribution::BVH(distribution) => distribution.lookup(p),
         }
     }
 }
@@ -89,7 +96,7 @@ pub struct PowerLightDistribution {
 impl PowerLightDistribution {
     pub fn new(scene: &Scene) -> Self {
         PowerLightDistribution {
-            distrib: compute_light_power_distribution(scene),
+            distrib: scene.light_power_distrib.clone(),
         }
     }
 
@@ -127,26 +134,7 @@ impl SpatialLightDistribution {
         // bounding box dimension has maxVoxels voxels and the other
         // dimensions have a number of voxels so that voxels are
         // roughly cube shaped.
-        let b: Bounds3f = scene.world_bound();
-        let diag: Vector3f = b.diagonal();
-        let bmax_i: XYZEnum = match b.maximum_extent() {
-            0 => XYZEnum::X,
-            1 => XYZEnum::Y,
-            _ => XYZEnum::Z,
-        };
-        let bmax: Float = diag[bmax_i];
-        let mut n_voxels: [i32; 3] = [0_i32; 3];
-        for i in XYZEnum::iter() {
-            n_voxels[i as usize] = std::cmp::max(
-                1 as i32,
-                (diag[i] / bmax * max_voxels as Float).round() as i32,
-            );
-            // in the Lookup() method, we require that 20 or fewer
-            // bits be sufficient to represent each coordinate
-            // value. It's fairly hard to imagine that this would ever
-            // be a problem.
-            assert!(n_voxels[i as usize] < (1 << 20));
-        }
+        let n_voxels: [i32; 3] = voxel_counts(&scene.world_bound(), max_voxels);
         let hash_table_size: usize = (4 as i32 * n_voxels[0] * n_voxels[1] * n_voxels[2]) as usize;
         let mut hash_table: Vec<HashEntry> = Vec::with_capacity(hash_table_size);
         // let null: *mut Distribution1D = std::ptr::null_mut();
@@ -169,20 +157,7 @@ impl SpatialLightDistribution {
     pub fn compute_distribution(&self, pi: &Point3i) -> Distribution1D {
         // Compute the world-space bounding box of the voxel
         // corresponding to |pi|.
-        let p0: Point3f = Point3f {
-            x: pi[XYZEnum::X] as Float / self.n_voxels[0] as Float,
-            y: pi[XYZEnum::Y] as Float / self.n_voxels[1] as Float,
-            z: pi[XYZEnum::Z] as Float / self.n_voxels[2] as Float,
-        };
-        let p1: Point3f = Point3f {
-            x: (pi[XYZEnum::X] + 1) as Float / self.n_voxels[0] as Float,
-            y: (pi[XYZEnum::Y] + 1) as Float / self.n_voxels[1] as Float,
-            z: (pi[XYZEnum::Z] + 1) as Float / self.n_voxels[2] as Float,
-        };
-        let voxel_bounds: Bounds3f = Bounds3f {
-            p_min: self.scene.world_bound().lerp(&p0),
-            p_max: self.scene.world_bound().lerp(&p1),
-        };
+        let voxel_bounds: Bounds3f = voxel_bounds(&self.scene.world_bound(), &self.n_voxels, pi);
         // Compute the sampling distribution. Sample a number of
         // points inside voxelBounds using a 3D Halton sequence; at
         // each one, sample each light source and compute a weight
@@ -271,19 +246,7 @@ impl SpatialLightDistribution {
 
         // first, compute integer voxel coordinates for the given
         // point |p| with respect to the overall voxel grid.
-        let offset: Vector3f = self.scene.world_bound().offset(&p); // offset in [0,1].
-        let mut pi: Point3i = Point3i::default();
-        for i in XYZEnum::iter() {
-            // the clamp should almost never be necessary, but is
-            // there to be robust to computed intersection points
-            // being slightly outside the scene bounds due to
-            // floating-point roundoff error.
-            pi[i] = clamp_t(
-                (offset[i] * self.n_voxels[i as usize] as Float) as i32,
-                0_i32,
-                self.n_voxels[i as usize] - 1_i32,
-            );
-        }
+        let pi: Point3i = voxel_of(&self.scene.world_bound(), &self.n_voxels, p);
         // pack the 3D integer voxel coordinates into a single 64-bit value.
         let packed_pos: u64 = ((pi[XYZEnum::X] as u64) << 40)
             | ((pi[XYZEnum::Y] as u64) << 20)
@@ -373,10 +336,413 @@ impl SpatialLightDistribution {
     }
 }
 
+/// Spatial and directional bounds of the light emitted by one light
+/// (or a group of lights), used by **LightBVHSampler** to estimate
+/// how much it can contribute to a region of space.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LightBounds {
+    pub bounds: Bounds3f,
+    /// Emitted power (luminance)
+    pub phi: Float,
+    /// Central emission direction
+    pub w: Vector3f,
+    /// Cosine of the angle around *w* containing all emission
+    /// directions
+    pub cos_theta_o: Float,
+    /// Cosine of the additional angle (beyond *theta_o*) over which
+    /// emission falls off to zero
+    pub cos_theta_e: Float,
+}
+
+impl LightBounds {
+    /// Bounds of the emission of *light*, **None** for lights at
+    /// infinity. Area lights are treated as emitting in all
+    /// directions.
+    pub fn new(light: &Light) -> Option<Self> {
+        let phi: Float = light.power().y();
+        let up: Vector3f = Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+        // emission in all directions
+        let omni = |bounds: Bounds3f| LightBounds {
+            bounds,
+            phi,
+            w: up,
+            cos_theta_o: -1.0 as Float,
+            cos_theta_e: 0.0 as Float,
+        };
+        let at = |p: Point3f| Bounds3f { p_min: p, p_max: p };
+        match light {
+            Light::DiffuseArea(light) => Some(omni(light.shape.world_bound())),
+            Light::Distant(_) | Light::InfiniteArea(_) => None,
+            Light::GonioPhotometric(light) => Some(omni(at(light.p_light))),
+            Light::Point(light) => {
+                let r: Vector3f = Vector3f {
+                    x: light.radius,
+                    y: light.radius,
+                    z: light.radius,
+                };
+                Some(omni(Bounds3f {
+                    p_min: light.p_light - r,
+                    p_max: light.p_light + r,
+                }))
+            }
+            Light::Projection(light) => Some(LightBounds {
+                bounds: at(light.p_light),
+                phi,
+                w: light.light_to_world.transform_vector(&up).normalize(),
+                cos_theta_o: light.cos_total_width,
+                cos_theta_e: 1.0 as Float,
+            }),
+            Light::Spot(light) => {
+                let theta_o: Float = clamp_t(light.cos_falloff_start, -1.0, 1.0).acos();
+                let theta_e: Float = clamp_t(light.cos_total_width, -1.0, 1.0).acos() - theta_o;
+                Some(LightBounds {
+                    bounds: at(light.p_light),
+                    phi,
+                    w: light.light_to_world.transform_vector(&up).normalize(),
+                    cos_theta_o: light.cos_falloff_start,
+                    cos_theta_e: theta_e.max(0.0 as Float).cos(),
+                })
+            }
+        }
+    }
+    /// Bounds of the emission of both *self* and *b*.
+    pub fn union(&self, b: &LightBounds) -> LightBounds {
+        let (w, cos_theta_o) = cone_union(self.w, self.cos_theta_o, b.w, b.cos_theta_o);
+        LightBounds {
+            bounds: bnd3_union_bnd3(&self.bounds, &b.bounds),
+            phi: self.phi + b.phi,
+            w,
+            cos_theta_o,
+            cos_theta_e: self.cos_theta_e.min(b.cos_theta_e),
+        }
+    }
+    /// Conservative estimate of the light arriving at any point within
+    /// *radius* of *p*, like PBRT-v4: the emitted power falls off with
+    /// the squared distance (clamped close to the bounds) and drops to
+    /// zero if no point is within the emission cone.
+    pub fn importance(&self, p: &Point3f, radius: Float) -> Float {
+        let mut center: Point3f = Point3f::default();
+        let mut light_radius: Float = 0.0;
+        Bounds3f::bounding_sphere(&self.bounds, &mut center, &mut light_radius);
+        let r: Float = light_radius + radius;
+        let d2: Float = pnt3_distance_squared(p, &center);
+        let cos_theta_p: Float = if d2 <= r * r {
+            // overlapping, any direction is possible
+            1.0 as Float
+        } else {
+            // angle between the emission direction and the direction
+            // to the point, reduced by the emission cone and the
+            // angle subtended by both spheres
+            let wi: Vector3f = (*p - center) / d2.sqrt();
+            let cos_theta_w: Float = clamp_t(vec3_dot_vec3(&self.w, &wi), -1.0, 1.0);
+            let sin_theta_w: Float = (1.0 as Float - cos_theta_w * cos_theta_w).max(0.0).sqrt();
+            let cos_theta_o: Float = clamp_t(self.cos_theta_o, -1.0, 1.0);
+            let sin_theta_o: Float = (1.0 as Float - cos_theta_o * cos_theta_o).max(0.0).sqrt();
+            let sin_theta_b: Float = r / d2.sqrt();
+            let cos_theta_b: Float = (1.0 as Float - sin_theta_b * sin_theta_b).max(0.0).sqrt();
+            let cos_theta_x: Float =
+                cos_sub_clamped(sin_theta_w, cos_theta_w, sin_theta_o, cos_theta_o);
+            let sin_theta_x: Float = (1.0 as Float - cos_theta_x * cos_theta_x).max(0.0).sqrt();
+            cos_sub_clamped(sin_theta_x, cos_theta_x, sin_theta_b, cos_theta_b)
+        };
+        if cos_theta_p < self.cos_theta_e {
+            return 0.0 as Float;
+        }
+        self.phi * cos_theta_p.max(0.0) / d2.max(r).max(std::f32::EPSILON)
+    }
+}
+
+/// Cosine of *max(0, theta_a - theta_b)*, given sine and cosine of
+/// both angles.
+fn cos_sub_clamped(
+    sin_theta_a: Float,
+    cos_theta_a: Float,
+    sin_theta_b: Float,
+    cos_theta_b: Float,
+) -> Float {
+    if cos_theta_a > cos_theta_b {
+        return 1.0 as Float;
+    }
+    cos_theta_a * cos_theta_b + sin_theta_a * sin_theta_b
+}
+
+/// Smallest cone (central direction and cosine of its spread) around
+/// the cones *(wa, cos_a)* and *(wb, cos_b)*.
+fn cone_union(wa: Vector3f, cos_a: Float, wb: Vector3f, cos_b: Float) -> (Vector3f, Float) {
+    let theta_a: Float = clamp_t(cos_a, -1.0, 1.0).acos();
+    let theta_b: Float = clamp_t(cos_b, -1.0, 1.0).acos();
+    let theta_d: Float = clamp_t(vec3_dot_vec3(&wa, &wb), -1.0, 1.0).acos();
+    if (theta_d + theta_b).min(PI) <= theta_a {
+        return (wa, cos_a);
+    }
+    if (theta_d + theta_a).min(PI) <= theta_b {
+        return (wb, cos_b);
+    }
+    let theta_o: Float = (theta_a + theta_d + theta_b) / 2.0 as Float;
+    let axis: Vector3f = vec3_cross_vec3(&wa, &wb);
+    if theta_o >= PI || axis.length_squared() == 0.0 as Float {
+        return (wa, -1.0 as Float);
+    }
+    // rotate wa towards wb
+    let w: Vector3f = Transform::rotate(degrees(theta_o - theta_a), &axis).transform_vector(&wa);
+    (w.normalize(), theta_o.cos())
+}
+
+/// Node of the hierarchy of **LightBVHSampler**, either a single light
+/// or two children.
+#[derive(Debug, Copy, Clone)]
+struct LightBVHNode {
+    light_bounds: LightBounds,
+    /// Index of the light (leaves only)
+    light_num: usize,
+    /// Indices of both children (interior nodes only)
+    children: Option<(usize, usize)>,
+}
+
+/// A light distribution for scenes with many lights: a bounding
+/// hierarchy over the lights' **LightBounds** is traversed to give
+/// each light a probability proportional to the importance of the
+/// subtrees containing it, so lights far away or facing away from a
+/// point are rarely picked. Lights at infinity can't be bounded and
+/// are picked with the same probability as the whole hierarchy. The
+/// distributions are cached per voxel of a grid over the scene
+/// bounds.
+pub struct LightBVHSampler {
+    nodes: Vec<LightBVHNode>,
+    /// Indices of the lights which aren't part of the hierarchy
+    pub infinite_lights: Vec<usize>,
+    pub n_lights: usize,
+    pub world_bound: Bounds3f,
+    pub n_voxels: [i32; 3],
+    distributions: Box<[AtomSetOnce<Arc<Distribution1D>>]>,
+}
+
+impl LightBVHSampler {
+    pub fn new(scene: &Scene, max_voxels: u32) -> Self {
+        let mut infinite_lights: Vec<usize> = Vec::new();
+        let mut bounded_lights: Vec<(usize, LightBounds)> = Vec::new();
+        for (light_num, light) in scene.lights.iter().enumerate() {
+            if let Some(light_bounds) = LightBounds::new(light) {
+                if light_bounds.phi > 0.0 as Float {
+                    bounded_lights.push((light_num, light_bounds));
+                }
+            } else {
+                infinite_lights.push(light_num);
+            }
+        }
+        let mut nodes: Vec<LightBVHNode> = Vec::with_capacity(2 * bounded_lights.len());
+        if !bounded_lights.is_empty() {
+            LightBVHSampler::build(&mut bounded_lights[..], &mut nodes);
+        }
+        let world_bound: Bounds3f = scene.world_bound();
+        let n_voxels: [i32; 3] = voxel_counts(&world_bound, max_voxels);
+        let n_distributions: usize = (n_voxels[0] * n_voxels[1] * n_voxels[2]) as usize;
+        let mut distributions: Vec<AtomSetOnce<Arc<Distribution1D>>> =
+            Vec::with_capacity(n_distributions);
+        for _i in 0..n_distributions {
+            distributions.push(AtomSetOnce::empty());
+        }
+        LightBVHSampler {
+            nodes,
+            infinite_lights,
+            n_lights: scene.lights.len(),
+            world_bound,
+            n_voxels,
+            distributions: distributions.into_boxed_slice(),
+        }
+    }
+    /// Recursively splits *lights* at the middle of the longest axis
+    /// of their centroids, returns the index of the subtree's root.
+    fn build(lights: &mut [(usize, LightBounds)], nodes: &mut Vec<LightBVHNode>) -> usize {
+        let node_num: usize = nodes.len();
+        if lights.len() == 1 {
+            nodes.push(LightBVHNode {
+                light_bounds: lights[0].1,
+                light_num: lights[0].0,
+                children: None,
+            });
+            return node_num;
+        }
+        let centroid = |lb: &LightBounds| lb.bounds.p_min * 0.5 + lb.bounds.p_max * 0.5;
+        let mut centroid_bounds: Bounds3f = Bounds3f {
+            p_min: centroid(&lights[0].1),
+            p_max: centroid(&lights[0].1),
+        };
+        for (_light_num, lb) in lights.iter() {
+            centroid_bounds = bnd3_union_pnt3(&centroid_bounds, &centroid(lb));
+        }
+        let dim: usize = centroid_bounds.maximum_extent() as usize;
+        let axis = |p: Point3f| match dim {
+            0 => p.x,
+            1 => p.y,
+            _ => p.z,
+        };
+        let p_mid: Float = (axis(centroid_bounds.p_min) + axis(centroid_bounds.p_max)) / 2.0;
+        lights.sort_by(|a, b| {
+            axis(centroid(&a.1))
+                .partial_cmp(&axis(centroid(&b.1)))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut mid: usize = lights
+            .iter()
+            .position(|(_light_num, lb)| axis(centroid(lb)) >= p_mid)
+            .unwrap_or(0);
+        if mid == 0 || mid == lights.len() {
+            // all centroids coincide, split in equally sized halves
+            mid = lights.len() / 2;
+        }
+        // reserve the interior node, fill it in after the children
+        nodes.push(LightBVHNode {
+            light_bounds: LightBounds::default(),
+            light_num: 0,
+            children: None,
+        });
+        let (left, right) = lights.split_at_mut(mid);
+        let c0: usize = LightBVHSampler::build(left, nodes);
+        let c1: usize = LightBVHSampler::build(right, nodes);
+        nodes[node_num].light_bounds = nodes[c0].light_bounds.union(&nodes[c1].light_bounds);
+        nodes[node_num].children = Some((c0, c1));
+        node_num
+    }
+    /// Probability of picking each light for points within *radius*
+    /// of *p*.
+    pub fn light_probabilities(&self, p: &Point3f, radius: Float) -> Vec<Float> {
+        let mut probs: Vec<Float> = vec![0.0 as Float; self.n_lights];
+        let n_strategies: usize =
+            self.infinite_lights.len() + if self.nodes.is_empty() { 0 } else { 1 };
+        if n_strategies == 0 {
+            return probs;
+        }
+        let p_strategy: Float = 1.0 as Float / n_strategies as Float;
+        for light_num in &self.infinite_lights {
+            probs[*light_num] = p_strategy;
+        }
+        if !self.nodes.is_empty() && self.nodes[0].light_bounds.importance(p, radius) > 0.0 {
+            // distribute the probability of the hierarchy among the
+            // lights like a stochastic traversal would
+            let mut todo: Vec<(usize, Float)> = vec![(0_usize, p_strategy)];
+            while let Some((node_num, prob)) = todo.pop() {
+                let node: &LightBVHNode = &self.nodes[node_num];
+                if let Some((c0, c1)) = node.children {
+                    let ci: [Float; 2] = [
+                        self.nodes[c0].light_bounds.importance(p, radius),
+                        self.nodes[c1].light_bounds.importance(p, radius),
+                    ];
+                    let sum: Float = ci[0] + ci[1];
+                    if sum > 0.0 as Float {
+                        todo.push((c0, prob * ci[0] / sum));
+                        todo.push((c1, prob * ci[1] / sum));
+                    }
+                } else {
+                    probs[node.light_num] = prob;
+                }
+            }
+        }
+        probs
+    }
+    /// Distribution for the voxel with integer coordinates *pi*.
+    pub fn compute_distribution(&self, pi: &Point3i) -> Distribution1D {
+        let b: Bounds3f = voxel_bounds(&self.world_bound, &self.n_voxels, pi);
+        let mut center: Point3f = Point3f::default();
+        let mut radius: Float = 0.0;
+        Bounds3f::bounding_sphere(&b, &mut center, &mut radius);
+        let mut probs: Vec<Float> = self.light_probabilities(&center, radius);
+        if probs.iter().all(|prob| *prob == 0.0 as Float) {
+            // nothing reaches the voxel, pick any light
+            probs = vec![1.0 as Float; self.n_lights];
+        }
+        Distribution1D::new(probs)
+    }
+
+    // LightDistribution
+
+    /// Given a point |p| in space, this method returns a (hopefully
+    /// effective) sampling distribution for light sources at that
+    /// point.
+    pub fn lookup(&self, p: &Point3f) -> Arc<Distribution1D> {
+        let pi: Point3i = voxel_of(&self.world_bound, &self.n_voxels, p);
+        let index: usize = ((pi.z * self.n_voxels[1] + pi.y) * self.n_voxels[0] + pi.x) as usize;
+        let entry: &AtomSetOnce<Arc<Distribution1D>> = &self.distributions[index];
+        if let Some(distrib) = entry.dup() {
+            return distrib;
+        }
+        // several threads might compute the same distribution, the
+        // first one stored wins
+        entry.set_if_none(Arc::new(self.compute_distribution(&pi)));
+        entry.dup().unwrap()
+    }
+}
+
 // see lightdistrib.cpp
 
 const INVALID_PACKED_POS: u64 = 0xffff_ffff_ffff_ffff;
 
+/// Number of voxels along each axis, so that the widest dimension of
+/// *b* has *max_voxels* voxels and voxels are roughly cube shaped.
+fn voxel_counts(b: &Bounds3f, max_voxels: u32) -> [i32; 3] {
+    let diag: Vector3f = b.diagonal();
+    let bmax_i: XYZEnum = match b.maximum_extent() {
+        0 => XYZEnum::X,
+        1 => XYZEnum::Y,
+        _ => XYZEnum::Z,
+    };
+    let bmax: Float = diag[bmax_i];
+    let mut n_voxels: [i32; 3] = [0_i32; 3];
+    for i in XYZEnum::iter() {
+        n_voxels[i as usize] = std::cmp::max(
+            1 as i32,
+            (diag[i] / bmax * max_voxels as Float).round() as i32,
+        );
+        // in the Lookup() method, we require that 20 or fewer
+        // bits be sufficient to represent each coordinate
+        // value. It's fairly hard to imagine that this would ever
+        // be a problem.
+        assert!(n_voxels[i as usize] < (1 << 20));
+    }
+    n_voxels
+}
+
+/// Integer coordinates of the voxel containing *p*.
+fn voxel_of(b: &Bounds3f, n_voxels: &[i32; 3], p: &Point3f) -> Point3i {
+    let offset: Vector3f = b.offset(p); // offset in [0,1].
+    let mut pi: Point3i = Point3i::default();
+    for i in XYZEnum::iter() {
+        // the clamp should almost never be necessary, but is
+        // there to be robust to computed intersection points
+        // being slightly outside the scene bounds due to
+        // floating-point roundoff error.
+        pi[i] = clamp_t(
+            (offset[i] * n_voxels[i as usize] as Float) as i32,
+            0_i32,
+            n_voxels[i as usize] - 1_i32,
+        );
+    }
+    pi
+}
+
+/// World-space bounding box of the voxel with integer coordinates
+/// *pi*.
+fn voxel_bounds(b: &Bounds3f, n_voxels: &[i32; 3], pi: &Point3i) -> Bounds3f {
+    let p0: Point3f = Point3f {
+        x: pi[XYZEnum::X] as Float / n_voxels[0] as Float,
+        y: pi[XYZEnum::Y] as Float / n_voxels[1] as Float,
+        z: pi[XYZEnum::Z] as Float / n_voxels[2] as Float,
+    };
+    let p1: Point3f = Point3f {
+        x: (pi[XYZEnum::X] + 1) as Float / n_voxels[0] as Float,
+        y: (pi[XYZEnum::Y] + 1) as Float / n_voxels[1] as Float,
+        z: (pi[XYZEnum::Z] + 1) as Float / n_voxels[2] as Float,
+    };
+    Bounds3f {
+        p_min: b.lerp(&p0),
+        p_max: b.lerp(&p1),
+    }
+}
+
 /// Decides based on the name and the number of scene lights which
 /// light distribution to return.
 pub fn create_light_sample_distribution(
@@ -391,6 +757,10 @@ pub fn create_light_sample_distribution(
         Some(Arc::new(LightDistribution::Power(
             PowerLightDistribution::new(scene),
         )))
+    } else if name == "bvh" {
+        Some(Arc::new(LightDistribution::BVH(LightBVHSampler::new(
+            scene, 64,
+        ))))
     } else if name == "spatial" {
         Some(Arc::new(LightDistribution::Spatial(
             SpatialLightDistribution::new(scene, 64),
@@ -4,7 +4,7 @@ use hexf::*;
 
 // pbrt
 use crate::core::geometry::{Point2f, Point2i};
-use crate::core::pbrt::Float;
+use crate::core::pbrt::{mix_bits, Float};
 use crate::core::rng::Rng;
 use crate::core::rng::FLOAT_ONE_MINUS_EPSILON;
 use crate::core::sampling::shuffle;
@@ -1029,7 +1029,7 @@ pub fn sobol_interval_to_index(m: u32, frame: u64, p: Point2i) -> u64 {
         c += 1_i32;
     }
     // flipped b
-    let mut b: u64 = (((p.x as u32) << m) as u64 | (p.y as u64)) ^ delta;
+    let mut b: u64 = (((p.x as u32 as u64) << m) | (p.y as u32 as u64)) ^ delta;
     c = 0;
     while b > 0_u64 {
         if b & 1 > 0_u64 {
@@ -1051,13 +1051,25 @@ pub fn sobol_sample(index: i64, dimension: i32, scramble: u64) -> Float {
 
 /// Takes a 64 bit index and 32x52 matrices to calculate sample values.
 pub fn sobol_sample_float(a: i64, dimension: i32, scramble: u32) -> Float {
+    let v: u32 = scramble ^ sobol_sample_bits(a, dimension);
+    //#ifndef PBRT_HAVE_HEX_FP_CONSTANTS
+    // let x = (2.0 as f32).powi(-32 as i32); // 0x1p-32f: 1/2^32
+    // (v as Float * x).min(FLOAT_ONE_MINUS_EPSILON)
+    //#else
+    (v as Float * hexf32!("0x1.0p-32") as Float).min(FLOAT_ONE_MINUS_EPSILON)
+    //#endif
+}
+
+/// The 32 bits of the (unscrambled) Sobol' sample *a* in the given
+/// *dimension*.
+pub fn sobol_sample_bits(a: i64, dimension: i32) -> u32 {
     assert!(
         dimension < NUM_SOBOL_DIMENSIONS as i32,
         "Integrator has consumed too many Sobol' dimensions; \
          you may want to use a Sampler without a dimension limit like \"02sequence.\""
     );
     let mut a: i64 = a;
-    let mut v: u32 = scramble;
+    let mut v: u32 = 0_u32;
     // for (int i = dimension * SobolMatrixSize; a != 0; a >>= 1, i++)
     let mut i: usize = dimension as usize * SOBOL_MATRIX_SIZE as usize;
     while a != 0 {
@@ -1067,12 +1079,37 @@ pub fn sobol_sample_float(a: i64, dimension: i32, scramble: u32) -> Float {
         a >>= 1;
         i += 1_usize;
     }
-    //#ifndef PBRT_HAVE_HEX_FP_CONSTANTS
-    // let x = (2.0 as f32).powi(-32 as i32); // 0x1p-32f: 1/2^32
-    // (v as Float * x).min(FLOAT_ONE_MINUS_EPSILON)
-    //#else
+    v
+}
+
+/// Nested uniform (Owen) scrambling of the bits of *v*: every bit is
+/// flipped (or not) depending on a hash of all the bits above it and
+/// the *seed*. Keeps the stratification of (0,m,2)-nets intact.
+pub fn owen_scramble(v: u32, seed: u32) -> u32 {
+    let mut v: u32 = v;
+    if seed & 1 != 0 {
+        v ^= 1_u32 << 31;
+    }
+    for b in 1..32 {
+        let mask: u32 = (!0_u32) << (32 - b);
+        if (mix_bits(((v & mask) ^ seed) as u64) as u32) & (1_u32 << b) != 0 {
+            v ^= 1_u32 << (31 - b);
+        }
+    }
+    v
+}
+
+/// Sobol' sample *a* in the given *dimension*, with the bits Owen
+/// scrambled using *seed* (see *owen_scramble()*).
+pub fn owen_scrambled_sobol_sample(a: i64, dimension: i32, seed: u32) -> Float {
+    let v: u32 = owen_scramble(sobol_sample_bits(a, dimension), seed);
     (v as Float * hexf32!("0x1.0p-32") as Float).min(FLOAT_ONE_MINUS_EPSILON)
-    //#endif
+}
+
+/// Owen scrambles a sample value *s* in [0,1) (see *owen_scramble()*).
+pub fn owen_scramble_float(s: Float, seed: u32) -> Float {
+    let v: u32 = (s as f64 * 4_294_967_296.0_f64).min(std::u32::MAX as f64) as u32;
+    (owen_scramble(v, seed) as Float * hexf32!("0x1.0p-32") as Float).min(FLOAT_ONE_MINUS_EPSILON)
 }
 
 // see lowdiscrepancy.cpp
@@ -14,6 +14,7 @@ use crate::materials::disney::DisneyMaterial;
 use crate::materials::fourier::FourierMaterial;
 use crate::materials::glass::GlassMaterial;
 use crate::materials::hair::HairMaterial;
+use crate::materials::kdsubsurface::KdSubsurfaceMaterial;
 use crate::materials::matte::MatteMaterial;
 use crate::materials::metal::MetalMaterial;
 use crate::materials::mirror::MirrorMaterial;
@@ -40,6 +41,7 @@ pub enum Material {
     Fourier(Box<FourierMaterial>),
     Glass(Box<GlassMaterial>),
     Hair(Box<HairMaterial>),
+    KdSubsurface(Box<KdSubsurfaceMaterial>),
     Matte(Box<MatteMaterial>),
     Metal(Box<MetalMaterial>),
     Mirror(Box<MirrorMaterial>),
@@ -81,6 +83,9 @@ impl Material {
             Material::Hair(material) => {
                 material.compute_scattering_functions(si, mode, allow_multiple_lobes, mat, scale)
             }
+            Material::KdSubsurface(material) => {
+                material.compute_scattering_functions(si, mode, allow_multiple_lobes, mat, scale)
+            }
             Material::Matte(material) => {
                 material.compute_scattering_functions(si, mode, allow_multiple_lobes, mat, scale)
             }
@@ -8,6 +8,7 @@ use std::sync::Arc;
 use crate::core::geometry::{spherical_direction_vec3, vec3_coordinate_system, vec3_dot_vec3};
 use crate::core::geometry::{Point2f, Ray, Vector3f, XYEnum};
 use crate::core::interaction::MediumInteraction;
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::INV_4_PI;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::sampler::Sampler;
@@ -294,15 +295,25 @@ impl Medium {
     }
 }
 
+/// Henyey-Greenstein phase function. The asymmetry *g* in (-1, 1)
+/// is the mean cosine of the scattering angle: positive values
+/// scatter forward, negative values backward, zero is isotropic.
 pub struct HenyeyGreenstein {
     pub g: Float,
 }
 
 impl HenyeyGreenstein {
+    /// Phase function value for the (outgoing) directions *wo* and
+    /// *wi*, both pointing away from the scattering point. Integrates
+    /// to one over the sphere.
     pub fn p(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
         // TODO: ProfilePhase _(Prof::PhaseFuncEvaluation);
         phase_hg(vec3_dot_vec3(wo, wi), self.g)
     }
+    /// Samples *wi* proportional to **p()** by inverting the CDF of
+    /// the scattering angle (measured from the propagation direction
+    /// *-wo*) and returns the phase function value, which equals the
+    /// pdf.
     pub fn sample_p(&self, wo: &Vector3f, wi: &mut Vector3f, u: Point2f) -> Float {
         // TODO: ProfilePhase _(Prof::PhaseFuncSampling);
         // compute $\cos \theta$ for Henyey--Greenstein sample
@@ -312,7 +323,9 @@ impl HenyeyGreenstein {
             let sqr_term: Float = (1.0 as Float - self.g * self.g)
                 / (1.0 as Float - self.g + 2.0 as Float * self.g * u[XYEnum::X]);
 
-            (1.0 as Float + self.g * self.g - sqr_term * sqr_term) / (2.0 as Float * self.g)
+            ((1.0 as Float + self.g * self.g - sqr_term * sqr_term) / (2.0 as Float * self.g))
+                .max(-1.0 as Float)
+                .min(1.0 as Float)
         };
         // compute direction _wi_ for Henyey--Greenstein sample
         let sin_theta: Float = (0.0 as Float)
@@ -394,6 +407,35 @@ pub fn get_medium_scattering_properties(
     false
 }
 
+/// Reads the scattering properties shared by all media from *ps*:
+/// optional *"preset"* (see **SUBSURFACE_PARAMETER_TABLE**),
+/// *"sigma_a"* and *"sigma_s"* (scaled by *"scale"*) and the
+/// Henyey-Greenstein asymmetry *"g"*. Returns *(sigma_a, sigma_s,
+/// g)*.
+pub fn get_medium_parameters(ps: &ParamSet) -> (Spectrum, Spectrum, Float) {
+    // MakeMedium (api.cpp:685)
+    let sig_a_rgb: [Float; 3] = [0.0011, 0.0024, 0.014];
+    let sig_s_rgb: [Float; 3] = [2.55, 3.21, 3.77];
+    let mut sig_a: Spectrum = Spectrum::from_rgb(&sig_a_rgb);
+    let mut sig_s: Spectrum = Spectrum::from_rgb(&sig_s_rgb);
+    let preset: String = ps.find_one_string("preset", String::new());
+    let found: bool = get_medium_scattering_properties(&preset, &mut sig_a, &mut sig_s);
+    if preset != "" && !found {
+        println!(
+            "WARNING: Material preset \"{:?}\" not found.  Using defaults.",
+            preset
+        );
+    }
+    let scale: Float = ps.find_one_float("scale", 1.0 as Float);
+    let g: Float = ps.find_one_float("g", 0.0 as Float);
+    sig_a = ps.find_one_spectrum("sigma_a", sig_a) * scale;
+    sig_s = ps.find_one_spectrum("sigma_s", sig_s) * scale;
+    (sig_a, sig_s, g)
+}
+
+/// Henyey-Greenstein phase function for the cosine between two
+/// directions pointing away from the scattering point (so forward
+/// scattering has *cos_theta* = -1).
 pub fn phase_hg(cos_theta: Float, g: Float) -> Float {
     let denom: Float = 1.0 as Float + g * g + 2.0 as Float * g * cos_theta;
     INV_4_PI * (1.0 as Float - g * g) / (denom * denom.sqrt())
@@ -6,7 +6,7 @@
 // std
 use std::f32::consts::PI;
 // pbrt
-use crate::core::geometry::{spherical_direction, vec3_abs_dot_vec3};
+use crate::core::geometry::{spherical_direction, vec3_abs_dot_vec3, vec3_cross_vec3};
 use crate::core::geometry::{Point2f, Vector3f, XYEnum};
 use crate::core::pbrt::Float;
 use crate::core::pbrt::{erf, erf_inv};
@@ -18,6 +18,24 @@ use crate::materials::disney::DisneyMicrofacetDistribution;
 
 // see microfacet.h
 
+/// Maps a user-friendly roughness value in [0, 1] to the *alpha*
+/// parameter of a microfacet distribution (used by materials if
+/// *remaproughness* is set). Roughness values are clamped to at
+/// least 1e-3.
+pub fn roughness_to_alpha(roughness: Float) -> Float {
+    let roughness: Float = roughness.max(1e-3 as Float);
+    let x: Float = roughness.ln(); // natural (base e) logarithm
+    1.62142
+        + 0.819_955 * x
+        + 0.1734 * x * x
+        + 0.017_120_1 * x * x * x
+        + 0.000_640_711 * x * x * x * x
+}
+
+/// Distribution of microfacet normals *wh* in the local shading
+/// coordinate system (normal along +z). The masking function
+/// *lambda* is derived from the distribution itself, so *g1* and
+/// *g* are consistent with *d*.
 #[derive(Copy, Clone)]
 pub enum MicrofacetDistribution {
     Beckmann(BeckmannDistribution),
@@ -100,18 +118,9 @@ impl BeckmannDistribution {
             sample_visible_area,
         }
     }
+    /// See [roughness_to_alpha](fn.roughness_to_alpha.html).
     pub fn roughness_to_alpha(roughness: Float) -> Float {
-        let mut roughness = roughness;
-        let limit: Float = 1e-3 as Float;
-        if limit > roughness {
-            roughness = limit;
-        }
-        let x: Float = roughness.ln(); // natural (base e) logarithm
-        1.62142
-            + 0.819_955 * x
-            + 0.1734 * x * x
-            + 0.017_120_1 * x * x * x
-            + 0.000_640_711 * x * x * x * x
+        roughness_to_alpha(roughness)
     }
     pub fn d(&self, wh: &Vector3f) -> Float {
         let tan_2_theta: Float = tan_2_theta(wh);
@@ -146,8 +155,13 @@ impl BeckmannDistribution {
     pub fn g(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
         1.0 as Float / (1.0 as Float + self.lambda(wo) + self.lambda(wi))
     }
+    /// Density of *sample_wh()* with respect to solid angle around
+    /// *wh*.
     pub fn pdf(&self, wo: &Vector3f, wh: &Vector3f) -> Float {
         if self.get_sample_visible_area() {
+            if abs_cos_theta(wo) == 0.0 as Float {
+                return 0.0 as Float;
+            }
             self.d(wh) * self.g1(wo) * vec3_abs_dot_vec3(wo, wh) / abs_cos_theta(wo)
         } else {
             self.d(wh) * abs_cos_theta(wh)
@@ -229,30 +243,25 @@ pub struct TrowbridgeReitzDistribution {
 }
 
 impl TrowbridgeReitzDistribution {
+    /// The alpha values are clamped to a small minimum, so that
+    /// (almost) smooth surfaces give a narrow, near-mirror lobe
+    /// instead of infinite (or NaN) distribution values.
     pub fn new(alpha_x: Float, alpha_y: Float, sample_visible_area: bool) -> Self {
+        let min_alpha: Float = 1e-4 as Float;
         TrowbridgeReitzDistribution {
-            alpha_x,
-            alpha_y,
+            alpha_x: alpha_x.max(min_alpha),
+            alpha_y: alpha_y.max(min_alpha),
             sample_visible_area,
         }
     }
+    /// See [roughness_to_alpha](fn.roughness_to_alpha.html).
+    pub fn roughness_to_alpha(roughness: Float) -> Float {
+        roughness_to_alpha(roughness)
+    }
     /// Microfacet distribution function: In comparison to the
     /// Beckmann-Spizzichino model, Trowbridge-Reitz has higher tails - it
     /// falls off to zero more slowly for directions far from the surface
     /// normal.
-    pub fn roughness_to_alpha(roughness: Float) -> Float {
-        let mut roughness = roughness;
-        let limit: Float = 1e-3 as Float;
-        if limit > roughness {
-            roughness = limit;
-        }
-        let x: Float = roughness.ln(); // natural (base e) logarithm
-        1.62142
-            + 0.819_955 * x
-            + 0.1734 * x * x
-            + 0.017_120_1 * x * x * x
-            + 0.000_640_711 * x * x * x * x
-    }
     pub fn d(&self, wh: &Vector3f) -> Float {
         let tan_2_theta: Float = tan_2_theta(wh);
         if tan_2_theta.is_infinite() {
@@ -288,8 +297,13 @@ impl TrowbridgeReitzDistribution {
     pub fn g(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
         1.0 as Float / (1.0 as Float + self.lambda(wo) + self.lambda(wi))
     }
+    /// Density of *sample_wh()* with respect to solid angle around
+    /// *wh*.
     pub fn pdf(&self, wo: &Vector3f, wh: &Vector3f) -> Float {
         if self.get_sample_visible_area() {
+            if abs_cos_theta(wo) == 0.0 as Float {
+                return 0.0 as Float;
+            }
             self.d(wh) * self.g1(wo) * vec3_abs_dot_vec3(wo, wh) / abs_cos_theta(wo)
         } else {
             self.d(wh) * abs_cos_theta(wh)
@@ -472,64 +486,9 @@ fn beckmann_sample(
     .normalize()
 }
 
-fn trowbridge_reitz_sample_11(
-    cos_theta: Float,
-    u1: Float,
-    u2: Float,
-    slope_x: &mut Float,
-    slope_y: &mut Float,
-) {
-    // special case (normal incidence)
-    if cos_theta > 0.9999 {
-        let r: Float = (u1 / (1.0 - u1)).sqrt();
-        let phi: Float = 6.283_185_307_18 * u2;
-        *slope_x = r * phi.cos();
-        *slope_y = r * phi.sin();
-        return;
-    }
-
-    let sin_theta: Float = (0.0 as Float)
-        .max(1.0 as Float - cos_theta * cos_theta)
-        .sqrt();
-    let tan_theta: Float = sin_theta / cos_theta;
-    let a: Float = 1.0 / tan_theta;
-    let g1: Float = 2.0 / (1.0 + (1.0 + 1.0 / (a * a)).sqrt());
-
-    // sample slope_x
-    let a: Float = 2.0 * u1 / g1 - 1.0;
-    let mut tmp: Float = 1.0 / (a * a - 1.0);
-    if tmp > 1e10 {
-        tmp = 1e10;
-    }
-    let b: Float = tan_theta;
-    let d: Float = (b * b * tmp * tmp - (a * a - b * b) * tmp)
-        .max(0.0 as Float)
-        .sqrt();
-    let slope_x_1: Float = b * tmp - d;
-    let slope_x_2: Float = b * tmp + d;
-    if a < 0.0 || slope_x_2 > 1.0 / tan_theta {
-        *slope_x = slope_x_1;
-    } else {
-        *slope_x = slope_x_2;
-    }
-
-    // sample slope_y
-    let s: Float;
-    let new_u2 = if u2 > 0.5 {
-        s = 1.0;
-        2.0 * (u2 - 0.5)
-    } else {
-        s = -1.0;
-        2.0 * (0.5 - u2)
-    };
-    let z: Float = (new_u2 * (new_u2 * (new_u2 * 0.27385 - 0.73369) + 0.46341))
-        / (new_u2 * (new_u2 * (new_u2 * 0.093_073 + 0.309_420) - 1.0) + 0.597_999);
-    *slope_y = s * z * (1.0 + *slope_x * *slope_x).sqrt();
-
-    assert!(!(*slope_y).is_infinite());
-    assert!(!(*slope_y).is_nan());
-}
-
+/// Samples the visible normals of a Trowbridge-Reitz distribution
+/// seen from *wi* (in the upper hemisphere), see Heitz, "Sampling
+/// the GGX Distribution of Visible Normals" (JCGT 2018).
 fn trowbridge_reitz_sample(
     wi: &Vector3f,
     alpha_x: Float,
@@ -537,7 +496,7 @@ fn trowbridge_reitz_sample(
     u1: Float,
     u2: Float,
 ) -> Vector3f {
-    // 1. stretch wi
+    // 1. stretch wi (hemispherical configuration)
     let wi_stretched: Vector3f = Vector3f {
         x: alpha_x * wi.x,
         y: alpha_y * wi.y,
@@ -545,25 +504,41 @@ fn trowbridge_reitz_sample(
     }
     .normalize();
 
-    // 2. simulate P22_{wi}(x_slope, y_slope, 1, 1)
-    let mut slope_x: Float = 0.0;
-    let mut slope_y: Float = 0.0;
-    trowbridge_reitz_sample_11(cos_theta(&wi_stretched), u1, u2, &mut slope_x, &mut slope_y);
-
-    // 3. rotate
-    let tmp: Float = cos_phi(&wi_stretched) * slope_x - sin_phi(&wi_stretched) * slope_y;
-    slope_y = sin_phi(&wi_stretched) * slope_x + cos_phi(&wi_stretched) * slope_y;
-    slope_x = tmp;
-
-    // 4. unstretch
-    slope_x *= alpha_x;
-    slope_y *= alpha_y;
-
-    // 5. compute normal
+    // 2. orthonormal basis around the stretched direction
+    let len_sq: Float = wi_stretched.x * wi_stretched.x + wi_stretched.y * wi_stretched.y;
+    let t1: Vector3f = if len_sq > 0.0 as Float {
+        Vector3f {
+            x: -wi_stretched.y,
+            y: wi_stretched.x,
+            z: 0.0,
+        } / len_sq.sqrt()
+    } else {
+        Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        }
+    };
+    let t2: Vector3f = vec3_cross_vec3(&wi_stretched, &t1);
+
+    // 3. uniformly sample the projected area (a disk, where the
+    // half facing away from wi is squeezed)
+    let r: Float = u1.sqrt();
+    let phi: Float = 2.0 as Float * PI * u2;
+    let p1: Float = r * phi.cos();
+    let mut p2: Float = r * phi.sin();
+    let s: Float = 0.5 as Float * (1.0 as Float + wi_stretched.z);
+    p2 = (1.0 as Float - s) * (1.0 as Float - p1 * p1).max(0.0 as Float).sqrt() + s * p2;
+
+    // 4. reproject onto the hemisphere
+    let pz: Float = (1.0 as Float - p1 * p1 - p2 * p2).max(0.0 as Float).sqrt();
+    let nh: Vector3f = t1 * p1 + t2 * p2 + wi_stretched * pz;
+
+    // 5. unstretch
     Vector3f {
-        x: -slope_x,
-        y: -slope_y,
-        z: 1.0,
+        x: alpha_x * nh.x,
+        y: alpha_y * nh.y,
+        z: nh.z.max(1e-6 as Float),
     }
     .normalize()
 }
@@ -38,6 +38,8 @@ pub struct MipMap<T> {
     pub pyramid: Vec<BlockedArray<T>>,
     // TODO: static Float weightLut[WeightLUTSize];
     pub weight_lut: [Float; WEIGHT_LUT_SIZE],
+    // returned for texels outside of the image (see ImageWrap::Black)
+    black: T,
 }
 
 impl<T> MipMap<T>
@@ -150,6 +152,7 @@ where
             resolution,
             pyramid: Vec::new(),
             weight_lut: [0.0 as Float; WEIGHT_LUT_SIZE],
+            black: T::zero(),
         };
         // initialize levels of MipMap for image
         let n_levels = 1 + (std::cmp::max(resolution.x, resolution.y) as Float).log2() as usize;
@@ -207,25 +210,17 @@ where
         let l = &self.pyramid[level];
         let (u_size, v_size) = (l.u_size() as isize, l.v_size() as isize);
         let (ss, tt): (usize, usize) = match self.wrap_mode {
-            ImageWrap::Repeat => (
-                mod_t(s as usize, u_size as usize),
-                mod_t(t as usize, v_size as usize),
-            ),
+            // negative coordinates wrap around, too
+            ImageWrap::Repeat => (s.rem_euclid(u_size) as usize, t.rem_euclid(v_size) as usize),
             ImageWrap::Clamp => (
                 clamp_t(s, 0, u_size - 1) as usize,
                 clamp_t(t, 0, v_size - 1) as usize,
             ),
             ImageWrap::Black => {
-                // TODO: let black: T = num::Zero::zero();
                 if s < 0 || s >= u_size || t < 0 || t >= v_size {
-                    // TODO: return &black;
-                    (
-                        clamp_t(s, 0, u_size - 1) as usize,
-                        clamp_t(t, 0, v_size - 1) as usize,
-                    ) // TMP
-                } else {
-                    (s as usize, t as usize)
+                    return &self.black;
                 }
+                (s as usize, t as usize)
             }
         };
         &l[(ss, tt)]
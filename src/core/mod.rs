@@ -2,6 +2,7 @@
 
 pub mod api;
 pub mod bssrdf;
+pub mod builder;
 pub mod camera;
 pub mod efloat;
 pub mod film;
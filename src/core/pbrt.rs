@@ -210,6 +210,31 @@ pub fn round_up_pow2_64(v: i64) -> i64 {
     ret + 1
 }
 
+/// Scramble the bits of a 64-bit value (finalizer of MurmurHash3),
+/// useful to derive decorrelated seeds from structured input like
+/// pixel coordinates.
+pub fn mix_bits(v: u64) -> u64 {
+    let mut v: u64 = v;
+    v ^= v >> 31;
+    v = v.wrapping_mul(0x7fb5_d329_728e_a185);
+    v ^= v >> 27;
+    v = v.wrapping_mul(0x81da_def4_bc2d_d44d);
+    v ^= v >> 33;
+    v
+}
+
+/// Stable 64-bit hash (FNV-1a, finalized with *mix_bits()*) of some
+/// data, e.g. a scene description, which doesn't change between runs
+/// or platforms.
+pub fn hash_bytes(data: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in data {
+        h ^= *byte as u64;
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    mix_bits(h)
+}
+
 /// Helper function which emulates the behavior of std::upper_bound().
 pub fn find_interval<P>(size: i32, pred: P) -> i32
 where
@@ -34,6 +34,17 @@ impl Primitive {
             Primitive::KdTree(primitive) => primitive.world_bound(),
         }
     }
+    /// True if any (nested) primitive moves during the shutter interval.
+    pub fn is_animated(&self) -> bool {
+        match self {
+            Primitive::Geometric(_primitive) => false,
+            Primitive::Transformed(primitive) => {
+                primitive.primitive_to_world.is_animated() || primitive.primitive.is_animated()
+            }
+            Primitive::BVH(primitive) => primitive.primitives.iter().any(|p| p.is_animated()),
+            Primitive::KdTree(primitive) => primitive.primitives.iter().any(|p| p.is_animated()),
+        }
+    }
     pub fn intersect(&self, ray: &mut Ray, isect: &mut SurfaceInteraction) -> bool {
         match self {
             Primitive::Geometric(primitive) => {
@@ -636,6 +636,9 @@ impl Bxdf {
     }
 }
 
+/// Fresnel reflectance shared by the specular and microfacet BxDFs,
+/// evaluated for the cosine of the angle between the incident
+/// direction and the (micro) surface normal.
 #[derive(Copy, Clone)]
 pub enum Fresnel {
     NoOp(FresnelNoOp),
@@ -677,6 +680,9 @@ impl DisneyFresnel {
     }
 }
 
+/// Reflectance of a conductor with the complex index of refraction
+/// *eta_t* + i *k* (per spectral channel) below a dielectric with
+/// index *eta_i*.
 #[derive(Debug, Default, Copy, Clone)]
 pub struct FresnelConductor {
     pub eta_i: Spectrum,
@@ -685,11 +691,19 @@ pub struct FresnelConductor {
 }
 
 impl FresnelConductor {
+    pub fn new(eta_i: Spectrum, eta_t: Spectrum, k: Spectrum) -> Self {
+        FresnelConductor { eta_i, eta_t, k }
+    }
+    /// Light can't enter a conductor, so both sides of the surface
+    /// reflect the same.
     pub fn evaluate(&self, cos_theta_i: Float) -> Spectrum {
-        fr_conductor(cos_theta_i, self.eta_i, self.eta_t, self.k)
+        fr_conductor(cos_theta_i.abs(), self.eta_i, self.eta_t, self.k)
     }
 }
 
+/// Reflectance of the boundary between dielectrics with the indices of
+/// refraction *eta_i* (outside, where the normal points to) and
+/// *eta_t* (inside).
 #[derive(Debug, Default, Copy, Clone)]
 pub struct FresnelDielectric {
     pub eta_i: Float,
@@ -697,11 +711,17 @@ pub struct FresnelDielectric {
 }
 
 impl FresnelDielectric {
+    pub fn new(eta_i: Float, eta_t: Float) -> Self {
+        FresnelDielectric { eta_i, eta_t }
+    }
+    /// A negative *cos_theta_i* means the light arrives from the
+    /// inside, see *fr_dielectric()*.
     pub fn evaluate(&self, cos_theta_i: Float) -> Spectrum {
         Spectrum::new(fr_dielectric(cos_theta_i, self.eta_i, self.eta_t))
     }
 }
 
+/// Reflects all light, e.g. for perfect mirrors.
 #[derive(Debug, Default, Copy, Clone)]
 pub struct FresnelNoOp {}
 
@@ -829,12 +849,10 @@ impl SpecularTransmission {
             ft / abs_cos_theta(&*wi)
         }
     }
-    pub fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
-        if vec3_same_hemisphere_vec3(wo, wi) {
-            abs_cos_theta(wi) * INV_PI
-        } else {
-            0.0 as Float
-        }
+    /// Like for the **SpecularReflection** the pdf of a delta
+    /// distribution isn't evaluated.
+    pub fn pdf(&self, _wo: &Vector3f, _wi: &Vector3f) -> Float {
+        0.0 as Float
     }
     pub fn get_type(&self) -> u8 {
         BxdfType::BsdfTransmission as u8 | BxdfType::BsdfSpecular as u8
@@ -849,6 +867,8 @@ pub struct FresnelSpecular {
     pub eta_b: Float,
     pub mode: TransportMode,
     pub sc_opt: Option<Spectrum>,
+    /// per channel *eta_b* (for dispersion), replaces *eta_b*
+    pub eta_b_rgb: Option<[Float; 3]>,
 }
 
 impl FresnelSpecular {
@@ -867,6 +887,29 @@ impl FresnelSpecular {
             eta_b,
             mode,
             sc_opt,
+            eta_b_rgb: None,
+        }
+    }
+    /// Wavelength-dependent index of refraction: each call to
+    /// *sample_f()* picks one of the three channels (using the first
+    /// sample dimension) and returns a spectrum which is non-zero
+    /// only in that channel.
+    pub fn new_dispersive(
+        r: Spectrum,
+        t: Spectrum,
+        eta_a: Float,
+        eta_b_rgb: [Float; 3],
+        mode: TransportMode,
+        sc_opt: Option<Spectrum>,
+    ) -> Self {
+        FresnelSpecular {
+            r,
+            t,
+            eta_a,
+            eta_b: eta_b_rgb[1],
+            mode,
+            sc_opt,
+            eta_b_rgb: Some(eta_b_rgb),
         }
     }
     pub fn f(&self, _wo: &Vector3f, _wi: &Vector3f) -> Spectrum {
@@ -880,6 +923,25 @@ impl FresnelSpecular {
         pdf: &mut Float,
         sampled_type: &mut u8,
     ) -> Spectrum {
+        if let Some(eta_b_rgb) = self.eta_b_rgb {
+            // select a channel and remap the sample
+            let u: Float = sample[XYEnum::X] * 3.0 as Float;
+            let channel: usize = (u as usize).min(2);
+            let single: FresnelSpecular = FresnelSpecular {
+                eta_b: eta_b_rgb[channel],
+                eta_b_rgb: None,
+                ..*self
+            };
+            let remapped: Point2f = Point2f {
+                x: (u - channel as Float).min(FLOAT_ONE_MINUS_EPSILON),
+                y: sample[XYEnum::Y],
+            };
+            let f: Spectrum = single.sample_f(wo, wi, remapped, pdf, sampled_type);
+            // divide by the probability (1/3) of picking the channel
+            let mut result: Spectrum = Spectrum::default();
+            result.c[channel] = f.c[channel] * 3.0 as Float;
+            return result;
+        }
         let ct: Float = cos_theta(wo);
         let f: Float = fr_dielectric(ct, self.eta_a, self.eta_b);
         if sample[XYEnum::X] < f {
@@ -939,12 +1001,8 @@ impl FresnelSpecular {
             }
         }
     }
-    pub fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
-        if vec3_same_hemisphere_vec3(wo, wi) {
-            abs_cos_theta(wi) * INV_PI
-        } else {
-            0.0 as Float
-        }
+    pub fn pdf(&self, _wo: &Vector3f, _wi: &Vector3f) -> Float {
+        0.0 as Float
     }
     pub fn get_type(&self) -> u8 {
         BxdfType::BsdfReflection as u8
@@ -983,11 +1041,7 @@ impl LambertianReflection {
             wi.z *= -1.0 as Float;
         }
         *pdf = self.pdf(wo, &*wi);
-        if let Some(sc) = self.sc_opt {
-            sc * self.f(wo, &*wi)
-        } else {
-            self.f(wo, &*wi)
-        }
+        self.f(wo, &*wi)
     }
     pub fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
         if vec3_same_hemisphere_vec3(wo, wi) {
@@ -1031,11 +1085,7 @@ impl LambertianTransmission {
             wi.z *= -1.0 as Float;
         }
         *pdf = self.pdf(wo, &*wi);
-        if let Some(sc) = self.sc_opt {
-            sc * self.f(wo, &*wi)
-        } else {
-            self.f(wo, &*wi)
-        }
+        self.f(wo, &*wi)
     }
     pub fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
         if !vec3_same_hemisphere_vec3(wo, wi) {
@@ -1110,11 +1160,7 @@ impl OrenNayar {
             wi.z *= -1.0 as Float;
         }
         *pdf = self.pdf(wo, &*wi);
-        if let Some(sc) = self.sc_opt {
-            sc * self.f(wo, &*wi)
-        } else {
-            self.f(wo, &*wi)
-        }
+        self.f(wo, &*wi)
     }
     pub fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
         if vec3_same_hemisphere_vec3(wo, wi) {
@@ -1192,11 +1238,7 @@ impl MicrofacetReflection {
         }
         // compute PDF of _wi_ for microfacet reflection
         *pdf = self.distribution.pdf(wo, &wh) / (4.0 * vec3_dot_vec3(wo, &wh));
-        if let Some(sc) = self.sc_opt {
-            sc * self.f(wo, &*wi)
-        } else {
-            self.f(wo, &*wi)
-        }
+        self.f(wo, &*wi)
     }
 
     pub fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
@@ -1335,11 +1377,7 @@ impl MicrofacetTransmission {
 
         if refract(wo, &wh.into(), eta, wi) {
             *pdf = self.pdf(wo, &wi);
-            if let Some(sc) = self.sc_opt {
-                sc * self.f(wo, wi)
-            } else {
-                self.f(wo, wi)
-            }
+            self.f(wo, wi)
         } else {
             Spectrum::zero()
         }
@@ -1363,6 +1401,11 @@ impl MicrofacetTransmission {
     }
 }
 
+/// Ashikhmin and Shirley's model of a glossy specular coating over a
+/// diffuse substrate: the Fresnel reflectance of the coating (Schlick's
+/// approximation for the reflectance *rs* at normal incidence) reduces
+/// the light reaching the substrate (with reflectance *rd*), so the
+/// blend doesn't reflect more light than it receives at any angle.
 #[derive(Copy, Clone)]
 pub struct FresnelBlend {
     pub rd: Spectrum,
@@ -1443,14 +1486,16 @@ impl FresnelBlend {
                 if !vec3_same_hemisphere_vec3(wo, &*wi) {
                     return Spectrum::new(0.0);
                 }
+            } else {
+                // without a coating there is only the substrate
+                *wi = cosine_sample_hemisphere(u);
+                if wo.z < 0.0 as Float {
+                    wi.z *= -1.0 as Float;
+                }
             }
         }
         *pdf = self.pdf(wo, &*wi);
-        if let Some(sc) = self.sc_opt {
-            sc * self.f(wo, &*wi)
-        } else {
-            self.f(wo, &*wi)
-        }
+        self.f(wo, &*wi)
     }
     pub fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
         // if (!SameHemisphere(wo, wi)) return 0;
@@ -1462,7 +1507,7 @@ impl FresnelBlend {
             let pdf_wh: Float = distribution.pdf(wo, &wh);
             0.5 as Float * (abs_cos_theta(wi) * INV_PI + pdf_wh / (4.0 * vec3_dot_vec3(wo, &wh)))
         } else {
-            0.0 as Float
+            abs_cos_theta(wi) * INV_PI
         }
     }
     pub fn get_type(&self) -> u8 {
@@ -1904,7 +1949,10 @@ pub fn vec3_same_hemisphere_vec3(w: &Vector3f, wp: &Vector3f) -> bool {
 // see reflection.cpp
 
 /// Computes the Fresnel reflection formula for dielectric materials
-/// and unpolarized light.
+/// and unpolarized light. For a negative *cos_theta_i* the light
+/// arrives from the side of *eta_t*, so the indices get swapped; light
+/// which can't leave the denser medium (total internal reflection) is
+/// reflected completely.
 pub fn fr_dielectric(cos_theta_i: Float, eta_i: Float, eta_t: Float) -> Float {
     let mut cos_theta_i = clamp_t(cos_theta_i, -1.0, 1.0);
     // potentially swap indices of refraction
@@ -1937,26 +1985,42 @@ pub fn fr_dielectric(cos_theta_i: Float, eta_i: Float, eta_t: Float) -> Float {
 }
 
 /// Computes the Fresnel reflectance at the boundary between a
-/// conductor and a dielectric medium.
+/// conductor and a dielectric medium. Each spectral channel is
+/// treated as an independent wavelength, see
+/// *fr_conductor_wavelength()*.
 pub fn fr_conductor(cos_theta_i: Float, eta_i: Spectrum, eta_t: Spectrum, k: Spectrum) -> Spectrum {
-    let not_clamped: Float = cos_theta_i;
-    let cos_theta_i: Float = clamp_t(not_clamped, -1.0, 1.0);
-    let eta: Spectrum = eta_t / eta_i;
-    let eta_k: Spectrum = k / eta_i;
+    let mut r: Spectrum = Spectrum::default();
+    for (c, r_c) in r.c.iter_mut().enumerate() {
+        *r_c = fr_conductor_wavelength(cos_theta_i, eta_i.c[c], eta_t.c[c], k.c[c]);
+    }
+    r
+}
+
+/// Fresnel reflectance of a conductor (complex index of refraction
+/// *eta_t* + i *k*) for a single wavelength, e.g. the hero wavelength
+/// in spectral mode.
+pub fn fr_conductor_wavelength(cos_theta_i: Float, eta_i: Float, eta_t: Float, k: Float) -> Float {
+    let cos_theta_i: Float = clamp_t(cos_theta_i, -1.0, 1.0);
+    let eta: Float = eta_t / eta_i;
+    let eta_k: Float = k / eta_i;
     let cos_theta_i2: Float = cos_theta_i * cos_theta_i;
     let sin_theta_i2: Float = 1.0 as Float - cos_theta_i2;
-    let eta_2: Spectrum = eta * eta;
-    let eta_k2: Spectrum = eta_k * eta_k;
-    let t0: Spectrum = eta_2 - eta_k2 - Spectrum::new(sin_theta_i2);
-    let a2_plus_b2: Spectrum = (t0 * t0 + eta_2 * eta_k2 * Spectrum::new(4.0 as Float)).sqrt();
-    let t1: Spectrum = a2_plus_b2 + Spectrum::new(cos_theta_i2);
-    let a: Spectrum = ((a2_plus_b2 + t0) * 0.5 as Float).sqrt();
-    let t2: Spectrum = a * 2.0 as Float * cos_theta_i;
-    let rs: Spectrum = (t1 - t2) / (t1 + t2);
-    let t3: Spectrum = a2_plus_b2 * cos_theta_i2 + Spectrum::new(sin_theta_i2 * sin_theta_i2);
-    let t4: Spectrum = t2 * sin_theta_i2;
-    let rp: Spectrum = rs * (t3 - t4) / (t3 + t4);
-    (rp + rs) * Spectrum::new(0.5 as Float)
+    let eta_2: Float = eta * eta;
+    let eta_k2: Float = eta_k * eta_k;
+    let t0: Float = eta_2 - eta_k2 - sin_theta_i2;
+    let a2_plus_b2: Float = (t0 * t0 + 4.0 as Float * eta_2 * eta_k2).sqrt();
+    let t1: Float = a2_plus_b2 + cos_theta_i2;
+    let a: Float = (0.5 as Float * (a2_plus_b2 + t0)).sqrt();
+    let t2: Float = 2.0 as Float * cos_theta_i * a;
+    if t1 + t2 <= 0.0 as Float {
+        // grazing angle at a matching index (nothing to reflect at)
+        return 0.0 as Float;
+    }
+    let rs: Float = (t1 - t2) / (t1 + t2);
+    let t3: Float = cos_theta_i2 * a2_plus_b2 + sin_theta_i2 * sin_theta_i2;
+    let t4: Float = t2 * sin_theta_i2;
+    let rp: Float = rs * (t3 - t4) / (t3 + t4);
+    0.5 as Float * (rp + rs)
 }
 
 fn pow5(v: Float) -> Float {
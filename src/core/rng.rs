@@ -63,7 +63,7 @@ impl Rng {
     }
     pub fn uniform_uint32_bounded(&mut self, b: u32) -> u32 {
         // bitwise not in Rust is ! (not the ~ operator like in C)
-        let threshold = (!b + 1) & b;
+        let threshold = (!b).wrapping_add(1) % b;
         loop {
             let r = self.uniform_uint32();
             if r >= threshold {
@@ -2,11 +2,20 @@
 //! samplers but also provides some common functionality for use by
 //! **Sampler** implementations.
 
+// std
+use std::io::Write;
+use std::sync::Arc;
+// others
+use image::DynamicImage;
 // pbrt
 use crate::core::camera::CameraSample;
-use crate::core::geometry::{Point2f, Point2i};
-use crate::core::pbrt::Float;
+use crate::core::geometry::{Bounds2i, Point2f, Point2i};
+use crate::core::paramset::ParamSet;
+use crate::core::pbrt::{mix_bits, Float};
 use crate::integrators::mlt::MLTSampler;
+use crate::samplers::adaptive::{AdaptiveSampleStats, AdaptiveSampler};
+use crate::samplers::block::BlockSampler;
+use crate::samplers::cmj::CmjSampler;
 use crate::samplers::halton::HaltonSampler;
 use crate::samplers::maxmin::MaxMinDistSampler;
 use crate::samplers::random::RandomSampler;
@@ -17,6 +26,11 @@ use crate::samplers::zerotwosequence::ZeroTwoSequenceSampler;
 // see sampler.h
 
 pub enum Sampler {
+    Adaptive(AdaptiveSampler),
+    Block(BlockSampler),
+    Cmj(CmjSampler),
+    /// User-defined sampler, see **SamplerTrait**
+    Custom(Box<dyn SamplerTrait + Send + Sync>),
     Halton(HaltonSampler),
     MaxMinDist(MaxMinDistSampler),
     MLT(MLTSampler),
@@ -26,9 +40,55 @@ pub enum Sampler {
     ZeroTwoSequence(ZeroTwoSequenceSampler),
 }
 
+/// Interface implemented by all samplers. Samplers implemented
+/// outside of this crate can be wrapped in **Sampler::Custom** (see
+/// *Sampler::from_trait()* and *Sampler::from_shared()*) to render
+/// with them, the built-in samplers keep their static dispatch.
+pub trait SamplerTrait {
+    /// Return a new sampler (of the same kind), seeded with *seed*,
+    /// e.g. for each render tile.
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync>;
+    fn start_pixel(&mut self, p: Point2i);
+    fn get_1d(&mut self) -> Float;
+    fn get_2d(&mut self) -> Point2f;
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f;
+    fn request_2d_array(&mut self, n: i32);
+    fn round_count(&self, count: i32) -> i32 {
+        count
+    }
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]>;
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize);
+    fn start_next_sample(&mut self) -> bool;
+    fn reseed(&mut self, seed: u64);
+    fn get_current_pixel(&self) -> Point2i;
+    fn get_current_sample_number(&self) -> i64;
+    fn get_samples_per_pixel(&self) -> i64;
+    // PixelSampler
+    fn ensure_sampled_dimensions(&mut self, _n: usize) {}
+    // GlobalSampler
+    fn set_sample_number(&mut self, _sample_num: i64) -> bool {
+        false
+    }
+}
+
 impl Sampler {
+    /// Wraps a user-defined sampler, e.g. to pass it to an
+    /// integrator's *new()*.
+    pub fn from_trait(sampler: Box<dyn SamplerTrait + Send + Sync>) -> Box<Sampler> {
+        Box::new(Sampler::Custom(sampler))
+    }
+    /// Like *from_trait()* for a sampler shared with other code. The
+    /// renderer only uses it as a prototype (render threads get their
+    /// own copies from *clone_with_seed()*), so a copy is wrapped.
+    pub fn from_shared(sampler: Arc<dyn SamplerTrait + Send + Sync>) -> Box<Sampler> {
+        Sampler::from_trait(sampler.clone_with_seed(0_u64))
+    }
     pub fn clone_with_seed(&self, seed: u64) -> Box<Sampler> {
         match self {
+            Sampler::Adaptive(sampler) => sampler.clone_with_seed(seed),
+            Sampler::Block(sampler) => sampler.clone_with_seed(seed),
+            Sampler::Cmj(sampler) => sampler.clone_with_seed(seed),
+            Sampler::Custom(sampler) => Box::new(Sampler::Custom(sampler.clone_with_seed(seed))),
             Sampler::Halton(sampler) => sampler.clone_with_seed(seed),
             Sampler::MaxMinDist(sampler) => sampler.clone_with_seed(seed),
             Sampler::MLT(sampler) => sampler.clone_with_seed(seed),
@@ -40,6 +100,10 @@ impl Sampler {
     }
     pub fn start_pixel(&mut self, p: Point2i) {
         match self {
+            Sampler::Adaptive(sampler) => sampler.start_pixel(p),
+            Sampler::Block(sampler) => sampler.start_pixel(p),
+            Sampler::Cmj(sampler) => sampler.start_pixel(p),
+            Sampler::Custom(sampler) => sampler.start_pixel(p),
             Sampler::Halton(sampler) => sampler.start_pixel(p),
             Sampler::MaxMinDist(sampler) => sampler.start_pixel(p),
             Sampler::MLT(sampler) => sampler.start_pixel(p),
@@ -51,6 +115,10 @@ impl Sampler {
     }
     pub fn get_1d(&mut self) -> Float {
         match self {
+            Sampler::Adaptive(sampler) => sampler.get_1d(),
+            Sampler::Block(sampler) => sampler.get_1d(),
+            Sampler::Cmj(sampler) => sampler.get_1d(),
+            Sampler::Custom(sampler) => sampler.get_1d(),
             Sampler::Halton(sampler) => sampler.get_1d(),
             Sampler::MaxMinDist(sampler) => sampler.get_1d(),
             Sampler::MLT(sampler) => sampler.get_1d(),
@@ -62,6 +130,10 @@ impl Sampler {
     }
     pub fn get_2d(&mut self) -> Point2f {
         match self {
+            Sampler::Adaptive(sampler) => sampler.get_2d(),
+            Sampler::Block(sampler) => sampler.get_2d(),
+            Sampler::Cmj(sampler) => sampler.get_2d(),
+            Sampler::Custom(sampler) => sampler.get_2d(),
             Sampler::Halton(sampler) => sampler.get_2d(),
             Sampler::MaxMinDist(sampler) => sampler.get_2d(),
             Sampler::MLT(sampler) => sampler.get_2d(),
@@ -73,6 +145,10 @@ impl Sampler {
     }
     pub fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
         match self {
+            Sampler::Adaptive(sampler) => sampler.get_2d_sample(array_idx, idx),
+            Sampler::Block(sampler) => sampler.get_2d_sample(array_idx, idx),
+            Sampler::Cmj(sampler) => sampler.get_2d_sample(array_idx, idx),
+            Sampler::Custom(sampler) => sampler.get_2d_sample(array_idx, idx),
             Sampler::Halton(sampler) => sampler.get_2d_sample(array_idx, idx),
             Sampler::MaxMinDist(sampler) => sampler.get_2d_sample(array_idx, idx),
             Sampler::MLT(sampler) => sampler.get_2d_sample(array_idx, idx),
@@ -92,8 +168,71 @@ impl Sampler {
         cs.p_lens = self.get_2d();
         cs
     }
+    /// Runs a full sample loop for pixel *p* and writes the 2D film
+    /// sample offsets (within the pixel) as CSV, one *x,y* line per
+    /// pixel sample. Useful to inspect the stratification of a
+    /// sampler; the sampler has to be restarted (*start_pixel()*)
+    /// afterwards.
+    pub fn dump_pixel_samples(&mut self, p: Point2i, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "x,y")?;
+        self.start_pixel(p);
+        loop {
+            // the first 2D sample is the film sample (see get_camera_sample())
+            let u: Point2f = self.get_2d();
+            writeln!(out, "{},{}", u.x, u.y)?;
+            if !self.start_next_sample() {
+                break;
+            }
+        }
+        Ok(())
+    }
+    /// Runs the full sample loop for pixel *p* and returns all camera
+    /// samples at once (in the order *get_camera_sample()* would
+    /// return them), for integrators which process a pixel's samples
+    /// in a batch. Other sample dimensions (e.g. sample arrays) aren't
+    /// available afterwards, the sampler has to be restarted
+    /// (*start_pixel()*) to draw them.
+    pub fn get_all_camera_samples(&mut self, p: Point2i) -> Vec<CameraSample> {
+        let mut samples: Vec<CameraSample> =
+            Vec::with_capacity(self.get_samples_per_pixel().max(0) as usize);
+        self.start_pixel(p);
+        loop {
+            samples.push(self.get_camera_sample(p));
+            if !self.start_next_sample() {
+                break;
+            }
+        }
+        samples
+    }
+    /// Runs a throwaway sample loop for a dummy pixel, so allocations
+    /// and precomputations (sample arrays, RNG, Halton offsets, ...)
+    /// don't end up in the timing of a benchmark. The sampler is
+    /// reseeded first, call *reseed()* and *start_pixel()* afterwards,
+    /// then samples are the same as without warming up.
+    pub fn warm_up(&mut self) {
+        let p: Point2i = match self {
+            // don't record statistics for the dummy pixel
+            Sampler::Adaptive(sampler) => return sampler.inner.warm_up(),
+            // Markov chain state, nothing to warm up
+            Sampler::MLT(_) => return,
+            Sampler::Sobol(sampler) => sampler.sample_bounds.p_min,
+            _ => Point2i::default(),
+        };
+        self.reseed(0_u64);
+        self.start_pixel(p);
+        loop {
+            self.get_camera_sample(p);
+            if !self.start_next_sample() {
+                break;
+            }
+        }
+    }
     pub fn request_2d_array(&mut self, n: i32) {
         match self {
+            Sampler::Adaptive(sampler) => sampler.request_2d_array(n),
+            Sampler::Block(sampler) => sampler.request_2d_array(n),
+            Sampler::Cmj(sampler) => sampler.request_2d_array(n),
+            Sampler::Custom(sampler) => sampler.request_2d_array(n),
             Sampler::Halton(sampler) => sampler.request_2d_array(n),
             Sampler::MaxMinDist(sampler) => sampler.request_2d_array(n),
             Sampler::MLT(sampler) => sampler.request_2d_array(n),
@@ -105,6 +244,10 @@ impl Sampler {
     }
     pub fn round_count(&self, count: i32) -> i32 {
         match self {
+            Sampler::Adaptive(sampler) => sampler.round_count(count),
+            Sampler::Block(sampler) => sampler.round_count(count),
+            Sampler::Cmj(sampler) => sampler.round_count(count),
+            Sampler::Custom(sampler) => sampler.round_count(count),
             Sampler::Halton(sampler) => sampler.round_count(count),
             Sampler::MaxMinDist(sampler) => sampler.round_count(count),
             Sampler::MLT(sampler) => sampler.round_count(count),
@@ -116,6 +259,10 @@ impl Sampler {
     }
     pub fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
         match self {
+            Sampler::Adaptive(sampler) => sampler.get_2d_array(n),
+            Sampler::Block(sampler) => sampler.get_2d_array(n),
+            Sampler::Cmj(sampler) => sampler.get_2d_array(n),
+            Sampler::Custom(sampler) => sampler.get_2d_array(n),
             Sampler::Halton(sampler) => sampler.get_2d_array(n),
             Sampler::MaxMinDist(sampler) => sampler.get_2d_array(n),
             Sampler::MLT(sampler) => sampler.get_2d_array(n),
@@ -125,19 +272,36 @@ impl Sampler {
             Sampler::ZeroTwoSequence(sampler) => sampler.get_2d_array(n),
         }
     }
-    pub fn get_2d_arrays(&mut self, n: i32) -> (Option<&[Point2f]>, Option<&[Point2f]>) {
-        match self {
-            Sampler::Halton(sampler) => sampler.get_2d_arrays(n),
-            Sampler::MaxMinDist(sampler) => sampler.get_2d_arrays(n),
-            Sampler::MLT(sampler) => sampler.get_2d_arrays(n),
-            Sampler::Random(sampler) => sampler.get_2d_arrays(n),
-            Sampler::Sobol(sampler) => sampler.get_2d_arrays(n),
-            Sampler::Stratified(sampler) => sampler.get_2d_arrays(n),
-            Sampler::ZeroTwoSequence(sampler) => sampler.get_2d_arrays(n),
+    /// Copies the next sample array of *n* 2D samples into *out*.
+    /// Unlike *get_2d_array()* the result isn't borrowed from the
+    /// sampler, so several arrays can be held at the same time.
+    /// Returns **false** (and leaves *out* empty) once all requested
+    /// arrays of the current pixel sample have been consumed.
+    pub fn fill_2d_array(&mut self, n: i32, out: &mut Vec<Point2f>) -> bool {
+        out.clear();
+        if let Some(array) = self.get_2d_array(n) {
+            out.extend_from_slice(array);
+            true
+        } else {
+            false
         }
     }
+    /// Fills the next two sample arrays of *n* 2D samples, see
+    /// *fill_2d_array()*.
+    pub fn get_2d_arrays(
+        &mut self,
+        n: i32,
+        out1: &mut Vec<Point2f>,
+        out2: &mut Vec<Point2f>,
+    ) -> bool {
+        self.fill_2d_array(n, out1) && self.fill_2d_array(n, out2)
+    }
     pub fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
         match self {
+            Sampler::Adaptive(sampler) => sampler.get_2d_array_idxs(n),
+            Sampler::Block(sampler) => sampler.get_2d_array_idxs(n),
+            Sampler::Cmj(sampler) => sampler.get_2d_array_idxs(n),
+            Sampler::Custom(sampler) => sampler.get_2d_array_idxs(n),
             Sampler::Halton(sampler) => sampler.get_2d_array_idxs(n),
             Sampler::MaxMinDist(sampler) => sampler.get_2d_array_idxs(n),
             Sampler::MLT(sampler) => sampler.get_2d_array_idxs(n),
@@ -149,6 +313,10 @@ impl Sampler {
     }
     pub fn start_next_sample(&mut self) -> bool {
         match self {
+            Sampler::Adaptive(sampler) => sampler.start_next_sample(),
+            Sampler::Block(sampler) => sampler.start_next_sample(),
+            Sampler::Cmj(sampler) => sampler.start_next_sample(),
+            Sampler::Custom(sampler) => sampler.start_next_sample(),
             Sampler::Halton(sampler) => sampler.start_next_sample(),
             Sampler::MaxMinDist(sampler) => sampler.start_next_sample(),
             Sampler::MLT(sampler) => sampler.start_next_sample(),
@@ -160,6 +328,10 @@ impl Sampler {
     }
     pub fn reseed(&mut self, seed: u64) {
         match self {
+            Sampler::Adaptive(sampler) => sampler.reseed(seed),
+            Sampler::Block(sampler) => sampler.reseed(seed),
+            Sampler::Cmj(sampler) => sampler.reseed(seed),
+            Sampler::Custom(sampler) => sampler.reseed(seed),
             Sampler::Halton(sampler) => sampler.reseed(seed),
             Sampler::MaxMinDist(sampler) => sampler.reseed(seed),
             Sampler::MLT(sampler) => sampler.reseed(seed),
@@ -171,6 +343,10 @@ impl Sampler {
     }
     pub fn get_current_pixel(&self) -> Point2i {
         match self {
+            Sampler::Adaptive(sampler) => sampler.get_current_pixel(),
+            Sampler::Block(sampler) => sampler.get_current_pixel(),
+            Sampler::Cmj(sampler) => sampler.get_current_pixel(),
+            Sampler::Custom(sampler) => sampler.get_current_pixel(),
             Sampler::Halton(sampler) => sampler.get_current_pixel(),
             Sampler::MaxMinDist(sampler) => sampler.get_current_pixel(),
             Sampler::MLT(sampler) => sampler.get_current_pixel(),
@@ -182,6 +358,10 @@ impl Sampler {
     }
     pub fn get_current_sample_number(&self) -> i64 {
         match self {
+            Sampler::Adaptive(sampler) => sampler.get_current_sample_number(),
+            Sampler::Block(sampler) => sampler.get_current_sample_number(),
+            Sampler::Cmj(sampler) => sampler.get_current_sample_number(),
+            Sampler::Custom(sampler) => sampler.get_current_sample_number(),
             Sampler::Halton(sampler) => sampler.get_current_sample_number(),
             Sampler::MaxMinDist(sampler) => sampler.get_current_sample_number(),
             Sampler::MLT(sampler) => sampler.get_current_sample_number(),
@@ -193,6 +373,10 @@ impl Sampler {
     }
     pub fn get_samples_per_pixel(&self) -> i64 {
         match self {
+            Sampler::Adaptive(sampler) => sampler.get_samples_per_pixel(),
+            Sampler::Block(sampler) => sampler.get_samples_per_pixel(),
+            Sampler::Cmj(sampler) => sampler.get_samples_per_pixel(),
+            Sampler::Custom(sampler) => sampler.get_samples_per_pixel(),
             Sampler::Halton(sampler) => sampler.get_samples_per_pixel(),
             Sampler::MaxMinDist(sampler) => sampler.get_samples_per_pixel(),
             Sampler::MLT(sampler) => sampler.get_samples_per_pixel(),
@@ -202,12 +386,221 @@ impl Sampler {
             Sampler::ZeroTwoSequence(sampler) => sampler.get_samples_per_pixel(),
         }
     }
+    // PixelSampler
+    pub fn ensure_sampled_dimensions(&mut self, n: usize) {
+        match self {
+            Sampler::Adaptive(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::Block(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::Cmj(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::Custom(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::Halton(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::MaxMinDist(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::MLT(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::Random(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::Sobol(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::Stratified(sampler) => sampler.ensure_sampled_dimensions(n),
+            Sampler::ZeroTwoSequence(sampler) => sampler.ensure_sampled_dimensions(n),
+        }
+    }
+    /// Per-pixel sample counts (e.g. from an importance map); only
+    /// supported by the **StratifiedSampler** (or an
+    /// **AdaptiveSampler** wrapping one).
+    pub fn set_pixel_budget(&mut self, budget: Arc<PixelSampleBudget>) {
+        match self {
+            Sampler::Adaptive(sampler) => sampler.set_pixel_budget(budget),
+            Sampler::Stratified(sampler) => sampler.pixel_budget = Some(budget),
+            _ => println!("WARNING: Sampler ignores per-pixel sample budget"),
+        }
+    }
+    /// Seed mixed into all (per tile or per pixel) seeds of the
+    /// random samplers, e.g. derived from the scene file (see
+    /// *seed_from_params()*). Deterministic samplers don't use a seed
+    /// and ignore it.
+    pub fn set_base_seed(&mut self, base_seed: u64) {
+        match self {
+            Sampler::Adaptive(sampler) => sampler.inner.set_base_seed(base_seed),
+            Sampler::Block(sampler) => sampler.base.base_seed = base_seed,
+            Sampler::Cmj(sampler) => sampler.base_seed = base_seed,
+            Sampler::MaxMinDist(sampler) => sampler.base_seed = base_seed,
+            Sampler::Random(sampler) => sampler.base_seed = base_seed,
+            Sampler::Sobol(sampler) => sampler.base_seed = base_seed,
+            Sampler::Stratified(sampler) => sampler.base_seed = base_seed,
+            Sampler::ZeroTwoSequence(sampler) => sampler.base_seed = base_seed,
+            _ => {}
+        }
+    }
+    /// Reports the luminance *y* of the current pixel sample, which
+    /// the **AdaptiveSampler** uses to decide when a pixel has
+    /// converged. Other samplers ignore it.
+    pub fn add_sample_luminance(&mut self, y: Float) {
+        if let Sampler::Adaptive(sampler) = self {
+            sampler.add_sample_luminance(y);
+        }
+    }
+    /// Samples each pixel actually used (**AdaptiveSampler** only).
+    pub fn get_adaptive_stats(&self) -> Option<Arc<AdaptiveSampleStats>> {
+        match self {
+            Sampler::Adaptive(sampler) => Some(sampler.stats.clone()),
+            _ => None,
+        }
+    }
+    /// Unwraps the sampler into a trait object, e.g. to hand a
+    /// built-in sampler to code which only knows **SamplerTrait**.
+    pub fn into_trait(self) -> Box<dyn SamplerTrait + Send + Sync> {
+        match self {
+            Sampler::Adaptive(sampler) => Box::new(sampler),
+            Sampler::Block(sampler) => Box::new(sampler),
+            Sampler::Cmj(sampler) => Box::new(sampler),
+            Sampler::Custom(sampler) => sampler,
+            Sampler::Halton(sampler) => Box::new(sampler),
+            Sampler::MaxMinDist(sampler) => Box::new(sampler),
+            Sampler::MLT(sampler) => Box::new(sampler),
+            Sampler::Random(sampler) => Box::new(sampler),
+            Sampler::Sobol(sampler) => Box::new(sampler),
+            Sampler::Stratified(sampler) => Box::new(sampler),
+            Sampler::ZeroTwoSequence(sampler) => Box::new(sampler),
+        }
+    }
     // GlobalSampler
     pub fn set_sample_number(&mut self, sample_num: i64) -> bool {
         match self {
+            Sampler::Adaptive(sampler) => sampler.set_sample_number(sample_num),
+            Sampler::Block(sampler) => sampler.set_sample_number(sample_num),
+            Sampler::Cmj(sampler) => sampler.set_sample_number(sample_num),
+            Sampler::Custom(sampler) => sampler.set_sample_number(sample_num),
             Sampler::Halton(sampler) => sampler.set_sample_number(sample_num),
+            Sampler::MaxMinDist(sampler) => sampler.set_sample_number(sample_num),
+            Sampler::MLT(sampler) => sampler.set_sample_number(sample_num),
+            Sampler::Random(sampler) => sampler.set_sample_number(sample_num),
             Sampler::Sobol(sampler) => sampler.set_sample_number(sample_num),
-            _ => false,
+            Sampler::Stratified(sampler) => sampler.set_sample_number(sample_num),
+            Sampler::ZeroTwoSequence(sampler) => sampler.set_sample_number(sample_num),
         }
     }
 }
+
+/// Frame of an animation sequence a sampler should generate samples
+/// for, from the *"frameindex"* parameter. With *"freezenoise"* the
+/// frame index is ignored, so all frames share the same noise (which
+/// is what temporal denoisers prefer).
+pub fn frame_index_from_params(params: &ParamSet) -> u64 {
+    let freeze_noise: bool = params.find_one_bool("freezenoise", false);
+    if freeze_noise {
+        0_u64
+    } else {
+        params.find_one_int("frameindex", 0).max(0) as u64
+    }
+}
+
+/// Base seed of a sampler from the *"seed"* parameter, zero (the
+/// seeds of the integrator stay unchanged) by default.
+pub fn seed_from_params(params: &ParamSet) -> u64 {
+    params.find_one_int("seed", 0) as u32 as u64
+}
+
+/// Mix the *frame_index* into a (per tile or per pixel) *seed*, so
+/// consecutive frames get different, but deterministic, sequences.
+/// Frame 0 keeps the seed unchanged. Deterministic samplers
+/// (**HaltonSampler**, **SobolSampler**) don't use a seed and
+/// therefore ignore the frame index.
+pub fn frame_seed(seed: u64, frame_index: u64) -> u64 {
+    if frame_index == 0_u64 {
+        seed
+    } else {
+        seed ^ mix_bits(frame_index)
+    }
+}
+
+/// Number of samples for each pixel of the sample bounds, allocated
+/// proportional to an importance map. Each pixel gets at least one
+/// sample and the counts add up to the total budget.
+#[derive(Debug, Clone)]
+pub struct PixelSampleBudget {
+    pub bounds: Bounds2i,
+    pub spp: Vec<i64>,
+}
+
+impl PixelSampleBudget {
+    /// Distribute *total_budget* samples over the pixels of *bounds*
+    /// proportional to *importance* (given at resolution *map_res*
+    /// and upscaled to the pixel bounds using the nearest map pixel).
+    pub fn new(
+        importance: &[Float],
+        map_res: Point2i,
+        bounds: Bounds2i,
+        total_budget: u64,
+    ) -> Self {
+        assert_eq!(importance.len(), (map_res.x * map_res.y) as usize);
+        let res: Point2i = Point2i {
+            x: bounds.p_max.x - bounds.p_min.x,
+            y: bounds.p_max.y - bounds.p_min.y,
+        };
+        let n_pixels: u64 = (res.x * res.y) as u64;
+        let mut total_budget: u64 = total_budget;
+        if total_budget < n_pixels {
+            println!(
+                "WARNING: Sample budget {} too small for {} pixels, using one sample per pixel",
+                total_budget, n_pixels
+            );
+            total_budget = n_pixels;
+        }
+        // upscale importance map to pixel bounds
+        let mut weights: Vec<f64> = Vec::with_capacity(n_pixels as usize);
+        for y in 0..res.y {
+            for x in 0..res.x {
+                let mx: i32 = (x * map_res.x / res.x).min(map_res.x - 1);
+                let my: i32 = (y * map_res.y / res.y).min(map_res.y - 1);
+                weights.push(importance[(my * map_res.x + mx) as usize].max(0.0 as Float) as f64);
+            }
+        }
+        let mut weight_sum: f64 = weights.iter().sum();
+        if weight_sum <= 0.0 {
+            // no importance at all, distribute uniformly
+            weights.iter_mut().for_each(|w| *w = 1.0);
+            weight_sum = n_pixels as f64;
+        }
+        // one sample per pixel, the rest proportional to importance
+        // (largest remainder method, so the counts add up exactly)
+        let extra: u64 = total_budget - n_pixels;
+        let mut spp: Vec<i64> = Vec::with_capacity(n_pixels as usize);
+        let mut remainders: Vec<(f64, usize)> = Vec::with_capacity(n_pixels as usize);
+        let mut assigned: u64 = 0_u64;
+        for (i, w) in weights.iter().enumerate() {
+            let share: f64 = extra as f64 * w / weight_sum;
+            let n: u64 = share.floor() as u64;
+            assigned += n;
+            spp.push(1_i64 + n as i64);
+            remainders.push((share - share.floor(), i));
+        }
+        remainders.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        for (_remainder, i) in remainders.iter().take((extra - assigned) as usize) {
+            spp[*i] += 1;
+        }
+        PixelSampleBudget { bounds, spp }
+    }
+    /// Uses the luminance of an image as importance.
+    pub fn from_image(map: &DynamicImage, bounds: Bounds2i, total_budget: u64) -> Self {
+        let luma = map.to_luma8();
+        let map_res: Point2i = Point2i {
+            x: luma.width() as i32,
+            y: luma.height() as i32,
+        };
+        let importance: Vec<Float> = luma.pixels().map(|p| Float::from(p[0])).collect();
+        PixelSampleBudget::new(&importance, map_res, bounds, total_budget)
+    }
+    /// Samples for pixel *p* (a single one outside of the bounds).
+    pub fn get(&self, p: Point2i) -> i64 {
+        if p.x < self.bounds.p_min.x
+            || p.y < self.bounds.p_min.y
+            || p.x >= self.bounds.p_max.x
+            || p.y >= self.bounds.p_max.y
+        {
+            return 1_i64;
+        }
+        let width: i32 = self.bounds.p_max.x - self.bounds.p_min.x;
+        self.spp[((p.y - self.bounds.p_min.y) * width + p.x - self.bounds.p_min.x) as usize]
+    }
+    pub fn total(&self) -> u64 {
+        self.spp.iter().sum::<i64>() as u64
+    }
+}
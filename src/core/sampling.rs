@@ -4,8 +4,9 @@
 use std::f32::consts::PI;
 use std::sync::Arc;
 // pbrt
-use crate::core::geometry::{Point2f, Vector2f, Vector3f, XYEnum};
+use crate::core::geometry::{Point2f, Point2i, Vector2f, Vector3f, XYEnum};
 use crate::core::pbrt::clamp_t;
+use crate::core::pbrt::mix_bits;
 use crate::core::pbrt::Float;
 use crate::core::pbrt::{INV_2_PI, INV_4_PI, INV_PI, PI_OVER_2, PI_OVER_4};
 use crate::core::rng::Rng;
@@ -211,6 +212,85 @@ pub fn shuffle<T>(samp: &mut [T], count: i32, n_dimensions: i32, rng: &mut Rng)
     }
 }
 
+/// Toroidally shift a 2D sample by *offset* (Cranley-Patterson
+/// rotation). Rotating a stratified set keeps it stratified (with
+/// respect to the shifted strata).
+pub fn cranley_patterson_rotation(u: Point2f, offset: Point2f) -> Point2f {
+    let x: Float = u.x + offset.x;
+    let y: Float = u.y + offset.y;
+    Point2f {
+        x: (x - x.floor()).min(FLOAT_ONE_MINUS_EPSILON),
+        y: (y - y.floor()).min(FLOAT_ONE_MINUS_EPSILON),
+    }
+}
+
+/// Deterministic offset in [0, 1)^2 for a Cranley-Patterson
+/// rotation, taken from the hash of *key*.
+pub fn hash_offset_2d(key: u64) -> Point2f {
+    let h: u64 = mix_bits(key);
+    let inv_2_32: f64 = 1.0 / 4_294_967_296.0;
+    Point2f {
+        x: ((h & 0xffff_ffff) as f64 * inv_2_32) as Float,
+        y: ((h >> 32) as f64 * inv_2_32) as Float,
+    }
+}
+
+/// Deterministic Cranley-Patterson rotation of pixel *p*.
+pub fn pixel_offset_2d(p: Point2i) -> Point2f {
+    hash_offset_2d(((p.x as u32 as u64) << 32) | p.y as u32 as u64)
+}
+
+/// Resampled importance sampling (RIS): given *M* candidates, each
+/// drawn from a source distribution and stored with its source pdf,
+/// select one with probability proportional to *target_fn(x) /
+/// pdf(x)* using the random number *u*. Returns the selected sample
+/// and its weight *(sum of weights / M) / target_fn(x)*, so that
+/// *f(x) * weight* is an unbiased estimate of the integral of *f*
+/// (for a target function proportional to *f*). Returns **None** if
+/// all candidates have zero weight.
+pub fn ris_select<T>(
+    candidates: Vec<(T, Float)>,
+    target_fn: impl Fn(&T) -> Float,
+    u: Float,
+) -> Option<(T, Float)> {
+    let m: usize = candidates.len();
+    let mut weights: Vec<Float> = Vec::with_capacity(m);
+    let mut targets: Vec<Float> = Vec::with_capacity(m);
+    let mut w_sum: Float = 0.0 as Float;
+    for (x, pdf) in &candidates {
+        let target: Float = target_fn(x);
+        let w: Float = if *pdf > 0.0 as Float && target > 0.0 as Float {
+            target / *pdf
+        } else {
+            0.0 as Float
+        };
+        w_sum += w;
+        weights.push(w);
+        targets.push(target);
+    }
+    if w_sum <= 0.0 as Float {
+        return None;
+    }
+    // select candidate proportional to its weight
+    let threshold: Float = u * w_sum;
+    let mut selected: usize = 0;
+    let mut cumulative: Float = 0.0 as Float;
+    for (i, w) in weights.iter().enumerate() {
+        if *w > 0.0 as Float {
+            selected = i;
+            cumulative += *w;
+            if cumulative > threshold {
+                break;
+            }
+        }
+    }
+    let weight: Float = (w_sum / m as Float) / targets[selected];
+    candidates
+        .into_iter()
+        .nth(selected)
+        .map(|(x, _pdf)| (x, weight))
+}
+
 /// Cosine-weighted hemisphere sampling using Malley's method.
 pub fn cosine_sample_hemisphere(u: Point2f) -> Vector3f {
     let d: Point2f = concentric_sample_disk(u);
@@ -225,6 +305,14 @@ pub fn cosine_hemisphere_pdf(cos_theta: Float) -> Float {
     cos_theta * INV_PI
 }
 
+/// Multiple importance sampling weight proportional to the sample
+/// densities (Veach's balance heuristic).
+pub fn balance_heuristic(nf: u8, f_pdf: Float, ng: u8, g_pdf: Float) -> Float {
+    let f: Float = nf as Float * f_pdf;
+    let g: Float = ng as Float * g_pdf;
+    f / (f + g)
+}
+
 /// Reducing the variance according to Veach's heuristic.
 pub fn power_heuristic(nf: u8, f_pdf: Float, ng: u8, g_pdf: Float) -> Float {
     let f: Float = nf as Float * f_pdf;
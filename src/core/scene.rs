@@ -4,33 +4,106 @@
 //!
 
 // std
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 // pbrt
-use crate::core::geometry::{Bounds3f, Ray, Vector3f};
+use crate::core::geometry::{Bounds3f, Point3f, Ray, Vector3f};
+use crate::core::integrator::compute_light_power_distribution;
 use crate::core::interaction::{Interaction, SurfaceInteraction};
-use crate::core::light::{Light, LightFlags};
-use crate::core::pbrt::Spectrum;
+use crate::core::light::{is_delta_light, Light, LightFlags, VisibilityCache};
+use crate::core::pbrt::{Float, Spectrum};
 use crate::core::primitive::Primitive;
 use crate::core::sampler::Sampler;
+use crate::core::sampling::Distribution1D;
 
 // see scene.h
 
+/// Coordinates of unbounded (or non-finite) scene bounds get clamped
+/// to this extent, so lights at infinity still get a finite bounding
+/// sphere
+pub const MAX_WORLD_EXTENT: Float = 1.0e6;
+
+/// Replaces non-finite coordinates of *b* with -/+ *MAX_WORLD_EXTENT*
+/// (empty bounds are kept as they are).
+fn clamp_world_bound(b: Bounds3f) -> Bounds3f {
+    let clamp_min = |v: Float| if v.is_finite() { v } else { -MAX_WORLD_EXTENT };
+    let clamp_max = |v: Float| if v.is_finite() { v } else { MAX_WORLD_EXTENT };
+    Bounds3f {
+        p_min: Point3f {
+            x: clamp_min(b.p_min.x),
+            y: clamp_min(b.p_min.y),
+            z: clamp_min(b.p_min.z),
+        },
+        p_max: Point3f {
+            x: clamp_max(b.p_max.x),
+            y: clamp_max(b.p_max.y),
+            z: clamp_max(b.p_max.z),
+        },
+    }
+}
+
+/// Bounding sphere of *b*. Empty or degenerate bounds (e.g. a scene
+/// with nothing but lights) get a unit sphere instead, so lights at
+/// infinity still have a positive radius to push shadow rays out to.
+fn world_bounding_sphere(b: &Bounds3f) -> (Point3f, Float) {
+    let mut center: Point3f = Point3f::default();
+    let mut radius: Float = 0.0;
+    Bounds3f::bounding_sphere(b, &mut center, &mut radius);
+    if radius > 0.0 as Float {
+        (center, radius)
+    } else if b.p_min.x <= b.p_max.x && b.p_min.y <= b.p_max.y && b.p_min.z <= b.p_max.z {
+        (center, 1.0 as Float)
+    } else {
+        (Point3f::default(), 1.0 as Float)
+    }
+}
+
+thread_local! {
+    // rays traced by this thread, not yet added to the scene totals
+    // (see *Scene::flush_ray_counts()*)
+    static N_INTERSECTION_TESTS: Cell<u64> = const { Cell::new(0_u64) };
+    static N_SHADOW_TESTS: Cell<u64> = const { Cell::new(0_u64) };
+}
+
 #[derive(Clone)]
 pub struct Scene {
     pub lights: Vec<Arc<Light>>,
     pub infinite_lights: Vec<Arc<Light>>,
     pub aggregate: Arc<Primitive>,
     pub world_bound: Bounds3f,
+    /// Bounding sphere of *world_bound*, see *world_bounding_sphere()*
+    pub world_center: Point3f,
+    pub world_radius: Float,
+    /// Light selection proportional to the lights' *power()* (uniform
+    /// if no light emits anything), **None** without lights
+    pub light_power_distrib: Option<Arc<Distribution1D>>,
+    // statistics (shared between clones)
+    pub n_intersection_tests: Arc<AtomicU64>,
+    pub n_shadow_tests: Arc<AtomicU64>,
+    /// Optional cache of shadow ray results (static scenes only)
+    pub visibility_cache: Option<Arc<VisibilityCache>>,
 }
 
 impl Scene {
+    /// Computes the world bound of the *aggregate* (and its bounding
+    /// sphere) once, then calls *preprocess()* exactly once on every
+    /// light, so distant and infinite lights can cache the scene's
+    /// extent before rendering starts.
     pub fn new(aggregate: Arc<Primitive>, lights: Vec<Arc<Light>>) -> Self {
-        let world_bound: Bounds3f = aggregate.world_bound();
+        let world_bound: Bounds3f = clamp_world_bound(aggregate.world_bound());
+        let (world_center, world_radius) = world_bounding_sphere(&world_bound);
         let scene: Scene = Scene {
             lights: Vec::new(),
             infinite_lights: Vec::new(),
             aggregate: aggregate.clone(),
             world_bound,
+            world_center,
+            world_radius,
+            light_power_distrib: None,
+            n_intersection_tests: Arc::new(AtomicU64::new(0)),
+            n_shadow_tests: Arc::new(AtomicU64::new(0)),
+            visibility_cache: None,
         };
         let mut changed_lights = Vec::new();
         let mut infinite_lights = Vec::new();
@@ -42,18 +115,120 @@ impl Scene {
                 infinite_lights.push(light);
             }
         }
-        Scene {
+        let mut scene: Scene = Scene {
             lights: changed_lights,
             infinite_lights,
             aggregate,
             world_bound,
+            world_center,
+            world_radius,
+            light_power_distrib: None,
+            n_intersection_tests: scene.n_intersection_tests,
+            n_shadow_tests: scene.n_shadow_tests,
+            visibility_cache: None,
+        };
+        // the lights' power depends on the scene bounds (see above)
+        scene.light_power_distrib = compute_light_power_distribution(&scene);
+        scene
+    }
+    /// Memoize *VisibilityTester::unoccluded_from_light()* results
+    /// for point lights in an LRU cache holding up to *capacity*
+    /// entries, with shading points quantized to cells of size
+    /// *cell_size*. Refused (returns
+    /// false) if any geometry is animated, because cached results
+    /// would be wrong for other shutter times.
+    pub fn enable_visibility_cache(&mut self, capacity: usize, cell_size: Float) -> bool {
+        if self.aggregate.is_animated() {
+            println!("WARNING: visibility cache disabled for animated geometry");
+            self.visibility_cache = None;
+            return false;
         }
+        self.visibility_cache = Some(Arc::new(VisibilityCache::new(capacity, cell_size)));
+        true
     }
+    /// World space bounds of all geometry, computed once in
+    /// *Scene::new()*.
     pub fn world_bound(&self) -> Bounds3f {
         self.world_bound
     }
+    /// Center and radius of a sphere enclosing *world_bound()*, with
+    /// a positive radius even for empty scenes.
+    pub fn world_bounding_sphere(&self) -> (Point3f, Float) {
+        (self.world_center, self.world_radius)
+    }
+    /// Picks a light proportional to its power using the 1D sample
+    /// *u*, returns its index and the probability of picking it. A
+    /// scene without lights returns a zero probability.
+    pub fn sample_light(&self, u: Float) -> (usize, Float) {
+        if let Some(distrib) = &self.light_power_distrib {
+            let mut pdf: Float = 0.0 as Float;
+            let light_num: usize = distrib.sample_discrete(u, Some(&mut pdf));
+            (light_num, pdf)
+        } else {
+            (0_usize, 0.0 as Float)
+        }
+    }
+    /// Probability of *sample_light()* picking the light with index
+    /// *light_num*.
+    pub fn light_pdf(&self, light_num: usize) -> Float {
+        match &self.light_power_distrib {
+            Some(distrib) if light_num < distrib.count() => distrib.discrete_pdf(light_num),
+            _ => 0.0 as Float,
+        }
+    }
+    /// Adds the rays traced by the calling thread since the last
+    /// flush to the scene totals. Rays are counted per thread, render
+    /// loops call this once per tile (instead of touching the shared
+    /// counters for every ray).
+    pub fn flush_ray_counts(&self) {
+        let n: u64 = N_INTERSECTION_TESTS.with(|n| n.replace(0_u64));
+        if n > 0 {
+            self.n_intersection_tests.fetch_add(n, Ordering::Relaxed);
+        }
+        let n: u64 = N_SHADOW_TESTS.with(|n| n.replace(0_u64));
+        if n > 0 {
+            self.n_shadow_tests.fetch_add(n, Ordering::Relaxed);
+        }
+    }
+    /// Number of rays traced so far (intersection and shadow tests),
+    /// including the ones of the calling thread which weren't flushed
+    /// yet.
+    pub fn get_rays_traced(&self) -> u64 {
+        self.flush_ray_counts();
+        self.n_intersection_tests.load(Ordering::Relaxed)
+            + self.n_shadow_tests.load(Ordering::Relaxed)
+    }
+    /// Density of sampling direction *wi* from *iref* via next event
+    /// estimation, i.e. *pdf_li()* of the hit *light* times the
+    /// probability of choosing it, either from *light_distrib* (the
+    /// integrator's light selection distribution) or uniformly. Use it
+    /// to weight BSDF samples hitting an emitter against light
+    /// sampling, e.g. with the balance heuristic. Delta lights can't
+    /// be hit, lights not part of the scene aren't sampled, both
+    /// return zero.
+    pub fn light_pdf_for_hit(
+        &self,
+        light: &Arc<Light>,
+        iref: &dyn Interaction,
+        wi: Vector3f,
+        light_distrib: Option<&Distribution1D>,
+    ) -> Float {
+        if is_delta_light(light.get_flags()) {
+            return 0.0 as Float;
+        }
+        if let Some(light_num) = self.lights.iter().position(|l| Arc::ptr_eq(l, light)) {
+            let selection_pdf: Float = if let Some(distrib) = light_distrib {
+                distrib.discrete_pdf(light_num)
+            } else {
+                1.0 as Float / self.lights.len() as Float
+            };
+            light.pdf_li(iref, wi) * selection_pdf
+        } else {
+            0.0 as Float
+        }
+    }
     pub fn intersect(&self, ray: &mut Ray, isect: &mut SurfaceInteraction) -> bool {
-        // TODO: ++nIntersectionTests;
+        N_INTERSECTION_TESTS.with(|n| n.set(n.get() + 1));
         assert_ne!(
             ray.d,
             Vector3f {
@@ -65,7 +240,7 @@ impl Scene {
         self.aggregate.intersect(ray, isect)
     }
     pub fn intersect_p(&self, ray: &mut Ray) -> bool {
-        // TODO: ++nShadowTests;
+        N_SHADOW_TESTS.with(|n| n.set(n.get() + 1));
         assert_ne!(
             ray.d,
             Vector3f {
@@ -83,7 +258,7 @@ impl Scene {
         isect: &mut SurfaceInteraction,
         tr: &mut Spectrum,
     ) -> bool {
-        // let mut tr: Spectrum = Spectrum::new(1.0 as Float);
+        *tr = Spectrum::new(1.0 as Float);
         loop {
             // bool hit_surface = Intersect(ray, isect);
             let hit_surface: bool = self.intersect(ray, isect);
@@ -10,6 +10,7 @@ use strum_macros::EnumIter;
 // pbrt
 use crate::core::pbrt::Float;
 use crate::core::pbrt::{clamp_t, find_interval, lerp};
+use crate::core::sampling::Distribution1D;
 
 // see spectrum.h
 
@@ -1512,6 +1513,66 @@ pub fn blackbody_normalized(lambda: &[Float], n: usize, t: Float, le: &mut Vec<F
     }
 }
 
+/// Strategy to pick the hero wavelength in spectral mode.
+#[derive(Debug, Clone)]
+pub enum WavelengthSampling {
+    /// Uniform over the visible (tabulated CIE) range
+    Uniform,
+    /// Proportional to the CIE Y matching function
+    CieY(Distribution1D),
+}
+
+impl WavelengthSampling {
+    pub fn new(name: &str) -> Self {
+        if name == "ciey" {
+            WavelengthSampling::CieY(Distribution1D::new(CIE_Y.to_vec()))
+        } else {
+            if name != "uniform" {
+                println!(
+                    "WARNING: Wavelength sampling strategy \"{}\" unknown. Using \"uniform\".",
+                    name
+                );
+            }
+            WavelengthSampling::Uniform
+        }
+    }
+    /// Map a 1D sample to a wavelength (in nm) and its pdf.
+    pub fn sample(&self, u: Float) -> (Float, Float) {
+        let lambda_min: Float = CIE_LAMBDA[0];
+        let lambda_max: Float = CIE_LAMBDA[(N_CIE_SAMPLES - 1) as usize];
+        match self {
+            WavelengthSampling::Uniform => (
+                lerp(u, lambda_min, lambda_max),
+                1.0 as Float / (lambda_max - lambda_min),
+            ),
+            WavelengthSampling::CieY(distrib) => {
+                let mut pdf: Float = 0.0 as Float;
+                let x: Float = distrib.sample_continuous(u, Some(&mut pdf), None);
+                (
+                    lerp(x, lambda_min, lambda_max),
+                    pdf / (lambda_max - lambda_min),
+                )
+            }
+        }
+    }
+    pub fn pdf(&self, lambda: Float) -> Float {
+        let lambda_min: Float = CIE_LAMBDA[0];
+        let lambda_max: Float = CIE_LAMBDA[(N_CIE_SAMPLES - 1) as usize];
+        if lambda < lambda_min || lambda > lambda_max {
+            return 0.0 as Float;
+        }
+        match self {
+            WavelengthSampling::Uniform => 1.0 as Float / (lambda_max - lambda_min),
+            WavelengthSampling::CieY(distrib) => {
+                let x: Float = (lambda - lambda_min) / (lambda_max - lambda_min);
+                let offset: usize =
+                    ((x * distrib.count() as Float) as usize).min(distrib.count() - 1);
+                distrib.func[offset] / (distrib.func_int * (lambda_max - lambda_min))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum SpectrumType {
     Reflectance,
@@ -1605,6 +1666,32 @@ impl RGBSpectrum {
         xyz[2] *= scale;
         RGBSpectrum::from_xyz(&xyz, SpectrumType::Reflectance)
     }
+    /// Spectral value at wavelength *lambda* (in nm), using a simple
+    /// three band (blue, green, red) box upsampling of the RGB values.
+    pub fn sample_wavelength(&self, lambda: Float) -> Float {
+        if lambda < 490.0 as Float {
+            self.c[2]
+        } else if lambda < 590.0 as Float {
+            self.c[1]
+        } else {
+            self.c[0]
+        }
+    }
+    /// Monte Carlo estimate of the RGB color for a single spectral
+    /// value *v* at wavelength *lambda*, sampled with density *pdf*.
+    pub fn from_wavelength_sample(lambda: Float, v: Float, pdf: Float) -> RGBSpectrum {
+        if pdf == 0.0 as Float {
+            return RGBSpectrum::default();
+        }
+        let n: i32 = N_CIE_SAMPLES as i32;
+        let scale: Float = v / (CIE_Y_INTEGRAL * pdf);
+        let xyz: [Float; 3] = [
+            interpolate_spectrum_samples(&CIE_LAMBDA, &CIE_X, n, lambda) * scale,
+            interpolate_spectrum_samples(&CIE_LAMBDA, &CIE_Y, n, lambda) * scale,
+            interpolate_spectrum_samples(&CIE_LAMBDA, &CIE_Z, n, lambda) * scale,
+        ];
+        RGBSpectrum::from_xyz(&xyz, SpectrumType::Illuminant)
+    }
     // from CoefficientSpectrum
     pub fn is_black(&self) -> bool {
         for i in 0..3 {
@@ -1862,6 +1949,122 @@ pub fn interpolate_spectrum_samples(lambda: &[Float], vals: &[Float], n: i32, l:
     lerp(t, vals[offset], vals[offset + 1])
 }
 
+/// Average of the piecewise linear function given by the sample
+/// values over the wavelength range *[lambda_start, lambda_end]*.
+/// Outside of the sampled range the function is assumed to be
+/// constant (clamped to the first/last sample value).
+pub fn average_spectrum_samples(
+    lambda: &[Float],
+    vals: &[Float],
+    n: i32,
+    lambda_start: Float,
+    lambda_end: Float,
+) -> Float {
+    let n: usize = n as usize;
+    for i in 0..(n - 1) {
+        assert!(lambda[i + 1] > lambda[i]);
+    }
+    assert!(lambda_start < lambda_end);
+    // handle cases with out-of-bounds range or single sample only
+    if lambda_end <= lambda[0] {
+        return vals[0];
+    }
+    if lambda_start >= lambda[n - 1] {
+        return vals[n - 1];
+    }
+    if n == 1 {
+        return vals[0];
+    }
+    let mut sum: Float = 0.0 as Float;
+    // add contributions of constant segments before/after samples
+    if lambda_start < lambda[0] {
+        sum += vals[0] * (lambda[0] - lambda_start);
+    }
+    if lambda_end > lambda[n - 1] {
+        sum += vals[n - 1] * (lambda_end - lambda[n - 1]);
+    }
+    // advance to first relevant wavelength segment
+    let mut i: usize = 0;
+    while lambda_start > lambda[i + 1] {
+        i += 1;
+    }
+    // loop over wavelength sample segments and add contributions
+    let interp = |w: Float, i: usize| -> Float {
+        lerp(
+            (w - lambda[i]) / (lambda[i + 1] - lambda[i]),
+            vals[i],
+            vals[i + 1],
+        )
+    };
+    while i + 1 < n && lambda_end >= lambda[i] {
+        let seg_lambda_start: Float = lambda_start.max(lambda[i]);
+        let seg_lambda_end: Float = lambda_end.min(lambda[i + 1]);
+        sum += 0.5 as Float
+            * (interp(seg_lambda_start, i) + interp(seg_lambda_end, i))
+            * (seg_lambda_end - seg_lambda_start);
+        i += 1;
+    }
+    sum / (lambda_end - lambda_start)
+}
+
+/// Resample a piecewise linear spectrum (given at the possibly
+/// non-uniformly spaced, sorted wavelengths *lambdas*) to
+/// *target_grid*. Each target value is the average of the input over
+/// the wavelength range the target sample is responsible for (half
+/// way to its neighbours), so narrow spectral features are not
+/// aliased away and the integral is preserved. Beyond the input range
+/// the first/last value is used.
+pub fn resample_linear(lambdas: &[Float], values: &[Float], target_grid: &[Float]) -> Vec<Float> {
+    assert_eq!(lambdas.len(), values.len());
+    assert!(!lambdas.is_empty());
+    let n: i32 = lambdas.len() as i32;
+    let m: usize = target_grid.len();
+    if m == 1 {
+        // a single sample represents the whole input range
+        if n == 1 {
+            return vec![values[0]];
+        }
+        return vec![average_spectrum_samples(
+            lambdas,
+            values,
+            n,
+            lambdas[0],
+            lambdas[n as usize - 1],
+        )];
+    }
+    let mut resampled: Vec<Float> = Vec::with_capacity(m);
+    for i in 0..m {
+        let (lambda_start, lambda_end) = resample_bin(target_grid, i);
+        resampled.push(average_spectrum_samples(
+            lambdas,
+            values,
+            n,
+            lambda_start,
+            lambda_end,
+        ));
+    }
+    resampled
+}
+
+/// The wavelength range the *i*-th sample of a (sorted) grid is
+/// responsible for, half way to its neighbours. The outermost samples
+/// use a range of the same width on both sides.
+pub fn resample_bin(grid: &[Float], i: usize) -> (Float, Float) {
+    let m: usize = grid.len();
+    assert!(m > 1);
+    let lambda_start: Float = if i == 0 {
+        grid[0] - 0.5 as Float * (grid[1] - grid[0])
+    } else {
+        0.5 as Float * (grid[i - 1] + grid[i])
+    };
+    let lambda_end: Float = if i + 1 == m {
+        grid[m - 1] + 0.5 as Float * (grid[m - 1] - grid[m - 2])
+    } else {
+        0.5 as Float * (grid[i] + grid[i + 1])
+    };
+    (lambda_start, lambda_end)
+}
+
 pub fn inverse_gamma_convert_float(v: Float) -> Float {
     if v <= 0.04045 {
         v / 12.92
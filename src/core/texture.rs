@@ -399,7 +399,7 @@ pub fn turbulence(
         0.0 as Float,
         max_octaves as Float,
     );
-    let n_int: usize = n.floor() as usize;
+    let n_int: i32 = n.floor() as i32;
     // compute sum of octaves of noise for turbulence
     let mut sum: Float = 0.0;
     let mut lambda: Float = 1.0;
@@ -416,7 +416,7 @@ pub fn turbulence(
         0.2,
         noise_pnt3(&(*p * lambda)).abs(),
     );
-    for _i in n_int..max_octaves as usize {
+    for _i in n_int..max_octaves {
         sum += o * 0.2 as Float;
         o *= omega;
     }
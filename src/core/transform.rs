@@ -2093,6 +2093,10 @@ impl AnimatedTransform {
         // compute scale _S_ using rotation and original matrix
         *s = mtx_mul(&Matrix4x4::inverse(&r), &*m);
     }
+    /// True if start and end transform differ.
+    pub fn is_animated(&self) -> bool {
+        self.actually_animated
+    }
     pub fn interpolate(&self, time: Float, t: &mut Transform) {
         // handle boundary conditions for matrix interpolation
         if !self.actually_animated || time <= self.start_time {
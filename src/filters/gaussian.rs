@@ -6,6 +6,9 @@ use crate::core::pbrt::Float;
 
 // see gaussian.h
 
+/// Gaussian filter, shifted down so it reaches zero at the filter
+/// radius. Larger values of *alpha* fall off faster (and give sharper
+/// images).
 #[derive(Debug, Default, Copy, Clone)]
 pub struct GaussianFilter {
     pub alpha: Float,
@@ -17,24 +20,23 @@ pub struct GaussianFilter {
 }
 
 impl GaussianFilter {
-    pub fn create(ps: &ParamSet) -> Box<Filter> {
-        let xw: Float = ps.find_one_float("xwidth", 2.0);
-        let yw: Float = ps.find_one_float("ywidth", 2.0);
-        let alpha: Float = ps.find_one_float("alpha", 2.0);
-        // see gaussian.h (GaussianFilter constructor)
-        let exp_x: Float = (-alpha * xw * xw).exp();
-        let exp_y: Float = (-alpha * yw * yw).exp();
-        let gaussian_filter: Box<Filter> = Box::new(Filter::Gaussian(GaussianFilter {
+    pub fn new(xw: Float, yw: Float, alpha: Float) -> Self {
+        GaussianFilter {
             alpha,
-            exp_x,
-            exp_y,
+            exp_x: (-alpha * xw * xw).exp(),
+            exp_y: (-alpha * yw * yw).exp(),
             radius: Vector2f { x: xw, y: yw },
             inv_radius: Vector2f {
                 x: 1.0 / xw,
                 y: 1.0 / yw,
             },
-        }));
-        gaussian_filter
+        }
+    }
+    pub fn create(ps: &ParamSet) -> Box<Filter> {
+        let xw: Float = ps.find_one_float("xwidth", 2.0);
+        let yw: Float = ps.find_one_float("ywidth", 2.0);
+        let alpha: Float = ps.find_one_float("alpha", 2.0);
+        Box::new(Filter::Gaussian(GaussianFilter::new(xw, yw, alpha)))
     }
     pub fn gaussian(&self, d: Float, expv: Float) -> Float {
         (0.0 as Float).max((-self.alpha * d * d).exp() - expv)
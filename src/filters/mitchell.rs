@@ -1,27 +1,33 @@
+// pbrt
 use crate::core::filter::Filter;
 use crate::core::geometry::{Point2f, Vector2f};
 use crate::core::paramset::ParamSet;
 use crate::core::pbrt::Float;
 
+// see mitchell.h
+
+/// Mitchell-Netravali filter, a cubic with parameters *B* and *C*
+/// (1/3 each by default). It has negative lobes, which sharpen the
+/// image but can ring around edges.
 #[derive(Debug, Default, Copy, Clone)]
 pub struct MitchellNetravali {
-    width: Float,
-    height: Float,
-    inv_width: Float,
-    inv_height: Float,
-    b: Float,
-    c: Float,
+    pub b: Float,
+    pub c: Float,
+    // inherited from Filter (see filter.h)
+    pub radius: Vector2f,
+    pub inv_radius: Vector2f,
 }
 
 impl MitchellNetravali {
     pub fn new(w: Float, h: Float, b: Float, c: Float) -> Self {
         MitchellNetravali {
-            width: w,
-            height: h,
-            inv_width: 1.0 / w,
-            inv_height: 1.0 / h,
             b,
             c,
+            radius: Vector2f { x: w, y: h },
+            inv_radius: Vector2f {
+                x: 1.0 / w,
+                y: 1.0 / h,
+            },
         }
     }
     pub fn mitchell_1d(&self, x: Float) -> Float {
@@ -51,12 +57,12 @@ impl MitchellNetravali {
     }
     // Filter
     pub fn evaluate(&self, p: Point2f) -> Float {
-        self.mitchell_1d(p.x * self.inv_width) * self.mitchell_1d(p.y * self.inv_height)
+        self.mitchell_1d(p.x * self.inv_radius.x) * self.mitchell_1d(p.y * self.inv_radius.y)
     }
     pub fn get_radius(&self) -> Vector2f {
         Vector2f {
-            x: self.width,
-            y: self.height,
+            x: self.radius.x,
+            y: self.radius.y,
         }
     }
 }
@@ -6,9 +6,14 @@ use crate::core::geometry::{Point2f, Vector2f};
 use crate::core::paramset::ParamSet;
 use crate::core::pbrt::Float;
 
+// see sinc.h
+
+/// Windowed sinc filter, the sinc function multiplied by a Lanczos
+/// window which stretches *tau* periods (lobes) out from the center.
+/// It's separable and zero at and beyond its radius.
 #[derive(Debug, Default, Copy, Clone)]
 pub struct LanczosSincFilter {
-    tau: Float,
+    pub tau: Float,
     // inherited from Filter (see filter.h)
     pub radius: Vector2f,
     pub inv_radius: Vector2f,
@@ -28,7 +33,14 @@ impl LanczosSincFilter {
     pub fn create(ps: &ParamSet) -> Box<Filter> {
         let xw: Float = ps.find_one_float("xwidth", 4.0);
         let yw: Float = ps.find_one_float("ywidth", 4.0);
-        let tau: Float = ps.find_one_float("tau", 3.0);
+        let mut tau: Float = ps.find_one_float("tau", 3.0);
+        if tau.is_nan() || tau <= 0.0 as Float {
+            println!(
+                "WARNING: \"tau\" for the sinc filter has to be positive, not {}. Using 3.",
+                tau
+            );
+            tau = 3.0;
+        }
         let sinc_filter: Box<Filter> = Box::new(Filter::LanczosSinc(LanczosSincFilter::new(
             Vector2f { x: xw, y: yw },
             tau,
@@ -44,7 +56,7 @@ impl LanczosSincFilter {
     }
     pub fn windowed_sinc(&self, x: Float, radius: Float) -> Float {
         let x = x.abs();
-        if x > radius {
+        if x >= radius {
             return 0.0 as Float;
         }
         let lanczos: Float = self.sinc(x / self.tau);
@@ -2,11 +2,15 @@
 use std::sync::Arc;
 // pbrt
 use crate::core::camera::Camera;
-use crate::core::geometry::{nrm_cross_vec3, nrm_faceforward_vec3, vec3_dot_nrm};
+use crate::core::geometry::{
+    nrm_cross_vec3, nrm_dot_vec3, nrm_faceforward_vec3, vec3_coordinate_system, vec3_dot_nrm,
+};
 use crate::core::geometry::{Bounds2i, Normal3f, Point2f, Ray, Vector3f};
+use crate::core::integrator::{parse_pixel_bounds, Integrator, SamplerIntegrator};
 use crate::core::interaction::{Interaction, SurfaceInteraction};
 use crate::core::material::TransportMode;
-use crate::core::pbrt::{Float, Spectrum};
+use crate::core::paramset::ParamSet;
+use crate::core::pbrt::{Float, Spectrum, INV_PI};
 use crate::core::sampler::Sampler;
 use crate::core::sampling::{
     cosine_hemisphere_pdf, cosine_sample_hemisphere, uniform_hemisphere_pdf,
@@ -16,7 +20,10 @@ use crate::core::scene::Scene;
 
 // see ao.h
 
-/// Ambient Occlusion
+/// Ambient Occlusion: the (cosine weighted) fraction of the
+/// hemisphere around the shading normal at the first hit which isn't
+/// occluded within *max_distance*, as grayscale value between zero
+/// and one.
 pub struct AOIntegrator {
     // inherited from SamplerIntegrator (see integrator.h)
     pub camera: Arc<Camera>,
@@ -25,6 +32,8 @@ pub struct AOIntegrator {
     // see ao.h
     pub cos_sample: bool,
     pub n_samples: i32,
+    /// Occluders further away are ignored (infinity by default)
+    pub max_distance: Float,
 }
 
 impl AOIntegrator {
@@ -35,13 +44,36 @@ impl AOIntegrator {
         sampler: Box<Sampler>,
         pixel_bounds: Bounds2i,
     ) -> Self {
+        let n_samples: i32 = sampler.round_count(n_samples);
         AOIntegrator {
             camera,
             sampler,
             pixel_bounds,
             cos_sample,
             n_samples,
+            max_distance: std::f32::INFINITY,
+        }
+    }
+    /// Reads *"nsamples"* (64), *"cossample"* (true),
+    /// *"maxdistance"* (infinity) and optional *"pixelbounds"* (x0 x1
+    /// y0 y1) from *params*.
+    pub fn create(
+        params: &ParamSet,
+        camera: Arc<Camera>,
+        sampler: Box<Sampler>,
+    ) -> Box<Integrator> {
+        let pixel_bounds: Bounds2i = parse_pixel_bounds(params, &camera);
+        let cos_sample: bool = params.find_one_bool("cossample", true);
+        let n_samples: i32 = params.find_one_int("nsamples", 64);
+        let mut max_distance: Float = params.find_one_float("maxdistance", std::f32::INFINITY);
+        if max_distance.is_nan() || max_distance <= 0.0 as Float {
+            println!("WARNING: \"maxdistance\" has to be positive, ignoring it");
+            max_distance = std::f32::INFINITY;
         }
+        let mut integrator: AOIntegrator =
+            AOIntegrator::new(cos_sample, n_samples.max(1), camera, sampler, pixel_bounds);
+        integrator.max_distance = max_distance;
+        Box::new(Integrator::Sampler(SamplerIntegrator::AO(integrator)))
     }
     pub fn preprocess(&mut self, _scene: &Scene) {
         self.sampler.request_2d_array(self.n_samples);
@@ -73,11 +105,19 @@ impl AOIntegrator {
             //     ray = isect.SpawnRay(ray.d);
             //     goto retry;
             // }
-            // compute coordinate frame based on true geometry, not
-            // shading geometry.
-            let n: Normal3f = nrm_faceforward_vec3(&isect.common.n, &-ray.d);
-            let s: Vector3f = isect.dpdu.normalize();
-            let t: Vector3f = nrm_cross_vec3(&isect.common.n, &s);
+            // compute coordinate frame based on the shading geometry
+            // (on the side the ray came from)
+            let n: Normal3f = nrm_faceforward_vec3(&isect.shading.n, &-ray.d);
+            let dpdu: Vector3f =
+                isect.shading.dpdu - Vector3f::from(n) * nrm_dot_vec3(&n, &isect.shading.dpdu);
+            let mut s: Vector3f = Vector3f::default();
+            let mut t: Vector3f = Vector3f::default();
+            if dpdu.length_squared() > 0.0 as Float {
+                s = dpdu.normalize();
+                t = nrm_cross_vec3(&n, &s);
+            } else {
+                vec3_coordinate_system(&Vector3f::from(n), &mut s, &mut t);
+            }
             let u_opt: Option<&[Point2f]> = sampler.get_2d_array(self.n_samples);
             if let Some(u) = u_opt {
                 for item in u.iter().take(self.n_samples as usize) {
@@ -96,9 +136,16 @@ impl AOIntegrator {
                         y: s.y * wi.x + t.y * wi.y + n.y * wi.z,
                         z: s.z * wi.x + t.z * wi.y + n.z * wi.z,
                     };
+                    // rays escaping the scene (or only hitting
+                    // geometry beyond the maximum distance) are
+                    // unoccluded, normalize by the cosine integral
+                    // (pi) to get the unoccluded fraction
                     let mut ray: Ray = isect.spawn_ray(&wi);
+                    ray.t_max = ray.t_max.min(self.max_distance);
                     if !scene.intersect_p(&mut ray) {
-                        l += Spectrum::new(vec3_dot_nrm(&wi, &n) / (pdf * self.n_samples as Float));
+                        l += Spectrum::new(
+                            vec3_dot_nrm(&wi, &n) * INV_PI / (pdf * self.n_samples as Float),
+                        );
                     }
                 }
             }
@@ -5,12 +5,14 @@ use std::sync::Arc;
 // pbrt
 use crate::blockqueue::BlockQueue;
 use crate::core::camera::{Camera, CameraSample};
+use crate::core::film::Film;
 use crate::core::geometry::{
     nrm_abs_dot_vec3, pnt2_inside_exclusive, pnt3_offset_ray_origin, vec3_abs_dot_nrm, vec3_dot_nrm,
 };
 use crate::core::geometry::{
-    Bounds2i, Bounds3f, Normal3f, Point2f, Point2i, Point3f, Ray, Vector2i, Vector3f,
+    Bounds2f, Bounds2i, Normal3f, Point2f, Point2i, Point3f, Ray, Vector2i, Vector3f,
 };
+use crate::core::integrator::{parse_pixel_bounds, Integrator, RenderStats};
 use crate::core::interaction::{
     Interaction, InteractionCommon, MediumInteraction, SurfaceInteraction,
 };
@@ -19,12 +21,14 @@ use crate::core::light::{Light, LightFlags, VisibilityTester};
 use crate::core::lightdistrib::create_light_sample_distribution;
 use crate::core::material::TransportMode;
 use crate::core::medium::{HenyeyGreenstein, Medium, MediumInterface};
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::reflection::Bsdf;
 use crate::core::reflection::BxdfType;
 use crate::core::sampler::Sampler;
 use crate::core::sampling::Distribution1D;
 use crate::core::scene::Scene;
+use crate::filters::boxfilter::BoxFilter;
 
 // see bdpt.h
 
@@ -505,12 +509,7 @@ impl<'a> Vertex<'a> {
             return false;
         } else if let Some(ref ei) = self.ei {
             if let Some(ref light) = ei.light {
-                let check: u8 = light.get_flags() & LightFlags::Infinite as u8;
-                if check == LightFlags::Infinite as u8 {
-                    return true;
-                }
-                let check: u8 = light.get_flags() & LightFlags::DeltaDirection as u8;
-                if check == LightFlags::DeltaDirection as u8 {
+                if light.is_infinite() {
                     return true;
                 }
             } else {
@@ -634,9 +633,7 @@ impl<'a> Vertex<'a> {
         w *= inv_dist2.sqrt();
         let mut pdf = if self.is_infinite_light() {
             // compute planar sampling density for infinite light sources
-            let mut world_center: Point3f = Point3f::default();
-            let mut world_radius: Float = 0.0;
-            Bounds3f::bounding_sphere(&scene.world_bound(), &mut world_center, &mut world_radius);
+            let (_world_center, world_radius) = scene.world_bounding_sphere();
             1.0 as Float / (PI * world_radius * world_radius)
         } else {
             assert!(self.is_light());
@@ -799,8 +796,12 @@ pub struct BDPTIntegrator {
     pub pixel_bounds: Bounds2i,
     // see bdpt.h
     pub max_depth: u32,
-    // visualize_strategies: bool,
-    // visualize_weights: bool,
+    /// Write an image per connection strategy with the unweighted
+    /// contributions
+    pub visualize_strategies: bool,
+    /// Write an image per connection strategy with the MIS weighted
+    /// contributions
+    pub visualize_weights: bool,
     pub light_sample_strategy: String, // "power"
 }
 
@@ -810,8 +811,8 @@ impl BDPTIntegrator {
         sampler: Box<Sampler>,
         pixel_bounds: Bounds2i,
         max_depth: u32,
-        // visualize_strategies: bool,
-        // visualize_weights: bool,
+        visualize_strategies: bool,
+        visualize_weights: bool,
         light_sample_strategy: String,
     ) -> Self {
         BDPTIntegrator {
@@ -819,15 +820,45 @@ impl BDPTIntegrator {
             sampler,
             pixel_bounds,
             max_depth,
-            // visualize_strategies,
-            // visualize_weights,
+            visualize_strategies,
+            visualize_weights,
             light_sample_strategy,
         }
     }
+    /// Reads *"maxdepth"* (5), *"visualizestrategies"* (false),
+    /// *"visualizeweights"* (false), *"lightsamplestrategy"*
+    /// ("power") and optional *"pixelbounds"* (x0 x1 y0 y1) from
+    /// *params*. The debug images are limited to a depth of 5.
+    pub fn create(
+        params: &ParamSet,
+        camera: Arc<Camera>,
+        sampler: Box<Sampler>,
+    ) -> Box<Integrator> {
+        let mut max_depth: i32 = params.find_one_int("maxdepth", 5);
+        let visualize_strategies: bool = params.find_one_bool("visualizestrategies", false);
+        let visualize_weights: bool = params.find_one_bool("visualizeweights", false);
+        if (visualize_strategies || visualize_weights) && max_depth > 5_i32 {
+            print!("WARNING: visualizestrategies/visualizeweights was enabled,");
+            println!(" limiting maxdepth to 5");
+            max_depth = 5;
+        }
+        let pixel_bounds: Bounds2i = parse_pixel_bounds(params, &camera);
+        let light_strategy: String =
+            params.find_one_string("lightsamplestrategy", String::from("power"));
+        Box::new(Integrator::BDPT(BDPTIntegrator::new(
+            camera,
+            sampler,
+            pixel_bounds,
+            max_depth.max(0) as u32,
+            visualize_strategies,
+            visualize_weights,
+            light_strategy,
+        )))
+    }
     pub fn get_light_sample_strategy(&self) -> String {
         self.light_sample_strategy.clone()
     }
-    pub fn render(&self, scene: &Scene, num_threads: u8) {
+    pub fn render(&self, scene: &Scene, num_threads: u8) -> RenderStats {
         // TODO
         // Compute a reverse mapping from light pointers to offsets into
         // the scene lights vector (and, equivalently, offsets into
@@ -842,12 +873,39 @@ impl BDPTIntegrator {
         let film = self.get_camera().get_film();
         let sample_bounds: Bounds2i = film.get_sample_bounds();
         let sample_extent: Vector2i = sample_bounds.diagonal();
-        let tile_size: i32 = 16;
+        let tile_size: i32 = film.get_tile_size();
         let n_x_tiles: i32 = (sample_extent.x + tile_size - 1) / tile_size;
         let n_y_tiles: i32 = (sample_extent.y + tile_size - 1) / tile_size;
         // TODO: ProgressReporter reporter(nXTiles * nYTiles, "Rendering");
-        // TODO: Allocate buffers for debug visualization
-        // ...
+        // allocate buffers for debug visualization
+        let mut weight_films: Vec<Option<Film>> = Vec::new();
+        if self.visualize_strategies || self.visualize_weights {
+            let max_depth: usize = self.max_depth as usize;
+            let buffer_count: usize = (1 + max_depth) * (6 + max_depth) / 2;
+            weight_films.resize_with(buffer_count, || None);
+            for depth in 0..=max_depth {
+                for s in 0..=(depth + 2) {
+                    let t: usize = depth + 2 - s;
+                    if t == 0 || (s == 1 && t == 1) {
+                        continue;
+                    }
+                    let filename: String = format!("bdpt_d{:02}_s{:02}_t{:02}.exr", depth, s, t);
+                    weight_films[buffer_index(s, t)] = Some(Film::new(
+                        film.full_resolution,
+                        Bounds2f {
+                            p_min: Point2f { x: 0.0, y: 0.0 },
+                            p_max: Point2f { x: 1.0, y: 1.0 },
+                        },
+                        BoxFilter::create(&ParamSet::default()),
+                        film.diagonal * 1000.0,
+                        filename,
+                        1.0 as Float,
+                        std::f32::INFINITY,
+                    ));
+                }
+            }
+        }
+        let mut stats: RenderStats = RenderStats::default();
         // render and write the output image to disk
         if !scene.lights.is_empty() {
             let samples_per_pixel: i64 = self.sampler.get_samples_per_pixel();
@@ -871,6 +929,7 @@ impl BDPTIntegrator {
                 let sampler = &self.get_sampler();
                 let camera = &self.get_camera();
                 let film = &film;
+                let weight_films = &weight_films;
                 // let pixel_bounds = integrator.get_pixel_bounds().clone();
                 crossbeam::scope(|scope| {
                     let (pixel_tx, pixel_rx) = crossbeam_channel::bounded(num_cores);
@@ -1004,15 +1063,28 @@ impl BDPTIntegrator {
                                                     //     println!("Connect bdpt s: {:?}, t: {:?}, lpath: {:?}, mis_weight: {:?}",
                                                     //              s, t, lpath, mis_weight_flt);
                                                     // }
-                                                    // if (visualizeStrategies || visualizeWeights) {
-                                                    //     Spectrum value;
-                                                    //     if (visualizeStrategies)
-                                                    //         value =
-                                                    //             mis_weight == 0 ? 0 : lpath / mis_weight;
-                                                    //     if (visualizeWeights) value = lpath;
-                                                    //     weightFilms[BufferIndex(s, t)]->AddSplat(
-                                                    //         pFilmNew, value);
-                                                    // }
+                                                    if integrator.visualize_strategies
+                                                        || integrator.visualize_weights
+                                                    {
+                                                        let mut value: Spectrum =
+                                                            Spectrum::default();
+                                                        if integrator.visualize_strategies {
+                                                            let weight: Float =
+                                                                mis_weight.unwrap_or(0.0 as Float);
+                                                            if weight != 0.0 as Float {
+                                                                value = lpath / weight;
+                                                            }
+                                                        }
+                                                        if integrator.visualize_weights {
+                                                            value = lpath;
+                                                        }
+                                                        if let Some(weight_film) =
+                                                            &weight_films[buffer_index(s, t)]
+                                                        {
+                                                            weight_film
+                                                                .add_splat(p_film_new, &value);
+                                                        }
+                                                    }
                                                     if t != 1 {
                                                         l += lpath;
                                                     } else if !lpath.is_black() {
@@ -1031,27 +1103,34 @@ impl BDPTIntegrator {
                                         }
                                     }
                                 }
-                                // send the tile through the channel to main thread
+                                // merge image tile into _Film_ (which locks the
+                                // pixels overlapped by neighbouring tiles)
+                                film.merge_film_tile(&film_tile);
+                                scene.flush_ray_counts();
+                                // signal the finished tile to the main thread
                                 pixel_tx
-                                    .send(film_tile)
+                                    .send(())
                                     .unwrap_or_else(|_| panic!("Failed to send tile"));
                             }
                         });
                     }
-                    // spawn thread to collect pixels and render image to file
+                    // spawn thread to report progress
                     scope.spawn(move |_| {
                         for _ in pbr::PbIter::new(0..bq.len()) {
-                            let film_tile = pixel_rx.recv().unwrap();
-                            // merge image tile into _Film_
-                            film.merge_film_tile(&film_tile);
+                            pixel_rx.recv().unwrap();
                         }
                     });
                 })
                 .unwrap();
             }
             film.write_image(1.0 as Float / samples_per_pixel as Float);
-            // TODO: Write buffers for debug visualization
+            // write buffers for debug visualization
+            for weight_film in weight_films.iter().flatten() {
+                weight_film.write_image(1.0 as Float / samples_per_pixel as Float);
+            }
+            stats.total_samples = samples_per_pixel as u64 * sample_bounds.area() as u64;
         }
+        stats
     }
     pub fn get_camera(&self) -> Arc<Camera> {
         self.camera.clone()
@@ -1063,6 +1142,14 @@ impl BDPTIntegrator {
 
 // BDPT Utility Functions
 
+/// Index of the debug image (see *visualize_strategies*) of the
+/// connection strategy with *s* light and *t* camera vertices. The
+/// images are ordered by path depth (*s + t - 2*).
+pub fn buffer_index(s: usize, t: usize) -> usize {
+    let above: usize = s + t - 2;
+    s + above * (5 + above) / 2
+}
+
 pub fn correct_shading_normal(
     isect: &SurfaceInteraction,
     wo: &Vector3f,
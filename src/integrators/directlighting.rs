@@ -4,9 +4,14 @@ use std::sync::Arc;
 use crate::core::camera::Camera;
 use crate::core::geometry::{vec3_abs_dot_nrm, vec3_dot_nrm};
 use crate::core::geometry::{Bounds2i, Normal3f, Ray, RayDifferential, Vector3f};
-use crate::core::integrator::{uniform_sample_all_lights, uniform_sample_one_light};
+use crate::core::integrator::{
+    add_to_light_group, ris_sample_one_light_groups, uniform_sample_all_lights_groups,
+    uniform_sample_one_light_groups,
+};
+use crate::core::integrator::{parse_pixel_bounds, Integrator, SamplerIntegrator};
 use crate::core::interaction::{Interaction, SurfaceInteraction};
 use crate::core::material::TransportMode;
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::reflection::BxdfType;
 use crate::core::sampler::Sampler;
@@ -27,9 +32,15 @@ pub struct DirectLightingIntegrator {
     pub sampler: Box<Sampler>,
     pixel_bounds: Bounds2i,
     // see directlighting.h
-    strategy: LightStrategy,
-    max_depth: u32,
+    pub strategy: LightStrategy,
+    pub max_depth: u32,
     n_light_samples: Vec<i32>,
+    /// Number of light sample candidates for resampled importance
+    /// sampling (0 disables RIS)
+    pub ris_candidates: u32,
+    /// Pick the single light (strategy "one") proportional to its
+    /// power instead of uniformly
+    pub power_light_sampling: bool,
 }
 
 impl DirectLightingIntegrator {
@@ -39,6 +50,7 @@ impl DirectLightingIntegrator {
         camera: Arc<Camera>,
         sampler: Box<Sampler>,
         pixel_bounds: Bounds2i,
+        ris_candidates: u32,
     ) -> Self {
         DirectLightingIntegrator {
             camera,
@@ -47,11 +59,70 @@ impl DirectLightingIntegrator {
             strategy,
             max_depth,
             n_light_samples: Vec::new(),
+            ris_candidates,
+            power_light_sampling: false,
+        }
+    }
+    /// Reads *"strategy"* ("all" or "one"), *"maxdepth"* (5),
+    /// *"riscandidates"* (0), *"lightsamplestrategy"* ("uniform" or
+    /// "power") and optional *"pixelbounds"* (x0 x1 y0 y1) from
+    /// *params*.
+    pub fn create(
+        params: &ParamSet,
+        camera: Arc<Camera>,
+        sampler: Box<Sampler>,
+    ) -> Box<Integrator> {
+        let max_depth: i32 = params.find_one_int("maxdepth", 5);
+        let st: String = params.find_one_string("strategy", String::from("all"));
+        let strategy: LightStrategy = if st == "one" {
+            LightStrategy::UniformSampleOne
+        } else {
+            if st != "all" {
+                println!(
+                    "WARNING: Strategy \"{}\" for direct lighting unknown. Using \"all\".",
+                    st
+                );
+            }
+            LightStrategy::UniformSampleAll
+        };
+        let pixel_bounds: Bounds2i = parse_pixel_bounds(params, &camera);
+        // resampled importance sampling of lights (only for strategy "one")
+        let ris_candidates: i32 = params.find_one_int("riscandidates", 0);
+        let light_strategy: String =
+            params.find_one_string("lightsamplestrategy", String::from("uniform"));
+        let mut integrator: DirectLightingIntegrator = DirectLightingIntegrator::new(
+            strategy,
+            max_depth.max(0) as u32,
+            camera,
+            sampler,
+            pixel_bounds,
+            ris_candidates.max(0) as u32,
+        );
+        integrator.set_light_sample_strategy(&light_strategy);
+        Box::new(Integrator::Sampler(SamplerIntegrator::DirectLighting(
+            integrator,
+        )))
+    }
+    /// Chooses how strategy "one" picks a light, either "uniform" or
+    /// "power" (proportional to the light's *power()*, see
+    /// *Scene::sample_light()*).
+    pub fn set_light_sample_strategy(&mut self, name: &str) {
+        if name == "power" {
+            self.power_light_sampling = true;
+        } else {
+            if name != "uniform" {
+                println!(
+                    "WARNING: Light sample strategy \"{}\" unknown for direct lighting. Using \"uniform\".",
+                    name
+                );
+            }
+            self.power_light_sampling = false;
         }
     }
     pub fn preprocess(&mut self, scene: &Scene) {
         if self.strategy == LightStrategy::UniformSampleAll {
             // compute number of samples to use for each light
+            self.n_light_samples.clear();
             for li in 0..scene.lights.len() {
                 let light = &scene.lights[li];
                 self.n_light_samples
@@ -73,6 +144,22 @@ impl DirectLightingIntegrator {
         sampler: &mut Sampler,
         // arena: &mut Arena,
         depth: i32,
+    ) -> Spectrum {
+        self.li_groups(ray, scene, sampler, depth, &mut [], &mut Vec::new())
+    }
+    /// Like *li()*, but also adds the contribution of each light to
+    /// its light group in *groups* (emission of surfaces goes to group
+    /// 0). Specular bounces take their buffers from *scratch* (and
+    /// return them afterwards).
+    pub fn li_groups(
+        &self,
+        ray: &mut Ray,
+        scene: &Scene,
+        sampler: &mut Sampler,
+        // arena: &mut Arena,
+        depth: i32,
+        groups: &mut [Spectrum],
+        scratch: &mut Vec<Vec<Spectrum>>,
     ) -> Spectrum {
         // TODO: ProfilePhase p(Prof::SamplerIntegratorLi);
         let mut l: Spectrum = Spectrum::new(0.0 as Float);
@@ -83,38 +170,77 @@ impl DirectLightingIntegrator {
             let mode: TransportMode = TransportMode::Radiance;
             isect.compute_scattering_functions(ray, false, mode);
             if isect.bsdf.is_none() {
-                return self.li(&mut isect.spawn_ray(&ray.d), scene, sampler, depth);
+                return self.li_groups(
+                    &mut isect.spawn_ray(&ray.d),
+                    scene,
+                    sampler,
+                    depth,
+                    groups,
+                    scratch,
+                );
             }
             let wo: Vector3f = isect.common.wo;
-            l += isect.le(&wo);
-            if !scene.lights.is_empty() {
+            let le: Spectrum = isect.le(&wo);
+            l += le;
+            add_to_light_group(groups, 0, le);
+            // (but skip this for perfectly specular BSDFs)
+            let mut sample_lights: bool = !scene.lights.is_empty();
+            if let Some(ref bsdf) = isect.bsdf {
+                let bsdf_flags: u8 = BxdfType::BsdfAll as u8 & !(BxdfType::BsdfSpecular as u8);
+                sample_lights = sample_lights && bsdf.num_components(bsdf_flags) > 0;
+            }
+            if sample_lights {
                 // compute direct lighting for _DirectLightingIntegrator_ integrator
                 if self.strategy == LightStrategy::UniformSampleAll {
-                    l += uniform_sample_all_lights(
+                    l += uniform_sample_all_lights_groups(
                         &isect,
                         scene,
                         sampler,
                         &self.n_light_samples,
                         false,
+                        groups,
+                    );
+                } else if self.ris_candidates > 0_u32 {
+                    l += ris_sample_one_light_groups(
+                        &isect,
+                        scene,
+                        sampler,
+                        self.ris_candidates,
+                        false,
+                        groups,
                     );
                 } else {
-                    l += uniform_sample_one_light(&isect, scene, sampler, false, None);
+                    let light_distrib = if self.power_light_sampling {
+                        scene.light_power_distrib.as_deref()
+                    } else {
+                        None
+                    };
+                    l += uniform_sample_one_light_groups(
+                        &isect,
+                        scene,
+                        sampler,
+                        false,
+                        light_distrib,
+                        groups,
+                    );
                 }
             }
             if ((depth + 1_i32) as u32) < self.max_depth {
                 // trace rays for specular reflection and refraction
-                l += self.specular_reflect(
+                l += self.specular_reflect_groups(
                     ray, &isect, scene, sampler, // arena,
-                    depth,
+                    depth, groups, scratch,
                 );
-                l += self.specular_transmit(
+                l += self.specular_transmit_groups(
                     ray, &isect, scene, sampler, // arena,
-                    depth,
+                    depth, groups, scratch,
                 );
             }
         } else {
             for light in &scene.lights {
-                l += light.le(ray);
+                let le: Spectrum = light.le_visible(ray, depth == 0_i32);
+                l += le;
+                add_to_light_group(groups, light.get_group(), le);
             }
         }
         l
@@ -136,6 +262,21 @@ impl DirectLightingIntegrator {
         sampler: &mut Sampler,
         // arena: &mut Arena,
         depth: i32,
+    ) -> Spectrum {
+        self.specular_reflect_groups(ray, isect, scene, sampler, depth, &mut [], &mut Vec::new())
+    }
+    /// Like *specular_reflect()*, but also adds the (scaled) light groups
+    /// of the traced ray to *groups*.
+    pub fn specular_reflect_groups(
+        &self,
+        ray: &Ray,
+        isect: &SurfaceInteraction,
+        scene: &Scene,
+        sampler: &mut Sampler,
+        // arena: &mut Arena,
+        depth: i32,
+        groups: &mut [Spectrum],
+        scratch: &mut Vec<Vec<Spectrum>>,
     ) -> Spectrum {
         // compute specular reflection direction _wi_ and BSDF value
         let wo: Vector3f = isect.common.wo;
@@ -179,8 +320,17 @@ impl DirectLightingIntegrator {
                     };
                     rd.differential = Some(diff);
                 }
-                f * self.li(&mut rd, scene, sampler, depth + 1)
-                    * Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf)
+                let scale: Spectrum = f * Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf);
+                let mut rd_groups: Vec<Spectrum> = scratch.pop().unwrap_or_default();
+                rd_groups.clear();
+                rd_groups.resize(groups.len(), Spectrum::default());
+                let li: Spectrum =
+                    self.li_groups(&mut rd, scene, sampler, depth + 1, &mut rd_groups, scratch);
+                for (l_group, rd_group) in groups.iter_mut().zip(rd_groups.iter()) {
+                    *l_group += scale * *rd_group;
+                }
+                scratch.push(rd_groups);
+                scale * li
             } else {
                 Spectrum::new(0.0)
             }
@@ -196,6 +346,21 @@ impl DirectLightingIntegrator {
         sampler: &mut Sampler,
         // arena: &mut Arena,
         depth: i32,
+    ) -> Spectrum {
+        self.specular_transmit_groups(ray, isect, scene, sampler, depth, &mut [], &mut Vec::new())
+    }
+    /// Like *specular_transmit()*, but also adds the (scaled) light groups
+    /// of the traced ray to *groups*.
+    pub fn specular_transmit_groups(
+        &self,
+        ray: &Ray,
+        isect: &SurfaceInteraction,
+        scene: &Scene,
+        sampler: &mut Sampler,
+        // arena: &mut Arena,
+        depth: i32,
+        groups: &mut [Spectrum],
+        scratch: &mut Vec<Vec<Spectrum>>,
     ) -> Spectrum {
         let wo: Vector3f = isect.common.wo;
         let mut wi: Vector3f = Vector3f::default();
@@ -246,8 +411,17 @@ impl DirectLightingIntegrator {
                     };
                     rd.differential = Some(diff);
                 }
-                f * self.li(&mut rd, scene, sampler, depth + 1)
-                    * Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf)
+                let scale: Spectrum = f * Spectrum::new(vec3_abs_dot_nrm(&wi, &ns) / pdf);
+                let mut rd_groups: Vec<Spectrum> = scratch.pop().unwrap_or_default();
+                rd_groups.clear();
+                rd_groups.resize(groups.len(), Spectrum::default());
+                let li: Spectrum =
+                    self.li_groups(&mut rd, scene, sampler, depth + 1, &mut rd_groups, scratch);
+                for (l_group, rd_group) in groups.iter_mut().zip(rd_groups.iter()) {
+                    *l_group += scale * *rd_group;
+                }
+                scratch.push(rd_groups);
+                scale * li
             } else {
                 Spectrum::new(0.0)
             }
@@ -6,12 +6,12 @@ use std::thread;
 use crate::core::camera::Camera;
 use crate::core::film::Film;
 use crate::core::geometry::{Bounds2f, Bounds2i, Point2f, Point2i};
-use crate::core::integrator::compute_light_power_distribution;
+use crate::core::integrator::{compute_light_power_distribution, RenderStats};
 use crate::core::pbrt::erf_inv;
 use crate::core::pbrt::SQRT_2;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::rng::Rng;
-use crate::core::sampler::Sampler;
+use crate::core::sampler::{Sampler, SamplerTrait};
 use crate::core::sampling::Distribution1D;
 use crate::core::scene::Scene;
 use crate::integrators::bdpt::Vertex;
@@ -56,6 +56,10 @@ pub struct MLTSampler {
     pub last_large_step_iteration: i64,
     pub stream_index: i32,
     pub sample_index: i32,
+    /// Dimensions per stream if the primary sample space is split into
+    /// contiguous ranges (see *partition_streams()*), zero for
+    /// interleaved streams (as in PBRT-v3)
+    pub stream_dimensions: i32,
     // inherited from class Sampler (see sampler.h)
     pub current_pixel: Point2i,
     pub current_pixel_sample_index: i64,
@@ -89,6 +93,7 @@ impl MLTSampler {
             last_large_step_iteration: 0_i64,
             stream_index: 0_i32,
             sample_index: 0_i32,
+            stream_dimensions: 0_i32,
             current_pixel: Point2i::default(),
             current_pixel_sample_index: 0_i64,
             samples_1d_array_sizes: Vec::new(),
@@ -112,6 +117,7 @@ impl MLTSampler {
             last_large_step_iteration: self.last_large_step_iteration,
             stream_index: self.stream_index,
             sample_index: self.sample_index,
+            stream_dimensions: self.stream_dimensions,
             current_pixel: self.current_pixel,
             current_pixel_sample_index: self.current_pixel_sample_index,
             samples_1d_array_sizes: self.samples_1d_array_sizes.to_vec(),
@@ -145,13 +151,45 @@ impl MLTSampler {
         }
         self.current_iteration -= 1;
     }
+    /// Splits the primary sample space into *stream_count* equal,
+    /// contiguous ranges of *total_dimensions* / *stream_count*
+    /// dimensions each, instead of interleaving the streams. Has to be
+    /// called before any sample is drawn; drawing more samples than a
+    /// stream holds panics instead of overlapping with the next one.
+    pub fn partition_streams(&mut self, stream_count: i32, total_dimensions: i32) {
+        assert!(stream_count > 0, "stream_count ({}) > 0", stream_count);
+        assert!(
+            total_dimensions >= stream_count,
+            "total_dimensions ({}) >= stream_count ({})",
+            total_dimensions,
+            stream_count
+        );
+        assert!(
+            self.x.is_empty(),
+            "streams have to be partitioned before drawing samples"
+        );
+        self.stream_count = stream_count;
+        self.stream_dimensions = total_dimensions / stream_count;
+        self.stream_index = 0;
+        self.sample_index = 0;
+    }
     pub fn start_stream(&mut self, index: i32) {
         assert!(index < self.stream_count);
         self.stream_index = index;
         self.sample_index = 0;
     }
     pub fn get_next_index(&mut self) -> i32 {
-        let ret = self.stream_index + self.stream_count * self.sample_index;
+        let ret = if self.stream_dimensions > 0 {
+            assert!(
+                self.sample_index < self.stream_dimensions,
+                "stream {} exhausted after {} dimensions",
+                self.stream_index,
+                self.stream_dimensions
+            );
+            self.stream_index * self.stream_dimensions + self.sample_index
+        } else {
+            self.stream_index + self.stream_count * self.sample_index
+        };
         self.sample_index += 1;
         ret
     }
@@ -190,43 +228,49 @@ impl MLTSampler {
             panic!("self.x.get_mut({:?}) failed", index);
         }
     }
+}
+
+impl SamplerTrait for MLTSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *MLTSampler::clone_with_seed(self, seed) {
+            Sampler::MLT(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
+    }
     // Sampler
-    pub fn start_pixel(&mut self, p: Point2i) {
+    fn start_pixel(&mut self, p: Point2i) {
         // Sampler::StartPixel(p);
         self.current_pixel = p;
         self.current_pixel_sample_index = 0_i64;
         self.array_1d_offset = 0_usize;
         self.array_2d_offset = 0_usize;
     }
-    pub fn get_1d(&mut self) -> Float {
+    fn get_1d(&mut self) -> Float {
         // TODO: ProfilePhase _(Prof::GetSample);
         let index: i32 = self.get_next_index();
         self.ensure_ready(index);
         self.x[index as usize].value
     }
-    pub fn get_2d(&mut self) -> Point2f {
+    fn get_2d(&mut self) -> Point2f {
         // C++: call x first
         let x: Float = self.get_1d();
         let y: Float = self.get_1d();
         Point2f { x, y }
     }
-    pub fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
         self.sample_array_2d[array_idx][idx]
     }
-    pub fn reseed(&mut self, seed: u64) {
-        self.rng.set_sequence(seed);
-    }
-    pub fn request_2d_array(&mut self, n: i32) {
+    fn request_2d_array(&mut self, n: i32) {
         assert_eq!(self.round_count(n), n);
         self.samples_2d_array_sizes.push(n);
         let size: usize = (n * self.samples_per_pixel as i32) as usize;
         let additional_points: Vec<Point2f> = vec![Point2f::default(); size];
         self.sample_array_2d.push(additional_points);
     }
-    pub fn round_count(&self, count: i32) -> i32 {
+    fn round_count(&self, count: i32) -> i32 {
         count
     }
-    pub fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return None;
         }
@@ -242,40 +286,7 @@ impl MLTSampler {
         self.array_2d_offset += 1;
         Some(&self.sample_array_2d[self.array_2d_offset - 1][start..end])
     }
-    pub fn get_2d_arrays(&mut self, n: i32) -> (Option<&[Point2f]>, Option<&[Point2f]>) {
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(
-            self.current_pixel_sample_index < self.samples_per_pixel,
-            "self.current_pixel_sample_index ({}) < self.samples_per_pixel ({})",
-            self.current_pixel_sample_index,
-            self.samples_per_pixel
-        );
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret1 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // repeat code from above
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(
-            self.current_pixel_sample_index < self.samples_per_pixel,
-            "self.current_pixel_sample_index ({}) < self.samples_per_pixel ({})",
-            self.current_pixel_sample_index,
-            self.samples_per_pixel
-        );
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret2 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // return tuple
-        (Some(ret1), Some(ret2))
-    }
-    pub fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return (true, 0_usize, 0_usize);
         }
@@ -291,20 +302,23 @@ impl MLTSampler {
         self.array_2d_offset += 1;
         (false, idx, start)
     }
-    pub fn start_next_sample(&mut self) -> bool {
+    fn start_next_sample(&mut self) -> bool {
         // reset array offsets for next pixel sample
         self.array_1d_offset = 0_usize;
         self.array_2d_offset = 0_usize;
         self.current_pixel_sample_index += 1_i64;
         self.current_pixel_sample_index < self.samples_per_pixel
     }
-    pub fn get_current_pixel(&self) -> Point2i {
+    fn reseed(&mut self, seed: u64) {
+        self.rng.set_sequence(seed);
+    }
+    fn get_current_pixel(&self) -> Point2i {
         self.current_pixel
     }
-    pub fn get_current_sample_number(&self) -> i64 {
+    fn get_current_sample_number(&self) -> i64 {
         self.current_pixel_sample_index
     }
-    pub fn get_samples_per_pixel(&self) -> i64 {
+    fn get_samples_per_pixel(&self) -> i64 {
         self.samples_per_pixel
     }
 }
@@ -437,7 +451,7 @@ impl MLTIntegrator {
             None,
         ) * (n_strategies as Float)
     }
-    pub fn render(&self, scene: &Scene, num_threads: u8) {
+    pub fn render(&self, scene: &Scene, num_threads: u8) -> RenderStats {
         let mut num_cores: usize; // TMP
         // let num_cores = if num_threads == 0_u8 {
         let num_cores_init = if num_threads == 0_u8 { // TMP
@@ -445,6 +459,7 @@ impl MLTIntegrator {
         } else {
             num_threads as usize
         };
+        let mut stats: RenderStats = RenderStats::default();
         if let Some(light_distr) = compute_light_power_distribution(scene) {
             println!("Generating bootstrap paths ...");
             // generate bootstrap samples and compute normalization constant $b$
@@ -491,6 +506,7 @@ impl MLTIntegrator {
                                         .y();
                                 }
                             });
+                            scene.flush_ray_counts();
                             // send progress through the channel to main thread
                             band_tx
                                 .send(b)
@@ -601,12 +617,15 @@ impl MLTIntegrator {
                         // }
                         // TODO: arena.Reset();
                     }
+                    scene.flush_ray_counts();
                 });
                 finish.join().unwrap();
             }
             // Store final image computed with MLT
             film.write_image(b / self.mutations_per_pixel as Float);
+            stats.total_samples = n_total_mutations;
         }
+        stats
     }
     pub fn get_camera(&self) -> Arc<Camera> {
         self.camera.clone()
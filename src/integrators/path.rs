@@ -6,11 +6,14 @@ use std::sync::Arc;
 use crate::core::camera::Camera;
 use crate::core::geometry::{vec3_abs_dot_nrm, vec3_dot_nrm};
 use crate::core::geometry::{Bounds2i, Point2f, Ray, Vector3f};
-use crate::core::integrator::uniform_sample_one_light;
+use crate::core::integrator::{add_to_light_group, parse_pixel_bounds, russian_roulette};
+use crate::core::integrator::{uniform_sample_one_light_index, Integrator, SamplerIntegrator};
 use crate::core::interaction::{Interaction, SurfaceInteraction};
+use crate::core::light::Light;
 use crate::core::lightdistrib::create_light_sample_distribution;
 use crate::core::lightdistrib::LightDistribution;
 use crate::core::material::TransportMode;
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::reflection::BxdfType;
 use crate::core::sampler::Sampler;
@@ -26,9 +29,11 @@ pub struct PathIntegrator {
     pub sampler: Box<Sampler>,
     pixel_bounds: Bounds2i,
     // see path.h
-    max_depth: u32,
-    rr_threshold: Float,           // 1.0
-    light_sample_strategy: String, // "spatial"
+    pub max_depth: u32,
+    /// Russian roulette starts once the maximum component of the
+    /// path throughput falls below this threshold
+    pub rr_threshold: Float, // 1.0
+    pub light_sample_strategy: String, // "spatial"
     light_distribution: Option<Arc<LightDistribution>>,
 }
 
@@ -51,17 +56,60 @@ impl PathIntegrator {
             light_distribution: None,
         }
     }
+    /// Reads *"maxdepth"* (5), *"rrthreshold"* (1),
+    /// *"lightsamplestrategy"* ("spatial", or "uniform", "power",
+    /// "bvh") and optional *"pixelbounds"* (x0 x1 y0 y1) from
+    /// *params*.
+    pub fn create(
+        params: &ParamSet,
+        camera: Arc<Camera>,
+        sampler: Box<Sampler>,
+    ) -> Box<Integrator> {
+        let max_depth: i32 = params.find_one_int("maxdepth", 5);
+        let pixel_bounds: Bounds2i = parse_pixel_bounds(params, &camera);
+        let rr_threshold: Float = params.find_one_float("rrthreshold", 1.0 as Float);
+        let light_strategy: String =
+            params.find_one_string("lightsamplestrategy", String::from("spatial"));
+        Box::new(Integrator::Sampler(SamplerIntegrator::Path(
+            PathIntegrator::new(
+                max_depth.max(0) as u32,
+                camera,
+                sampler,
+                pixel_bounds,
+                rr_threshold,
+                light_strategy,
+            ),
+        )))
+    }
     pub fn preprocess(&mut self, scene: &Scene) {
         self.light_distribution =
             create_light_sample_distribution(self.light_sample_strategy.clone(), scene);
+        // camera sample (film, lens) plus light, scattering and
+        // BSDF samples per bounce
+        self.sampler
+            .ensure_sampled_dimensions(2 + 3 * (self.max_depth as usize + 1));
     }
     pub fn li(
+        &self,
+        r: &Ray,
+        scene: &Scene,
+        sampler: &mut Sampler,
+        // arena: &mut Arena,
+        depth: i32,
+    ) -> Spectrum {
+        self.li_groups(r, scene, sampler, depth, &mut [])
+    }
+    /// Like *li()*, but also adds the contribution of each light
+    /// (weighted by the path throughput) to its light group in
+    /// *groups* (emission of surfaces goes to group 0).
+    pub fn li_groups(
         &self,
         r: &Ray,
         scene: &Scene,
         sampler: &mut Sampler,
         // arena: &mut Arena,
         _depth: i32,
+        groups: &mut [Spectrum],
     ) -> Spectrum {
         // TODO: ProfilePhase p(Prof::SamplerIntegratorLi);
         let mut l: Spectrum = Spectrum::default();
@@ -95,7 +143,11 @@ impl PathIntegrator {
                 // possibly add emitted light at intersection
                 if bounces == 0 || specular_bounce {
                     // add emitted light at path vertex
-                    l += beta * isect.le(&-ray.d);
+                    let le: Spectrum = beta * isect.le(&-ray.d);
+                    l += le;
+                    add_to_light_group(groups, 0, le);
+                    // and visible bulbs in front of it
+                    l += PathIntegrator::bulbs_le(scene, &mut ray, bounces == 0, beta, groups);
                     // println!("Added Le -> L = {:?}", l);
                 }
                 // terminate path if _maxDepth_ was reached
@@ -122,14 +174,17 @@ impl PathIntegrator {
                         if bsdf.num_components(bsdf_flags) > 0 {
                             // TODO: ++total_paths;
                             let it: &SurfaceInteraction = isect.borrow();
-                            let ld: Spectrum = beta
-                                * uniform_sample_one_light(
-                                    it,
-                                    scene,
-                                    sampler,
-                                    false,
-                                    Some(&distrib),
-                                );
+                            let (ld, light_num) = uniform_sample_one_light_index(
+                                it,
+                                scene,
+                                sampler,
+                                false,
+                                Some(&distrib),
+                            );
+                            let ld: Spectrum = beta * ld;
+                            if let Some(light_num) = light_num {
+                                add_to_light_group(groups, scene.lights[light_num].get_group(), ld);
+                            }
                             // TODO: println!("Sampled direct lighting Ld = {:?}", ld);
                             // TODO: if ld.is_black() {
                             //     ++zero_radiance_paths;
@@ -212,14 +267,21 @@ impl PathIntegrator {
                                     // account for the direct subsurface scattering component
                                     let distrib: Arc<Distribution1D> =
                                         light_distribution.lookup(&pi.common.p);
-                                    l += beta
-                                        * uniform_sample_one_light(
-                                            &pi,
-                                            scene,
-                                            sampler,
-                                            false,
-                                            Some(&distrib),
+                                    let (ld, light_num) = uniform_sample_one_light_index(
+                                        &pi,
+                                        scene,
+                                        sampler,
+                                        false,
+                                        Some(&distrib),
+                                    );
+                                    l += beta * ld;
+                                    if let Some(light_num) = light_num {
+                                        add_to_light_group(
+                                            groups,
+                                            scene.lights[light_num].get_group(),
+                                            beta * ld,
                                         );
+                                    }
                                     // account for the indirect subsurface scattering component
                                     let mut wi: Vector3f = Vector3f::default();
                                     let mut pdf: Float = 0.0 as Float;
@@ -250,13 +312,11 @@ impl PathIntegrator {
                         // Possibly terminate the path with Russian roulette.
                         // Factor out radiance scaling due to refraction in rr_beta.
                         let rr_beta: Spectrum = beta * eta_scale;
-                        if rr_beta.max_component_value() < self.rr_threshold && bounces > 3 {
-                            let q: Float =
-                                (0.05 as Float).max(1.0 as Float - rr_beta.max_component_value());
-                            if sampler.get_1d() < q {
-                                break;
+                        if rr_beta.max_component_value() < self.rr_threshold {
+                            match russian_roulette(rr_beta, sampler.get_1d(), 3, bounces as usize) {
+                                Some(scale) => beta = beta * scale,
+                                None => break,
                             }
-                            beta /= 1.0 as Float - q;
                             assert!(!(beta.y().is_infinite()));
                         }
                     } else {
@@ -268,8 +328,11 @@ impl PathIntegrator {
                 if bounces == 0 || specular_bounce {
                     // for (const auto &light : scene.infiniteLights)
                     for light in &scene.infinite_lights {
-                        l += beta * light.le(&mut ray);
+                        let le: Spectrum = beta * light.le(&mut ray);
+                        l += le;
+                        add_to_light_group(groups, light.get_group(), le);
                     }
+                    l += PathIntegrator::bulbs_le(scene, &mut ray, bounces == 0, beta, groups);
                     // println!("Added infinite area lights -> L = {:?}", l);
                 }
                 // terminate path if ray escaped
@@ -279,6 +342,26 @@ impl PathIntegrator {
         }
         l
     }
+    /// Radiance of visible point light bulbs hit by *ray* (before
+    /// *ray.t_max*), see *Light::le_visible()* for *camera_ray*,
+    /// weighted by *beta* and added to the bulbs' light groups.
+    fn bulbs_le(
+        scene: &Scene,
+        ray: &mut Ray,
+        camera_ray: bool,
+        beta: Spectrum,
+        groups: &mut [Spectrum],
+    ) -> Spectrum {
+        let mut l: Spectrum = Spectrum::default();
+        for light in &scene.lights {
+            if let Light::Point(_) = &**light {
+                let le: Spectrum = beta * light.le_visible(ray, camera_ray);
+                l += le;
+                add_to_light_group(groups, light.get_group(), le);
+            }
+        }
+        l
+    }
     pub fn get_camera(&self) -> Arc<Camera> {
         self.camera.clone()
     }
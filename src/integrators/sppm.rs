@@ -19,10 +19,12 @@ use crate::core::geometry::{
     XYZEnum,
 };
 use crate::core::integrator::{compute_light_power_distribution, uniform_sample_one_light};
+use crate::core::integrator::{Integrator, RenderStats};
 use crate::core::interaction::{Interaction, SurfaceInteraction};
 use crate::core::lowdiscrepancy::radical_inverse;
 use crate::core::material::TransportMode;
 use crate::core::parallel::AtomicFloat;
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{clamp_t, lerp};
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::reflection::{Bsdf, BxdfType};
@@ -41,6 +43,37 @@ pub struct SPPMIntegrator {
 }
 
 impl SPPMIntegrator {
+    /// Reads *"numiterations"* (64, or *"iterations"*),
+    /// *"photonsperiteration"* (-1, one photon per pixel),
+    /// *"imagewritefrequency"* (never), *"maxdepth"* (5) and
+    /// *"radius"* (1), the initial search radius, from *params*.
+    pub fn create(params: &ParamSet, camera: Arc<Camera>) -> Box<Integrator> {
+        let mut n_iterations: i32 = params.find_one_int("numiterations", 64);
+        n_iterations = params.find_one_int("iterations", n_iterations);
+        let max_depth: i32 = params.find_one_int("maxdepth", 5);
+        let photons_per_iter: i32 = params.find_one_int("photonsperiteration", -1);
+        let mut write_freq: i32 = params.find_one_int("imagewritefrequency", std::i32::MAX);
+        if write_freq <= 0_i32 {
+            println!(
+                "WARNING: \"imagewritefrequency\" has to be positive, only writing the final image"
+            );
+            write_freq = std::i32::MAX;
+        }
+        let mut radius: Float = params.find_one_float("radius", 1.0 as Float);
+        if radius.is_nan() || radius <= 0.0 as Float {
+            println!("WARNING: \"radius\" has to be positive, using 1");
+            radius = 1.0 as Float;
+        }
+        // TODO: if (PbrtOptions.quickRender) nIterations = std::max(1, nIterations / 16);
+        Box::new(Integrator::SPPM(SPPMIntegrator::new(
+            camera,
+            n_iterations.max(1),
+            photons_per_iter,
+            max_depth.max(0) as u32,
+            radius,
+            write_freq,
+        )))
+    }
     pub fn new(
         camera: Arc<Camera>,
         n_iterations: i32,
@@ -64,7 +97,7 @@ impl SPPMIntegrator {
             write_frequency,
         }
     }
-    pub fn render(&self, scene: &Scene, num_threads: u8) {
+    pub fn render(&self, scene: &Scene, num_threads: u8) -> RenderStats {
         let num_cores = if num_threads == 0_u8 {
             num_cpus::get()
         } else {
@@ -85,6 +118,7 @@ impl SPPMIntegrator {
         }
         let inv_sqrt_spp: Float = 1.0 as Float / (self.n_iterations as Float).sqrt();
         // TODO: let pixel_memory_bytes: usize = n_pixels as usize * std::mem::size_of::<SPPMPixel>();
+        let mut stats: RenderStats = RenderStats::default();
 
         // compute _light_distr_ for sampling lights proportional to power
         if let Some(light_distr) = compute_light_power_distribution(scene) {
@@ -136,8 +170,9 @@ impl SPPMIntegrator {
                                         // TODO: MemoryArena &arena = perThreadArenas[ThreadIndex];
 
                                         // follow camera paths for _tile_ in image for SPPM
-                                        // TODO: let tile_index: i32 = tile.y * n_tiles.x + tile.x;
-                                        let mut tile_sampler = sampler.clone_with_seed(0_u64);
+                                        let tile_index: i32 = tile.y * n_tiles.x + tile.x;
+                                        let mut tile_sampler =
+                                            sampler.clone_with_seed(tile_index as u64);
                                         // compute _tileBounds_ for SPPM tile
                                         let x0: i32 = pixel_bounds.p_min.x + tile.x * tile_size;
                                         let x1: i32 =
@@ -292,6 +327,7 @@ impl SPPMIntegrator {
                                             }
                                             tile_bq.push(pixel);
                                         }
+                                        scene.flush_ray_counts();
                                         // send progress through the channel to main thread
                                         pixel_tx
                                             .send(tile_bq)
@@ -352,8 +388,13 @@ impl SPPMIntegrator {
                     // compute resolution of SPPM grid in each dimension
                     let diag: Vector3f = grid_bounds.diagonal();
                     let max_diag: Float = vec3_max_component(&diag);
-                    let base_grid_res: i32 = (max_diag / max_radius).floor() as i32;
-                    assert!(base_grid_res > 0_i32);
+                    // without any visible points the (empty) grid gets a
+                    // single cell
+                    let base_grid_res: i32 = if max_radius > 0.0 as Float {
+                        ((max_diag / max_radius).floor() as i32).max(1)
+                    } else {
+                        1_i32
+                    };
                     for i in XYZEnum::iter() {
                         grid_res[i as usize] = ((base_grid_res as Float * diag[i] / max_diag)
                             .floor() as i32)
@@ -361,9 +402,10 @@ impl SPPMIntegrator {
                     }
                     // add visible points to SPPM grid
                     // println!("Add visible points to SPPM grid ...");
-                    let chunk_size: usize = (n_pixels / num_cores as i32) as usize;
+                    let chunk_size: usize = band_size(n_pixels as usize, num_cores);
                     {
                         let bands: Vec<&mut [SPPMPixel]> = pixels.chunks_mut(chunk_size).collect();
+                        let n_bands: usize = bands.len();
                         let grid = &grid;
                         crossbeam::scope(|scope| {
                             let (band_tx, band_rx) = crossbeam_channel::bounded(num_cores);
@@ -425,6 +467,7 @@ impl SPPMIntegrator {
                                         }
                                     }
                                 });
+                                scene.flush_ray_counts();
                                 // send progress through the channel to main thread
                                 band_tx
                                     .send(b)
@@ -432,7 +475,7 @@ impl SPPMIntegrator {
                             }
                             // spawn thread to report progress
                             scope.spawn(move |_| {
-                                for _ in 0..num_cores {
+                                for _ in 0..n_bands {
                                     band_rx.recv().unwrap();
                                 }
                             });
@@ -453,10 +496,11 @@ impl SPPMIntegrator {
                     // TODO: ProfilePhase _(Prof::SPPMPhotonPass);
                     // println!("Trace photons and accumulate contributions ...");
                     let chunk_size: usize =
-                        (self.photons_per_iteration / num_cores as i32) as usize;
+                        band_size(self.photons_per_iteration as usize, num_cores);
                     {
                         let photons_vec: Vec<i32> = (0..self.photons_per_iteration).collect();
                         let bands: Vec<&[i32]> = photons_vec.chunks(chunk_size).collect();
+                        let n_bands: usize = bands.len();
                         let grid_once = &grid_once;
                         let integrator = &self;
                         let light_distr = &light_distr;
@@ -532,7 +576,7 @@ impl SPPMIntegrator {
                                             //     light_num, pdf_pos, pdf_dir, le
                                             // );
                                             // C++: return; (from ParallelFor(...{}, photonsPerIteration, 8192);)
-                                            break;
+                                            continue;
                                         }
                                         let mut beta: Spectrum = (le
                                             * nrm_abs_dot_vec3(&n_light, &photon_ray.d))
@@ -540,7 +584,7 @@ impl SPPMIntegrator {
                                         if beta.is_black() {
                                             // println!("light[{}]: beta = {:?}", light_num, beta);
                                             // C++:  return; (from ParallelFor(...{}, photonsPerIteration, 8192);)
-                                            break;
+                                            continue;
                                         }
                                         // follow photon path through scene and record intersections
                                         for depth in 0..integrator.max_depth {
@@ -695,12 +739,13 @@ impl SPPMIntegrator {
                                     }
                                 }
                             });
+                            scene.flush_ray_counts();
                             // send progress through the channel to main thread
                             band_tx.send(b).unwrap_or_else(|_| panic!("Failed to send progress"));
                         }
                         // spawn thread to report progress
                         scope.spawn(move |_| {
-                            for _ in 0..num_cores {
+                            for _ in 0..n_bands {
                                 band_rx.recv().unwrap();
                             }
                         });
@@ -712,9 +757,10 @@ impl SPPMIntegrator {
                 {
                     // TODO: ProfilePhase _(Prof::SPPMStatsUpdate);
                     // println!("Update pixel values from this pass's photons ...");
-                    let chunk_size: usize = (n_pixels / num_cores as i32) as usize;
+                    let chunk_size: usize = band_size(n_pixels as usize, num_cores);
                     {
                         let bands: Vec<&mut [SPPMPixel]> = pixels.chunks_mut(chunk_size).collect();
+                        let n_bands: usize = bands.len();
                         crossbeam::scope(|scope| {
                             let (band_tx, band_rx) = crossbeam_channel::bounded(num_cores);
                             // spawn worker threads
@@ -759,6 +805,7 @@ impl SPPMIntegrator {
                                         p.vp.bsdf = None;
                                     }
                                 });
+                                scene.flush_ray_counts();
                                 // send progress through the channel to main thread
                                 band_tx
                                     .send(b)
@@ -766,7 +813,7 @@ impl SPPMIntegrator {
                             }
                             // spawn thread to report progress
                             scope.spawn(move |_| {
-                                for _ in 0..num_cores {
+                                for _ in 0..n_bands {
                                     band_rx.recv().unwrap();
                                 }
                             });
@@ -831,7 +878,9 @@ impl SPPMIntegrator {
                 }
             }
             // TODO: progress.Done();
+            stats.total_samples = self.n_iterations as u64 * n_pixels as u64;
         }
+        stats
     }
     pub fn get_camera(&self) -> Arc<Camera> {
         self.camera.clone()
@@ -871,6 +920,12 @@ impl<'p> SPPMPixelListNode<'p> {
     }
 }
 
+/// Number of items per band to split *n* items into (at most)
+/// *num_cores* non-empty bands.
+fn band_size(n: usize, num_cores: usize) -> usize {
+    n.div_ceil(num_cores).max(1)
+}
+
 fn to_grid(p: &Point3f, bounds: &Bounds3f, grid_res: &[i32; 3], pi: &mut Point3i) -> bool {
     let mut in_bounds: bool = true;
     let pg: Vector3f = bounds.offset(p);
@@ -4,8 +4,10 @@ use std::sync::Arc;
 use crate::core::camera::Camera;
 use crate::core::geometry::{vec3_abs_dot_nrm, vec3_dot_nrm};
 use crate::core::geometry::{Bounds2i, Normal3f, Ray, RayDifferential, Vector3f};
+use crate::core::integrator::{parse_pixel_bounds, Integrator, SamplerIntegrator};
 use crate::core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
 use crate::core::material::TransportMode;
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::reflection::BxdfType;
 use crate::core::sampler::Sampler;
@@ -13,14 +15,17 @@ use crate::core::scene::Scene;
 
 // see whitted.h
 
-/// Whitted’s ray-tracing algorithm
+/// Whitted’s ray-tracing algorithm: direct lighting from all lights
+/// at the first hit (without sampling the BSDF), plus perfect
+/// specular reflection and transmission. Rays escaping the scene pick
+/// up the radiance of infinite lights.
 pub struct WhittedIntegrator {
     // inherited from SamplerIntegrator (see integrator.h)
     pub camera: Arc<Camera>,
     pub sampler: Box<Sampler>,
     pixel_bounds: Bounds2i,
     // see whitted.h
-    max_depth: u32,
+    pub max_depth: u32,
 }
 
 impl WhittedIntegrator {
@@ -37,6 +42,19 @@ impl WhittedIntegrator {
             max_depth,
         }
     }
+    /// Reads *"maxdepth"* (5) and optional *"pixelbounds"* (x0 x1 y0
+    /// y1) from *params*.
+    pub fn create(
+        params: &ParamSet,
+        camera: Arc<Camera>,
+        sampler: Box<Sampler>,
+    ) -> Box<Integrator> {
+        let max_depth: i32 = params.find_one_int("maxdepth", 5);
+        let pixel_bounds: Bounds2i = parse_pixel_bounds(params, &camera);
+        Box::new(Integrator::Sampler(SamplerIntegrator::Whitted(
+            WhittedIntegrator::new(max_depth.max(0) as u32, camera, sampler, pixel_bounds),
+        )))
+    }
     pub fn preprocess(&mut self, _scene: &Scene) {}
     pub fn li(
         &self,
@@ -52,13 +70,14 @@ impl WhittedIntegrator {
         if scene.intersect(ray, &mut isect) {
             // compute emitted and reflected light at ray intersection point
 
-            // initialize common variables for Whitted integrator
-            let n: Normal3f = isect.shading.n;
-            let wo: Vector3f = isect.common.wo;
-
             // compute scattering functions for surface interaction
             let mode: TransportMode = TransportMode::Radiance;
             isect.compute_scattering_functions(ray, false, mode);
+
+            // initialize common variables for Whitted integrator (after
+            // bump mapping changed the shading normal)
+            let n: Normal3f = isect.shading.n;
+            let wo: Vector3f = isect.common.wo;
             // if (!isect.bsdf)
             if let Some(ref _bsdf) = isect.bsdf {
             } else {
@@ -68,7 +87,7 @@ impl WhittedIntegrator {
             l += isect.le(&wo);
 
             // add contribution of each light source
-            for light in &scene.lights {
+            for (light_num, light) in scene.lights.iter().enumerate() {
                 let mut wi: Vector3f = Vector3f::default();
                 let mut pdf: Float = 0.0 as Float;
                 let it_common: &InteractionCommon = isect.get_common();
@@ -81,7 +100,7 @@ impl WhittedIntegrator {
                     let bsdf_flags: u8 = BxdfType::BsdfAll as u8;
                     let f: Spectrum = bsdf.f(&wo, &wi, bsdf_flags);
                     if let Some(visibility) = visibility_opt {
-                        if !f.is_black() && visibility.unoccluded(scene) {
+                        if !f.is_black() && visibility.unoccluded_from_light(scene, light_num) {
                             l += f * li * vec3_abs_dot_nrm(&wi, &n) / pdf;
                         }
                     }
@@ -103,7 +122,7 @@ impl WhittedIntegrator {
             l
         } else {
             for light in &scene.lights {
-                l += light.le(ray);
+                l += light.le_visible(ray, depth == 0_i32);
             }
             l
         }
@@ -3,10 +3,11 @@ use std::f32::consts::PI;
 use std::sync::Arc;
 // pbrt
 use crate::core::geometry::{nrm_abs_dot_vec3, nrm_dot_vec3, vec3_coordinate_system};
-use crate::core::geometry::{Normal3f, Point2f, Ray, Vector3f, XYEnum};
-use crate::core::interaction::{Interaction, InteractionCommon};
-use crate::core::light::{LightFlags, VisibilityTester};
+use crate::core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f, XYEnum};
+use crate::core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
+use crate::core::light::{Light, LightFlags, VisibilityTester};
 use crate::core::medium::{Medium, MediumInterface};
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::rng::FLOAT_ONE_MINUS_EPSILON;
 use crate::core::sampling::{cosine_hemisphere_pdf, cosine_sample_hemisphere};
@@ -60,6 +61,30 @@ impl DiffuseAreaLight {
             // world_to_light: Transform::inverse(*light_to_world),
         }
     }
+    /// Creates an area light emitting *L* (times *scale*) from the
+    /// front side (or both sides if *twosided*) of *shape*.
+    pub fn create(
+        params: &ParamSet,
+        light_to_world: &Transform,
+        medium_interface: &MediumInterface,
+        shape: Arc<Shape>,
+    ) -> Arc<Light> {
+        let l: Spectrum = params.find_one_spectrum("L", Spectrum::new(1.0 as Float));
+        let sc: Spectrum = params.find_one_spectrum("scale", Spectrum::new(1.0 as Float));
+        // try "nsamples" first, then "samples"
+        let n_samples: i32 = params.find_one_int("nsamples", 1);
+        let n_samples: i32 = params.find_one_int("samples", n_samples);
+        let two_sided: bool = params.find_one_bool("twosided", false);
+        // TODO: if (PbrtOptions.quickRender) nSamples = std::max(1, nSamples / 4);
+        Arc::new(Light::DiffuseArea(Box::new(DiffuseAreaLight::new(
+            light_to_world,
+            medium_interface,
+            &(l * sc),
+            n_samples,
+            shape,
+            two_sided,
+        ))))
+    }
     // Light
     pub fn sample_li(
         &self,
@@ -86,7 +111,7 @@ impl DiffuseAreaLight {
                     p_error: iref.p_error,
                     wo: iref.wo,
                     n: iref.n,
-                    medium_interface: None,
+                    medium_interface: iref.medium_interface.clone(),
                 },
                 p1: p_shape,
             }),
@@ -107,6 +132,22 @@ impl DiffuseAreaLight {
     pub fn le(&self, _ray: &mut Ray) -> Spectrum {
         Spectrum::default()
     }
+    /// Radiance arriving at *p* from direction *wi* without sampling,
+    /// found by intersecting the ray with the emitting shape.
+    pub fn li_toward(&self, p: Point3f, wi: Vector3f) -> Spectrum {
+        let ray: Ray = Ray {
+            o: p,
+            d: wi,
+            t_max: Float::INFINITY,
+            ..Default::default()
+        };
+        let mut t_hit: Float = 0.0 as Float;
+        let mut isect: SurfaceInteraction = SurfaceInteraction::default();
+        if !self.shape.intersect(&ray, &mut t_hit, &mut isect) {
+            return Spectrum::default();
+        }
+        self.l(&isect.common, &-wi)
+    }
     pub fn pdf_li(&self, iref: &dyn Interaction, wi: Vector3f) -> Float {
         // TODO: ProfilePhase _(Prof::LightPdf);
         self.shape.pdf_with_ref_point(iref, &wi)
@@ -171,6 +212,8 @@ impl DiffuseAreaLight {
         self.n_samples
     }
     // AreaLight
+    /// Emitted radiance leaving *intr* in direction *w*, one-sided
+    /// lights only emit on the side the surface normal points to.
     pub fn l(&self, intr: &InteractionCommon, w: &Vector3f) -> Spectrum {
         if self.two_sided || nrm_dot_vec3(&intr.n, &w) > 0.0 as Float {
             self.l_emit
@@ -1,14 +1,16 @@
 // std
 use std::f32::consts::PI;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 // pbrt
-use crate::core::geometry::vec3_coordinate_system;
-use crate::core::geometry::{Bounds3f, Normal3f, Point2f, Point3f, Ray, Vector3f};
+use crate::core::geometry::{coordinate_system, vec3_coordinate_system, vec3_dot_vec3};
+use crate::core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
 use crate::core::interaction::{Interaction, InteractionCommon};
-use crate::core::light::{LightFlags, VisibilityTester};
+use crate::core::light::{DeltaGeometry, Light, LightFlags, VisibilityTester};
 use crate::core::medium::MediumInterface;
-use crate::core::pbrt::{Float, Spectrum};
+use crate::core::paramset::ParamSet;
+use crate::core::pbrt::{radians, Float, Spectrum};
 use crate::core::sampling::concentric_sample_disk;
+use crate::core::sampling::{uniform_cone_pdf, uniform_sample_cone};
 use crate::core::scene::Scene;
 use crate::core::transform::Transform;
 
@@ -18,6 +20,9 @@ pub struct DistantLight {
     // private data (see distant.h)
     pub l: Spectrum,
     pub w_light: Vector3f,
+    /// Cosine of the half-angle of the cone the light is seen
+    /// through (1.0 for an ideal, delta distant light)
+    pub cos_theta_max: Float,
     pub world_center: RwLock<Point3f>,
     pub world_radius: RwLock<Float>,
     // inherited from class Light (see light.h)
@@ -29,34 +34,101 @@ pub struct DistantLight {
 }
 
 impl DistantLight {
-    pub fn new(light_to_world: &Transform, l: &Spectrum, w_light: &Vector3f) -> Self {
+    /// A positive *angle* (in degrees) turns the light into a soft
+    /// distant light (e.g. the sun) which subtends a cone of that
+    /// half-angle. It is no longer a delta light, but an infinite
+    /// one, so escaping rays can hit it and MIS applies.
+    pub fn new(light_to_world: &Transform, l: &Spectrum, w_light: &Vector3f, angle: Float) -> Self {
+        let cos_theta_max: Float = if angle > 0.0 as Float {
+            radians(angle.min(90.0 as Float)).cos()
+        } else {
+            1.0 as Float
+        };
+        let flags: u8 = if cos_theta_max < 1.0 as Float {
+            LightFlags::Infinite as u8
+        } else {
+            LightFlags::DeltaDirection as u8
+        };
         DistantLight {
             l: *l,
             w_light: light_to_world.transform_vector(&*w_light).normalize(),
+            cos_theta_max,
             world_center: RwLock::new(Point3f::default()),
             world_radius: RwLock::new(0.0),
-            flags: LightFlags::DeltaDirection as u8,
+            flags,
             n_samples: 1_i32,
             medium_interface: MediumInterface::default(),
             light_to_world: Transform::default(),
             world_to_light: Transform::default(),
         }
     }
+    /// Reads the radiance *L* and the *from*/*to* points from
+    /// *params*, the light arrives from *from* towards *to*. The
+    /// defaults give a light shining down the positive z axis.
+    pub fn create(params: &ParamSet, light_to_world: &Transform) -> Arc<Light> {
+        let l: Spectrum = params.find_one_spectrum("L", Spectrum::new(1.0 as Float));
+        let sc: Spectrum = params.find_one_spectrum("scale", Spectrum::new(1.0 as Float));
+        let from: Point3f = params.find_one_point3f(
+            "from",
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        let to: Point3f = params.find_one_point3f(
+            "to",
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+        );
+        let dir: Vector3f = from - to;
+        // half-angle (in degrees) of a soft distant light, e.g. the sun
+        let angle: Float = params.find_one_float("angle", 0.0 as Float);
+        Arc::new(Light::Distant(Box::new(DistantLight::new(
+            light_to_world,
+            &(l * sc),
+            &dir,
+            angle,
+        ))))
+    }
+    pub fn is_soft(&self) -> bool {
+        self.cos_theta_max < 1.0 as Float
+    }
+    /// Radiance of a soft distant light. *L* is spread over the cone,
+    /// so the irradiance at normal incidence matches the delta light.
+    fn cone_radiance(&self, w: &Vector3f) -> Spectrum {
+        if vec3_dot_vec3(w, &self.w_light) < self.cos_theta_max {
+            return Spectrum::default();
+        }
+        self.l * uniform_cone_pdf(self.cos_theta_max)
+    }
     // Light
     pub fn sample_li(
         &self,
         iref: &InteractionCommon,
-        _u: Point2f,
+        u: Point2f,
         wi: &mut Vector3f,
         pdf: &mut Float,
     ) -> (Spectrum, Option<VisibilityTester>) {
         // TODO: ProfilePhase _(Prof::LightSample);
-        *wi = self.w_light;
-        *pdf = 1.0 as Float;
-        let p_outside: Point3f =
-            iref.p + self.w_light * (2.0 as Float * *self.world_radius.read().unwrap());
+        let li: Spectrum = if self.is_soft() {
+            // sample a direction within the cone around the light direction
+            let (v1, v2) = coordinate_system(self.w_light);
+            let w: Vector3f = uniform_sample_cone(u, self.cos_theta_max);
+            *wi = (v1 * w.x + v2 * w.y + self.w_light * w.z).normalize();
+            *pdf = uniform_cone_pdf(self.cos_theta_max);
+            self.l * *pdf
+        } else {
+            *wi = self.w_light;
+            *pdf = 1.0 as Float;
+            self.l
+        };
+        let p_outside: Point3f = iref.p + *wi * (2.0 as Float * *self.world_radius.read().unwrap());
         (
-            self.l,
+            li,
             Some(VisibilityTester {
                 p0: InteractionCommon {
                     p: iref.p,
@@ -64,7 +136,7 @@ impl DistantLight {
                     p_error: iref.p_error,
                     wo: iref.wo,
                     n: iref.n,
-                    medium_interface: None,
+                    medium_interface: iref.medium_interface.clone(),
                 },
                 p1: InteractionCommon {
                     p: p_outside,
@@ -89,21 +161,39 @@ impl DistantLight {
     /// method to get the bound. This method is called at the end of
     /// the **Scene** constructor.
     pub fn preprocess(&self, scene: &Scene) {
-        let mut world_center_ref = self.world_center.write().unwrap();
-        let mut world_radius_ref = self.world_radius.write().unwrap();
-        Bounds3f::bounding_sphere(
-            &scene.world_bound(),
-            &mut world_center_ref,
-            &mut world_radius_ref,
-        );
+        let (world_center, world_radius) = scene.world_bounding_sphere();
+        *self.world_center.write().unwrap() = world_center;
+        *self.world_radius.write().unwrap() = world_radius;
     }
-    /// Default implementation returns no emitted radiance for a ray
-    /// that escapes the scene bounds.
-    pub fn le(&self, _ray: &mut Ray) -> Spectrum {
-        Spectrum::new(0.0 as Float)
+    /// Returns no emitted radiance for a ray that escapes the scene
+    /// bounds, unless the ray leaves within the cone of a soft
+    /// distant light.
+    pub fn le(&self, ray: &mut Ray) -> Spectrum {
+        if !self.is_soft() {
+            return Spectrum::new(0.0 as Float);
+        }
+        self.cone_radiance(&ray.d.normalize())
     }
-    pub fn pdf_li(&self, _iref: &dyn Interaction, _wi: Vector3f) -> Float {
-        0.0 as Float
+    /// Radiance arriving from direction *wi* without sampling. As a
+    /// delta light it only contributes if *wi* is exactly the light
+    /// direction.
+    pub fn li_toward(&self, _p: Point3f, wi: Vector3f) -> Spectrum {
+        if self.is_soft() {
+            return self.cone_radiance(&wi.normalize());
+        }
+        if wi != self.w_light {
+            return Spectrum::default();
+        }
+        self.l
+    }
+    /// For a soft distant light the density of *sample_li()*, so MIS
+    /// weights for BSDF sampled directions within the cone are
+    /// correct.
+    pub fn pdf_li(&self, _iref: &dyn Interaction, wi: Vector3f) -> Float {
+        if !self.is_soft() || vec3_dot_vec3(&wi.normalize(), &self.w_light) < self.cos_theta_max {
+            return 0.0 as Float;
+        }
+        uniform_cone_pdf(self.cos_theta_max)
     }
     pub fn sample_le(
         &self,
@@ -150,6 +240,13 @@ impl DistantLight {
         *pdf_pos = 1.0 as Float / (PI * world_radius * world_radius);
         *pdf_dir = 0.0 as Float;
     }
+    /// A soft distant light is not a delta light anymore.
+    pub fn delta_geometry(&self) -> Option<DeltaGeometry> {
+        if self.is_soft() {
+            return None;
+        }
+        Some(DeltaGeometry::Direction(self.w_light))
+    }
     pub fn get_flags(&self) -> u8 {
         self.flags
     }
@@ -1,6 +1,7 @@
 // std
 use std::f32::consts::PI;
-use std::io::BufReader;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 use std::sync::Arc;
 // others
 #[cfg(feature = "openexr")]
@@ -8,15 +9,20 @@ use half::f16;
 #[cfg(feature = "openexr")]
 use openexr::{FrameBufferMut, InputFile, PixelType};
 // pbrt
-use crate::core::geometry::{pnt3_distance_squared, spherical_phi, spherical_theta};
+use crate::core::geometry::{
+    pnt3_distance_squared, spherical_direction, spherical_phi, spherical_theta,
+};
 use crate::core::geometry::{Normal3f, Point2f, Point2i, Point3f, Ray, Vector3f};
 use crate::core::interaction::{Interaction, InteractionCommon};
-use crate::core::light::{LightFlags, VisibilityTester};
+use crate::core::light::{
+    points_at_light, solid_angle_integral, DeltaGeometry, Light, LightFlags, VisibilityTester,
+};
 use crate::core::medium::MediumInterface;
 use crate::core::mipmap::{ImageWrap, MipMap};
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::pbrt::{INV_2_PI, INV_PI};
-use crate::core::sampling::{uniform_sample_sphere, uniform_sphere_pdf};
+use crate::core::sampling::{uniform_sample_sphere, uniform_sphere_pdf, Distribution2D};
 use crate::core::scene::Scene;
 use crate::core::transform::Transform;
 
@@ -41,6 +47,210 @@ fn decode_f16(half: u16) -> f32 {
     }
 }
 
+/// Resolution (phi x theta) of the tabulated **LightProfile** used
+/// for importance sampling.
+const PROFILE_DISTRIBUTION_RES: (usize, usize) = (128, 64);
+
+/// Intensity distribution of a fixture on a spherical *theta x phi*
+/// grid, e.g. read from a *theta,phi,intensity* CSV file. Angles are
+/// the same as for the image map of a **GonioPhotometricLight**:
+/// *theta* is measured from the light's +y axis, *phi* around it
+/// starting at +x. Intensities are bilinearly interpolated, wrapping
+/// around in *phi* and clamped to the measured *theta* range.
+#[derive(Clone)]
+pub struct LightProfile {
+    /// sorted grid angles (in radians)
+    pub thetas: Vec<Float>,
+    pub phis: Vec<Float>,
+    /// *phis.len()* intensities per theta
+    pub intensity: Vec<Float>,
+    /// (phi, theta) distribution proportional to intensity * sin(theta)
+    pub distribution: Distribution2D,
+    /// intensity integrated over the sphere
+    pub integral: Float,
+}
+
+impl LightProfile {
+    /// Reads one *theta,phi,intensity* sample (angles in degrees) per
+    /// line. Empty lines, comments (#) and a header line are skipped.
+    pub fn read_csv(filename: &str) -> Option<LightProfile> {
+        let file = match File::open(filename) {
+            Ok(file) => file,
+            Err(_) => {
+                println!("ERROR: Unable to open light profile {:?}", filename);
+                return None;
+            }
+        };
+        let mut samples: Vec<(Float, Float, Float)> = Vec::new();
+        for (line_number, line_result) in BufReader::new(file).lines().enumerate() {
+            let line: String = line_result.ok()?;
+            let line: &str = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values: Vec<Float> = line
+                .split(',')
+                .filter_map(|token| token.trim().parse::<Float>().ok())
+                .collect();
+            if values.len() == 3 {
+                samples.push((values[0], values[1], values[2]));
+            } else if line_number > 0 || !samples.is_empty() {
+                println!(
+                    "WARNING: Ignoring line {} of light profile {:?}",
+                    line_number + 1,
+                    filename
+                );
+            }
+        }
+        LightProfile::from_samples(&samples)
+    }
+    /// Builds the profile from *(theta, phi, intensity)* samples
+    /// (angles in degrees) which have to cover a full grid. Values at
+    /// the poles (theta 0 or 180 degrees) get averaged over phi.
+    pub fn from_samples(samples: &[(Float, Float, Float)]) -> Option<LightProfile> {
+        let mut thetas: Vec<Float> = Vec::new();
+        let mut phis: Vec<Float> = Vec::new();
+        for (theta, phi, _intensity) in samples {
+            thetas.push(theta.to_radians());
+            phis.push(phi.rem_euclid(360.0 as Float).to_radians());
+        }
+        let eps: Float = 1e-4 as Float;
+        for angles in [&mut thetas, &mut phis].iter_mut() {
+            angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            angles.dedup_by(|a, b| (*a - *b).abs() < eps);
+        }
+        // phi = 360 degrees is the same as phi = 0
+        if phis.len() > 1 && (phis[phis.len() - 1] - 2.0 as Float * PI).abs() < eps {
+            phis.pop();
+        }
+        if thetas.is_empty() {
+            println!("ERROR: Light profile without samples");
+            return None;
+        }
+        if thetas[0] < 0.0 as Float || thetas[thetas.len() - 1] > PI + eps {
+            println!("ERROR: Light profile theta outside of [0, 180] degrees");
+            return None;
+        }
+        let index = |angles: &[Float], a: Float| angles.iter().position(|x| (x - a).abs() < eps);
+        let n_phi: usize = phis.len();
+        let mut intensity: Vec<Float> = vec![-1.0 as Float; thetas.len() * n_phi];
+        for (theta, phi, value) in samples {
+            let phi: Float = phi.rem_euclid(360.0 as Float).to_radians();
+            let ip: usize = index(&phis, phi).unwrap_or(0);
+            let it: usize = index(&thetas, theta.to_radians()).unwrap();
+            intensity[it * n_phi + ip] = value.max(0.0 as Float);
+        }
+        if intensity.iter().any(|value| *value < 0.0 as Float) {
+            println!(
+                "ERROR: Light profile doesn't cover a full {} x {} (theta x phi) grid",
+                thetas.len(),
+                n_phi
+            );
+            return None;
+        }
+        // intensity at the poles can't depend on phi
+        let n_theta: usize = thetas.len();
+        for (it, theta) in [(0_usize, thetas[0]), (n_theta - 1, thetas[n_theta - 1])].iter() {
+            if theta.sin().abs() < eps {
+                let row: &mut [Float] = &mut intensity[it * n_phi..(it + 1) * n_phi];
+                let average: Float = row.iter().sum::<Float>() / n_phi as Float;
+                row.iter_mut().for_each(|value| *value = average);
+            }
+        }
+        let mut profile: LightProfile = LightProfile {
+            thetas,
+            phis,
+            intensity,
+            distribution: Distribution2D::default(),
+            integral: 0.0 as Float,
+        };
+        // tabulate intensity * sin(theta) for importance sampling
+        let (nu, nv) = PROFILE_DISTRIBUTION_RES;
+        let mut func: Vec<Float> = Vec::with_capacity(nu * nv);
+        for v in 0..nv {
+            let theta: Float = (v as Float + 0.5 as Float) / nv as Float * PI;
+            for u in 0..nu {
+                let phi: Float = (u as Float + 0.5 as Float) / nu as Float * 2.0 as Float * PI;
+                func.push(profile.evaluate(theta, phi) * theta.sin());
+            }
+        }
+        profile.integral =
+            func.iter().sum::<Float>() * (PI / nv as Float) * (2.0 as Float * PI / nu as Float);
+        profile.distribution = Distribution2D::new(func, nu as i32, nv as i32);
+        Some(profile)
+    }
+    /// Bilinearly interpolated intensity (angles in radians).
+    pub fn evaluate(&self, theta: Float, phi: Float) -> Float {
+        let (t0, t1, dt) = LightProfile::find_interval(&self.thetas, theta, None);
+        let (p0, p1, dp) = LightProfile::find_interval(&self.phis, phi, Some(2.0 as Float * PI));
+        let n_phi: usize = self.phis.len();
+        let i = |it: usize, ip: usize| self.intensity[it * n_phi + ip];
+        (1.0 as Float - dt) * ((1.0 as Float - dp) * i(t0, p0) + dp * i(t0, p1))
+            + dt * ((1.0 as Float - dp) * i(t1, p0) + dp * i(t1, p1))
+    }
+    /// Grid indices enclosing angle *a* and the interpolation weight
+    /// of the second one. With a *period* the grid wraps around,
+    /// otherwise *a* gets clamped to the grid.
+    fn find_interval(angles: &[Float], a: Float, period: Option<Float>) -> (usize, usize, Float) {
+        let n: usize = angles.len();
+        if n == 1 {
+            return (0, 0, 0.0 as Float);
+        }
+        if let Some(period) = period {
+            let a: Float = a.rem_euclid(period);
+            if a < angles[0] || a >= angles[n - 1] {
+                // between last and first angle (wrap around)
+                let width: Float = angles[0] + period - angles[n - 1];
+                let mut offset: Float = a - angles[n - 1];
+                if offset < 0.0 as Float {
+                    offset += period;
+                }
+                return (n - 1, 0, (offset / width).min(1.0 as Float));
+            }
+            let k: usize = angles.iter().rposition(|x| *x <= a).unwrap();
+            let dt: Float = (a - angles[k]) / (angles[k + 1] - angles[k]);
+            (k, k + 1, dt)
+        } else {
+            if a <= angles[0] {
+                return (0, 0, 0.0 as Float);
+            }
+            if a >= angles[n - 1] {
+                return (n - 1, n - 1, 0.0 as Float);
+            }
+            let k: usize = angles.iter().rposition(|x| *x <= a).unwrap();
+            let dt: Float = (a - angles[k]) / (angles[k + 1] - angles[k]);
+            (k, k + 1, dt)
+        }
+    }
+    /// Samples *(theta, phi)* proportional to the intensity and
+    /// returns the solid angle density in *pdf*.
+    pub fn sample(&self, u: Point2f, pdf: &mut Float) -> (Float, Float) {
+        let mut map_pdf: Float = 0.0 as Float;
+        let uv: Point2f = self.distribution.sample_continuous(u, &mut map_pdf);
+        let theta: Float = uv.y * PI;
+        let phi: Float = uv.x * 2.0 as Float * PI;
+        let sin_theta: Float = theta.sin();
+        if map_pdf == 0.0 as Float || sin_theta == 0.0 as Float {
+            *pdf = 0.0 as Float;
+        } else {
+            *pdf = map_pdf / (2.0 as Float * PI * PI * sin_theta);
+        }
+        (theta, phi)
+    }
+    /// Solid angle density of *sample()*.
+    pub fn pdf(&self, theta: Float, phi: Float) -> Float {
+        let sin_theta: Float = theta.sin();
+        if sin_theta == 0.0 as Float {
+            return 0.0 as Float;
+        }
+        let uv: Point2f = Point2f {
+            x: phi * INV_2_PI,
+            y: theta * INV_PI,
+        };
+        self.distribution.pdf(uv) / (2.0 as Float * PI * PI * sin_theta)
+    }
+}
+
 // see goniometric.h
 
 #[derive(Clone)]
@@ -48,6 +258,8 @@ pub struct GonioPhotometricLight {
     pub p_light: Point3f,
     pub i: Spectrum,
     pub mipmap: Option<Arc<MipMap<Spectrum>>>,
+    /// replaces the image map and is used for importance sampling
+    pub profile: Option<Arc<LightProfile>>,
     // inherited from class Light (see light.h)
     pub flags: u8,
     pub n_samples: i32,
@@ -136,11 +348,12 @@ impl GonioPhotometricLight {
                         p_light: light_to_world.transform_point(&Point3f::default()),
                         i: *i,
                         mipmap: Some(projection_map),
+                        profile: None,
                         flags: LightFlags::DeltaPosition as u8,
                         n_samples: 1_i32,
-                        medium_interface: MediumInterface::default(),
-                        light_to_world: Transform::default(),
-                        world_to_light: Transform::default(),
+                        medium_interface: medium_interface.clone(),
+                        light_to_world: *light_to_world,
+                        world_to_light: Transform::inverse(&*light_to_world),
                     }
                 } else {
                     // try to open an HDR image instead (TODO: check extension upfront)
@@ -155,17 +368,18 @@ impl GonioPhotometricLight {
                 p_light: light_to_world.transform_point(&Point3f::default()),
                 i: *i,
                 mipmap: None,
+                profile: None,
                 flags: LightFlags::DeltaPosition as u8,
                 n_samples: 1_i32,
-                medium_interface: MediumInterface::default(),
-                light_to_world: Transform::default(),
-                world_to_light: Transform::default(),
+                medium_interface: medium_interface.clone(),
+                light_to_world: *light_to_world,
+                world_to_light: Transform::inverse(&*light_to_world),
             }
         }
     }
     pub fn new_hdr(
         light_to_world: &Transform,
-        _medium_interface: &MediumInterface,
+        medium_interface: &MediumInterface,
         i: &Spectrum,
         texname: String,
     ) -> Self {
@@ -206,9 +420,10 @@ impl GonioPhotometricLight {
                             p_light,
                             i: *i,
                             mipmap: Some(projection_map),
+                            profile: None,
                             flags: LightFlags::DeltaPosition as u8,
                             n_samples: 1_i32,
-                            medium_interface: MediumInterface::default(),
+                            medium_interface: medium_interface.clone(),
                             light_to_world: *light_to_world,
                             world_to_light: Transform::inverse(&*light_to_world),
                         };
@@ -222,24 +437,93 @@ impl GonioPhotometricLight {
             p_light: light_to_world.transform_point(&Point3f::default()),
             i: *i,
             mipmap: None,
+            profile: None,
             flags: LightFlags::DeltaPosition as u8,
             n_samples: 1_i32,
-            medium_interface: MediumInterface::default(),
-            light_to_world: Transform::default(),
-            world_to_light: Transform::default(),
+            medium_interface: medium_interface.clone(),
+            light_to_world: *light_to_world,
+            world_to_light: Transform::inverse(&*light_to_world),
         }
     }
-    pub fn scale(&self, w: &Vector3f) -> Spectrum {
+    /// Light with a spherical intensity *profile* instead of an image
+    /// map, see **LightProfile**.
+    pub fn new_profile(
+        light_to_world: &Transform,
+        medium_interface: &MediumInterface,
+        i: &Spectrum,
+        profile: Arc<LightProfile>,
+    ) -> Self {
+        GonioPhotometricLight {
+            p_light: light_to_world.transform_point(&Point3f::default()),
+            i: *i,
+            mipmap: None,
+            profile: Some(profile),
+            flags: LightFlags::DeltaPosition as u8,
+            n_samples: 1_i32,
+            medium_interface: medium_interface.clone(),
+            light_to_world: *light_to_world,
+            world_to_light: Transform::inverse(&*light_to_world),
+        }
+    }
+    /// Creates the light from the scene file parameters: intensity
+    /// *"I"* times *"scale"*, and either an intensity image *"mapname"*
+    /// or a *"profile"* (theta,phi,intensity CSV, see
+    /// **LightProfile**).
+    pub fn create(
+        params: &ParamSet,
+        light_to_world: &Transform,
+        medium_interface: &MediumInterface,
+    ) -> Arc<Light> {
+        let i: Spectrum = params.find_one_spectrum("I", Spectrum::new(1.0 as Float));
+        let sc: Spectrum = params.find_one_spectrum("scale", Spectrum::new(1.0 as Float));
+        let texname: String = params.find_one_filename("mapname", String::from(""));
+        let profile_name: String = params.find_one_filename("profile", String::from(""));
+        let mut profile: Option<Arc<LightProfile>> = None;
+        if !profile_name.is_empty() {
+            profile = LightProfile::read_csv(&profile_name).map(Arc::new);
+        }
+        let gonio_light: GonioPhotometricLight = if let Some(profile) = profile {
+            GonioPhotometricLight::new_profile(light_to_world, medium_interface, &(i * sc), profile)
+        } else {
+            GonioPhotometricLight::new(light_to_world, medium_interface, &(i * sc), texname)
+        };
+        Arc::new(Light::GonioPhotometric(Box::new(gonio_light)))
+    }
+    /// Bilinearly interpolated image value at *st* (*phi* and *theta*
+    /// mapped to [0, 1]), wrapping around in *phi* and clamped at the
+    /// poles.
+    pub fn lookup_map(mipmap: &MipMap<Spectrum>, st: Point2f) -> Spectrum {
+        let width: isize = mipmap.pyramid[0].u_size() as isize;
+        let height: isize = mipmap.pyramid[0].v_size() as isize;
+        let s: Float = st.x * width as Float - 0.5 as Float;
+        let t: Float = st.y * height as Float - 0.5 as Float;
+        let s0: isize = s.floor() as isize;
+        let t0: isize = t.floor() as isize;
+        let ds: Float = s - s0 as Float;
+        let dt: Float = t - t0 as Float;
+        let texel =
+            |s: isize, t: isize| *mipmap.texel(0, s.rem_euclid(width), t.max(0).min(height - 1));
+        texel(s0, t0) * ((1.0 as Float - ds) * (1.0 as Float - dt))
+            + texel(s0 + 1, t0) * (ds * (1.0 as Float - dt))
+            + texel(s0, t0 + 1) * ((1.0 as Float - ds) * dt)
+            + texel(s0 + 1, t0 + 1) * (ds * dt)
+    }
+    /// Profile angles *(theta, phi)* of world space direction *w*.
+    fn light_angles(&self, w: &Vector3f) -> (Float, Float) {
         let mut wp: Vector3f = self.world_to_light.transform_vector(w).normalize();
         std::mem::swap(&mut wp.y, &mut wp.z);
-        let theta: Float = spherical_theta(&wp);
-        let phi: Float = spherical_phi(&wp);
-        if let Some(mipmap) = &self.mipmap {
+        (spherical_theta(&wp), spherical_phi(&wp))
+    }
+    pub fn scale(&self, w: &Vector3f) -> Spectrum {
+        let (theta, phi) = self.light_angles(w);
+        if let Some(profile) = &self.profile {
+            Spectrum::new(profile.evaluate(theta, phi))
+        } else if let Some(mipmap) = &self.mipmap {
             let st: Point2f = Point2f {
                 x: phi * INV_2_PI,
                 y: theta * INV_PI,
             };
-            mipmap.lookup_pnt_flt(st, 0.0 as Float)
+            GonioPhotometricLight::lookup_map(mipmap, st)
         } else {
             Spectrum::new(1.0 as Float)
         }
@@ -263,7 +547,7 @@ impl GonioPhotometricLight {
                     p_error: iref.p_error,
                     wo: iref.wo,
                     n: iref.n,
-                    medium_interface: None,
+                    medium_interface: iref.medium_interface.clone(),
                 },
                 p1: InteractionCommon {
                     p: self.p_light,
@@ -277,16 +561,10 @@ impl GonioPhotometricLight {
         )
     }
     pub fn power(&self) -> Spectrum {
-        if let Some(mipmap) = &self.mipmap {
-            mipmap.lookup_pnt_flt(
-                Point2f {
-                    x: 0.5 as Float,
-                    y: 0.5 as Float,
-                },
-                0.5 as Float,
-            ) * self.i
-                * 4.0 as Float
-                * PI
+        if let Some(profile) = &self.profile {
+            self.i * profile.integral
+        } else if let Some(mipmap) = &self.mipmap {
+            self.i * solid_angle_integral(mipmap)
         } else {
             Spectrum::new(1.0 as Float) * self.i * 4.0 as Float * PI
         }
@@ -297,6 +575,13 @@ impl GonioPhotometricLight {
     pub fn le(&self, _ray: &mut Ray) -> Spectrum {
         Spectrum::new(0.0 as Float)
     }
+    /// Radiance arriving at *p* from direction *wi* without sampling.
+    pub fn li_toward(&self, p: Point3f, wi: Vector3f) -> Spectrum {
+        if !points_at_light(&p, &wi, &self.p_light) {
+            return Spectrum::default();
+        }
+        self.i * self.scale(&-wi) / pnt3_distance_squared(&self.p_light, &p)
+    }
     pub fn pdf_li(&self, _iref: &dyn Interaction, _wi: Vector3f) -> Float {
         0.0 as Float
     }
@@ -310,6 +595,26 @@ impl GonioPhotometricLight {
         pdf_pos: &mut Float,
         pdf_dir: &mut Float,
     ) -> Spectrum {
+        if let Some(profile) = &self.profile {
+            // importance sample the profile
+            let (theta, phi) = profile.sample(u1, pdf_dir);
+            if *pdf_dir == 0.0 as Float {
+                return Spectrum::default();
+            }
+            let mut wp: Vector3f = spherical_direction(theta.sin(), theta.cos(), phi);
+            std::mem::swap(&mut wp.y, &mut wp.z);
+            *ray = Ray {
+                o: self.p_light,
+                d: self.light_to_world.transform_vector(&wp).normalize(),
+                t_max: std::f32::INFINITY,
+                time,
+                differential: None,
+                medium: None,
+            };
+            *n_light = Normal3f::from(ray.d);
+            *pdf_pos = 1.0 as Float;
+            return self.i * profile.evaluate(theta, phi);
+        }
         *ray = Ray {
             o: self.p_light,
             d: uniform_sample_sphere(u1),
@@ -323,20 +628,22 @@ impl GonioPhotometricLight {
         *pdf_dir = uniform_sphere_pdf();
         self.i * self.scale(&ray.d)
     }
+    pub fn delta_geometry(&self) -> Option<DeltaGeometry> {
+        Some(DeltaGeometry::Position(self.p_light))
+    }
     pub fn get_flags(&self) -> u8 {
         self.flags
     }
     pub fn get_n_samples(&self) -> i32 {
         self.n_samples
     }
-    pub fn pdf_le(
-        &self,
-        _ray: &Ray,
-        _n_light: &Normal3f,
-        pdf_pos: &mut Float,
-        pdf_dir: &mut Float,
-    ) {
+    pub fn pdf_le(&self, ray: &Ray, _n_light: &Normal3f, pdf_pos: &mut Float, pdf_dir: &mut Float) {
         *pdf_pos = 0.0 as Float;
-        *pdf_dir = uniform_sphere_pdf();
+        if let Some(profile) = &self.profile {
+            let (theta, phi) = self.light_angles(&ray.d);
+            *pdf_dir = profile.pdf(theta, phi);
+        } else {
+            *pdf_dir = uniform_sphere_pdf();
+        }
     }
 }
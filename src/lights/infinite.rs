@@ -1,6 +1,7 @@
 // std
 use std::f32::consts::PI;
 use std::io::BufReader;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 // others
 #[cfg(feature = "openexr")]
@@ -9,11 +10,12 @@ use half::f16;
 use openexr::{FrameBufferMut, InputFile, PixelType};
 // pbrt
 use crate::core::geometry::{spherical_phi, spherical_theta, vec3_coordinate_system};
-use crate::core::geometry::{Bounds3f, Normal3f, Point2f, Point2i, Point3f, Ray, Vector3f, XYEnum};
+use crate::core::geometry::{Normal3f, Point2f, Point2i, Point3f, Ray, Vector3f, XYEnum};
 use crate::core::interaction::{Interaction, InteractionCommon};
-use crate::core::light::{LightFlags, VisibilityTester};
+use crate::core::light::{solid_angle_integral, Light, LightFlags, VisibilityTester};
 use crate::core::medium::MediumInterface;
 use crate::core::mipmap::{ImageWrap, MipMap};
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::pbrt::{INV_2_PI, INV_PI};
 use crate::core::sampling::concentric_sample_disk;
@@ -115,49 +117,12 @@ impl InfiniteAreaLight {
                             ) * *l,
                         );
                     }
-                    // create _MipMap_ from converted texels (see above)
-                    let do_trilinear: bool = false;
-                    let max_aniso: Float = 8.0 as Float;
-                    let wrap_mode: ImageWrap = ImageWrap::Repeat;
-                    let lmap = Arc::new(MipMap::new(
+                    InfiniteAreaLight::from_texels(
+                        light_to_world,
                         resolution,
                         &texels[..],
-                        do_trilinear,
-                        max_aniso,
-                        wrap_mode,
-                    ));
-
-                    // initialize sampling PDFs for infinite area light
-
-                    // compute scalar-valued image _img_ from environment map
-                    let width: i32 = 2_i32 * lmap.width();
-                    let height: i32 = 2_i32 * lmap.height();
-                    let mut img: Vec<Float> = Vec::new();
-                    let fwidth: Float = 0.5 as Float / (width as Float).min(height as Float);
-                    // TODO: ParallelFor(...) {...}
-                    for v in 0..height {
-                        let vp: Float = (v as Float + 0.5 as Float) / height as Float;
-                        let sin_theta: Float =
-                            (PI * (v as Float + 0.5 as Float) / height as Float).sin();
-                        for u in 0..width {
-                            let up: Float = (u as Float + 0.5 as Float) / width as Float;
-                            let st: Point2f = Point2f { x: up, y: vp };
-                            img.push(lmap.lookup_pnt_flt(st, fwidth).y() * sin_theta);
-                        }
-                    }
-                    let distribution: Arc<Distribution2D> =
-                        Arc::new(Distribution2D::new(img, width, height));
-                    InfiniteAreaLight {
-                        lmap,
-                        world_center: RwLock::new(Point3f::default()),
-                        world_radius: RwLock::new(0.0),
-                        distribution,
-                        flags: LightFlags::Infinite as u8,
-                        n_samples: std::cmp::max(1_i32, n_samples),
-                        medium_interface: MediumInterface::default(),
-                        light_to_world: *light_to_world,
-                        world_to_light: Transform::inverse(&*light_to_world),
-                    }
+                        n_samples,
+                    )
                 } else {
                     // try to open an HDR image instead (TODO: check extension upfront)
                     InfiniteAreaLight::new_hdr(light_to_world, l, n_samples, texmap)
@@ -167,7 +132,7 @@ impl InfiniteAreaLight {
                 InfiniteAreaLight::new_hdr(light_to_world, l, n_samples, texmap)
             }
         } else {
-            InfiniteAreaLight::default(n_samples, l)
+            InfiniteAreaLight::default(light_to_world, n_samples, l)
         }
     }
     pub fn new_hdr(
@@ -178,7 +143,13 @@ impl InfiniteAreaLight {
     ) -> Self {
         // read texel data from _texmap_ and initialize _Lmap_
         if texmap != "" {
-            let file = std::fs::File::open(texmap).unwrap();
+            let file = match std::fs::File::open(texmap.clone()) {
+                Ok(file) => file,
+                Err(err) => {
+                    println!("ERROR: Can't open environment map {:?}: {}", texmap, err);
+                    return InfiniteAreaLight::default(light_to_world, n_samples, l);
+                }
+            };
             let reader = BufReader::new(file);
             let img_result = image::hdr::HdrDecoder::with_strictness(reader, false);
             if img_result.is_ok() {
@@ -198,66 +169,69 @@ impl InfiniteAreaLight {
                         &mut texels,
                     );
                     if img_result.is_ok() {
-                        // create _MipMap_ from converted texels (see above)
-                        let do_trilinear: bool = false;
-                        let max_aniso: Float = 8.0 as Float;
-                        let wrap_mode: ImageWrap = ImageWrap::Repeat;
-                        let lmap = Arc::new(MipMap::new(
+                        return InfiniteAreaLight::from_texels(
+                            light_to_world,
                             resolution,
                             &texels[..],
-                            do_trilinear,
-                            max_aniso,
-                            wrap_mode,
-                        ));
-
-                        // initialize sampling PDFs for infinite area light
-
-                        // compute scalar-valued image _img_ from environment map
-                        let width: i32 = 2_i32 * lmap.width();
-                        let height: i32 = 2_i32 * lmap.height();
-                        let mut img: Vec<Float> = Vec::new();
-                        let fwidth: Float = 0.5 as Float / (width as Float).min(height as Float);
-                        // TODO: ParallelFor(...) {...}
-                        for v in 0..height {
-                            let vp: Float = (v as Float + 0.5 as Float) / height as Float;
-                            let sin_theta: Float =
-                                (PI * (v as Float + 0.5 as Float) / height as Float).sin();
-                            for u in 0..width {
-                                let up: Float = (u as Float + 0.5 as Float) / width as Float;
-                                let st: Point2f = Point2f { x: up, y: vp };
-                                img.push(lmap.lookup_pnt_flt(st, fwidth).y() * sin_theta);
-                            }
-                        }
-                        let distribution: Arc<Distribution2D> =
-                            Arc::new(Distribution2D::new(img, width, height));
-                        return InfiniteAreaLight {
-                            lmap,
-                            world_center: RwLock::new(Point3f::default()),
-                            world_radius: RwLock::new(0.0),
-                            distribution,
-                            flags: LightFlags::Infinite as u8,
-                            n_samples: std::cmp::max(1_i32, n_samples),
-                            medium_interface: MediumInterface::default(),
-                            light_to_world: *light_to_world,
-                            world_to_light: Transform::inverse(&*light_to_world),
-                        };
+                            n_samples,
+                        );
                     }
                 }
             } else {
                 println!("WARNING: InfiniteAreaLight::new() ... no OpenEXR support !!!");
             }
         }
-        InfiniteAreaLight::default(n_samples, l)
+        InfiniteAreaLight::default(light_to_world, n_samples, l)
+    }
+    /// Creates an environment light from the equirectangular image
+    /// *mapname* (relative names are looked up in
+    /// *search_directory*), scaled by *L* and *scale*.
+    pub fn create(
+        params: &ParamSet,
+        light_to_world: &Transform,
+        search_directory: Option<&PathBuf>,
+    ) -> Arc<Light> {
+        let l: Spectrum = params.find_one_spectrum("L", Spectrum::new(1.0 as Float));
+        let sc: Spectrum = params.find_one_spectrum("scale", Spectrum::new(1.0 as Float));
+        let mut texmap: String = params.find_one_filename("mapname", String::from(""));
+        if texmap != "" {
+            if let Some(search_directory) = search_directory {
+                // texmap = AbsolutePath(ResolveFilename(texmap));
+                let mut path_buf: PathBuf = PathBuf::from("/");
+                path_buf.push(search_directory);
+                path_buf.push(texmap);
+                texmap = String::from(path_buf.to_str().unwrap());
+            }
+        }
+        let n_samples: i32 =
+            params.find_one_int("samples", params.find_one_int("nsamples", 1 as i32));
+        // TODO: if (PbrtOptions.quickRender) nSamples = std::max(1, nSamples / 4);
+        Arc::new(Light::InfiniteArea(Box::new(InfiniteAreaLight::new(
+            light_to_world,
+            &(l * sc),
+            n_samples,
+            texmap,
+        ))))
     }
-    fn default(n_samples: i32, l: &Spectrum) -> Self {
+    fn default(light_to_world: &Transform, n_samples: i32, l: &Spectrum) -> Self {
         let resolution: Point2i = Point2i { x: 1_i32, y: 1_i32 };
-        let texels: Vec<Spectrum> = vec![*l];
+        InfiniteAreaLight::from_texels(light_to_world, resolution, &[*l], n_samples)
+    }
+    /// Builds the light from an equirectangular image (*texels* of
+    /// the given *resolution*, already scaled by *L*), with *phi*
+    /// along the image width and *theta* along its height.
+    fn from_texels(
+        light_to_world: &Transform,
+        resolution: Point2i,
+        texels: &[Spectrum],
+        n_samples: i32,
+    ) -> Self {
         let do_trilinear: bool = false;
         let max_aniso: Float = 8.0 as Float;
         let wrap_mode: ImageWrap = ImageWrap::Repeat;
         let lmap = Arc::new(MipMap::new(
             resolution,
-            &texels[..],
+            texels,
             do_trilinear,
             max_aniso,
             wrap_mode,
@@ -273,6 +247,7 @@ impl InfiniteAreaLight {
         // TODO: ParallelFor(...) {...}
         for v in 0..height {
             let vp: Float = (v as Float + 0.5 as Float) / height as Float;
+            // rows close to the poles cover less solid angle
             let sin_theta: Float = (PI * (v as Float + 0.5 as Float) / height as Float).sin();
             for u in 0..width {
                 let up: Float = (u as Float + 0.5 as Float) / width as Float;
@@ -289,8 +264,8 @@ impl InfiniteAreaLight {
             flags: LightFlags::Infinite as u8,
             n_samples: std::cmp::max(1_i32, n_samples),
             medium_interface: MediumInterface::default(),
-            light_to_world: Transform::default(),
-            world_to_light: Transform::default(),
+            light_to_world: *light_to_world,
+            world_to_light: Transform::inverse(&*light_to_world),
         }
     }
     // Light
@@ -321,10 +296,13 @@ impl InfiniteAreaLight {
             z: cos_theta,
         };
         *wi = self.light_to_world.transform_vector(&vec);
-        // compute PDF for sampled infinite light direction
-        *pdf = map_pdf / (2.0 as Float * PI * PI * sin_theta);
+        // compute PDF for sampled infinite light direction, the
+        // Jacobian of the mapping from $(u,v)$ to directions is
+        // $2 \pi^2 \sin \theta$
         if sin_theta == 0.0 as Float {
             *pdf = 0.0 as Float;
+        } else {
+            *pdf = map_pdf / (2.0 as Float * PI * PI * sin_theta);
         }
         // return radiance value for infinite light direction
         let world_radius: Float = *self.world_radius.read().unwrap();
@@ -356,26 +334,24 @@ impl InfiniteAreaLight {
         )
     }
     /// Like directional lights, the total power from the infinite
-    /// area light is related to the surface area of the scene. Like
-    /// many other lights the power computed here is approximate.
+    /// area light is related to the surface area of the scene: the
+    /// radiance averaged over all directions (each texel weighted by
+    /// the solid angle it covers), arriving at the disk of the
+    /// scene's bounding sphere.
     pub fn power(&self) -> Spectrum {
-        let p: Point2f = Point2f { x: 0.5, y: 0.5 };
+        let sum: Spectrum = solid_angle_integral(&self.lmap);
         let world_radius: Float = *self.world_radius.read().unwrap();
         // TODO: SpectrumType::Illuminant
-        self.lmap.lookup_pnt_flt(p, 0.5 as Float) * Spectrum::new(PI * world_radius * world_radius)
+        sum * (PI * world_radius * world_radius / (4.0 as Float * PI))
     }
     /// Like **DistanceLights**, **InfiniteAreaLights** also need the
     /// scene bounds; here again, the **preprocess()** method finds
     /// the scene bounds after all of the scene geometry has been
     /// created.
     pub fn preprocess(&self, scene: &Scene) {
-        let mut world_center_ref = self.world_center.write().unwrap();
-        let mut world_radius_ref = self.world_radius.write().unwrap();
-        Bounds3f::bounding_sphere(
-            &scene.world_bound(),
-            &mut world_center_ref,
-            &mut world_radius_ref,
-        );
+        let (world_center, world_radius) = scene.world_bounding_sphere();
+        *self.world_center.write().unwrap() = world_center;
+        *self.world_radius.write().unwrap() = world_radius;
     }
     /// Because infinte area lights need to be able to contribute
     /// radiance to rays that don't hit any geometry in the scene,
@@ -392,6 +368,17 @@ impl InfiniteAreaLight {
         // TODO: SpectrumType::Illuminant
         self.lmap.lookup_pnt_flt(st, 0.0 as Float)
     }
+    /// Radiance arriving at *p* from direction *wi* without sampling,
+    /// which is the same as *le()* for a ray leaving in direction *wi*.
+    pub fn li_toward(&self, p: Point3f, wi: Vector3f) -> Spectrum {
+        let mut ray: Ray = Ray {
+            o: p,
+            d: wi,
+            t_max: Float::INFINITY,
+            ..Default::default()
+        };
+        self.le(&mut ray)
+    }
     pub fn pdf_li(&self, _iref: &dyn Interaction, w: Vector3f) -> Float {
         // TODO: ProfilePhase _(Prof::LightPdf);
         let wi: Vector3f = self.world_to_light.transform_vector(&w);
@@ -448,7 +435,7 @@ impl InfiniteAreaLight {
         *ray = Ray {
             o: p_disk + -d * world_radius,
             d,
-            t_max: std::f32::INFINITY,
+            t_max: Float::INFINITY,
             time,
             differential: None,
             medium: None,
@@ -473,7 +460,12 @@ impl InfiniteAreaLight {
         };
         let map_pdf: Float = self.distribution.pdf(uv);
         let world_radius: Float = *self.world_radius.read().unwrap();
-        *pdf_dir = map_pdf / (2.0 as Float * PI * PI * theta.sin());
+        let sin_theta: Float = theta.sin();
+        if sin_theta == 0.0 as Float {
+            *pdf_dir = 0.0 as Float;
+        } else {
+            *pdf_dir = map_pdf / (2.0 as Float * PI * PI * sin_theta);
+        }
         *pdf_pos = 1.0 as Float / (PI * world_radius * world_radius);
     }
     pub fn get_flags(&self) -> u8 {
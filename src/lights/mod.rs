@@ -30,7 +30,10 @@
 //!
 //! ## Goniophotometric Diagram Lights
 //!
-//! TODO
+//! A point light whose intensity varies with direction, given by a
+//! latitude-longitude image (*"mapname"*, bilinearly interpolated)
+//! or a measured intensity profile of a real-world fixture
+//! (*"profile"*).
 //!
 //! ## Infinite Area Lights
 //!
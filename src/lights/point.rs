@@ -2,14 +2,17 @@
 use std::f32::consts::PI;
 use std::sync::Arc;
 // pbrt
-use crate::core::geometry::pnt3_distance_squared;
-use crate::core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
+use crate::core::geometry::{pnt3_distance_squared, vec3_dot_vec3};
+use crate::core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f, XYEnum};
 use crate::core::interaction::{Interaction, InteractionCommon};
-use crate::core::light::{LightFlags, VisibilityTester};
+use crate::core::light::{
+    points_at_light, DeltaGeometry, LightFlags, VisibilityTester, LIGHT_VISIBILITY_ALL,
+};
 use crate::core::medium::{Medium, MediumInterface};
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::sampling::{uniform_sample_sphere, uniform_sphere_pdf};
 use crate::core::scene::Scene;
+use crate::core::spectrum::{RGBSpectrum, WavelengthSampling};
 use crate::core::transform::Transform;
 
 // see point.h
@@ -18,7 +21,20 @@ use crate::core::transform::Transform;
 pub struct PointLight {
     // private data (see point.h)
     pub p_light: Point3f,
+    /// Intensity; negative channels are allowed ("negative" lights
+    /// subtract light, the film clamps only on output)
     pub i: Spectrum,
+    /// Hero wavelength sampling (spectral mode) if set
+    pub wavelength_sampling: Option<WavelengthSampling>,
+    /// Radius of a visible bulb seen by camera (and specular) rays;
+    /// 0.0 keeps the light invisible. Illumination is still computed
+    /// from the (delta) point light.
+    pub radius: Float,
+    /// Light group the contributions of this light are accumulated
+    /// in (if the film has *"lightgroups"*)
+    pub group: usize,
+    /// **LightVisibility** bitmask (all ray types by default)
+    pub visibility: u8,
     // inherited from class Light (see light.h)
     pub flags: u8,
     pub n_samples: i32,
@@ -42,6 +58,10 @@ impl PointLight {
         PointLight {
             p_light: light_to_world.transform_point(&Point3f::default()),
             i: *i,
+            wavelength_sampling: None,
+            radius: 0.0 as Float,
+            group: 0_usize,
+            visibility: LIGHT_VISIBILITY_ALL,
             flags: LightFlags::DeltaPosition as u8,
             n_samples: 1_i32,
             medium_interface: MediumInterface { inside, outside },
@@ -51,15 +71,26 @@ impl PointLight {
     pub fn sample_li(
         &self,
         iref: &InteractionCommon,
-        _u: Point2f,
+        u: Point2f,
         wi: &mut Vector3f,
         pdf: &mut Float,
     ) -> (Spectrum, Option<VisibilityTester>) {
         // TODO: ProfilePhase _(Prof::LightSample);
         *wi = (self.p_light - iref.p).normalize();
         *pdf = 1.0 as Float;
+        let mut li: Spectrum = self.i / pnt3_distance_squared(&self.p_light, &iref.p);
+        if let Some(ref wavelength_sampling) = self.wavelength_sampling {
+            // spectral mode: the (otherwise unused) sample picks the
+            // hero wavelength, weighted by the wavelength pdf
+            let (lambda, lambda_pdf) = wavelength_sampling.sample(u[XYEnum::X]);
+            li = RGBSpectrum::from_wavelength_sample(
+                lambda,
+                li.sample_wavelength(lambda),
+                lambda_pdf,
+            );
+        }
         (
-            self.i / pnt3_distance_squared(&self.p_light, &iref.p),
+            li,
             Some(VisibilityTester {
                 p0: InteractionCommon {
                     p: iref.p,
@@ -67,7 +98,7 @@ impl PointLight {
                     p_error: iref.p_error,
                     wo: iref.wo,
                     n: iref.n,
-                    medium_interface: None,
+                    medium_interface: iref.medium_interface.clone(),
                 },
                 p1: InteractionCommon {
                     p: self.p_light,
@@ -84,10 +115,39 @@ impl PointLight {
         self.i * (4.0 as Float * PI)
     }
     pub fn preprocess(&self, _scene: &Scene) {}
-    /// Default implementation returns no emitted radiance for a ray
-    /// that escapes the scene bounds.
-    pub fn le(&self, _ray: &mut Ray) -> Spectrum {
-        Spectrum::new(0.0 as Float)
+    /// Returns the surface radiance of the visible bulb if the ray
+    /// hits it before *t_max*, no emitted radiance for an invisible
+    /// (delta) point light. The bulb radiance *I / (pi r^2)* is
+    /// consistent with the intensity: seen from afar, a sphere of
+    /// uniform radiance *L* has the intensity *L pi r^2*.
+    pub fn le(&self, ray: &mut Ray) -> Spectrum {
+        if self.radius <= 0.0 as Float {
+            return Spectrum::new(0.0 as Float);
+        }
+        // intersect ray with bulb sphere
+        let oc: Vector3f = ray.o - self.p_light;
+        let a: Float = vec3_dot_vec3(&ray.d, &ray.d);
+        let b: Float = vec3_dot_vec3(&oc, &ray.d);
+        let c: Float = vec3_dot_vec3(&oc, &oc) - self.radius * self.radius;
+        let discrim: Float = b * b - a * c;
+        if discrim < 0.0 as Float {
+            return Spectrum::new(0.0 as Float);
+        }
+        let root: Float = discrim.sqrt();
+        let t0: Float = (-b - root) / a;
+        let t1: Float = (-b + root) / a;
+        let t_hit: Float = if t0 > 0.0 as Float { t0 } else { t1 };
+        if t_hit <= 0.0 as Float || t_hit >= ray.t_max {
+            return Spectrum::new(0.0 as Float);
+        }
+        self.i / (PI * self.radius * self.radius)
+    }
+    /// Radiance arriving at *p* from direction *wi* without sampling.
+    pub fn li_toward(&self, p: Point3f, wi: Vector3f) -> Spectrum {
+        if !points_at_light(&p, &wi, &self.p_light) {
+            return Spectrum::default();
+        }
+        self.i / pnt3_distance_squared(&self.p_light, &p)
     }
     pub fn pdf_li(&self, _iref: &dyn Interaction, _wi: Vector3f) -> Float {
         0.0 as Float
@@ -116,6 +176,9 @@ impl PointLight {
         *pdf_dir = uniform_sphere_pdf();
         self.i
     }
+    pub fn delta_geometry(&self) -> Option<DeltaGeometry> {
+        Some(DeltaGeometry::Position(self.p_light))
+    }
     pub fn get_flags(&self) -> u8 {
         self.flags
     }
@@ -1,5 +1,4 @@
 // std
-use std::f32::consts::PI;
 use std::io::BufReader;
 use std::sync::Arc;
 // others
@@ -11,10 +10,11 @@ use openexr::{FrameBufferMut, InputFile, PixelType};
 use crate::core::geometry::{pnt2_inside_bnd2, pnt3_distance_squared};
 use crate::core::geometry::{Bounds2f, Normal3f, Point2f, Point2i, Point3f, Ray, Vector3f};
 use crate::core::interaction::{Interaction, InteractionCommon};
-use crate::core::light::{LightFlags, VisibilityTester};
+use crate::core::light::{points_at_light, DeltaGeometry, Light, LightFlags, VisibilityTester};
 use crate::core::medium::{Medium, MediumInterface};
 use crate::core::mipmap::{ImageWrap, MipMap};
-use crate::core::pbrt::{Float, Spectrum};
+use crate::core::paramset::ParamSet;
+use crate::core::pbrt::{radians, Float, Spectrum};
 use crate::core::reflection::cos_theta;
 use crate::core::sampling::{uniform_cone_pdf, uniform_sample_cone};
 use crate::core::scene::Scene;
@@ -49,6 +49,8 @@ pub struct ProjectionLight {
     pub p_light: Point3f,
     pub i: Spectrum,
     pub light_projection: Transform,
+    /// field of view (in degrees)
+    pub fov: Float,
     pub hither: Float,
     pub yon: Float,
     pub screen_bounds: Bounds2f,
@@ -130,7 +132,9 @@ impl ProjectionLight {
                     // create _MipMap_ from converted texels (see above)
                     let do_trilinear: bool = false;
                     let max_aniso: Float = 8.0 as Float;
-                    let wrap_mode: ImageWrap = ImageWrap::Repeat;
+                    // clamp, so points on the screen window boundary don't
+                    // pick up the opposite edge of the image
+                    let wrap_mode: ImageWrap = ImageWrap::Clamp;
                     let projection_map = Arc::new(MipMap::new(
                         resolution,
                         &texels[..],
@@ -138,59 +142,13 @@ impl ProjectionLight {
                         max_aniso,
                         wrap_mode,
                     ));
-                    let p_light: Point3f = light_to_world.transform_point(&Point3f::default());
-                    let aspect: Float = resolution.x as Float / resolution.y as Float;
-                    let screen_bounds: Bounds2f;
-                    if aspect > 1.0 as Float {
-                        screen_bounds = Bounds2f {
-                            p_min: Point2f {
-                                x: -aspect,
-                                y: -1.0 as Float,
-                            },
-                            p_max: Point2f {
-                                x: aspect,
-                                y: 1.0 as Float,
-                            },
-                        };
-                    } else {
-                        screen_bounds = Bounds2f {
-                            p_min: Point2f {
-                                x: -1.0 as Float,
-                                y: -1.0 as Float / aspect,
-                            },
-                            p_max: Point2f {
-                                x: 1.0 as Float,
-                                y: 1.0 as Float / aspect,
-                            },
-                        };
-                    }
-                    let hither: Float = 1e-3 as Float;
-                    let yon: Float = 1e30 as Float;
-                    let light_projection: Transform = Transform::perspective(fov, hither, yon);
-                    let screen_to_light: Transform = Transform::inverse(&light_projection);
-                    let p_corner: Point3f = Point3f {
-                        x: screen_bounds.p_max.x,
-                        y: screen_bounds.p_max.y,
-                        z: 0.0 as Float,
-                    };
-                    let w_corner: Vector3f =
-                        Vector3f::from(screen_to_light.transform_point(&p_corner)).normalize();
-                    let cos_total_width: Float = w_corner.z;
-                    return ProjectionLight {
-                        projection_map: Some(projection_map),
-                        p_light,
-                        i: *i,
-                        light_projection,
-                        hither,
-                        yon,
-                        screen_bounds,
-                        cos_total_width,
-                        flags: LightFlags::DeltaPosition as u8,
-                        n_samples: 1_i32,
-                        medium_interface: MediumInterface::default(),
-                        light_to_world: *light_to_world,
-                        world_to_light: Transform::inverse(&*light_to_world),
-                    };
+                    return ProjectionLight::with_map(
+                        light_to_world,
+                        medium_interface,
+                        i,
+                        Some(projection_map),
+                        fov,
+                    );
                 } else {
                     // try to open an HDR image instead (TODO: check extension upfront)
                     return ProjectionLight::new_hdr(
@@ -206,25 +164,11 @@ impl ProjectionLight {
                 return ProjectionLight::new_hdr(light_to_world, medium_interface, i, texname, fov);
             }
         }
-        ProjectionLight {
-            projection_map: None,
-            p_light: Point3f::default(),
-            i: Spectrum::default(),
-            light_projection: Transform::default(),
-            hither: 0.0 as Float,
-            yon: 0.0 as Float,
-            screen_bounds: Bounds2f::default(),
-            cos_total_width: 0.0 as Float,
-            flags: LightFlags::DeltaPosition as u8,
-            n_samples: 1_i32,
-            medium_interface: MediumInterface::default(),
-            light_to_world: Transform::default(),
-            world_to_light: Transform::default(),
-        }
+        ProjectionLight::with_map(light_to_world, medium_interface, i, None, fov)
     }
     pub fn new_hdr(
         light_to_world: &Transform,
-        _medium_interface: &MediumInterface,
+        medium_interface: &MediumInterface,
         i: &Spectrum,
         texname: String,
         fov: Float,
@@ -253,7 +197,9 @@ impl ProjectionLight {
                         // create _MipMap_ from converted texels (see above)
                         let do_trilinear: bool = false;
                         let max_aniso: Float = 8.0 as Float;
-                        let wrap_mode: ImageWrap = ImageWrap::Repeat;
+                        // clamp, so points on the screen window boundary don't
+                        // pick up the opposite edge of the image
+                        let wrap_mode: ImageWrap = ImageWrap::Clamp;
                         let projection_map = Arc::new(MipMap::new(
                             resolution,
                             &texels[..],
@@ -261,80 +207,109 @@ impl ProjectionLight {
                             max_aniso,
                             wrap_mode,
                         ));
-                        let p_light: Point3f = light_to_world.transform_point(&Point3f::default());
-                        let aspect: Float = resolution.x as Float / resolution.y as Float;
-                        let screen_bounds = if aspect > 1.0 as Float {
-                            Bounds2f {
-                                p_min: Point2f {
-                                    x: -aspect,
-                                    y: -1.0 as Float,
-                                },
-                                p_max: Point2f {
-                                    x: aspect,
-                                    y: 1.0 as Float,
-                                },
-                            }
-                        } else {
-                            Bounds2f {
-                                p_min: Point2f {
-                                    x: -1.0 as Float,
-                                    y: -1.0 as Float / aspect,
-                                },
-                                p_max: Point2f {
-                                    x: 1.0 as Float,
-                                    y: 1.0 as Float / aspect,
-                                },
-                            }
-                        };
-                        let hither: Float = 1e-3 as Float;
-                        let yon: Float = 1e30 as Float;
-                        let light_projection: Transform = Transform::perspective(fov, hither, yon);
-                        let screen_to_light: Transform = Transform::inverse(&light_projection);
-                        let p_corner: Point3f = Point3f {
-                            x: screen_bounds.p_max.x,
-                            y: screen_bounds.p_max.y,
-                            z: 0.0 as Float,
-                        };
-                        let w_corner: Vector3f =
-                            Vector3f::from(screen_to_light.transform_point(&p_corner)).normalize();
-                        let cos_total_width: Float = w_corner.z;
-                        return ProjectionLight {
-                            projection_map: Some(projection_map),
-                            p_light,
-                            i: *i,
-                            light_projection,
-                            hither,
-                            yon,
-                            screen_bounds,
-                            cos_total_width,
-                            flags: LightFlags::DeltaPosition as u8,
-                            n_samples: 1_i32,
-                            medium_interface: MediumInterface::default(),
-                            light_to_world: *light_to_world,
-                            world_to_light: Transform::inverse(&*light_to_world),
-                        };
+                        return ProjectionLight::with_map(
+                            light_to_world,
+                            medium_interface,
+                            i,
+                            Some(projection_map),
+                            fov,
+                        );
                     }
                 }
             } else {
                 println!("WARNING: ProjectionLight::new() ... no OpenEXR support !!!");
             }
         }
+        ProjectionLight::with_map(light_to_world, medium_interface, i, None, fov)
+    }
+    /// Sets up the projection for an (optional) *projection_map*; the
+    /// screen window matches the aspect ratio of the image and
+    /// *fov* is the field of view of its shorter axis.
+    pub fn with_map(
+        light_to_world: &Transform,
+        medium_interface: &MediumInterface,
+        i: &Spectrum,
+        projection_map: Option<Arc<MipMap<Spectrum>>>,
+        fov: Float,
+    ) -> Self {
+        let p_light: Point3f = light_to_world.transform_point(&Point3f::default());
+        let aspect: Float = if let Some(projection_map) = &projection_map {
+            projection_map.width() as Float / projection_map.height() as Float
+        } else {
+            1.0 as Float
+        };
+        let screen_bounds: Bounds2f = if aspect > 1.0 as Float {
+            Bounds2f {
+                p_min: Point2f {
+                    x: -aspect,
+                    y: -1.0 as Float,
+                },
+                p_max: Point2f {
+                    x: aspect,
+                    y: 1.0 as Float,
+                },
+            }
+        } else {
+            Bounds2f {
+                p_min: Point2f {
+                    x: -1.0 as Float,
+                    y: -1.0 as Float / aspect,
+                },
+                p_max: Point2f {
+                    x: 1.0 as Float,
+                    y: 1.0 as Float / aspect,
+                },
+            }
+        };
+        let hither: Float = 1e-3 as Float;
+        let yon: Float = 1e30 as Float;
+        let light_projection: Transform = Transform::perspective(fov, hither, yon);
+        let screen_to_light: Transform = Transform::inverse(&light_projection);
+        let p_corner: Point3f = Point3f {
+            x: screen_bounds.p_max.x,
+            y: screen_bounds.p_max.y,
+            z: 0.0 as Float,
+        };
+        let w_corner: Vector3f =
+            Vector3f::from(screen_to_light.transform_point(&p_corner)).normalize();
+        let cos_total_width: Float = w_corner.z;
         ProjectionLight {
-            projection_map: None,
-            p_light: Point3f::default(),
-            i: Spectrum::default(),
-            light_projection: Transform::default(),
-            hither: 0.0 as Float,
-            yon: 0.0 as Float,
-            screen_bounds: Bounds2f::default(),
-            cos_total_width: 0.0 as Float,
+            projection_map,
+            p_light,
+            i: *i,
+            light_projection,
+            fov,
+            hither,
+            yon,
+            screen_bounds,
+            cos_total_width,
             flags: LightFlags::DeltaPosition as u8,
             n_samples: 1_i32,
-            medium_interface: MediumInterface::default(),
-            light_to_world: Transform::default(),
-            world_to_light: Transform::default(),
+            medium_interface: medium_interface.clone(),
+            light_to_world: *light_to_world,
+            world_to_light: Transform::inverse(&*light_to_world),
         }
     }
+    /// Creates the light from the scene file parameters: intensity
+    /// *"I"* times *"scale"*, the field of view *"fov"* (45 degrees)
+    /// and the projected image *"mapname"*.
+    pub fn create(
+        params: &ParamSet,
+        light_to_world: &Transform,
+        medium_interface: &MediumInterface,
+    ) -> Arc<Light> {
+        let i: Spectrum = params.find_one_spectrum("I", Spectrum::new(1.0 as Float));
+        let sc: Spectrum = params.find_one_spectrum("scale", Spectrum::new(1.0 as Float));
+        let fov: Float = params.find_one_float("fov", 45.0 as Float);
+        let texname: String = params.find_one_filename("mapname", String::from(""));
+        Arc::new(Light::Projection(Box::new(ProjectionLight::new(
+            light_to_world,
+            medium_interface,
+            &(i * sc),
+            texname,
+            fov,
+        ))))
+    }
     pub fn projection(&self, w: &Vector3f) -> Spectrum {
         let wl: Vector3f = self.world_to_light.transform_vector(w);
         // discard directions behind projection light
@@ -376,7 +351,7 @@ impl ProjectionLight {
                     p_error: iref.p_error,
                     wo: iref.wo,
                     n: iref.n,
-                    medium_interface: None,
+                    medium_interface: iref.medium_interface.clone(),
                 },
                 p1: InteractionCommon {
                     p: self.p_light,
@@ -389,25 +364,46 @@ impl ProjectionLight {
             }),
         )
     }
+    /// Total flux: the image averaged over the screen window, each
+    /// texel weighted by the solid angle it projects to.
     pub fn power(&self) -> Spectrum {
-        if let Some(projection_map) = &self.projection_map {
-            projection_map.lookup_pnt_flt(
-                Point2f {
-                    x: 0.5 as Float,
-                    y: 0.5 as Float,
-                },
-                0.5 as Float,
-            ) * self.i
-                * 2.0 as Float
-                * PI
-                * (1.0 as Float - self.cos_total_width)
+        // resolution of the integration grid without an image
+        let (width, height) = if let Some(projection_map) = &self.projection_map {
+            (
+                projection_map.pyramid[0].u_size(),
+                projection_map.pyramid[0].v_size(),
+            )
         } else {
-            Spectrum::new(1.0 as Float)
-                * self.i
-                * 2.0 as Float
-                * PI
-                * (1.0 as Float - self.cos_total_width)
+            (64_usize, 64_usize)
+        };
+        let screen_to_light: Transform = Transform::inverse(&self.light_projection);
+        // area of the screen window on the plane z = 1 (light space)
+        let tan_half_fov: Float = radians(self.fov / 2.0 as Float).tan();
+        let area: Float = self.screen_bounds.area() * tan_half_fov * tan_half_fov;
+        let mut sum: Spectrum = Spectrum::default();
+        for y in 0..height {
+            for x in 0..width {
+                let ps: Point2f = self.screen_bounds.lerp(Point2f {
+                    x: (x as Float + 0.5 as Float) / width as Float,
+                    y: (y as Float + 0.5 as Float) / height as Float,
+                });
+                let w: Vector3f = Vector3f::from(screen_to_light.transform_point(&Point3f {
+                    x: ps.x,
+                    y: ps.y,
+                    z: 0.0 as Float,
+                }))
+                .normalize();
+                // solid angle per area on the plane z = 1
+                let dwda: Float = w.z * w.z * w.z;
+                let l: Spectrum = if let Some(projection_map) = &self.projection_map {
+                    *projection_map.texel(0, x as isize, y as isize)
+                } else {
+                    Spectrum::new(1.0 as Float)
+                };
+                sum += l * dwda;
+            }
         }
+        self.i * sum * (area / (width * height) as Float)
     }
     pub fn preprocess(&self, _scene: &Scene) {}
     /// Default implementation returns no emitted radiance for a ray
@@ -415,6 +411,13 @@ impl ProjectionLight {
     pub fn le(&self, _ray: &mut Ray) -> Spectrum {
         Spectrum::new(0.0 as Float)
     }
+    /// Radiance arriving at *p* from direction *wi* without sampling.
+    pub fn li_toward(&self, p: Point3f, wi: Vector3f) -> Spectrum {
+        if !points_at_light(&p, &wi, &self.p_light) {
+            return Spectrum::default();
+        }
+        self.i * self.projection(&-wi) / pnt3_distance_squared(&self.p_light, &p)
+    }
     pub fn pdf_li(&self, _iref: &dyn Interaction, _wi: Vector3f) -> Float {
         0.0 as Float
     }
@@ -446,6 +449,9 @@ impl ProjectionLight {
         *pdf_dir = uniform_cone_pdf(self.cos_total_width);
         self.i * self.projection(&ray.d)
     }
+    pub fn delta_geometry(&self) -> Option<DeltaGeometry> {
+        Some(DeltaGeometry::Position(self.p_light))
+    }
     pub fn get_flags(&self) -> u8 {
         self.flags
     }
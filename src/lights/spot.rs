@@ -2,16 +2,18 @@
 use std::f32::consts::PI;
 use std::sync::Arc;
 // pbrt
-use crate::core::geometry::pnt3_distance_squared;
+use crate::core::geometry::{pnt3_distance_squared, vec3_coordinate_system};
 use crate::core::geometry::{Normal3f, Point2f, Point3f, Ray, Vector3f};
-use crate::core::interaction::{Interaction, InteractionCommon};
-use crate::core::light::{LightFlags, VisibilityTester};
+use crate::core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
+use crate::core::light::{points_at_light, DeltaGeometry, Light, LightFlags, VisibilityTester};
 use crate::core::medium::{Medium, MediumInterface};
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::radians;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::reflection::cos_theta;
 use crate::core::sampling::{uniform_cone_pdf, uniform_sample_cone};
 use crate::core::scene::Scene;
+use crate::core::texture::Texture;
 use crate::core::transform::Transform;
 
 // see spot.h
@@ -23,6 +25,8 @@ pub struct SpotLight {
     pub i: Spectrum,
     pub cos_total_width: Float,
     pub cos_falloff_start: Float,
+    /// Optional gobo (cucoloris) pattern projected through the cone
+    pub gobo: Option<Arc<dyn Texture<Float> + Send + Sync>>,
     // inherited from class Light (see light.h)
     pub flags: u8,
     pub n_samples: i32,
@@ -38,6 +42,7 @@ impl SpotLight {
         i: &Spectrum,
         total_width: Float,
         falloff_start: Float,
+        gobo: Option<Arc<dyn Texture<Float> + Send + Sync>>,
     ) -> Self {
         let mut inside: Option<Arc<Medium>> = None;
         let mut outside: Option<Arc<Medium>> = None;
@@ -51,6 +56,7 @@ impl SpotLight {
             i: *i,
             cos_total_width: radians(total_width).cos(),
             cos_falloff_start: radians(falloff_start).cos(),
+            gobo,
             flags: LightFlags::DeltaPosition as u8,
             n_samples: 1_i32,
             medium_interface: MediumInterface { inside, outside },
@@ -58,19 +64,130 @@ impl SpotLight {
             world_to_light: Transform::inverse(light_to_world),
         }
     }
+    /// Reads *coneangle*, *conedeltaangle* and the *from*/*to* points
+    /// from *params*, the spot light points from *from* to *to* in the
+    /// coordinate system given by *light_to_world*.
+    pub fn create(
+        params: &ParamSet,
+        light_to_world: &Transform,
+        medium_interface: &MediumInterface,
+        gobo: Option<Arc<dyn Texture<Float> + Send + Sync>>,
+    ) -> Arc<Light> {
+        let i: Spectrum = params.find_one_spectrum("I", Spectrum::new(1.0 as Float));
+        let sc: Spectrum = params.find_one_spectrum("scale", Spectrum::new(1.0 as Float));
+        let coneangle: Float = params.find_one_float("coneangle", 30.0 as Float);
+        let conedelta: Float = params.find_one_float("conedeltaangle", 5.0 as Float);
+        // compute spotlight world to light transformation
+        let from: Point3f = params.find_one_point3f(
+            "from",
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        let to: Point3f = params.find_one_point3f(
+            "to",
+            Point3f {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+        );
+        let dir: Vector3f = (to - from).normalize();
+        let mut du: Vector3f = Vector3f::default();
+        let mut dv: Vector3f = Vector3f::default();
+        vec3_coordinate_system(&dir, &mut du, &mut dv);
+        let dir_to_z: Transform = Transform::new(
+            du.x, du.y, du.z, 0.0, dv.x, dv.y, dv.z, 0.0, dir.x, dir.y, dir.z, 0.0, 0.0, 0.0, 0.0,
+            1.0,
+        );
+        let light2world: Transform = *light_to_world
+            * Transform::translate(&Vector3f {
+                x: from.x,
+                y: from.y,
+                z: from.z,
+            })
+            * Transform::inverse(&dir_to_z);
+        Arc::new(Light::Spot(Box::new(SpotLight::new(
+            &light2world,
+            medium_interface,
+            &(i * sc),
+            coneangle,
+            coneangle - conedelta,
+            gobo,
+        ))))
+    }
     pub fn falloff(&self, w: &Vector3f) -> Float {
         let wl: Vector3f = self.world_to_light.transform_vector(w).normalize();
+        self.falloff_light(&wl)
+    }
+    /// Like *falloff()*, but for a (normalized) light space direction.
+    fn falloff_light(&self, wl: &Vector3f) -> Float {
         let cos_theta: Float = wl.z;
         if cos_theta < self.cos_total_width {
             return 0.0 as Float;
         }
+        let gobo: Float = self.gobo_value(wl);
         if cos_theta >= self.cos_falloff_start {
-            return 1.0 as Float;
+            return gobo;
         }
         // compute falloff inside spotlight cone
         let delta: Float =
             (cos_theta - self.cos_total_width) / (self.cos_falloff_start - self.cos_total_width);
-        (delta * delta) * (delta * delta)
+        (delta * delta) * (delta * delta) * gobo
+    }
+    /// Looks up the gobo pattern for a (normalized) light space
+    /// direction inside the cone. The direction is projected onto the
+    /// plane *z = 1* and the cone's footprint on that plane is mapped
+    /// to the texture's *[0,1]^2* (u,v) domain. Cones of 90 degrees
+    /// or wider have no planar footprint, they map the angle to the
+    /// cone's axis linearly to the distance from the center instead.
+    pub fn gobo_value(&self, wl: &Vector3f) -> Float {
+        if let Some(ref gobo) = self.gobo {
+            let sin_total_width: Float = (1.0 as Float
+                - self.cos_total_width * self.cos_total_width)
+                .max(0.0 as Float)
+                .sqrt();
+            let uv: Point2f = if self.cos_total_width > 0.0 as Float {
+                let tan_total_width: Float = sin_total_width / self.cos_total_width;
+                if wl.z <= 0.0 as Float || tan_total_width <= 0.0 as Float {
+                    return 0.0 as Float;
+                }
+                Point2f {
+                    x: 0.5 as Float * (wl.x / (wl.z * tan_total_width) + 1.0 as Float),
+                    y: 0.5 as Float * (wl.y / (wl.z * tan_total_width) + 1.0 as Float),
+                }
+            } else {
+                let sin_theta: Float = (wl.x * wl.x + wl.y * wl.y).sqrt();
+                if sin_theta == 0.0 as Float {
+                    Point2f { x: 0.5, y: 0.5 }
+                } else {
+                    let total_width: Float = self.cos_total_width.acos();
+                    let r: Float = wl.z.max(-1.0 as Float).min(1.0 as Float).acos()
+                        / (total_width * sin_theta);
+                    Point2f {
+                        x: 0.5 as Float * (wl.x * r + 1.0 as Float),
+                        y: 0.5 as Float * (wl.y * r + 1.0 as Float),
+                    }
+                }
+            };
+            let si: SurfaceInteraction = SurfaceInteraction {
+                common: InteractionCommon {
+                    p: Point3f {
+                        x: uv.x,
+                        y: uv.y,
+                        z: 0.0 as Float,
+                    },
+                    ..Default::default()
+                },
+                uv,
+                ..Default::default()
+            };
+            gobo.evaluate(&si).max(0.0 as Float)
+        } else {
+            1.0 as Float
+        }
     }
     // Light
     pub fn sample_li(
@@ -129,11 +246,29 @@ impl SpotLight {
             }),
         )
     }
+    /// Integrates the intensity over the cone: the full intensity
+    /// inside the falloff start, and the falloff *delta^4* in between
+    /// (which integrates to a fifth of the cosine range). With a gobo
+    /// the integral is estimated with stratified samples of the cone,
+    /// so the power is scaled by the gobo's average transmission.
     pub fn power(&self) -> Spectrum {
+        if self.gobo.is_some() {
+            let n: u32 = 64;
+            let mut sum: Float = 0.0 as Float;
+            for i in 0..n * n {
+                let u: Point2f = Point2f {
+                    x: ((i % n) as Float + 0.5 as Float) / n as Float,
+                    y: ((i / n) as Float + 0.5 as Float) / n as Float,
+                };
+                sum += self.falloff_light(&uniform_sample_cone(u, self.cos_total_width));
+            }
+            return self.i * (sum / (n * n) as Float) / uniform_cone_pdf(self.cos_total_width);
+        }
+        let cos_delta: Float = (self.cos_falloff_start - self.cos_total_width).max(0.0 as Float);
         self.i
             * 2.0 as Float
             * PI
-            * (1.0 as Float - 0.5 as Float * (self.cos_falloff_start + self.cos_total_width))
+            * ((1.0 as Float - self.cos_falloff_start) + cos_delta / 5.0 as Float)
     }
     pub fn preprocess(&self, _scene: &Scene) {}
     /// Default implementation returns no emitted radiance for a ray
@@ -141,6 +276,13 @@ impl SpotLight {
     pub fn le(&self, _ray: &mut Ray) -> Spectrum {
         Spectrum::new(0.0 as Float)
     }
+    /// Radiance arriving at *p* from direction *wi* without sampling.
+    pub fn li_toward(&self, p: Point3f, wi: Vector3f) -> Spectrum {
+        if !points_at_light(&p, &wi, &self.p_light) {
+            return Spectrum::default();
+        }
+        self.i * self.falloff(&-wi) / pnt3_distance_squared(&self.p_light, &p)
+    }
     pub fn pdf_li(&self, _iref: &dyn Interaction, _wi: Vector3f) -> Float {
         0.0 as Float
     }
@@ -173,6 +315,9 @@ impl SpotLight {
         *pdf_dir = uniform_cone_pdf(self.cos_total_width);
         self.i * self.falloff(&ray.d)
     }
+    pub fn delta_geometry(&self) -> Option<DeltaGeometry> {
+        Some(DeltaGeometry::Position(self.p_light))
+    }
     pub fn get_flags(&self) -> u8 {
         self.flags
     }
@@ -19,11 +19,18 @@ use crate::core::reflection::{
 };
 use crate::core::texture::Texture;
 
+/// Disney's principled BSDF (Burley 2012 and 2015): a diffuse base
+/// with retro-reflection and sheen, a Trowbridge-Reitz specular lobe,
+/// a clearcoat and (rough or diffuse) transmission. *metallic* blends
+/// the dielectric Fresnel term of the specular lobe into a Schlick
+/// Fresnel term tinted by *color*.
 pub struct DisneyMaterial {
     color: Arc<dyn Texture<Spectrum> + Send + Sync>,
     // base_color: Arc<TextureFloat>,
     metallic: Arc<dyn Texture<Float> + Send + Sync>,
     eta: Arc<dyn Texture<Float> + Send + Sync>,
+    // overrides eta, see eta_from_specular()
+    specular: Option<Arc<dyn Texture<Float> + Send + Sync>>,
     roughness: Arc<dyn Texture<Float> + Send + Sync>,
     specular_tint: Arc<dyn Texture<Float> + Send + Sync>,
     anisotropic: Arc<dyn Texture<Float> + Send + Sync>,
@@ -44,6 +51,7 @@ impl DisneyMaterial {
         let color = mp.get_spectrum_texture("color", Spectrum::from(0.5));
         let metallic = mp.get_float_texture("metallic", 0.0);
         let eta = mp.get_float_texture("eta", 1.5);
+        let specular = mp.get_float_texture_or_null("specular");
         let roughness = mp.get_float_texture("roughness", 0.5);
         let specular_tint = mp.get_float_texture("speculartint", 0.0);
         let anisotropic = mp.get_float_texture("anisotropic", 0.0);
@@ -62,6 +70,7 @@ impl DisneyMaterial {
             color,
             metallic,
             eta,
+            specular,
             roughness,
             specular_tint,
             anisotropic,
@@ -98,7 +107,11 @@ impl DisneyMaterial {
         // diffuse
         let c = self.color.evaluate(si).clamp(0.0, f32::INFINITY);
         let metallic_weight = self.metallic.evaluate(si);
-        let e = self.eta.evaluate(si);
+        let e = if let Some(ref specular) = self.specular {
+            eta_from_specular(specular.evaluate(si))
+        } else {
+            self.eta.evaluate(si)
+        };
         let strans = self.spec_trans.evaluate(si);
         let diffuse_weight = (1.0 - metallic_weight) * (1.0 - strans);
         let dt = self.diff_trans.evaluate(si) / 2.0; // 0: all diffuse is reflected -> 1, transmitted
@@ -225,6 +238,8 @@ impl DisneyMaterial {
                 MicrofacetDistribution::DisneyMicrofacet(DisneyMicrofacetDistribution::new(ax, ay));
 
             // Specular is Trowbridge-Reitz with a modified Fresnel function
+            // (which already contains the tint, so the lobe itself is
+            // white)
             let cspec0 = lerp(
                 metallic_weight,
                 schlick_r0_from_eta(e) * lerp(spec_tint, Spectrum::new(1.0), c_tint),
@@ -232,12 +247,20 @@ impl DisneyMaterial {
             );
             let fresnel = Fresnel::Disney(DisneyFresnel::new(cspec0, metallic_weight, e));
             if use_scale {
-                bsdf.bxdfs[bxdf_idx] =
-                    Bxdf::MicrofacetRefl(MicrofacetReflection::new(c, distrib, fresnel, Some(sc)));
+                bsdf.bxdfs[bxdf_idx] = Bxdf::MicrofacetRefl(MicrofacetReflection::new(
+                    Spectrum::new(1.0),
+                    distrib,
+                    fresnel,
+                    Some(sc),
+                ));
                 bxdf_idx += 1;
             } else {
-                bsdf.bxdfs[bxdf_idx] =
-                    Bxdf::MicrofacetRefl(MicrofacetReflection::new(c, distrib, fresnel, None));
+                bsdf.bxdfs[bxdf_idx] = Bxdf::MicrofacetRefl(MicrofacetReflection::new(
+                    Spectrum::new(1.0),
+                    distrib,
+                    fresnel,
+                    None,
+                ));
                 bxdf_idx += 1;
             }
             // Clearcoat
@@ -538,11 +561,7 @@ impl DisneyClearCoat {
 
         *pdf = self.pdf(wo, &wi);
 
-        if let Some(sc) = self.sc_opt {
-            sc * self.f(wo, wi)
-        } else {
-            self.f(wo, wi)
-        }
+        self.f(wo, wi)
     }
     pub fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
         if !vec3_same_hemisphere_vec3(wo, wi) {
@@ -593,6 +612,9 @@ impl DisneyMicrofacetDistribution {
     }
     pub fn pdf(&self, wo: &Vector3f, wh: &Vector3f) -> Float {
         if self.get_sample_visible_area() {
+            if abs_cos_theta(wo) == 0.0 as Float {
+                return 0.0 as Float;
+            }
             self.d(wh) * self.g1(wo) * vec3_abs_dot_vec3(wo, wh) / abs_cos_theta(wo)
         } else {
             self.d(wh) * abs_cos_theta(wh)
@@ -624,11 +646,18 @@ fn schlick_r0_from_eta(eta: Float) -> Float {
     sqr(eta - 1.0) / sqr(eta + 1.0)
 }
 
+// The inverse of the above for the "specular" parameter, which maps
+// [0, 1] to R(0) in [0, 0.08] (0.5 is eta = 1.5).
+fn eta_from_specular(specular: Float) -> Float {
+    let r0: Float = clamp_t(0.08 * specular, 0.0, 0.99);
+    (1.0 + r0.sqrt()) / (1.0 - r0.sqrt())
+}
+
 fn gtr1(cos_theta: Float, alpha: Float) -> Float {
     let alpha2 = alpha * alpha;
 
     (alpha2 - 1.0)
-        / (f32::consts::PI * Float::log10(alpha2) * (1.0 + (alpha2 - 1.0) * cos_theta * cos_theta))
+        / (f32::consts::PI * Float::ln(alpha2) * (1.0 + (alpha2 - 1.0) * cos_theta * cos_theta))
 }
 
 fn smith_g_ggx(cos_theta: Float, alpha: Float) -> Float {
@@ -24,6 +24,11 @@ pub struct GlassMaterial {
     pub index: Arc<dyn Texture<Float> + Sync + Send>,
     pub bump_map: Option<Arc<dyn Texture<Float> + Send + Sync>>,
     pub remap_roughness: bool,
+    /// Cauchy coefficient *B* (in square micrometers), *index* is
+    /// used for green light (default: 0.0, no dispersion); only
+    /// smooth glass sampled with a single **FresnelSpecular** BxDF
+    /// disperses light
+    pub dispersion: Float,
 }
 
 impl GlassMaterial {
@@ -44,40 +49,35 @@ impl GlassMaterial {
             index,
             bump_map,
             remap_roughness,
+            dispersion: 0.0 as Float,
         }
     }
     pub fn create(mp: &mut TextureParams) -> Arc<Material> {
         let kr = mp.get_spectrum_texture("Kr", Spectrum::new(1.0 as Float));
         let kt = mp.get_spectrum_texture("Kt", Spectrum::new(1.0 as Float));
-        let roughu = mp.get_float_texture("uroughness", 0.0 as Float);
-        let roughv = mp.get_float_texture("vroughness", 0.0 as Float);
+        let roughness: Float = mp.find_float("roughness", 0.0 as Float);
+        let roughu = mp.get_float_texture("uroughness", roughness);
+        let roughv = mp.get_float_texture("vroughness", roughness);
         let bump_map = mp.get_float_texture_or_null("bumpmap");
         let remap_roughness: bool = mp.find_bool("remaproughness", true);
-        let eta_option: Option<Arc<dyn Texture<Float> + Send + Sync>> =
-            mp.get_float_texture_or_null("eta");
-        if let Some(ref eta) = eta_option {
-            Arc::new(Material::Glass(Box::new(GlassMaterial::new(
-                kr,
-                kt,
-                roughu,
-                roughv,
-                eta.clone(),
-                bump_map,
-                remap_roughness,
-            ))))
-        } else {
-            let eta: Arc<dyn Texture<Float> + Send + Sync> =
-                mp.get_float_texture("index", 1.5 as Float);
-            Arc::new(Material::Glass(Box::new(GlassMaterial::new(
-                kr,
-                kt,
-                roughu,
-                roughv,
-                eta,
-                bump_map,
-                remap_roughness,
-            ))))
-        }
+        let eta: Arc<dyn Texture<Float> + Send + Sync> =
+            if let Some(eta) = mp.get_float_texture_or_null("eta") {
+                eta
+            } else {
+                mp.get_float_texture("index", 1.5 as Float)
+            };
+        let mut glass: GlassMaterial =
+            GlassMaterial::new(kr, kt, roughu, roughv, eta, bump_map, remap_roughness);
+        glass.dispersion = mp.find_float("dispersion", 0.0 as Float);
+        Arc::new(Material::Glass(Box::new(glass)))
+    }
+    /// Index of refraction for the red, green, and blue channels
+    /// (wavelengths of 650, 550, and 450 nanometers).
+    pub fn eta_rgb(&self, eta: Float) -> [Float; 3] {
+        let offset = |lambda: Float| -> Float {
+            self.dispersion * (1.0 as Float / (lambda * lambda) - 1.0 as Float / (0.55 * 0.55))
+        };
+        [eta + offset(0.65), eta, eta + offset(0.45)]
     }
     // Material
     pub fn compute_scattering_functions(
@@ -113,7 +113,16 @@ impl GlassMaterial {
         si.bsdf = Some(Bsdf::new(si, eta));
         if let Some(bsdf) = &mut si.bsdf {
             let mut bxdf_idx: usize = 0;
-            if is_specular && allow_multiple_lobes {
+            if is_specular && allow_multiple_lobes && self.dispersion != 0.0 as Float {
+                bsdf.bxdfs[bxdf_idx] = Bxdf::FresnelSpec(FresnelSpecular::new_dispersive(
+                    r,
+                    t,
+                    1.0 as Float,
+                    self.eta_rgb(eta),
+                    mode,
+                    scale_opt,
+                ));
+            } else if is_specular && allow_multiple_lobes {
                 if use_scale {
                     bsdf.bxdfs[bxdf_idx] = Bxdf::FresnelSpec(FresnelSpecular::new(
                         r,
@@ -543,11 +543,7 @@ impl HairBSDF {
                 self.v[P_MAX as usize],
             )
             * (1.0 as Float / (2.0 as Float * PI));
-        if let Some(sc) = self.sc_opt {
-            sc * self.f(wo, &*wi)
-        } else {
-            self.f(wo, &*wi)
-        }
+        self.f(wo, &*wi)
     }
     pub fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> Float {
         // compute hair coordinate system terms related to _wo_
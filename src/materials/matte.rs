@@ -13,8 +13,11 @@ use crate::core::texture::Texture;
 
 /// Describes a purely diffuse surface.
 pub struct MatteMaterial {
-    pub kd: Arc<dyn Texture<Spectrum> + Sync + Send>, // default: 0.5
-    pub sigma: Arc<dyn Texture<Float> + Sync + Send>, // default: 0.0
+    /// diffuse reflectance (default: 0.5)
+    pub kd: Arc<dyn Texture<Spectrum> + Sync + Send>,
+    /// roughness in degrees (default: 0.0), zero gives a Lambertian
+    /// surface, otherwise the Oren-Nayar model is used
+    pub sigma: Arc<dyn Texture<Float> + Sync + Send>,
     pub bump_map: Option<Arc<dyn Texture<Float> + Send + Sync>>,
 }
 
@@ -40,6 +43,9 @@ impl MatteMaterial {
         ))))
     }
     // Material
+    /// Both textures are evaluated at the (possibly bump mapped)
+    /// surface interaction, so reflectance and roughness can vary
+    /// over the surface.
     pub fn compute_scattering_functions(
         &self,
         si: &mut SurfaceInteraction,
@@ -62,11 +68,7 @@ impl MatteMaterial {
             .kd
             .evaluate(si)
             .clamp(0.0 as Float, std::f32::INFINITY as Float);
-        let sig: Float = clamp_t(
-            self.sigma.evaluate(si) as Float,
-            0.0 as Float,
-            90.0 as Float,
-        );
+        let sig: Float = clamp_t(self.sigma.evaluate(si), 0.0 as Float, 90.0 as Float);
         si.bsdf = Some(Bsdf::new(si, 1.0));
         if let Some(bsdf) = &mut si.bsdf {
             let bxdf_idx: usize = 0;
@@ -84,9 +84,25 @@ pub const COPPER_K: [Float; COPPER_SAMPLES as usize] = [
     5.485_625, 5.717,
 ];
 
+// approximate measured data between 400 and 700 nm (rounded values
+// after Johnson and Christy for gold and silver, and after Rakic for
+// aluminium)
+pub const METAL_SAMPLES: u8 = 7_u8;
+pub const METAL_WAVELENGTHS: [Float; METAL_SAMPLES as usize] =
+    [400.0, 450.0, 500.0, 550.0, 600.0, 650.0, 700.0];
+pub const GOLD_N: [Float; METAL_SAMPLES as usize] = [1.64, 1.47, 0.97, 0.40, 0.22, 0.16, 0.13];
+pub const GOLD_K: [Float; METAL_SAMPLES as usize] = [1.95, 1.90, 1.87, 2.40, 2.98, 3.52, 3.97];
+pub const SILVER_N: [Float; METAL_SAMPLES as usize] = [0.05, 0.04, 0.05, 0.06, 0.06, 0.05, 0.04];
+pub const SILVER_K: [Float; METAL_SAMPLES as usize] = [2.07, 2.66, 3.09, 3.34, 4.01, 4.48, 4.83];
+pub const ALUMINIUM_N: [Float; METAL_SAMPLES as usize] = [0.49, 0.62, 0.77, 0.96, 1.20, 1.49, 1.83];
+pub const ALUMINIUM_K: [Float; METAL_SAMPLES as usize] = [4.86, 5.47, 6.08, 6.69, 7.26, 7.82, 8.31];
+
+/// Conductor with a microfacet reflection lobe (Trowbridge-Reitz),
+/// weighted by the Fresnel reflectance for the complex index of
+/// refraction *eta* + i *k*.
 pub struct MetalMaterial {
-    pub eta: Arc<dyn Texture<Spectrum> + Sync + Send>, // default: copper
-    pub k: Arc<dyn Texture<Spectrum> + Sync + Send>,   // default: copper
+    pub eta: Arc<dyn Texture<Spectrum> + Sync + Send>, // default: "metal" (copper)
+    pub k: Arc<dyn Texture<Spectrum> + Sync + Send>,   // default: "metal" (copper)
     pub roughness: Arc<dyn Texture<Float> + Sync + Send>, // default: 0.01
     pub u_roughness: Option<Arc<dyn Texture<Float> + Sync + Send>>,
     pub v_roughness: Option<Arc<dyn Texture<Float> + Sync + Send>>,
@@ -114,14 +130,37 @@ impl MetalMaterial {
             remap_roughness,
         }
     }
+    /// Index of refraction (*eta*) and absorption coefficient (*k*)
+    /// of a metal selected by *name* ("aluminium", "copper", "gold",
+    /// or "silver"), converted to RGB.
+    pub fn named_spectra(name: &str) -> Option<(Spectrum, Spectrum)> {
+        let (lambda, n, k, samples): (&[Float], &[Float], &[Float], u8) = match name {
+            "aluminium" | "aluminum" => (
+                &METAL_WAVELENGTHS,
+                &ALUMINIUM_N,
+                &ALUMINIUM_K,
+                METAL_SAMPLES,
+            ),
+            "copper" => (&COPPER_WAVELENGTHS, &COPPER_N, &COPPER_K, COPPER_SAMPLES),
+            "gold" => (&METAL_WAVELENGTHS, &GOLD_N, &GOLD_K, METAL_SAMPLES),
+            "silver" => (&METAL_WAVELENGTHS, &SILVER_N, &SILVER_K, METAL_SAMPLES),
+            _ => return None,
+        };
+        Some((
+            Spectrum::from_sampled(lambda, n, samples as i32),
+            Spectrum::from_sampled(lambda, k, samples as i32),
+        ))
+    }
     pub fn create(mp: &mut TextureParams) -> Arc<Material> {
-        let copper_n: Spectrum =
-            Spectrum::from_sampled(&COPPER_WAVELENGTHS, &COPPER_N, COPPER_SAMPLES as i32);
-        let eta: Arc<dyn Texture<Spectrum> + Send + Sync> =
-            mp.get_spectrum_texture("eta", copper_n);
-        let copper_k: Spectrum =
-            Spectrum::from_sampled(&COPPER_WAVELENGTHS, &COPPER_K, COPPER_SAMPLES as i32);
-        let k: Arc<dyn Texture<Spectrum> + Send + Sync> = mp.get_spectrum_texture("k", copper_k);
+        let name: String = mp.find_string("metal", String::from("copper"));
+        let (metal_n, metal_k) = if let Some(spectra) = MetalMaterial::named_spectra(&name) {
+            spectra
+        } else {
+            println!("WARNING: Unknown metal \"{}\". Using copper.", name);
+            MetalMaterial::named_spectra("copper").unwrap()
+        };
+        let eta: Arc<dyn Texture<Spectrum> + Send + Sync> = mp.get_spectrum_texture("eta", metal_n);
+        let k: Arc<dyn Texture<Spectrum> + Send + Sync> = mp.get_spectrum_texture("k", metal_k);
         let roughness: Arc<dyn Texture<Float> + Send + Sync> =
             mp.get_float_texture("roughness", 0.01 as Float);
         let u_roughness: Option<Arc<dyn Texture<Float> + Send + Sync>> =
@@ -10,7 +10,10 @@ use crate::core::texture::Texture;
 
 // see mirror.h
 
-/// A simple mirror, modeled with perfect specular reflection.
+/// A simple mirror, modeled with perfect specular reflection: a
+/// single **SpecularReflection** without Fresnel term reflects *kr*
+/// at all angles. Integrators treat it as a specular bounce and don't
+/// sample lights directly.
 pub struct MirrorMaterial {
     pub kr: Arc<dyn Texture<Spectrum> + Sync + Send>, // default: 0.9
     pub bump_map: Option<Arc<dyn Texture<Float> + Send + Sync>>,
@@ -54,6 +57,10 @@ impl MirrorMaterial {
             .evaluate(si)
             .clamp(0.0 as Float, std::f32::INFINITY as Float);
         si.bsdf = Some(Bsdf::new(si, 1.0));
+        // regions which don't reflect at all get an empty BSDF
+        if r.is_black() {
+            return;
+        }
         if let Some(bsdf) = &mut si.bsdf {
             let bxdf_idx: usize = 0;
             let fresnel = Fresnel::NoOp(FresnelNoOp {});
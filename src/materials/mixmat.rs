@@ -1,24 +1,13 @@
 //std
+use std::collections::HashMap;
 use std::sync::Arc;
 // pbrt
-use crate::core::bssrdf::SeparableBssrdfAdapter;
 use crate::core::interaction::SurfaceInteraction;
 use crate::core::material::{Material, TransportMode};
-use crate::core::microfacet::{
-    BeckmannDistribution, MicrofacetDistribution, TrowbridgeReitzDistribution,
-};
+use crate::core::paramset::TextureParams;
 use crate::core::pbrt::{Float, Spectrum};
-use crate::core::reflection::{
-    Bxdf, FourierBSDF, Fresnel, FresnelBlend, FresnelConductor, FresnelDielectric, FresnelNoOp,
-    FresnelSpecular, LambertianReflection, LambertianTransmission, MicrofacetReflection,
-    MicrofacetTransmission, OrenNayar, SpecularReflection, SpecularTransmission,
-};
+use crate::core::reflection::Bxdf;
 use crate::core::texture::Texture;
-use crate::materials::disney::{
-    DisneyClearCoat, DisneyDiffuse, DisneyFakeSS, DisneyMicrofacetDistribution, DisneyRetro,
-    DisneySheen,
-};
-use crate::materials::hair::HairBSDF;
 
 // see mixmat.h
 
@@ -39,6 +28,32 @@ impl MixMaterial {
     ) -> Self {
         MixMaterial { m1, m2, scale }
     }
+    pub fn create(
+        mp: &mut TextureParams,
+        named_materials: &HashMap<String, Option<Arc<Material>>>,
+    ) -> Option<Arc<Material>> {
+        let mut materials: Vec<Arc<Material>> = Vec::with_capacity(2);
+        for param in &["namedmaterial1", "namedmaterial2"] {
+            let name: String = mp.find_string(param, String::from(""));
+            match named_materials.get(&name) {
+                Some(Some(material)) => materials.push(material.clone()),
+                _ => {
+                    println!(
+                        "ERROR: Named material \"{}\" for \"{}\" not defined.",
+                        name, param
+                    );
+                    return None;
+                }
+            }
+        }
+        let scale: Arc<dyn Texture<Spectrum> + Send + Sync> =
+            mp.get_spectrum_texture("amount", Spectrum::new(0.5));
+        Some(Arc::new(Material::Mix(Box::new(MixMaterial::new(
+            materials[0].clone(),
+            materials[1].clone(),
+            scale,
+        )))))
+    }
     // Material
     pub fn compute_scattering_functions(
         &self,
@@ -47,14 +62,19 @@ impl MixMaterial {
         mode: TransportMode,
         allow_multiple_lobes: bool,
         _material: Option<Arc<Material>>,
-        _scale: Option<Spectrum>,
+        scale_opt: Option<Spectrum>,
     ) {
-        let s1: Spectrum = self
+        let mut s1: Spectrum = self
             .scale
             .evaluate(si)
             .clamp(0.0 as Float, std::f32::INFINITY as Float);
-        let s2: Spectrum =
+        let mut s2: Spectrum =
             (Spectrum::new(1.0 as Float) - s1).clamp(0.0 as Float, std::f32::INFINITY as Float);
+        // nested mix materials scale the BxDFs of their children
+        if let Some(scale) = scale_opt {
+            s1 = scale * s1;
+            s2 = scale * s2;
+        }
         let mut si2: SurfaceInteraction = SurfaceInteraction::new(
             &si.common.p,
             &si.common.p_error,
@@ -67,6 +87,7 @@ impl MixMaterial {
             si.common.time,
             si.shape,
         );
+        si2.shading = si.shading;
         self.m1.compute_scattering_functions(
             si,
             mode,
@@ -81,8 +102,8 @@ impl MixMaterial {
             None,
             Some(s2),
         );
-        let mut last_idx: usize = 0;
         // find next empty slot
+        let mut last_idx: usize = 8;
         if let Some(bsdf) = &si.bsdf {
             for bxdf_idx in 0..8 {
                 if let Bxdf::Empty(_bxdf) = &bsdf.bxdfs[bxdf_idx] {
@@ -92,223 +113,18 @@ impl MixMaterial {
             }
         }
         // get Bxdfs from si2 before it gets out of scope
-        if si2.bsdf.is_some() {
-            let bxdfs: [Bxdf; 8] = si2.bsdf.unwrap().bxdfs;
+        if let Some(bsdf2) = si2.bsdf {
             if let Some(bsdf) = &mut si.bsdf {
-                for (bxdf_idx, item) in bxdfs.iter().enumerate() {
-                    bsdf.bxdfs[bxdf_idx + last_idx] = match item {
-                        Bxdf::Empty(_bxdf) => break,
-                        Bxdf::SpecRefl(bxdf) => {
-                            let fresnel = match &bxdf.fresnel {
-                                Fresnel::Conductor(fresnel) => {
-                                    Fresnel::Conductor(FresnelConductor {
-                                        eta_i: fresnel.eta_i,
-                                        eta_t: fresnel.eta_t,
-                                        k: fresnel.k,
-                                    })
-                                }
-                                Fresnel::Dielectric(fresnel) => {
-                                    Fresnel::Dielectric(FresnelDielectric {
-                                        eta_i: fresnel.eta_i,
-                                        eta_t: fresnel.eta_t,
-                                    })
-                                }
-                                _ => Fresnel::NoOp(FresnelNoOp {}),
-                            };
-                            Bxdf::SpecRefl(SpecularReflection::new(bxdf.r, fresnel, bxdf.sc_opt))
-                        }
-                        Bxdf::SpecTrans(bxdf) => Bxdf::SpecTrans(SpecularTransmission::new(
-                            bxdf.t,
-                            bxdf.eta_a,
-                            bxdf.eta_b,
-                            bxdf.mode,
-                            bxdf.sc_opt,
-                        )),
-                        Bxdf::FresnelSpec(bxdf) => Bxdf::FresnelSpec(FresnelSpecular::new(
-                            bxdf.r,
-                            bxdf.t,
-                            bxdf.eta_a,
-                            bxdf.eta_b,
-                            bxdf.mode,
-                            bxdf.sc_opt,
-                        )),
-                        Bxdf::LambertianRefl(bxdf) => {
-                            Bxdf::LambertianRefl(LambertianReflection::new(bxdf.r, bxdf.sc_opt))
-                        }
-                        Bxdf::LambertianTrans(bxdf) => {
-                            Bxdf::LambertianTrans(LambertianTransmission::new(bxdf.t, bxdf.sc_opt))
-                        }
-                        Bxdf::OrenNayarRefl(bxdf) => Bxdf::OrenNayarRefl(OrenNayar {
-                            r: bxdf.r,
-                            a: bxdf.a,
-                            b: bxdf.b,
-                            sc_opt: bxdf.sc_opt,
-                        }),
-                        Bxdf::MicrofacetRefl(bxdf) => {
-                            let distribution = match &bxdf.distribution {
-                                MicrofacetDistribution::Beckmann(distribution) => {
-                                    MicrofacetDistribution::Beckmann(BeckmannDistribution {
-                                        alpha_x: distribution.alpha_x,
-                                        alpha_y: distribution.alpha_y,
-                                        sample_visible_area: distribution.sample_visible_area,
-                                    })
-                                }
-                                MicrofacetDistribution::TrowbridgeReitz(distribution) => {
-                                    MicrofacetDistribution::TrowbridgeReitz(
-                                        TrowbridgeReitzDistribution {
-                                            alpha_x: distribution.alpha_x,
-                                            alpha_y: distribution.alpha_y,
-                                            sample_visible_area: distribution.sample_visible_area,
-                                        },
-                                    )
-                                }
-                                MicrofacetDistribution::DisneyMicrofacet(distribution) => {
-                                    MicrofacetDistribution::DisneyMicrofacet(
-                                        DisneyMicrofacetDistribution::new(
-                                            distribution.inner.alpha_x,
-                                            distribution.inner.alpha_y,
-                                        ),
-                                    )
-                                }
-                            };
-                            let fresnel = match &bxdf.fresnel {
-                                Fresnel::Conductor(fresnel) => {
-                                    Fresnel::Conductor(FresnelConductor {
-                                        eta_i: fresnel.eta_i,
-                                        eta_t: fresnel.eta_t,
-                                        k: fresnel.k,
-                                    })
-                                }
-                                Fresnel::Dielectric(fresnel) => {
-                                    Fresnel::Dielectric(FresnelDielectric {
-                                        eta_i: fresnel.eta_i,
-                                        eta_t: fresnel.eta_t,
-                                    })
-                                }
-                                _ => Fresnel::NoOp(FresnelNoOp {}),
-                            };
-                            Bxdf::MicrofacetRefl(MicrofacetReflection::new(
-                                bxdf.r,
-                                distribution,
-                                fresnel,
-                                bxdf.sc_opt,
-                            ))
-                        }
-                        Bxdf::MicrofacetTrans(bxdf) => {
-                            let distribution = match &bxdf.distribution {
-                                MicrofacetDistribution::Beckmann(distribution) => {
-                                    MicrofacetDistribution::Beckmann(BeckmannDistribution {
-                                        alpha_x: distribution.alpha_x,
-                                        alpha_y: distribution.alpha_y,
-                                        sample_visible_area: distribution.sample_visible_area,
-                                    })
-                                }
-                                MicrofacetDistribution::TrowbridgeReitz(distribution) => {
-                                    MicrofacetDistribution::TrowbridgeReitz(
-                                        TrowbridgeReitzDistribution {
-                                            alpha_x: distribution.alpha_x,
-                                            alpha_y: distribution.alpha_y,
-                                            sample_visible_area: distribution.sample_visible_area,
-                                        },
-                                    )
-                                }
-                                MicrofacetDistribution::DisneyMicrofacet(distribution) => {
-                                    MicrofacetDistribution::DisneyMicrofacet(
-                                        DisneyMicrofacetDistribution::new(
-                                            distribution.inner.alpha_x,
-                                            distribution.inner.alpha_y,
-                                        ),
-                                    )
-                                }
-                            };
-                            Bxdf::MicrofacetTrans(MicrofacetTransmission::new(
-                                bxdf.t,
-                                distribution,
-                                bxdf.eta_a,
-                                bxdf.eta_b,
-                                bxdf.mode,
-                                bxdf.sc_opt,
-                            ))
-                        }
-                        Bxdf::FresnelBlnd(bxdf) => {
-                            let mut distrib: Option<MicrofacetDistribution> = None;
-                            if let Some(distribution) = &bxdf.distribution {
-                                distrib = match &distribution {
-                                    MicrofacetDistribution::Beckmann(distribution) => Some(
-                                        MicrofacetDistribution::Beckmann(BeckmannDistribution {
-                                            alpha_x: distribution.alpha_x,
-                                            alpha_y: distribution.alpha_y,
-                                            sample_visible_area: distribution.sample_visible_area,
-                                        }),
-                                    ),
-                                    MicrofacetDistribution::TrowbridgeReitz(distribution) => {
-                                        Some(MicrofacetDistribution::TrowbridgeReitz(
-                                            TrowbridgeReitzDistribution {
-                                                alpha_x: distribution.alpha_x,
-                                                alpha_y: distribution.alpha_y,
-                                                sample_visible_area: distribution
-                                                    .sample_visible_area,
-                                            },
-                                        ))
-                                    }
-                                    MicrofacetDistribution::DisneyMicrofacet(distribution) => {
-                                        Some(MicrofacetDistribution::DisneyMicrofacet(
-                                            DisneyMicrofacetDistribution::new(
-                                                distribution.inner.alpha_x,
-                                                distribution.inner.alpha_y,
-                                            ),
-                                        ))
-                                    }
-                                }
-                            }
-                            Bxdf::FresnelBlnd(FresnelBlend::new(
-                                bxdf.rd,
-                                bxdf.rs,
-                                distrib,
-                                bxdf.sc_opt,
-                            ))
-                        }
-                        Bxdf::Fourier(bxdf) => Bxdf::Fourier(FourierBSDF::new(
-                            bxdf.bsdf_table.clone(),
-                            bxdf.mode,
-                            bxdf.sc_opt,
-                        )),
-                        Bxdf::Bssrdf(bxdf) => Bxdf::Bssrdf(SeparableBssrdfAdapter {
-                            bssrdf: bxdf.bssrdf.clone(),
-                            mode: bxdf.mode,
-                            eta2: bxdf.eta2,
-                        }),
-                        Bxdf::DisDiff(bxdf) => {
-                            Bxdf::DisDiff(DisneyDiffuse::new(bxdf.r, bxdf.sc_opt))
-                        }
-                        Bxdf::DisSS(bxdf) => {
-                            Bxdf::DisSS(DisneyFakeSS::new(bxdf.r, bxdf.roughness, bxdf.sc_opt))
-                        }
-                        Bxdf::DisRetro(bxdf) => {
-                            Bxdf::DisRetro(DisneyRetro::new(bxdf.r, bxdf.roughness, bxdf.sc_opt))
-                        }
-                        Bxdf::DisSheen(bxdf) => {
-                            Bxdf::DisSheen(DisneySheen::new(bxdf.r, bxdf.sc_opt))
-                        }
-                        Bxdf::DisClearCoat(bxdf) => Bxdf::DisClearCoat(DisneyClearCoat::new(
-                            bxdf.weight,
-                            bxdf.gloss,
-                            bxdf.sc_opt,
-                        )),
-                        Bxdf::Hair(bxdf) => Bxdf::Hair(HairBSDF {
-                            h: bxdf.h,
-                            gamma_o: bxdf.gamma_o,
-                            eta: bxdf.eta,
-                            sigma_a: bxdf.sigma_a,
-                            beta_m: bxdf.beta_m,
-                            beta_n: bxdf.beta_n,
-                            v: bxdf.v,
-                            s: bxdf.s,
-                            sin_2k_alpha: bxdf.sin_2k_alpha,
-                            cos_2k_alpha: bxdf.cos_2k_alpha,
-                            sc_opt: bxdf.sc_opt,
-                        }),
-                    };
+                for (bxdf_idx, item) in bsdf2.bxdfs.iter().enumerate() {
+                    if let Bxdf::Empty(_bxdf) = item {
+                        break;
+                    }
+                    if bxdf_idx + last_idx >= bsdf.bxdfs.len() {
+                        println!("WARNING: Too many BxDFs for mix material. Ignoring the rest.");
+                        break;
+                    }
+                    // the BxDFs were already scaled by *s2*
+                    bsdf.bxdfs[bxdf_idx + last_idx] = item.clone();
                 }
             }
         }
@@ -11,6 +11,10 @@ use crate::core::texture::Texture;
 
 // see substrate.h
 
+/// A coated diffuse surface (e.g. car paint), modeled by a single
+/// **FresnelBlend** with an anisotropic Trowbridge-Reitz distribution
+/// (*nu* along the first, *nv* along the second tangent of the
+/// surface).
 pub struct SubstrateMaterial {
     pub kd: Arc<dyn Texture<Spectrum> + Sync + Send>, // default: 0.5
     pub ks: Arc<dyn Texture<Spectrum> + Sync + Send>, // default: 0.5
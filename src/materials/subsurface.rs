@@ -93,7 +93,7 @@ impl SubsurfaceMaterial {
         let kr: Arc<dyn Texture<Spectrum> + Sync + Send> =
             mp.get_spectrum_texture("Kr", Spectrum::new(1.0));
         let kt: Arc<dyn Texture<Spectrum> + Sync + Send> =
-            mp.get_spectrum_texture("Kr", Spectrum::new(1.0));
+            mp.get_spectrum_texture("Kt", Spectrum::new(1.0));
         let roughu: Arc<dyn Texture<Float> + Sync + Send> =
             mp.get_float_texture("uroughness", 0.0 as Float);
         let roughv: Arc<dyn Texture<Float> + Sync + Send> =
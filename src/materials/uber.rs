@@ -14,6 +14,11 @@ use crate::core::texture::Texture;
 
 // see uber.h
 
+/// The "kitchen sink" material of many exported scenes: a diffuse
+/// lobe (*kd*), a glossy microfacet lobe (*ks*) and perfectly
+/// specular reflection (*kr*) and transmission (*kt*), all scaled by
+/// the *opacity*. Whatever isn't opaque passes straight through the
+/// surface (a specular event, e.g. for cutout textures).
 pub struct UberMaterial {
     pub kd: Arc<dyn Texture<Spectrum> + Sync + Send>, // default: 0.25
     pub ks: Arc<dyn Texture<Spectrum> + Sync + Send>, // default: 0.25
@@ -175,6 +180,7 @@ impl UberMaterial {
         }
         if let Some(bsdf) = &mut si.bsdf {
             let mut bxdf_idx: usize = 0;
+            // partially transparent: continue the ray unchanged
             if !t.is_black() {
                 if use_scale {
                     bsdf.bxdfs[bxdf_idx] =
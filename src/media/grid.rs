@@ -4,7 +4,8 @@ use std::sync::Arc;
 use crate::core::geometry::pnt3i_inside_exclusive;
 use crate::core::geometry::{Bounds3f, Bounds3i, Point3f, Point3i, Ray, Vector3f, Vector3i};
 use crate::core::interaction::MediumInteraction;
-use crate::core::medium::{HenyeyGreenstein, Medium};
+use crate::core::medium::{get_medium_parameters, HenyeyGreenstein, Medium};
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::lerp;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::sampler::Sampler;
@@ -13,6 +14,10 @@ use crate::core::transform::Transform;
 
 // see grid.h
 
+/// Heterogeneous medium whose density is given by a grid of
+/// *nx* x *ny* x *nz* values over the unit cube in medium space,
+/// scaling *sigma_a* and *sigma_s*. Only the first channel of the
+/// extinction coefficient is used for tracking.
 pub struct GridDensityMedium {
     pub sigma_a: Spectrum,
     pub sigma_s: Spectrum,
@@ -54,6 +59,66 @@ impl GridDensityMedium {
             inv_max_density: 1.0 as Float / max_density,
         }
     }
+    /// Reads *"nx"*, *"ny"*, *"nz"*, the *"density"* values (x
+    /// varying fastest), the corners *"p0"* and *"p1"* of the grid in
+    /// medium space and the scattering properties (see
+    /// **get_medium_parameters()**). Returns **None** if the
+    /// densities don't match the grid resolution.
+    pub fn create(ps: &ParamSet, medium_to_world: &Transform) -> Option<Self> {
+        let (sig_a, sig_s, g) = get_medium_parameters(ps);
+        let data: Vec<Float> = ps.find_float("density");
+        if data.is_empty() {
+            println!("ERROR: No \"density\" values provided for heterogeneous medium?");
+            return None;
+        }
+        let nx: i32 = ps.find_one_int("nx", 1_i32);
+        let ny: i32 = ps.find_one_int("ny", 1_i32);
+        let nz: i32 = ps.find_one_int("nz", 1_i32);
+        if nx < 1 || ny < 1 || nz < 1 {
+            println!(
+                "ERROR: GridDensityMedium resolution {} x {} x {} is invalid",
+                nx, ny, nz
+            );
+            return None;
+        }
+        let p0: Point3f = ps.find_one_point3f(
+            "p0",
+            Point3f {
+                x: 0.0 as Float,
+                y: 0.0 as Float,
+                z: 0.0 as Float,
+            },
+        );
+        let p1: Point3f = ps.find_one_point3f(
+            "p1",
+            Point3f {
+                x: 1.0 as Float,
+                y: 1.0 as Float,
+                z: 1.0 as Float,
+            },
+        );
+        if data.len() != (nx as usize) * (ny as usize) * (nz as usize) {
+            println!(
+                "ERROR: GridDensityMedium has {} density values; expected nx*ny*nz = {}",
+                data.len(),
+                (nx as usize) * (ny as usize) * (nz as usize)
+            );
+            return None;
+        }
+        let data_2_medium: Transform = Transform::translate(&Vector3f::from(p0))
+            * Transform::scale(p1.x - p0.x, p1.y - p0.y, p1.z - p0.z);
+        Some(GridDensityMedium::new(
+            &sig_a,
+            &sig_s,
+            g,
+            nx,
+            ny,
+            nz,
+            &(*medium_to_world * data_2_medium),
+            Arc::new(data),
+        ))
+    }
+    /// Density of voxel *p*, zero outside of the grid.
     pub fn d(&self, p: &Point3i) -> Float {
         let sample_bounds: Bounds3i = Bounds3i {
             p_min: Point3i {
@@ -73,6 +138,8 @@ impl GridDensityMedium {
             self.density[((p.z * self.ny + p.y) * self.nx + p.x) as usize]
         }
     }
+    /// Trilinearly interpolated density at *p* (in medium space,
+    /// where the grid covers the unit cube).
     pub fn density(&self, p: &Point3f) -> Float {
         // compute voxel coordinates and offsets for _p_
         let p_samples: Point3f = Point3f {
@@ -151,7 +218,16 @@ impl GridDensityMedium {
         let d1: Float = lerp(d.y, d01, d11);
         lerp(d.z, d0, d1)
     }
+    /// Extinction coefficient of the densest voxel, used as majorant
+    /// for delta and ratio tracking (zero for empty media).
+    pub fn max_sigma_t(&self) -> Float {
+        // inv_max_density is infinite for a grid without density
+        self.sigma_t / self.inv_max_density
+    }
     // Medium
+    /// Estimates the transmittance along *r_world* with ratio
+    /// tracking, which is unbiased regardless of how far the local
+    /// density is below the maximum.
     pub fn tr(&self, r_world: &Ray, sampler: &mut Sampler) -> Spectrum {
         // TODO: ProfilePhase _(Prof::MediumTr);
         // TODO: ++nTrCalls;
@@ -179,12 +255,16 @@ impl GridDensityMedium {
         if !b.intersect_b(&ray, &mut t_min, &mut t_max) {
             return Spectrum::new(1.0 as Float);
         }
+        let max_sigma_t: Float = self.max_sigma_t();
+        if max_sigma_t.is_nan() || max_sigma_t <= 0.0 as Float {
+            return Spectrum::new(1.0 as Float);
+        }
         // perform ratio tracking to estimate the transmittance value
         let mut tr: Float = 1.0;
         let mut t: Float = t_min;
         loop {
             // TODO: ++nTrSteps;
-            t -= (1.0 as Float - sampler.get_1d()).ln() * self.inv_max_density / self.sigma_t;
+            t -= (1.0 as Float - sampler.get_1d()).ln() / max_sigma_t;
             if t >= t_max {
                 break;
             }
@@ -204,6 +284,8 @@ impl GridDensityMedium {
         }
         Spectrum::new(tr)
     }
+    /// Samples a collision along *r_world* with delta tracking
+    /// against the maximum density.
     pub fn sample(
         &self,
         r_world: &Ray,
@@ -233,18 +315,23 @@ impl GridDensityMedium {
         if !b.intersect_b(&ray, &mut t_min, &mut t_max) {
             return (Spectrum::new(1.0 as Float), None);
         }
+        let max_sigma_t: Float = self.max_sigma_t();
+        if max_sigma_t.is_nan() || max_sigma_t <= 0.0 as Float {
+            return (Spectrum::new(1.0 as Float), None);
+        }
         // run delta-tracking iterations to sample a medium interaction
         let mut t: Float = t_min;
         loop {
-            t -= (1.0 as Float - sampler.get_1d()).ln() * self.inv_max_density / self.sigma_t;
+            t -= (1.0 as Float - sampler.get_1d()).ln() / max_sigma_t;
             if t >= t_max {
                 break;
             }
             if self.density(&ray.position(t)) * self.inv_max_density > sampler.get_1d() {
                 let mi_opt: Option<MediumInteraction>;
                 // populate _mi_ with medium interaction information and return
+                // _t_ is a distance, _r_world.d_ isn't normalized
                 let mi: MediumInteraction = MediumInteraction::new(
-                    &r_world.position(t),
+                    &r_world.position(t / r_world.d.length()),
                     &(-r_world.d),
                     r_world.time,
                     Some(Arc::new(Medium::GridDensity(GridDensityMedium {
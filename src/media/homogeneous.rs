@@ -6,13 +6,17 @@ use strum::IntoEnumIterator;
 // pbrt
 use crate::core::geometry::Ray;
 use crate::core::interaction::MediumInteraction;
-use crate::core::medium::{HenyeyGreenstein, Medium};
+use crate::core::medium::{get_medium_parameters, HenyeyGreenstein, Medium};
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::sampler::Sampler;
 use crate::core::spectrum::RGBEnum;
 
 // see homogeneous.h
 
+/// Medium with constant absorption (*sigma_a*) and scattering
+/// (*sigma_s*) coefficients and a Henyey-Greenstein phase function
+/// with asymmetry *g*.
 pub struct HomogeneousMedium {
     pub sigma_a: Spectrum,
     pub sigma_s: Spectrum,
@@ -29,11 +33,21 @@ impl HomogeneousMedium {
             g,
         }
     }
+    /// Reads *"sigma_a"*, *"sigma_s"*, *"scale"*, *"g"* and
+    /// *"preset"* (see **get_medium_parameters()**).
+    pub fn create(ps: &ParamSet) -> Self {
+        let (sigma_a, sigma_s, g) = get_medium_parameters(ps);
+        HomogeneousMedium::new(&sigma_a, &sigma_s, g)
+    }
     // Medium
+    /// Beer-Lambert transmittance along *ray* (up to *t_max*).
     pub fn tr(&self, ray: &Ray, _sampler: &mut Sampler) -> Spectrum {
         // TODO: ProfilePhase _(Prof::MediumTr);
         (-self.sigma_t * (ray.t_max * ray.d.length()).min(f32::MAX)).exp()
     }
+    /// Samples a free-flight distance along *ray* for a channel
+    /// chosen uniformly at random. Returns the path throughput weight
+    /// and, for a collision before *t_max*, the medium interaction.
     pub fn sample(
         &self,
         ray: &Ray,
@@ -66,8 +80,10 @@ impl HomogeneousMedium {
         } else {
             None
         };
-        // compute the transmittance and sampling density
-        let tr: Spectrum = (-self.sigma_t * t.min(f32::MAX) * ray.d.length()).exp();
+        // compute the transmittance and sampling density (the
+        // average over the channels, as each channel is chosen with
+        // the same probability)
+        let tr: Spectrum = (-self.sigma_t * (t * ray.d.length()).min(f32::MAX)).exp();
         let density = if sampled_medium {
             self.sigma_t * tr
         } else {
@@ -0,0 +1,243 @@
+// std
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+// pbrt
+use crate::core::geometry::{Bounds2i, Point2f, Point2i};
+use crate::core::paramset::ParamSet;
+use crate::core::pbrt::Float;
+use crate::core::sampler::{PixelSampleBudget, Sampler, SamplerTrait};
+
+/// Number of samples each pixel of the sample bounds actually used,
+/// shared by all (per tile) clones of an **AdaptiveSampler**.
+#[derive(Debug)]
+pub struct AdaptiveSampleStats {
+    pub bounds: Bounds2i,
+    pub samples_used: Vec<AtomicU32>,
+}
+
+impl AdaptiveSampleStats {
+    pub fn new(bounds: Bounds2i) -> Self {
+        let n_pixels: i32 = bounds.area().max(0);
+        let mut samples_used: Vec<AtomicU32> = Vec::with_capacity(n_pixels as usize);
+        samples_used.resize_with(n_pixels as usize, || AtomicU32::new(0));
+        AdaptiveSampleStats {
+            bounds,
+            samples_used,
+        }
+    }
+    fn index(&self, p: Point2i) -> Option<usize> {
+        if p.x < self.bounds.p_min.x
+            || p.y < self.bounds.p_min.y
+            || p.x >= self.bounds.p_max.x
+            || p.y >= self.bounds.p_max.y
+        {
+            return None;
+        }
+        let width: i32 = self.bounds.p_max.x - self.bounds.p_min.x;
+        Some(((p.y - self.bounds.p_min.y) * width + p.x - self.bounds.p_min.x) as usize)
+    }
+    /// Remembers that pixel *p* used *n_samples* samples (ignored
+    /// outside of the bounds).
+    pub fn record(&self, p: Point2i, n_samples: u32) {
+        if let Some(idx) = self.index(p) {
+            self.samples_used[idx].store(n_samples, Ordering::Relaxed);
+        }
+    }
+    /// Samples used by pixel *p* (zero if it wasn't rendered yet).
+    pub fn get(&self, p: Point2i) -> u32 {
+        match self.index(p) {
+            Some(idx) => self.samples_used[idx].load(Ordering::Relaxed),
+            None => 0_u32,
+        }
+    }
+    /// Samples used, summed over all pixels.
+    pub fn total(&self) -> u64 {
+        self.samples_used
+            .iter()
+            .map(|n| n.load(Ordering::Relaxed) as u64)
+            .sum()
+    }
+    /// Average number of samples per pixel.
+    pub fn average(&self) -> Float {
+        if self.samples_used.is_empty() {
+            return 0.0 as Float;
+        }
+        (self.total() as f64 / self.samples_used.len() as f64) as Float
+    }
+}
+
+/// Wraps another sampler (which provides *max_samples* samples per
+/// pixel) and stops sampling a pixel early, once the estimated error
+/// of its luminance is small enough. The integrator has to report
+/// the luminance of each sample via *add_sample_luminance()*.
+pub struct AdaptiveSampler {
+    pub inner: Box<Sampler>,
+    pub min_samples: i64,
+    pub max_samples: i64,
+    /// stop once the standard error falls below this fraction of the
+    /// mean luminance ...
+    pub max_relative_error: Float,
+    /// ... or below this absolute value (for dark pixels, where the
+    /// relative error is meaningless)
+    pub max_absolute_error: Float,
+    pub stats: Arc<AdaptiveSampleStats>,
+    // running luminance statistics of the current pixel (Welford)
+    pub n_luminance: i64,
+    pub mean_luminance: f64,
+    pub m2_luminance: f64,
+}
+
+impl AdaptiveSampler {
+    pub fn new(
+        inner: Box<Sampler>,
+        min_samples: i64,
+        max_relative_error: Float,
+        max_absolute_error: Float,
+        sample_bounds: &Bounds2i,
+    ) -> Self {
+        let max_samples: i64 = inner.get_samples_per_pixel();
+        AdaptiveSampler {
+            inner,
+            // at least two samples are needed for a variance estimate
+            min_samples: min_samples.max(2).min(max_samples),
+            max_samples,
+            max_relative_error,
+            max_absolute_error,
+            stats: Arc::new(AdaptiveSampleStats::new(*sample_bounds)),
+            n_luminance: 0_i64,
+            mean_luminance: 0.0,
+            m2_luminance: 0.0,
+        }
+    }
+    pub fn clone_with_seed(&self, seed: u64) -> Box<Sampler> {
+        let adaptive_sampler = AdaptiveSampler {
+            inner: self.inner.clone_with_seed(seed),
+            min_samples: self.min_samples,
+            max_samples: self.max_samples,
+            max_relative_error: self.max_relative_error,
+            max_absolute_error: self.max_absolute_error,
+            // all clones record into the same statistics
+            stats: self.stats.clone(),
+            n_luminance: self.n_luminance,
+            mean_luminance: self.mean_luminance,
+            m2_luminance: self.m2_luminance,
+        };
+        let sampler = Sampler::Adaptive(adaptive_sampler);
+        Box::new(sampler)
+    }
+    /// Wraps *inner*, whose samples per pixel are the maximum.
+    pub fn create(
+        params: &ParamSet,
+        inner: Box<Sampler>,
+        sample_bounds: &Bounds2i,
+    ) -> Box<Sampler> {
+        let min_samples: i32 = params.find_one_int("minsamples", 16);
+        let max_relative_error: Float = params.find_one_float("maxerror", 0.01);
+        let max_absolute_error: Float = params.find_one_float("maxabsoluteerror", 0.001);
+        let sampler = AdaptiveSampler::new(
+            inner,
+            min_samples as i64,
+            max_relative_error,
+            max_absolute_error,
+            sample_bounds,
+        );
+        Box::new(Sampler::Adaptive(sampler))
+    }
+    /// Adds the luminance *y* of the current pixel sample to the
+    /// running statistics.
+    pub fn add_sample_luminance(&mut self, y: Float) {
+        let y: f64 = y as f64;
+        self.n_luminance += 1;
+        let delta: f64 = y - self.mean_luminance;
+        self.mean_luminance += delta / self.n_luminance as f64;
+        self.m2_luminance += delta * (y - self.mean_luminance);
+    }
+    /// Standard error of the current pixel's mean luminance (infinite
+    /// with less than two samples).
+    pub fn standard_error(&self) -> f64 {
+        if self.n_luminance < 2 {
+            return std::f64::INFINITY;
+        }
+        let n: f64 = self.n_luminance as f64;
+        let variance: f64 = (self.m2_luminance / (n - 1.0)).max(0.0);
+        (variance / n).sqrt()
+    }
+    /// Did the current pixel converge? Only decided after
+    /// *min_samples*; the absolute error floor keeps (nearly) black
+    /// pixels from running into *max_samples*.
+    pub fn converged(&self) -> bool {
+        if self.n_luminance < self.min_samples {
+            return false;
+        }
+        let error: f64 = self.standard_error();
+        error <= self.max_absolute_error as f64
+            || error <= self.max_relative_error as f64 * self.mean_luminance.abs()
+    }
+    pub fn set_pixel_budget(&mut self, budget: Arc<PixelSampleBudget>) {
+        self.inner.set_pixel_budget(budget);
+    }
+}
+
+impl SamplerTrait for AdaptiveSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *AdaptiveSampler::clone_with_seed(self, seed) {
+            Sampler::Adaptive(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
+    }
+    // Sampler
+    fn start_pixel(&mut self, p: Point2i) {
+        self.n_luminance = 0_i64;
+        self.mean_luminance = 0.0;
+        self.m2_luminance = 0.0;
+        self.inner.start_pixel(p);
+    }
+    fn get_1d(&mut self) -> Float {
+        self.inner.get_1d()
+    }
+    fn get_2d(&mut self) -> Point2f {
+        self.inner.get_2d()
+    }
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+        self.inner.get_2d_sample(array_idx, idx)
+    }
+    fn request_2d_array(&mut self, n: i32) {
+        self.inner.request_2d_array(n);
+    }
+    fn round_count(&self, count: i32) -> i32 {
+        self.inner.round_count(count)
+    }
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+        self.inner.get_2d_array(n)
+    }
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+        self.inner.get_2d_array_idxs(n)
+    }
+    fn start_next_sample(&mut self) -> bool {
+        let p: Point2i = self.inner.get_current_pixel();
+        let n_samples: i64 = self.inner.get_current_sample_number() + 1;
+        if self.converged() || !self.inner.start_next_sample() {
+            self.stats.record(p, n_samples as u32);
+            return false;
+        }
+        true
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.inner.reseed(seed);
+    }
+    fn get_current_pixel(&self) -> Point2i {
+        self.inner.get_current_pixel()
+    }
+    fn get_current_sample_number(&self) -> i64 {
+        self.inner.get_current_sample_number()
+    }
+    fn get_samples_per_pixel(&self) -> i64 {
+        self.max_samples
+    }
+    fn ensure_sampled_dimensions(&mut self, n: usize) {
+        self.inner.ensure_sampled_dimensions(n);
+    }
+    fn set_sample_number(&mut self, sample_num: i64) -> bool {
+        self.inner.set_sample_number(sample_num)
+    }
+}
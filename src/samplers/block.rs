@@ -0,0 +1,168 @@
+// pbrt
+use crate::core::geometry::{Point2f, Point2i};
+use crate::core::paramset::ParamSet;
+use crate::core::pbrt::{mix_bits, Float};
+use crate::core::rng::FLOAT_ONE_MINUS_EPSILON;
+use crate::core::sampler::{frame_index_from_params, seed_from_params, Sampler, SamplerTrait};
+use crate::core::sampling::{cranley_patterson_rotation, pixel_offset_2d};
+use crate::samplers::stratified::StratifiedSampler;
+
+/// Shares one stratified sample set between all pixels of a
+/// *block_size x block_size* block. Each pixel applies its own
+/// Cranley-Patterson rotation to the shared set, so neighboring
+/// pixels are correlated (and converge together), but don't sample
+/// the exact same positions.
+pub struct BlockSampler {
+    pub block_size: i32,
+    /// Generates the stratified set shared by the pixels of a block
+    pub base: StratifiedSampler,
+    /// Block the shared set in *base* was generated for
+    pub current_block: Option<Point2i>,
+    /// Cranley-Patterson rotation of the current pixel
+    pub pixel_offset: Point2f,
+    /// Rotated copies of the shared 2D arrays for the current pixel
+    pub pixel_array_2d: Vec<Vec<Point2f>>,
+}
+
+impl BlockSampler {
+    pub fn new(block_size: i32, base: StratifiedSampler) -> Self {
+        let block_size: i32 = if block_size < 1 {
+            println!(
+                "WARNING: blocksize = {} clamped to 1 for BlockSampler.",
+                block_size
+            );
+            1
+        } else {
+            block_size
+        };
+        BlockSampler {
+            block_size,
+            base,
+            current_block: None,
+            pixel_offset: Point2f::default(),
+            pixel_array_2d: Vec::new(),
+        }
+    }
+    pub fn clone_with_seed(&self, seed: u64) -> Box<Sampler> {
+        let base: StratifiedSampler = match *self.base.clone_with_seed(seed) {
+            Sampler::Stratified(sampler) => sampler,
+            _ => unreachable!(),
+        };
+        let sampler = BlockSampler {
+            block_size: self.block_size,
+            base,
+            current_block: None,
+            pixel_offset: self.pixel_offset,
+            pixel_array_2d: self.pixel_array_2d.to_vec(),
+        };
+        Box::new(Sampler::Block(sampler))
+    }
+    pub fn create(params: &ParamSet) -> Box<Sampler> {
+        let jitter: bool = params.find_one_bool("jitter", true);
+        let xsamp: i32 = params.find_one_int("xsamples", 4);
+        let ysamp: i32 = params.find_one_int("ysamples", 4);
+        let sd: i32 = params.find_one_int("dimensions", 4);
+        let block_size: i32 = params.find_one_int("blocksize", 4);
+        let mut base = StratifiedSampler::new(xsamp, ysamp, jitter, sd as i64);
+        base.frame_index = frame_index_from_params(params);
+        base.base_seed = seed_from_params(params);
+        Box::new(Sampler::Block(BlockSampler::new(block_size, base)))
+    }
+}
+
+impl SamplerTrait for BlockSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *BlockSampler::clone_with_seed(self, seed) {
+            Sampler::Block(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
+    }
+    // Sampler
+    fn start_pixel(&mut self, p: Point2i) {
+        // TODO: ProfilePhase _(Prof::StartPixel);
+        let block: Point2i = Point2i {
+            x: p.x.div_euclid(self.block_size),
+            y: p.y.div_euclid(self.block_size),
+        };
+        let same_block: bool = match self.current_block {
+            Some(current) => current.x == block.x && current.y == block.y,
+            None => false,
+        };
+        if same_block {
+            // rewind the shared set
+            self.base.current_pixel_sample_index = 0_i64;
+            self.base.current_1d_dimension = 0_i32;
+            self.base.current_2d_dimension = 0_i32;
+            self.base.array_1d_offset = 0_usize;
+            self.base.array_2d_offset = 0_usize;
+        } else {
+            // seed by block (not by tile), so blocks crossing tile
+            // boundaries still share one set
+            self.base.reseed(mix_bits(
+                ((block.x as u32 as u64) << 32) | block.y as u32 as u64,
+            ));
+            self.base.start_pixel(Point2i {
+                x: block.x * self.block_size,
+                y: block.y * self.block_size,
+            });
+            self.current_block = Some(block);
+        }
+        self.base.current_pixel = p;
+        self.pixel_offset = pixel_offset_2d(p);
+        for i in 0..self.base.sample_array_2d.len() {
+            let shared: &[Point2f] = &self.base.sample_array_2d[i];
+            let rotated: &mut Vec<Point2f> = &mut self.pixel_array_2d[i];
+            for j in 0..shared.len() {
+                rotated[j] = cranley_patterson_rotation(shared[j], self.pixel_offset);
+            }
+        }
+    }
+    fn get_1d(&mut self) -> Float {
+        let u: Float = self.base.get_1d() + self.pixel_offset.x;
+        (u - u.floor()).min(FLOAT_ONE_MINUS_EPSILON)
+    }
+    fn get_2d(&mut self) -> Point2f {
+        cranley_patterson_rotation(self.base.get_2d(), self.pixel_offset)
+    }
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+        self.pixel_array_2d[array_idx][idx]
+    }
+    fn request_2d_array(&mut self, n: i32) {
+        self.base.request_2d_array(n);
+        let size: usize = (n * self.base.samples_per_pixel as i32) as usize;
+        self.pixel_array_2d.push(vec![Point2f::default(); size]);
+    }
+    fn round_count(&self, count: i32) -> i32 {
+        self.base.round_count(count)
+    }
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+        let (done, idx, start) = self.base.get_2d_array_idxs(n);
+        if done {
+            return None;
+        }
+        Some(&self.pixel_array_2d[idx][start..start + n as usize])
+    }
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+        self.base.get_2d_array_idxs(n)
+    }
+    fn start_next_sample(&mut self) -> bool {
+        self.base.start_next_sample()
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.base.reseed(seed);
+        self.current_block = None;
+    }
+    fn get_current_pixel(&self) -> Point2i {
+        self.base.get_current_pixel()
+    }
+    fn get_current_sample_number(&self) -> i64 {
+        self.base.get_current_sample_number()
+    }
+    fn get_samples_per_pixel(&self) -> i64 {
+        self.base.get_samples_per_pixel()
+    }
+    fn ensure_sampled_dimensions(&mut self, n: usize) {
+        self.base.ensure_sampled_dimensions(n);
+        self.current_block = None;
+    }
+}
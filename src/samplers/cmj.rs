@@ -0,0 +1,278 @@
+// pbrt
+use crate::core::geometry::{Point2f, Point2i};
+use crate::core::paramset::ParamSet;
+use crate::core::pbrt::mix_bits;
+use crate::core::pbrt::Float;
+use crate::core::rng::FLOAT_ONE_MINUS_EPSILON;
+use crate::core::sampler::{
+    frame_index_from_params, frame_seed, seed_from_params, Sampler, SamplerTrait,
+};
+
+// see Kensler: Correlated Multi-Jittered Sampling (Pixar Technical Memo 13-01)
+
+/// Pseudo-random permutation of *i* within *[0, l)* for pattern *p*.
+pub fn cmj_permute(i: u32, l: u32, p: u32) -> u32 {
+    let mut w: u32 = l - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    let mut i: u32 = i;
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < l {
+            break;
+        }
+    }
+    (i.wrapping_add(p)) % l
+}
+
+/// Pseudo-random float in *[0, 1)* for index *i* and pattern *p*.
+pub fn cmj_randfloat(i: u32, p: u32) -> Float {
+    let mut i: u32 = i;
+    i ^= p;
+    i ^= i >> 17;
+    i ^= i >> 10;
+    i = i.wrapping_mul(0xb365_34e5);
+    i ^= i >> 12;
+    i ^= i >> 21;
+    i = i.wrapping_mul(0x93fc_4795);
+    i ^= 0xdf6e_307f;
+    i ^= i >> 17;
+    i = i.wrapping_mul(1 | p >> 18);
+    (i as f64 * (1.0 / 4_294_967_808.0)) as Float
+}
+
+/// The *s*-th point of a correlated multi-jittered pattern with *m x
+/// n* strata.
+pub fn cmj_sample_2d(s: u32, m: u32, n: u32, p: u32) -> Point2f {
+    let s: u32 = cmj_permute(s, m * n, p.wrapping_mul(0x5163_3e2d));
+    let sx: u32 = cmj_permute(s % m, m, p.wrapping_mul(0xa511_e9b3));
+    let sy: u32 = cmj_permute(s / m, n, p.wrapping_mul(0x63d8_3595));
+    let jx: Float = cmj_randfloat(s, p.wrapping_mul(0xa399_d265));
+    let jy: Float = cmj_randfloat(s, p.wrapping_mul(0x711a_d6a5));
+    Point2f {
+        x: (((s % m) as Float + (sy as Float + jx) / n as Float) / m as Float)
+            .min(FLOAT_ONE_MINUS_EPSILON),
+        y: (((s / m) as Float + (sx as Float + jy) / m as Float) / n as Float)
+            .min(FLOAT_ONE_MINUS_EPSILON),
+    }
+}
+
+/// The *s*-th value of a jittered 1D pattern with *n* strata,
+/// visited in permuted order.
+pub fn cmj_sample_1d(s: u32, n: u32, p: u32) -> Float {
+    let sx: u32 = cmj_permute(s, n, p.wrapping_mul(0x68bc_21eb));
+    let jx: Float = cmj_randfloat(s, p.wrapping_mul(0x967a_889b));
+    ((sx as Float + jx) / n as Float).min(FLOAT_ONE_MINUS_EPSILON)
+}
+
+/// Split *count* into two (almost square) factors for a *m x n*
+/// stratification.
+pub fn cmj_strata(count: u32) -> (u32, u32) {
+    let mut m: u32 = (count as Float).sqrt() as u32;
+    while m > 1 && (count / m) * m != count {
+        m -= 1;
+    }
+    let m: u32 = m.max(1);
+    (m, count / m)
+}
+
+/// Correlated multi-jittered sampler. Each sample is computed on the
+/// fly from *(sample index, strata, pattern seed)*, so there are no
+/// per-dimension arrays to fill in *start_pixel()*.
+pub struct CmjSampler {
+    pub samples_per_pixel: i64,
+    pub x_pixel_samples: i32,
+    pub y_pixel_samples: i32,
+    pub seed: u64,
+    pub frame_index: u64,
+    pub base_seed: u64,
+    pub current_1d_dimension: i32,
+    pub current_2d_dimension: i32,
+    // inherited from class Sampler (see sampler.h)
+    pub current_pixel: Point2i,
+    pub current_pixel_sample_index: i64,
+    pub samples_2d_array_sizes: Vec<i32>,
+    /// Only the current pixel sample's array values are stored
+    pub sample_array_2d: Vec<Vec<Point2f>>,
+    pub array_2d_offset: usize,
+}
+
+impl CmjSampler {
+    pub fn new(x_pixel_samples: i32, y_pixel_samples: i32) -> Self {
+        CmjSampler {
+            samples_per_pixel: (x_pixel_samples * y_pixel_samples) as i64,
+            x_pixel_samples,
+            y_pixel_samples,
+            seed: 0_u64,
+            frame_index: 0_u64,
+            base_seed: 0_u64,
+            current_1d_dimension: 0_i32,
+            current_2d_dimension: 0_i32,
+            current_pixel: Point2i::default(),
+            current_pixel_sample_index: 0_i64,
+            samples_2d_array_sizes: Vec::new(),
+            sample_array_2d: Vec::new(),
+            array_2d_offset: 0_usize,
+        }
+    }
+    pub fn clone_with_seed(&self, seed: u64) -> Box<Sampler> {
+        let cmj_sampler = CmjSampler {
+            samples_per_pixel: self.samples_per_pixel,
+            x_pixel_samples: self.x_pixel_samples,
+            y_pixel_samples: self.y_pixel_samples,
+            seed: frame_seed(seed ^ self.base_seed, self.frame_index),
+            frame_index: self.frame_index,
+            base_seed: self.base_seed,
+            current_1d_dimension: self.current_1d_dimension,
+            current_2d_dimension: self.current_2d_dimension,
+            current_pixel: self.current_pixel,
+            current_pixel_sample_index: self.current_pixel_sample_index,
+            samples_2d_array_sizes: self.samples_2d_array_sizes.to_vec(),
+            sample_array_2d: self.sample_array_2d.to_vec(),
+            array_2d_offset: self.array_2d_offset,
+        };
+        let sampler = Sampler::Cmj(cmj_sampler);
+        Box::new(sampler)
+    }
+    pub fn create(params: &ParamSet) -> Box<Sampler> {
+        let xsamp: i32 = params.find_one_int("xsamples", 4);
+        let ysamp: i32 = params.find_one_int("ysamples", 4);
+        // TODO: if (PbrtOptions.quickRender) nsamp = 1;
+        let mut sampler = CmjSampler::new(xsamp, ysamp);
+        sampler.frame_index = frame_index_from_params(params);
+        sampler.base_seed = seed_from_params(params);
+        Box::new(Sampler::Cmj(sampler))
+    }
+    /// Pattern seed for a given dimension of the current pixel.
+    pub fn pattern_seed(&self, dimension: u64) -> u32 {
+        let pixel: u64 =
+            ((self.current_pixel.x as u32 as u64) << 32) | self.current_pixel.y as u32 as u64;
+        mix_bits(pixel ^ mix_bits(self.seed.wrapping_add(dimension))) as u32
+    }
+    fn fill_2d_array(&mut self, array_idx: usize) {
+        let n: u32 = self.samples_2d_array_sizes[array_idx] as u32;
+        // stratify the array values of all pixel samples together
+        let (m, k) = cmj_strata(n * self.samples_per_pixel as u32);
+        let p: u32 = self.pattern_seed(0xffff_0000_u64 + array_idx as u64);
+        let first: u32 = self.current_pixel_sample_index as u32 * n;
+        for j in 0..n {
+            self.sample_array_2d[array_idx][j as usize] = cmj_sample_2d(first + j, m, k, p);
+        }
+    }
+}
+
+impl SamplerTrait for CmjSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *CmjSampler::clone_with_seed(self, seed) {
+            Sampler::Cmj(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
+    }
+    // Sampler
+    fn start_pixel(&mut self, p: Point2i) {
+        // TODO: ProfilePhase _(Prof::StartPixel);
+        self.current_pixel = p;
+        self.current_pixel_sample_index = 0_i64;
+        self.current_1d_dimension = 0_i32;
+        self.current_2d_dimension = 0_i32;
+        self.array_2d_offset = 0_usize;
+    }
+    fn get_1d(&mut self) -> Float {
+        // TODO: ProfilePhase _(Prof::GetSample);
+        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
+        let p: u32 = self.pattern_seed(2 * self.current_1d_dimension as u64);
+        self.current_1d_dimension += 1;
+        cmj_sample_1d(
+            self.current_pixel_sample_index as u32,
+            self.samples_per_pixel as u32,
+            p,
+        )
+    }
+    fn get_2d(&mut self) -> Point2f {
+        // TODO: ProfilePhase _(Prof::GetSample);
+        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
+        let p: u32 = self.pattern_seed(2 * self.current_2d_dimension as u64 + 1);
+        self.current_2d_dimension += 1;
+        cmj_sample_2d(
+            self.current_pixel_sample_index as u32,
+            self.x_pixel_samples as u32,
+            self.y_pixel_samples as u32,
+            p,
+        )
+    }
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+        self.sample_array_2d[array_idx][idx]
+    }
+    fn request_2d_array(&mut self, n: i32) {
+        assert_eq!(self.round_count(n), n);
+        self.samples_2d_array_sizes.push(n);
+        self.sample_array_2d
+            .push(vec![Point2f::default(); n as usize]);
+    }
+    fn round_count(&self, count: i32) -> i32 {
+        count
+    }
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+        if self.array_2d_offset == self.sample_array_2d.len() {
+            return None;
+        }
+        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
+        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
+        self.fill_2d_array(self.array_2d_offset);
+        self.array_2d_offset += 1;
+        Some(&self.sample_array_2d[self.array_2d_offset - 1][..])
+    }
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+        if self.array_2d_offset == self.sample_array_2d.len() {
+            return (true, 0_usize, 0_usize);
+        }
+        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
+        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
+        let idx: usize = self.array_2d_offset;
+        self.fill_2d_array(idx);
+        self.array_2d_offset += 1;
+        // only the current pixel sample is stored, so it starts at 0
+        (false, idx, 0_usize)
+    }
+    fn start_next_sample(&mut self) -> bool {
+        self.current_1d_dimension = 0_i32;
+        self.current_2d_dimension = 0_i32;
+        // Sampler::StartNextSample()
+        // reset array offsets for next pixel sample
+        self.array_2d_offset = 0_usize;
+        self.current_pixel_sample_index += 1_i64;
+        self.current_pixel_sample_index < self.samples_per_pixel
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.seed = frame_seed(seed ^ self.base_seed, self.frame_index);
+    }
+    fn get_current_pixel(&self) -> Point2i {
+        self.current_pixel
+    }
+    fn get_current_sample_number(&self) -> i64 {
+        self.current_pixel_sample_index
+    }
+    fn get_samples_per_pixel(&self) -> i64 {
+        self.samples_per_pixel
+    }
+}
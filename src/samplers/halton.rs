@@ -13,7 +13,7 @@ use crate::core::paramset::ParamSet;
 use crate::core::pbrt::mod_t;
 use crate::core::pbrt::Float;
 use crate::core::rng::Rng;
-use crate::core::sampler::Sampler;
+use crate::core::sampler::{Sampler, SamplerTrait};
 
 // Generate random digit permutations for Halton sampler
 lazy_static::lazy_static! {
@@ -60,6 +60,9 @@ pub struct HaltonSampler {
     pub pixel_for_offset_y: AtomicI32,
     pub offset_for_current_pixel: AtomicU64,
     pub sample_at_pixel_center: bool, // default: false
+    /// pixel offsets are computed relative to *sample_bounds.p_min*,
+    /// so crop windows don't alias pixels onto the same samples
+    pub sample_bounds: Bounds2i,
     // inherited from class GlobalSampler (see sampler.h)
     pub dimension: i64,
     pub interval_sample_index: u64,
@@ -116,6 +119,7 @@ impl HaltonSampler {
             pixel_for_offset_y: AtomicI32::new(0_i32),
             offset_for_current_pixel: AtomicU64::new(0_u64),
             sample_at_pixel_center,
+            sample_bounds: *sample_bounds,
             dimension: 0_i64,
             interval_sample_index: 0_u64,
             array_start_dim: 5_i64, // static const int arrayStartDim = 5;
@@ -144,6 +148,7 @@ impl HaltonSampler {
             pixel_for_offset_y: AtomicI32::new(pixel_for_offset_y),
             offset_for_current_pixel: AtomicU64::new(offset_for_current_pixel),
             sample_at_pixel_center: self.sample_at_pixel_center,
+            sample_bounds: self.sample_bounds,
             dimension: self.dimension,
             interval_sample_index: self.interval_sample_index,
             array_start_dim: self.array_start_dim,
@@ -180,9 +185,12 @@ impl HaltonSampler {
             self.offset_for_current_pixel
                 .store(0_u64, Ordering::Relaxed);
             if self.sample_stride > 1_u64 {
+                // the base scales only cover the sample bounds, not
+                // the full image resolution
+                let p: Vector2i = self.current_pixel - self.sample_bounds.p_min;
                 let pm: Point2i = Point2i {
-                    x: mod_t(self.current_pixel[XYEnum::X], K_MAX_RESOLUTION),
-                    y: mod_t(self.current_pixel[XYEnum::Y], K_MAX_RESOLUTION),
+                    x: mod_t(p.x, K_MAX_RESOLUTION),
+                    y: mod_t(p.y, K_MAX_RESOLUTION),
                 };
                 for i in XYEnum::iter() {
                     let dim_offset = if (i as u8) == 0 {
@@ -233,8 +241,17 @@ impl HaltonSampler {
         }
         &RADICAL_INVERSE_PERMUTATIONS[PRIME_SUMS[dim as usize] as usize..]
     }
+}
+
+impl SamplerTrait for HaltonSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *HaltonSampler::clone_with_seed(self, seed) {
+            Sampler::Halton(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
+    }
     // Sampler
-    pub fn start_pixel(&mut self, p: Point2i) {
+    fn start_pixel(&mut self, p: Point2i) {
         // TODO: ProfilePhase _(Prof::StartPixel);
         // Sampler::StartPixel(p);
         self.current_pixel = p;
@@ -272,7 +289,7 @@ impl HaltonSampler {
         }
         assert!(self.array_end_dim == dim);
     }
-    pub fn get_1d(&mut self) -> Float {
+    fn get_1d(&mut self) -> Float {
         // TODO: ProfilePhase _(Prof::GetSample);
         if self.dimension >= self.array_start_dim && self.dimension < self.array_end_dim {
             self.dimension = self.array_end_dim;
@@ -283,7 +300,7 @@ impl HaltonSampler {
         // then return
         ret
     }
-    pub fn get_2d(&mut self) -> Point2f {
+    fn get_2d(&mut self) -> Point2f {
         // TODO: ProfilePhase _(Prof::GetSample);
         if self.dimension + 1 >= self.array_start_dim && self.dimension < self.array_end_dim {
             self.dimension = self.array_end_dim;
@@ -295,20 +312,20 @@ impl HaltonSampler {
         self.dimension += 2;
         p
     }
-    pub fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
         self.sample_array_2d[array_idx][idx]
     }
-    pub fn request_2d_array(&mut self, n: i32) {
+    fn request_2d_array(&mut self, n: i32) {
         assert_eq!(self.round_count(n), n);
         self.samples_2d_array_sizes.push(n);
         let size: usize = (n * self.samples_per_pixel as i32) as usize;
         let additional_points: Vec<Point2f> = vec![Point2f::default(); size];
         self.sample_array_2d.push(additional_points);
     }
-    pub fn round_count(&self, count: i32) -> i32 {
+    fn round_count(&self, count: i32) -> i32 {
         count
     }
-    pub fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return None;
         }
@@ -319,30 +336,7 @@ impl HaltonSampler {
         self.array_2d_offset += 1;
         Some(&self.sample_array_2d[self.array_2d_offset - 1][start..end])
     }
-    pub fn get_2d_arrays(&mut self, n: i32) -> (Option<&[Point2f]>, Option<&[Point2f]>) {
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret1 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // repeat code from above
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret2 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // return tuple
-        (Some(ret1), Some(ret2))
-    }
-    pub fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return (true, 0_usize, 0_usize);
         }
@@ -353,7 +347,7 @@ impl HaltonSampler {
         self.array_2d_offset += 1;
         (false, idx, start)
     }
-    pub fn start_next_sample(&mut self) -> bool {
+    fn start_next_sample(&mut self) -> bool {
         self.dimension = 0_i64;
         self.interval_sample_index =
             self.get_index_for_sample(self.current_pixel_sample_index as u64 + 1_u64);
@@ -364,20 +358,20 @@ impl HaltonSampler {
         self.current_pixel_sample_index += 1_i64;
         self.current_pixel_sample_index < self.samples_per_pixel
     }
-    pub fn reseed(&mut self, _seed: u64) {
+    fn reseed(&mut self, _seed: u64) {
         // do nothing
     }
-    pub fn get_current_pixel(&self) -> Point2i {
+    fn get_current_pixel(&self) -> Point2i {
         self.current_pixel
     }
-    pub fn get_current_sample_number(&self) -> i64 {
+    fn get_current_sample_number(&self) -> i64 {
         self.current_pixel_sample_index
     }
-    pub fn get_samples_per_pixel(&self) -> i64 {
+    fn get_samples_per_pixel(&self) -> i64 {
         self.samples_per_pixel
     }
     // GlobalSampler
-    pub fn set_sample_number(&mut self, sample_num: i64) -> bool {
+    fn set_sample_number(&mut self, sample_num: i64) -> bool {
         // GlobalSampler::SetSampleNumber(...)
         self.dimension = 0_i64;
         self.interval_sample_index = self.get_index_for_sample(sample_num as u64);
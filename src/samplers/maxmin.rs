@@ -6,9 +6,15 @@ use crate::core::paramset::ParamSet;
 use crate::core::pbrt::Float;
 use crate::core::pbrt::{is_power_of_2, log_2_int_i64, round_up_pow2_32, round_up_pow2_64};
 use crate::core::rng::Rng;
-use crate::core::sampler::Sampler;
-use crate::core::sampling::shuffle;
+use crate::core::sampler::{
+    frame_index_from_params, frame_seed, seed_from_params, Sampler, SamplerTrait,
+};
+use crate::core::sampling::{cranley_patterson_rotation, pixel_offset_2d, shuffle};
 
+/// Pixel sampler using point sets with maximized minimum distance
+/// (Grünschloß and Keller) for the first 2D dimension (usually the
+/// film position) and scrambled (0,2)-sequences for all others.
+/// Sample counts are powers of two, up to 2^16.
 pub struct MaxMinDistSampler {
     pub samples_per_pixel: i64,
     pub c_pixel: [u32; 32],
@@ -18,6 +24,8 @@ pub struct MaxMinDistSampler {
     pub current_1d_dimension: i32,
     pub current_2d_dimension: i32,
     pub rng: Rng,
+    pub frame_index: u64,
+    pub base_seed: u64,
     // inherited from class Sampler (see sampler.h)
     pub current_pixel: Point2i,
     pub current_pixel_sample_index: i64,
@@ -31,20 +39,14 @@ pub struct MaxMinDistSampler {
 
 impl MaxMinDistSampler {
     pub fn new(samples_per_pixel: i64, n_sampled_dimensions: i64) -> Self {
-        let mut samples_per_pixel: i64 = samples_per_pixel;
-        let c_index: i32 = log_2_int_i64(samples_per_pixel) as i32;
-        if c_index as usize >= 17_usize {
-            panic!(
-                "No more than {} samples per pixel are supported with MaxMinDistSampler.",
-                c_index
+        let mut samples_per_pixel: i64 = samples_per_pixel.max(1_i64);
+        let max_samples_per_pixel: i64 = 1_i64 << (C_MAX_MIN_DIST.len() - 1);
+        if samples_per_pixel > max_samples_per_pixel {
+            println!(
+                "WARNING: No more than {} samples per pixel are supported with MaxMinDistSampler. Rounding down.",
+                max_samples_per_pixel
             );
-            //     Warning(
-            //         "No more than %d samples per pixel are supported with "
-            //             "MaxMinDistSampler. Rounding down.",
-            //         (1 << int(sizeof(CMaxMinDist) / sizeof(CMaxMinDist[0]))) -
-            //             1);
-            //     spp =
-            //         (1 << (sizeof(CMaxMinDist) / sizeof(CMaxMinDist[0]))) - 1;
+            samples_per_pixel = max_samples_per_pixel;
         }
         if !is_power_of_2(samples_per_pixel) {
             samples_per_pixel = round_up_pow2_64(samples_per_pixel);
@@ -54,7 +56,6 @@ impl MaxMinDistSampler {
             );
         }
         let c_index: i32 = log_2_int_i64(samples_per_pixel) as i32;
-        assert!(c_index >= 0_i32 && c_index < 17);
         let mut mmds: MaxMinDistSampler = MaxMinDistSampler {
             samples_per_pixel,
             c_pixel: C_MAX_MIN_DIST[c_index as usize],
@@ -62,7 +63,9 @@ impl MaxMinDistSampler {
             samples_2d: Vec::new(),
             current_1d_dimension: 0_i32,
             current_2d_dimension: 0_i32,
-            rng: Rng::default(),
+            rng: Rng::new(),
+            frame_index: 0_u64,
+            base_seed: 0_u64,
             current_pixel: Point2i::default(),
             current_pixel_sample_index: 0_i64,
             samples_1d_array_sizes: Vec::new(),
@@ -72,7 +75,8 @@ impl MaxMinDistSampler {
             array_1d_offset: 0_usize,
             array_2d_offset: 0_usize,
         };
-        for _i in 0..n_sampled_dimensions {
+        // the first 2D dimension holds the max-min distance set
+        for _i in 0..n_sampled_dimensions.max(1) {
             let additional_1d: Vec<Float> = vec![0.0; mmds.samples_per_pixel as usize];
             let additional_2d: Vec<Point2f> =
                 vec![Point2f::default(); mmds.samples_per_pixel as usize];
@@ -90,6 +94,8 @@ impl MaxMinDistSampler {
             current_1d_dimension: self.current_1d_dimension,
             current_2d_dimension: self.current_2d_dimension,
             rng: self.rng,
+            frame_index: self.frame_index,
+            base_seed: self.base_seed,
             current_pixel: self.current_pixel,
             current_pixel_sample_index: self.current_pixel_sample_index,
             samples_1d_array_sizes: self.samples_1d_array_sizes.to_vec(),
@@ -103,24 +109,43 @@ impl MaxMinDistSampler {
         let sampler = Sampler::MaxMinDist(mmds);
         Box::new(sampler)
     }
+    /// Reads *"pixelsamples"* (rounded up to a power of two) and
+    /// *"dimensions"*.
     pub fn create(params: &ParamSet) -> Box<Sampler> {
         let nsamp: i32 = params.find_one_int("pixelsamples", 16);
         let sd: i32 = params.find_one_int("dimensions", 4);
         // TODO: if (PbrtOptions.quickRender) nsamp = 1;
-        Box::new(Sampler::MaxMinDist(MaxMinDistSampler::new(
-            nsamp as i64,
-            sd as i64,
-        )))
+        let mut sampler = MaxMinDistSampler::new(nsamp as i64, sd as i64);
+        sampler.frame_index = frame_index_from_params(params);
+        sampler.base_seed = seed_from_params(params);
+        Box::new(Sampler::MaxMinDist(sampler))
+    }
+}
+
+impl SamplerTrait for MaxMinDistSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *MaxMinDistSampler::clone_with_seed(self, seed) {
+            Sampler::MaxMinDist(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
     }
     // Sampler
-    pub fn start_pixel(&mut self, p: Point2i) {
+    /// Generates all samples of pixel *p*. The maximized minimum
+    /// distance set is toroidally shifted by an offset derived from
+    /// the pixel coordinates (which keeps the distances), so
+    /// neighbouring pixels don't share the same pattern.
+    fn start_pixel(&mut self, p: Point2i) {
         // TODO: ProfilePhase _(Prof::StartPixel);
         let inv_spp: Float = 1.0 as Float / self.samples_per_pixel as Float;
+        let offset: Point2f = pixel_offset_2d(p);
         for i in 0..self.samples_per_pixel as usize {
-            self.samples_2d[0_usize][i] = Point2f {
-                x: i as Float * inv_spp,
-                y: sample_generator_matrix(&self.c_pixel, i as u32, 0_u32),
-            };
+            self.samples_2d[0_usize][i] = cranley_patterson_rotation(
+                Point2f {
+                    x: i as Float * inv_spp,
+                    y: sample_generator_matrix(&self.c_pixel, i as u32, 0_u32),
+                },
+                offset,
+            );
         }
         let samples: &mut [Point2f] = self.samples_2d[0].as_mut_slice();
         shuffle(samples, self.samples_per_pixel as i32, 1, &mut self.rng);
@@ -158,7 +183,7 @@ impl MaxMinDistSampler {
         self.array_1d_offset = 0_usize;
         self.array_2d_offset = 0_usize;
     }
-    pub fn get_1d(&mut self) -> Float {
+    fn get_1d(&mut self) -> Float {
         // TODO: ProfilePhase _(Prof::GetSample);
         assert!(self.current_pixel_sample_index < self.samples_per_pixel);
         if self.current_1d_dimension < self.samples_1d.len() as i32 {
@@ -170,7 +195,7 @@ impl MaxMinDistSampler {
             self.rng.uniform_float()
         }
     }
-    pub fn get_2d(&mut self) -> Point2f {
+    fn get_2d(&mut self) -> Point2f {
         // TODO: ProfilePhase _(Prof::GetSample);
         assert!(self.current_pixel_sample_index < self.samples_per_pixel);
         if self.current_2d_dimension < self.samples_2d.len() as i32 {
@@ -185,20 +210,23 @@ impl MaxMinDistSampler {
             Point2f { x, y }
         }
     }
-    pub fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
         self.sample_array_2d[array_idx][idx]
     }
-    pub fn request_2d_array(&mut self, n: i32) {
+    /// Arrays are filled with (0,2)-sequence samples, so each pixel
+    /// sample's *n* values (a power of two) are stratified in all
+    /// elementary intervals.
+    fn request_2d_array(&mut self, n: i32) {
         assert_eq!(self.round_count(n), n);
         self.samples_2d_array_sizes.push(n);
         let size: usize = (n * self.samples_per_pixel as i32) as usize;
         let additional_points: Vec<Point2f> = vec![Point2f::default(); size];
         self.sample_array_2d.push(additional_points);
     }
-    pub fn round_count(&self, count: i32) -> i32 {
+    fn round_count(&self, count: i32) -> i32 {
         round_up_pow2_32(count)
     }
-    pub fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return None;
         }
@@ -209,30 +237,7 @@ impl MaxMinDistSampler {
         self.array_2d_offset += 1;
         Some(&self.sample_array_2d[self.array_2d_offset - 1][start..end])
     }
-    pub fn get_2d_arrays(&mut self, n: i32) -> (Option<&[Point2f]>, Option<&[Point2f]>) {
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret1 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // repeat code from above
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret2 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // return tuple
-        (Some(ret1), Some(ret2))
-    }
-    pub fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return (true, 0_usize, 0_usize);
         }
@@ -243,7 +248,7 @@ impl MaxMinDistSampler {
         self.array_2d_offset += 1;
         (false, idx, start)
     }
-    pub fn start_next_sample(&mut self) -> bool {
+    fn start_next_sample(&mut self) -> bool {
         self.current_1d_dimension = 0_i32;
         self.current_2d_dimension = 0_i32;
         // Sampler::StartNextSample()
@@ -253,16 +258,17 @@ impl MaxMinDistSampler {
         self.current_pixel_sample_index += 1_i64;
         self.current_pixel_sample_index < self.samples_per_pixel
     }
-    pub fn reseed(&mut self, seed: u64) {
-        self.rng.set_sequence(seed);
+    fn reseed(&mut self, seed: u64) {
+        self.rng
+            .set_sequence(frame_seed(seed ^ self.base_seed, self.frame_index));
     }
-    pub fn get_current_pixel(&self) -> Point2i {
+    fn get_current_pixel(&self) -> Point2i {
         self.current_pixel
     }
-    pub fn get_current_sample_number(&self) -> i64 {
+    fn get_current_sample_number(&self) -> i64 {
         self.current_pixel_sample_index
     }
-    pub fn get_samples_per_pixel(&self) -> i64 {
+    fn get_samples_per_pixel(&self) -> i64 {
         self.samples_per_pixel
     }
 }
@@ -2,6 +2,9 @@
 //! samplers but also provides some common functionality for use by
 //! **Sampler** implementations.
 //!
+//! - AdaptiveSampler
+//! - BlockSampler
+//! - CmjSampler
 //! - HaltonSampler
 //! - MaxMinDistSampler
 //! - RandomSampler
@@ -9,6 +12,29 @@
 //! - StratifiedSampler
 //! - ZeroTwoSequenceSampler
 //!
+//! ## Adaptive Sampler
+//!
+//! The Adaptive Sampler wraps another sampler and stops sampling a
+//! pixel as soon as the standard error of its luminance drops below
+//! *"maxerror"* (relative to the mean) or *"maxabsoluteerror"*, but
+//! only after *"minsamples"* samples. The samples per pixel of the
+//! wrapped sampler (named by *"sampler"*) are the maximum.
+//!
+//! ## Block Sampler
+//!
+//! The Block Sampler generates one stratified sample set per block of
+//! *"blocksize" x "blocksize"* pixels and applies a per-pixel
+//! Cranley-Patterson rotation to it. Neighboring pixels are
+//! correlated, which helps denoisers, and the stratified set is only
+//! computed once per block.
+//!
+//! ## Correlated Multi-Jittered Sampler
+//!
+//! The CMJ Sampler (after Kensler) combines a 2D jittered
+//! stratification with well stratified 1D projections. Each sample is
+//! computed on the fly from the sample index and a per-pixel pattern
+//! seed, which keeps memory constant even for high sample counts.
+//!
 //! ## Halton Sampler
 //!
 //! The Halton Sampler generates not only points that are guaranteed
@@ -36,6 +62,10 @@
 //!
 //! ![sobol](/doc/img/cornell_box_pbrt_rust_sobol.png)
 //!
+//! The **"sobol_pbrtcompat"** sampler generates exactly the sample
+//! values of PBRT-v3's Sobol sampler, which allows to validate images
+//! against reference renders of the C++ version.
+//!
 //! ## (0,2)-Sequence Sampler
 //!
 //! Certain low-discrepancy sequences allow us to satisfy two
@@ -49,6 +79,9 @@
 //! ![lowdiscrepancy](/doc/img/cornell_box_pbrt_rust_lowdiscrepancy.png)
 //!
 
+pub mod adaptive;
+pub mod block;
+pub mod cmj;
 pub mod halton;
 pub mod maxmin;
 pub mod random;
@@ -3,13 +3,17 @@ use crate::core::geometry::{Point2f, Point2i};
 use crate::core::paramset::ParamSet;
 use crate::core::pbrt::Float;
 use crate::core::rng::Rng;
-use crate::core::sampler::Sampler;
+use crate::core::sampler::{
+    frame_index_from_params, frame_seed, seed_from_params, Sampler, SamplerTrait,
+};
 
 // see random.h
 
 pub struct RandomSampler {
     pub samples_per_pixel: i64,
     pub rng: Rng,
+    pub frame_index: u64,
+    pub base_seed: u64,
     // inherited from class Sampler (see sampler.h)
     pub current_pixel: Point2i,
     pub current_pixel_sample_index: i64,
@@ -25,7 +29,9 @@ impl RandomSampler {
     pub fn new(samples_per_pixel: i64) -> Self {
         RandomSampler {
             samples_per_pixel,
-            rng: Rng::default(),
+            rng: Rng::new(),
+            frame_index: 0_u64,
+            base_seed: 0_u64,
             current_pixel: Point2i::default(),
             current_pixel_sample_index: 0_i64,
             samples_1d_array_sizes: Vec::new(),
@@ -38,7 +44,11 @@ impl RandomSampler {
     }
     pub fn clone_with_seed(&self, seed: u64) -> Box<Sampler> {
         let mut random_sampler = RandomSampler::new(self.samples_per_pixel);
-        random_sampler.rng.set_sequence(seed);
+        random_sampler.frame_index = self.frame_index;
+        random_sampler.base_seed = self.base_seed;
+        random_sampler
+            .rng
+            .set_sequence(frame_seed(seed ^ self.base_seed, self.frame_index));
         // manually copy remaining bits
         random_sampler.current_pixel = self.current_pixel;
         random_sampler.current_pixel_sample_index = self.current_pixel_sample_index;
@@ -54,10 +64,22 @@ impl RandomSampler {
     pub fn create(params: &ParamSet) -> Box<Sampler> {
         let nsamp: i32 = params.find_one_int("pixelsamples", 4);
         // TODO: if (PbrtOptions.quickRender) nsamp = 1;
-        Box::new(Sampler::Random(RandomSampler::new(nsamp as i64)))
+        let mut sampler = RandomSampler::new(nsamp as i64);
+        sampler.frame_index = frame_index_from_params(params);
+        sampler.base_seed = seed_from_params(params);
+        Box::new(Sampler::Random(sampler))
+    }
+}
+
+impl SamplerTrait for RandomSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *RandomSampler::clone_with_seed(self, seed) {
+            Sampler::Random(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
     }
     // Sampler
-    pub fn start_pixel(&mut self, p: Point2i) {
+    fn start_pixel(&mut self, p: Point2i) {
         // TODO: ProfilePhase _(Prof::StartPixel);
         for i in 0..self.sample_array_1d.len() {
             for j in 0..self.sample_array_1d[i].len() {
@@ -78,12 +100,12 @@ impl RandomSampler {
         self.array_1d_offset = 0_usize;
         self.array_2d_offset = 0_usize;
     }
-    pub fn get_1d(&mut self) -> Float {
+    fn get_1d(&mut self) -> Float {
         // TODO: ProfilePhase _(Prof::GetSample);
         assert!(self.current_pixel_sample_index < self.samples_per_pixel);
         self.rng.uniform_float()
     }
-    pub fn get_2d(&mut self) -> Point2f {
+    fn get_2d(&mut self) -> Point2f {
         // TODO: ProfilePhase _(Prof::GetSample);
         assert!(self.current_pixel_sample_index < self.samples_per_pixel);
         // C++: call x first
@@ -91,23 +113,20 @@ impl RandomSampler {
         let y = self.rng.uniform_float();
         Point2f { x, y }
     }
-    pub fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
         self.sample_array_2d[array_idx][idx]
     }
-    pub fn reseed(&mut self, seed: u64) {
-        self.rng.set_sequence(seed);
-    }
-    pub fn request_2d_array(&mut self, n: i32) {
+    fn request_2d_array(&mut self, n: i32) {
         assert_eq!(self.round_count(n), n);
         self.samples_2d_array_sizes.push(n);
         let size: usize = (n * self.samples_per_pixel as i32) as usize;
         let additional_points: Vec<Point2f> = vec![Point2f::default(); size];
         self.sample_array_2d.push(additional_points);
     }
-    pub fn round_count(&self, count: i32) -> i32 {
+    fn round_count(&self, count: i32) -> i32 {
         count
     }
-    pub fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return None;
         }
@@ -118,30 +137,7 @@ impl RandomSampler {
         self.array_2d_offset += 1;
         Some(&self.sample_array_2d[self.array_2d_offset - 1][start..end])
     }
-    pub fn get_2d_arrays(&mut self, n: i32) -> (Option<&[Point2f]>, Option<&[Point2f]>) {
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret1 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // repeat code from above
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret2 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // return tuple
-        (Some(ret1), Some(ret2))
-    }
-    pub fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return (true, 0_usize, 0_usize);
         }
@@ -152,20 +148,23 @@ impl RandomSampler {
         self.array_2d_offset += 1;
         (false, idx, start)
     }
-    pub fn start_next_sample(&mut self) -> bool {
+    fn start_next_sample(&mut self) -> bool {
         // reset array offsets for next pixel sample
         self.array_1d_offset = 0_usize;
         self.array_2d_offset = 0_usize;
         self.current_pixel_sample_index += 1_i64;
         self.current_pixel_sample_index < self.samples_per_pixel
     }
-    pub fn get_current_pixel(&self) -> Point2i {
+    fn reseed(&mut self, seed: u64) {
+        self.rng.set_sequence(frame_seed(seed ^ self.base_seed, self.frame_index));
+    }
+    fn get_current_pixel(&self) -> Point2i {
         self.current_pixel
     }
-    pub fn get_current_sample_number(&self) -> i64 {
+    fn get_current_sample_number(&self) -> i64 {
         self.current_pixel_sample_index
     }
-    pub fn get_samples_per_pixel(&self) -> i64 {
+    fn get_samples_per_pixel(&self) -> i64 {
         self.samples_per_pixel
     }
 }
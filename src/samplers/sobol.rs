@@ -1,13 +1,14 @@
 // pbrt
 use crate::core::geometry::{Bounds2i, Point2f, Point2i, Vector2i, XYEnum};
+use crate::core::lowdiscrepancy::{owen_scramble_float, owen_scrambled_sobol_sample};
 use crate::core::lowdiscrepancy::{sobol_interval_to_index, sobol_sample};
 use crate::core::paramset::ParamSet;
-use crate::core::pbrt::Float;
 use crate::core::pbrt::{
     clamp_t, is_power_of_2, log_2_int_u32, round_up_pow2_32, round_up_pow2_64,
 };
+use crate::core::pbrt::{mix_bits, Float};
 use crate::core::rng::FLOAT_ONE_MINUS_EPSILON;
-use crate::core::sampler::Sampler;
+use crate::core::sampler::{seed_from_params, Sampler, SamplerTrait};
 use crate::core::sobolmatrices::NUM_SOBOL_DIMENSIONS;
 
 // see sobol.h
@@ -17,6 +18,10 @@ pub struct SobolSampler {
     pub sample_bounds: Bounds2i,
     pub resolution: i32,
     pub log_2_resolution: i32,
+    /// Owen scrambling of all dimensions, seeded per pixel (and
+    /// dimension), so results don't depend on the rendering order
+    pub owen_scramble: bool,
+    pub base_seed: u64,
     // inherited from class GlobalSampler (see sampler.h)
     pub dimension: i64,
     pub interval_sample_index: u64,
@@ -63,6 +68,8 @@ impl SobolSampler {
             },
             resolution,
             log_2_resolution,
+            owen_scramble: false,
+            base_seed: 0_u64,
             dimension: 0_i64,
             interval_sample_index: 0_u64,
             array_start_dim: 5_i64, // static const int arrayStartDim = 5;
@@ -83,6 +90,8 @@ impl SobolSampler {
             sample_bounds: self.sample_bounds,
             resolution: self.resolution,
             log_2_resolution: self.log_2_resolution,
+            owen_scramble: self.owen_scramble,
+            base_seed: self.base_seed,
             dimension: self.dimension,
             interval_sample_index: self.interval_sample_index,
             array_start_dim: self.array_start_dim,
@@ -101,12 +110,30 @@ impl SobolSampler {
     }
     pub fn create(params: &ParamSet, sample_bounds: &Bounds2i) -> Box<Sampler> {
         let nsamp: i32 = params.find_one_int("pixelsamples", 16);
+        // "samplesperpixel" is accepted as an alias
+        let nsamp: i32 = params.find_one_int("samplesperpixel", nsamp);
         // TODO: if (PbrtOptions.quickRender) nsamp = 1;
+        let mut sampler: SobolSampler = SobolSampler::new(nsamp as i64, sample_bounds);
+        sampler.owen_scramble = params.find_one_bool("owenscramble", true);
+        sampler.base_seed = seed_from_params(params);
+        Box::new(Sampler::Sobol(sampler))
+    }
+    /// Reproduces the sample values of PBRT-v3's **SobolSampler** (a
+    /// **GlobalSampler**) exactly: no scrambling, pixel samples in
+    /// dimensions 0 and 1 and array samples starting at dimension 5.
+    pub fn create_pbrt_compat(params: &ParamSet, sample_bounds: &Bounds2i) -> Box<Sampler> {
+        let nsamp: i32 = params.find_one_int("pixelsamples", 16);
         Box::new(Sampler::Sobol(SobolSampler::new(
             nsamp as i64,
             sample_bounds,
         )))
     }
+    /// Scramble seed for a given dimension of the current pixel.
+    pub fn pattern_seed(&self, dimension: u64) -> u32 {
+        let pixel: u64 =
+            ((self.current_pixel.x as u32 as u64) << 32) | self.current_pixel.y as u32 as u64;
+        mix_bits(pixel ^ mix_bits(self.base_seed.wrapping_add(dimension))) as u32
+    }
     pub fn get_index_for_sample(&self, sample_num: u64) -> u64 {
         let v: Vector2i = self.current_pixel - self.sample_bounds.p_min;
         sobol_interval_to_index(
@@ -122,6 +149,13 @@ impl SobolSampler {
                 NUM_SOBOL_DIMENSIONS
             );
         }
+        if self.owen_scramble && dim > 1 {
+            return owen_scrambled_sobol_sample(
+                index as i64,
+                dim as i32,
+                self.pattern_seed(dim as u64),
+            );
+        }
         let mut s: Float = sobol_sample(index as i64, dim as i32, 0_u64);
         // remap Sobol$'$ dimensions used for pixel samples
         if dim == 0 || dim == 1 {
@@ -135,11 +169,24 @@ impl SobolSampler {
                 0.0 as Float,
                 FLOAT_ONE_MINUS_EPSILON,
             );
+            if self.owen_scramble {
+                // scramble the offset within the pixel only
+                s = owen_scramble_float(s, self.pattern_seed(dim as u64));
+            }
         }
         s
     }
+}
+
+impl SamplerTrait for SobolSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *SobolSampler::clone_with_seed(self, seed) {
+            Sampler::Sobol(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
+    }
     // Sampler
-    pub fn start_pixel(&mut self, p: Point2i) {
+    fn start_pixel(&mut self, p: Point2i) {
         // TODO: ProfilePhase _(Prof::StartPixel);
         // Sampler::StartPixel(p);
         self.current_pixel = p;
@@ -177,7 +224,7 @@ impl SobolSampler {
         }
         assert!(self.array_end_dim == dim);
     }
-    pub fn get_1d(&mut self) -> Float {
+    fn get_1d(&mut self) -> Float {
         // TODO: ProfilePhase _(Prof::GetSample);
         if self.dimension >= self.array_start_dim && self.dimension < self.array_end_dim {
             self.dimension = self.array_end_dim;
@@ -188,7 +235,7 @@ impl SobolSampler {
         // then return
         ret
     }
-    pub fn get_2d(&mut self) -> Point2f {
+    fn get_2d(&mut self) -> Point2f {
         // TODO: ProfilePhase _(Prof::GetSample);
         if self.dimension + 1 >= self.array_start_dim && self.dimension < self.array_end_dim {
             self.dimension = self.array_end_dim;
@@ -200,20 +247,20 @@ impl SobolSampler {
         self.dimension += 2;
         p
     }
-    pub fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
         self.sample_array_2d[array_idx][idx]
     }
-    pub fn request_2d_array(&mut self, n: i32) {
+    fn request_2d_array(&mut self, n: i32) {
         assert_eq!(self.round_count(n), n);
         self.samples_2d_array_sizes.push(n);
         let size: usize = (n * self.samples_per_pixel as i32) as usize;
         let additional_points: Vec<Point2f> = vec![Point2f::default(); size];
         self.sample_array_2d.push(additional_points);
     }
-    pub fn round_count(&self, count: i32) -> i32 {
+    fn round_count(&self, count: i32) -> i32 {
         count
     }
-    pub fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return None;
         }
@@ -224,30 +271,7 @@ impl SobolSampler {
         self.array_2d_offset += 1;
         Some(&self.sample_array_2d[self.array_2d_offset - 1][start..end])
     }
-    pub fn get_2d_arrays(&mut self, n: i32) -> (Option<&[Point2f]>, Option<&[Point2f]>) {
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret1 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // repeat code from above
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret2 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // return tuple
-        (Some(ret1), Some(ret2))
-    }
-    pub fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return (true, 0_usize, 0_usize);
         }
@@ -258,7 +282,7 @@ impl SobolSampler {
         self.array_2d_offset += 1;
         (false, idx, start)
     }
-    pub fn start_next_sample(&mut self) -> bool {
+    fn start_next_sample(&mut self) -> bool {
         self.dimension = 0_i64;
         self.interval_sample_index =
             self.get_index_for_sample(self.current_pixel_sample_index as u64 + 1_u64);
@@ -269,20 +293,20 @@ impl SobolSampler {
         self.current_pixel_sample_index += 1_i64;
         self.current_pixel_sample_index < self.samples_per_pixel
     }
-    pub fn reseed(&mut self, _seed: u64) {
+    fn reseed(&mut self, _seed: u64) {
         // do nothing
     }
-    pub fn get_current_pixel(&self) -> Point2i {
+    fn get_current_pixel(&self) -> Point2i {
         self.current_pixel
     }
-    pub fn get_current_sample_number(&self) -> i64 {
+    fn get_current_sample_number(&self) -> i64 {
         self.current_pixel_sample_index
     }
-    pub fn get_samples_per_pixel(&self) -> i64 {
+    fn get_samples_per_pixel(&self) -> i64 {
         self.samples_per_pixel
     }
     // GlobalSampler
-    pub fn set_sample_number(&mut self, sample_num: i64) -> bool {
+    fn set_sample_number(&mut self, sample_num: i64) -> bool {
         // GlobalSampler::SetSampleNumber(...)
         self.dimension = 0_i64;
         self.interval_sample_index = self.get_index_for_sample(sample_num as u64);
@@ -1,10 +1,18 @@
+// std
+use std::sync::Arc;
 // pbrt
 use crate::core::geometry::{Point2f, Point2i};
 use crate::core::paramset::ParamSet;
 use crate::core::pbrt::Float;
 use crate::core::rng::Rng;
-use crate::core::sampler::Sampler;
-use crate::core::sampling::{latin_hypercube, shuffle, stratified_sample_1d, stratified_sample_2d};
+use crate::core::sampler::{
+    frame_index_from_params, frame_seed, seed_from_params, PixelSampleBudget, Sampler, SamplerTrait,
+};
+use crate::core::sampling::{
+    cranley_patterson_rotation, hash_offset_2d, latin_hypercube, shuffle, stratified_sample_1d,
+    stratified_sample_2d,
+};
+use crate::samplers::cmj::cmj_strata;
 
 pub struct StratifiedSampler {
     pub samples_per_pixel: i64,
@@ -17,6 +25,10 @@ pub struct StratifiedSampler {
     pub current_1d_dimension: i32,
     pub current_2d_dimension: i32,
     pub rng: Rng,
+    pub frame_index: u64,
+    pub base_seed: u64,
+    /// Optional per-pixel sample counts, overriding *x/y_pixel_samples*
+    pub pixel_budget: Option<Arc<PixelSampleBudget>>,
     // inherited from class Sampler (see sampler.h)
     pub current_pixel: Point2i,
     pub current_pixel_sample_index: i64,
@@ -35,6 +47,25 @@ impl StratifiedSampler {
         jitter_samples: bool,
         n_sampled_dimensions: i64,
     ) -> Self {
+        // a malformed scene shouldn't end in an opaque assertion later on
+        let x_pixel_samples: i32 = if x_pixel_samples < 1 {
+            println!(
+                "WARNING: xsamples = {} clamped to 1 for StratifiedSampler.",
+                x_pixel_samples
+            );
+            1
+        } else {
+            x_pixel_samples
+        };
+        let y_pixel_samples: i32 = if y_pixel_samples < 1 {
+            println!(
+                "WARNING: ysamples = {} clamped to 1 for StratifiedSampler.",
+                y_pixel_samples
+            );
+            1
+        } else {
+            y_pixel_samples
+        };
         let mut ss = StratifiedSampler {
             samples_per_pixel: (x_pixel_samples * y_pixel_samples) as i64,
             x_pixel_samples,
@@ -44,7 +75,10 @@ impl StratifiedSampler {
             samples_2d: Vec::new(),
             current_1d_dimension: 0_i32,
             current_2d_dimension: 0_i32,
-            rng: Rng::default(),
+            rng: Rng::new(),
+            frame_index: 0_u64,
+            base_seed: 0_u64,
+            pixel_budget: None,
             current_pixel: Point2i::default(),
             current_pixel_sample_index: 0_i64,
             samples_1d_array_sizes: Vec::new(),
@@ -74,6 +108,9 @@ impl StratifiedSampler {
             current_1d_dimension: self.current_1d_dimension,
             current_2d_dimension: self.current_2d_dimension,
             rng: self.rng,
+            frame_index: self.frame_index,
+            base_seed: self.base_seed,
+            pixel_budget: self.pixel_budget.clone(),
             current_pixel: self.current_pixel,
             current_pixel_sample_index: self.current_pixel_sample_index,
             samples_1d_array_sizes: self.samples_1d_array_sizes.to_vec(),
@@ -93,13 +130,57 @@ impl StratifiedSampler {
         let ysamp: i32 = params.find_one_int("ysamples", 4);
         let sd: i32 = params.find_one_int("dimensions", 4);
         // TODO: if (PbrtOptions.quickRender) nsamp = 1;
-        Box::new(Sampler::Stratified(StratifiedSampler::new(
-            xsamp, ysamp, jitter, sd as i64,
-        )))
+        let mut sampler = StratifiedSampler::new(xsamp, ysamp, jitter, sd as i64);
+        sampler.frame_index = frame_index_from_params(params);
+        sampler.base_seed = seed_from_params(params);
+        Box::new(Sampler::Stratified(sampler))
+    }
+    /// Deterministic Cranley-Patterson rotation for a 2D dimension,
+    /// so the stratified sets of different bounces don't line up. The
+    /// first dimension (the film sample) is not rotated.
+    pub fn dimension_offset_2d(dimension: i32) -> Point2f {
+        hash_offset_2d(dimension as u64)
+    }
+    /// Switch to the sample count of pixel *p* from the pixel budget,
+    /// stratified into (almost square) *x_pixel_samples x
+    /// y_pixel_samples* strata.
+    fn set_pixel_samples(&mut self, p: Point2i) {
+        let spp: i64 = match self.pixel_budget {
+            Some(ref budget) => budget.get(p),
+            None => return,
+        };
+        let (nx, ny) = cmj_strata(spp as u32);
+        self.x_pixel_samples = nx as i32;
+        self.y_pixel_samples = ny as i32;
+        self.samples_per_pixel = spp;
+        for samples in &mut self.samples_1d {
+            samples.resize(spp as usize, 0.0 as Float);
+        }
+        for samples in &mut self.samples_2d {
+            samples.resize(spp as usize, Point2f::default());
+        }
+        for i in 0..self.sample_array_1d.len() {
+            let count: usize = self.samples_1d_array_sizes[i] as usize;
+            self.sample_array_1d[i].resize(count * spp as usize, 0.0 as Float);
+        }
+        for i in 0..self.sample_array_2d.len() {
+            let count: usize = self.samples_2d_array_sizes[i] as usize;
+            self.sample_array_2d[i].resize(count * spp as usize, Point2f::default());
+        }
+    }
+}
+
+impl SamplerTrait for StratifiedSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *StratifiedSampler::clone_with_seed(self, seed) {
+            Sampler::Stratified(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
     }
     // Sampler
-    pub fn start_pixel(&mut self, p: Point2i) {
+    fn start_pixel(&mut self, p: Point2i) {
         // TODO: ProfilePhase _(Prof::StartPixel);
+        self.set_pixel_samples(p);
         // generate single stratified samples for the pixel
         for i in 0..self.samples_1d.len() {
             let samples: &mut [Float] = self.samples_1d[i].as_mut_slice();
@@ -160,7 +241,7 @@ impl StratifiedSampler {
         self.array_1d_offset = 0_usize;
         self.array_2d_offset = 0_usize;
     }
-    pub fn get_1d(&mut self) -> Float {
+    fn get_1d(&mut self) -> Float {
         // TODO: ProfilePhase _(Prof::GetSample);
         assert!(self.current_pixel_sample_index < self.samples_per_pixel);
         if self.current_1d_dimension < self.samples_1d.len() as i32 {
@@ -172,14 +253,15 @@ impl StratifiedSampler {
             self.rng.uniform_float()
         }
     }
-    pub fn get_2d(&mut self) -> Point2f {
+    fn get_2d(&mut self) -> Point2f {
         // TODO: ProfilePhase _(Prof::GetSample);
         assert!(self.current_pixel_sample_index < self.samples_per_pixel);
         if self.current_2d_dimension < self.samples_2d.len() as i32 {
             let sample: Point2f = self.samples_2d[self.current_2d_dimension as usize]
                 [self.current_pixel_sample_index as usize];
+            let offset: Point2f = StratifiedSampler::dimension_offset_2d(self.current_2d_dimension);
             self.current_2d_dimension += 1;
-            sample
+            cranley_patterson_rotation(sample, offset)
         } else {
             // C++ call order for Point2f(rng.UniformFloat(), rng.UniformFloat());
             let y = self.rng.uniform_float();
@@ -187,20 +269,20 @@ impl StratifiedSampler {
             Point2f { x, y }
         }
     }
-    pub fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
         self.sample_array_2d[array_idx][idx]
     }
-    pub fn request_2d_array(&mut self, n: i32) {
+    fn request_2d_array(&mut self, n: i32) {
         assert_eq!(self.round_count(n), n);
         self.samples_2d_array_sizes.push(n);
         let size: usize = (n * self.samples_per_pixel as i32) as usize;
         let additional_points: Vec<Point2f> = vec![Point2f::default(); size];
         self.sample_array_2d.push(additional_points);
     }
-    pub fn round_count(&self, count: i32) -> i32 {
+    fn round_count(&self, count: i32) -> i32 {
         count
     }
-    pub fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return None;
         }
@@ -211,30 +293,7 @@ impl StratifiedSampler {
         self.array_2d_offset += 1;
         Some(&self.sample_array_2d[self.array_2d_offset - 1][start..end])
     }
-    pub fn get_2d_arrays(&mut self, n: i32) -> (Option<&[Point2f]>, Option<&[Point2f]>) {
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret1 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // repeat code from above
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret2 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // return tuple
-        (Some(ret1), Some(ret2))
-    }
-    pub fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return (true, 0_usize, 0_usize);
         }
@@ -245,7 +304,7 @@ impl StratifiedSampler {
         self.array_2d_offset += 1;
         (false, idx, start)
     }
-    pub fn start_next_sample(&mut self) -> bool {
+    fn start_next_sample(&mut self) -> bool {
         self.current_1d_dimension = 0_i32;
         self.current_2d_dimension = 0_i32;
         // Sampler::StartNextSample()
@@ -255,16 +314,29 @@ impl StratifiedSampler {
         self.current_pixel_sample_index += 1_i64;
         self.current_pixel_sample_index < self.samples_per_pixel
     }
-    pub fn reseed(&mut self, seed: u64) {
-        self.rng.set_sequence(seed);
+    fn reseed(&mut self, seed: u64) {
+        self.rng
+            .set_sequence(frame_seed(seed ^ self.base_seed, self.frame_index));
     }
-    pub fn get_current_pixel(&self) -> Point2i {
+    fn get_current_pixel(&self) -> Point2i {
         self.current_pixel
     }
-    pub fn get_current_sample_number(&self) -> i64 {
+    fn get_current_sample_number(&self) -> i64 {
         self.current_pixel_sample_index
     }
-    pub fn get_samples_per_pixel(&self) -> i64 {
+    fn get_samples_per_pixel(&self) -> i64 {
         self.samples_per_pixel
     }
+    /// Grow the number of precomputed (stratified) dimensions to at
+    /// least *n*; dimensions beyond that fall back to the RNG.
+    fn ensure_sampled_dimensions(&mut self, n: usize) {
+        while self.samples_1d.len() < n {
+            self.samples_1d
+                .push(vec![0.0; self.samples_per_pixel as usize]);
+        }
+        while self.samples_2d.len() < n {
+            self.samples_2d
+                .push(vec![Point2f::default(); self.samples_per_pixel as usize]);
+        }
+    }
 }
@@ -2,13 +2,18 @@
 use crate::core::geometry::{Point2f, Point2i};
 use crate::core::lowdiscrepancy::{sobol_2d, van_der_corput};
 use crate::core::paramset::ParamSet;
-use crate::core::pbrt::round_up_pow2_32;
 use crate::core::pbrt::Float;
+use crate::core::pbrt::{is_power_of_2, mix_bits, round_up_pow2_32, round_up_pow2_64};
 use crate::core::rng::Rng;
-use crate::core::sampler::Sampler;
+use crate::core::sampler::{
+    frame_index_from_params, frame_seed, seed_from_params, Sampler, SamplerTrait,
+};
 
 // see zerotwosequence.h
 
+/// Pixel sampler ("lowdiscrepancy" or "02sequence") using scrambled
+/// van der Corput points for 1D and scrambled (0,2)-sequences for 2D
+/// samples, including sample arrays. Sample counts are powers of two.
 pub struct ZeroTwoSequenceSampler {
     pub samples_per_pixel: i64,
     pub n_sampled_dimensions: i64,
@@ -18,6 +23,8 @@ pub struct ZeroTwoSequenceSampler {
     pub current_1d_dimension: i32,
     pub current_2d_dimension: i32,
     pub rng: Rng,
+    pub frame_index: u64,
+    pub base_seed: u64,
     // inherited from class Sampler (see sampler.h)
     pub current_pixel: Point2i,
     pub current_pixel_sample_index: i64,
@@ -38,7 +45,9 @@ impl Default for ZeroTwoSequenceSampler {
             samples_2d: Vec::new(),
             current_1d_dimension: 0_i32,
             current_2d_dimension: 0_i32,
-            rng: Rng::default(),
+            rng: Rng::new(),
+            frame_index: 0_u64,
+            base_seed: 0_u64,
             current_pixel: Point2i::default(),
             current_pixel_sample_index: 0_i64,
             samples_1d_array_sizes: Vec::new(),
@@ -61,6 +70,14 @@ impl Default for ZeroTwoSequenceSampler {
 
 impl ZeroTwoSequenceSampler {
     pub fn new(samples_per_pixel: i64, n_sampled_dimensions: i64) -> Self {
+        let requested: i64 = samples_per_pixel.max(1_i64);
+        let samples_per_pixel: i64 = round_up_pow2_64(requested);
+        if !is_power_of_2(requested) {
+            println!(
+                "WARNING: Pixel samples being rounded up to power of 2 (from {} to {}).",
+                requested, samples_per_pixel
+            );
+        }
         let mut lds: ZeroTwoSequenceSampler = ZeroTwoSequenceSampler {
             samples_per_pixel,
             n_sampled_dimensions,
@@ -68,7 +85,9 @@ impl ZeroTwoSequenceSampler {
             samples_2d: Vec::new(),
             current_1d_dimension: 0_i32,
             current_2d_dimension: 0_i32,
-            rng: Rng::default(),
+            rng: Rng::new(),
+            frame_index: 0_u64,
+            base_seed: 0_u64,
             current_pixel: Point2i::default(),
             current_pixel_sample_index: 0_i64,
             samples_1d_array_sizes: Vec::new(),
@@ -96,6 +115,8 @@ impl ZeroTwoSequenceSampler {
             current_1d_dimension: self.current_1d_dimension,
             current_2d_dimension: self.current_2d_dimension,
             rng: self.rng,
+            frame_index: self.frame_index,
+            base_seed: self.base_seed,
             current_pixel: self.current_pixel,
             current_pixel_sample_index: self.current_pixel_sample_index,
             samples_1d_array_sizes: self.samples_1d_array_sizes.to_vec(),
@@ -109,18 +130,56 @@ impl ZeroTwoSequenceSampler {
         let sampler = Sampler::ZeroTwoSequence(zero_two_sampler);
         Box::new(sampler)
     }
+    /// Reads *"pixelsamples"* (rounded up to a power of two) and
+    /// *"dimensions"*.
     pub fn create(params: &ParamSet) -> Box<Sampler> {
         let nsamp: i32 = params.find_one_int("pixelsamples", 16);
         let sd: i32 = params.find_one_int("dimensions", 4);
         // TODO: if (PbrtOptions.quickRender) nsamp = 1;
-        Box::new(Sampler::ZeroTwoSequence(ZeroTwoSequenceSampler::new(
-            nsamp as i64,
-            sd as i64,
-        )))
+        let mut sampler = ZeroTwoSequenceSampler::new(nsamp as i64, sd as i64);
+        sampler.frame_index = frame_index_from_params(params);
+        sampler.base_seed = seed_from_params(params);
+        Box::new(Sampler::ZeroTwoSequence(sampler))
+    }
+    /// Requests an array of *n* (a power of two, see
+    /// **round_count()**) 1D samples per pixel sample, stratified by
+    /// a scrambled van der Corput sequence.
+    pub fn request_1d_array(&mut self, n: i32) {
+        assert_eq!(self.round_count(n), n);
+        self.samples_1d_array_sizes.push(n);
+        let size: usize = (n * self.samples_per_pixel as i32) as usize;
+        self.sample_array_1d.push(vec![0.0 as Float; size]);
+    }
+    pub fn get_1d_array(&mut self, n: i32) -> Option<&[Float]> {
+        if self.array_1d_offset == self.sample_array_1d.len() {
+            return None;
+        }
+        assert_eq!(self.samples_1d_array_sizes[self.array_1d_offset], n);
+        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
+        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
+        let end: usize = start + n as usize;
+        self.array_1d_offset += 1;
+        Some(&self.sample_array_1d[self.array_1d_offset - 1][start..end])
+    }
+}
+
+impl SamplerTrait for ZeroTwoSequenceSampler {
+    fn clone_with_seed(&self, seed: u64) -> Box<dyn SamplerTrait + Send + Sync> {
+        match *ZeroTwoSequenceSampler::clone_with_seed(self, seed) {
+            Sampler::ZeroTwoSequence(sampler) => Box::new(sampler),
+            _ => unreachable!(),
+        }
     }
     // Sampler
-    pub fn start_pixel(&mut self, p: Point2i) {
+    /// Generates all samples of pixel *p*. The scrambles are seeded
+    /// by the pixel position (and the scene seed and frame), so
+    /// neighbouring pixels get different sequences and the samples
+    /// don't depend on how the image is split into tiles.
+    fn start_pixel(&mut self, p: Point2i) {
         // TODO: ProfilePhase _(Prof::StartPixel);
+        let pixel_seed: u64 = mix_bits(((p.x as u32 as u64) << 32) | p.y as u32 as u64);
+        self.rng
+            .set_sequence(frame_seed(pixel_seed ^ self.base_seed, self.frame_index));
         // generate 1D and 2D pixel sample components using $(0,2)$-sequence
         for samples in &mut self.samples_1d {
             van_der_corput(1, self.samples_per_pixel as i32, samples, &mut self.rng);
@@ -154,7 +213,7 @@ impl ZeroTwoSequenceSampler {
         self.array_1d_offset = 0_usize;
         self.array_2d_offset = 0_usize;
     }
-    pub fn get_1d(&mut self) -> Float {
+    fn get_1d(&mut self) -> Float {
         // TODO: ProfilePhase _(Prof::GetSample);
         assert!(self.current_pixel_sample_index < self.samples_per_pixel);
         if self.current_1d_dimension < self.samples_1d.len() as i32 {
@@ -166,7 +225,7 @@ impl ZeroTwoSequenceSampler {
             self.rng.uniform_float()
         }
     }
-    pub fn get_2d(&mut self) -> Point2f {
+    fn get_2d(&mut self) -> Point2f {
         // TODO: ProfilePhase _(Prof::GetSample);
         assert!(self.current_pixel_sample_index < self.samples_per_pixel);
         if self.current_2d_dimension < self.samples_2d.len() as i32 {
@@ -181,20 +240,23 @@ impl ZeroTwoSequenceSampler {
             Point2f { x, y }
         }
     }
-    pub fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
+    fn get_2d_sample(&self, array_idx: usize, idx: usize) -> Point2f {
         self.sample_array_2d[array_idx][idx]
     }
-    pub fn request_2d_array(&mut self, n: i32) {
+    /// Requests an array of *n* (a power of two, see
+    /// **round_count()**) 2D samples per pixel sample, stratified by
+    /// a scrambled (0,2)-sequence.
+    fn request_2d_array(&mut self, n: i32) {
         assert_eq!(self.round_count(n), n);
         self.samples_2d_array_sizes.push(n);
         let size: usize = (n * self.samples_per_pixel as i32) as usize;
         let additional_points: Vec<Point2f> = vec![Point2f::default(); size];
         self.sample_array_2d.push(additional_points);
     }
-    pub fn round_count(&self, count: i32) -> i32 {
+    fn round_count(&self, count: i32) -> i32 {
         round_up_pow2_32(count)
     }
-    pub fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
+    fn get_2d_array(&mut self, n: i32) -> Option<&[Point2f]> {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return None;
         }
@@ -205,30 +267,7 @@ impl ZeroTwoSequenceSampler {
         self.array_2d_offset += 1;
         Some(&self.sample_array_2d[self.array_2d_offset - 1][start..end])
     }
-    pub fn get_2d_arrays(&mut self, n: i32) -> (Option<&[Point2f]>, Option<&[Point2f]>) {
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret1 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // repeat code from above
-        if self.array_2d_offset == self.sample_array_2d.len() {
-            return (None, None);
-        }
-        assert_eq!(self.samples_2d_array_sizes[self.array_2d_offset], n);
-        assert!(self.current_pixel_sample_index < self.samples_per_pixel);
-        let start: usize = (self.current_pixel_sample_index * n as i64) as usize;
-        let end: usize = start + n as usize;
-        self.array_2d_offset += 1;
-        let ret2 = &self.sample_array_2d[self.array_2d_offset - 1][start..end];
-        // return tuple
-        (Some(ret1), Some(ret2))
-    }
-    pub fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
+    fn get_2d_array_idxs(&mut self, n: i32) -> (bool, usize, usize) {
         if self.array_2d_offset == self.sample_array_2d.len() {
             return (true, 0_usize, 0_usize);
         }
@@ -239,7 +278,7 @@ impl ZeroTwoSequenceSampler {
         self.array_2d_offset += 1;
         (false, idx, start)
     }
-    pub fn start_next_sample(&mut self) -> bool {
+    fn start_next_sample(&mut self) -> bool {
         self.current_1d_dimension = 0_i32;
         self.current_2d_dimension = 0_i32;
         // Sampler::StartNextSample()
@@ -249,16 +288,29 @@ impl ZeroTwoSequenceSampler {
         self.current_pixel_sample_index += 1_i64;
         self.current_pixel_sample_index < self.samples_per_pixel
     }
-    pub fn reseed(&mut self, seed: u64) {
-        self.rng.set_sequence(seed);
+    fn reseed(&mut self, seed: u64) {
+        self.rng.set_sequence(frame_seed(seed ^ self.base_seed, self.frame_index));
     }
-    pub fn get_current_pixel(&self) -> Point2i {
+    fn get_current_pixel(&self) -> Point2i {
         self.current_pixel
     }
-    pub fn get_current_sample_number(&self) -> i64 {
+    fn get_current_sample_number(&self) -> i64 {
         self.current_pixel_sample_index
     }
-    pub fn get_samples_per_pixel(&self) -> i64 {
+    fn get_samples_per_pixel(&self) -> i64 {
         self.samples_per_pixel
     }
+    /// Grow the number of precomputed (low-discrepancy) dimensions to
+    /// at least *n*; dimensions beyond that fall back to the RNG.
+    fn ensure_sampled_dimensions(&mut self, n: usize) {
+        while self.samples_1d.len() < n {
+            self.samples_1d
+                .push(vec![0.0; self.samples_per_pixel as usize]);
+        }
+        while self.samples_2d.len() < n {
+            self.samples_2d
+                .push(vec![Point2f::default(); self.samples_per_pixel as usize]);
+        }
+        self.n_sampled_dimensions = self.n_sampled_dimensions.max(n as i64);
+    }
 }
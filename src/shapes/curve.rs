@@ -46,7 +46,11 @@ impl CurveCommon {
             let n1: Normal3f = norm[1].normalize();
             let normal_angle: Float =
                 clamp_t(nrm_dot_nrm(&n0, &n1), 0.0 as Float, 1.0 as Float).acos();
-            let inv_sin_normal_angle: Float = 1.0 as Float / normal_angle.sin();
+            let inv_sin_normal_angle: Float = if normal_angle > 0.0 as Float {
+                1.0 as Float / normal_angle.sin()
+            } else {
+                0.0 as Float
+            };
             CurveCommon {
                 curve_type,
                 cp_obj: [c[0], c[1], c[2], c[3]],
@@ -136,6 +140,13 @@ impl Curve {
         // TODO: curveBytes += sizeof(CurveCommon) + n_segments * sizeof(Curve);
         segments
     }
+    /// Intersects the ray (in the ray coordinate system given by
+    /// *ray_to_object*) with the sub-curve *cp*, which spans the
+    /// parametric range *u0* to *u1*. The curve is split *depth*
+    /// more times before the refined segments are tested against the
+    /// swept width. On entry *t_hit* holds the closest hit found so
+    /// far (or the ray's *t_max*), closer hits update *t_hit* and
+    /// *isect*.
     pub fn recursive_intersect(
         &self,
         ray: &Ray,
@@ -159,6 +170,7 @@ impl Curve {
             // for intersection with it.
 
             let u: [Float; 3] = [u0, (u0 + u1) / 2.0 as Float, u1];
+            let mut hit: bool = false;
             // pointer to the 4 control points for the current segment.
             for seg in 0..2 {
                 let cps: &[Point3f] = &cp_split[seg * 3..seg * 3 + 4];
@@ -184,7 +196,7 @@ impl Curve {
                     continue;
                 }
 
-                let z_max: Float = ray_length * ray.t_max;
+                let z_max: Float = ray_length * *t_hit;
                 if cps[0].z.max(cps[1].z).max(cps[2].z.max(cps[3].z)) + 0.5 as Float * max_width
                     < 0.0 as Float
                     || cps[0].z.min(cps[1].z).min(cps[2].z.min(cps[3].z)) - 0.5 as Float * max_width
@@ -203,14 +215,11 @@ impl Curve {
                     t_hit,
                     isect,
                 ) {
-                    // If we found an intersection and this is a shadow ray,
-                    // we can exit out immediately.
-                    if *t_hit == 0.0 as Float {
-                        true;
-                    }
+                    // keep looking for a closer hit in the other half
+                    hit = true;
                 }
             }
-            false
+            hit
         } else {
             // intersect ray with curve segment
 
@@ -253,12 +262,18 @@ impl Curve {
             let mut hit_width: Float = lerp(u, self.common.width[0], self.common.width[1]);
             let mut n_hit: Normal3f = Normal3f::default();
             if self.common.curve_type == CurveType::Ribbon {
-                // scale _hitWidth_ based on ribbon orientation
+                // scale _hitWidth_ based on ribbon orientation (spherical
+                // linear interpolation of the endpoint normals)
                 let sin0: Float = ((1.0 as Float - u) * self.common.normal_angle).sin()
                     * self.common.inv_sin_normal_angle;
                 let sin1: Float =
                     (u * self.common.normal_angle).sin() * self.common.inv_sin_normal_angle;
-                n_hit = self.common.n[0] * sin0 + self.common.n[1] * sin1;
+                n_hit = if self.common.normal_angle > 0.0 as Float {
+                    self.common.n[0] * sin0 + self.common.n[1] * sin1
+                } else {
+                    // parallel normals, nothing to interpolate
+                    self.common.n[0]
+                };
                 hit_width *= nrm_abs_dot_vec3(&n_hit, &ray.d) / ray_length;
             }
 
@@ -270,7 +285,7 @@ impl Curve {
             if pt_curve_dist2 > hit_width * hit_width * 0.25 as Float {
                 return false;
             }
-            let z_max: Float = ray_length * ray.t_max;
+            let z_max: Float = ray_length * *t_hit;
             if pc.z < 0.0 as Float || pc.z > z_max {
                 return false;
             }
@@ -320,8 +335,8 @@ impl Curve {
                 }
                 dpdv = ray_to_object.transform_vector(&dpdv_plane);
             }
-            let mut si: SurfaceInteraction = SurfaceInteraction::new(
-                &ray.position(pc.z),
+            *isect = SurfaceInteraction::new(
+                &ray.position(*t_hit),
                 &p_error,
                 Point2f { x: u, y: v },
                 &-ray.d,
@@ -332,7 +347,7 @@ impl Curve {
                 ray.time,
                 None,
             );
-            self.object_to_world.transform_surface_interaction(&mut si);
+            self.object_to_world.transform_surface_interaction(isect);
             // if let Some(ref shape) = si.shape {
             //     isect.shape = Some(shape.clone());
             // }
@@ -463,16 +478,22 @@ impl Curve {
             log2(1.414_213_562_37 as Float * 6.0 as Float * l0 / (8.0 as Float * eps)) / 2_i32;
         let max_depth: i32 = clamp_t(r0, 0_i32, 10_i32);
         // TODO: ReportValue(refinementLevel, maxDepth);
-        self.recursive_intersect(
+        let mut t_closest: Float = ray.t_max;
+        if self.recursive_intersect(
             &ray,
             &[cp[0], cp[1], cp[2], cp[3]],
             &Transform::inverse(&object_to_ray),
             self.u_min,
             self.u_max,
             max_depth,
-            t_hit,
+            &mut t_closest,
             isect,
-        )
+        ) {
+            *t_hit = t_closest;
+            true
+        } else {
+            false
+        }
     }
     pub fn intersect_p(&self, r: &Ray) -> bool {
         let mut t_hit: Float = 0.0;
@@ -551,6 +572,13 @@ impl Curve {
     }
 }
 
+/// Creates the curve segments described by the control points *P*.
+/// Several segments can share control points: a cubic (or
+/// quadratic) Bézier *basis* needs 4 + 3 * n (or 3 + 2 * n) points
+/// for n + 1 segments, a *bspline* basis one more point than the
+/// *degree* per segment. The width is interpolated from *width0* to
+/// *width1* over all segments, *ribbon* curves need one normal *N*
+/// per segment endpoint.
 pub fn create_curve_shape(
     o2w: &Transform,
     w2o: &Transform,
@@ -560,12 +588,52 @@ pub fn create_curve_shape(
     let width: Float = params.find_one_float("width", 1.0 as Float);
     let width0: Float = params.find_one_float("width0", width);
     let width1: Float = params.find_one_float("width1", width);
-    let cp = params.find_point3f("P");
-    if cp.len() != 4_usize {
-        panic!(
-            "Must provide 4 control points for \"curve\" primitive. ((Provided {:?}).",
-            cp.len()
+    let degree: i32 = params.find_one_int("degree", 3_i32);
+    if degree != 2 && degree != 3 {
+        println!(
+            "ERROR: Invalid degree {}: only degree 2 and 3 curves are supported.",
+            degree
         );
+        return Vec::new();
+    }
+    let degree: usize = degree as usize;
+    let basis: String = params.find_one_string("basis", String::from("bezier"));
+    if basis != "bezier" && basis != "bspline" {
+        println!(
+            "ERROR: Invalid basis {:?}: only \"bezier\" and \"bspline\" are supported.",
+            basis
+        );
+        return Vec::new();
+    }
+    let cp: Vec<Point3f> = params.find_point3f("P");
+    let n_segments: usize;
+    if basis == "bezier" {
+        // after the first segment, which uses degree+1 control
+        // points, subsequent segments reuse the last control point
+        // of the previous one and then use degree more control
+        // points
+        if cp.len() <= degree || (cp.len() - 1 - degree) % degree != 0 {
+            println!(
+                "ERROR: Invalid number of control points {}: for the degree {} Bezier basis {} + n * {} are required, for n >= 0.",
+                cp.len(),
+                degree,
+                degree + 1,
+                degree
+            );
+            return Vec::new();
+        }
+        n_segments = (cp.len() - 1) / degree;
+    } else {
+        if cp.len() < degree + 1 {
+            println!(
+                "ERROR: Invalid number of control points {}: for the degree {} b-spline basis, must have >= {}.",
+                cp.len(),
+                degree,
+                degree + 1
+            );
+            return Vec::new();
+        }
+        n_segments = cp.len() - degree;
     }
     let curve_type_string: String = params.find_one_string("type", String::from("flat"));
     let mut curve_type: CurveType = CurveType::Flat;
@@ -583,45 +651,95 @@ pub fn create_curve_shape(
     }
     let mut n: Vec<Normal3f> = params.find_normal3f("N");
     if !n.is_empty() {
-        if curve_type_string != "ribbon" {
+        if curve_type != CurveType::Ribbon {
             println!("WARNING: Curve normals are only used with \"ribbon\" type curves.");
             n = Vec::new();
-        } else if n.len() != 2_usize {
-            panic!(
-                "Must provide two normals with \"N\" parameter for ribbon curves. (Provided {:?}).",
-                n.len()
+        } else if n.len() != n_segments + 1 {
+            println!(
+                "ERROR: Invalid number of normals {}: must provide {} normals for ribbon curves with {} segments.",
+                n.len(),
+                n_segments + 1,
+                n_segments
             );
+            return Vec::new();
         }
     }
-    let sd: i32 = params.find_one_int("splitdepth", 3_i32);
     if curve_type == CurveType::Ribbon && n.is_empty() {
-        panic!("Must provide normals \"N\" at curve endpoints with ribbon curves.");
+        println!("ERROR: Must provide normals \"N\" at curve endpoints with ribbon curves.");
+        return Vec::new();
     }
-    if n.is_empty() {
-        Curve::create(
-            *o2w,
-            *w2o,
-            reverse_orientation,
-            &[cp[0], cp[1], cp[2], cp[3]],
-            width0,
-            width1,
-            curve_type,
-            None,
-            sd,
-        )
-    } else {
-        Curve::create(
+    let sd: i32 = params.find_one_int("splitdepth", 3_i32);
+    let mut curves: Vec<Arc<Shape>> = Vec::new();
+    for seg in 0..n_segments {
+        // compute cubic Bezier control points for the segment
+        let seg_cp_bezier: [Point3f; 4] = if basis == "bezier" {
+            if degree == 2 {
+                // elevate to degree 3
+                [
+                    cp[2 * seg],
+                    pnt3_lerp(2.0 as Float / 3.0 as Float, &cp[2 * seg], &cp[2 * seg + 1]),
+                    pnt3_lerp(
+                        1.0 as Float / 3.0 as Float,
+                        &cp[2 * seg + 1],
+                        &cp[2 * seg + 2],
+                    ),
+                    cp[2 * seg + 2],
+                ]
+            } else {
+                [
+                    cp[3 * seg],
+                    cp[3 * seg + 1],
+                    cp[3 * seg + 2],
+                    cp[3 * seg + 3],
+                ]
+            }
+        } else if degree == 2 {
+            // convert the quadratic b-spline segment to Bezier form
+            let p01: Point3f = cp[seg];
+            let p12: Point3f = cp[seg + 1];
+            let p23: Point3f = cp[seg + 2];
+            let p11: Point3f = pnt3_lerp(0.5 as Float, &p01, &p12);
+            let p22: Point3f = pnt3_lerp(0.5 as Float, &p12, &p23);
+            [
+                p11,
+                pnt3_lerp(2.0 as Float / 3.0 as Float, &p11, &p12),
+                pnt3_lerp(1.0 as Float / 3.0 as Float, &p12, &p22),
+                p22,
+            ]
+        } else {
+            // convert the cubic b-spline segment to Bezier form
+            let p012: Point3f = cp[seg];
+            let p123: Point3f = cp[seg + 1];
+            let p234: Point3f = cp[seg + 2];
+            let p345: Point3f = cp[seg + 3];
+            let p122: Point3f = pnt3_lerp(2.0 as Float / 3.0 as Float, &p012, &p123);
+            let p223: Point3f = pnt3_lerp(1.0 as Float / 3.0 as Float, &p123, &p234);
+            let p233: Point3f = pnt3_lerp(2.0 as Float / 3.0 as Float, &p123, &p234);
+            let p334: Point3f = pnt3_lerp(1.0 as Float / 3.0 as Float, &p234, &p345);
+            let p222: Point3f = pnt3_lerp(0.5 as Float, &p122, &p223);
+            let p333: Point3f = pnt3_lerp(0.5 as Float, &p233, &p334);
+            [p222, p223, p233, p333]
+        };
+        let w0: Float = lerp(seg as Float / n_segments as Float, width0, width1);
+        let w1: Float = lerp((seg + 1) as Float / n_segments as Float, width0, width1);
+        let norm: Option<[Normal3f; 2]> = if n.is_empty() {
+            None
+        } else {
+            Some([n[seg], n[seg + 1]])
+        };
+        curves.extend(Curve::create(
             *o2w,
             *w2o,
             reverse_orientation,
-            &[cp[0], cp[1], cp[2], cp[3]],
-            width0,
-            width1,
-            curve_type,
-            Some([n[0], n[1]]),
+            &seg_cp_bezier,
+            w0,
+            w1,
+            curve_type.clone(),
+            norm,
             sd,
-        )
+        ));
     }
+    curves
 }
 
 // Curve Utility Functions
@@ -672,6 +790,6 @@ fn log2(v: Float) -> i32 {
 
     // (With an additional add so get round-to-nearest rather than
     // round down.)
-    let one_or_zero = if (1 << 22) > 0 { 1_i32 } else { 0_i32 };
-    (bits >> 23) - 127 + (bits & one_or_zero)
+    let one_or_zero = if bits & (1 << 22) != 0 { 1_i32 } else { 0_i32 };
+    (bits >> 23) - 127 + one_or_zero
 }
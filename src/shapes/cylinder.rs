@@ -10,8 +10,10 @@ use crate::core::geometry::{
 use crate::core::geometry::{Bounds3f, Normal3f, Point2f, Point3f, Ray, Vector3f, XYEnum};
 use crate::core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
 use crate::core::material::Material;
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::Float;
 use crate::core::pbrt::{clamp_t, gamma, lerp, radians};
+use crate::core::shape::Shape;
 use crate::core::transform::Transform;
 
 // see cylinder.h
@@ -73,6 +75,28 @@ impl Cylinder {
             material: None,
         }
     }
+    /// Creates a cylinder from the *radius*, *zmin*, *zmax* and
+    /// *phimax* (in degrees) parameters.
+    pub fn create(
+        o2w: &Transform,
+        w2o: &Transform,
+        reverse_orientation: bool,
+        params: &ParamSet,
+    ) -> Arc<Shape> {
+        let radius: Float = params.find_one_float("radius", 1.0);
+        let z_min: Float = params.find_one_float("zmin", -1.0);
+        let z_max: Float = params.find_one_float("zmax", 1.0);
+        let phi_max: Float = params.find_one_float("phimax", 360.0);
+        Arc::new(Shape::Clndr(Cylinder::new(
+            *o2w,
+            *w2o,
+            reverse_orientation,
+            radius,
+            z_min,
+            z_max,
+            phi_max,
+        )))
+    }
     // Shape
     pub fn object_bound(&self) -> Bounds3f {
         Bounds3f {
@@ -114,6 +138,10 @@ impl Cylinder {
         let b: EFloat = (dx * ox + dy * oy) * 2.0f32;
         let c: EFloat = ox * ox + oy * oy
             - EFloat::new(self.radius as f32, 0.0) * EFloat::new(self.radius as f32, 0.0);
+        // rays parallel to the cylinder's axis never hit its surface
+        if a.v == 0.0f32 {
+            return false;
+        }
 
         // Solve quadratic equation for _t_ values
         let mut t0: EFloat = EFloat::default();
@@ -233,6 +261,11 @@ impl Cylinder {
         *isect = SurfaceInteraction::new(
             &p_hit, &p_error, uv_hit, &wo, &dpdu, &dpdv, &dndu, &dndv, ray.time, None,
         );
+        // adjust normal based on orientation and handedness
+        if self.reverse_orientation ^ self.transform_swaps_handedness {
+            isect.common.n *= -1.0 as Float;
+            isect.shading.n *= -1.0 as Float;
+        }
         self.object_to_world.transform_surface_interaction(isect);
         // if let Some(ref shape) = si.shape {
         //     isect.shape = Some(shape.clone());
@@ -265,6 +298,10 @@ impl Cylinder {
         let b: EFloat = (dx * ox + dy * oy) * 2.0f32;
         let c: EFloat = ox * ox + oy * oy
             - EFloat::new(self.radius as f32, 0.0) * EFloat::new(self.radius as f32, 0.0);
+        // rays parallel to the cylinder's axis never hit its surface
+        if a.v == 0.0f32 {
+            return false;
+        }
 
         // Solve quadratic equation for _t_ values
         let mut t0: EFloat = EFloat::default();
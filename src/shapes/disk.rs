@@ -6,9 +6,11 @@ use crate::core::geometry::{nrm_abs_dot_vec3, pnt3_distance_squared};
 use crate::core::geometry::{Bounds3f, Normal3f, Point2f, Point3f, Ray, Vector3f};
 use crate::core::interaction::{Interaction, InteractionCommon, SurfaceInteraction};
 use crate::core::material::Material;
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::Float;
-use crate::core::pbrt::{clamp_t, radians};
+use crate::core::pbrt::{clamp_t, lerp, radians};
 use crate::core::sampling::concentric_sample_disk;
+use crate::core::shape::Shape;
 use crate::core::transform::Transform;
 
 // see disk.h
@@ -70,6 +72,28 @@ impl Disk {
             material: None,
         }
     }
+    /// Creates a disk from the *height*, *radius*, *innerradius* and
+    /// *phimax* (in degrees) parameters.
+    pub fn create(
+        o2w: &Transform,
+        w2o: &Transform,
+        reverse_orientation: bool,
+        params: &ParamSet,
+    ) -> Arc<Shape> {
+        let height: Float = params.find_one_float("height", 0.0);
+        let radius: Float = params.find_one_float("radius", 1.0);
+        let inner_radius: Float = params.find_one_float("innerradius", 0.0);
+        let phi_max: Float = params.find_one_float("phimax", 360.0);
+        Arc::new(Shape::Dsk(Disk::new(
+            *o2w,
+            *w2o,
+            reverse_orientation,
+            height,
+            radius,
+            inner_radius,
+            phi_max,
+        )))
+    }
     // Shape
     pub fn object_bound(&self) -> Bounds3f {
         Bounds3f {
@@ -114,8 +138,12 @@ impl Disk {
         if dist2 > self.radius * self.radius || dist2 < self.inner_radius * self.inner_radius {
             return false;
         }
-        // test disk $\phi$ value against $\phimax$
-        let mut phi: Float = p_hit.y.atan2(p_hit.x);
+        // test disk $\phi$ value against $\phimax$ ($\phi = 0$ at the center)
+        let mut phi: Float = if dist2 == 0.0 {
+            0.0
+        } else {
+            p_hit.y.atan2(p_hit.x)
+        };
         if phi < 0.0 {
             phi += 2.0_f32 * PI;
         }
@@ -127,17 +155,24 @@ impl Disk {
         let r_hit: Float = dist2.sqrt();
         let one_minus_v: Float = (r_hit - self.inner_radius) / (self.radius - self.inner_radius);
         let v: Float = 1.0 - one_minus_v;
+        // the derivatives vanish at the center, use the ones along
+        // $\phi = 0$ (at unit distance) there
+        let (dir_x, dir_y, dir_len): (Float, Float, Float) = if dist2 == 0.0 {
+            (1.0, 0.0, 1.0)
+        } else {
+            (p_hit.x, p_hit.y, r_hit)
+        };
         let dpdu: Vector3f = Vector3f {
-            x: -self.phi_max * p_hit.y,
-            y: self.phi_max * p_hit.x,
+            x: -self.phi_max * dir_y,
+            y: self.phi_max * dir_x,
             z: 0.0,
         };
         let dpdv: Vector3f = Vector3f {
-            x: p_hit.x,
-            y: p_hit.y,
+            x: dir_x,
+            y: dir_y,
             z: 0.0,
         } * (self.inner_radius - self.radius)
-            / r_hit;
+            / dir_len;
         let dndu: Normal3f = Normal3f::default();
         let dndv: Normal3f = Normal3f::default();
         // refine disk intersection point
@@ -150,6 +185,11 @@ impl Disk {
         *isect = SurfaceInteraction::new(
             &p_hit, &p_error, uv_hit, &wo, &dpdu, &dpdv, &dndu, &dndv, ray.time, None,
         );
+        // adjust normal based on orientation and handedness
+        if self.reverse_orientation ^ self.transform_swaps_handedness {
+            isect.common.n *= -1.0 as Float;
+            isect.shading.n *= -1.0 as Float;
+        }
         self.object_to_world.transform_surface_interaction(isect);
         // if let Some(ref shape) = si.shape {
         //     isect.shape = Some(shape.clone());
@@ -185,8 +225,12 @@ impl Disk {
         if dist2 > self.radius * self.radius || dist2 < self.inner_radius * self.inner_radius {
             return false;
         }
-        // test disk $\phi$ value against $\phimax$
-        let mut phi: Float = p_hit.y.atan2(p_hit.x);
+        // test disk $\phi$ value against $\phimax$ ($\phi = 0$ at the center)
+        let mut phi: Float = if dist2 == 0.0 {
+            0.0
+        } else {
+            p_hit.y.atan2(p_hit.x)
+        };
         if phi < 0.0 {
             phi += 2.0_f32 * PI;
         }
@@ -210,12 +254,30 @@ impl Disk {
             * (self.radius * self.radius - self.inner_radius * self.inner_radius)
     }
     pub fn sample(&self, u: Point2f, pdf: &mut Float) -> InteractionCommon {
-        let pd: Point2f = concentric_sample_disk(u);
-        let p_obj: Point3f = Point3f {
-            x: pd.x * self.radius,
-            y: pd.y * self.radius,
-            z: self.height,
-        };
+        let p_obj: Point3f =
+            if self.inner_radius == 0.0 as Float && self.phi_max >= radians(360.0 as Float) {
+                // full disk
+                let pd: Point2f = concentric_sample_disk(u);
+                Point3f {
+                    x: pd.x * self.radius,
+                    y: pd.y * self.radius,
+                    z: self.height,
+                }
+            } else {
+                // uniformly distributed over the area of the partial annulus
+                let r: Float = lerp(
+                    u.x,
+                    self.inner_radius * self.inner_radius,
+                    self.radius * self.radius,
+                )
+                .sqrt();
+                let phi: Float = u.y * self.phi_max;
+                Point3f {
+                    x: r * phi.cos(),
+                    y: r * phi.sin(),
+                    z: self.height,
+                }
+            };
         let mut it: InteractionCommon = InteractionCommon::default();
         it.n = self
             .object_to_world
@@ -263,7 +325,7 @@ impl Disk {
         // performing this intersection. Hack for the "San Miguel"
         // scene, where this is used to make an invisible area light.
         let mut t_hit: Float = 0.0;
-        let mut isect_light: SurfaceInteraction = SurfaceInteraction::default(); 
+        let mut isect_light: SurfaceInteraction = SurfaceInteraction::default();
         if self.intersect(&ray, &mut t_hit, &mut isect_light) {
             // convert light sample weight to solid angle measure
             let mut pdf: Float = pnt3_distance_squared(&iref.get_p(), &isect_light.common.p)
@@ -12,14 +12,23 @@ use std::vec::Vec;
 use ply_rs::parser;
 use ply_rs::ply;
 // pbrt
-use crate::core::geometry::{Normal3f, Point2f, Point3f, Vector3f};
+use crate::core::geometry::{Normal3f, Point2f, Point3f};
 use crate::core::paramset::ParamSet;
 use crate::core::pbrt::Float;
 use crate::core::shape::Shape;
 use crate::core::texture::Texture;
 use crate::core::transform::Transform;
-use crate::shapes::triangle::{Triangle, TriangleMesh};
-use crate::textures::constant::ConstantTexture;
+use crate::shapes::triangle::{create_triangles, find_alpha_textures};
+
+/// Reads a (vertex) property as **Float**, no matter if it was
+/// stored with single or double precision.
+fn property_to_float(property: &ply::Property) -> Option<Float> {
+    match property {
+        ply::Property::Double(value) => Some(*value as Float),
+        ply::Property::Float(value) => Some(*value),
+        _ => None,
+    }
+}
 
 pub fn create_ply_mesh<S: BuildHasher>(
     o2w: &Transform,
@@ -73,53 +82,52 @@ pub fn create_ply_mesh<S: BuildHasher>(
                     for (name2, list2) in elem.into_iter() {
                         match name2.as_ref() {
                             "x" => {
-                                if let ply::Property::Float(x) = list2 {
+                                if let Some(x) = property_to_float(&list2) {
                                     pnt.x = x;
                                 }
                             }
                             "y" => {
-                                if let ply::Property::Float(y) = list2 {
+                                if let Some(y) = property_to_float(&list2) {
                                     pnt.y = y;
                                 }
                             }
                             "z" => {
-                                if let ply::Property::Float(z) = list2 {
+                                if let Some(z) = property_to_float(&list2) {
                                     pnt.z = z;
                                 }
                             }
                             "nx" => {
                                 has_normals = true;
-                                if let ply::Property::Float(x) = list2 {
+                                if let Some(x) = property_to_float(&list2) {
                                     nrm.x = x;
                                 }
                             }
                             "ny" => {
                                 has_normals = true;
-                                if let ply::Property::Float(y) = list2 {
+                                if let Some(y) = property_to_float(&list2) {
                                     nrm.y = y;
                                 }
                             }
                             "nz" => {
                                 has_normals = true;
-                                if let ply::Property::Float(z) = list2 {
+                                if let Some(z) = property_to_float(&list2) {
                                     nrm.z = z;
                                 }
                             }
                             "u" | "s" => {
                                 has_uvs = true;
-                                if let ply::Property::Float(x) = list2 {
+                                if let Some(x) = property_to_float(&list2) {
                                     pt2.x = x;
                                 }
                             }
                             "v" | "t" => {
                                 has_uvs = true;
-                                if let ply::Property::Float(y) = list2 {
+                                if let Some(y) = property_to_float(&list2) {
                                     pt2.y = y;
                                 }
                             }
                             _ => {
-                                println!("name2 = {:?}", name2);
-                                unreachable!();
+                                // ignore other vertex properties (e.g. colors)
                             }
                         }
                     }
@@ -134,10 +142,9 @@ pub fn create_ply_mesh<S: BuildHasher>(
             }
             "face" => {
                 for elem in list.into_iter() {
-                    let mut nrm: Normal3f = Normal3f::default();
                     for (name2, list2) in elem.into_iter() {
                         match name2.as_ref() {
-                            "vertex_indices" => {
+                            "vertex_indices" | "vertex_index" => {
                                 if let ply::Property::ListInt(li) = list2 {
                                     let mut vertex_indices: Vec<usize> = Vec::new();
                                     for i in li.into_iter() {
@@ -188,109 +195,33 @@ pub fn create_ply_mesh<S: BuildHasher>(
                                     }
                                 }
                             }
-                            "nx" => {
-                                has_normals = true;
-                                if let ply::Property::Float(x) = list2 {
-                                    nrm.x = x;
-                                }
-                            }
-                            "ny" => {
-                                has_normals = true;
-                                if let ply::Property::Float(y) = list2 {
-                                    nrm.y = y;
-                                }
-                            }
-                            "nz" => {
-                                has_normals = true;
-                                if let ply::Property::Float(z) = list2 {
-                                    nrm.z = z;
-                                }
+                            _ => {
+                                // ignore other face properties (e.g. face normals)
                             }
-                            _ => unreachable!(),
                         }
                     }
                 }
             }
-            _ => unreachable!(),
-        }
-    }
-    // for i in 0..p.len() {
-    //     println!("{:?}: {:?}", i, p[i]);
-    // }
-    // println!("tm_vertex_indices = {:?}", tm_vertex_indices);
-    let mut n_ws: Vec<Normal3f> = Vec::new();
-    if !n.is_empty() {
-        assert!(n.len() == p.len());
-        // transform normals to world space
-        let n_normals: usize = n.len();
-        for item in n.iter().take(n_normals) {
-            n_ws.push(o2w.transform_normal(item));
-        }
-    }
-    // transform mesh vertices to world space
-    let mut p_ws: Vec<Point3f> = Vec::new();
-    let n_vertices: usize = p.len();
-    for item in p.iter().take(n_vertices) {
-        p_ws.push(o2w.transform_point(item));
-    }
-    let s_ws: Vec<Vector3f> = Vec::new();
-    // look up an alpha texture, if applicable
-    let mut alpha_tex: Option<Arc<dyn Texture<Float> + Send + Sync>> = None;
-    let alpha_tex_name: String = params.find_texture("alpha");
-    if alpha_tex_name != "" {
-        alpha_tex = match float_textures.get(alpha_tex_name.as_str()) {
-            Some(float_texture) => Some(float_texture.clone()),
-            None => {
+            _ => {
                 println!(
-                    "Couldn't find float texture {:?} for \"alpha\" parameter",
-                    alpha_tex_name.as_str()
+                    "WARNING: plymesh: Ignoring {} elements of type {:?}",
+                    list.len(),
+                    name
                 );
-                None
             }
         }
-    } else if params.find_one_float("alpha", 1.0 as Float) == 0.0 as Float {
-        alpha_tex = Some(Arc::new(ConstantTexture::new(0.0 as Float)));
     }
-    let mut shadow_alpha_tex: Option<Arc<dyn Texture<Float> + Send + Sync>> = None;
-    let shadow_alpha_tex_name: String = params.find_texture("shadowalpha");
-    if shadow_alpha_tex_name != "" {
-        shadow_alpha_tex = match float_textures.get(shadow_alpha_tex_name.as_str()) {
-            Some(float_texture) => Some(float_texture.clone()),
-            None => {
-                println!(
-                    "Couldn't find float texture {:?} for \"shadowalpha\" parameter",
-                    shadow_alpha_tex_name.as_str()
-                );
-                None
-            }
-        }
-    } else if params.find_one_float("shadowalpha", 1.0 as Float) == 0.0 as Float {
-        shadow_alpha_tex = Some(Arc::new(ConstantTexture::new(0.0 as Float)));
-    }
-    let mesh = Arc::new(TriangleMesh::new(
-        *o2w,
-        *w2o,
+    let (alpha_tex, shadow_alpha_tex) = find_alpha_textures(params, &float_textures);
+    create_triangles(
+        o2w,
+        w2o,
         reverse_orientation,
-        (tm_vertex_indices.len() / 3).try_into().unwrap(), // n_triangles
         tm_vertex_indices,
-        n_vertices.try_into().unwrap(),
-        p_ws, // in world space
-        s_ws, // in world space
-        n_ws, // in world space
+        &p,
+        &[],
+        &n,
         uvs,
         alpha_tex,
         shadow_alpha_tex,
-    ));
-    let mut shapes: Vec<Arc<Shape>> = Vec::new();
-    for id in 0..mesh.n_triangles {
-        let triangle = Arc::new(Shape::Trngl(Triangle::new(
-            mesh.object_to_world,
-            mesh.world_to_object,
-            mesh.transform_swaps_handedness,
-            mesh.clone(),
-            id.try_into().unwrap(),
-        )));
-        shapes.push(triangle.clone());
-    }
-    shapes
+    )
 }
@@ -1,5 +1,8 @@
 // std
 use std::cell::Cell;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::hash::BuildHasher;
 use std::mem;
 use std::sync::Arc;
 // pbrt
@@ -13,11 +16,14 @@ use crate::core::geometry::{
 };
 use crate::core::interaction::{Interaction, InteractionCommon, Shading, SurfaceInteraction};
 use crate::core::material::Material;
+use crate::core::paramset::ParamSet;
 use crate::core::pbrt::gamma;
 use crate::core::pbrt::Float;
 use crate::core::sampling::uniform_sample_triangle;
+use crate::core::shape::Shape;
 use crate::core::texture::Texture;
 use crate::core::transform::Transform;
+use crate::textures::constant::ConstantTexture;
 
 // see triangle.h
 
@@ -107,7 +113,7 @@ impl Triangle {
             object_to_world,
             world_to_object,
             reverse_orientation,
-            transform_swaps_handedness: false,
+            transform_swaps_handedness: object_to_world.swaps_handedness(),
             material: None,
         }
     }
@@ -353,6 +359,9 @@ impl Triangle {
         // override surface normal in _isect_ for triangle
         let mut surface_normal: Normal3f =
             Normal3f::from(vec3_cross_vec3(&dp02, &dp12).normalize());
+        if self.reverse_orientation ^ self.transform_swaps_handedness {
+            surface_normal = -surface_normal;
+        }
         let mut shading: Shading = Shading {
             n: surface_normal,
             dpdu,
@@ -378,7 +387,8 @@ impl Triangle {
             } else {
                 ns = surface_normal;
             }
-            // compute shading tangent _ss_ for triangle
+            // compute shading tangent _ss_ for triangle (without
+            // tangents use the direction of the $u$ gradient)
             let mut ss: Vector3f;
             if !self.mesh.s.is_empty() {
                 let s0 = self.mesh.s[self.mesh.vertex_indices[(self.id * 3) as usize] as usize];
@@ -764,3 +774,195 @@ impl Triangle {
         }
     }
 }
+
+/// Looks up the (optional) *alpha* and *shadowalpha* textures used
+/// to cut away parts of a triangle mesh.
+pub fn find_alpha_textures<S: BuildHasher>(
+    params: &ParamSet,
+    float_textures: &HashMap<String, Arc<dyn Texture<Float> + Send + Sync>, S>,
+) -> (
+    Option<Arc<dyn Texture<Float> + Send + Sync>>,
+    Option<Arc<dyn Texture<Float> + Send + Sync>>,
+) {
+    let mut alpha_tex: Option<Arc<dyn Texture<Float> + Send + Sync>> = None;
+    let alpha_tex_name: String = params.find_texture("alpha");
+    if !alpha_tex_name.is_empty() {
+        alpha_tex = match float_textures.get(alpha_tex_name.as_str()) {
+            Some(float_texture) => Some(float_texture.clone()),
+            None => {
+                println!(
+                    "Couldn't find float texture {:?} for \"alpha\" parameter",
+                    alpha_tex_name.as_str()
+                );
+                None
+            }
+        }
+    } else if params.find_one_float("alpha", 1.0 as Float) == 0.0 as Float {
+        alpha_tex = Some(Arc::new(ConstantTexture::new(0.0 as Float)));
+    }
+    let mut shadow_alpha_tex: Option<Arc<dyn Texture<Float> + Send + Sync>> = None;
+    let shadow_alpha_tex_name: String = params.find_texture("shadowalpha");
+    if !shadow_alpha_tex_name.is_empty() {
+        shadow_alpha_tex = match float_textures.get(shadow_alpha_tex_name.as_str()) {
+            Some(float_texture) => Some(float_texture.clone()),
+            None => {
+                println!(
+                    "Couldn't find float texture {:?} for \"shadowalpha\" parameter",
+                    shadow_alpha_tex_name.as_str()
+                );
+                None
+            }
+        }
+    } else if params.find_one_float("shadowalpha", 1.0 as Float) == 0.0 as Float {
+        shadow_alpha_tex = Some(Arc::new(ConstantTexture::new(0.0 as Float)));
+    }
+    (alpha_tex, shadow_alpha_tex)
+}
+
+/// Transforms the (object space) vertex data to world space and
+/// creates one **Triangle** per three *vertex_indices*, all sharing
+/// the same **TriangleMesh**.
+pub fn create_triangles(
+    o2w: &Transform,
+    w2o: &Transform,
+    reverse_orientation: bool,
+    vertex_indices: Vec<u32>,
+    p: &[Point3f],
+    s: &[Vector3f],
+    n: &[Normal3f],
+    uv: Vec<Point2f>,
+    alpha_mask: Option<Arc<dyn Texture<Float> + Send + Sync>>,
+    shadow_alpha_mask: Option<Arc<dyn Texture<Float> + Send + Sync>>,
+) -> Vec<Arc<Shape>> {
+    // transform mesh vertices, tangents and normals to world space
+    let p_ws: Vec<Point3f> = p.iter().map(|item| o2w.transform_point(item)).collect();
+    let s_ws: Vec<Vector3f> = s.iter().map(|item| o2w.transform_vector(item)).collect();
+    let n_ws: Vec<Normal3f> = n.iter().map(|item| o2w.transform_normal(item)).collect();
+    let n_vertices: usize = p_ws.len();
+    let mesh = Arc::new(TriangleMesh::new(
+        *o2w,
+        *w2o,
+        reverse_orientation,
+        (vertex_indices.len() / 3).try_into().unwrap(), // n_triangles
+        vertex_indices,
+        n_vertices.try_into().unwrap(),
+        p_ws, // in world space
+        s_ws, // in world space
+        n_ws, // in world space
+        uv,
+        alpha_mask,
+        shadow_alpha_mask,
+    ));
+    let mut shapes: Vec<Arc<Shape>> = Vec::with_capacity(mesh.n_triangles as usize);
+    for id in 0..mesh.n_triangles {
+        let triangle = Arc::new(Shape::Trngl(Triangle::new(
+            mesh.object_to_world,
+            mesh.world_to_object,
+            mesh.reverse_orientation,
+            mesh.clone(),
+            id,
+        )));
+        shapes.push(triangle);
+    }
+    shapes
+}
+
+/// Creates the triangles of a mesh given by the *indices* and *P*
+/// parameters, with optional per vertex normals *N*, tangents *S*
+/// and texture coordinates *uv* (or *st*).
+pub fn create_triangle_mesh<S: BuildHasher>(
+    o2w: &Transform,
+    w2o: &Transform,
+    reverse_orientation: bool,
+    params: &ParamSet,
+    float_textures: Arc<HashMap<String, Arc<dyn Texture<Float> + Send + Sync>, S>>,
+) -> Vec<Arc<Shape>> {
+    let mut vi: Vec<i32> = params.find_int("indices");
+    let p: Vec<Point3f> = params.find_point3f("P");
+    // try "uv" with Point2f
+    let mut uvs: Vec<Point2f> = params.find_point2f("uv");
+    if uvs.is_empty() {
+        // try "st" with Point2f
+        uvs = params.find_point2f("st");
+    }
+    if uvs.is_empty() {
+        // try "uv" with float
+        let mut fuv: Vec<Float> = params.find_float("uv");
+        if fuv.is_empty() {
+            // try "st" with float
+            fuv = params.find_float("st");
+        }
+        for i in 0..(fuv.len() / 2) {
+            uvs.push(Point2f {
+                x: fuv[2 * i],
+                y: fuv[2 * i + 1],
+            });
+        }
+    }
+    if vi.is_empty() {
+        println!("ERROR: Vertex indices \"indices\" not provided with triangle mesh shape");
+        return Vec::new();
+    }
+    if vi.len() % 3 != 0 {
+        println!(
+            "ERROR: Number of vertex indices {} not a multiple of 3. Discarding {} excess.",
+            vi.len(),
+            vi.len() % 3
+        );
+        vi.truncate(vi.len() - vi.len() % 3);
+    }
+    if p.is_empty() {
+        println!("ERROR: Vertex positions \"P\" not provided with triangle mesh shape");
+        return Vec::new();
+    }
+    if !uvs.is_empty() {
+        if uvs.len() < p.len() {
+            println!(
+                "ERROR: Not enough \"uv\"s for triangle mesh. Expected {}, found {}. Discarding.",
+                p.len(),
+                uvs.len()
+            );
+            uvs.clear();
+        } else if uvs.len() > p.len() {
+            println!(
+                "WARNING: More \"uv\"s provided than will be used for triangle mesh. ({} expected, {} found)",
+                p.len(),
+                uvs.len()
+            );
+            uvs.truncate(p.len());
+        }
+    }
+    let mut s: Vec<Vector3f> = params.find_vector3f("S");
+    if !s.is_empty() && s.len() != p.len() {
+        println!("ERROR: Number of \"S\"s for triangle mesh must match \"P\"s. Discarding.");
+        s.clear();
+    }
+    let mut n: Vec<Normal3f> = params.find_normal3f("N");
+    if !n.is_empty() && n.len() != p.len() {
+        println!("ERROR: Number of \"N\"s for triangle mesh must match \"P\"s. Discarding.");
+        n.clear();
+    }
+    for item in &vi {
+        if *item < 0 || *item as usize >= p.len() {
+            println!(
+                "ERROR: trianglemesh has out of-bounds vertex index {} ({} \"P\" values were given)",
+                item,
+                p.len()
+            );
+            return Vec::new();
+        }
+    }
+    let (alpha_tex, shadow_alpha_tex) = find_alpha_textures(params, &float_textures);
+    create_triangles(
+        o2w,
+        w2o,
+        reverse_orientation,
+        vi.iter().map(|item| *item as u32).collect(),
+        &p,
+        &s,
+        &n,
+        uvs,
+        alpha_tex,
+        shadow_alpha_tex,
+    )
+}
@@ -0,0 +1,72 @@
+// std
+use std::ops::{Add, Mul};
+// pbrt
+use crate::core::geometry::{Point2f, Vector2f};
+use crate::core::interaction::SurfaceInteraction;
+use crate::core::paramset::TextureParams;
+use crate::core::pbrt::{Float, Spectrum};
+use crate::core::texture::{Texture, TextureMapping2D};
+
+// see bilerp.h
+
+/// Bilinear interpolation between four values at the corners of the
+/// unit square in (mapped) texture space.
+pub struct BilerpTexture<T> {
+    pub mapping: Box<TextureMapping2D>,
+    pub v00: T,
+    pub v01: T,
+    pub v10: T,
+    pub v11: T,
+}
+
+impl<T: Copy> BilerpTexture<T> {
+    pub fn new(mapping: Box<TextureMapping2D>, v00: T, v01: T, v10: T, v11: T) -> Self {
+        BilerpTexture {
+            mapping,
+            v00,
+            v01,
+            v10,
+            v11,
+        }
+    }
+}
+
+impl BilerpTexture<Float> {
+    pub fn create(tp: &mut TextureParams, mapping: Box<TextureMapping2D>) -> Self {
+        BilerpTexture::new(
+            mapping,
+            tp.find_float("v00", 0.0 as Float),
+            tp.find_float("v01", 1.0 as Float),
+            tp.find_float("v10", 0.0 as Float),
+            tp.find_float("v11", 1.0 as Float),
+        )
+    }
+}
+
+impl BilerpTexture<Spectrum> {
+    pub fn create(tp: &mut TextureParams, mapping: Box<TextureMapping2D>) -> Self {
+        BilerpTexture::new(
+            mapping,
+            tp.find_spectrum("v00", Spectrum::new(0.0 as Float)),
+            tp.find_spectrum("v01", Spectrum::new(1.0 as Float)),
+            tp.find_spectrum("v10", Spectrum::new(0.0 as Float)),
+            tp.find_spectrum("v11", Spectrum::new(1.0 as Float)),
+        )
+    }
+}
+
+impl<T: Copy> Texture<T> for BilerpTexture<T>
+where
+    T: Add<Output = T>,
+    T: Mul<Float, Output = T>,
+{
+    fn evaluate(&self, si: &SurfaceInteraction) -> T {
+        let mut dstdx: Vector2f = Vector2f::default();
+        let mut dstdy: Vector2f = Vector2f::default();
+        let st: Point2f = self.mapping.map(si, &mut dstdx, &mut dstdy);
+        self.v00 * ((1.0 as Float - st.x) * (1.0 as Float - st.y))
+            + self.v01 * ((1.0 as Float - st.x) * st.y)
+            + self.v10 * (st.x * (1.0 as Float - st.y))
+            + self.v11 * (st.x * st.y)
+    }
+}
@@ -1,40 +1,172 @@
 // std
+use std::ops::{Add, Mul};
 use std::sync::Arc;
 // pbrt
-use crate::core::geometry::{Point2f, Vector2f};
+use crate::core::geometry::{Point2f, Point3f, Vector2f, Vector3f};
 use crate::core::interaction::SurfaceInteraction;
-use crate::core::texture::{Texture, TextureMapping2D};
+use crate::core::paramset::TextureParams;
+use crate::core::pbrt::{clamp_t, Float};
+use crate::core::texture::{Texture, TextureMapping2D, TextureMapping3D};
 
 // checkerboard.h
 
+/// Beyond this magnitude texture coordinates have no fractional bits
+/// left, so the checks can't be resolved anymore.
+const MAX_RESOLVABLE: Float = 8_388_608.0; // 2^23
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AAMethod {
+    None,
+    ClosedForm,
+}
+
+/// Index of the check containing *x*, also for negative values.
+fn check_index(x: Float) -> i64 {
+    x.floor() as i64
+}
+
 pub struct Checkerboard2DTexture<T> {
     pub tex1: Arc<dyn Texture<T> + Send + Sync>,
     pub tex2: Arc<dyn Texture<T> + Send + Sync>,
     pub mapping: Box<TextureMapping2D>,
-    // TODO: const AAMethod aaMethod;
+    pub aa_method: AAMethod,
 }
 
 impl<T: Copy> Checkerboard2DTexture<T> {
     pub fn new(
         mapping: Box<TextureMapping2D>,
         tex1: Arc<dyn Texture<T> + Send + Sync>,
-        tex2: Arc<dyn Texture<T> + Send + Sync>, // , TODO: aaMethod
+        tex2: Arc<dyn Texture<T> + Send + Sync>,
+        aa_method: AAMethod,
     ) -> Self {
         Checkerboard2DTexture {
             tex1,
             tex2,
             mapping,
+            aa_method,
         }
     }
+    pub fn create(
+        tp: &mut TextureParams,
+        mapping: Box<TextureMapping2D>,
+        tex1: Arc<dyn Texture<T> + Send + Sync>,
+        tex2: Arc<dyn Texture<T> + Send + Sync>,
+    ) -> Self {
+        // compute _aaMethod_ for _CheckerboardTexture_
+        let aa: String = tp.find_string("aamode", String::from("closedform"));
+        let aa_method: AAMethod = match aa.as_str() {
+            "none" => AAMethod::None,
+            "closedform" => AAMethod::ClosedForm,
+            _ => {
+                println!(
+                    "WARNING: Antialiasing mode \"{}\" unknown. Using \"closedform\".",
+                    aa
+                );
+                AAMethod::ClosedForm
+            }
+        };
+        Checkerboard2DTexture::new(mapping, tex1, tex2, aa_method)
+    }
 }
 
-impl<T: Copy> Texture<T> for Checkerboard2DTexture<T> {
+impl<T: Copy> Texture<T> for Checkerboard2DTexture<T>
+where
+    T: Add<Output = T>,
+    T: Mul<Float, Output = T>,
+{
     fn evaluate(&self, si: &SurfaceInteraction) -> T {
         let mut dstdx: Vector2f = Vector2f::default();
         let mut dstdy: Vector2f = Vector2f::default();
         let st: Point2f = self.mapping.map(si, &mut dstdx, &mut dstdy);
-        // TODO: if (aaMethod == AAMethod::None) {
-        if (st.x.floor() as u32 + st.y.floor() as u32) % 2 == 0 {
+        if self.aa_method == AAMethod::None {
+            // point sample _Checkerboard2DTexture_
+            if (check_index(st.x) + check_index(st.y)) % 2 == 0 {
+                return self.tex1.evaluate(si);
+            }
+            return self.tex2.evaluate(si);
+        }
+        // close-form box-filtered _Checkerboard2DTexture_ evaluation
+
+        // compute _st_ filter region and return the average of both
+        // textures if the checks are too small to be resolved
+        let ds: Float = dstdx.x.abs().max(dstdy.x.abs());
+        let dt: Float = dstdx.y.abs().max(dstdy.y.abs());
+        if !(ds.is_finite() && dt.is_finite())
+            || ds > 1.0 as Float
+            || dt > 1.0 as Float
+            || st.x.abs() >= MAX_RESOLVABLE
+            || st.y.abs() >= MAX_RESOLVABLE
+        {
+            return self.tex1.evaluate(si) * 0.5 as Float + self.tex2.evaluate(si) * 0.5 as Float;
+        }
+        let s0: Float = st.x - ds;
+        let s1: Float = st.x + ds;
+        let t0: Float = st.y - dt;
+        let t1: Float = st.y + dt;
+        if s0.floor() == s1.floor() && t0.floor() == t1.floor() {
+            // point sample _Checkerboard2DTexture_
+            if (check_index(st.x) + check_index(st.y)) % 2 == 0 {
+                return self.tex1.evaluate(si);
+            }
+            return self.tex2.evaluate(si);
+        }
+        // apply box filter to checkerboard region (the clamp guards
+        // against rounding errors for tiny filter widths)
+        let bump_int = |x: Float| -> Float {
+            let half: Float = x * 0.5 as Float;
+            half.floor() + 2.0 as Float * (half - half.floor() - 0.5 as Float).max(0.0 as Float)
+        };
+        let s_int: Float = if s0.floor() == s1.floor() {
+            (check_index(st.x) % 2).abs() as Float
+        } else {
+            clamp_t(
+                (bump_int(s1) - bump_int(s0)) / (2.0 as Float * ds),
+                0.0 as Float,
+                1.0 as Float,
+            )
+        };
+        let t_int: Float = if t0.floor() == t1.floor() {
+            (check_index(st.y) % 2).abs() as Float
+        } else {
+            clamp_t(
+                (bump_int(t1) - bump_int(t0)) / (2.0 as Float * dt),
+                0.0 as Float,
+                1.0 as Float,
+            )
+        };
+        let area2: Float = s_int + t_int - 2.0 as Float * s_int * t_int;
+        self.tex1.evaluate(si) * (1.0 as Float - area2) + self.tex2.evaluate(si) * area2
+    }
+}
+
+/// Solid checkerboard, alternating between two textures in a 3D
+/// grid (without antialiasing).
+pub struct Checkerboard3DTexture<T> {
+    pub tex1: Arc<dyn Texture<T> + Send + Sync>,
+    pub tex2: Arc<dyn Texture<T> + Send + Sync>,
+    pub mapping: Box<TextureMapping3D>,
+}
+
+impl<T: Copy> Checkerboard3DTexture<T> {
+    pub fn new(
+        mapping: Box<TextureMapping3D>,
+        tex1: Arc<dyn Texture<T> + Send + Sync>,
+        tex2: Arc<dyn Texture<T> + Send + Sync>,
+    ) -> Self {
+        Checkerboard3DTexture {
+            tex1,
+            tex2,
+            mapping,
+        }
+    }
+}
+
+impl<T: Copy> Texture<T> for Checkerboard3DTexture<T> {
+    fn evaluate(&self, si: &SurfaceInteraction) -> T {
+        let mut dpdx: Vector3f = Vector3f::default();
+        let mut dpdy: Vector3f = Vector3f::default();
+        let p: Point3f = self.mapping.map(si, &mut dpdx, &mut dpdy);
+        if (check_index(p.x) + check_index(p.y) + check_index(p.z)) % 2 == 0 {
             self.tex1.evaluate(si)
         } else {
             self.tex2.evaluate(si)
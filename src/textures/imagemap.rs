@@ -1,13 +1,17 @@
 // std
+use std::io::BufReader;
 use std::ops::{Add, AddAssign, Div, Mul};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 // others
-use image::{DynamicImage, ImageResult};
+use image::DynamicImage;
+#[cfg(feature = "openexr")]
+use openexr::{FrameBufferMut, InputFile};
 // pbrt
 use crate::core::geometry::{Point2f, Point2i, Vector2f};
 use crate::core::interaction::SurfaceInteraction;
 use crate::core::mipmap::{Clampable, ImageWrap, MipMap};
+use crate::core::paramset::TextureParams;
 use crate::core::pbrt::{Float, Spectrum};
 use crate::core::texture::{Texture, TextureMapping2D};
 
@@ -41,26 +45,22 @@ where
         gamma: bool,
         convert: F,
     ) -> ImageTexture<T> {
-        let path = Path::new(&filename);
-        let img_result: ImageResult<DynamicImage> = image::open(path);
-        if img_result.is_err() {
-            panic!("Error reading \"{}\"", filename);
-        }
-        let buf = img_result.unwrap();
-        let rgb = buf.to_rgb();
-        let res = Point2i {
-            x: rgb.width() as i32,
-            y: rgb.height() as i32,
+        let image: Option<(Point2i, Vec<Spectrum>, bool)> = read_image(&filename);
+        let (res, mut texels, gamma): (Point2i, Vec<Spectrum>, bool) = match image {
+            // only 8-bit images are stored gamma encoded
+            Some((res, texels, is_8_bit)) => (res, texels, gamma && is_8_bit),
+            None => {
+                println!(
+                    "ERROR: Unable to read image texture {:?}. Using a constant texture.",
+                    filename
+                );
+                (
+                    Point2i { x: 1, y: 1 },
+                    vec![Spectrum::new(1.0 as Float)],
+                    false,
+                )
+            }
         };
-        let mut texels: Vec<Spectrum> = rgb
-            .pixels()
-            .map(|p| {
-                let r = Float::from(p[0]) / 255.0;
-                let g = Float::from(p[1]) / 255.0;
-                let b = Float::from(p[2]) / 255.0;
-                Spectrum::rgb(r, g, b)
-            })
-            .collect();
         // flip image in y; texture coordinate space has (0,0) at the
         // lower left corner.
         for y in 0..res.y / 2 {
@@ -92,6 +92,135 @@ where
         ));
         ImageTexture { mapping, mipmap }
     }
+    pub fn create<F: Fn(&Spectrum) -> T>(
+        tp: &mut TextureParams,
+        mapping: Box<TextureMapping2D>,
+        search_directory: Option<&PathBuf>,
+        convert: F,
+    ) -> ImageTexture<T> {
+        // initialize _ImageTexture_ parameters
+        let max_aniso: Float = tp.find_float("maxanisotropy", 8.0);
+        let do_trilinear: bool = tp.find_bool("trilinear", false);
+        let wrap: String = tp.find_string("wrap", String::from("repeat"));
+        let wrap_mode: ImageWrap = match wrap.as_str() {
+            "black" => ImageWrap::Black,
+            "clamp" => ImageWrap::Clamp,
+            "repeat" => ImageWrap::Repeat,
+            _ => {
+                println!("WARNING: Wrap mode \"{}\" unknown. Using \"repeat\".", wrap);
+                ImageWrap::Repeat
+            }
+        };
+        let scale: Float = tp.find_float("scale", 1.0);
+        let mut filename: String = tp.find_filename("filename", String::new());
+        if let Some(search_directory) = search_directory {
+            // filename = AbsolutePath(ResolveFilename(filename));
+            let mut path_buf: PathBuf = PathBuf::from("/");
+            path_buf.push(search_directory);
+            path_buf.push(filename);
+            filename = String::from(path_buf.to_str().unwrap());
+        }
+        // floating point images are stored linear, the others
+        // (usually) gamma encoded
+        let gamma: bool = tp.find_bool("gamma", !is_floating_point(&filename));
+        ImageTexture::new(
+            mapping,
+            filename,
+            do_trilinear,
+            max_aniso,
+            wrap_mode,
+            scale,
+            gamma,
+            convert,
+        )
+    }
+}
+
+/// Floating point image formats (OpenEXR and Radiance HDR) are
+/// recognized by their file extension.
+fn is_floating_point(filename: &str) -> bool {
+    match Path::new(filename).extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => {
+            let ext: String = ext.to_lowercase();
+            ext == "exr" || ext == "hdr"
+        }
+        None => false,
+    }
+}
+
+/// Reads the texels of an image (from top to bottom), together with
+/// its resolution and whether it was stored with 8 bits per channel.
+fn read_image(filename: &str) -> Option<(Point2i, Vec<Spectrum>, bool)> {
+    if is_floating_point(filename) {
+        return read_floating_point_image(filename);
+    }
+    let buf: DynamicImage = image::open(Path::new(filename)).ok()?;
+    let rgb = buf.to_rgb();
+    let res = Point2i {
+        x: rgb.width() as i32,
+        y: rgb.height() as i32,
+    };
+    let texels: Vec<Spectrum> = rgb
+        .pixels()
+        .map(|p| {
+            let r = Float::from(p[0]) / 255.0;
+            let g = Float::from(p[1]) / 255.0;
+            let b = Float::from(p[2]) / 255.0;
+            Spectrum::rgb(r, g, b)
+        })
+        .collect();
+    Some((res, texels, true))
+}
+
+#[cfg(feature = "openexr")]
+fn read_floating_point_image(filename: &str) -> Option<(Point2i, Vec<Spectrum>, bool)> {
+    if filename.to_lowercase().ends_with(".exr") {
+        let mut file = std::fs::File::open(filename).ok()?;
+        let mut input_file = InputFile::new(&mut file).ok()?;
+        let (width, height) = input_file.header().data_dimensions();
+        let mut pixel_data: Vec<(f32, f32, f32)> =
+            vec![(0.0_f32, 0.0_f32, 0.0_f32); (width * height) as usize];
+        {
+            let mut fb = FrameBufferMut::new(width, height);
+            fb.insert_channels(&[("R", 0.0), ("G", 0.0), ("B", 0.0)], &mut pixel_data);
+            input_file.read_pixels(&mut fb).ok()?;
+        }
+        let res = Point2i {
+            x: width as i32,
+            y: height as i32,
+        };
+        let texels: Vec<Spectrum> = pixel_data
+            .iter()
+            .map(|&(r, g, b)| Spectrum::rgb(r, g, b))
+            .collect();
+        return Some((res, texels, false));
+    }
+    read_hdr_image(filename)
+}
+
+#[cfg(not(feature = "openexr"))]
+fn read_floating_point_image(filename: &str) -> Option<(Point2i, Vec<Spectrum>, bool)> {
+    if filename.to_lowercase().ends_with(".exr") {
+        println!("WARNING: No OpenEXR support for {:?}.", filename);
+        return None;
+    }
+    read_hdr_image(filename)
+}
+
+fn read_hdr_image(filename: &str) -> Option<(Point2i, Vec<Spectrum>, bool)> {
+    let file = std::fs::File::open(filename).ok()?;
+    let hdr = image::hdr::HdrDecoder::with_strictness(BufReader::new(file), false).ok()?;
+    let meta = hdr.metadata();
+    let res = Point2i {
+        x: meta.width as i32,
+        y: meta.height as i32,
+    };
+    let pixels = hdr.read_image_hdr().ok()?;
+    let texels: Vec<Spectrum> = pixels
+        .iter()
+        .map(|p| Spectrum::rgb(p[0], p[1], p[2]))
+        .collect();
+    Some((res, texels, false))
 }
 
 pub trait ImageTextureConvert<T> {
@@ -1,12 +1,30 @@
 // pbrt
 use crate::core::geometry::{Point3f, Vector3f};
 use crate::core::interaction::SurfaceInteraction;
+use crate::core::interpolation::catmull_rom_weights;
+use crate::core::paramset::TextureParams;
 use crate::core::pbrt::{Float, Spectrum};
-use crate::core::texture::fbm;
-use crate::core::texture::{Texture, TextureMapping3D};
+use crate::core::texture::turbulence;
+use crate::core::texture::{IdentityMapping3D, Texture, TextureMapping3D};
+use crate::core::transform::Transform;
 
 // see marble.h
 
+/// Control points of the marble color spline.
+pub const MARBLE_COLORS: [[Float; 3]; 9] = [
+    [0.58, 0.58, 0.6],
+    [0.58, 0.58, 0.6],
+    [0.58, 0.58, 0.6],
+    [0.5, 0.5, 0.5],
+    [0.6, 0.59, 0.58],
+    [0.58, 0.58, 0.6],
+    [0.58, 0.58, 0.6],
+    [0.2, 0.2, 0.33],
+    [0.58, 0.58, 0.6],
+];
+
+/// Marble veins: a sine wave along x, perturbed by turbulence and
+/// mapped through a Catmull-Rom spline of marble colors.
 pub struct MarbleTexture {
     pub mapping: Box<TextureMapping3D>,
     pub octaves: i32,     // default: 8
@@ -31,6 +49,40 @@ impl MarbleTexture {
             variation,
         }
     }
+    pub fn create(tex_2_world: &Transform, tp: &mut TextureParams) -> Self {
+        // initialize 3D texture mapping _map_ from _tp_
+        let map: Box<TextureMapping3D> = Box::new(TextureMapping3D::Identity(
+            IdentityMapping3D::new(*tex_2_world),
+        ));
+        let octaves: i32 = tp.find_int("octaves", 8_i32);
+        let roughness: Float = tp.find_float("roughness", 0.5 as Float);
+        let scale: Float = tp.find_float("scale", 1.0 as Float);
+        let variation: Float = tp.find_float("variation", 0.2 as Float);
+        MarbleTexture::new(map, octaves, roughness, scale, variation)
+    }
+    /// Evaluates the marble color spline at *t* in [0, 1].
+    pub fn color(t: Float) -> Spectrum {
+        let nodes: [Float; 9] = [0.0, 0.125, 0.25, 0.375, 0.5, 0.625, 0.75, 0.875, 1.0];
+        let mut offset: i32 = 0;
+        let mut weights: [Float; 4] = [0.0 as Float; 4];
+        if !catmull_rom_weights(
+            &nodes,
+            t.max(0.0 as Float).min(1.0 as Float),
+            &mut offset,
+            &mut weights,
+        ) {
+            return Spectrum::default();
+        }
+        let mut s: Spectrum = Spectrum::default();
+        for (i, weight) in weights.iter().enumerate() {
+            if *weight != 0.0 as Float {
+                let idx: usize = (offset + i as i32) as usize;
+                s += Spectrum::from_rgb(&MARBLE_COLORS[idx]) * *weight;
+            }
+        }
+        // extra scale of 1.5 to increase variation among colors
+        s * 1.5 as Float
+    }
 }
 
 impl Texture<Spectrum> for MarbleTexture {
@@ -39,45 +91,16 @@ impl Texture<Spectrum> for MarbleTexture {
         let mut dpdy: Vector3f = Vector3f::default();
         let mut p: Point3f = self.mapping.map(si, &mut dpdx, &mut dpdy);
         p *= self.scale;
-        let marble: Float = p.y
+        let marble: Float = p.x
             + self.variation
-                * fbm(
+                * turbulence(
                     &p,
                     &(dpdx * self.scale),
                     &(dpdy * self.scale),
                     self.omega,
                     self.octaves,
                 );
-        let mut t: Float = 0.5 as Float + 0.5 as Float * marble.sin();
-        let c: [[Float; 3]; 9] = [
-            [0.58 as Float, 0.58 as Float, 0.6 as Float],
-            [0.58 as Float, 0.58 as Float, 0.6 as Float],
-            [0.58 as Float, 0.58 as Float, 0.6 as Float],
-            [0.5 as Float, 0.5 as Float, 0.5 as Float],
-            [0.6 as Float, 0.59 as Float, 0.58 as Float],
-            [0.58 as Float, 0.58 as Float, 0.6 as Float],
-            [0.58 as Float, 0.58 as Float, 0.6 as Float],
-            [0.2 as Float, 0.2 as Float, 0.33 as Float],
-            [0.58 as Float, 0.58 as Float, 0.6 as Float],
-        ];
-        let nseg: usize = 6;
-        let mut first: usize = (t * nseg as Float).floor() as usize;
-        if first > 5 {
-            // make sure we don't panic with index out of bounds
-            first = 5;
-        }
-        t = t * nseg as Float - first as Float;
-        let c0: Spectrum = Spectrum::from_rgb(&c[first]);
-        let c1: Spectrum = Spectrum::from_rgb(&c[first + 1]);
-        let c2: Spectrum = Spectrum::from_rgb(&c[first + 2]);
-        let c3: Spectrum = Spectrum::from_rgb(&c[first + 3]);
-        // Bezier spline evaluated with de Castilejau's algorithm
-        let mut s0: Spectrum = c0 * (1.0 as Float - t) + c1 * t;
-        let mut s1: Spectrum = c1 * (1.0 as Float - t) + c2 * t;
-        let s2: Spectrum = c2 * (1.0 as Float - t) + c3 * t;
-        s0 = s0 * (1.0 as Float - t) + s1 * t;
-        s1 = s1 * (1.0 as Float - t) + s2 * t;
-        // extra scale of 1.5 to increase variation among colors
-        (s0 * (1.0 as Float - t) + s1 * t) * 1.5 as Float
+        let t: Float = 0.5 as Float + 0.5 as Float * marble.sin();
+        MarbleTexture::color(t)
     }
 }
@@ -3,9 +3,12 @@ use std::ops::{Add, Mul};
 use std::sync::Arc;
 // pbrt
 use crate::core::interaction::SurfaceInteraction;
-use crate::core::pbrt::Float;
+use crate::core::paramset::TextureParams;
+use crate::core::pbrt::{Float, Spectrum};
 use crate::core::texture::Texture;
 
+/// Linear interpolation between two textures, controlled by the
+/// float texture *amount* (0 gives *tex1*, 1 gives *tex2*).
 pub struct MixTexture<T> {
     pub tex1: Arc<dyn Texture<T> + Send + Sync>,
     pub tex2: Arc<dyn Texture<T> + Send + Sync>,
@@ -22,6 +25,26 @@ impl<T: Copy> MixTexture<T> {
     }
 }
 
+impl MixTexture<Float> {
+    pub fn create(tp: &mut TextureParams) -> Self {
+        MixTexture::new(
+            tp.get_float_texture("tex1", 0.0 as Float),
+            tp.get_float_texture("tex2", 1.0 as Float),
+            tp.get_float_texture("amount", 0.5 as Float),
+        )
+    }
+}
+
+impl MixTexture<Spectrum> {
+    pub fn create(tp: &mut TextureParams) -> Self {
+        MixTexture::new(
+            tp.get_spectrum_texture("tex1", Spectrum::new(0.0 as Float)),
+            tp.get_spectrum_texture("tex2", Spectrum::new(1.0 as Float)),
+            tp.get_float_texture("amount", 0.5 as Float),
+        )
+    }
+}
+
 impl<T: Copy> Texture<T> for MixTexture<T>
 where
     T: Add<Output = T>,
@@ -6,6 +6,7 @@
 //!
 //! - BilerpTexture
 //! - Checkerboard2DTexture
+//! - Checkerboard3DTexture
 //! - ConstantTexture
 //! - DotsTexture
 //! - FBmTexture
@@ -42,6 +43,7 @@
 //!
 //! ![WrinkledTexture](/doc/img/wrinkled_pbrt_rust.png)
 
+pub mod bilerp;
 pub mod checkerboard;
 pub mod constant;
 pub mod dots;
@@ -3,8 +3,11 @@ use std::ops::Mul;
 use std::sync::Arc;
 // pbrt
 use crate::core::interaction::SurfaceInteraction;
+use crate::core::paramset::TextureParams;
+use crate::core::pbrt::{Float, Spectrum};
 use crate::core::texture::Texture;
 
+/// Product of two textures.
 pub struct ScaleTexture<T> {
     pub tex1: Arc<dyn Texture<T> + Send + Sync>,
     pub tex2: Arc<dyn Texture<T> + Send + Sync>,
@@ -19,6 +22,24 @@ impl<T: Copy> ScaleTexture<T> {
     }
 }
 
+impl ScaleTexture<Float> {
+    pub fn create(tp: &mut TextureParams) -> Self {
+        ScaleTexture::new(
+            tp.get_float_texture("tex1", 1.0 as Float),
+            tp.get_float_texture("tex2", 1.0 as Float),
+        )
+    }
+}
+
+impl ScaleTexture<Spectrum> {
+    pub fn create(tp: &mut TextureParams) -> Self {
+        ScaleTexture::new(
+            tp.get_spectrum_texture("tex1", Spectrum::new(1.0 as Float)),
+            tp.get_spectrum_texture("tex2", Spectrum::new(1.0 as Float)),
+        )
+    }
+}
+
 impl<T: Copy> Texture<T> for ScaleTexture<T>
 where
     T: Mul<Output = T>,
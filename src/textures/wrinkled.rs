@@ -1,9 +1,11 @@
 // pbrt
 use crate::core::geometry::{Point3f, Vector3f};
 use crate::core::interaction::SurfaceInteraction;
+use crate::core::paramset::TextureParams;
 use crate::core::pbrt::Float;
 use crate::core::texture::turbulence;
-use crate::core::texture::{Texture, TextureMapping3D};
+use crate::core::texture::{IdentityMapping3D, Texture, TextureMapping3D};
+use crate::core::transform::Transform;
 
 // see wrinkled.h
 
@@ -21,6 +23,15 @@ impl WrinkledTexture {
             octaves,
         }
     }
+    pub fn create(tex_2_world: &Transform, tp: &mut TextureParams) -> Self {
+        // initialize 3D texture mapping _map_ from _tp_
+        let map: Box<TextureMapping3D> = Box::new(TextureMapping3D::Identity(
+            IdentityMapping3D::new(*tex_2_world),
+        ));
+        let octaves: i32 = tp.find_int("octaves", 8_i32);
+        let roughness: Float = tp.find_float("roughness", 0.5 as Float);
+        WrinkledTexture::new(map, octaves, roughness)
+    }
 }
 
 impl<T> Texture<T> for WrinkledTexture